@@ -0,0 +1,64 @@
+//! Price cache lock contention: one shard (global lock) vs N shards
+//!
+//! Run with `cargo bench -p adapters --bench price_cache_contention`. Each
+//! iteration runs `THREADS` writers/readers over `SYMBOLS` symbols; the
+//! shard stats printed at the end report how many writes waited on a lock.
+
+use adapters::price_cache::{ExchangeQuote, PriceCacheConfig, ShardedPriceCache};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const THREADS: usize = 8;
+const SYMBOLS: usize = 256;
+const OPS_PER_THREAD: usize = 5_000;
+const EXCHANGES: [&str; 4] = ["binance", "okx", "bybit", "gateio"];
+
+fn symbols() -> Vec<String> {
+    (0..SYMBOLS).map(|i| format!("SYM{}USDT", i)).collect()
+}
+
+/// 4 updates per read, spread over symbols and exchanges
+fn run(cache: &ShardedPriceCache, symbols: &[String]) {
+    std::thread::scope(|scope| {
+        for t in 0..THREADS {
+            scope.spawn(move || {
+                for i in 0..OPS_PER_THREAD {
+                    let symbol = &symbols[(i * 31 + t * 7) % SYMBOLS];
+                    let ts = (i + 1) as u64;
+                    if i % 5 == 4 {
+                        black_box(cache.optimal(symbol, ts));
+                    } else {
+                        let price = 100.0 + (i % 10) as f64;
+                        cache.update(
+                            EXCHANGES[(i + t) % EXCHANGES.len()],
+                            symbol,
+                            ExchangeQuote { bid: price, bid_quantity: 1.0, ask: price + 0.1, ask_quantity: 1.0, timestamp_ns: ts },
+                        );
+                    }
+                }
+            });
+        }
+    });
+}
+
+fn bench_contention(c: &mut Criterion) {
+    let symbols = symbols();
+    let mut group = c.benchmark_group("price_cache_contention");
+    for shards in [1, 4, 16, 64] {
+        let cache = ShardedPriceCache::new(PriceCacheConfig { shards, ..Default::default() });
+        group.bench_with_input(BenchmarkId::from_parameter(shards), &cache, |b, cache| b.iter(|| run(cache, &symbols)));
+        let stats = cache.shard_stats();
+        let updates: u64 = stats.iter().map(|s| s.updates).sum();
+        let contended: u64 = stats.iter().map(|s| s.contended).sum();
+        println!(
+            "shards={:<3} updates={} contended={} ({:.2}%)",
+            shards,
+            updates,
+            contended,
+            contended as f64 * 100.0 / updates.max(1) as f64
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_contention);
+criterion_main!(benches);
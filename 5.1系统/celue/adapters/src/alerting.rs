@@ -0,0 +1,505 @@
+//! Alert routing and notification sinks
+//!
+//! `AlertManager` routes alerts to Slack, Telegram and email sinks by
+//! severity. Repeated alerts with the same key are deduplicated, non-critical
+//! alerts are batched over `aggregation_window_ms`, and delivery is retried
+//! with exponential backoff. Each channel renders alerts from a template
+//! with `{severity}`, `{title}`, `{message}`, `{source}` and `{time}`
+//! placeholders.
+
+use crate::{AdapterError, AdapterResult};
+use chrono::{DateTime, Utc};
+use common::pagination::{paginate, Page, PageQuery, Pageable};
+use parking_lot::Mutex;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{debug, warn};
+
+/// Alert severity, ordered from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum AlertSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl AlertSeverity {
+    fn emoji(&self) -> &'static str {
+        match self {
+            AlertSeverity::Info => "ℹ️",
+            AlertSeverity::Warning => "⚠️",
+            AlertSeverity::Critical => "🚨",
+        }
+    }
+}
+
+/// An alert raised by any component
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alert {
+    /// Deduplication key, e.g. "exchange_disconnected:binance"
+    pub key: String,
+    pub severity: AlertSeverity,
+    pub title: String,
+    pub message: String,
+    pub source: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Alert {
+    pub fn new(key: &str, severity: AlertSeverity, title: &str, message: &str, source: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            severity,
+            title: title.to_string(),
+            message: message.to_string(),
+            source: source.to_string(),
+            timestamp: Utc::now(),
+        }
+    }
+}
+
+/// Alert routing configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertConfig {
+    /// Enabled channels: "email", "slack", "telegram"
+    pub channels: Vec<String>,
+    /// Minimum severity delivered per channel; channels not listed get everything
+    pub min_severity: HashMap<String, AlertSeverity>,
+    /// Non-critical alerts are batched over this window
+    pub aggregation_window_ms: u64,
+    /// Identical alert keys within this window are suppressed
+    pub dedup_window_ms: u64,
+    pub max_retries: u32,
+    pub initial_backoff_ms: u64,
+    /// Per-channel template overrides
+    pub templates: HashMap<String, String>,
+    /// Alerts kept in history
+    pub history_size: usize,
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        let mut min_severity = HashMap::new();
+        min_severity.insert("email".to_string(), AlertSeverity::Critical);
+        min_severity.insert("telegram".to_string(), AlertSeverity::Warning);
+        Self {
+            channels: vec!["slack".to_string(), "telegram".to_string(), "email".to_string()],
+            min_severity,
+            aggregation_window_ms: 30_000,
+            dedup_window_ms: 300_000,
+            max_retries: 5,
+            initial_backoff_ms: 500,
+            templates: HashMap::new(),
+            history_size: 1000,
+        }
+    }
+}
+
+/// Fill `{severity}`, `{emoji}`, `{title}`, `{message}`, `{source}` and `{time}`
+pub fn render_template(template: &str, alert: &Alert) -> String {
+    template
+        .replace("{severity}", &format!("{:?}", alert.severity))
+        .replace("{emoji}", alert.severity.emoji())
+        .replace("{title}", &alert.title)
+        .replace("{message}", &alert.message)
+        .replace("{source}", &alert.source)
+        .replace("{time}", &alert.timestamp.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+}
+
+/// A notification channel
+#[async_trait::async_trait]
+pub trait NotificationSink: Send + Sync {
+    /// Channel name matched against `AlertConfig::channels`
+    fn channel(&self) -> &str;
+
+    /// Default per-alert template for this channel
+    fn default_template(&self) -> &str;
+
+    /// Deliver a batch of rendered alerts
+    async fn deliver(&self, alerts: &[Alert], rendered: &[String]) -> AdapterResult<()>;
+}
+
+fn http_client() -> Client {
+    Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .expect("Failed to create HTTP client")
+}
+
+async fn post_json(client: &Client, url: &str, body: &serde_json::Value, bearer: Option<&str>) -> AdapterResult<()> {
+    let mut request = client.post(url).json(body);
+    if let Some(token) = bearer {
+        request = request.bearer_auth(token);
+    }
+    let response = request.send().await.map_err(|e| AdapterError::Connection(e.to_string()))?;
+    if !response.status().is_success() {
+        return Err(AdapterError::Connection(format!("{} returned {}", url, response.status())));
+    }
+    Ok(())
+}
+
+/// Slack incoming-webhook sink
+pub struct SlackSink {
+    webhook_url: String,
+    client: Client,
+}
+
+impl SlackSink {
+    pub fn new(webhook_url: &str) -> Self {
+        Self { webhook_url: webhook_url.to_string(), client: http_client() }
+    }
+}
+
+#[async_trait::async_trait]
+impl NotificationSink for SlackSink {
+    fn channel(&self) -> &str {
+        "slack"
+    }
+
+    fn default_template(&self) -> &str {
+        "{emoji} *[{severity}] {title}*\n{message}\n_{source} · {time}_"
+    }
+
+    async fn deliver(&self, _alerts: &[Alert], rendered: &[String]) -> AdapterResult<()> {
+        let body = serde_json::json!({ "text": rendered.join("\n\n") });
+        post_json(&self.client, &self.webhook_url, &body, None).await
+    }
+}
+
+/// Telegram bot sink
+pub struct TelegramSink {
+    bot_token: String,
+    chat_id: String,
+    client: Client,
+}
+
+impl TelegramSink {
+    pub fn new(bot_token: &str, chat_id: &str) -> Self {
+        Self { bot_token: bot_token.to_string(), chat_id: chat_id.to_string(), client: http_client() }
+    }
+}
+
+#[async_trait::async_trait]
+impl NotificationSink for TelegramSink {
+    fn channel(&self) -> &str {
+        "telegram"
+    }
+
+    fn default_template(&self) -> &str {
+        "{emoji} <b>[{severity}] {title}</b>\n{message}\n<i>{source} · {time}</i>"
+    }
+
+    async fn deliver(&self, _alerts: &[Alert], rendered: &[String]) -> AdapterResult<()> {
+        let url = format!("https://api.telegram.org/bot{}/sendMessage", self.bot_token);
+        let body = serde_json::json!({
+            "chat_id": self.chat_id,
+            "text": rendered.join("\n\n"),
+            "parse_mode": "HTML",
+        });
+        post_json(&self.client, &url, &body, None).await
+    }
+}
+
+/// Email sink through an HTTP mail relay (SendGrid v3 compatible)
+pub struct EmailSink {
+    relay_url: String,
+    api_key: String,
+    from: String,
+    to: Vec<String>,
+    client: Client,
+}
+
+impl EmailSink {
+    pub fn new(relay_url: &str, api_key: &str, from: &str, to: Vec<String>) -> Self {
+        Self {
+            relay_url: relay_url.to_string(),
+            api_key: api_key.to_string(),
+            from: from.to_string(),
+            to,
+            client: http_client(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl NotificationSink for EmailSink {
+    fn channel(&self) -> &str {
+        "email"
+    }
+
+    fn default_template(&self) -> &str {
+        "[{severity}] {title}\n\n{message}\n\nSource: {source}\nTime: {time}"
+    }
+
+    async fn deliver(&self, alerts: &[Alert], rendered: &[String]) -> AdapterResult<()> {
+        let worst = alerts.iter().map(|a| a.severity).max().unwrap_or(AlertSeverity::Info);
+        let subject = match alerts {
+            [single] => format!("[{:?}] {}", single.severity, single.title),
+            _ => format!("[{:?}] {} alerts", worst, alerts.len()),
+        };
+        let body = serde_json::json!({
+            "personalizations": [{ "to": self.to.iter().map(|t| serde_json::json!({ "email": t })).collect::<Vec<_>>() }],
+            "from": { "email": self.from },
+            "subject": subject,
+            "content": [{ "type": "text/plain", "value": rendered.join("\n\n----\n\n") }],
+        });
+        post_json(&self.client, &self.relay_url, &body, Some(&self.api_key)).await
+    }
+}
+
+/// Alert history entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRecord {
+    pub alert: Alert,
+    /// Channels the alert was delivered to
+    pub delivered: Vec<String>,
+    /// Repeats suppressed by deduplication before this alert was sent
+    pub suppressed_repeats: u64,
+    /// Paging incident opened for this alert, if escalated
+    #[serde(default)]
+    pub incident_id: Option<String>,
+}
+
+impl Pageable for AlertRecord {
+    fn timestamp_ns(&self) -> u64 {
+        self.alert.timestamp.timestamp_nanos_opt().unwrap_or(0) as u64
+    }
+
+    fn record_id(&self) -> String {
+        self.alert.key.clone()
+    }
+
+    fn field(&self, name: &str) -> Option<String> {
+        match name {
+            "key" => Some(self.alert.key.clone()),
+            "severity" => Some(format!("{:?}", self.alert.severity)),
+            "source" => Some(self.alert.source.clone()),
+            _ => None,
+        }
+    }
+}
+
+struct DedupEntry {
+    last_sent: DateTime<Utc>,
+    suppressed: u64,
+}
+
+/// Alert manager
+pub struct AlertManager {
+    config: AlertConfig,
+    sinks: Vec<Arc<dyn NotificationSink>>,
+    dedup: Mutex<HashMap<String, DedupEntry>>,
+    pending: Mutex<Vec<(Alert, u64)>>,
+    history: Mutex<VecDeque<AlertRecord>>,
+    raised: broadcast::Sender<Alert>,
+}
+
+impl AlertManager {
+    pub fn new(config: AlertConfig) -> Self {
+        let (raised, _) = broadcast::channel(1024);
+        Self {
+            config,
+            sinks: Vec::new(),
+            dedup: Mutex::new(HashMap::new()),
+            pending: Mutex::new(Vec::new()),
+            history: Mutex::new(VecDeque::new()),
+            raised,
+        }
+    }
+
+    /// Every raised alert, including repeats suppressed by deduplication
+    pub fn subscribe(&self) -> broadcast::Receiver<Alert> {
+        self.raised.subscribe()
+    }
+
+    /// Register a sink; ignored unless its channel is enabled in the config
+    pub fn with_sink(mut self, sink: Arc<dyn NotificationSink>) -> Self {
+        if self.config.channels.iter().any(|c| c == sink.channel()) {
+            self.sinks.push(sink);
+        } else {
+            warn!("Alert channel {} is not enabled, sink ignored", sink.channel());
+        }
+        self
+    }
+
+    /// Raise an alert; returns false if it was suppressed as a duplicate.
+    /// Critical alerts are delivered immediately, others on the next flush.
+    pub async fn raise(&self, alert: Alert) -> bool {
+        let _ = self.raised.send(alert.clone());
+        let suppressed = {
+            let mut dedup = self.dedup.lock();
+            let window = chrono::Duration::milliseconds(self.config.dedup_window_ms as i64);
+            if let Some(entry) = dedup.get_mut(&alert.key) {
+                if alert.timestamp - entry.last_sent < window {
+                    entry.suppressed += 1;
+                    debug!("Alert {} suppressed ({} repeats)", alert.key, entry.suppressed);
+                    return false;
+                }
+            }
+            dedup
+                .insert(alert.key.clone(), DedupEntry { last_sent: alert.timestamp, suppressed: 0 })
+                .map(|e| e.suppressed)
+                .unwrap_or(0)
+        };
+
+        if alert.severity == AlertSeverity::Critical {
+            self.dispatch(vec![(alert, suppressed)]).await;
+        } else {
+            self.pending.lock().push((alert, suppressed));
+        }
+        true
+    }
+
+    /// Deliver all batched alerts
+    pub async fn flush(&self) {
+        let batch = std::mem::take(&mut *self.pending.lock());
+        if !batch.is_empty() {
+            self.dispatch(batch).await;
+        }
+    }
+
+    async fn dispatch(&self, batch: Vec<(Alert, u64)>) {
+        let mut delivered: Vec<Vec<String>> = vec![Vec::new(); batch.len()];
+        for sink in &self.sinks {
+            let min = self.config.min_severity.get(sink.channel()).copied().unwrap_or(AlertSeverity::Info);
+            let selected: Vec<usize> = (0..batch.len()).filter(|&i| batch[i].0.severity >= min).collect();
+            if selected.is_empty() {
+                continue;
+            }
+            let template = self
+                .config
+                .templates
+                .get(sink.channel())
+                .map(|t| t.as_str())
+                .unwrap_or_else(|| sink.default_template());
+            let alerts: Vec<Alert> = selected.iter().map(|&i| batch[i].0.clone()).collect();
+            let rendered: Vec<String> = selected
+                .iter()
+                .map(|&i| {
+                    let (alert, suppressed) = &batch[i];
+                    let text = render_template(template, alert);
+                    if *suppressed > 0 { format!("{} (repeated {}x)", text, suppressed + 1) } else { text }
+                })
+                .collect();
+
+            if self.deliver_with_retry(sink.as_ref(), &alerts, &rendered).await {
+                for &i in &selected {
+                    delivered[i].push(sink.channel().to_string());
+                }
+            }
+        }
+
+        let mut history = self.history.lock();
+        for ((alert, suppressed_repeats), delivered) in batch.into_iter().zip(delivered) {
+            history.push_back(AlertRecord { alert, delivered, suppressed_repeats, incident_id: None });
+            while history.len() > self.config.history_size {
+                history.pop_front();
+            }
+        }
+    }
+
+    async fn deliver_with_retry(&self, sink: &dyn NotificationSink, alerts: &[Alert], rendered: &[String]) -> bool {
+        let mut backoff = Duration::from_millis(self.config.initial_backoff_ms);
+        for attempt in 0..=self.config.max_retries {
+            match sink.deliver(alerts, rendered).await {
+                Ok(()) => return true,
+                Err(e) if attempt < self.config.max_retries => {
+                    warn!("Alert delivery to {} failed (attempt {}): {}", sink.channel(), attempt + 1, e);
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                Err(e) => warn!("Alert delivery to {} gave up: {}", sink.channel(), e),
+            }
+        }
+        false
+    }
+
+    /// Attach an incident ID to the most recent alert with `key`
+    pub fn record_incident(&self, key: &str, incident_id: &str) -> bool {
+        let mut history = self.history.lock();
+        match history.iter_mut().rev().find(|r| r.alert.key == key) {
+            Some(record) => {
+                record.incident_id = Some(incident_id.to_string());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Most recent alerts, newest first
+    pub fn history(&self, limit: usize) -> Vec<AlertRecord> {
+        self.history.lock().iter().rev().take(limit).cloned().collect()
+    }
+
+    /// Paged alert history, filterable by key, severity and source
+    pub fn history_page(&self, query: &PageQuery) -> AdapterResult<Page<AlertRecord>> {
+        let records: Vec<AlertRecord> = self.history.lock().iter().cloned().collect();
+        paginate(records, query).map_err(|e| AdapterError::Validation { message: e.to_string() })
+    }
+
+    /// Spawn the aggregation flush loop
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_millis(self.config.aggregation_window_ms.max(1)));
+            loop {
+                ticker.tick().await;
+                self.flush().await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct CountingSink {
+        batches: AtomicUsize,
+        failures_left: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl NotificationSink for CountingSink {
+        fn channel(&self) -> &str {
+            "slack"
+        }
+
+        fn default_template(&self) -> &str {
+            "{title}"
+        }
+
+        async fn deliver(&self, _alerts: &[Alert], _rendered: &[String]) -> AdapterResult<()> {
+            if self.failures_left.load(Ordering::SeqCst) > 0 {
+                self.failures_left.fetch_sub(1, Ordering::SeqCst);
+                return Err(AdapterError::Connection("unavailable".into()));
+            }
+            self.batches.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dedup_aggregation_and_retry() {
+        let sink = Arc::new(CountingSink { batches: AtomicUsize::new(0), failures_left: AtomicUsize::new(1) });
+        let manager = AlertManager::new(AlertConfig { initial_backoff_ms: 1, ..AlertConfig::default() })
+            .with_sink(sink.clone());
+
+        assert!(manager.raise(Alert::new("lag:okx", AlertSeverity::Warning, "lag", "okx lag", "test")).await);
+        assert!(!manager.raise(Alert::new("lag:okx", AlertSeverity::Warning, "lag", "okx lag", "test")).await);
+        assert!(manager.raise(Alert::new("lag:binance", AlertSeverity::Warning, "lag", "binance lag", "test")).await);
+        assert_eq!(sink.batches.load(Ordering::SeqCst), 0);
+
+        // One failed attempt, then both warnings go out in a single batch
+        manager.flush().await;
+        assert_eq!(sink.batches.load(Ordering::SeqCst), 1);
+        assert_eq!(manager.history(10).len(), 2);
+
+        manager.raise(Alert::new("kill_switch", AlertSeverity::Critical, "kill", "engaged", "test")).await;
+        assert_eq!(sink.batches.load(Ordering::SeqCst), 2);
+    }
+}
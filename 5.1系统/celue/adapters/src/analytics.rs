@@ -0,0 +1,313 @@
+//! Rolling volatility and spread analytics
+//!
+//! Tracks, per exchange and symbol, realized volatility of the mid price,
+//! the average bid/ask spread and the spread half-life. Each is an
+//! exponentially weighted estimate with a configurable half-life in
+//! seconds, decayed by the actual time between ticks. Estimates are
+//! persisted to a JSON file periodically and reloaded on start, so
+//! thresholds do not start cold after a restart.
+
+use crate::{AdapterError, AdapterResult};
+use common::{NormalizedSnapshot, OrderBook};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalyticsConfig {
+    /// Half-life of the volatility estimate (seconds)
+    pub volatility_halflife_secs: f64,
+    /// Half-life of the spread estimates (seconds)
+    pub spread_halflife_secs: f64,
+    /// Estimates are persisted here when set
+    pub persist_path: Option<PathBuf>,
+    pub persist_interval_secs: u64,
+}
+
+impl Default for AnalyticsConfig {
+    fn default() -> Self {
+        Self {
+            volatility_halflife_secs: 3_600.0,
+            spread_halflife_secs: 600.0,
+            persist_path: None,
+            persist_interval_secs: 60,
+        }
+    }
+}
+
+/// Decay weight of a new observation after `dt` seconds
+fn alpha(dt_secs: f64, halflife_secs: f64) -> f64 {
+    1.0 - (-std::f64::consts::LN_2 * dt_secs / halflife_secs.max(f64::EPSILON)).exp()
+}
+
+/// Exponentially weighted state for one exchange/symbol
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SeriesState {
+    last_mid: f64,
+    last_timestamp_ns: u64,
+    /// Variance of log returns per second
+    variance_per_sec: f64,
+    spread_bps: f64,
+    /// Previous deviation of the spread from its mean
+    last_spread_dev: f64,
+    /// E[dev_t * dev_{t-1}] and E[dev_{t-1}^2], for the AR(1) coefficient
+    spread_cov: f64,
+    spread_var: f64,
+    /// Mean seconds between ticks
+    mean_dt_secs: f64,
+    samples: u64,
+}
+
+/// Current estimates for one exchange/symbol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketStats {
+    pub exchange: String,
+    pub symbol: String,
+    /// Realized daily volatility of the mid price (fraction)
+    pub daily_volatility: f64,
+    pub avg_spread_bps: f64,
+    /// Seconds for a spread deviation to decay by half; None if the spread
+    /// does not mean-revert
+    pub spread_half_life_secs: Option<f64>,
+    pub samples: u64,
+}
+
+impl SeriesState {
+    fn update(&mut self, mid: f64, spread_bps: f64, timestamp_ns: u64, config: &AnalyticsConfig) {
+        if self.samples == 0 {
+            self.last_mid = mid;
+            self.last_timestamp_ns = timestamp_ns;
+            self.spread_bps = spread_bps;
+            self.samples = 1;
+            return;
+        }
+        if timestamp_ns <= self.last_timestamp_ns || mid <= 0.0 || self.last_mid <= 0.0 {
+            return;
+        }
+        let dt = (timestamp_ns - self.last_timestamp_ns) as f64 / 1e9;
+        let ret = (mid / self.last_mid).ln();
+
+        // Seed with the first return so the estimate does not start at zero
+        if self.samples == 1 {
+            self.variance_per_sec = ret * ret / dt;
+        } else {
+            let a_vol = alpha(dt, config.volatility_halflife_secs);
+            self.variance_per_sec += a_vol * (ret * ret / dt - self.variance_per_sec);
+        }
+
+        let a_spread = alpha(dt, config.spread_halflife_secs);
+        self.spread_bps += a_spread * (spread_bps - self.spread_bps);
+        let dev = spread_bps - self.spread_bps;
+        self.spread_cov += a_spread * (dev * self.last_spread_dev - self.spread_cov);
+        self.spread_var += a_spread * (self.last_spread_dev * self.last_spread_dev - self.spread_var);
+        self.mean_dt_secs += a_spread * (dt - self.mean_dt_secs);
+        self.last_spread_dev = dev;
+
+        self.last_mid = mid;
+        self.last_timestamp_ns = timestamp_ns;
+        self.samples += 1;
+    }
+
+    fn daily_volatility(&self) -> f64 {
+        (self.variance_per_sec * SECONDS_PER_DAY).sqrt()
+    }
+
+    fn spread_half_life_secs(&self) -> Option<f64> {
+        if self.spread_var <= 0.0 {
+            return None;
+        }
+        let phi = self.spread_cov / self.spread_var;
+        (phi > 0.0 && phi < 1.0).then(|| -std::f64::consts::LN_2 / phi.ln() * self.mean_dt_secs)
+    }
+}
+
+/// Per exchange/symbol volatility and spread estimator
+pub struct MarketAnalytics {
+    config: AnalyticsConfig,
+    series: RwLock<HashMap<(String, String), SeriesState>>,
+}
+
+impl MarketAnalytics {
+    pub fn new(config: AnalyticsConfig) -> Self {
+        Self {
+            config,
+            series: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Update from one exchange's top of book
+    pub fn on_book(&self, book: &OrderBook) {
+        let (Some(bid), Some(ask)) = (book.best_bid(), book.best_ask()) else { return };
+        let (bid, ask) = (bid.price.to_f64(), ask.price.to_f64());
+        let mid = (bid + ask) / 2.0;
+        if mid <= 0.0 {
+            return;
+        }
+        let spread_bps = (ask - bid) / mid * 10_000.0;
+        self.series
+            .write()
+            .entry((book.exchange.as_str().to_lowercase(), book.symbol.as_str().to_string()))
+            .or_default()
+            .update(mid, spread_bps, book.timestamp_ns, &self.config);
+    }
+
+    pub fn on_snapshot(&self, snapshot: &NormalizedSnapshot) {
+        for book in &snapshot.exchanges {
+            self.on_book(book);
+        }
+    }
+
+    fn to_stats(key: &(String, String), state: &SeriesState) -> MarketStats {
+        MarketStats {
+            exchange: key.0.clone(),
+            symbol: key.1.clone(),
+            daily_volatility: state.daily_volatility(),
+            avg_spread_bps: state.spread_bps,
+            spread_half_life_secs: state.spread_half_life_secs(),
+            samples: state.samples,
+        }
+    }
+
+    pub fn stats(&self, exchange: &str, symbol: &str) -> Option<MarketStats> {
+        let key = (exchange.to_lowercase(), symbol.to_string());
+        self.series.read().get(&key).map(|s| Self::to_stats(&key, s))
+    }
+
+    pub fn all_stats(&self) -> Vec<MarketStats> {
+        self.series.read().iter().map(|(k, s)| Self::to_stats(k, s)).collect()
+    }
+
+    /// Daily volatility of `symbol`, averaged over the exchanges with at
+    /// least two ticks
+    pub fn daily_volatility(&self, symbol: &str) -> Option<f64> {
+        self.mean_over_exchanges(symbol, |s| (s.samples > 1).then(|| s.daily_volatility()))
+    }
+
+    /// Average spread of `symbol` across exchanges (bps)
+    pub fn avg_spread_bps(&self, symbol: &str) -> Option<f64> {
+        self.mean_over_exchanges(symbol, |s| Some(s.spread_bps))
+    }
+
+    /// Spread half-life of `symbol` across exchanges (seconds)
+    pub fn spread_half_life_secs(&self, symbol: &str) -> Option<f64> {
+        self.mean_over_exchanges(symbol, |s| s.spread_half_life_secs())
+    }
+
+    fn mean_over_exchanges(&self, symbol: &str, value: impl Fn(&SeriesState) -> Option<f64>) -> Option<f64> {
+        let series = self.series.read();
+        let values: Vec<f64> = series
+            .iter()
+            .filter(|((_, s), _)| s == symbol)
+            .filter_map(|(_, state)| value(state))
+            .collect();
+        (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+    }
+
+    /// Write estimates to the configured file
+    pub fn persist(&self) -> AdapterResult<()> {
+        let Some(path) = &self.config.persist_path else { return Ok(()) };
+        let entries: Vec<(String, String, SeriesState)> = self
+            .series
+            .read()
+            .iter()
+            .map(|((e, s), state)| (e.clone(), s.clone(), state.clone()))
+            .collect();
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec(&entries)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Load estimates from the configured file, if it exists
+    pub fn restore(&self) -> AdapterResult<usize> {
+        let Some(path) = &self.config.persist_path else { return Ok(0) };
+        if !path.exists() {
+            return Ok(0);
+        }
+        let content = std::fs::read(path)?;
+        let entries: Vec<(String, String, SeriesState)> = serde_json::from_slice(&content)
+            .map_err(|e| AdapterError::Configuration(format!("invalid analytics file {}: {}", path.display(), e)))?;
+        let count = entries.len();
+        let mut series = self.series.write();
+        for (exchange, symbol, state) in entries {
+            series.insert((exchange, symbol), state);
+        }
+        info!("Restored analytics for {} exchange/symbol pairs", count);
+        Ok(count)
+    }
+
+    /// Update from the snapshot stream and persist periodically
+    pub fn spawn(self: Arc<Self>, mut snapshots: broadcast::Receiver<NormalizedSnapshot>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut persist = tokio::time::interval(Duration::from_secs(self.config.persist_interval_secs.max(1)));
+            loop {
+                tokio::select! {
+                    received = snapshots.recv() => match received {
+                        Ok(snapshot) => self.on_snapshot(&snapshot),
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!("Market analytics lagged, skipped {} snapshots", skipped);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                    _ = persist.tick() => {
+                        if let Err(e) = self.persist() {
+                            warn!("Failed to persist market analytics: {}", e);
+                        }
+                    }
+                }
+            }
+            if let Err(e) = self.persist() {
+                warn!("Failed to persist market analytics: {}", e);
+            }
+        })
+    }
+}
+
+impl Default for MarketAnalytics {
+    fn default() -> Self {
+        Self::new(AnalyticsConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::testing;
+
+    fn book(mid: f64, spread: f64, ts_secs: u64) -> OrderBook {
+        testing::book("binance", "BTCUSDT", mid - spread / 2.0, mid + spread / 2.0, ts_secs * 1_000_000_000)
+    }
+
+    #[test]
+    fn test_volatility_spread_and_persistence() {
+        let path = std::env::temp_dir().join(format!("celue_analytics_{}.json", std::process::id()));
+        let config = AnalyticsConfig { persist_path: Some(path.clone()), ..Default::default() };
+        let analytics = MarketAnalytics::new(config.clone());
+        assert!(analytics.daily_volatility("BTCUSDT").is_none());
+
+        // Alternating 0.1% moves every second; the spread reverts towards 2
+        for t in 0..600u64 {
+            let mid = if t % 2 == 0 { 100.0 } else { 100.1 };
+            let spread = if t % 10 == 0 { 0.06 } else { 0.02 };
+            analytics.on_book(&book(mid, spread, t));
+        }
+        let vol = analytics.daily_volatility("BTCUSDT").unwrap();
+        let expected = 0.000999_f64 * SECONDS_PER_DAY.sqrt();
+        assert!((vol - expected).abs() / expected < 0.05, "vol {} vs {}", vol, expected);
+        let spread = analytics.avg_spread_bps("BTCUSDT").unwrap();
+        assert!(spread > 2.0 && spread < 6.0);
+
+        analytics.persist().unwrap();
+        let restored = MarketAnalytics::new(config);
+        assert_eq!(restored.restore().unwrap(), 1);
+        assert_eq!(restored.stats("binance", "BTCUSDT").unwrap().samples, 600);
+        let _ = std::fs::remove_file(path);
+    }
+}
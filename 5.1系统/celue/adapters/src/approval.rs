@@ -0,0 +1,366 @@
+//! Manual approval gate for large opportunities
+//!
+//! Opportunities whose required funds (capital committed by the buy legs)
+//! exceed a configured notional are not executed automatically. They are
+//! queued as pending approvals and published on the `approvals` frontend
+//! topic. An operator approves or rejects each one through the gateway. The
+//! deadline is the opportunity's own TTL: its prices are only trusted that
+//! long, so a pending or approved request whose deadline passes before it is
+//! executed expires and is never executed. Decided requests are kept in a
+//! bounded history.
+
+use crate::scheduler::required_capital;
+use crate::ws_gateway::WsGateway;
+use crate::{AdapterError, AdapterResult};
+use common::{ArbitrageOpportunity, SharedClock};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Frontend stream topic for approval events
+pub const APPROVAL_TOPIC: &str = "approvals";
+const APPROVAL_SCHEMA: &str = "approval_event.v1";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalConfig {
+    /// Required funds above which execution needs approval
+    pub notional_threshold: f64,
+    /// Pending requests beyond this are rejected on arrival
+    pub max_pending: usize,
+    /// Decided requests kept for the API
+    pub history_len: usize,
+}
+
+impl Default for ApprovalConfig {
+    fn default() -> Self {
+        Self { notional_threshold: 50_000.0, max_pending: 100, history_len: 500 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalStatus {
+    Pending,
+    /// Approved, waiting for the engine to execute it
+    Approved,
+    Rejected,
+    Expired,
+    Executed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRequest {
+    pub opportunity: ArbitrageOpportunity,
+    pub required_funds: f64,
+    pub requested_at_ns: u64,
+    pub expires_at_ns: u64,
+    pub status: ApprovalStatus,
+    pub decided_by: Option<String>,
+    pub decided_at_ns: Option<u64>,
+    pub reason: Option<String>,
+}
+
+/// Approval lifecycle events, mirrored to the frontend stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ApprovalEvent {
+    Requested { request: Box<ApprovalRequest> },
+    Decided { id: Uuid, status: ApprovalStatus, by: Option<String>, reason: Option<String> },
+}
+
+#[derive(Default)]
+struct GateState {
+    open: HashMap<Uuid, ApprovalRequest>,
+    history: VecDeque<ApprovalRequest>,
+}
+
+/// Queue of opportunities waiting for an operator decision
+pub struct ApprovalGate {
+    config: ApprovalConfig,
+    state: Mutex<GateState>,
+    events: broadcast::Sender<ApprovalEvent>,
+    gateway: Option<Arc<WsGateway>>,
+    clock: SharedClock,
+}
+
+impl ApprovalGate {
+    pub fn new(config: ApprovalConfig) -> Self {
+        let (events, _) = broadcast::channel(256);
+        Self { config, state: Mutex::new(GateState::default()), events, gateway: None, clock: common::clock::system_clock() }
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Mirror approval events to the frontend stream
+    pub fn with_gateway(mut self, gateway: Arc<WsGateway>) -> Self {
+        self.gateway = Some(gateway);
+        self
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ApprovalEvent> {
+        self.events.subscribe()
+    }
+
+    fn emit(&self, event: ApprovalEvent) {
+        if let Some(gateway) = &self.gateway {
+            if let Err(e) = gateway.publish(APPROVAL_TOPIC, APPROVAL_SCHEMA, &event) {
+                warn!("Failed to publish approval event: {}", e);
+            }
+        }
+        let _ = self.events.send(event);
+    }
+
+    pub fn requires_approval(&self, opportunity: &ArbitrageOpportunity) -> bool {
+        required_capital(opportunity) > self.config.notional_threshold
+    }
+
+    /// Queue an opportunity; the returned request is rejected when the
+    /// queue is full and expired when its TTL has already passed
+    pub fn submit(&self, opportunity: ArbitrageOpportunity) -> ApprovalRequest {
+        let now_ns = self.clock.now_ns();
+        let mut request = ApprovalRequest {
+            required_funds: required_capital(&opportunity),
+            requested_at_ns: now_ns,
+            expires_at_ns: opportunity.created_at_ns.saturating_add(opportunity.ttl_ns),
+            opportunity,
+            status: ApprovalStatus::Pending,
+            decided_by: None,
+            decided_at_ns: None,
+            reason: None,
+        };
+        let id = request.opportunity.id;
+        let mut state = self.state.lock();
+        if state.open.contains_key(&id) {
+            return state.open[&id].clone();
+        }
+        let refusal = if request.expires_at_ns <= now_ns {
+            Some((ApprovalStatus::Expired, "deadline passed"))
+        } else if state.open.len() >= self.config.max_pending {
+            Some((ApprovalStatus::Rejected, "approval queue full"))
+        } else {
+            None
+        };
+        if let Some((status, reason)) = refusal {
+            request.status = status;
+            request.decided_at_ns = Some(now_ns);
+            request.reason = Some(reason.to_string());
+            Self::archive(&mut state, request.clone(), self.config.history_len);
+            drop(state);
+            warn!("Opportunity {} not queued for approval: {}", id, reason);
+            self.emit(ApprovalEvent::Decided { id, status, by: None, reason: request.reason.clone() });
+            return request;
+        }
+        state.open.insert(id, request.clone());
+        metrics::gauge!("approvals_pending").set(state.open.len() as f64);
+        drop(state);
+        info!("Opportunity {} needs approval: required funds {:.2}", id, request.required_funds);
+        self.emit(ApprovalEvent::Requested { request: Box::new(request.clone()) });
+        request
+    }
+
+    pub fn approve(&self, id: &Uuid, operator: &str) -> AdapterResult<ApprovalRequest> {
+        self.decide(id, operator, ApprovalStatus::Approved, None)
+    }
+
+    pub fn reject(&self, id: &Uuid, operator: &str, reason: Option<String>) -> AdapterResult<ApprovalRequest> {
+        self.decide(id, operator, ApprovalStatus::Rejected, reason)
+    }
+
+    fn decide(
+        &self,
+        id: &Uuid,
+        operator: &str,
+        status: ApprovalStatus,
+        reason: Option<String>,
+    ) -> AdapterResult<ApprovalRequest> {
+        let now_ns = self.clock.now_ns();
+        self.sweep(now_ns);
+        let request = {
+            let mut state = self.state.lock();
+            let request = state.open.get_mut(id).ok_or_else(|| AdapterError::Validation {
+                message: format!("no pending approval for opportunity {}", id),
+            })?;
+            if request.status != ApprovalStatus::Pending {
+                return Err(AdapterError::Validation {
+                    message: format!("opportunity {} is already {:?}", id, request.status),
+                });
+            }
+            request.status = status;
+            request.decided_by = Some(operator.to_string());
+            request.decided_at_ns = Some(now_ns);
+            request.reason = reason.clone();
+            let request = request.clone();
+            if status == ApprovalStatus::Rejected {
+                state.open.remove(id);
+                Self::archive(&mut state, request.clone(), self.config.history_len);
+            }
+            metrics::gauge!("approvals_pending").set(state.open.len() as f64);
+            request
+        };
+        metrics::counter!("approval_decisions_total", "status" => format!("{:?}", status).to_lowercase()).increment(1);
+        info!("Opportunity {} {:?} by {}", id, status, operator);
+        self.emit(ApprovalEvent::Decided { id: *id, status, by: Some(operator.to_string()), reason });
+        Ok(request)
+    }
+
+    /// Approved opportunities still within their deadline, handed to the
+    /// engine exactly once
+    pub fn take_approved(&self) -> Vec<ArbitrageOpportunity> {
+        let now_ns = self.clock.now_ns();
+        self.sweep(now_ns);
+        let mut state = self.state.lock();
+        let ids: Vec<Uuid> =
+            state.open.values().filter(|r| r.status == ApprovalStatus::Approved).map(|r| r.opportunity.id).collect();
+        let mut approved = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(mut request) = state.open.remove(&id) {
+                request.status = ApprovalStatus::Executed;
+                approved.push(request.opportunity.clone());
+                Self::archive(&mut state, request, self.config.history_len);
+            }
+        }
+        metrics::gauge!("approvals_pending").set(state.open.len() as f64);
+        approved
+    }
+
+    /// Expire pending and approved requests past their deadline
+    pub fn sweep(&self, now_ns: u64) -> Vec<Uuid> {
+        let expired: Vec<ApprovalRequest> = {
+            let mut state = self.state.lock();
+            let ids: Vec<Uuid> =
+                state.open.values().filter(|r| r.expires_at_ns <= now_ns).map(|r| r.opportunity.id).collect();
+            let mut expired = Vec::with_capacity(ids.len());
+            for id in ids {
+                if let Some(mut request) = state.open.remove(&id) {
+                    request.status = ApprovalStatus::Expired;
+                    request.decided_at_ns = Some(now_ns);
+                    expired.push(request.clone());
+                    Self::archive(&mut state, request, self.config.history_len);
+                }
+            }
+            metrics::gauge!("approvals_pending").set(state.open.len() as f64);
+            expired
+        };
+        for request in &expired {
+            warn!("Approval for opportunity {} expired unexecuted", request.opportunity.id);
+            metrics::counter!("approval_decisions_total", "status" => "expired").increment(1);
+            self.emit(ApprovalEvent::Decided {
+                id: request.opportunity.id,
+                status: ApprovalStatus::Expired,
+                by: None,
+                reason: Some("deadline passed".to_string()),
+            });
+        }
+        expired.iter().map(|r| r.opportunity.id).collect()
+    }
+
+    fn archive(state: &mut GateState, request: ApprovalRequest, history_len: usize) {
+        state.history.push_back(request);
+        while state.history.len() > history_len {
+            state.history.pop_front();
+        }
+    }
+
+    /// Requests waiting for a decision or for execution, oldest first
+    pub fn pending(&self) -> Vec<ApprovalRequest> {
+        let mut open: Vec<_> = self.state.lock().open.values().cloned().collect();
+        open.sort_by_key(|r| r.requested_at_ns);
+        open
+    }
+
+    pub fn get(&self, id: &Uuid) -> Option<ApprovalRequest> {
+        let state = self.state.lock();
+        state.open.get(id).cloned().or_else(|| state.history.iter().rev().find(|r| &r.opportunity.id == id).cloned())
+    }
+
+    /// Most recent decided requests, newest first
+    pub fn history(&self, limit: usize) -> Vec<ApprovalRequest> {
+        self.state.lock().history.iter().rev().take(limit).cloned().collect()
+    }
+
+    /// Expiry sweep loop
+    pub fn spawn(self: Arc<Self>, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.sweep(self.clock.now_ns());
+            }
+        })
+    }
+}
+
+impl Default for ApprovalGate {
+    fn default() -> Self {
+        Self::new(ApprovalConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::testing::{leg, opportunity_with_legs};
+    use common::{Side, SimulatedClock};
+
+    fn opportunity(quantity: f64, created_at_ns: u64) -> ArbitrageOpportunity {
+        let legs = vec![
+            leg("binance", "BTCUSDT", Side::Buy, 100.0, quantity),
+            leg("okx", "BTCUSDT", Side::Sell, 101.0, quantity),
+        ];
+        let mut opportunity = opportunity_with_legs("inter_exchange", legs, 0.01, created_at_ns);
+        opportunity.ttl_ns = 30_000_000_000;
+        opportunity
+    }
+
+    #[test]
+    fn test_approval_lifecycle_and_expiry() {
+        let second = 1_000_000_000u64;
+        let clock = Arc::new(SimulatedClock::from_nanos(1_000 * second));
+        let gate = ApprovalGate::new(ApprovalConfig { notional_threshold: 10_000.0, ..Default::default() })
+            .with_clock(clock.clone());
+        let mut events = gate.subscribe();
+
+        assert!(!gate.requires_approval(&opportunity(50.0, 1_000 * second)));
+        let large = opportunity(200.0, 1_000 * second);
+        assert!(gate.requires_approval(&large));
+        let request = gate.submit(large.clone());
+        assert_eq!((request.status, request.required_funds), (ApprovalStatus::Pending, 20_000.0));
+        assert_eq!(request.expires_at_ns, 1_030 * second);
+        assert!(matches!(events.try_recv().unwrap(), ApprovalEvent::Requested { .. }));
+
+        // Not handed out before approval; approval is one-shot
+        assert!(gate.take_approved().is_empty());
+        gate.approve(&large.id, "alice").unwrap();
+        assert!(gate.approve(&large.id, "bob").is_err());
+        assert_eq!(gate.take_approved().len(), 1);
+        assert!(gate.take_approved().is_empty());
+        assert_eq!(gate.get(&large.id).unwrap().status, ApprovalStatus::Executed);
+
+        // Rejected and expired requests never execute
+        let rejected = opportunity(300.0, 1_000 * second);
+        gate.submit(rejected.clone());
+        gate.reject(&rejected.id, "alice", Some("too large".into())).unwrap();
+        let stale = opportunity(400.0, 1_000 * second);
+        gate.submit(stale.clone());
+        gate.approve(&stale.id, "alice").unwrap();
+        clock.advance(chrono::Duration::seconds(31));
+        assert!(gate.take_approved().is_empty());
+        assert_eq!(gate.get(&stale.id).unwrap().status, ApprovalStatus::Expired);
+        assert!(gate.approve(&stale.id, "alice").is_err());
+        assert!(gate.pending().is_empty());
+        let statuses: Vec<_> = gate.history(10).iter().map(|r| r.status).collect();
+        assert_eq!(statuses, vec![ApprovalStatus::Expired, ApprovalStatus::Rejected, ApprovalStatus::Executed]);
+
+        // The deadline never outlives the opportunity's own TTL
+        let late = opportunity(500.0, 1_000 * second);
+        assert_eq!(gate.submit(late.clone()).status, ApprovalStatus::Expired);
+        assert!(gate.pending().is_empty());
+    }
+}
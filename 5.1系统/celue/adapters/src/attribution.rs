@@ -0,0 +1,309 @@
+//! Per-strategy performance attribution
+//!
+//! Breaks realized results down by strategy type and exchange pair:
+//! profit, hit rate, average slippage, fee drag and capital usage. Reports
+//! can be rendered as JSON, CSV or HTML, grouped by strategy with one row
+//! per exchange pair.
+
+use crate::{AdapterError, AdapterResult};
+use common::pagination::{paginate, Page, PageQuery, Pageable};
+use common::types::StrategyKind;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+/// Trades kept for the fee/trade history endpoints
+const TRADE_HISTORY_SIZE: usize = 10_000;
+
+/// Report output format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReportFormat {
+    Json,
+    Csv,
+    Html,
+}
+
+/// One completed arbitrage execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeRecord {
+    pub strategy: StrategyKind,
+    pub buy_exchange: String,
+    pub sell_exchange: String,
+    /// Profit before fees (USD)
+    pub gross_profit: f64,
+    /// Fees paid (USD)
+    pub fees: f64,
+    /// Realized vs expected price, positive is worse (bps)
+    pub slippage_bps: f64,
+    /// Capital committed to the trade (USD)
+    pub capital_used: f64,
+    pub timestamp_ns: u64,
+}
+
+impl TradeRecord {
+    pub fn net_profit(&self) -> f64 {
+        self.gross_profit - self.fees
+    }
+
+    /// "buy->sell" for cross-exchange trades, the venue name otherwise
+    pub fn exchange_pair(&self) -> String {
+        if self.buy_exchange == self.sell_exchange {
+            self.buy_exchange.clone()
+        } else {
+            format!("{}->{}", self.buy_exchange, self.sell_exchange)
+        }
+    }
+}
+
+impl Pageable for TradeRecord {
+    fn timestamp_ns(&self) -> u64 {
+        self.timestamp_ns
+    }
+
+    fn record_id(&self) -> String {
+        format!("{:?}:{}", self.strategy, self.exchange_pair())
+    }
+
+    fn field(&self, name: &str) -> Option<String> {
+        match name {
+            "strategy" => Some(format!("{:?}", self.strategy)),
+            "buy_exchange" => Some(self.buy_exchange.clone()),
+            "sell_exchange" => Some(self.sell_exchange.clone()),
+            "exchange_pair" => Some(self.exchange_pair()),
+            _ => None,
+        }
+    }
+}
+
+/// Aggregated results for one group
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AttributionStats {
+    pub trades: u64,
+    pub wins: u64,
+    pub gross_profit: f64,
+    pub net_profit: f64,
+    pub fees: f64,
+    pub total_slippage_bps: f64,
+    pub total_capital_used: f64,
+    pub max_capital_used: f64,
+}
+
+impl AttributionStats {
+    fn record(&mut self, trade: &TradeRecord) {
+        self.trades += 1;
+        if trade.net_profit() > 0.0 {
+            self.wins += 1;
+        }
+        self.gross_profit += trade.gross_profit;
+        self.net_profit += trade.net_profit();
+        self.fees += trade.fees;
+        self.total_slippage_bps += trade.slippage_bps;
+        self.total_capital_used += trade.capital_used;
+        self.max_capital_used = self.max_capital_used.max(trade.capital_used);
+    }
+
+    pub fn hit_rate(&self) -> f64 {
+        if self.trades == 0 { 0.0 } else { self.wins as f64 / self.trades as f64 }
+    }
+
+    pub fn avg_slippage_bps(&self) -> f64 {
+        if self.trades == 0 { 0.0 } else { self.total_slippage_bps / self.trades as f64 }
+    }
+
+    /// Share of gross profit consumed by fees
+    pub fn fee_drag(&self) -> f64 {
+        if self.gross_profit <= 0.0 { 0.0 } else { self.fees / self.gross_profit }
+    }
+
+    /// Net profit per unit of capital committed
+    pub fn return_on_capital(&self) -> f64 {
+        if self.total_capital_used <= 0.0 { 0.0 } else { self.net_profit / self.total_capital_used }
+    }
+}
+
+/// Attribution for one strategy type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyAttribution {
+    pub strategy: StrategyKind,
+    pub total: AttributionStats,
+    /// Keyed by exchange pair
+    pub by_exchange_pair: BTreeMap<String, AttributionStats>,
+}
+
+/// Performance analyzer with per-strategy attribution
+#[derive(Default)]
+pub struct PerformanceAnalyzer {
+    groups: RwLock<BTreeMap<StrategyKind, BTreeMap<String, AttributionStats>>>,
+    trades: RwLock<VecDeque<TradeRecord>>,
+}
+
+impl PerformanceAnalyzer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, trade: &TradeRecord) {
+        self.groups
+            .write()
+            .entry(trade.strategy)
+            .or_default()
+            .entry(trade.exchange_pair())
+            .or_default()
+            .record(trade);
+
+        let mut trades = self.trades.write();
+        trades.push_back(trade.clone());
+        if trades.len() > TRADE_HISTORY_SIZE {
+            trades.pop_front();
+        }
+    }
+
+    /// Paged trade history with per-trade fees, filterable by strategy and exchange
+    pub fn trade_history(&self, query: &PageQuery) -> AdapterResult<Page<TradeRecord>> {
+        let trades: Vec<TradeRecord> = self.trades.read().iter().cloned().collect();
+        paginate(trades, query).map_err(|e| AdapterError::Validation { message: e.to_string() })
+    }
+
+    /// Retained trades with `from_ns <= timestamp < to_ns`, oldest first
+    pub fn trades_between(&self, from_ns: u64, to_ns: u64) -> Vec<TradeRecord> {
+        self.trades
+            .read()
+            .iter()
+            .filter(|t| t.timestamp_ns >= from_ns && t.timestamp_ns < to_ns)
+            .cloned()
+            .collect()
+    }
+
+    /// Account-level totals across every strategy and exchange pair
+    pub fn get_performance_stats(&self) -> AttributionStats {
+        self.attribution()
+            .into_iter()
+            .fold(AttributionStats::default(), |acc, a| merge(acc, &a.total))
+    }
+
+    /// Results grouped by strategy, then exchange pair
+    pub fn attribution(&self) -> Vec<StrategyAttribution> {
+        self.groups
+            .read()
+            .iter()
+            .map(|(strategy, pairs)| StrategyAttribution {
+                strategy: *strategy,
+                total: pairs.values().fold(AttributionStats::default(), merge),
+                by_exchange_pair: pairs.clone(),
+            })
+            .collect()
+    }
+
+    /// Render the grouped attribution report
+    pub fn render_report(&self, format: ReportFormat) -> AdapterResult<String> {
+        let attribution = self.attribution();
+        Ok(match format {
+            ReportFormat::Json => serde_json::to_string_pretty(&attribution)?,
+            ReportFormat::Csv => render_csv(&attribution),
+            ReportFormat::Html => render_html(&attribution),
+        })
+    }
+}
+
+fn merge(mut acc: AttributionStats, other: &AttributionStats) -> AttributionStats {
+    acc.trades += other.trades;
+    acc.wins += other.wins;
+    acc.gross_profit += other.gross_profit;
+    acc.net_profit += other.net_profit;
+    acc.fees += other.fees;
+    acc.total_slippage_bps += other.total_slippage_bps;
+    acc.total_capital_used += other.total_capital_used;
+    acc.max_capital_used = acc.max_capital_used.max(other.max_capital_used);
+    acc
+}
+
+const COLUMNS: [&str; 9] = [
+    "strategy", "exchange_pair", "trades", "net_profit", "hit_rate",
+    "avg_slippage_bps", "fees", "fee_drag", "capital_used",
+];
+
+fn row(strategy: StrategyKind, pair: &str, s: &AttributionStats) -> [String; 9] {
+    [
+        format!("{:?}", strategy),
+        pair.to_string(),
+        s.trades.to_string(),
+        format!("{:.4}", s.net_profit),
+        format!("{:.4}", s.hit_rate()),
+        format!("{:.2}", s.avg_slippage_bps()),
+        format!("{:.4}", s.fees),
+        format!("{:.4}", s.fee_drag()),
+        format!("{:.2}", s.total_capital_used),
+    ]
+}
+
+/// One row per exchange pair followed by a strategy subtotal row ("*")
+fn render_csv(attribution: &[StrategyAttribution]) -> String {
+    let mut out = COLUMNS.join(",");
+    out.push('\n');
+    for a in attribution {
+        for (pair, stats) in &a.by_exchange_pair {
+            out.push_str(&row(a.strategy, pair, stats).join(","));
+            out.push('\n');
+        }
+        out.push_str(&row(a.strategy, "*", &a.total).join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// One table per strategy with the subtotal in the footer
+fn render_html(attribution: &[StrategyAttribution]) -> String {
+    let header: String = COLUMNS[1..].iter().map(|c| format!("<th>{}</th>", c)).collect();
+    let mut out = String::from("<html><head><title>Strategy Attribution</title></head><body>\n");
+    for a in attribution {
+        out.push_str(&format!("<h2>{:?}</h2>\n<table>\n<thead><tr>{}</tr></thead>\n<tbody>\n", a.strategy, header));
+        for (pair, stats) in &a.by_exchange_pair {
+            let cells: String = row(a.strategy, pair, stats)[1..].iter().map(|c| format!("<td>{}</td>", c)).collect();
+            out.push_str(&format!("<tr>{}</tr>\n", cells));
+        }
+        let total: String = row(a.strategy, "total", &a.total)[1..].iter().map(|c| format!("<th>{}</th>", c)).collect();
+        out.push_str(&format!("</tbody>\n<tfoot><tr>{}</tr></tfoot>\n</table>\n", total));
+    }
+    out.push_str("</body></html>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(strategy: StrategyKind, buy: &str, sell: &str, gross: f64, fees: f64) -> TradeRecord {
+        TradeRecord {
+            strategy,
+            buy_exchange: buy.into(),
+            sell_exchange: sell.into(),
+            gross_profit: gross,
+            fees,
+            slippage_bps: 2.0,
+            capital_used: 1_000.0,
+            timestamp_ns: 0,
+        }
+    }
+
+    #[test]
+    fn test_attribution_by_strategy_and_pair() {
+        let analyzer = PerformanceAnalyzer::new();
+        analyzer.record(&trade(StrategyKind::InterExchange, "binance", "okx", 10.0, 2.0));
+        analyzer.record(&trade(StrategyKind::InterExchange, "binance", "okx", 1.0, 2.0));
+        analyzer.record(&trade(StrategyKind::Triangular, "binance", "binance", 5.0, 1.0));
+
+        let attribution = analyzer.attribution();
+        assert_eq!(attribution.len(), 2);
+        let inter = &attribution[0].by_exchange_pair["binance->okx"];
+        assert_eq!(inter.trades, 2);
+        assert!((inter.hit_rate() - 0.5).abs() < 1e-9);
+        assert!((inter.fee_drag() - 4.0 / 11.0).abs() < 1e-9);
+
+        let totals = analyzer.get_performance_stats();
+        assert_eq!(totals.trades, 3);
+        assert!((totals.net_profit - 11.0).abs() < 1e-9);
+
+        let csv = analyzer.render_report(ReportFormat::Csv).unwrap();
+        assert_eq!(csv.lines().count(), 1 + 2 + 2);
+    }
+}
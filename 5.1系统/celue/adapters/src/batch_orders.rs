@@ -0,0 +1,243 @@
+//! Batch order submission for exchanges with batch endpoints
+//!
+//! Binance futures (`POST /fapi/v1/batchOrders`, up to 5 orders) and OKX
+//! (`POST /api/v5/trade/batch-orders`, up to 20 orders) accept several
+//! orders in one request and report success or failure per item. This
+//! module holds the per-exchange limits, request payloads and response
+//! parsing; `SlicingEngine` groups child orders into batches and falls
+//! back to one-at-a-time submission for other venues or when a batch is
+//! rejected with a validation error before it is sent. Once a request has
+//! gone out, errors are reported as exchange errors so that orders which
+//! may already be live are never submitted twice.
+
+use crate::order_matching::OrderFlags;
+use crate::{AdapterError, AdapterResult};
+use common::{ArbitrageLeg, MarketType, Side};
+use serde_json::{json, Value};
+
+/// Largest batch any supported exchange accepts
+pub const MAX_ORDER_BATCH_SIZE: usize = 20;
+
+/// Batch size limit for `exchange`'s `market`, None when it has no batch endpoint
+pub fn max_batch_size(exchange: &str, market: MarketType) -> Option<usize> {
+    match exchange.to_lowercase().as_str() {
+        // Binance only batches on the futures API
+        "binance" if market == MarketType::Perpetual => Some(5),
+        "okx" => Some(MAX_ORDER_BATCH_SIZE),
+        _ => None,
+    }
+}
+
+/// Margin mode the derivatives account trades in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MarginMode {
+    #[default]
+    Cross,
+    Isolated,
+}
+
+/// One order in a batch
+#[derive(Debug, Clone)]
+pub struct BatchOrderItem {
+    pub leg: ArbitrageLeg,
+    pub quantity: f64,
+    /// Client order id echoed back by the exchange
+    pub client_order_id: String,
+    /// Time-in-force / post-only / reduce-only
+    pub flags: OrderFlags,
+    /// Margin mode for derivative legs; ignored for spot
+    pub margin_mode: MarginMode,
+}
+
+fn side_str(side: Side) -> &'static str {
+    match side {
+        Side::Buy => "BUY",
+        Side::Sell => "SELL",
+    }
+}
+
+/// OKX `tdMode`: spot trades in cash, derivatives in the configured margin mode
+fn okx_td_mode(market: MarketType, margin_mode: MarginMode) -> &'static str {
+    match (market, margin_mode) {
+        (MarketType::Spot, _) => "cash",
+        (_, MarginMode::Cross) => "cross",
+        (_, MarginMode::Isolated) => "isolated",
+    }
+}
+
+/// Request body for `exchange`'s batch endpoint
+pub fn batch_payload(exchange: &str, items: &[BatchOrderItem]) -> AdapterResult<Value> {
+    let market = items.first().map(|i| i.leg.market).ok_or_else(|| AdapterError::Validation {
+        message: format!("{} batch must hold at least one order", exchange),
+    })?;
+    let limit = max_batch_size(exchange, market).ok_or_else(|| AdapterError::Validation {
+        message: format!("{} has no batch order endpoint for {:?}", exchange, market),
+    })?;
+    if items.iter().any(|i| i.leg.market != market) || items.len() > limit {
+        return Err(AdapterError::Validation {
+            message: format!("{} {:?} batch must hold 1..={} orders of one market, got {}", exchange, market, limit, items.len()),
+        });
+    }
+    let payload = match exchange.to_lowercase().as_str() {
+        "binance" => {
+            let orders: Vec<Value> = items
+                .iter()
+                .map(|i| {
+                    json!({
+                        "symbol": i.leg.symbol.as_str(),
+                        "side": side_str(i.leg.side),
+                        "type": "LIMIT",
+                        "timeInForce": i.flags.binance_time_in_force(),
+                        "reduceOnly": i.flags.reduce_only.to_string(),
+                        "price": i.leg.price.to_f64().to_string(),
+                        "quantity": i.quantity.to_string(),
+                        "newClientOrderId": i.client_order_id,
+                    })
+                })
+                .collect();
+            // Binance takes the list as a JSON-encoded string parameter
+            json!({ "batchOrders": serde_json::to_string(&orders)? })
+        }
+        _ => Value::Array(
+            items
+                .iter()
+                .map(|i| {
+                    json!({
+                        "instId": i.leg.symbol.as_str(),
+                        "tdMode": okx_td_mode(i.leg.market, i.margin_mode),
+                        "side": side_str(i.leg.side).to_lowercase(),
+                        "ordType": i.flags.okx_ord_type(),
+                        "reduceOnly": i.flags.reduce_only,
+                        "px": i.leg.price.to_f64().to_string(),
+                        "sz": i.quantity.to_string(),
+                        "clOrdId": i.client_order_id,
+                    })
+                })
+                .collect(),
+        ),
+    };
+    Ok(payload)
+}
+
+/// Per-item results of a batch response, in request order
+pub fn parse_batch_response(exchange: &str, response: &Value, expected: usize) -> AdapterResult<Vec<AdapterResult<String>>> {
+    let item_error = |code: String, message: String| AdapterError::Exchange {
+        exchange: exchange.to_string(),
+        code,
+        message,
+    };
+    let malformed = |message: String| AdapterError::Exchange {
+        exchange: exchange.to_string(),
+        code: "malformed_batch_response".to_string(),
+        message,
+    };
+    let results: Vec<AdapterResult<String>> = match exchange.to_lowercase().as_str() {
+        // Array of orders or {code, msg} error objects
+        "binance" => response
+            .as_array()
+            .ok_or_else(|| malformed(format!("unexpected binance batch response: {}", response)))?
+            .iter()
+            .map(|item| match item.get("orderId") {
+                Some(id) => Ok(id.to_string().trim_matches('"').to_string()),
+                None => Err(item_error(
+                    item.get("code").map(|c| c.to_string()).unwrap_or_default(),
+                    item.get("msg").and_then(Value::as_str).unwrap_or("unknown error").to_string(),
+                )),
+            })
+            .collect(),
+        // {code, msg, data: [{ordId, sCode, sMsg}]}; code "1" = partial failure
+        _ => response
+            .get("data")
+            .and_then(Value::as_array)
+            .ok_or_else(|| malformed(format!("unexpected okx batch response: {}", response)))?
+            .iter()
+            .map(|item| {
+                let code = item.get("sCode").and_then(Value::as_str).unwrap_or("");
+                if code == "0" {
+                    Ok(item.get("ordId").and_then(Value::as_str).unwrap_or_default().to_string())
+                } else {
+                    Err(item_error(
+                        code.to_string(),
+                        item.get("sMsg").and_then(Value::as_str).unwrap_or("unknown error").to_string(),
+                    ))
+                }
+            })
+            .collect(),
+    };
+    if results.len() != expected {
+        return Err(malformed(format!("{} batch response has {} items, expected {}", exchange, results.len(), expected)));
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::precision::{FixedPrice, FixedQuantity};
+    use common::types::{Exchange, Symbol};
+
+    fn item(id: &str) -> BatchOrderItem {
+        BatchOrderItem {
+            leg: ArbitrageLeg {
+                exchange: Exchange::new("okx"),
+                symbol: Symbol::new("BTC-USDT"),
+                market: MarketType::Spot,
+                side: Side::Buy,
+                price: FixedPrice::from_f64(100.0, 2),
+                quantity: FixedQuantity::from_f64(1.0, 4),
+                cost: FixedPrice::from_f64(100.0, 2),
+            },
+            quantity: 0.5,
+            client_order_id: id.into(),
+            flags: OrderFlags::post_only(),
+            margin_mode: MarginMode::Cross,
+        }
+    }
+
+    #[test]
+    fn test_payloads_and_partial_failures() {
+        assert!(batch_payload("bybit", &[item("a")]).is_err());
+        assert_eq!(max_batch_size("binance", MarketType::Spot), None);
+        assert_eq!(max_batch_size("binance", MarketType::Perpetual), Some(5));
+        assert!(batch_payload("okx", &[]).is_err());
+        // Binance spot has no batch endpoint
+        assert!(batch_payload("binance", &[item("a")]).is_err());
+        let perpetual = |id: &str| {
+            let mut item = item(id);
+            item.leg.market = MarketType::Perpetual;
+            item
+        };
+        assert!(batch_payload("binance", &[perpetual("a")]).is_ok());
+        assert!(batch_payload("binance", &(0..6).map(|i| perpetual(&i.to_string())).collect::<Vec<_>>()).is_err());
+        assert!(batch_payload("okx", &[item("a"), perpetual("b")]).is_err());
+        let okx = batch_payload("okx", &[item("a"), item("b")]).unwrap();
+        assert_eq!(okx[1]["clOrdId"], "b");
+        assert_eq!(okx[0]["side"], "buy");
+        assert_eq!(okx[0]["ordType"], "post_only");
+        assert_eq!(okx[0]["tdMode"], "cash");
+
+        // Perpetual legs trade in the account's margin mode
+        let okx = batch_payload("okx", &[perpetual("a")]).unwrap();
+        assert_eq!(okx[0]["tdMode"], "cross");
+        let mut isolated = perpetual("b");
+        isolated.margin_mode = MarginMode::Isolated;
+        assert_eq!(batch_payload("okx", &[isolated]).unwrap()[0]["tdMode"], "isolated");
+
+        let response = json!({
+            "code": "1",
+            "data": [
+                { "ordId": "111", "sCode": "0", "sMsg": "" },
+                { "ordId": "", "sCode": "51008", "sMsg": "Insufficient balance" },
+            ],
+        });
+        let results = parse_batch_response("okx", &response, 2).unwrap();
+        assert_eq!(results[0].as_ref().unwrap(), "111");
+        assert!(matches!(&results[1], Err(AdapterError::Exchange { code, .. }) if code == "51008"));
+
+        let binance = json!([{ "orderId": 42 }, { "code": -2019, "msg": "Margin is insufficient." }]);
+        let results = parse_batch_response("binance", &binance, 2).unwrap();
+        assert_eq!(results[0].as_ref().unwrap(), "42");
+        assert!(results[1].is_err());
+        assert!(matches!(parse_batch_response("binance", &binance, 3), Err(AdapterError::Exchange { .. })));
+    }
+}
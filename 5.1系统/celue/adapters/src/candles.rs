@@ -0,0 +1,429 @@
+//! Real-time candlestick (OHLCV) aggregation
+//!
+//! Builds 1s/1m/5m/1h bars per exchange and symbol from the tick stream.
+//! Every update of the forming bar and every closed bar is published to
+//! the WebSocket gateway on `candles.<exchange>.<symbol>.<interval>` and
+//! to in-process subscribers. Closed bars are kept for historical
+//! queries; when the stream skips whole intervals the gap is recorded and
+//! filled from the exchange's REST klines.
+
+use crate::price_history::{Ohlcv, Tick};
+use crate::ws_gateway::WsGateway;
+use crate::{AdapterError, AdapterResult};
+use common::NormalizedSnapshot;
+use parking_lot::RwLock;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+const CANDLE_SCHEMA: &str = "candle.v1";
+const NS_PER_MS: u64 = 1_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CandleInterval {
+    #[serde(rename = "1s")]
+    S1,
+    #[serde(rename = "1m")]
+    M1,
+    #[serde(rename = "5m")]
+    M5,
+    #[serde(rename = "1h")]
+    H1,
+}
+
+impl CandleInterval {
+    pub const ALL: [CandleInterval; 4] = [Self::S1, Self::M1, Self::M5, Self::H1];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::S1 => "1s",
+            Self::M1 => "1m",
+            Self::M5 => "5m",
+            Self::H1 => "1h",
+        }
+    }
+
+    pub fn as_ns(self) -> u64 {
+        1_000 * NS_PER_MS
+            * match self {
+                Self::S1 => 1,
+                Self::M1 => 60,
+                Self::M5 => 300,
+                Self::H1 => 3_600,
+            }
+    }
+
+    /// OKX spells the hourly bar `1H`
+    fn okx_bar(self) -> &'static str {
+        match self {
+            Self::H1 => "1H",
+            other => other.as_str(),
+        }
+    }
+}
+
+/// A bar as published to subscribers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Candle {
+    pub exchange: String,
+    pub symbol: String,
+    pub interval: CandleInterval,
+    #[serde(flatten)]
+    pub bar: Ohlcv,
+    /// False while the bar is still forming
+    pub closed: bool,
+}
+
+/// WebSocket topic for one series
+pub fn candle_topic(exchange: &str, symbol: &str, interval: CandleInterval) -> String {
+    format!("candles.{}.{}.{}", exchange.to_lowercase(), symbol, interval.as_str())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandleConfig {
+    pub intervals: Vec<CandleInterval>,
+    /// Closed bars kept per series and interval
+    pub history_len: usize,
+    /// How often recorded gaps are backfilled
+    pub backfill_interval_secs: u64,
+}
+
+impl Default for CandleConfig {
+    fn default() -> Self {
+        Self {
+            intervals: CandleInterval::ALL.to_vec(),
+            history_len: 1_000,
+            backfill_interval_secs: 30,
+        }
+    }
+}
+
+type SeriesKey = (String, String, CandleInterval);
+
+#[derive(Default)]
+struct SeriesCandles {
+    forming: Option<Ohlcv>,
+    /// Sorted by start time
+    closed: VecDeque<Ohlcv>,
+}
+
+/// Missing bars to fetch from REST
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CandleGap {
+    pub exchange: String,
+    pub symbol: String,
+    pub interval: CandleInterval,
+    pub from_ns: u64,
+    pub to_ns: u64,
+}
+
+/// Candle aggregation service
+pub struct CandleService {
+    config: CandleConfig,
+    series: RwLock<HashMap<SeriesKey, SeriesCandles>>,
+    gaps: RwLock<Vec<CandleGap>>,
+    events: broadcast::Sender<Candle>,
+    gateway: Option<Arc<WsGateway>>,
+    http_client: Client,
+}
+
+impl CandleService {
+    pub fn new(config: CandleConfig) -> Self {
+        let (events, _) = broadcast::channel(4096);
+        Self {
+            config,
+            series: RwLock::new(HashMap::new()),
+            gaps: RwLock::new(Vec::new()),
+            events,
+            gateway: None,
+            http_client: Client::builder().timeout(Duration::from_secs(10)).build().unwrap_or_default(),
+        }
+    }
+
+    /// Publish candles to the frontend stream
+    pub fn with_gateway(mut self, gateway: Arc<WsGateway>) -> Self {
+        self.gateway = Some(gateway);
+        self
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Candle> {
+        self.events.subscribe()
+    }
+
+    fn emit(&self, candle: Candle) {
+        if let Some(gateway) = &self.gateway {
+            let topic = candle_topic(&candle.exchange, &candle.symbol, candle.interval);
+            if let Err(e) = gateway.publish(&topic, CANDLE_SCHEMA, &candle) {
+                debug!("Failed to publish candle: {}", e);
+            }
+        }
+        let _ = self.events.send(candle);
+    }
+
+    /// Fold a tick into every interval's forming bar
+    pub fn on_tick(&self, exchange: &str, symbol: &str, tick: Tick) {
+        let exchange = exchange.to_lowercase();
+        let mut emitted = Vec::new();
+        {
+            let mut series = self.series.write();
+            for interval in &self.config.intervals {
+                let key = (exchange.clone(), symbol.to_string(), *interval);
+                let candles = series.entry(key).or_default();
+                let interval_ns = interval.as_ns();
+                let start_ns = tick.timestamp_ns - tick.timestamp_ns % interval_ns;
+                match candles.forming.map(|b| b.start_ns) {
+                    Some(current) if current == start_ns => {
+                        let bar = candles.forming.as_mut().expect("forming bar checked above");
+                        bar.high = bar.high.max(tick.price);
+                        bar.low = bar.low.min(tick.price);
+                        bar.close = tick.price;
+                        bar.volume += tick.quantity;
+                        bar.ticks += 1;
+                    }
+                    // Ticks for an already closed bar are ignored
+                    Some(current) if start_ns < current => continue,
+                    _ => {
+                        if let Some(previous) = candles.forming.take() {
+                            if start_ns > previous.start_ns + interval_ns {
+                                self.gaps.write().push(CandleGap {
+                                    exchange: exchange.clone(),
+                                    symbol: symbol.to_string(),
+                                    interval: *interval,
+                                    from_ns: previous.start_ns + interval_ns,
+                                    to_ns: start_ns,
+                                });
+                            }
+                            candles.closed.push_back(previous);
+                            while candles.closed.len() > self.config.history_len.max(1) {
+                                candles.closed.pop_front();
+                            }
+                            emitted.push((*interval, previous, true));
+                        }
+                        candles.forming = Some(Ohlcv {
+                            start_ns,
+                            open: tick.price,
+                            high: tick.price,
+                            low: tick.price,
+                            close: tick.price,
+                            volume: tick.quantity,
+                            ticks: 1,
+                        });
+                    }
+                }
+                if let Some(bar) = candles.forming {
+                    emitted.push((*interval, bar, false));
+                }
+            }
+        }
+        for (interval, bar, closed) in emitted {
+            self.emit(Candle { exchange: exchange.clone(), symbol: symbol.to_string(), interval, bar, closed });
+        }
+    }
+
+    /// Record each book's mid price
+    pub fn on_snapshot(&self, snapshot: &NormalizedSnapshot) {
+        for book in &snapshot.exchanges {
+            if let (Some(bid), Some(ask)) = (book.best_bid(), book.best_ask()) {
+                let tick = Tick {
+                    timestamp_ns: book.timestamp_ns,
+                    price: (bid.price.to_f64() + ask.price.to_f64()) / 2.0,
+                    quantity: 0.0,
+                };
+                self.on_tick(book.exchange.as_str(), book.symbol.as_str(), tick);
+            }
+        }
+    }
+
+    /// Closed bars (and the forming one) starting in [from_ns, to_ns)
+    pub fn history(&self, exchange: &str, symbol: &str, interval: CandleInterval, from_ns: u64, to_ns: u64) -> Vec<Ohlcv> {
+        let series = self.series.read();
+        let Some(candles) = series.get(&(exchange.to_lowercase(), symbol.to_string(), interval)) else {
+            return Vec::new();
+        };
+        candles
+            .closed
+            .iter()
+            .chain(candles.forming.iter())
+            .filter(|b| b.start_ns >= from_ns && b.start_ns < to_ns)
+            .copied()
+            .collect()
+    }
+
+    pub fn pending_gaps(&self) -> Vec<CandleGap> {
+        self.gaps.read().clone()
+    }
+
+    /// Insert REST bars into the closed history, keeping it sorted
+    pub fn merge(&self, exchange: &str, symbol: &str, interval: CandleInterval, bars: &[Ohlcv]) -> usize {
+        let mut series = self.series.write();
+        let candles = series.entry((exchange.to_lowercase(), symbol.to_string(), interval)).or_default();
+        let forming_start = candles.forming.map(|b| b.start_ns).unwrap_or(u64::MAX);
+        let mut inserted = 0;
+        for bar in bars.iter().filter(|b| b.start_ns < forming_start) {
+            match candles.closed.binary_search_by_key(&bar.start_ns, |b| b.start_ns) {
+                Ok(_) => {}
+                Err(at) => {
+                    candles.closed.insert(at, *bar);
+                    inserted += 1;
+                }
+            }
+        }
+        while candles.closed.len() > self.config.history_len.max(1) {
+            candles.closed.pop_front();
+        }
+        inserted
+    }
+
+    /// Fetch bars in [from_ns, to_ns) from the exchange's klines endpoint
+    pub async fn backfill(&self, exchange: &str, symbol: &str, interval: CandleInterval, from_ns: u64, to_ns: u64) -> AdapterResult<usize> {
+        let (from_ms, to_ms) = (from_ns / NS_PER_MS, to_ns / NS_PER_MS);
+        let url = match exchange.to_lowercase().as_str() {
+            "binance" => format!(
+                "https://api.binance.com/api/v3/klines?symbol={}&interval={}&startTime={}&endTime={}&limit=1000",
+                symbol, interval.as_str(), from_ms, to_ms.saturating_sub(1)
+            ),
+            // `after`/`before` are exclusive upper/lower bounds
+            "okx" => format!(
+                "https://www.okx.com/api/v5/market/history-candles?instId={}&bar={}&after={}&before={}&limit=100",
+                symbol, interval.okx_bar(), to_ms, from_ms.saturating_sub(1)
+            ),
+            other => return Err(AdapterError::Configuration(format!("klines not supported for exchange {}", other))),
+        };
+        let body: Value = self
+            .http_client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| AdapterError::Connection(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| AdapterError::Connection(e.to_string()))?;
+        let bars: Vec<Ohlcv> = parse_klines(exchange, &body)?
+            .into_iter()
+            .filter(|b| b.start_ns >= from_ns && b.start_ns < to_ns)
+            .collect();
+        Ok(self.merge(exchange, symbol, interval, &bars))
+    }
+
+    /// Backfill recorded gaps; gaps that fail are retried next round
+    pub async fn backfill_gaps(&self) -> usize {
+        let gaps = std::mem::take(&mut *self.gaps.write());
+        let mut filled = 0;
+        for gap in gaps {
+            match self.backfill(&gap.exchange, &gap.symbol, gap.interval, gap.from_ns, gap.to_ns).await {
+                Ok(count) => filled += count,
+                Err(AdapterError::Configuration(_)) => {}
+                Err(e) => {
+                    warn!("Candle backfill failed for {} {} {}: {}", gap.exchange, gap.symbol, gap.interval.as_str(), e);
+                    self.gaps.write().push(gap);
+                }
+            }
+        }
+        if filled > 0 {
+            info!("Backfilled {} candles from REST", filled);
+        }
+        filled
+    }
+
+    /// Aggregate from the snapshot stream and backfill gaps periodically
+    pub fn spawn(self: Arc<Self>, mut snapshots: broadcast::Receiver<NormalizedSnapshot>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut backfill = tokio::time::interval(Duration::from_secs(self.config.backfill_interval_secs.max(1)));
+            loop {
+                tokio::select! {
+                    received = snapshots.recv() => match received {
+                        Ok(snapshot) => self.on_snapshot(&snapshot),
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!("Candle service lagged, skipped {} snapshots", skipped);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                    _ = backfill.tick() => {
+                        self.backfill_gaps().await;
+                    }
+                }
+            }
+        })
+    }
+}
+
+impl Default for CandleService {
+    fn default() -> Self {
+        Self::new(CandleConfig::default())
+    }
+}
+
+/// Parse a REST klines response into bars, oldest first
+pub fn parse_klines(exchange: &str, body: &Value) -> AdapterResult<Vec<Ohlcv>> {
+    let rows = match exchange.to_lowercase().as_str() {
+        "okx" => body.get("data").and_then(Value::as_array),
+        _ => body.as_array(),
+    }
+    .ok_or_else(|| AdapterError::Validation { message: format!("unexpected klines payload from {}", exchange) })?;
+
+    // Numbers arrive as strings (prices) or integers (timestamps)
+    let num = |v: Option<&Value>| -> Option<f64> {
+        v.and_then(|v| v.as_str().and_then(|s| s.parse().ok()).or_else(|| v.as_f64()))
+    };
+    let mut bars: Vec<Ohlcv> = rows
+        .iter()
+        .filter_map(|row| {
+            let row = row.as_array()?;
+            Some(Ohlcv {
+                start_ns: num(row.first())? as u64 * NS_PER_MS,
+                open: num(row.get(1))?,
+                high: num(row.get(2))?,
+                low: num(row.get(3))?,
+                close: num(row.get(4))?,
+                volume: num(row.get(5))?,
+                ticks: 0,
+            })
+        })
+        .collect();
+    bars.sort_by_key(|b| b.start_ns);
+    Ok(bars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEC: u64 = 1_000_000_000;
+
+    #[test]
+    fn test_rollover_gap_and_backfill_merge() {
+        let service = CandleService::new(CandleConfig { intervals: vec![CandleInterval::M1], ..Default::default() });
+        let mut events = service.subscribe();
+        service.on_tick("binance", "BTCUSDT", Tick { timestamp_ns: 5 * SEC, price: 100.0, quantity: 1.0 });
+        service.on_tick("binance", "BTCUSDT", Tick { timestamp_ns: 30 * SEC, price: 102.0, quantity: 2.0 });
+        // Two minutes without ticks
+        service.on_tick("binance", "BTCUSDT", Tick { timestamp_ns: 185 * SEC, price: 99.0, quantity: 1.0 });
+
+        let bars = service.history("binance", "BTCUSDT", CandleInterval::M1, 0, 600 * SEC);
+        assert_eq!(bars.len(), 2);
+        assert_eq!((bars[0].open, bars[0].high, bars[0].close, bars[0].volume), (100.0, 102.0, 102.0, 3.0));
+        let closed: Vec<Candle> = std::iter::from_fn(|| events.try_recv().ok()).filter(|c| c.closed).collect();
+        assert_eq!(closed.len(), 1);
+        assert_eq!(service.pending_gaps()[0].from_ns, 60 * SEC);
+        assert_eq!(service.pending_gaps()[0].to_ns, 180 * SEC);
+
+        let okx = serde_json::json!({ "code": "0", "data": [
+            ["120000", "101", "103", "100", "102", "5", "0", "0", "1"],
+            ["60000", "102", "104", "101", "101", "4", "0", "0", "1"],
+        ]});
+        let fetched = parse_klines("okx", &okx).unwrap();
+        assert_eq!(fetched[0].start_ns, 60 * SEC);
+        assert_eq!(service.merge("binance", "BTCUSDT", CandleInterval::M1, &fetched), 2);
+        let starts: Vec<u64> = service
+            .history("binance", "BTCUSDT", CandleInterval::M1, 0, 600 * SEC)
+            .iter()
+            .map(|b| b.start_ns / SEC)
+            .collect();
+        assert_eq!(starts, vec![0, 60, 120, 180]);
+    }
+}
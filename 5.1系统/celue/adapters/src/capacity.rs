@@ -0,0 +1,277 @@
+//! Capacity planning from historical usage
+//!
+//! Usage samples for CPU, memory, connections and throughput are fitted
+//! with a linear trend plus an hour-of-day seasonal profile. The forecast
+//! projects when the trend plus the worst seasonal peak crosses the
+//! configured limit. If that happens within the planning horizon, a
+//! `CapacityPlanning` insight recommends a new limit that leaves headroom
+//! over the usage projected at the end of the horizon.
+
+use crate::alerting::AlertSeverity;
+use crate::self_monitor::{InsightKind, PerformanceInsight};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+const NS_PER_HOUR: f64 = 3_600e9;
+const NS_PER_DAY: f64 = 86_400e9;
+
+/// Resource limits the system is provisioned for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SystemLimitsConfig {
+    pub max_cpu_pct: f64,
+    pub max_memory_mb: f64,
+    pub max_connections: f64,
+    pub max_throughput_per_sec: f64,
+}
+
+impl Default for SystemLimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_cpu_pct: 80.0,
+            max_memory_mb: 16_384.0,
+            max_connections: 512.0,
+            max_throughput_per_sec: 50_000.0,
+        }
+    }
+}
+
+/// Tracked resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resource {
+    Cpu,
+    Memory,
+    Connections,
+    Throughput,
+}
+
+impl Resource {
+    pub const ALL: [Resource; 4] = [Resource::Cpu, Resource::Memory, Resource::Connections, Resource::Throughput];
+
+    /// `SystemLimitsConfig` field governing this resource
+    pub fn limit_name(&self) -> &'static str {
+        match self {
+            Resource::Cpu => "max_cpu_pct",
+            Resource::Memory => "max_memory_mb",
+            Resource::Connections => "max_connections",
+            Resource::Throughput => "max_throughput_per_sec",
+        }
+    }
+
+    fn limit(&self, limits: &SystemLimitsConfig) -> f64 {
+        match self {
+            Resource::Cpu => limits.max_cpu_pct,
+            Resource::Memory => limits.max_memory_mb,
+            Resource::Connections => limits.max_connections,
+            Resource::Throughput => limits.max_throughput_per_sec,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapacityConfig {
+    /// How far ahead to look for limit breaches
+    pub horizon_days: f64,
+    /// Recommended limit = projected peak at the horizon * (1 + headroom)
+    pub headroom: f64,
+    /// Samples needed before forecasting
+    pub min_samples: usize,
+    /// History kept per resource
+    pub max_samples: usize,
+}
+
+impl Default for CapacityConfig {
+    fn default() -> Self {
+        Self { horizon_days: 30.0, headroom: 0.25, min_samples: 48, max_samples: 24 * 60 }
+    }
+}
+
+/// Fitted model for one resource
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapacityForecast {
+    pub resource: Resource,
+    pub limit: f64,
+    /// Trend value at the newest sample
+    pub current_trend: f64,
+    /// Growth per day
+    pub slope_per_day: f64,
+    /// Largest hour-of-day excess over the trend
+    pub seasonal_peak: f64,
+    /// Days until trend + seasonal peak reaches the limit; None if never
+    pub days_to_limit: Option<f64>,
+    /// Trend + seasonal peak at the end of the horizon
+    pub projected_peak: f64,
+}
+
+/// Least-squares trend with an hour-of-day seasonal profile
+fn fit(samples: &VecDeque<(u64, f64)>) -> Option<(f64, f64, f64, u64)> {
+    let n = samples.len() as f64;
+    let (t0, _) = *samples.front()?;
+    let xs: Vec<f64> = samples.iter().map(|(t, _)| (t - t0) as f64 / NS_PER_DAY).collect();
+    let mean_x = xs.iter().sum::<f64>() / n;
+    let mean_y = samples.iter().map(|(_, v)| v).sum::<f64>() / n;
+    let sxx: f64 = xs.iter().map(|x| (x - mean_x).powi(2)).sum();
+    let sxy: f64 = xs.iter().zip(samples).map(|(x, (_, y))| (x - mean_x) * (y - mean_y)).sum();
+    let slope = if sxx > 0.0 { sxy / sxx } else { 0.0 };
+    let intercept = mean_y - slope * mean_x;
+
+    let mut hourly: HashMap<u64, (f64, usize)> = HashMap::new();
+    for (x, (t, y)) in xs.iter().zip(samples) {
+        let hour = (*t as f64 / NS_PER_HOUR) as u64 % 24;
+        let entry = hourly.entry(hour).or_default();
+        entry.0 += y - (intercept + slope * x);
+        entry.1 += 1;
+    }
+    let seasonal_peak = hourly.values().map(|(sum, count)| sum / *count as f64).fold(0.0, f64::max);
+    let (t_last, _) = *samples.back()?;
+    let current = intercept + slope * (t_last - t0) as f64 / NS_PER_DAY;
+    Some((current, slope, seasonal_peak, t_last))
+}
+
+/// Projects resource exhaustion from usage history
+pub struct CapacityForecaster {
+    config: CapacityConfig,
+    samples: Mutex<HashMap<Resource, VecDeque<(u64, f64)>>>,
+}
+
+impl CapacityForecaster {
+    pub fn new(config: CapacityConfig) -> Self {
+        Self { config, samples: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn record(&self, resource: Resource, value: f64, timestamp_ns: u64) {
+        if !value.is_finite() {
+            return;
+        }
+        let mut samples = self.samples.lock();
+        let series = samples.entry(resource).or_default();
+        series.push_back((timestamp_ns, value));
+        while series.len() > self.config.max_samples.max(2) {
+            series.pop_front();
+        }
+    }
+
+    pub fn forecast(&self, resource: Resource, limits: &SystemLimitsConfig) -> Option<CapacityForecast> {
+        let samples = self.samples.lock();
+        let series = samples.get(&resource).filter(|s| s.len() >= self.config.min_samples.max(2))?;
+        let (current, slope, seasonal_peak, _) = fit(series)?;
+        let limit = resource.limit(limits);
+        let peak_now = current + seasonal_peak;
+        let days_to_limit = if peak_now >= limit {
+            Some(0.0)
+        } else if slope > 0.0 {
+            Some((limit - peak_now) / slope)
+        } else {
+            None
+        };
+        Some(CapacityForecast {
+            resource,
+            limit,
+            current_trend: current,
+            slope_per_day: slope,
+            seasonal_peak,
+            days_to_limit,
+            projected_peak: peak_now + slope.max(0.0) * self.config.horizon_days,
+        })
+    }
+
+    /// Insights for resources projected to hit their limit within the horizon
+    pub fn insights(&self, limits: &SystemLimitsConfig, now_ns: u64) -> Vec<PerformanceInsight> {
+        Resource::ALL
+            .iter()
+            .filter_map(|resource| self.forecast(*resource, limits))
+            .filter(|f| f.days_to_limit.is_some_and(|d| d <= self.config.horizon_days))
+            .map(|f| {
+                let days = f.days_to_limit.unwrap_or_default();
+                let recommended = (f.projected_peak * (1.0 + self.config.headroom)).ceil();
+                let series = self
+                    .samples
+                    .lock()
+                    .get(&f.resource)
+                    .map(|s| s.iter().copied().collect())
+                    .unwrap_or_default();
+                PerformanceInsight {
+                    component: "capacity".to_string(),
+                    metric: f.resource.limit_name().to_string(),
+                    kind: InsightKind::CapacityPlanning,
+                    severity: if days <= self.config.horizon_days / 4.0 {
+                        AlertSeverity::Critical
+                    } else {
+                        AlertSeverity::Warning
+                    },
+                    description: format!(
+                        "{:?} projected to reach limit {:.0} in {:.1} days (growth {:.2}/day); raise {} to {:.0}",
+                        f.resource,
+                        f.limit,
+                        days,
+                        f.slope_per_day,
+                        f.resource.limit_name(),
+                        recommended
+                    ),
+                    value: f.current_trend + f.seasonal_peak,
+                    baseline_mean: f.current_trend,
+                    baseline_std: 0.0,
+                    z_score: 0.0,
+                    detected_at_ns: now_ns,
+                    series,
+                    recommendations: HashMap::from([(f.resource.limit_name().to_string(), recommended)]),
+                }
+            })
+            .collect()
+    }
+
+    /// Copy of `limits` with every recommendation applied
+    pub fn recommended_limits(&self, limits: &SystemLimitsConfig, now_ns: u64) -> SystemLimitsConfig {
+        let mut updated = limits.clone();
+        for insight in self.insights(limits, now_ns) {
+            for (name, value) in insight.recommendations {
+                match name.as_str() {
+                    "max_cpu_pct" => updated.max_cpu_pct = value.min(100.0),
+                    "max_memory_mb" => updated.max_memory_mb = value,
+                    "max_connections" => updated.max_connections = value,
+                    "max_throughput_per_sec" => updated.max_throughput_per_sec = value,
+                    _ => {}
+                }
+            }
+        }
+        updated
+    }
+}
+
+impl Default for CapacityForecaster {
+    fn default() -> Self {
+        Self::new(CapacityConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_growing_connections_with_daily_peak() {
+        let forecaster = CapacityForecaster::default();
+        let limits = SystemLimitsConfig { max_connections: 400.0, ..Default::default() };
+        // 20 days hourly: 200 connections growing 5/day, +50 during hour 14
+        for h in 0..(20 * 24u64) {
+            let day = h as f64 / 24.0;
+            let peak = if h % 24 == 14 { 50.0 } else { 0.0 };
+            forecaster.record(Resource::Connections, 200.0 + 5.0 * day + peak, h * NS_PER_HOUR as u64);
+            forecaster.record(Resource::Cpu, 30.0, h * NS_PER_HOUR as u64);
+        }
+
+        let forecast = forecaster.forecast(Resource::Connections, &limits).unwrap();
+        assert!((forecast.slope_per_day - 5.0).abs() < 0.1);
+        // trend ~300 + peak ~48 -> about 10 days to 400
+        let days = forecast.days_to_limit.unwrap();
+        assert!((9.0..12.0).contains(&days), "days {}", days);
+
+        let insights = forecaster.insights(&limits, 0);
+        assert_eq!(insights.len(), 1);
+        assert_eq!(insights[0].kind, InsightKind::CapacityPlanning);
+        let updated = forecaster.recommended_limits(&limits, 0);
+        assert!(updated.max_connections > 500.0);
+        assert_eq!(updated.max_cpu_pct, limits.max_cpu_pct);
+    }
+}
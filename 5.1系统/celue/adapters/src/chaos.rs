@@ -0,0 +1,369 @@
+//! Network fault injection for chaos testing (feature `chaos`)
+//!
+//! Scenarios loaded from config describe latency, jitter, message drops
+//! and disconnect windows per target (an exchange or a NATS subject
+//! prefix). `ChaosController` starts and stops one experiment at a time;
+//! wrapped exchange executors and NATS publishers call `inject` before
+//! each operation and `record_outcome` after it, so the experiment report
+//! shows both what was injected and how the system behaved.
+
+use crate::nats::{NatsAdapter, NatsMessage};
+use crate::slicing::SliceExecutor;
+use crate::{AdapterError, AdapterResult};
+use chrono::{DateTime, Utc};
+use common::ArbitrageLeg;
+use parking_lot::RwLock;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// What a fault applies to
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "name", rename_all = "snake_case")]
+pub enum FaultTarget {
+    /// Order placement on one exchange
+    Exchange(String),
+    /// NATS publishes/requests on subjects with this prefix
+    Nats(String),
+}
+
+impl FaultTarget {
+    fn matches(&self, other: &FaultTarget) -> bool {
+        match (self, other) {
+            (Self::Exchange(a), Self::Exchange(b)) => a.eq_ignore_ascii_case(b),
+            (Self::Nats(prefix), Self::Nats(subject)) => subject.starts_with(prefix.as_str()),
+            _ => false,
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            Self::Exchange(name) => format!("exchange:{}", name),
+            Self::Nats(subject) => format!("nats:{}", subject),
+        }
+    }
+}
+
+/// Faults applied to one target
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FaultSpec {
+    /// Added to every operation (ms)
+    #[serde(default)]
+    pub latency_ms: u64,
+    /// Uniform extra delay in [0, jitter_ms] (ms)
+    #[serde(default)]
+    pub jitter_ms: u64,
+    /// Probability an operation is dropped
+    #[serde(default)]
+    pub drop_probability: f64,
+    /// Disconnect window relative to experiment start: (start_ms, duration_ms)
+    #[serde(default)]
+    pub disconnect: Option<(u64, u64)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetedFault {
+    pub target: FaultTarget,
+    pub spec: FaultSpec,
+}
+
+/// A named chaos experiment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChaosScenario {
+    pub name: String,
+    /// The experiment stops by itself after this long
+    pub duration_secs: u64,
+    pub faults: Vec<TargetedFault>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChaosConfig {
+    #[serde(default)]
+    pub scenarios: Vec<ChaosScenario>,
+}
+
+impl ChaosConfig {
+    pub fn from_json_file(path: &str) -> AdapterResult<Self> {
+        let raw = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&raw)?)
+    }
+}
+
+/// Per-target tally of an experiment
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TargetStats {
+    pub operations: u64,
+    pub delayed: u64,
+    pub injected_delay_ms: u64,
+    pub dropped: u64,
+    pub disconnected: u64,
+    /// Outcomes of operations that were let through
+    pub succeeded: u64,
+    pub failed: u64,
+    pub total_latency_ms: f64,
+}
+
+/// Summary of a finished (or running) experiment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExperimentReport {
+    pub id: String,
+    pub scenario: String,
+    pub started_at: DateTime<Utc>,
+    pub stopped_at: Option<DateTime<Utc>>,
+    pub targets: HashMap<String, TargetStats>,
+}
+
+struct ActiveExperiment {
+    scenario: ChaosScenario,
+    started: Instant,
+    report: ExperimentReport,
+}
+
+enum Injection {
+    Pass,
+    Delay(Duration),
+    Drop,
+    Disconnect,
+}
+
+/// Chaos experiment controller
+pub struct ChaosController {
+    scenarios: HashMap<String, ChaosScenario>,
+    active: RwLock<Option<ActiveExperiment>>,
+    history: RwLock<Vec<ExperimentReport>>,
+}
+
+impl ChaosController {
+    pub fn new(config: ChaosConfig) -> Self {
+        Self {
+            scenarios: config.scenarios.into_iter().map(|s| (s.name.clone(), s)).collect(),
+            active: RwLock::new(None),
+            history: RwLock::new(Vec::new()),
+        }
+    }
+
+    pub fn scenarios(&self) -> Vec<ChaosScenario> {
+        self.scenarios.values().cloned().collect()
+    }
+
+    /// Start a scenario; fails while another experiment is running
+    pub fn start(&self, scenario: &str) -> AdapterResult<String> {
+        self.expire();
+        let scenario = self.scenarios.get(scenario).cloned().ok_or_else(|| AdapterError::Validation {
+            message: format!("unknown chaos scenario: {}", scenario),
+        })?;
+        let mut active = self.active.write();
+        if active.is_some() {
+            return Err(AdapterError::AlreadyRunning);
+        }
+        let id = uuid::Uuid::new_v4().to_string();
+        warn!("Chaos experiment {} started: {}", id, scenario.name);
+        *active = Some(ActiveExperiment {
+            report: ExperimentReport {
+                id: id.clone(),
+                scenario: scenario.name.clone(),
+                started_at: Utc::now(),
+                stopped_at: None,
+                targets: HashMap::new(),
+            },
+            scenario,
+            started: Instant::now(),
+        });
+        Ok(id)
+    }
+
+    /// Stop the running experiment and return its report
+    pub fn stop(&self) -> Option<ExperimentReport> {
+        let experiment = self.active.write().take()?;
+        let mut report = experiment.report;
+        report.stopped_at = Some(Utc::now());
+        info!("Chaos experiment {} stopped", report.id);
+        self.history.write().push(report.clone());
+        Some(report)
+    }
+
+    /// Report of the running experiment so far
+    pub fn current(&self) -> Option<ExperimentReport> {
+        self.expire();
+        self.active.read().as_ref().map(|e| e.report.clone())
+    }
+
+    pub fn history(&self) -> Vec<ExperimentReport> {
+        self.history.read().clone()
+    }
+
+    fn expire(&self) {
+        let expired = self
+            .active
+            .read()
+            .as_ref()
+            .is_some_and(|e| e.started.elapsed() >= Duration::from_secs(e.scenario.duration_secs));
+        if expired {
+            self.stop();
+        }
+    }
+
+    fn decide(&self, target: &FaultTarget) -> Injection {
+        self.expire();
+        let mut active = self.active.write();
+        let Some(experiment) = active.as_mut() else { return Injection::Pass };
+        let Some(fault) = experiment.scenario.faults.iter().find(|f| f.target.matches(target)).cloned() else {
+            return Injection::Pass;
+        };
+        let spec = fault.spec;
+
+        let elapsed_ms = experiment.started.elapsed().as_millis() as u64;
+        // Tallied per configured target, not per subject
+        let stats = experiment.report.targets.entry(fault.target.label()).or_default();
+        stats.operations += 1;
+        let mut rng = rand::thread_rng();
+        if spec.disconnect.is_some_and(|(start, len)| elapsed_ms >= start && elapsed_ms < start + len) {
+            stats.disconnected += 1;
+            return Injection::Disconnect;
+        }
+        if spec.drop_probability > 0.0 && rng.gen_bool(spec.drop_probability.min(1.0)) {
+            stats.dropped += 1;
+            return Injection::Drop;
+        }
+        let jitter = if spec.jitter_ms > 0 { rng.gen_range(0..=spec.jitter_ms) } else { 0 };
+        let delay_ms = spec.latency_ms + jitter;
+        if delay_ms == 0 {
+            return Injection::Pass;
+        }
+        stats.delayed += 1;
+        stats.injected_delay_ms += delay_ms;
+        Injection::Delay(Duration::from_millis(delay_ms))
+    }
+
+    /// Apply the active faults for `target`; Err means the operation is lost
+    pub async fn inject(&self, target: &FaultTarget) -> AdapterResult<()> {
+        match self.decide(target) {
+            Injection::Pass => Ok(()),
+            Injection::Delay(delay) => {
+                tokio::time::sleep(delay).await;
+                Ok(())
+            }
+            Injection::Drop => Err(AdapterError::Connection(format!("chaos: dropped {}", target.label()))),
+            Injection::Disconnect => Err(AdapterError::Connection(format!("chaos: {} disconnected", target.label()))),
+        }
+    }
+
+    /// Record how an operation that went through behaved
+    pub fn record_outcome(&self, target: &FaultTarget, ok: bool, latency: Duration) {
+        let mut active = self.active.write();
+        let Some(experiment) = active.as_mut() else { return };
+        if let Some(fault) = experiment.scenario.faults.iter().find(|f| f.target.matches(target)) {
+            let stats = experiment.report.targets.entry(fault.target.label()).or_default();
+            if ok {
+                stats.succeeded += 1;
+            } else {
+                stats.failed += 1;
+            }
+            stats.total_latency_ms += latency.as_secs_f64() * 1000.0;
+        }
+    }
+}
+
+/// Exchange order placement behind the chaos layer
+pub struct ChaosSliceExecutor<E> {
+    inner: E,
+    chaos: Arc<ChaosController>,
+}
+
+impl<E: SliceExecutor> ChaosSliceExecutor<E> {
+    pub fn new(inner: E, chaos: Arc<ChaosController>) -> Self {
+        Self { inner, chaos }
+    }
+}
+
+#[async_trait::async_trait]
+impl<E: SliceExecutor> SliceExecutor for ChaosSliceExecutor<E> {
+    async fn execute_slice(&self, leg: &ArbitrageLeg, quantity: f64) -> AdapterResult<(String, f64)> {
+        let target = FaultTarget::Exchange(leg.exchange.as_str().to_string());
+        let started = Instant::now();
+        self.chaos.inject(&target).await?;
+        let result = self.inner.execute_slice(leg, quantity).await;
+        self.chaos.record_outcome(&target, result.is_ok(), started.elapsed());
+        result
+    }
+
+    fn batch_limit(&self, leg: &ArbitrageLeg) -> Option<usize> {
+        self.inner.batch_limit(leg)
+    }
+}
+
+/// NATS publishing behind the chaos layer
+pub struct ChaosNats {
+    inner: Arc<NatsAdapter>,
+    chaos: Arc<ChaosController>,
+}
+
+impl ChaosNats {
+    pub fn new(inner: Arc<NatsAdapter>, chaos: Arc<ChaosController>) -> Self {
+        Self { inner, chaos }
+    }
+
+    pub async fn publish(&self, subject: &str, message: &NatsMessage) -> AdapterResult<()> {
+        let target = FaultTarget::Nats(subject.to_string());
+        let started = Instant::now();
+        self.chaos.inject(&target).await?;
+        let result = self.inner.publish(subject, message).await;
+        self.chaos.record_outcome(&target, result.is_ok(), started.elapsed());
+        result
+    }
+
+    pub async fn request(&self, subject: &str, message: &NatsMessage) -> AdapterResult<NatsMessage> {
+        let target = FaultTarget::Nats(subject.to_string());
+        let started = Instant::now();
+        self.chaos.inject(&target).await?;
+        let result = self.inner.request(subject, message).await;
+        self.chaos.record_outcome(&target, result.is_ok(), started.elapsed());
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_experiment_injects_and_reports() {
+        let controller = ChaosController::new(ChaosConfig {
+            scenarios: vec![ChaosScenario {
+                name: "okx-outage".into(),
+                duration_secs: 60,
+                faults: vec![
+                    TargetedFault {
+                        target: FaultTarget::Exchange("okx".into()),
+                        spec: FaultSpec { disconnect: Some((0, 60_000)), ..Default::default() },
+                    },
+                    TargetedFault {
+                        target: FaultTarget::Nats("market.".into()),
+                        spec: FaultSpec { latency_ms: 5, ..Default::default() },
+                    },
+                ],
+            }],
+        });
+        let okx = FaultTarget::Exchange("OKX".into());
+        assert!(controller.inject(&okx).await.is_ok());
+
+        controller.start("okx-outage").unwrap();
+        assert!(controller.start("okx-outage").is_err());
+        assert!(controller.inject(&okx).await.is_err());
+        let subject = FaultTarget::Nats("market.binance.btcusdt".into());
+        controller.inject(&subject).await.unwrap();
+        controller.record_outcome(&subject, true, Duration::from_millis(6));
+        assert!(controller.inject(&FaultTarget::Exchange("binance".into())).await.is_ok());
+
+        let report = controller.stop().unwrap();
+        assert_eq!(report.targets["exchange:okx"].disconnected, 1);
+        assert_eq!(report.targets["nats:market."].delayed, 1);
+        assert_eq!(report.targets["nats:market."].succeeded, 1);
+        assert!(!report.targets.contains_key("exchange:binance"));
+        assert!(controller.inject(&okx).await.is_ok());
+        assert_eq!(controller.history().len(), 1);
+    }
+}
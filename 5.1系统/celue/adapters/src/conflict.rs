@@ -0,0 +1,268 @@
+//! Cross-strategy opportunity conflict resolution
+//!
+//! One price dislocation often shows up in several detectors at once, e.g.
+//! the inter-exchange and triangular strategies both buying BTCUSDT on the
+//! same venue. Executing both doubles the exposure to a single edge. Two
+//! opportunities conflict when they were created within a time window and
+//! share a leg (same exchange, symbol and side). Exact duplicates, where
+//! the leg sets are equal, are merged into one opportunity. Otherwise the
+//! one with the higher risk-adjusted return is kept. Every suppressed
+//! opportunity is recorded for later analysis.
+
+use common::{ArbitrageOpportunity, Side};
+use metrics::counter;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConflictConfig {
+    /// Opportunities created further apart than this never conflict
+    pub window_ns: u64,
+    /// Multiplier applied per leg beyond two, reflecting execution risk
+    pub extra_leg_penalty: f64,
+    /// Suppressed records kept for analysis
+    pub history_size: usize,
+}
+
+impl Default for ConflictConfig {
+    fn default() -> Self {
+        Self {
+            window_ns: 500_000_000,
+            extra_leg_penalty: 0.9,
+            history_size: 1_000,
+        }
+    }
+}
+
+/// How a suppressed opportunity lost
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuppressionReason {
+    /// Same legs as the kept opportunity; folded into it
+    Merged,
+    /// Overlapping legs and a lower risk-adjusted return
+    Outscored,
+}
+
+/// A duplicate that was not executed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuppressedOpportunity {
+    pub suppressed_id: Uuid,
+    pub suppressed_strategy: String,
+    pub suppressed_score: f64,
+    pub kept_id: Uuid,
+    pub kept_strategy: String,
+    pub kept_score: f64,
+    pub reason: SuppressionReason,
+    /// "exchange:symbol:side" keys both opportunities trade
+    pub shared_legs: Vec<String>,
+    pub at_ns: u64,
+}
+
+/// Outcome of checking a new opportunity against the live ones
+#[derive(Debug, Clone, PartialEq)]
+pub enum Resolution {
+    /// No conflict, or the newcomer wins; the listed live opportunities
+    /// must be withdrawn
+    Keep { displaced: Vec<Uuid> },
+    /// A live opportunity wins; the newcomer must be dropped
+    Suppress { by: Uuid },
+}
+
+fn leg_keys(opportunity: &ArbitrageOpportunity) -> BTreeSet<String> {
+    opportunity
+        .legs
+        .iter()
+        .map(|l| {
+            let side = match l.side {
+                Side::Buy => "buy",
+                Side::Sell => "sell",
+            };
+            format!("{}:{}:{}", l.exchange.as_str().to_ascii_lowercase(), l.symbol.as_str(), side)
+        })
+        .collect()
+}
+
+/// Detects overlapping opportunities and picks a winner
+pub struct ConflictResolver {
+    config: ConflictConfig,
+    suppressed: Mutex<VecDeque<SuppressedOpportunity>>,
+}
+
+impl ConflictResolver {
+    pub fn new(config: ConflictConfig) -> Self {
+        Self { config, suppressed: Mutex::new(VecDeque::new()) }
+    }
+
+    /// Net return scaled by the scorer's confidence and risk tags, when
+    /// present, and by a penalty for each leg beyond two
+    pub fn risk_adjusted_return(&self, opportunity: &ArbitrageOpportunity) -> f64 {
+        let tag = |name: &str| opportunity.tags.get(name).and_then(|v| v.parse::<f64>().ok());
+        let confidence = tag("confidence_score").unwrap_or(1.0);
+        let risk = tag("risk_score").unwrap_or(0.0).clamp(0.0, 1.0);
+        let extra_legs = opportunity.legs.len().saturating_sub(2) as i32;
+        opportunity.net_profit_pct.to_f64() * confidence * (1.0 - risk) * self.config.extra_leg_penalty.powi(extra_legs)
+    }
+
+    /// Resolve `candidate` against the live opportunities. A merged
+    /// duplicate's strategy is added to the winner's `merged_strategies` tag.
+    pub fn resolve<'a>(
+        &self,
+        candidate: &mut ArbitrageOpportunity,
+        live: impl IntoIterator<Item = &'a mut ArbitrageOpportunity>,
+    ) -> Resolution {
+        let candidate_legs = leg_keys(candidate);
+        let candidate_score = self.risk_adjusted_return(candidate);
+        let mut displaced = Vec::new();
+        let mut beaten = Vec::new();
+
+        for existing in live {
+            if existing.created_at_ns.abs_diff(candidate.created_at_ns) > self.config.window_ns {
+                continue;
+            }
+            let existing_legs = leg_keys(existing);
+            let shared: Vec<String> = candidate_legs.intersection(&existing_legs).cloned().collect();
+            if shared.is_empty() {
+                continue;
+            }
+            let reason = if candidate_legs == existing_legs { SuppressionReason::Merged } else { SuppressionReason::Outscored };
+            let existing_score = self.risk_adjusted_return(existing);
+
+            if existing_score >= candidate_score {
+                if reason == SuppressionReason::Merged {
+                    merge_tag(existing, &candidate.strategy_name);
+                }
+                self.record(candidate, candidate_score, existing, existing_score, reason, shared);
+                // Anything the candidate would have displaced stays live
+                return Resolution::Suppress { by: existing.id };
+            }
+            displaced.push(existing.id);
+            beaten.push((existing.clone(), existing_score, reason, shared));
+        }
+        for (existing, existing_score, reason, shared) in beaten {
+            if reason == SuppressionReason::Merged {
+                merge_tag(candidate, &existing.strategy_name);
+            }
+            self.record(&existing, existing_score, candidate, candidate_score, reason, shared);
+        }
+        Resolution::Keep { displaced }
+    }
+
+    fn record(
+        &self,
+        loser: &ArbitrageOpportunity,
+        loser_score: f64,
+        winner: &ArbitrageOpportunity,
+        winner_score: f64,
+        reason: SuppressionReason,
+        shared_legs: Vec<String>,
+    ) {
+        counter!(
+            "opportunity_duplicates_suppressed_total",
+            "strategy" => loser.strategy_name.clone(),
+            "kept_strategy" => winner.strategy_name.clone()
+        )
+        .increment(1);
+        let mut suppressed = self.suppressed.lock();
+        suppressed.push_back(SuppressedOpportunity {
+            suppressed_id: loser.id,
+            suppressed_strategy: loser.strategy_name.clone(),
+            suppressed_score: loser_score,
+            kept_id: winner.id,
+            kept_strategy: winner.strategy_name.clone(),
+            kept_score: winner_score,
+            reason,
+            shared_legs,
+            at_ns: chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64,
+        });
+        while suppressed.len() > self.config.history_size.max(1) {
+            suppressed.pop_front();
+        }
+    }
+
+    /// Most recent suppressed duplicates, newest first
+    pub fn suppressed(&self, limit: usize) -> Vec<SuppressedOpportunity> {
+        self.suppressed.lock().iter().rev().take(limit).cloned().collect()
+    }
+
+    /// Suppression counts by (suppressed strategy, kept strategy)
+    pub fn suppression_counts(&self) -> HashMap<(String, String), u64> {
+        let mut counts = HashMap::new();
+        for record in self.suppressed.lock().iter() {
+            *counts
+                .entry((record.suppressed_strategy.clone(), record.kept_strategy.clone()))
+                .or_insert(0) += 1;
+        }
+        counts
+    }
+}
+
+impl Default for ConflictResolver {
+    fn default() -> Self {
+        Self::new(ConflictConfig::default())
+    }
+}
+
+fn merge_tag(winner: &mut ArbitrageOpportunity, strategy: &str) {
+    let merged = winner.tags.entry("merged_strategies".to_string()).or_default();
+    if !merged.split(',').any(|s| s == strategy) {
+        if !merged.is_empty() {
+            merged.push(',');
+        }
+        merged.push_str(strategy);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::testing::{leg, opportunity_with_legs};
+
+    #[test]
+    fn test_select_merge_and_window() {
+        let resolver = ConflictResolver::default();
+        let mut live = HashMap::new();
+
+        let inter = opportunity_with_legs(
+            "inter_exchange",
+            vec![leg("binance", "BTCUSDT", Side::Buy, 100.0, 1.0), leg("okx", "BTCUSDT", Side::Sell, 100.0, 1.0)],
+            0.004,
+            0,
+        );
+        live.insert(inter.id, inter.clone());
+
+        // Triangular shares the binance buy but its 3 legs are penalised: 0.0044 * 0.9 < 0.004
+        let mut triangular = opportunity_with_legs(
+            "triangular",
+            vec![
+                leg("binance", "BTCUSDT", Side::Buy, 100.0, 1.0),
+                leg("binance", "ETHBTC", Side::Buy, 100.0, 1.0),
+                leg("binance", "ETHUSDT", Side::Sell, 100.0, 1.0),
+            ],
+            0.0044,
+            100_000_000,
+        );
+        assert_eq!(resolver.resolve(&mut triangular, live.values_mut()), Resolution::Suppress { by: inter.id });
+
+        // An exact duplicate with a better return replaces the live one and absorbs its strategy
+        let mut better = opportunity_with_legs(
+            "inter_exchange_v2",
+            vec![leg("binance", "BTCUSDT", Side::Buy, 100.0, 1.0), leg("okx", "BTCUSDT", Side::Sell, 100.0, 1.0)],
+            0.005,
+            200_000_000,
+        );
+        assert_eq!(resolver.resolve(&mut better, live.values_mut()), Resolution::Keep { displaced: vec![inter.id] });
+        assert_eq!(better.tags["merged_strategies"], "inter_exchange");
+
+        // Outside the window nothing conflicts
+        let mut later = opportunity_with_legs("triangular", triangular.legs.clone(), 0.001, 2_000_000_000);
+        assert_eq!(resolver.resolve(&mut later, live.values_mut()), Resolution::Keep { displaced: vec![] });
+
+        let history = resolver.suppressed(10);
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].reason, SuppressionReason::Merged);
+        assert_eq!(history[1].shared_legs, vec!["binance:BTCUSDT:buy".to_string()]);
+    }
+}
@@ -0,0 +1,333 @@
+//! Stablecoin depeg monitor
+//!
+//! Tracks each configured stablecoin against USD on every venue that
+//! quotes it (books such as USDT/USD or USDCUSD, or prices pushed through
+//! `observe`). The consensus price is the median of the fresh venue
+//! quotes. Beyond `warn_deviation` a Warning alert is raised; beyond
+//! `halt_deviation` a `QuoteAsset` halt stops new opportunities quoted in
+//! that coin and a Critical alert is raised. The halt is released once the
+//! deviation has stayed under `restore_deviation` for `restore_secs`.
+
+use crate::alerting::{Alert, AlertManager, AlertSeverity};
+use crate::halt::{HaltRegistry, HaltScope};
+use common::{OrderBook, SharedClock};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+const OPERATOR: &str = "depeg_monitor";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepegConfig {
+    pub stablecoins: Vec<String>,
+    /// Absolute deviation from 1.0 that raises a warning
+    pub warn_deviation: f64,
+    /// Absolute deviation from 1.0 that halts the quote currency
+    pub halt_deviation: f64,
+    /// Deviation the peg must stay under before the halt is released
+    pub restore_deviation: f64,
+    pub restore_secs: u64,
+    /// Venue quotes older than this are ignored
+    pub max_quote_age_ms: u64,
+}
+
+impl Default for DepegConfig {
+    fn default() -> Self {
+        Self {
+            stablecoins: vec!["USDT".to_string(), "USDC".to_string(), "FDUSD".to_string()],
+            warn_deviation: 0.003,
+            halt_deviation: 0.01,
+            restore_deviation: 0.002,
+            restore_secs: 300,
+            max_quote_age_ms: 60_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PegState {
+    Pegged,
+    Warning,
+    /// Quote currency excluded from new opportunities
+    Depegged,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PegStatus {
+    pub stablecoin: String,
+    pub state: PegState,
+    /// Median of fresh venue quotes
+    pub price: Option<f64>,
+    pub deviation: Option<f64>,
+    pub venues: HashMap<String, f64>,
+    /// Since when the peg has been within `restore_deviation` while depegged
+    pub restoring_since_ns: Option<u64>,
+}
+
+/// State change reported by `evaluate`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PegTransition {
+    pub stablecoin: String,
+    pub from: PegState,
+    pub to: PegState,
+    pub price: f64,
+}
+
+struct CoinState {
+    state: PegState,
+    venues: HashMap<String, (f64, u64)>,
+    restoring_since_ns: Option<u64>,
+}
+
+/// Watches stablecoin pegs and halts affected quote currencies
+pub struct DepegMonitor {
+    config: DepegConfig,
+    coins: RwLock<HashMap<String, CoinState>>,
+    halts: Arc<HaltRegistry>,
+    alerts: Option<Arc<AlertManager>>,
+    clock: SharedClock,
+}
+
+impl DepegMonitor {
+    pub fn new(config: DepegConfig, halts: Arc<HaltRegistry>) -> Self {
+        let coins = config
+            .stablecoins
+            .iter()
+            .map(|c| {
+                let state = CoinState { state: PegState::Pegged, venues: HashMap::new(), restoring_since_ns: None };
+                (c.to_uppercase(), state)
+            })
+            .collect();
+        Self {
+            config,
+            coins: RwLock::new(coins),
+            halts,
+            alerts: None,
+            clock: common::clock::system_clock(),
+        }
+    }
+
+    pub fn with_alerts(mut self, alerts: Arc<AlertManager>) -> Self {
+        self.alerts = Some(alerts);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Record a venue's USD price for a stablecoin
+    pub fn observe(&self, venue: &str, stablecoin: &str, price_usd: f64, timestamp_ns: u64) {
+        if !price_usd.is_finite() || price_usd <= 0.0 {
+            return;
+        }
+        if let Some(coin) = self.coins.write().get_mut(&stablecoin.to_uppercase()) {
+            coin.venues.insert(venue.to_lowercase(), (price_usd, timestamp_ns));
+        }
+    }
+
+    /// Feed a book; only `<STABLECOIN>` / USD books are used
+    pub fn on_book(&self, book: &OrderBook) {
+        let symbol: String =
+            book.symbol.as_str().chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_uppercase();
+        let Some(coin) = symbol.strip_suffix("USD") else { return };
+        let (Some(bid), Some(ask)) = (book.best_bid(), book.best_ask()) else { return };
+        let mid = (bid.price.to_f64() + ask.price.to_f64()) / 2.0;
+        self.observe(book.exchange.as_str(), coin, mid, book.timestamp_ns);
+    }
+
+    fn consensus(&self, coin: &CoinState, now_ns: u64) -> Option<f64> {
+        let max_age_ns = self.config.max_quote_age_ms * 1_000_000;
+        let mut prices: Vec<f64> = coin
+            .venues
+            .values()
+            .filter(|(_, ts)| now_ns.saturating_sub(*ts) <= max_age_ns)
+            .map(|(p, _)| *p)
+            .collect();
+        if prices.is_empty() {
+            return None;
+        }
+        prices.sort_by(f64::total_cmp);
+        let mid = prices.len() / 2;
+        Some(if prices.len().is_multiple_of(2) { (prices[mid - 1] + prices[mid]) / 2.0 } else { prices[mid] })
+    }
+
+    /// Re-check every peg, applying halts and releases
+    pub async fn evaluate(&self) -> Vec<PegTransition> {
+        let now_ns = self.clock.now_ns();
+        let mut transitions = Vec::new();
+        {
+            let mut coins = self.coins.write();
+            for (name, coin) in coins.iter_mut() {
+                let Some(price) = self.consensus(coin, now_ns) else { continue };
+                let deviation = (price - 1.0).abs();
+                metrics::gauge!("stablecoin_peg_deviation", "stablecoin" => name.clone()).set(deviation);
+
+                let next = match coin.state {
+                    _ if deviation >= self.config.halt_deviation => {
+                        coin.restoring_since_ns = None;
+                        PegState::Depegged
+                    }
+                    PegState::Depegged => {
+                        if deviation >= self.config.restore_deviation {
+                            coin.restoring_since_ns = None;
+                            PegState::Depegged
+                        } else {
+                            let since = *coin.restoring_since_ns.get_or_insert(now_ns);
+                            if now_ns.saturating_sub(since) >= self.config.restore_secs * 1_000_000_000 {
+                                coin.restoring_since_ns = None;
+                                PegState::Pegged
+                            } else {
+                                PegState::Depegged
+                            }
+                        }
+                    }
+                    _ if deviation >= self.config.warn_deviation => PegState::Warning,
+                    _ => PegState::Pegged,
+                };
+                if next != coin.state {
+                    transitions.push(PegTransition { stablecoin: name.clone(), from: coin.state, to: next, price });
+                    coin.state = next;
+                }
+            }
+        }
+
+        for t in &transitions {
+            let scope = HaltScope::QuoteAsset(t.stablecoin.clone());
+            match (t.from, t.to) {
+                (_, PegState::Depegged) => {
+                    error!("{} depegged at {:.4}, excluding it as quote currency", t.stablecoin, t.price);
+                    let reason = format!("{} depegged at {:.4}", t.stablecoin, t.price);
+                    if let Err(e) = self.halts.halt(scope, &reason, OPERATOR).await {
+                        warn!("Failed to halt {} quotes: {}", t.stablecoin, e);
+                    }
+                    self.alert(t, AlertSeverity::Critical, "depegged; new opportunities quoted in it are blocked");
+                }
+                (PegState::Depegged, _) => {
+                    info!("{} peg restored at {:.4}", t.stablecoin, t.price);
+                    self.halts.release(&scope, OPERATOR);
+                    self.alert(t, AlertSeverity::Info, "peg restored; quote currency re-enabled");
+                }
+                (_, PegState::Warning) => {
+                    warn!("{} drifting from peg at {:.4}", t.stablecoin, t.price);
+                    self.alert(t, AlertSeverity::Warning, "drifting from peg");
+                }
+                _ => {}
+            }
+        }
+        transitions
+    }
+
+    fn alert(&self, transition: &PegTransition, severity: AlertSeverity, what: &str) {
+        let Some(alerts) = self.alerts.clone() else { return };
+        let alert = Alert::new(
+            &format!("depeg:{}", transition.stablecoin),
+            severity,
+            &format!("{} {}", transition.stablecoin, what),
+            &format!("{} trades at {:.4} USD", transition.stablecoin, transition.price),
+            OPERATOR,
+        );
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            handle.spawn(async move {
+                alerts.raise(alert).await;
+            });
+        }
+    }
+
+    pub fn status(&self) -> Vec<PegStatus> {
+        let now_ns = self.clock.now_ns();
+        let mut status: Vec<PegStatus> = self
+            .coins
+            .read()
+            .iter()
+            .map(|(name, coin)| {
+                let price = self.consensus(coin, now_ns);
+                PegStatus {
+                    stablecoin: name.clone(),
+                    state: coin.state,
+                    price,
+                    deviation: price.map(|p| (p - 1.0).abs()),
+                    venues: coin.venues.iter().map(|(v, (p, _))| (v.clone(), *p)).collect(),
+                    restoring_since_ns: coin.restoring_since_ns,
+                }
+            })
+            .collect();
+        status.sort_by(|a, b| a.stablecoin.cmp(&b.stablecoin));
+        status
+    }
+
+    /// Quote currencies currently excluded
+    pub fn excluded_quotes(&self) -> Vec<String> {
+        self.status().into_iter().filter(|s| s.state == PegState::Depegged).map(|s| s.stablecoin).collect()
+    }
+
+    pub fn spawn(self: Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.evaluate().await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::testing::inter_exchange_opportunity;
+    use common::{Clock, SimulatedClock};
+
+    #[tokio::test]
+    async fn test_depeg_halts_quote_until_restored() {
+        let clock = Arc::new(SimulatedClock::from_nanos(1_000_000_000));
+        let halts = Arc::new(HaltRegistry::new());
+        let monitor = DepegMonitor::new(DepegConfig::default(), halts.clone()).with_clock(clock.clone());
+
+        monitor.observe("kraken", "USDT", 0.97, clock.now_ns());
+        monitor.observe("coinbase", "USDT", 0.98, clock.now_ns());
+        monitor.observe("coinbase", "USDC", 1.0001, clock.now_ns());
+        let transitions = monitor.evaluate().await;
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].to, PegState::Depegged);
+        assert!(halts.blocking_halt(&inter_exchange_opportunity("inter_exchange", "BTC-USDT", 0.01)).is_some());
+        assert!(halts.blocking_halt(&inter_exchange_opportunity("inter_exchange", "BTC/USDC", 0.01)).is_none());
+
+        // Back on peg, but the halt holds until restore_secs have passed
+        monitor.observe("kraken", "USDT", 0.9995, clock.now_ns());
+        monitor.observe("coinbase", "USDT", 1.0, clock.now_ns());
+        assert!(monitor.evaluate().await.is_empty());
+        clock.advance(chrono::Duration::seconds(301));
+        monitor.observe("kraken", "USDT", 0.9995, clock.now_ns());
+        monitor.observe("coinbase", "USDT", 1.0, clock.now_ns());
+        let transitions = monitor.evaluate().await;
+        assert_eq!(transitions[0].to, PegState::Pegged);
+        assert!(halts.blocking_halt(&inter_exchange_opportunity("inter_exchange", "BTCUSDT", 0.01)).is_none());
+        assert!(monitor.excluded_quotes().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_restore_keeps_operator_halt() {
+        let clock = Arc::new(SimulatedClock::from_nanos(1_000_000_000));
+        let halts = Arc::new(HaltRegistry::new());
+        let monitor = DepegMonitor::new(DepegConfig::default(), halts.clone()).with_clock(clock.clone());
+        halts.halt(HaltScope::QuoteAsset("USDT".into()), "manual review", "ops").await.unwrap();
+
+        monitor.observe("kraken", "USDT", 0.97, clock.now_ns());
+        monitor.evaluate().await;
+        clock.advance(chrono::Duration::seconds(301));
+        monitor.observe("kraken", "USDT", 1.0, clock.now_ns());
+        monitor.evaluate().await;
+        clock.advance(chrono::Duration::seconds(301));
+        monitor.observe("kraken", "USDT", 1.0, clock.now_ns());
+        assert_eq!(monitor.evaluate().await[0].to, PegState::Pegged);
+
+        let halt = halts.blocking_halt(&inter_exchange_opportunity("inter_exchange", "BTC-USDT", 0.01)).unwrap();
+        assert_eq!(halt.operator, "ops");
+    }
+}
@@ -0,0 +1,278 @@
+//! Reference on-chain venue: constant-product pools (feature `dex`)
+//!
+//! Quotes come from Uniswap V2 style pools (`getReserves` over JSON-RPC)
+//! using the `x * y = k` curve with the pool fee, so the quoted price
+//! already includes price impact. Swaps are sent as raw transactions
+//! produced by a `SwapSigner`; key management stays outside this crate.
+//! Gas is priced by `OnChainSettlement` from the gas price captured with
+//! each quote.
+
+use crate::venue::{VenueConnector, VenueFill, VenueQuote};
+use crate::{AdapterError, AdapterResult};
+use common::{ArbitrageLeg, Side};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// `getReserves()` selector
+const GET_RESERVES: &str = "0x0902f1ac";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DexPoolConfig {
+    /// Symbol as used on opportunity legs, e.g. `ETHUSDC`
+    pub symbol: String,
+    pub address: String,
+    /// Whether the base asset is `token0` of the pair
+    pub base_is_token0: bool,
+    pub base_decimals: u8,
+    pub quote_decimals: u8,
+    #[serde(default = "default_pool_fee_bps")]
+    pub fee_bps: u32,
+}
+
+fn default_pool_fee_bps() -> u32 {
+    30
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DexConfig {
+    /// Venue name used on opportunity legs
+    pub name: String,
+    pub rpc_url: String,
+    pub pools: Vec<DexPoolConfig>,
+    /// Minimum output is the quoted output less this tolerance
+    #[serde(default = "default_slippage_tolerance_bps")]
+    pub slippage_tolerance_bps: u32,
+    #[serde(default = "default_deadline_secs")]
+    pub deadline_secs: u64,
+}
+
+fn default_slippage_tolerance_bps() -> u32 {
+    50
+}
+
+fn default_deadline_secs() -> u64 {
+    60
+}
+
+/// Swap handed to the signer, amounts in token base units
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SwapRequest {
+    pub pool: String,
+    pub side: Side,
+    pub amount_in: u128,
+    pub min_amount_out: u128,
+    /// Unix seconds after which the swap reverts
+    pub deadline: u64,
+    pub gas_price_wei: u128,
+}
+
+/// Builds and signs the router transaction for a swap
+pub trait SwapSigner: Send + Sync {
+    /// Hex encoded signed transaction
+    fn sign(&self, swap: &SwapRequest) -> AdapterResult<String>;
+}
+
+/// Chain access needed by the venue
+#[async_trait::async_trait]
+pub trait ChainRpc: Send + Sync {
+    /// (reserve0, reserve1)
+    async fn reserves(&self, pool: &str) -> AdapterResult<(u128, u128)>;
+    async fn gas_price_wei(&self) -> AdapterResult<u128>;
+    /// Broadcast a swap, returns the transaction hash
+    async fn send_swap(&self, swap: &SwapRequest) -> AdapterResult<String>;
+}
+
+/// Ethereum JSON-RPC client
+pub struct JsonRpcChain {
+    url: String,
+    http: reqwest::Client,
+    signer: Option<Arc<dyn SwapSigner>>,
+}
+
+impl JsonRpcChain {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into(), http: reqwest::Client::new(), signer: None }
+    }
+
+    pub fn with_signer(mut self, signer: Arc<dyn SwapSigner>) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    async fn call(&self, method: &str, params: Value) -> AdapterResult<Value> {
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response: Value = self
+            .http
+            .post(&self.url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| AdapterError::Connection(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| AdapterError::Connection(e.to_string()))?;
+        if let Some(error) = response.get("error") {
+            return Err(AdapterError::Generic { message: format!("{} failed: {}", method, error) });
+        }
+        response
+            .get("result")
+            .cloned()
+            .ok_or_else(|| AdapterError::Generic { message: format!("{}: missing result", method) })
+    }
+}
+
+/// Parse a hex quantity, ignoring bits above 128
+fn parse_hex_u128(hex: &str) -> AdapterResult<u128> {
+    let digits = hex.trim_start_matches("0x");
+    let digits = &digits[digits.len().saturating_sub(32)..];
+    if digits.is_empty() {
+        return Ok(0);
+    }
+    u128::from_str_radix(digits, 16)
+        .map_err(|e| AdapterError::Validation { message: format!("bad hex quantity {}: {}", hex, e) })
+}
+
+#[async_trait::async_trait]
+impl ChainRpc for JsonRpcChain {
+    async fn reserves(&self, pool: &str) -> AdapterResult<(u128, u128)> {
+        let result = self.call("eth_call", json!([{ "to": pool, "data": GET_RESERVES }, "latest"])).await?;
+        let data = result.as_str().unwrap_or_default().trim_start_matches("0x");
+        if data.len() < 128 {
+            return Err(AdapterError::Validation { message: format!("short getReserves response from {}", pool) });
+        }
+        Ok((parse_hex_u128(&data[..64])?, parse_hex_u128(&data[64..128])?))
+    }
+
+    async fn gas_price_wei(&self) -> AdapterResult<u128> {
+        let result = self.call("eth_gasPrice", json!([])).await?;
+        parse_hex_u128(result.as_str().unwrap_or_default())
+    }
+
+    async fn send_swap(&self, swap: &SwapRequest) -> AdapterResult<String> {
+        let signer = self
+            .signer
+            .as_ref()
+            .ok_or_else(|| AdapterError::Configuration("no swap signer configured".into()))?;
+        let raw = signer.sign(swap)?;
+        let result = self.call("eth_sendRawTransaction", json!([raw])).await?;
+        Ok(result.as_str().unwrap_or_default().to_string())
+    }
+}
+
+/// Output of a constant-product swap after the pool fee
+pub fn amount_out(amount_in: f64, reserve_in: f64, reserve_out: f64, fee_bps: u32) -> f64 {
+    let in_after_fee = amount_in * (10_000 - fee_bps) as f64 / 10_000.0;
+    reserve_out * in_after_fee / (reserve_in + in_after_fee)
+}
+
+/// Input needed for `amount_out` of a constant-product swap, None if the pool is too shallow
+pub fn amount_in(amount_out: f64, reserve_in: f64, reserve_out: f64, fee_bps: u32) -> Option<f64> {
+    if amount_out >= reserve_out {
+        return None;
+    }
+    Some(reserve_in * amount_out / (reserve_out - amount_out) * 10_000.0 / (10_000 - fee_bps) as f64)
+}
+
+/// Constant-product pool venue
+pub struct DexConnector<R> {
+    config: DexConfig,
+    pools: HashMap<String, DexPoolConfig>,
+    rpc: R,
+}
+
+impl<R: ChainRpc> DexConnector<R> {
+    pub fn new(config: DexConfig, rpc: R) -> Self {
+        let pools = config.pools.iter().map(|p| (p.symbol.to_uppercase(), p.clone())).collect();
+        Self { config, pools, rpc }
+    }
+
+    fn pool(&self, leg: &ArbitrageLeg) -> AdapterResult<&DexPoolConfig> {
+        self.pools.get(&leg.symbol.as_str().to_uppercase()).ok_or_else(|| AdapterError::Validation {
+            message: format!("{}: no pool for {}", self.config.name, leg.symbol.as_str()),
+        })
+    }
+
+    /// (base reserve, quote reserve) in whole tokens
+    async fn reserves(&self, pool: &DexPoolConfig) -> AdapterResult<(f64, f64)> {
+        let (r0, r1) = self.rpc.reserves(&pool.address).await?;
+        let (base, quote) = if pool.base_is_token0 { (r0, r1) } else { (r1, r0) };
+        let base = base as f64 / 10f64.powi(pool.base_decimals as i32);
+        let quote = quote as f64 / 10f64.powi(pool.quote_decimals as i32);
+        if base <= 0.0 || quote <= 0.0 {
+            return Err(AdapterError::Validation { message: format!("empty pool {}", pool.address) });
+        }
+        Ok((base, quote))
+    }
+}
+
+#[async_trait::async_trait]
+impl<R: ChainRpc> VenueConnector for DexConnector<R> {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    async fn quote(&self, leg: &ArbitrageLeg, quantity: f64) -> AdapterResult<VenueQuote> {
+        let pool = self.pool(leg)?;
+        let (base, quote) = self.reserves(pool).await?;
+        let gas_price_wei = self.rpc.gas_price_wei().await?;
+        let notional = match leg.side {
+            // Quote tokens paid for `quantity` base
+            Side::Buy => amount_in(quantity, quote, base, pool.fee_bps).ok_or_else(|| AdapterError::Validation {
+                message: format!("{}: pool too shallow for {} {}", self.config.name, quantity, leg.symbol.as_str()),
+            })?,
+            // Quote tokens received for `quantity` base
+            Side::Sell => amount_out(quantity, base, quote, pool.fee_bps),
+        };
+        let mid = quote / base;
+        let price = notional / quantity;
+        Ok(VenueQuote {
+            price,
+            quantity,
+            price_impact_bps: ((price - mid) / mid).abs() * 10_000.0,
+            gas_price_gwei: Some(gas_price_wei as f64 / 1e9),
+        })
+    }
+
+    async fn place(&self, leg: &ArbitrageLeg, quantity: f64, quote: &VenueQuote) -> AdapterResult<VenueFill> {
+        let pool = self.pool(leg)?;
+        let base_unit = 10f64.powi(pool.base_decimals as i32);
+        let quote_unit = 10f64.powi(pool.quote_decimals as i32);
+        let tolerance = 1.0 - self.config.slippage_tolerance_bps as f64 / 10_000.0;
+        let notional = quote.price * quantity;
+        let (amount_in, min_amount_out) = match leg.side {
+            // Spend up to the quoted notional plus tolerance for at least `quantity`
+            Side::Buy => (notional / tolerance * quote_unit, quantity * base_unit),
+            Side::Sell => (quantity * base_unit, notional * tolerance * quote_unit),
+        };
+        let swap = SwapRequest {
+            pool: pool.address.clone(),
+            side: leg.side,
+            amount_in: amount_in as u128,
+            min_amount_out: min_amount_out as u128,
+            deadline: chrono::Utc::now().timestamp().max(0) as u64 + self.config.deadline_secs,
+            gas_price_wei: (quote.gas_price_gwei.unwrap_or_default() * 1e9) as u128,
+        };
+        let tx_hash = self.rpc.send_swap(&swap).await?;
+        tracing::info!("{} swap {:?} {} {} sent: {}", self.config.name, leg.side, quantity, leg.symbol.as_str(), tx_hash);
+        Ok(VenueFill { order_id: tx_hash, price: quote.price, quantity })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constant_product_math() {
+        // 1000 ETH / 2,000,000 USDC, 0.3% fee
+        let out = amount_out(1.0, 1_000.0, 2_000_000.0, 30);
+        assert!((out - 1_992.013).abs() < 1e-3);
+        let paid = amount_in(1.0, 2_000_000.0, 1_000.0, 30).unwrap();
+        assert!((paid - 2_008.026).abs() < 1e-3);
+        assert!(amount_in(1_000.0, 2_000_000.0, 1_000.0, 30).is_none());
+        assert_eq!(parse_hex_u128("0x0").unwrap(), 0);
+        assert_eq!(parse_hex_u128(&format!("0x{:064x}", 1_234_567u64)).unwrap(), 1_234_567);
+    }
+}
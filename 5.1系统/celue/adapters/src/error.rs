@@ -22,6 +22,9 @@ pub enum AdapterError {
     #[error("Validation error: {message}")]
     Validation { message: String },
     
+    #[error("Exchange error from {exchange} ({code}): {message}")]
+    Exchange { exchange: String, code: String, message: String },
+    
     #[error("Timeout error: operation timed out after {duration_ms}ms")]
     Timeout { duration_ms: u64 },
     
@@ -45,3 +48,26 @@ pub enum AdapterError {
 }
 
 pub type AdapterResult<T> = Result<T, AdapterError>;
+
+impl From<AdapterError> for common::SystemError {
+    fn from(error: AdapterError) -> Self {
+        use common::{DataError, ExecutionError, StorageError, SystemError};
+        match error {
+            AdapterError::Configuration(message) => SystemError::Configuration(message),
+            AdapterError::Validation { message } => SystemError::InvalidRequest(message),
+            AdapterError::Exchange { exchange, code, message } => {
+                ExecutionError::Exchange { exchange, code, message }.into()
+            }
+            AdapterError::Timeout { duration_ms } => ExecutionError::Timeout { timeout_ms: duration_ms }.into(),
+            AdapterError::Serialization(e) => DataError::Malformed(e.to_string()).into(),
+            AdapterError::Io(e) => StorageError::Backend(e.to_string()).into(),
+            e @ (AdapterError::NotInitialized
+            | AdapterError::Connection(_)
+            | AdapterError::NatsPublish(_)
+            | AdapterError::NatsRequest(_)
+            | AdapterError::NatsSubscribe(_)
+            | AdapterError::Nats(_)) => SystemError::Unavailable(e.to_string()),
+            e => SystemError::Internal(e.to_string()),
+        }
+    }
+}
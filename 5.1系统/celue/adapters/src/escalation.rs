@@ -0,0 +1,349 @@
+//! Paging escalation for critical alerts
+//!
+//! Sits on top of `AlertManager` for unattended operation. Critical alerts
+//! matching an escalation rule (kill switch, prolonged exchange connection
+//! loss, daily loss breach) open an incident through PagerDuty or
+//! Opsgenie; the incident is resolved automatically when the condition
+//! clears and its ID is recorded in the alert history.
+
+use crate::alerting::{Alert, AlertManager, AlertSeverity};
+use crate::{AdapterError, AdapterResult};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info};
+
+/// Incident management backend
+#[async_trait::async_trait]
+pub trait IncidentProvider: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Open an incident and return its ID
+    async fn open(&self, alert: &Alert) -> AdapterResult<String>;
+
+    /// Resolve a previously opened incident
+    async fn resolve(&self, incident_id: &str) -> AdapterResult<()>;
+}
+
+fn http_client() -> Client {
+    Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .expect("Failed to create HTTP client")
+}
+
+/// PagerDuty Events API v2
+pub struct PagerDutyProvider {
+    routing_key: String,
+    client: Client,
+}
+
+impl PagerDutyProvider {
+    const EVENTS_URL: &'static str = "https://events.pagerduty.com/v2/enqueue";
+
+    pub fn new(routing_key: &str) -> Self {
+        Self { routing_key: routing_key.to_string(), client: http_client() }
+    }
+
+    async fn enqueue(&self, body: serde_json::Value) -> AdapterResult<serde_json::Value> {
+        let response = self
+            .client
+            .post(Self::EVENTS_URL)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| AdapterError::Connection(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(AdapterError::Connection(format!("PagerDuty returned {}", response.status())));
+        }
+        response.json().await.map_err(|e| AdapterError::Connection(e.to_string()))
+    }
+}
+
+#[async_trait::async_trait]
+impl IncidentProvider for PagerDutyProvider {
+    fn name(&self) -> &str {
+        "pagerduty"
+    }
+
+    async fn open(&self, alert: &Alert) -> AdapterResult<String> {
+        let body = serde_json::json!({
+            "routing_key": self.routing_key,
+            "event_action": "trigger",
+            "dedup_key": alert.key,
+            "payload": {
+                "summary": format!("{}: {}", alert.title, alert.message),
+                "source": alert.source,
+                "severity": "critical",
+                "timestamp": alert.timestamp.to_rfc3339(),
+            },
+        });
+        let response = self.enqueue(body).await?;
+        // The dedup key identifies the incident for later resolution
+        Ok(response["dedup_key"].as_str().unwrap_or(&alert.key).to_string())
+    }
+
+    async fn resolve(&self, incident_id: &str) -> AdapterResult<()> {
+        let body = serde_json::json!({
+            "routing_key": self.routing_key,
+            "event_action": "resolve",
+            "dedup_key": incident_id,
+        });
+        self.enqueue(body).await.map(|_| ())
+    }
+}
+
+/// Opsgenie Alert API v2
+pub struct OpsgenieProvider {
+    api_key: String,
+    base_url: String,
+    client: Client,
+}
+
+impl OpsgenieProvider {
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+            base_url: "https://api.opsgenie.com".to_string(),
+            client: http_client(),
+        }
+    }
+
+    /// Use the EU instance or a proxy
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    async fn post(&self, path: &str, body: serde_json::Value) -> AdapterResult<()> {
+        let response = self
+            .client
+            .post(format!("{}{}", self.base_url, path))
+            .header("Authorization", format!("GenieKey {}", self.api_key))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| AdapterError::Connection(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(AdapterError::Connection(format!("Opsgenie returned {}", response.status())));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl IncidentProvider for OpsgenieProvider {
+    fn name(&self) -> &str {
+        "opsgenie"
+    }
+
+    async fn open(&self, alert: &Alert) -> AdapterResult<String> {
+        // Alert alias doubles as the incident ID so the close call needs no lookup
+        let body = serde_json::json!({
+            "message": alert.title,
+            "alias": alert.key,
+            "description": alert.message,
+            "source": alert.source,
+            "priority": "P1",
+        });
+        self.post("/v2/alerts", body).await?;
+        Ok(alert.key.clone())
+    }
+
+    async fn resolve(&self, incident_id: &str) -> AdapterResult<()> {
+        let path = format!("/v2/alerts/{}/close?identifierType=alias", incident_id);
+        self.post(&path, serde_json::json!({ "source": "celue" })).await
+    }
+}
+
+/// When a critical alert should page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscalationRule {
+    /// Alert key prefix, e.g. "kill_switch" or "exchange_disconnected"
+    pub key_prefix: String,
+    /// How long the condition must persist before paging (0 = immediately)
+    pub min_duration_ms: u64,
+}
+
+/// Escalation policy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscalationPolicy {
+    pub rules: Vec<EscalationRule>,
+}
+
+impl Default for EscalationPolicy {
+    fn default() -> Self {
+        let disconnect_minutes: u64 = std::env::var("CELUE_PAGE_DISCONNECT_MINUTES")
+            .ok().and_then(|s| s.parse().ok())
+            .unwrap_or(5);
+        Self {
+            rules: vec![
+                EscalationRule { key_prefix: "kill_switch".to_string(), min_duration_ms: 0 },
+                EscalationRule {
+                    key_prefix: "exchange_disconnected".to_string(),
+                    min_duration_ms: disconnect_minutes * 60_000,
+                },
+                EscalationRule { key_prefix: "daily_loss_breach".to_string(), min_duration_ms: 0 },
+            ],
+        }
+    }
+}
+
+impl EscalationPolicy {
+    fn rule_for(&self, key: &str) -> Option<&EscalationRule> {
+        self.rules.iter().find(|r| key.starts_with(&r.key_prefix))
+    }
+}
+
+/// State of one escalating condition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscalationState {
+    pub alert: Alert,
+    pub first_seen: DateTime<Utc>,
+    pub incident_id: Option<String>,
+}
+
+/// Escalation engine
+pub struct EscalationEngine {
+    policy: EscalationPolicy,
+    alerts: Arc<AlertManager>,
+    provider: Arc<dyn IncidentProvider>,
+    conditions: Mutex<HashMap<String, EscalationState>>,
+}
+
+impl EscalationEngine {
+    pub fn new(policy: EscalationPolicy, alerts: Arc<AlertManager>, provider: Arc<dyn IncidentProvider>) -> Self {
+        Self {
+            policy,
+            alerts,
+            provider,
+            conditions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Raise through the alert manager and page if an escalation rule matches
+    pub async fn raise(&self, alert: Alert) {
+        let escalates = alert.severity == AlertSeverity::Critical && self.policy.rule_for(&alert.key).is_some();
+        if escalates {
+            self.conditions
+                .lock()
+                .entry(alert.key.clone())
+                .or_insert_with(|| EscalationState { alert: alert.clone(), first_seen: alert.timestamp, incident_id: None });
+        }
+        let now = alert.timestamp;
+        self.alerts.raise(alert).await;
+        if escalates {
+            self.tick(now).await;
+        }
+    }
+
+    /// Open incidents for conditions that have persisted long enough
+    pub async fn tick(&self, now: DateTime<Utc>) {
+        let due: Vec<Alert> = self
+            .conditions
+            .lock()
+            .values()
+            .filter(|s| s.incident_id.is_none())
+            .filter(|s| {
+                let min = self.policy.rule_for(&s.alert.key).map(|r| r.min_duration_ms).unwrap_or(0);
+                (now - s.first_seen).num_milliseconds() >= min as i64
+            })
+            .map(|s| s.alert.clone())
+            .collect();
+
+        for alert in due {
+            match self.provider.open(&alert).await {
+                Ok(incident_id) => {
+                    info!("📟 Opened {} incident {} for {}", self.provider.name(), incident_id, alert.key);
+                    self.alerts.record_incident(&alert.key, &incident_id);
+                    if let Some(state) = self.conditions.lock().get_mut(&alert.key) {
+                        state.incident_id = Some(incident_id);
+                    }
+                }
+                Err(e) => error!("Failed to open {} incident for {}: {}", self.provider.name(), alert.key, e),
+            }
+        }
+    }
+
+    /// Condition cleared: forget it and resolve its incident if one was opened
+    pub async fn clear(&self, key: &str) -> AdapterResult<()> {
+        let Some(state) = self.conditions.lock().remove(key) else { return Ok(()) };
+        if let Some(incident_id) = state.incident_id {
+            self.provider.resolve(&incident_id).await?;
+            info!("✅ Resolved {} incident {} for {}", self.provider.name(), incident_id, key);
+        }
+        Ok(())
+    }
+
+    pub fn open_conditions(&self) -> Vec<EscalationState> {
+        self.conditions.lock().values().cloned().collect()
+    }
+
+    /// Periodically escalate persisting conditions
+    pub fn spawn(self: Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.tick(Utc::now()).await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerting::AlertConfig;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct RecordingProvider {
+        opened: AtomicUsize,
+        resolved: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl IncidentProvider for RecordingProvider {
+        fn name(&self) -> &str {
+            "test"
+        }
+
+        async fn open(&self, alert: &Alert) -> AdapterResult<String> {
+            self.opened.fetch_add(1, Ordering::SeqCst);
+            Ok(format!("INC-{}", alert.key))
+        }
+
+        async fn resolve(&self, _incident_id: &str) -> AdapterResult<()> {
+            self.resolved.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_pages_after_grace_period_and_resolves() {
+        let alerts = Arc::new(AlertManager::new(AlertConfig::default()));
+        let provider = Arc::new(RecordingProvider::default());
+        let policy = EscalationPolicy {
+            rules: vec![EscalationRule { key_prefix: "exchange_disconnected".into(), min_duration_ms: 300_000 }],
+        };
+        let engine = EscalationEngine::new(policy, alerts.clone(), provider.clone());
+
+        let alert = Alert::new("exchange_disconnected:okx", AlertSeverity::Critical, "okx down", "ws closed", "test");
+        let raised_at = alert.timestamp;
+        engine.raise(alert).await;
+        assert_eq!(provider.opened.load(Ordering::SeqCst), 0);
+
+        engine.tick(raised_at + chrono::Duration::minutes(6)).await;
+        assert_eq!(provider.opened.load(Ordering::SeqCst), 1);
+        assert_eq!(alerts.history(1)[0].incident_id.as_deref(), Some("INC-exchange_disconnected:okx"));
+
+        engine.clear("exchange_disconnected:okx").await.unwrap();
+        assert_eq!(provider.resolved.load(Ordering::SeqCst), 1);
+        assert!(engine.open_conditions().is_empty());
+    }
+}
@@ -1,19 +1,59 @@
 //! Execution adapter for order placement
 
 use crate::{Adapter, AdapterError, AdapterResult};
-use common::{ArbitrageOpportunity, ExecutionResult};
+use crate::jitter::{ExecutionJitter, JitterConfig};
+use crate::halt::HaltRegistry;
+use crate::trading_calendar::TradingCalendar;
+use crate::venue_selector::VenueSelector;
+use crate::order_books::OrderBookManager;
+use crate::staleness::{StalenessGuard, StalenessStats};
+use crate::execution_policy::{ExecutionPolicy, ExecutionPolicyConfig, LegPolicyInput};
+use crate::slicing::{SliceExecutor, SlicingConfig, SlicingEngine};
+use crate::trading_mode::{TradingMode, TradingModeController};
+use crate::microstructure::{MicrostructureMonitor, TimingDecision};
+use crate::latency_heatmap::{LatencyOperation, LatencyRecorder};
+use crate::venue::{VenueRegistry, VenueSliceExecutor};
+use crate::symbol_controls::SymbolControls;
+use crate::slippage_guard::SlippageGuard;
+use crate::markout::MarkoutEngine;
+use common::symbols::SymbolRegistry;
+use common::{ArbitrageLeg, ArbitrageOpportunity, ExecutionResult, FixedPrice, FixedQuantity, LegExecutionMode, SharedClock};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 
+/// Opportunity tag carrying the leader's fencing token
+pub const FENCING_TOKEN_TAG: &str = "fencing_token";
+
+/// Confirms that this node still holds the lease behind a fencing token
+pub trait FencingValidator: Send + Sync {
+    fn validate(&self, token: u64) -> bool;
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionConfig {
     pub exchanges: HashMap<String, ExchangeCredentials>,
     pub timeout: std::time::Duration,
     pub retry_count: u32,
+    /// Order timing/size jitter
+    #[serde(default)]
+    pub jitter: JitterConfig,
+    /// Maximum age of the oldest source quote at claim time (0 disables)
+    #[serde(default = "default_max_quote_staleness_ms")]
+    pub max_quote_staleness_ms: u64,
+    /// Maker/taker selection per leg
+    #[serde(default)]
+    pub policy: ExecutionPolicyConfig,
+    /// TWAP/iceberg slicing of large legs
+    #[serde(default)]
+    pub slicing: SlicingConfig,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+fn default_max_quote_staleness_ms() -> u64 {
+    500
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ExchangeCredentials {
     pub api_key: String,
     pub api_secret: String,
@@ -27,6 +67,10 @@ impl Default for ExecutionConfig {
             exchanges: HashMap::new(),
             timeout: std::time::Duration::from_secs(5),
             retry_count: 3,
+            jitter: JitterConfig::default(),
+            max_quote_staleness_ms: default_max_quote_staleness_ms(),
+            policy: ExecutionPolicyConfig::default(),
+            slicing: SlicingConfig::default(),
         }
     }
 }
@@ -39,6 +83,24 @@ pub trait OrderExecutor: Send + Sync {
 pub struct ExecutionAdapter {
     config: Option<ExecutionConfig>,
     running: Arc<parking_lot::Mutex<bool>>,
+    jitter: Arc<ExecutionJitter>,
+    staleness: Arc<StalenessGuard>,
+    policy: Arc<ExecutionPolicy>,
+    slicing: Arc<SlicingEngine>,
+    halts: Option<Arc<HaltRegistry>>,
+    symbols: Option<Arc<SymbolRegistry>>,
+    calendar: Option<Arc<TradingCalendar>>,
+    venue_selector: Option<Arc<VenueSelector>>,
+    order_books: Option<Arc<OrderBookManager>>,
+    trading_mode: Arc<TradingModeController>,
+    microstructure: Option<Arc<MicrostructureMonitor>>,
+    latency: Option<Arc<LatencyRecorder>>,
+    venues: Option<Arc<VenueRegistry>>,
+    symbol_controls: Option<Arc<SymbolControls>>,
+    slippage_guard: Option<Arc<SlippageGuard>>,
+    markout: Option<Arc<MarkoutEngine>>,
+    fencing: Option<Arc<dyn FencingValidator>>,
+    clock: SharedClock,
 }
 
 impl ExecutionAdapter {
@@ -46,21 +108,424 @@ impl ExecutionAdapter {
         Self {
             config: None,
             running: Arc::new(parking_lot::Mutex::new(false)),
+            jitter: Arc::new(ExecutionJitter::default()),
+            staleness: Arc::new(StalenessGuard::new(default_max_quote_staleness_ms())),
+            policy: Arc::new(ExecutionPolicy::default()),
+            slicing: Arc::new(SlicingEngine::default()),
+            halts: None,
+            symbols: None,
+            calendar: None,
+            venue_selector: None,
+            order_books: None,
+            trading_mode: Arc::new(TradingModeController::default()),
+            microstructure: None,
+            latency: None,
+            venues: None,
+            symbol_controls: None,
+            slippage_guard: None,
+            markout: None,
+            fencing: None,
+            clock: common::clock::system_clock(),
         }
     }
+
+    /// Time source for trading-calendar and quote-staleness checks, e.g. a
+    /// simulated clock during replays
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Require a valid fencing token (tag `fencing_token`) and re-check it
+    /// before every child order, so a node that lost leadership mid-execution
+    /// stops placing orders
+    pub fn with_fencing(mut self, fencing: Arc<dyn FencingValidator>) -> Self {
+        self.fencing = Some(fencing);
+        self
+    }
+
+    /// Delay or skip opportunities when microstructure signals indicate
+    /// adverse selection
+    pub fn with_microstructure(mut self, monitor: Arc<MicrostructureMonitor>) -> Self {
+        self.microstructure = Some(monitor);
+        self
+    }
+
+    /// Share the system-wide trading mode; without one the adapter runs dry
+    pub fn with_trading_mode(mut self, trading_mode: Arc<TradingModeController>) -> Self {
+        self.trading_mode = trading_mode;
+        self
+    }
+
+    /// Refuse opportunities covered by an active scoped halt
+    pub fn with_halts(mut self, halts: Arc<HaltRegistry>) -> Self {
+        self.halts = Some(halts);
+        self
+    }
+
+    /// Refuse opportunities with a leg on a symbol disabled for execution
+    pub fn with_symbol_controls(mut self, controls: Arc<SymbolControls>) -> Self {
+        self.symbol_controls = Some(controls);
+        self
+    }
+
+    /// Refuse opportunities touching a venue in a maintenance or settlement window
+    pub fn with_trading_calendar(mut self, calendar: Arc<TradingCalendar>) -> Self {
+        self.calendar = Some(calendar);
+        self
+    }
+
+    /// Feed per-leg latency and fill outcomes to the venue selector
+    pub fn with_venue_selector(mut self, selector: Arc<VenueSelector>) -> Self {
+        self.venue_selector = Some(selector);
+        self
+    }
+
+    /// Record per-leg order latency for the latency heat map
+    pub fn with_latency_recorder(mut self, latency: Arc<LatencyRecorder>) -> Self {
+        self.latency = Some(latency);
+        self
+    }
+
+    /// Route legs on registered venues (e.g. on-chain pools) through their
+    /// connector and settlement model
+    pub fn with_venues(mut self, venues: Arc<VenueRegistry>) -> Self {
+        self.venues = Some(venues);
+        self
+    }
+
+    /// Take top-of-book depth from maintained order books when the caller
+    /// supplies none
+    pub fn with_order_books(mut self, books: Arc<OrderBookManager>) -> Self {
+        self.order_books = Some(books);
+        self
+    }
+
+    /// Round legs to each venue's tick/lot size and refuse orders below
+    /// the lot size or minimum notional
+    pub fn with_symbol_registry(mut self, symbols: Arc<SymbolRegistry>) -> Self {
+        self.symbols = Some(symbols);
+        self
+    }
+
+    /// Report realized vs estimated slippage of every execution so a
+    /// strategy whose fills keep breaching its estimate is paused
+    pub fn with_slippage_guard(mut self, guard: Arc<SlippageGuard>) -> Self {
+        self.slippage_guard = Some(guard);
+        self
+    }
+
+    /// Mark out every fill against the consolidated mid
+    pub fn with_markout(mut self, markout: Arc<MarkoutEngine>) -> Self {
+        self.markout = Some(markout);
+        self
+    }
+
+    /// Staleness-rejection statistics per strategy
+    /// Exchanges this adapter can place orders on: configured credentials
+    /// and registered venues
+    pub fn exchanges(&self) -> Vec<String> {
+        let mut exchanges: Vec<String> = self
+            .config
+            .iter()
+            .flat_map(|c| c.exchanges.keys().map(|e| e.to_lowercase()))
+            .chain(self.venues.iter().flat_map(|v| v.exchanges()))
+            .collect();
+        exchanges.sort();
+        exchanges.dedup();
+        exchanges
+    }
+
+    /// Cancel every open order on `exchange`: resting dry-run orders on the
+    /// shadow engine and orders placed through a registered venue
+    pub async fn cancel_all_orders(&self, exchange: &str) -> AdapterResult<usize> {
+        // Dry-run books are keyed "exchange:symbol"
+        let mut cancelled = self
+            .trading_mode
+            .shadow_engine()
+            .cancel_where(|book| book.split_once(':').is_some_and(|(e, _)| e.eq_ignore_ascii_case(exchange)));
+        if let Some(venue) = self.venues.as_ref().and_then(|v| v.get(exchange)) {
+            cancelled += venue.cancel_all().await?;
+        }
+        if cancelled > 0 {
+            tracing::info!("Cancelled {} open orders on {}", cancelled, exchange);
+        }
+        Ok(cancelled)
+    }
+
+    pub fn staleness_stats(&self) -> HashMap<String, StalenessStats> {
+        self.staleness.all_stats()
+    }
+
+    /// Seed of the jitter RNG used by this adapter
+    pub fn jitter_seed(&self) -> u64 {
+        self.jitter.seed()
+    }
     
     pub async fn execute(&self, opportunity: &ArbitrageOpportunity) -> AdapterResult<ExecutionResult> {
-        // Mock execution for now
-        let order_ids = vec![
-            format!("order_{}", uuid::Uuid::new_v4()),
-            format!("order_{}", uuid::Uuid::new_v4()),
-        ];
+        let top_depths: Vec<Option<f64>> = match &self.order_books {
+            Some(books) => opportunity
+                .legs
+                .iter()
+                .map(|l| books.passive_top_quantity(l.exchange.as_str(), l.symbol.as_str(), l.side))
+                .collect(),
+            None => Vec::new(),
+        };
+        self.execute_with_depth(opportunity, &top_depths).await
+    }
+
+    /// Execute with the passive-side top-of-book quantity for each leg
+    /// (in leg order), used by the maker/taker policy and order slicing.
+    pub async fn execute_with_depth(
+        &self,
+        opportunity: &ArbitrageOpportunity,
+        top_depths: &[Option<f64>],
+    ) -> AdapterResult<ExecutionResult> {
+        let fence = match &self.fencing {
+            Some(fencing) => {
+                let token = opportunity.tags.get(FENCING_TOKEN_TAG).and_then(|t| t.parse::<u64>().ok());
+                match token.filter(|t| fencing.validate(*t)) {
+                    Some(token) => Some((fencing.clone(), token)),
+                    None => {
+                        tracing::warn!("Refusing opportunity {}: no valid fencing token", opportunity.id);
+                        return Ok(ExecutionResult::rejected(
+                            opportunity.id.to_string(),
+                            "fencing token missing or expired".to_string(),
+                            None,
+                        ));
+                    }
+                }
+            }
+            None => None,
+        };
+
+        if let Some(halt) = self.halts.as_ref().and_then(|h| h.blocking_halt(opportunity)) {
+            tracing::warn!(
+                "Refusing opportunity {} from {}: halted ({:?}: {})",
+                opportunity.id, opportunity.strategy_name, halt.scope, halt.reason
+            );
+            return Ok(ExecutionResult::rejected(
+                opportunity.id.to_string(),
+                format!("halted: {:?}", halt.scope),
+                None,
+            ));
+        }
+
+        if let Some(control) = self.symbol_controls.as_ref().and_then(|c| c.blocking_control(opportunity)) {
+            tracing::warn!(
+                "Refusing opportunity {} from {}: {} disabled ({})",
+                opportunity.id, opportunity.strategy_name, control.key.symbol, control.reason
+            );
+            return Ok(ExecutionResult::rejected(
+                opportunity.id.to_string(),
+                format!("symbol disabled: {}", control.key.symbol),
+                None,
+            ));
+        }
+
+        if let Some(window) = self.calendar.as_ref().and_then(|c| c.blocking_window_for(opportunity, self.clock.now())) {
+            tracing::warn!(
+                "Refusing opportunity {} from {}: {} in {:?} window ({})",
+                opportunity.id, opportunity.strategy_name, window.exchange, window.kind, window.reason
+            );
+            return Ok(ExecutionResult::rejected(
+                opportunity.id.to_string(),
+                format!("{:?} window on {}", window.kind, window.exchange),
+                None,
+            ));
+        }
+
+        let now_ns = self.clock.now_ns();
+        if let Err(age_ms) = self.staleness.try_claim(opportunity, now_ns) {
+            tracing::warn!(
+                "Refusing stale opportunity {} from {}: quote age {}ms > {}ms",
+                opportunity.id, opportunity.strategy_name, age_ms, self.staleness.max_quote_staleness_ms()
+            );
+            return Ok(ExecutionResult::rejected(
+                opportunity.id.to_string(),
+                format!("stale quotes: {}ms", age_ms),
+                None,
+            ));
+        }
+
+        // Validate every leg before the first order goes out
+        if let Some(symbols) = &self.symbols {
+            for leg in &opportunity.legs {
+                if let Err(e) = symbols.prepare_order(
+                    leg.exchange.as_str(),
+                    leg.symbol.as_str(),
+                    leg.price.to_f64(),
+                    leg.quantity.to_f64(),
+                ) {
+                    tracing::warn!("Refusing opportunity {}: {}", opportunity.id, e);
+                    return Ok(ExecutionResult::rejected(opportunity.id.to_string(), e.to_string(), None));
+                }
+            }
+        }
+
+        if let Some(monitor) = &self.microstructure {
+            let mut decision = monitor.assess(opportunity);
+            // A delayed opportunity gets one re-check after the wait
+            if let TimingDecision::Delay { delay_ms, reason } = &decision {
+                tracing::debug!("Delaying opportunity {} by {}ms: {}", opportunity.id, delay_ms, reason);
+                tokio::time::sleep(std::time::Duration::from_millis(*delay_ms)).await;
+                decision = monitor.assess(opportunity);
+            }
+            match decision {
+                TimingDecision::Proceed => {}
+                TimingDecision::Delay { reason, .. } | TimingDecision::Skip { reason } => {
+                    tracing::warn!("Skipping opportunity {}: {}", opportunity.id, reason);
+                    return Ok(ExecutionResult::rejected(
+                        opportunity.id.to_string(),
+                        format!("adverse selection: {}", reason),
+                        None,
+                    ));
+                }
+            }
+        }
+
+        match self.trading_mode.mode() {
+            TradingMode::DryRun => return self.trading_mode.route_to_shadow(opportunity),
+            TradingMode::Shadow => {
+                let exchanges = self.config.as_ref().map(|c| &c.exchanges);
+                for leg in &opportunity.legs {
+                    let exchange = leg.exchange.as_str();
+                    let credentials = exchanges.and_then(|e| e.get(exchange));
+                    if let Err(e) = self.trading_mode.check_exchange(exchange, credentials) {
+                        tracing::warn!("Refusing opportunity {}: {}", opportunity.id, e);
+                        return Ok(ExecutionResult::rejected(opportunity.id.to_string(), e.to_string(), None));
+                    }
+                }
+            }
+            TradingMode::Live => {}
+        }
+
+        let expires_at_ns = opportunity.created_at_ns.saturating_add(opportunity.ttl_ns);
+        let time_to_expiry_ms = expires_at_ns.saturating_sub(now_ns) / 1_000_000;
+
+        let mut sized_legs = Vec::with_capacity(opportunity.legs.len());
+        let mut leg_fills = Vec::with_capacity(opportunity.legs.len());
+        let mut leg_modes = Vec::with_capacity(opportunity.legs.len());
+        for (i, leg) in opportunity.legs.iter().enumerate() {
+            let top_depth = top_depths.get(i).copied().flatten();
+            let mode = self.policy.choose(leg, LegPolicyInput {
+                time_to_expiry_ms,
+                top_depth,
+            });
+
+            let delay = self.jitter.next_delay();
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
+            }
+            // Size stays within the risk-approved quantity and never jitters
+            // below one lot
+            let spec = self.symbols.as_ref().and_then(|s| s.spec(leg.exchange.as_str(), leg.symbol.as_str()));
+            let mut sized_leg = leg.clone();
+            sized_leg.quantity = self.jitter.jitter_quantity(leg.quantity, spec.as_ref().map_or(0.0, |s| s.lot_size));
+            if let Some(spec) = spec {
+                sized_leg.price = FixedPrice::from_f64(spec.round_price(leg.price.to_f64()), leg.price.scale());
+                sized_leg.quantity = FixedQuantity::from_f64(
+                    spec.round_quantity(sized_leg.quantity.to_f64()),
+                    sized_leg.quantity.scale(),
+                );
+            }
+
+            // Mock execution for now: maker orders are assumed to fill
+            // immediately; a real venue fill check plugs into the policy's
+            // timeout-and-cross fallback here.
+            let mode = match mode {
+                LegExecutionMode::Maker => {
+                    self.policy.await_maker_fill(async { true }, || async {}).await
+                }
+                other => other,
+            };
+            let leg_started = std::time::Instant::now();
+            let fills = match &self.venues {
+                Some(venues) => {
+                    let executor = FencedSliceExecutor {
+                        inner: VenueSliceExecutor::new(venues.clone(), MockSliceExecutor),
+                        fence: fence.clone(),
+                    };
+                    self.slicing.execute_leg(i, &sized_leg, top_depth, &executor).await
+                }
+                None => {
+                    let executor = FencedSliceExecutor { inner: MockSliceExecutor, fence: fence.clone() };
+                    self.slicing.execute_leg(i, &sized_leg, top_depth, &executor).await
+                }
+            };
+            let latency_ms = leg_started.elapsed().as_secs_f64() * 1000.0;
+            if let Some(selector) = &self.venue_selector {
+                selector.record_execution(leg.exchange.as_str(), latency_ms, fills.is_ok());
+            }
+            if let Some(latency) = &self.latency {
+                latency.record(leg.exchange.as_str(), LatencyOperation::RestOrder, latency_ms);
+            }
+            leg_fills.push(fills?);
+            sized_legs.push(sized_leg);
+            leg_modes.push(mode);
+        }
         
-        Ok(ExecutionResult::accepted(
-            opportunity.id.to_string(),
-            order_ids,
-            None,
-        ))
+        let mut result = self.slicing.aggregate(opportunity.id.to_string(), &sized_legs, leg_fills);
+        result.leg_modes = leg_modes;
+        if self.jitter.is_enabled() {
+            result.details = format!("{} (jitter_seed={})", result.details, self.jitter.seed());
+        }
+        if let Some(guard) = &self.slippage_guard {
+            guard.record_execution(opportunity, &result).await;
+        }
+        if let Some(markout) = &self.markout {
+            markout.record_execution(opportunity, &result);
+        }
+        Ok(result)
+    }
+}
+
+/// Fills every child order at the leg's quoted price
+struct MockSliceExecutor;
+
+#[async_trait::async_trait]
+impl SliceExecutor for MockSliceExecutor {
+    async fn execute_slice(&self, leg: &ArbitrageLeg, _quantity: f64) -> AdapterResult<(String, f64)> {
+        Ok((format!("order_{}", uuid::Uuid::new_v4()), leg.price.to_f64()))
+    }
+
+    fn batch_limit(&self, leg: &ArbitrageLeg) -> Option<usize> {
+        crate::batch_orders::max_batch_size(leg.exchange.as_str(), leg.market)
+    }
+}
+
+/// Checks the fencing token before every order it forwards
+struct FencedSliceExecutor<E> {
+    inner: E,
+    fence: Option<(Arc<dyn FencingValidator>, u64)>,
+}
+
+impl<E> FencedSliceExecutor<E> {
+    fn check(&self, leg: &ArbitrageLeg) -> AdapterResult<()> {
+        match &self.fence {
+            Some((fencing, token)) if !fencing.validate(*token) => {
+                tracing::warn!("Fencing token {} expired, not sending order to {}", token, leg.exchange.as_str());
+                Err(AdapterError::Generic { message: format!("fencing token {} no longer valid", token) })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<E: SliceExecutor> SliceExecutor for FencedSliceExecutor<E> {
+    async fn execute_slice(&self, leg: &ArbitrageLeg, quantity: f64) -> AdapterResult<(String, f64)> {
+        self.check(leg)?;
+        self.inner.execute_slice(leg, quantity).await
+    }
+
+    fn batch_limit(&self, leg: &ArbitrageLeg) -> Option<usize> {
+        self.inner.batch_limit(leg)
+    }
+
+    async fn execute_batch(&self, leg: &ArbitrageLeg, quantities: &[f64]) -> AdapterResult<Vec<AdapterResult<(String, f64)>>> {
+        self.check(leg)?;
+        self.inner.execute_batch(leg, quantities).await
     }
 }
 
@@ -70,6 +535,10 @@ impl Adapter for ExecutionAdapter {
     type Error = AdapterError;
     
     async fn initialize(&mut self, config: Self::Config) -> Result<(), Self::Error> {
+        self.jitter = Arc::new(ExecutionJitter::new(config.jitter.clone()));
+        self.staleness = Arc::new(StalenessGuard::new(config.max_quote_staleness_ms));
+        self.policy = Arc::new(ExecutionPolicy::new(config.policy.clone()));
+        self.slicing = Arc::new(SlicingEngine::new(config.slicing.clone()));
         self.config = Some(config);
         Ok(())
     }
@@ -101,3 +570,68 @@ impl Default for ExecutionAdapter {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::testing::inter_exchange_opportunity;
+    use common::SimulatedClock;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Lease that stays valid for a fixed number of checks, then expires
+    struct ExpiringLease {
+        token: u64,
+        checks_left: AtomicU32,
+    }
+
+    impl FencingValidator for ExpiringLease {
+        fn validate(&self, token: u64) -> bool {
+            token == self.token
+                && self.checks_left.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1)).is_ok()
+        }
+    }
+
+    fn live_adapter(lease: Arc<ExpiringLease>) -> ExecutionAdapter {
+        ExecutionAdapter::new()
+            .with_trading_mode(Arc::new(TradingModeController::new(TradingMode::Live)))
+            .with_fencing(lease)
+    }
+
+    #[tokio::test]
+    async fn test_fencing_token_is_checked_before_every_order() {
+        let mut opportunity = inter_exchange_opportunity("inter_exchange", "BTCUSDT", 0.01);
+
+        // Without a token nothing is sent
+        let lease = Arc::new(ExpiringLease { token: 7, checks_left: AtomicU32::new(10) });
+        let result = live_adapter(lease.clone()).execute(&opportunity).await.unwrap();
+        assert!(!result.success);
+        assert!(result.order_ids.is_empty());
+
+        opportunity.tags.insert(FENCING_TOKEN_TAG.to_string(), "7".to_string());
+        let result = live_adapter(lease).execute(&opportunity).await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.order_ids.len(), 2);
+
+        // The lease expires after the first leg went out: the second leg is never sent
+        let lease = Arc::new(ExpiringLease { token: 7, checks_left: AtomicU32::new(2) });
+        assert!(live_adapter(lease.clone()).execute(&opportunity).await.is_err());
+        assert_eq!(lease.checks_left.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_quote_staleness_follows_the_injected_clock() {
+        let quoted_ns = 1_700_000_000_000_000_000;
+        let opportunity = inter_exchange_opportunity("inter_exchange", "BTCUSDT", 0.01).with_quote_watermark([quoted_ns]);
+        let clock = Arc::new(SimulatedClock::from_nanos(quoted_ns + 100_000_000));
+        let adapter = ExecutionAdapter::new()
+            .with_trading_mode(Arc::new(TradingModeController::new(TradingMode::Live)))
+            .with_clock(clock.clone());
+        assert!(adapter.execute(&opportunity).await.unwrap().success);
+
+        // 1s after the quote the same opportunity is stale, regardless of wall time
+        clock.set_nanos(quoted_ns + 1_000_000_000);
+        let result = adapter.execute(&opportunity).await.unwrap();
+        assert!(!result.success);
+        assert!(result.details.starts_with("stale quotes"));
+    }
+}
@@ -0,0 +1,217 @@
+//! Per-leg maker/taker execution policy
+//!
+//! Chooses a passive limit (maker) or aggressive (taker) order for each
+//! leg based on top-of-book depth, time left before the opportunity
+//! expires and the maker/taker fee differential. Maker orders that do not
+//! fill within the timeout are cancelled and crossed as taker.
+
+use common::{ArbitrageLeg, LegExecutionMode};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+
+/// Maker/taker fee rates for an exchange, in basis points
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct FeeRates {
+    pub maker_bps: f64,
+    pub taker_bps: f64,
+}
+
+impl FeeRates {
+    /// Saving in bps from resting as maker instead of crossing
+    pub fn maker_saving_bps(&self) -> f64 {
+        self.taker_bps - self.maker_bps
+    }
+}
+
+/// Execution policy configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionPolicyConfig {
+    /// Allow maker orders at all
+    pub maker_enabled: bool,
+    /// Minimum fee saving (bps) that justifies a maker order
+    pub min_maker_saving_bps: f64,
+    /// Minimum time left before expiry to attempt a maker order (ms)
+    pub min_time_to_expiry_ms: u64,
+    /// Leg size must not exceed this multiple of the top-of-book quantity
+    pub max_size_to_top_depth: f64,
+    /// Time a maker order may rest before being crossed (ms)
+    pub maker_timeout_ms: u64,
+    /// Fee rates per exchange
+    pub fees: HashMap<String, FeeRates>,
+}
+
+impl Default for ExecutionPolicyConfig {
+    fn default() -> Self {
+        Self {
+            maker_enabled: false,
+            min_maker_saving_bps: 2.0,
+            min_time_to_expiry_ms: 100,
+            max_size_to_top_depth: 0.5,
+            maker_timeout_ms: 50,
+            fees: HashMap::new(),
+        }
+    }
+}
+
+/// Inputs for a single leg decision
+#[derive(Debug, Clone, Copy)]
+pub struct LegPolicyInput {
+    /// Time left before the opportunity expires (ms)
+    pub time_to_expiry_ms: u64,
+    /// Quantity available at the best level on the leg's passive side
+    pub top_depth: Option<f64>,
+}
+
+/// Maker/taker selection policy
+pub struct ExecutionPolicy {
+    config: ExecutionPolicyConfig,
+    /// Observed adverse selection per exchange (bps), from markout analysis
+    adverse_selection_bps: RwLock<HashMap<String, f64>>,
+}
+
+impl ExecutionPolicy {
+    pub fn new(config: ExecutionPolicyConfig) -> Self {
+        Self {
+            config,
+            adverse_selection_bps: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Update the adverse selection cost charged against maker orders on `exchange`
+    pub fn set_adverse_selection_bps(&self, exchange: &str, bps: f64) {
+        self.adverse_selection_bps.write().insert(exchange.to_string(), bps.max(0.0));
+    }
+
+    /// Maker order timeout
+    pub fn maker_timeout(&self) -> Duration {
+        Duration::from_millis(self.config.maker_timeout_ms)
+    }
+
+    /// Choose the execution mode for one leg
+    pub fn choose(&self, leg: &ArbitrageLeg, input: LegPolicyInput) -> LegExecutionMode {
+        if !self.config.maker_enabled {
+            return LegExecutionMode::Taker;
+        }
+
+        // Not enough time to rest and still cross on timeout
+        if input.time_to_expiry_ms < self.config.min_time_to_expiry_ms + self.config.maker_timeout_ms {
+            return LegExecutionMode::Taker;
+        }
+
+        let Some(fees) = self.config.fees.get(leg.exchange.as_str()) else {
+            return LegExecutionMode::Taker;
+        };
+        // Passive fills are adversely selected; the fee saving must cover it
+        let adverse_bps = self
+            .adverse_selection_bps
+            .read()
+            .get(leg.exchange.as_str())
+            .copied()
+            .unwrap_or(0.0);
+        if fees.maker_saving_bps() - adverse_bps < self.config.min_maker_saving_bps {
+            return LegExecutionMode::Taker;
+        }
+
+        // Large orders relative to the book are unlikely to fill passively
+        match input.top_depth {
+            Some(depth) if depth > 0.0 => {
+                if leg.quantity.to_f64() > depth * self.config.max_size_to_top_depth {
+                    return LegExecutionMode::Taker;
+                }
+            }
+            _ => return LegExecutionMode::Taker,
+        }
+
+        LegExecutionMode::Maker
+    }
+
+    /// Wait for a maker order to fill; on timeout run `cross` (cancel and
+    /// re-submit as taker) and report the fallback mode.
+    pub async fn await_maker_fill<F, C, CF>(&self, filled: F, cross: C) -> LegExecutionMode
+    where
+        F: Future<Output = bool>,
+        C: FnOnce() -> CF,
+        CF: Future<Output = ()>,
+    {
+        match tokio::time::timeout(self.maker_timeout(), filled).await {
+            Ok(true) => LegExecutionMode::Maker,
+            _ => {
+                cross().await;
+                LegExecutionMode::MakerFallbackTaker
+            }
+        }
+    }
+}
+
+impl Default for ExecutionPolicy {
+    fn default() -> Self {
+        Self::new(ExecutionPolicyConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::testing::leg;
+    use common::Side;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    fn policy() -> ExecutionPolicy {
+        ExecutionPolicy::new(ExecutionPolicyConfig {
+            maker_enabled: true,
+            fees: HashMap::from([("binance".to_string(), FeeRates { maker_bps: 2.0, taker_bps: 5.0 })]),
+            ..ExecutionPolicyConfig::default()
+        })
+    }
+
+    fn input(time_to_expiry_ms: u64, top_depth: f64) -> LegPolicyInput {
+        LegPolicyInput { time_to_expiry_ms, top_depth: Some(top_depth) }
+    }
+
+    #[test]
+    fn test_maker_only_when_every_condition_holds() {
+        let policy = policy();
+        let small = leg("binance", "BTCUSDT", Side::Buy, 100.0, 1.0);
+        assert_eq!(policy.choose(&small, input(1_000, 4.0)), LegExecutionMode::Maker);
+
+        // Disabled, short on time, unknown exchange, large order, empty book
+        assert_eq!(ExecutionPolicy::default().choose(&small, input(1_000, 4.0)), LegExecutionMode::Taker);
+        assert_eq!(policy.choose(&small, input(149, 4.0)), LegExecutionMode::Taker);
+        let elsewhere = leg("okx", "BTCUSDT", Side::Buy, 100.0, 1.0);
+        assert_eq!(policy.choose(&elsewhere, input(1_000, 4.0)), LegExecutionMode::Taker);
+        assert_eq!(policy.choose(&small, input(1_000, 1.5)), LegExecutionMode::Taker);
+        assert_eq!(
+            policy.choose(&small, LegPolicyInput { time_to_expiry_ms: 1_000, top_depth: None }),
+            LegExecutionMode::Taker
+        );
+    }
+
+    #[test]
+    fn test_adverse_selection_eats_the_fee_saving() {
+        let policy = policy();
+        let small = leg("binance", "BTCUSDT", Side::Sell, 100.0, 1.0);
+        policy.set_adverse_selection_bps("binance", 0.5);
+        assert_eq!(policy.choose(&small, input(1_000, 4.0)), LegExecutionMode::Maker);
+        // 3 bps saving - 1.5 bps adverse < 2 bps minimum
+        policy.set_adverse_selection_bps("binance", 1.5);
+        assert_eq!(policy.choose(&small, input(1_000, 4.0)), LegExecutionMode::Taker);
+    }
+
+    #[tokio::test]
+    async fn test_unfilled_maker_is_crossed_after_timeout() {
+        let policy = policy();
+        let crossed = AtomicBool::new(false);
+        let mode = policy.await_maker_fill(async { true }, || async { crossed.store(true, Ordering::SeqCst) }).await;
+        assert_eq!(mode, LegExecutionMode::Maker);
+        assert!(!crossed.load(Ordering::SeqCst));
+
+        let mode = policy
+            .await_maker_fill(std::future::pending::<bool>(), || async { crossed.store(true, Ordering::SeqCst) })
+            .await;
+        assert_eq!(mode, LegExecutionMode::MakerFallbackTaker);
+        assert!(crossed.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,244 @@
+//! Execution preview for manual approval
+//!
+//! Before an operator approves an opportunity from the dashboard, the
+//! previewer replays its legs against the current full-depth books on a
+//! private shadow matching engine: each level a taker order would sweep is
+//! fed as a price and matched with an immediate-or-cancel market order for
+//! the quantity resting there. Nothing is sent to an exchange and the shared
+//! dry-run engine is left untouched. The result lists the expected fills per
+//! leg, slippage against the quoted price, taker fees, and the net profit
+//! compared with what the opportunity was detected at.
+
+use crate::opportunity_ttl::OpportunityPool;
+use crate::order_books::OrderBookManager;
+use crate::order_matching::{OrderFlags, ShadowMatchingEngine, ShadowOrderType};
+use crate::{AdapterError, AdapterResult};
+use common::{ArbitrageOpportunity, Side};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreviewConfig {
+    /// Book levels considered per leg
+    pub depth_levels: usize,
+    /// Taker fee per exchange (bps)
+    pub taker_fee_bps: HashMap<String, f64>,
+    pub default_taker_fee_bps: f64,
+}
+
+impl Default for PreviewConfig {
+    fn default() -> Self {
+        Self { depth_levels: 50, taker_fee_bps: HashMap::new(), default_taker_fee_bps: 10.0 }
+    }
+}
+
+/// One simulated fill
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreviewFill {
+    pub price: f64,
+    pub quantity: f64,
+}
+
+/// Expected execution of one leg
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegPreview {
+    pub exchange: String,
+    pub symbol: String,
+    pub side: Side,
+    pub quantity: f64,
+    pub quoted_price: f64,
+    pub fills: Vec<PreviewFill>,
+    pub filled_quantity: f64,
+    /// Volume-weighted fill price, 0 without fills
+    pub average_price: f64,
+    /// Positive when the average price is worse than quoted
+    pub slippage_bps: f64,
+    pub fee: f64,
+}
+
+impl LegPreview {
+    pub fn fully_filled(&self) -> bool {
+        self.filled_quantity + 1e-12 >= self.quantity
+    }
+
+    fn notional(&self) -> f64 {
+        self.fills.iter().map(|f| f.price * f.quantity).sum()
+    }
+}
+
+/// Expected outcome of executing an opportunity now
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionPreview {
+    pub opportunity_id: Uuid,
+    pub strategy: String,
+    pub legs: Vec<LegPreview>,
+    pub fully_fillable: bool,
+    /// Sell proceeds minus buy cost at the simulated fills
+    pub expected_gross_profit: f64,
+    pub expected_fees: f64,
+    pub expected_net_profit: f64,
+    /// Net profit the opportunity was detected with
+    pub quoted_net_profit: f64,
+}
+
+/// Simulates opportunities against the current books
+pub struct ExecutionPreviewer {
+    config: PreviewConfig,
+    books: Arc<OrderBookManager>,
+    pool: Option<Arc<OpportunityPool>>,
+}
+
+impl ExecutionPreviewer {
+    pub fn new(config: PreviewConfig, books: Arc<OrderBookManager>) -> Self {
+        Self { config, books, pool: None }
+    }
+
+    /// Look opportunities up by id in the live pool
+    pub fn with_pool(mut self, pool: Arc<OpportunityPool>) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    /// Preview a live opportunity by id
+    pub fn preview_id(&self, id: &Uuid) -> AdapterResult<Option<ExecutionPreview>> {
+        let pool = self.pool.as_ref().ok_or_else(|| AdapterError::Configuration("no opportunity pool".to_string()))?;
+        pool.get(id).map(|opportunity| self.preview(&opportunity)).transpose()
+    }
+
+    pub fn preview(&self, opportunity: &ArbitrageOpportunity) -> AdapterResult<ExecutionPreview> {
+        let engine = ShadowMatchingEngine::new();
+        let legs = opportunity
+            .legs
+            .iter()
+            .map(|leg| {
+                self.preview_leg(&engine, leg.exchange.as_str(), leg.symbol.as_str(), leg.side, leg.quantity.to_f64(), leg.price.to_f64())
+            })
+            .collect::<AdapterResult<Vec<_>>>()?;
+
+        let expected_gross_profit = legs
+            .iter()
+            .map(|leg| match leg.side {
+                Side::Sell => leg.notional(),
+                Side::Buy => -leg.notional(),
+            })
+            .sum::<f64>();
+        let expected_fees = legs.iter().map(|l| l.fee).sum::<f64>();
+        Ok(ExecutionPreview {
+            opportunity_id: opportunity.id,
+            strategy: opportunity.strategy_name.clone(),
+            fully_fillable: legs.iter().all(LegPreview::fully_filled),
+            legs,
+            expected_gross_profit,
+            expected_fees,
+            expected_net_profit: expected_gross_profit - expected_fees,
+            quoted_net_profit: opportunity.net_profit.to_f64(),
+        })
+    }
+
+    fn preview_leg(
+        &self,
+        engine: &ShadowMatchingEngine,
+        exchange: &str,
+        symbol: &str,
+        side: Side,
+        quantity: f64,
+        quoted_price: f64,
+    ) -> AdapterResult<LegPreview> {
+        let book = self.books.book_depth(exchange, symbol, self.config.depth_levels).ok_or_else(|| {
+            AdapterError::Validation { message: format!("no synced book for {} {}", exchange, symbol) }
+        })?;
+        let (prices, quantities) = match side {
+            Side::Buy => (&book.ask_prices, &book.ask_quantities),
+            Side::Sell => (&book.bid_prices, &book.bid_quantities),
+        };
+
+        let name = format!("{}:{}", exchange, symbol);
+        let mut fills = Vec::new();
+        let mut remaining = quantity;
+        for (price, available) in prices.iter().zip(quantities) {
+            if remaining <= 1e-12 {
+                break;
+            }
+            let take = remaining.min(available.to_f64());
+            if take <= 0.0 {
+                continue;
+            }
+            engine.on_price(&name, price.to_f64());
+            let (id, executed) = engine.submit_with_flags(&name, side, take, ShadowOrderType::Market, OrderFlags::ioc())?;
+            for fill in executed.into_iter().filter(|f| f.order_id == id) {
+                remaining -= fill.quantity;
+                fills.push(PreviewFill { price: fill.price, quantity: fill.quantity });
+            }
+        }
+
+        let filled_quantity = quantity - remaining.max(0.0);
+        let notional: f64 = fills.iter().map(|f| f.price * f.quantity).sum();
+        let average_price = if filled_quantity > 0.0 { notional / filled_quantity } else { 0.0 };
+        let slippage_bps = if filled_quantity > 0.0 && quoted_price > 0.0 {
+            let adverse = match side {
+                Side::Buy => average_price - quoted_price,
+                Side::Sell => quoted_price - average_price,
+            };
+            adverse / quoted_price * 10_000.0
+        } else {
+            0.0
+        };
+        let fee_bps = self.config.taker_fee_bps.get(exchange).copied().unwrap_or(self.config.default_taker_fee_bps);
+        Ok(LegPreview {
+            exchange: exchange.to_string(),
+            symbol: symbol.to_string(),
+            side,
+            quantity,
+            quoted_price,
+            fills,
+            filled_quantity,
+            average_price,
+            slippage_bps,
+            fee: notional * fee_bps / 10_000.0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::testing::leg;
+    use crate::order_books::BookSnapshot;
+    use crate::opportunity_ttl::OpportunityTtlModel;
+    use common::precision::FixedPrice;
+
+    fn snapshot(exchange: &str, bids: Vec<(f64, f64)>, asks: Vec<(f64, f64)>) -> BookSnapshot {
+        BookSnapshot { exchange: exchange.into(), symbol: "BTCUSDT".into(), sequence: 1, bids, asks, timestamp_ns: 0 }
+    }
+
+    #[test]
+    fn test_preview_sweeps_depth_without_touching_pool() {
+        let books = Arc::new(OrderBookManager::new());
+        books.apply_snapshot(snapshot("binance", vec![(99.0, 5.0)], vec![(100.0, 1.0), (100.5, 1.0)]));
+        books.apply_snapshot(snapshot("okx", vec![(101.0, 3.0)], vec![(102.0, 5.0)]));
+        let pool = Arc::new(OpportunityPool::new(Arc::new(OpportunityTtlModel::default())));
+        let opportunity = pool.insert(ArbitrageOpportunity::new_inter_exchange(
+            "inter_exchange",
+            leg("binance", "BTCUSDT", Side::Buy, 100.0, 2.0),
+            leg("okx", "BTCUSDT", Side::Sell, 101.0, 2.0),
+            FixedPrice::from_f64(1.6, 2),
+            FixedPrice::from_f64(0.008, 6),
+            1_000_000_000,
+        ));
+        let config = PreviewConfig { taker_fee_bps: HashMap::from([("okx".to_string(), 0.0)]), ..Default::default() };
+        let previewer = ExecutionPreviewer::new(config, books).with_pool(pool.clone());
+
+        let preview = previewer.preview_id(&opportunity.id).unwrap().unwrap();
+        assert!(preview.fully_fillable);
+        let buy = &preview.legs[0];
+        assert_eq!(buy.fills.len(), 2);
+        assert!((buy.average_price - 100.25).abs() < 1e-9);
+        assert!((buy.slippage_bps - 25.0).abs() < 1e-6);
+        assert!((preview.expected_gross_profit - 1.5).abs() < 1e-9);
+        assert!((preview.expected_fees - 200.5 * 0.001).abs() < 1e-9);
+        assert!(pool.get(&opportunity.id).is_some());
+        assert!(previewer.preview_id(&Uuid::new_v4()).unwrap().is_none());
+    }
+}
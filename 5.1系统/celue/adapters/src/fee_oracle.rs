@@ -0,0 +1,390 @@
+//! Gas and network fee oracle for on-chain transfers
+//!
+//! Polls the current gas price of each configured chain (`eth_gasPrice` on
+//! EVM chains, the energy fee from `getchainparameters` on TRON) and turns
+//! it into the network cost of moving an asset: gas units for a native or
+//! token transfer times the gas price, converted to USD and to units of the
+//! asset being moved. Withdrawal networks in the rebalancing planner are
+//! matched by chain name or alias (`ERC20`, `TRC20`, `BEP20`), and a fresh
+//! estimate raises their configured withdrawal fee when gas is expensive.
+//! With a [`FeePredictor`] attached, estimates for an exchange use the gas
+//! forecast of the model that has been most accurate on its withdrawals.
+
+use crate::fee_prediction::{FeePredictor, ModelAccuracy, PredictionModel};
+use crate::{AdapterError, AdapterResult};
+use common::SharedClock;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChainKind {
+    /// Gas price in gwei, fee = gas units * gwei * 1e-9 native
+    Evm,
+    /// Energy price in sun, fee = energy * sun * 1e-6 TRX
+    Tron,
+}
+
+impl ChainKind {
+    /// Native tokens per gas price unit per gas unit
+    fn unit_scale(&self) -> f64 {
+        match self {
+            ChainKind::Evm => 1e-9,
+            ChainKind::Tron => 1e-6,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainFeeConfig {
+    pub chain: String,
+    pub kind: ChainKind,
+    pub rpc_url: String,
+    /// Gas token, e.g. ETH, TRX, BNB
+    pub native_asset: String,
+    /// Withdrawal network names that settle on this chain
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Gas (or energy) used by a native transfer
+    pub native_transfer_units: u64,
+    /// Gas (or energy) used by a token transfer
+    pub token_transfer_units: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeOracleConfig {
+    pub chains: Vec<ChainFeeConfig>,
+    pub poll_interval_secs: u64,
+    /// Gas quotes older than this are not used for estimates
+    pub max_age_secs: u64,
+}
+
+impl Default for FeeOracleConfig {
+    fn default() -> Self {
+        let chain = |chain: &str, kind, rpc_url: &str, native: &str, alias: &str, native_units, token_units| ChainFeeConfig {
+            chain: chain.to_string(),
+            kind,
+            rpc_url: rpc_url.to_string(),
+            native_asset: native.to_string(),
+            aliases: vec![alias.to_string()],
+            native_transfer_units: native_units,
+            token_transfer_units: token_units,
+        };
+        Self {
+            chains: vec![
+                chain("ETH", ChainKind::Evm, "https://ethereum-rpc.publicnode.com", "ETH", "ERC20", 21_000, 65_000),
+                chain("TRON", ChainKind::Tron, "https://api.trongrid.io", "TRX", "TRC20", 0, 65_000),
+                chain("BSC", ChainKind::Evm, "https://bsc-dataseed.binance.org", "BNB", "BEP20", 21_000, 55_000),
+            ],
+            poll_interval_secs: 30,
+            max_age_secs: 300,
+        }
+    }
+}
+
+/// Latest gas price of a chain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GasQuote {
+    pub chain: String,
+    /// gwei on EVM chains, sun per energy on TRON
+    pub gas_price: f64,
+    pub timestamp_ms: u64,
+}
+
+/// Network cost of one transfer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransferCostEstimate {
+    pub chain: String,
+    pub asset: String,
+    pub amount: f64,
+    pub gas_price: f64,
+    pub fee_native: f64,
+    pub fee_usd: f64,
+    /// Fee expressed in the transferred asset, None without a price for it
+    pub fee_in_asset: Option<f64>,
+}
+
+/// Where gas prices come from
+#[async_trait::async_trait]
+pub trait GasPriceSource: Send + Sync {
+    async fn gas_price(&self, chain: &ChainFeeConfig) -> AdapterResult<f64>;
+}
+
+/// JSON-RPC / HTTP API of each chain
+pub struct RpcGasPriceSource {
+    http: reqwest::Client,
+}
+
+impl RpcGasPriceSource {
+    pub fn new() -> Self {
+        Self { http: reqwest::Client::new() }
+    }
+
+    async fn post(&self, url: &str, body: Value) -> AdapterResult<Value> {
+        self.http
+            .post(url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| AdapterError::Connection(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| AdapterError::Connection(e.to_string()))
+    }
+}
+
+impl Default for RpcGasPriceSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl GasPriceSource for RpcGasPriceSource {
+    async fn gas_price(&self, chain: &ChainFeeConfig) -> AdapterResult<f64> {
+        match chain.kind {
+            ChainKind::Evm => {
+                let response = self
+                    .post(&chain.rpc_url, json!({ "jsonrpc": "2.0", "id": 1, "method": "eth_gasPrice", "params": [] }))
+                    .await?;
+                let hex = response["result"].as_str().unwrap_or_default().trim_start_matches("0x");
+                let wei = u128::from_str_radix(hex, 16).map_err(|e| AdapterError::Validation {
+                    message: format!("{}: bad eth_gasPrice result: {}", chain.chain, e),
+                })?;
+                Ok(wei as f64 / 1e9)
+            }
+            ChainKind::Tron => {
+                let url = format!("{}/wallet/getchainparameters", chain.rpc_url.trim_end_matches('/'));
+                let response = self.post(&url, json!({})).await?;
+                response["chainParameter"]
+                    .as_array()
+                    .and_then(|params| params.iter().find(|p| p["key"] == "getEnergyFee"))
+                    .and_then(|p| p["value"].as_f64())
+                    .ok_or_else(|| AdapterError::Validation {
+                        message: format!("{}: getEnergyFee missing from chain parameters", chain.chain),
+                    })
+            }
+        }
+    }
+}
+
+/// Polled gas prices and transfer cost estimates
+pub struct FeeOracle {
+    config: FeeOracleConfig,
+    source: Arc<dyn GasPriceSource>,
+    quotes: RwLock<HashMap<String, GasQuote>>,
+    /// USD prices of native and transferred assets
+    prices: RwLock<HashMap<String, f64>>,
+    predictor: Option<Arc<FeePredictor>>,
+    clock: SharedClock,
+}
+
+impl FeeOracle {
+    pub fn new(config: FeeOracleConfig, source: Arc<dyn GasPriceSource>) -> Self {
+        Self {
+            config,
+            source,
+            quotes: RwLock::new(HashMap::new()),
+            prices: RwLock::new(HashMap::new()),
+            predictor: None,
+            clock: common::clock::system_clock(),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Backtest gas forecasts against realized withdrawal fees
+    pub fn with_predictor(mut self, predictor: Arc<FeePredictor>) -> Self {
+        self.predictor = Some(predictor);
+        self
+    }
+
+    /// USD price of an asset, fed from index prices
+    pub fn update_price(&self, asset: &str, usd: f64) {
+        if usd.is_finite() && usd > 0.0 {
+            self.prices.write().insert(asset.to_uppercase(), usd);
+        }
+    }
+
+    pub fn record_gas_price(&self, chain: &str, gas_price: f64) {
+        let Some(config) = self.chain(chain) else { return };
+        metrics::gauge!("chain_gas_price", "chain" => config.chain.clone()).set(gas_price);
+        let quote = GasQuote { chain: config.chain.clone(), gas_price, timestamp_ms: self.clock.now_millis() };
+        self.quotes.write().insert(config.chain.to_uppercase(), quote);
+        if let Some(predictor) = &self.predictor {
+            predictor.observe(&config.chain, gas_price);
+        }
+    }
+
+    /// Chain by name or withdrawal network alias
+    pub fn chain(&self, name: &str) -> Option<&ChainFeeConfig> {
+        self.config.chains.iter().find(|c| {
+            c.chain.eq_ignore_ascii_case(name) || c.aliases.iter().any(|a| a.eq_ignore_ascii_case(name))
+        })
+    }
+
+    /// Latest gas quote if fresh
+    pub fn gas_quote(&self, chain: &str) -> Option<GasQuote> {
+        let config = self.chain(chain)?;
+        let quote = self.quotes.read().get(&config.chain.to_uppercase()).cloned()?;
+        let age_ms = self.clock.now_millis().saturating_sub(quote.timestamp_ms);
+        (age_ms <= self.config.max_age_secs * 1000).then_some(quote)
+    }
+
+    /// Network cost of moving `amount` of `asset` over `chain`
+    pub fn estimate(&self, chain: &str, asset: &str, amount: f64) -> Option<TransferCostEstimate> {
+        let quote = self.gas_quote(chain)?;
+        self.estimate_at(chain, asset, amount, quote.gas_price)
+    }
+
+    /// Estimate for a withdrawal from `exchange`, priced with the gas forecast
+    /// of the model currently selected for that exchange and chain
+    pub fn estimate_for(&self, exchange: &str, chain: &str, asset: &str, amount: f64) -> Option<TransferCostEstimate> {
+        let Some(predictor) = &self.predictor else { return self.estimate(chain, asset, amount) };
+        let config = self.chain(chain)?;
+        self.gas_quote(chain)?;
+        let gas_price = predictor.predict(&config.chain, predictor.active_model(exchange, &config.chain))?;
+        self.estimate_at(chain, asset, amount, gas_price)
+    }
+
+    /// Store every model's fee for withdrawal `id` and return the estimate of
+    /// the active model; pair with [`FeeOracle::record_realized_fee`]
+    pub fn predict_withdrawal(
+        &self,
+        id: &str,
+        exchange: &str,
+        chain: &str,
+        asset: &str,
+        amount: f64,
+    ) -> Option<TransferCostEstimate> {
+        if let Some(predictor) = &self.predictor {
+            let config = self.chain(chain)?;
+            let predicted: Vec<(PredictionModel, f64)> = PredictionModel::ALL
+                .iter()
+                .filter_map(|model| {
+                    let gas_price = predictor.predict(&config.chain, *model)?;
+                    Some((*model, self.fee_native(config, asset, gas_price)))
+                })
+                .collect();
+            if !predicted.is_empty() {
+                predictor.record_prediction(id, exchange, &config.chain, predicted);
+            }
+        }
+        self.estimate_for(exchange, chain, asset, amount)
+    }
+
+    /// Network fee actually charged for withdrawal `id`, in the native asset
+    pub fn record_realized_fee(&self, id: &str, fee_native: f64) -> bool {
+        self.predictor.as_ref().is_some_and(|p| p.record_realized(id, fee_native))
+    }
+
+    pub fn prediction_accuracy(&self) -> Vec<ModelAccuracy> {
+        self.predictor.as_ref().map(|p| p.accuracy()).unwrap_or_default()
+    }
+
+    fn fee_native(&self, config: &ChainFeeConfig, asset: &str, gas_price: f64) -> f64 {
+        let native = asset.eq_ignore_ascii_case(&config.native_asset);
+        let units = if native { config.native_transfer_units } else { config.token_transfer_units };
+        units as f64 * gas_price * config.kind.unit_scale()
+    }
+
+    fn estimate_at(&self, chain: &str, asset: &str, amount: f64, gas_price: f64) -> Option<TransferCostEstimate> {
+        let config = self.chain(chain)?;
+        let native = asset.eq_ignore_ascii_case(&config.native_asset);
+        let fee_native = self.fee_native(config, asset, gas_price);
+
+        let prices = self.prices.read();
+        let native_usd = prices.get(&config.native_asset.to_uppercase()).copied();
+        let asset_usd = if native { native_usd } else { prices.get(&asset.to_uppercase()).copied() };
+        let fee_usd = fee_native * native_usd?;
+        Some(TransferCostEstimate {
+            chain: config.chain.clone(),
+            asset: asset.to_uppercase(),
+            amount,
+            gas_price,
+            fee_native,
+            fee_usd,
+            fee_in_asset: asset_usd.map(|usd| fee_usd / usd),
+        })
+    }
+
+    /// Estimates on every chain with fresh data, cheapest first
+    pub fn estimates(&self, asset: &str, amount: f64) -> Vec<TransferCostEstimate> {
+        let mut estimates: Vec<TransferCostEstimate> =
+            self.config.chains.iter().filter_map(|c| self.estimate(&c.chain, asset, amount)).collect();
+        estimates.sort_by(|a, b| a.fee_usd.partial_cmp(&b.fee_usd).unwrap_or(std::cmp::Ordering::Equal));
+        estimates
+    }
+
+    pub async fn poll_once(&self) {
+        for chain in &self.config.chains {
+            match self.source.gas_price(chain).await {
+                Ok(price) => self.record_gas_price(&chain.chain, price),
+                Err(e) => warn!("Gas price poll failed for {}: {}", chain.chain, e),
+            }
+        }
+    }
+
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs(self.config.poll_interval_secs.max(1)));
+            loop {
+                ticker.tick().await;
+                self.poll_once().await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::SimulatedClock;
+
+    struct FixedSource;
+
+    #[async_trait::async_trait]
+    impl GasPriceSource for FixedSource {
+        async fn gas_price(&self, chain: &ChainFeeConfig) -> AdapterResult<f64> {
+            match chain.chain.as_str() {
+                "ETH" => Ok(20.0),
+                "TRON" => Ok(420.0),
+                _ => Err(AdapterError::Connection("down".into())),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_estimates_per_chain() {
+        let clock = Arc::new(SimulatedClock::from_nanos(1_000_000_000_000));
+        let oracle = FeeOracle::new(FeeOracleConfig::default(), Arc::new(FixedSource)).with_clock(clock.clone());
+        oracle.update_price("ETH", 2_000.0);
+        oracle.update_price("TRX", 0.1);
+        oracle.update_price("USDT", 1.0);
+        oracle.poll_once().await;
+
+        // 65k gas * 20 gwei = 0.0013 ETH = $2.6
+        let erc20 = oracle.estimate("ERC20", "USDT", 1_000.0).unwrap();
+        assert!((erc20.fee_usd - 2.6).abs() < 1e-9);
+        // 65k energy * 420 sun = 27.3 TRX = $2.73
+        let trc20 = oracle.estimate("trc20", "USDT", 1_000.0).unwrap();
+        assert!((trc20.fee_in_asset.unwrap() - 2.73).abs() < 1e-9);
+        let eth = oracle.estimate("ETH", "ETH", 1.0).unwrap();
+        assert!((eth.fee_in_asset.unwrap() - 0.00042).abs() < 1e-12);
+        assert!(oracle.estimate("BEP20", "USDT", 1_000.0).is_none());
+
+        let ranked = oracle.estimates("USDT", 1_000.0);
+        assert_eq!(ranked.iter().map(|e| e.chain.as_str()).collect::<Vec<_>>(), ["ETH", "TRON"]);
+
+        clock.advance(chrono::Duration::seconds(301));
+        assert!(oracle.estimate("ERC20", "USDT", 1_000.0).is_none());
+    }
+}
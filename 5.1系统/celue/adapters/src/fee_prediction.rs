@@ -0,0 +1,318 @@
+//! Fee prediction models and their evaluation against realized fees
+//!
+//! The predictor keeps a short gas price history per chain and forecasts
+//! the next price with several models. When a withdrawal is planned, every
+//! model's fee prediction is stored under the withdrawal id; once the
+//! exchange reports the fee actually charged, each model's error goes into
+//! a rolling window per exchange and chain. MAE and bias (mean signed
+//! error, positive when over-predicting) are tracked per model, and the
+//! model with the lowest MAE becomes the active one for that exchange and
+//! chain once it has enough samples and beats the current model by a margin.
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use tracing::info;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PredictionModel {
+    /// Latest observed gas price
+    LastValue,
+    /// Mean of the last `ma_window` observations
+    MovingAverage,
+    /// Exponentially weighted average
+    Ewma,
+    /// Upper percentile of the history, conservative under spikes
+    Percentile,
+}
+
+impl PredictionModel {
+    pub const ALL: [PredictionModel; 4] =
+        [PredictionModel::LastValue, PredictionModel::MovingAverage, PredictionModel::Ewma, PredictionModel::Percentile];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PredictionModel::LastValue => "last_value",
+            PredictionModel::MovingAverage => "moving_average",
+            PredictionModel::Ewma => "ewma",
+            PredictionModel::Percentile => "percentile",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeePredictionConfig {
+    /// Gas price observations kept per chain
+    pub history_len: usize,
+    pub ma_window: usize,
+    pub ewma_alpha: f64,
+    /// Quantile used by the percentile model (0-1)
+    pub percentile: f64,
+    /// Realized fees kept per model, exchange and chain
+    pub eval_window: usize,
+    /// Samples required before a model can be selected
+    pub min_samples: usize,
+    /// Required MAE improvement over the active model (%)
+    pub switch_margin_pct: f64,
+    /// Predictions awaiting a realized fee; oldest are dropped beyond this
+    pub max_pending: usize,
+}
+
+impl Default for FeePredictionConfig {
+    fn default() -> Self {
+        Self {
+            history_len: 120,
+            ma_window: 10,
+            ewma_alpha: 0.3,
+            percentile: 0.75,
+            eval_window: 50,
+            min_samples: 10,
+            switch_margin_pct: 5.0,
+            max_pending: 1_000,
+        }
+    }
+}
+
+/// Rolling accuracy of one model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelAccuracy {
+    pub exchange: String,
+    pub chain: String,
+    pub model: PredictionModel,
+    pub samples: usize,
+    pub mae: f64,
+    /// Mean of predicted - realized
+    pub bias: f64,
+    pub active: bool,
+}
+
+struct PendingPrediction {
+    exchange: String,
+    chain: String,
+    predicted: Vec<(PredictionModel, f64)>,
+}
+
+#[derive(Default)]
+struct ChainHistory {
+    prices: VecDeque<f64>,
+    ewma: Option<f64>,
+}
+
+/// Gas price forecasts and per-exchange model selection
+pub struct FeePredictor {
+    config: FeePredictionConfig,
+    history: RwLock<HashMap<String, ChainHistory>>,
+    pending: RwLock<(HashMap<String, PendingPrediction>, VecDeque<String>)>,
+    /// (exchange, chain, model) -> signed errors
+    errors: RwLock<HashMap<(String, String, PredictionModel), VecDeque<f64>>>,
+    active: RwLock<HashMap<(String, String), PredictionModel>>,
+}
+
+impl FeePredictor {
+    pub fn new(config: FeePredictionConfig) -> Self {
+        Self {
+            config,
+            history: RwLock::new(HashMap::new()),
+            pending: RwLock::new((HashMap::new(), VecDeque::new())),
+            errors: RwLock::new(HashMap::new()),
+            active: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn observe(&self, chain: &str, gas_price: f64) {
+        if !gas_price.is_finite() || gas_price < 0.0 {
+            return;
+        }
+        let mut history = self.history.write();
+        let chain = history.entry(chain.to_uppercase()).or_default();
+        chain.prices.push_back(gas_price);
+        while chain.prices.len() > self.config.history_len.max(1) {
+            chain.prices.pop_front();
+        }
+        let alpha = self.config.ewma_alpha.clamp(0.0, 1.0);
+        chain.ewma = Some(chain.ewma.map_or(gas_price, |prev| alpha * gas_price + (1.0 - alpha) * prev));
+    }
+
+    /// Gas price forecast of `model` for `chain`
+    pub fn predict(&self, chain: &str, model: PredictionModel) -> Option<f64> {
+        let history = self.history.read();
+        let chain = history.get(&chain.to_uppercase())?;
+        let last = *chain.prices.back()?;
+        Some(match model {
+            PredictionModel::LastValue => last,
+            PredictionModel::MovingAverage => {
+                let window = self.config.ma_window.max(1).min(chain.prices.len());
+                chain.prices.iter().rev().take(window).sum::<f64>() / window as f64
+            }
+            PredictionModel::Ewma => chain.ewma.unwrap_or(last),
+            PredictionModel::Percentile => {
+                let mut sorted: Vec<f64> = chain.prices.iter().copied().collect();
+                sorted.sort_by(f64::total_cmp);
+                let rank = (self.config.percentile.clamp(0.0, 1.0) * (sorted.len() - 1) as f64).round() as usize;
+                sorted[rank]
+            }
+        })
+    }
+
+    /// Model currently used for an exchange and chain
+    pub fn active_model(&self, exchange: &str, chain: &str) -> PredictionModel {
+        self.active
+            .read()
+            .get(&(exchange.to_lowercase(), chain.to_uppercase()))
+            .copied()
+            .unwrap_or(PredictionModel::LastValue)
+    }
+
+    /// Store every model's fee prediction for a planned withdrawal
+    pub fn record_prediction(&self, id: &str, exchange: &str, chain: &str, predicted: Vec<(PredictionModel, f64)>) {
+        let mut pending = self.pending.write();
+        let (by_id, order) = &mut *pending;
+        let entry = PendingPrediction { exchange: exchange.to_lowercase(), chain: chain.to_uppercase(), predicted };
+        if by_id.insert(id.to_string(), entry).is_none() {
+            order.push_back(id.to_string());
+        }
+        while order.len() > self.config.max_pending {
+            if let Some(oldest) = order.pop_front() {
+                by_id.remove(&oldest);
+            }
+        }
+    }
+
+    /// Score stored predictions against the fee actually charged; false if
+    /// the id is unknown. May switch the active model.
+    pub fn record_realized(&self, id: &str, realized_fee: f64) -> bool {
+        let Some(prediction) = ({
+            let mut pending = self.pending.write();
+            let (by_id, order) = &mut *pending;
+            let prediction = by_id.remove(id);
+            if prediction.is_some() {
+                order.retain(|p| p != id);
+            }
+            prediction
+        }) else {
+            return false;
+        };
+
+        {
+            let mut errors = self.errors.write();
+            for (model, predicted) in &prediction.predicted {
+                let window = errors.entry((prediction.exchange.clone(), prediction.chain.clone(), *model)).or_default();
+                window.push_back(predicted - realized_fee);
+                while window.len() > self.config.eval_window.max(1) {
+                    window.pop_front();
+                }
+            }
+        }
+        self.select_model(&prediction.exchange, &prediction.chain);
+        true
+    }
+
+    fn select_model(&self, exchange: &str, chain: &str) {
+        let accuracy = self.accuracy_for(exchange, chain);
+        for a in &accuracy {
+            metrics::gauge!("fee_prediction_mae", "exchange" => exchange.to_string(), "chain" => chain.to_string(), "model" => a.model.as_str())
+                .set(a.mae);
+            metrics::gauge!("fee_prediction_bias", "exchange" => exchange.to_string(), "chain" => chain.to_string(), "model" => a.model.as_str())
+                .set(a.bias);
+        }
+        let Some(best) = accuracy
+            .iter()
+            .filter(|a| a.samples >= self.config.min_samples)
+            .min_by(|a, b| a.mae.total_cmp(&b.mae))
+        else {
+            return;
+        };
+        let current = self.active_model(exchange, chain);
+        if best.model == current {
+            return;
+        }
+        let current_mae = accuracy.iter().find(|a| a.model == current).map(|a| a.mae).unwrap_or(f64::INFINITY);
+        if best.mae < current_mae * (1.0 - self.config.switch_margin_pct / 100.0) {
+            info!(
+                "Fee model for {} {}: {} -> {} (MAE {:.6} vs {:.6})",
+                exchange,
+                chain,
+                current.as_str(),
+                best.model.as_str(),
+                best.mae,
+                current_mae
+            );
+            metrics::counter!("fee_model_switches_total", "exchange" => exchange.to_string(), "chain" => chain.to_string())
+                .increment(1);
+            self.active.write().insert((exchange.to_string(), chain.to_string()), best.model);
+        }
+    }
+
+    fn accuracy_for(&self, exchange: &str, chain: &str) -> Vec<ModelAccuracy> {
+        let errors = self.errors.read();
+        let active = self.active_model(exchange, chain);
+        PredictionModel::ALL
+            .iter()
+            .filter_map(|model| {
+                let window = errors.get(&(exchange.to_string(), chain.to_string(), *model))?;
+                let samples = window.len().max(1) as f64;
+                Some(ModelAccuracy {
+                    exchange: exchange.to_string(),
+                    chain: chain.to_string(),
+                    model: *model,
+                    samples: window.len(),
+                    mae: window.iter().map(|e| e.abs()).sum::<f64>() / samples,
+                    bias: window.iter().sum::<f64>() / samples,
+                    active: *model == active,
+                })
+            })
+            .collect()
+    }
+
+    /// Accuracy of every model on every exchange and chain with realized fees
+    pub fn accuracy(&self) -> Vec<ModelAccuracy> {
+        let mut keys: Vec<(String, String)> =
+            self.errors.read().keys().map(|(exchange, chain, _)| (exchange.clone(), chain.clone())).collect();
+        keys.sort();
+        keys.dedup();
+        keys.iter().flat_map(|(exchange, chain)| self.accuracy_for(exchange, chain)).collect()
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.read().0.len()
+    }
+}
+
+impl Default for FeePredictor {
+    fn default() -> Self {
+        Self::new(FeePredictionConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_switches_to_most_accurate_model() {
+        let predictor = FeePredictor::new(FeePredictionConfig { min_samples: 5, ..Default::default() });
+        // Gas oscillates between 10 and 30; the realized fee tracks the mean
+        for i in 0..40 {
+            predictor.observe("eth", if i % 2 == 0 { 10.0 } else { 30.0 });
+            let predicted: Vec<_> =
+                PredictionModel::ALL.iter().map(|m| (*m, predictor.predict("ETH", *m).unwrap())).collect();
+            predictor.record_prediction(&format!("w{}", i), "Binance", "ETH", predicted);
+            if i < 39 {
+                assert!(predictor.record_realized(&format!("w{}", i), 20.0));
+            }
+        }
+        assert!(!predictor.record_realized("unknown", 1.0));
+        assert_eq!(predictor.pending(), 1);
+
+        assert_eq!(predictor.active_model("binance", "eth"), PredictionModel::MovingAverage);
+        let accuracy = predictor.accuracy();
+        let last = accuracy.iter().find(|a| a.model == PredictionModel::LastValue).unwrap();
+        assert!((last.mae - 10.0).abs() < 1e-9);
+        let percentile = accuracy.iter().find(|a| a.model == PredictionModel::Percentile).unwrap();
+        assert!(percentile.bias > 9.0, "percentile over-predicts");
+        assert!(accuracy.iter().any(|a| a.active && a.model == PredictionModel::MovingAverage));
+        // Other exchanges keep the default until they have samples
+        assert_eq!(predictor.active_model("okx", "eth"), PredictionModel::LastValue);
+    }
+}
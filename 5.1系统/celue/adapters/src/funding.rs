@@ -0,0 +1,354 @@
+//! Funding-rate collection for perpetual futures
+//!
+//! Polls exchange REST endpoints (and accepts WebSocket pushes where the
+//! exchange offers them), keeps a bounded history per (exchange, market,
+//! symbol) and projects funding cost over an expected holding period. The
+//! market is part of the key because venues such as Binance use the same
+//! symbol for the spot pair and the perpetual; only perpetual legs pay
+//! funding.
+
+use crate::quota::{CallPriority, QuotaTracker};
+use crate::{AdapterError, AdapterResult};
+use common::{ArbitrageOpportunity, FixedPrice, MarketType, Side};
+use parking_lot::RwLock;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Default funding interval used by most perpetual venues (8h)
+pub const DEFAULT_FUNDING_INTERVAL_MS: u64 = 8 * 60 * 60 * 1000;
+
+/// A single funding-rate observation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingRate {
+    pub exchange: String,
+    /// Market the rate applies to; collected rates are perpetual
+    #[serde(default = "perpetual")]
+    pub market: MarketType,
+    pub symbol: String,
+    /// Rate per funding interval (e.g. 0.0001 = 0.01%)
+    pub rate: f64,
+    /// Funding interval in milliseconds
+    pub interval_ms: u64,
+    /// Next funding settlement time (ms since epoch)
+    pub next_funding_time_ms: u64,
+    /// Observation time (ms since epoch)
+    pub observed_at_ms: u64,
+}
+
+fn perpetual() -> MarketType {
+    MarketType::Perpetual
+}
+
+/// Storage backend for funding-rate history
+pub trait FundingRateStore: Send + Sync {
+    fn record(&self, rate: FundingRate);
+    fn latest(&self, exchange: &str, market: MarketType, symbol: &str) -> Option<FundingRate>;
+    fn history(&self, exchange: &str, market: MarketType, symbol: &str, limit: usize) -> Vec<FundingRate>;
+}
+
+type FundingKey = (String, MarketType, String);
+
+/// Bounded in-memory funding history
+pub struct InMemoryFundingStore {
+    history: RwLock<HashMap<FundingKey, VecDeque<FundingRate>>>,
+    max_entries: usize,
+}
+
+impl InMemoryFundingStore {
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            history: RwLock::new(HashMap::new()),
+            max_entries: max_entries.max(1),
+        }
+    }
+}
+
+impl FundingRateStore for InMemoryFundingStore {
+    fn record(&self, rate: FundingRate) {
+        let key = (rate.exchange.clone(), rate.market, rate.symbol.clone());
+        let mut history = self.history.write();
+        let entries = history.entry(key).or_default();
+        entries.push_back(rate);
+        while entries.len() > self.max_entries {
+            entries.pop_front();
+        }
+    }
+
+    fn latest(&self, exchange: &str, market: MarketType, symbol: &str) -> Option<FundingRate> {
+        let key = (exchange.to_string(), market, symbol.to_string());
+        self.history.read().get(&key).and_then(|e| e.back().cloned())
+    }
+
+    fn history(&self, exchange: &str, market: MarketType, symbol: &str, limit: usize) -> Vec<FundingRate> {
+        let key = (exchange.to_string(), market, symbol.to_string());
+        self.history
+            .read()
+            .get(&key)
+            .map(|e| e.iter().rev().take(limit).cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Funding collector configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingConfig {
+    /// REST poll interval
+    pub poll_interval: Duration,
+    /// Symbols to poll per exchange
+    pub symbols: HashMap<String, Vec<String>>,
+    /// History entries kept per (exchange, market, symbol)
+    pub history_size: usize,
+}
+
+impl Default for FundingConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(60),
+            symbols: HashMap::new(),
+            history_size: 1024,
+        }
+    }
+}
+
+/// Funding-rate collector (REST polling + WebSocket ingestion)
+pub struct FundingRateCollector {
+    config: FundingConfig,
+    store: Arc<dyn FundingRateStore>,
+    http_client: Client,
+    quota: Option<Arc<QuotaTracker>>,
+}
+
+impl FundingRateCollector {
+    pub fn new(config: FundingConfig, store: Arc<dyn FundingRateStore>) -> Self {
+        Self {
+            config,
+            store,
+            http_client: Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .expect("Failed to create HTTP client"),
+            quota: None,
+        }
+    }
+
+    /// Account polling against exchange API quotas; polls are non-critical
+    /// and are skipped when execution headroom runs low
+    pub fn with_quota(mut self, quota: Arc<QuotaTracker>) -> Self {
+        self.quota = Some(quota);
+        self
+    }
+
+    /// Backing store
+    pub fn store(&self) -> Arc<dyn FundingRateStore> {
+        self.store.clone()
+    }
+
+    /// Ingest a funding update pushed over an exchange WebSocket
+    pub fn ingest_ws_update(&self, rate: FundingRate) {
+        debug!("funding ws update {}:{} rate={}", rate.exchange, rate.symbol, rate.rate);
+        self.store.record(rate);
+    }
+
+    /// Poll all configured symbols once
+    pub async fn poll_once(&self) -> AdapterResult<usize> {
+        let mut collected = 0;
+        for (exchange, symbols) in &self.config.symbols {
+            for symbol in symbols {
+                if let Some(quota) = &self.quota {
+                    if !quota.try_acquire(exchange, 1, CallPriority::NonCritical) {
+                        debug!("funding poll throttled for {}:{}", exchange, symbol);
+                        continue;
+                    }
+                }
+                match self.fetch_rest(exchange, symbol).await {
+                    Ok(rate) => {
+                        self.store.record(rate);
+                        collected += 1;
+                    }
+                    Err(e) => warn!("funding poll failed for {}:{}: {}", exchange, symbol, e),
+                }
+            }
+        }
+        Ok(collected)
+    }
+
+    /// Spawn the REST polling loop
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.config.poll_interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.poll_once().await {
+                    warn!("funding poll cycle failed: {}", e);
+                }
+            }
+        })
+    }
+
+    async fn fetch_rest(&self, exchange: &str, symbol: &str) -> AdapterResult<FundingRate> {
+        let url = match exchange {
+            "binance" => format!("https://fapi.binance.com/fapi/v1/premiumIndex?symbol={}", symbol),
+            "okx" => format!("https://www.okx.com/api/v5/public/funding-rate?instId={}", symbol),
+            "bybit" => format!("https://api.bybit.com/v5/market/tickers?category=linear&symbol={}", symbol),
+            other => {
+                return Err(AdapterError::Configuration(format!(
+                    "funding rates not supported for exchange {}",
+                    other
+                )))
+            }
+        };
+
+        let body: serde_json::Value = self
+            .http_client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| AdapterError::Connection(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| AdapterError::Connection(e.to_string()))?;
+
+        parse_funding_response(exchange, symbol, &body).ok_or_else(|| AdapterError::Validation {
+            message: format!("unexpected funding payload from {}", exchange),
+        })
+    }
+}
+
+/// Parse a REST funding response for the supported exchanges
+pub fn parse_funding_response(exchange: &str, symbol: &str, body: &serde_json::Value) -> Option<FundingRate> {
+    let entry = match exchange {
+        "binance" => body,
+        "okx" => body.get("data")?.get(0)?,
+        "bybit" => body.get("result")?.get("list")?.get(0)?,
+        _ => return None,
+    };
+    let (rate_field, next_field) = match exchange {
+        "binance" => ("lastFundingRate", "nextFundingTime"),
+        _ => ("fundingRate", "nextFundingTime"),
+    };
+
+    let rate = json_f64(entry.get(rate_field)?)?;
+    let next_funding_time_ms = entry.get(next_field).and_then(json_f64).unwrap_or(0.0) as u64;
+
+    Some(FundingRate {
+        exchange: exchange.to_string(),
+        market: MarketType::Perpetual,
+        symbol: symbol.to_string(),
+        rate,
+        interval_ms: DEFAULT_FUNDING_INTERVAL_MS,
+        next_funding_time_ms,
+        observed_at_ms: chrono::Utc::now().timestamp_millis() as u64,
+    })
+}
+
+fn json_f64(value: &serde_json::Value) -> Option<f64> {
+    match value {
+        serde_json::Value::String(s) => s.parse().ok(),
+        serde_json::Value::Number(n) => n.as_f64(),
+        _ => None,
+    }
+}
+
+/// Projected funding cost (positive = we pay) for holding `notional_usd`
+/// on `side` for `holding_period_ms`. Longs pay positive funding.
+pub fn projected_funding_cost(rate: &FundingRate, side: Side, notional_usd: f64, holding_period_ms: u64) -> f64 {
+    let interval_ms = rate.interval_ms.max(1) as f64;
+    let periods = holding_period_ms as f64 / interval_ms;
+    let direction = match side {
+        Side::Buy => 1.0,
+        Side::Sell => -1.0,
+    };
+    rate.rate * notional_usd * periods * direction
+}
+
+/// Subtract projected funding cost of all perpetual legs from the
+/// opportunity's net profit. Legs without a known funding rate for their
+/// market (spot, delivery) contribute nothing. Returns the applied cost.
+pub fn apply_funding_cost(
+    opportunity: &mut ArbitrageOpportunity,
+    store: &dyn FundingRateStore,
+    holding_period_ms: u64,
+) -> f64 {
+    let mut total_cost = 0.0;
+    let mut invested = 0.0;
+    for leg in &opportunity.legs {
+        invested += leg.cost.to_f64().abs();
+        if let Some(rate) = store.latest(leg.exchange.as_str(), leg.market, leg.symbol.as_str()) {
+            total_cost += projected_funding_cost(&rate, leg.side, leg.cost.to_f64().abs(), holding_period_ms);
+        }
+    }
+
+    if total_cost != 0.0 {
+        let scale = opportunity.net_profit.scale();
+        let net = opportunity.net_profit.to_f64() - total_cost;
+        opportunity.net_profit = FixedPrice::from_f64(net, scale);
+        if invested > 0.0 {
+            let pct_scale = opportunity.net_profit_pct.scale();
+            opportunity.net_profit_pct = FixedPrice::from_f64(net / invested, pct_scale);
+        }
+        opportunity
+            .tags
+            .insert("funding_cost".to_string(), format!("{:.6}", total_cost));
+    }
+    total_cost
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::testing::{inter_exchange_opportunity, leg, opportunity_with_legs};
+
+    fn rate(exchange: &str, symbol: &str, rate: f64) -> FundingRate {
+        FundingRate {
+            exchange: exchange.into(),
+            market: MarketType::Perpetual,
+            symbol: symbol.into(),
+            rate,
+            interval_ms: DEFAULT_FUNDING_INTERVAL_MS,
+            next_funding_time_ms: 0,
+            observed_at_ms: 0,
+        }
+    }
+
+    #[test]
+    fn test_funding_applies_to_perpetual_legs_only() {
+        let store = InMemoryFundingStore::new(8);
+        store.record(rate("binance", "BTCUSDT", 0.0001));
+        store.record(rate("binance", "BTCUSDT", 0.001));
+        assert_eq!(store.history("binance", MarketType::Perpetual, "BTCUSDT", 10).len(), 2);
+        assert!(store.latest("binance", MarketType::Spot, "BTCUSDT").is_none());
+
+        // Spot legs on the same symbol pay no perpetual funding
+        let mut spot = inter_exchange_opportunity("inter_exchange", "BTCUSDT", 0.01);
+        assert_eq!(apply_funding_cost(&mut spot, &store, DEFAULT_FUNDING_INTERVAL_MS), 0.0);
+        assert_eq!(spot.net_profit.to_f64(), 1.0);
+
+        // Long 100 notional of the perpetual for two intervals at 0.1% pays 0.2
+        let mut perp_leg = leg("binance", "BTCUSDT", Side::Buy, 100.0, 1.0);
+        perp_leg.market = MarketType::Perpetual;
+        let legs = vec![perp_leg, leg("okx", "BTCUSDT", Side::Sell, 100.0, 1.0)];
+        let mut basis = opportunity_with_legs("basis", legs, 0.005, 0);
+        let cost = apply_funding_cost(&mut basis, &store, 2 * DEFAULT_FUNDING_INTERVAL_MS);
+        assert!((cost - 0.2).abs() < 1e-9);
+        assert!((basis.net_profit.to_f64() - 0.8).abs() < 1e-9);
+        assert!((basis.net_profit_pct.to_f64() - 0.004).abs() < 1e-9);
+        assert_eq!(basis.tags["funding_cost"], "0.200000");
+    }
+
+    #[test]
+    fn test_parse_funding_response_is_perpetual() {
+        let body = serde_json::json!({ "lastFundingRate": "0.00010000", "nextFundingTime": 1_700_000_000_000u64 });
+        let rate = parse_funding_response("binance", "BTCUSDT", &body).unwrap();
+        assert_eq!((rate.market, rate.rate, rate.next_funding_time_ms), (MarketType::Perpetual, 0.0001, 1_700_000_000_000));
+        let legacy: FundingRate = serde_json::from_value(serde_json::json!({
+            "exchange": "okx", "symbol": "BTC-USDT-SWAP", "rate": 0.0002,
+            "interval_ms": 1, "next_funding_time_ms": 0, "observed_at_ms": 0,
+        }))
+        .unwrap();
+        assert_eq!(legacy.market, MarketType::Perpetual);
+    }
+}
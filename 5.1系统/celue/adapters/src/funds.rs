@@ -2,6 +2,8 @@
 //! Implements fund availability checks and balance management
 
 use crate::{AdapterError, AdapterResult};
+use crate::rebalance::{RebalancePlanner, TransferRecommendation};
+use crate::trading_mode::TradingModeController;
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -26,7 +28,7 @@ pub struct AssetBalance {
 }
 
 /// Fund limits and constraints
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FundLimits {
     /// Maximum position size per symbol (in USD)
     pub max_position_per_symbol: f64,
@@ -91,6 +93,8 @@ pub struct FundsAdapter {
     positions: Arc<RwLock<HashMap<String, f64>>>,
     /// Configuration
     config: FundsConfig,
+    /// Transfers are only scheduled in live mode
+    trading_mode: Arc<TradingModeController>,
 }
 
 impl FundsAdapter {
@@ -101,9 +105,26 @@ impl FundsAdapter {
             limits: Arc::new(RwLock::new(config.limits.clone())),
             positions: Arc::new(RwLock::new(HashMap::new())),
             config,
+            trading_mode: Arc::new(TradingModeController::default()),
         }
     }
 
+    /// Consult the system-wide trading mode before scheduling transfers
+    pub fn with_trading_mode(mut self, trading_mode: Arc<TradingModeController>) -> Self {
+        self.trading_mode = trading_mode;
+        self
+    }
+
+    /// Whether a transfer may be executed in the current trading mode
+    pub fn authorize_transfer(&self, transfer: &TransferRecommendation) -> AdapterResult<()> {
+        self.trading_mode.authorize_transfer().inspect_err(|e| {
+            tracing::warn!(
+                "Transfer of {} {} {} -> {} refused: {}",
+                transfer.amount, transfer.asset, transfer.from_exchange, transfer.to_exchange, e
+            );
+        })
+    }
+
     /// Update balance for an asset
     pub fn update_balance(&self, balance: AssetBalance) {
         let key = (balance.exchange.clone(), balance.asset.clone());
@@ -116,6 +137,11 @@ impl FundsAdapter {
         self.balances.read().get(&key).cloned()
     }
 
+    /// All tracked balances
+    pub fn balances(&self) -> Vec<AssetBalance> {
+        self.balances.read().values().cloned().collect()
+    }
+
     /// Check if funds are available for allocation
     pub fn check_allocation(&self, symbol: &str, _exchange: &str, amount_usd: f64) -> FundAllocation {
         let limits = self.limits.read();
@@ -193,6 +219,28 @@ impl FundsAdapter {
     pub fn get_positions(&self) -> HashMap<String, f64> {
         self.positions.read().clone()
     }
+
+    /// Plan cross-exchange transfers from the current balances, scheduling
+    /// the authorized ones when the planner auto-schedules
+    pub fn plan_rebalance(&self, planner: &RebalancePlanner) -> Vec<TransferRecommendation> {
+        let balances: Vec<AssetBalance> = self.balances.read().values().cloned().collect();
+        let mut transfers = planner.plan(&balances);
+        if planner.auto_schedule() {
+            for transfer in &mut transfers {
+                // Outside live mode transfers stay recommendations
+                if self.authorize_transfer(transfer).is_err() {
+                    continue;
+                }
+                if let Err(e) = planner.schedule(transfer) {
+                    tracing::warn!(
+                        "Failed to schedule transfer of {} {} {} -> {}: {}",
+                        transfer.amount, transfer.asset, transfer.from_exchange, transfer.to_exchange, e
+                    );
+                }
+            }
+        }
+        transfers
+    }
 }
 
 #[async_trait]
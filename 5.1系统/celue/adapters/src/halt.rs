@@ -0,0 +1,289 @@
+//! Scoped emergency halts
+//!
+//! Complements the global kill-switch with targeted controls such as
+//! "halt binance", "halt BTC/USDT everywhere" or "halt all triangular".
+//! Activating a halt blocks new claims for matching opportunities, asks
+//! the registered canceller to pull matching open orders and broadcasts a
+//! scoped alert. Each owner holds its own record on a scope, so one owner
+//! releasing its halt never lifts a halt another owner placed there.
+
+use crate::AdapterResult;
+use common::ArbitrageOpportunity;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// What a halt applies to
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "scope", content = "value", rename_all = "snake_case")]
+pub enum HaltScope {
+    Global,
+    Exchange(String),
+    /// Symbol on every exchange; separators and case are ignored
+    Symbol(String),
+    /// Strategy name prefix, e.g. "triangular"
+    Strategy(String),
+    /// Every symbol quoted in an asset, e.g. "USDT" during a depeg
+    QuoteAsset(String),
+}
+
+impl HaltScope {
+    /// Normalized form used for matching
+    fn normalized(&self) -> Self {
+        match self {
+            HaltScope::Global => HaltScope::Global,
+            HaltScope::Exchange(e) => HaltScope::Exchange(e.to_lowercase()),
+            HaltScope::Symbol(s) => HaltScope::Symbol(normalize_symbol(s)),
+            HaltScope::Strategy(s) => HaltScope::Strategy(s.to_lowercase()),
+            HaltScope::QuoteAsset(q) => HaltScope::QuoteAsset(q.to_uppercase()),
+        }
+    }
+
+    /// Whether this scope covers the given opportunity
+    pub fn matches(&self, opportunity: &ArbitrageOpportunity) -> bool {
+        match self.normalized() {
+            HaltScope::Global => true,
+            HaltScope::Exchange(e) => opportunity
+                .legs
+                .iter()
+                .any(|l| l.exchange.as_str().eq_ignore_ascii_case(&e)),
+            HaltScope::Symbol(s) => opportunity
+                .legs
+                .iter()
+                .any(|l| normalize_symbol(l.symbol.as_str()) == s),
+            HaltScope::Strategy(s) => opportunity.strategy_name.to_lowercase().starts_with(&s),
+            HaltScope::QuoteAsset(q) => opportunity.legs.iter().any(|l| is_quoted_in(l.symbol.as_str(), &q)),
+        }
+    }
+
+    /// Whether this scope covers an order on `exchange` / `symbol`
+    pub fn matches_order(&self, exchange: &str, symbol: &str, strategy: &str) -> bool {
+        match self.normalized() {
+            HaltScope::Global => true,
+            HaltScope::Exchange(e) => exchange.eq_ignore_ascii_case(&e),
+            HaltScope::Symbol(s) => normalize_symbol(symbol) == s,
+            HaltScope::Strategy(s) => strategy.to_lowercase().starts_with(&s),
+            HaltScope::QuoteAsset(q) => is_quoted_in(symbol, &q),
+        }
+    }
+}
+
+/// Trailing symbol segments that name a contract type rather than an asset
+const CONTRACT_SUFFIXES: [&str; 4] = ["SWAP", "PERP", "PERPETUAL", "FUTURES"];
+
+/// Whether `symbol` ("BTC-USDT", "BTC/USDT", "BTCUSDT", or a contract such
+/// as "BTC-USDT-SWAP" / "BTCUSDT_250328") is quoted in `quote`
+fn is_quoted_in(symbol: &str, quote: &str) -> bool {
+    let mut segments: Vec<&str> = symbol
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .collect();
+    // Contract type and delivery date follow the quote asset
+    while let [_, .., last] = segments.as_slice() {
+        let is_suffix = last.bytes().all(|b| b.is_ascii_digit())
+            || CONTRACT_SUFFIXES.iter().any(|s| last.eq_ignore_ascii_case(s));
+        if !is_suffix {
+            break;
+        }
+        segments.pop();
+    }
+    match segments.as_slice() {
+        [single] => single.to_ascii_uppercase().ends_with(&quote.to_ascii_uppercase()),
+        [.., last] => last.eq_ignore_ascii_case(quote),
+        [] => false,
+    }
+}
+
+pub(crate) fn normalize_symbol(symbol: &str) -> String {
+    symbol
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// An active halt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HaltRecord {
+    pub scope: HaltScope,
+    pub reason: String,
+    pub operator: String,
+    pub activated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Scoped alert emitted on halt changes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum HaltEvent {
+    Activated { record: HaltRecord, cancelled_orders: usize },
+    Released { scope: HaltScope, operator: String },
+}
+
+/// Cancels open orders covered by a halt scope
+#[async_trait::async_trait]
+pub trait OrderCanceller: Send + Sync {
+    /// Cancel matching orders; returns how many were cancelled
+    async fn cancel_matching(&self, scope: &HaltScope) -> AdapterResult<usize>;
+}
+
+/// Registry of active halts, one record per scope and owner
+pub struct HaltRegistry {
+    halts: RwLock<HashMap<HaltScope, Vec<HaltRecord>>>,
+    canceller: RwLock<Option<Arc<dyn OrderCanceller>>>,
+    events: broadcast::Sender<HaltEvent>,
+}
+
+impl HaltRegistry {
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(256);
+        Self {
+            halts: RwLock::new(HashMap::new()),
+            canceller: RwLock::new(None),
+            events,
+        }
+    }
+
+    /// Register the component that cancels orders when a halt activates
+    pub fn set_canceller(&self, canceller: Arc<dyn OrderCanceller>) {
+        *self.canceller.write() = Some(canceller);
+    }
+
+    /// Subscribe to scoped halt alerts
+    pub fn subscribe(&self) -> broadcast::Receiver<HaltEvent> {
+        self.events.subscribe()
+    }
+
+    /// Activate a halt: block claims, cancel matching orders and alert
+    pub async fn halt(&self, scope: HaltScope, reason: &str, operator: &str) -> AdapterResult<usize> {
+        let scope = scope.normalized();
+        let record = HaltRecord {
+            scope: scope.clone(),
+            reason: reason.to_string(),
+            operator: operator.to_string(),
+            activated_at: chrono::Utc::now(),
+        };
+        // Block first so nothing new is claimed while cancels are in flight
+        {
+            let mut halts = self.halts.write();
+            let records = halts.entry(scope.clone()).or_default();
+            records.retain(|r| r.operator != operator);
+            records.push(record.clone());
+        }
+        tracing::error!("Halt activated for {:?} by {}: {}", scope, operator, reason);
+
+        let canceller = self.canceller.read().clone();
+        let cancelled_orders = match canceller {
+            Some(c) => c.cancel_matching(&scope).await?,
+            None => 0,
+        };
+        let _ = self.events.send(HaltEvent::Activated { record, cancelled_orders });
+        Ok(cancelled_orders)
+    }
+
+    /// Release `operator`'s own halt on a scope; halts other owners placed
+    /// on it stay active. Returns false if `operator` held none
+    pub fn release(&self, scope: &HaltScope, operator: &str) -> bool {
+        let scope = scope.normalized();
+        let removed = {
+            let mut halts = self.halts.write();
+            let Some(records) = halts.get_mut(&scope) else {
+                return false;
+            };
+            let before = records.len();
+            records.retain(|r| r.operator != operator);
+            let removed = records.len() < before;
+            if records.is_empty() {
+                halts.remove(&scope);
+            }
+            removed
+        };
+        if removed {
+            tracing::warn!("Halt released for {:?} by {}", scope, operator);
+            let _ = self.events.send(HaltEvent::Released { scope, operator: operator.to_string() });
+        }
+        removed
+    }
+
+    /// First active halt covering the opportunity, if any
+    pub fn blocking_halt(&self, opportunity: &ArbitrageOpportunity) -> Option<HaltRecord> {
+        self.halts
+            .read()
+            .values()
+            .flatten()
+            .find(|h| h.scope.matches(opportunity))
+            .cloned()
+    }
+
+    /// Whether `operator` holds a halt on `scope`
+    pub fn holds(&self, scope: &HaltScope, operator: &str) -> bool {
+        self.halts
+            .read()
+            .get(&scope.normalized())
+            .is_some_and(|records| records.iter().any(|r| r.operator == operator))
+    }
+
+    pub fn active(&self) -> Vec<HaltRecord> {
+        self.halts.read().values().flatten().cloned().collect()
+    }
+}
+
+impl Default for HaltRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::testing::inter_exchange_opportunity;
+
+    #[tokio::test]
+    async fn test_scoped_halts() {
+        let registry = HaltRegistry::new();
+        let opportunity = inter_exchange_opportunity("inter_exchange", "BTC-USDT", 0.01);
+
+        registry.halt(HaltScope::Strategy("triangular".into()), "test", "ops").await.unwrap();
+        assert!(registry.blocking_halt(&opportunity).is_none());
+
+        registry.halt(HaltScope::Symbol("btc/usdt".into()), "test", "ops").await.unwrap();
+        assert!(registry.blocking_halt(&opportunity).is_some());
+
+        assert!(registry.release(&HaltScope::Symbol("BTC/USDT".into()), "ops"));
+        assert!(registry.blocking_halt(&opportunity).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_release_only_drops_the_callers_halt() {
+        let registry = HaltRegistry::new();
+        let opportunity = inter_exchange_opportunity("inter_exchange", "BTC-USDT", 0.01);
+        let scope = HaltScope::QuoteAsset("USDT".into());
+
+        registry.halt(scope.clone(), "manual", "ops").await.unwrap();
+        registry.halt(scope.clone(), "depeg", "depeg_monitor").await.unwrap();
+        registry.halt(scope.clone(), "depeg again", "depeg_monitor").await.unwrap();
+        assert_eq!(registry.active().len(), 2);
+
+        // The monitor recovering must not lift the operator's halt
+        assert!(registry.release(&scope, "depeg_monitor"));
+        assert!(!registry.release(&scope, "depeg_monitor"));
+        assert!(!registry.holds(&scope, "depeg_monitor"));
+        assert_eq!(registry.blocking_halt(&opportunity).unwrap().operator, "ops");
+
+        assert!(registry.release(&scope, "ops"));
+        assert!(registry.blocking_halt(&opportunity).is_none());
+        assert!(registry.active().is_empty());
+    }
+
+    #[test]
+    fn test_quote_asset_matches_contracts() {
+        let usdt = HaltScope::QuoteAsset("usdt".into());
+        for symbol in ["BTC-USDT", "BTC/USDT", "BTCUSDT", "BTC-USDT-SWAP", "BTC-USDT-250328", "BTCUSDT_250328", "BTCUSDT_PERP"] {
+            assert!(usdt.matches_order("okx", symbol, "inter_exchange"), "{}", symbol);
+        }
+        for symbol in ["BTC-USD-SWAP", "BTCUSD_250328", "ETH-BTC", "BTC-PERPETUAL"] {
+            assert!(!usdt.matches_order("okx", symbol, "inter_exchange"), "{}", symbol);
+        }
+    }
+}
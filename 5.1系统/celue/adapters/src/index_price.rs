@@ -0,0 +1,280 @@
+//! Consolidated index price per symbol
+//!
+//! Every tick updates the exchange's mid price and displayed volume for
+//! the symbol. The index is the volume-weighted average of the fresh mids
+//! across all connected exchanges. An exchange whose mid moves outside
+//! the deviation band around the index raises an alert. Pre-trade risk
+//! uses the index as its price-sanity reference: legs priced outside the
+//! band are refused.
+
+use crate::alerting::{Alert, AlertManager, AlertSeverity};
+use common::{ArbitrageOpportunity, NormalizedSnapshot, OrderBook};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::warn;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexPriceConfig {
+    /// Deviation from the index that raises an alert (percent)
+    pub deviation_band_pct: f64,
+    /// Exchange prices older than this are left out of the index (ms)
+    pub stale_after_ms: u64,
+    /// Book levels counted towards an exchange's volume weight
+    pub weight_levels: usize,
+}
+
+impl Default for IndexPriceConfig {
+    fn default() -> Self {
+        Self {
+            deviation_band_pct: 1.0,
+            stale_after_ms: 5_000,
+            weight_levels: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ExchangePrice {
+    mid: f64,
+    volume: f64,
+    timestamp_ns: u64,
+}
+
+/// Consolidated price for one symbol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexPrice {
+    pub symbol: String,
+    pub price: f64,
+    /// Exchanges contributing to the index
+    pub exchanges: usize,
+    pub timestamp_ns: u64,
+}
+
+/// An exchange or order price outside the band
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceDeviation {
+    pub exchange: String,
+    pub symbol: String,
+    pub price: f64,
+    pub index_price: f64,
+    pub deviation_pct: f64,
+}
+
+/// Volume-weighted index price service
+pub struct IndexPriceService {
+    config: IndexPriceConfig,
+    /// symbol -> exchange -> latest price
+    prices: RwLock<HashMap<String, HashMap<String, ExchangePrice>>>,
+    /// (exchange, symbol) currently outside the band
+    deviating: Mutex<HashSet<(String, String)>>,
+    alerts: Option<Arc<AlertManager>>,
+}
+
+impl IndexPriceService {
+    pub fn new(config: IndexPriceConfig) -> Self {
+        Self {
+            config,
+            prices: RwLock::new(HashMap::new()),
+            deviating: Mutex::new(HashSet::new()),
+            alerts: None,
+        }
+    }
+
+    /// Raise deviation alerts through the alert manager
+    pub fn with_alerts(mut self, alerts: Arc<AlertManager>) -> Self {
+        self.alerts = Some(alerts);
+        self
+    }
+
+    /// Record one exchange's book and return exchanges newly outside the band
+    pub fn on_book(&self, book: &OrderBook) -> Vec<PriceDeviation> {
+        let (Some(bid), Some(ask)) = (book.best_bid(), book.best_ask()) else {
+            return Vec::new();
+        };
+        let levels = self.config.weight_levels.max(1);
+        let volume: f64 = book.bid_quantities.iter().take(levels).map(|q| q.to_f64()).sum::<f64>()
+            + book.ask_quantities.iter().take(levels).map(|q| q.to_f64()).sum::<f64>();
+        let price = ExchangePrice {
+            mid: (bid.price.to_f64() + ask.price.to_f64()) / 2.0,
+            volume,
+            timestamp_ns: book.timestamp_ns,
+        };
+        let symbol = book.symbol.as_str().to_string();
+        self.prices
+            .write()
+            .entry(symbol.clone())
+            .or_default()
+            .insert(book.exchange.as_str().to_lowercase(), price);
+        self.check_deviations(&symbol, book.timestamp_ns)
+    }
+
+    /// Record every exchange in a snapshot
+    pub fn on_snapshot(&self, snapshot: &NormalizedSnapshot) -> Vec<PriceDeviation> {
+        let mut deviations = Vec::new();
+        for book in &snapshot.exchanges {
+            deviations.extend(self.on_book(book));
+        }
+        deviations
+    }
+
+    fn fresh(&self, price: &ExchangePrice, now_ns: u64) -> bool {
+        now_ns.saturating_sub(price.timestamp_ns) <= self.config.stale_after_ms * 1_000_000
+    }
+
+    /// Index price for `symbol` from exchanges updated within the staleness limit
+    pub fn index_price(&self, symbol: &str, now_ns: u64) -> Option<IndexPrice> {
+        let prices = self.prices.read();
+        let fresh: Vec<&ExchangePrice> = prices.get(symbol)?.values().filter(|p| self.fresh(p, now_ns)).collect();
+        if fresh.is_empty() {
+            return None;
+        }
+        let total_volume: f64 = fresh.iter().map(|p| p.volume).sum();
+        // Fall back to a plain average when no volume is displayed
+        let price = if total_volume > 0.0 {
+            fresh.iter().map(|p| p.mid * p.volume).sum::<f64>() / total_volume
+        } else {
+            fresh.iter().map(|p| p.mid).sum::<f64>() / fresh.len() as f64
+        };
+        Some(IndexPrice {
+            symbol: symbol.to_string(),
+            price,
+            exchanges: fresh.len(),
+            timestamp_ns: fresh.iter().map(|p| p.timestamp_ns).max().unwrap_or(now_ns),
+        })
+    }
+
+    /// Deviation of `price` from the index in percent, if an index exists
+    pub fn deviation_pct(&self, symbol: &str, price: f64, now_ns: u64) -> Option<f64> {
+        let index = self.index_price(symbol, now_ns)?;
+        (index.price > 0.0).then(|| (price - index.price) / index.price * 100.0)
+    }
+
+    fn check_deviations(&self, symbol: &str, now_ns: u64) -> Vec<PriceDeviation> {
+        let Some(index) = self.index_price(symbol, now_ns).filter(|i| i.exchanges > 1 && i.price > 0.0) else {
+            return Vec::new();
+        };
+        let prices = self.prices.read();
+        let mut deviating = self.deviating.lock();
+        let mut newly = Vec::new();
+        for (exchange, price) in prices.get(symbol).into_iter().flatten() {
+            let key = (exchange.clone(), symbol.to_string());
+            let deviation_pct = (price.mid - index.price) / index.price * 100.0;
+            if !self.fresh(price, now_ns) || deviation_pct.abs() <= self.config.deviation_band_pct {
+                deviating.remove(&key);
+                continue;
+            }
+            if deviating.insert(key) {
+                newly.push(PriceDeviation {
+                    exchange: exchange.clone(),
+                    symbol: symbol.to_string(),
+                    price: price.mid,
+                    index_price: index.price,
+                    deviation_pct,
+                });
+            }
+        }
+        newly
+    }
+
+    /// Legs priced outside the band around the index; legs without an
+    /// index pass
+    pub fn check_opportunity(&self, opportunity: &ArbitrageOpportunity, now_ns: u64) -> Vec<PriceDeviation> {
+        opportunity
+            .legs
+            .iter()
+            .filter_map(|leg| {
+                let symbol = leg.symbol.as_str();
+                let price = leg.price.to_f64();
+                let index = self.index_price(symbol, now_ns)?;
+                let deviation_pct = self.deviation_pct(symbol, price, now_ns)?;
+                (deviation_pct.abs() > self.config.deviation_band_pct).then(|| PriceDeviation {
+                    exchange: leg.exchange.as_str().to_string(),
+                    symbol: symbol.to_string(),
+                    price,
+                    index_price: index.price,
+                    deviation_pct,
+                })
+            })
+            .collect()
+    }
+
+    async fn raise(&self, deviation: &PriceDeviation) {
+        warn!(
+            "{} {} mid {:.8} deviates {:.2}% from index {:.8}",
+            deviation.exchange, deviation.symbol, deviation.price, deviation.deviation_pct, deviation.index_price
+        );
+        if let Some(alerts) = &self.alerts {
+            alerts
+                .raise(Alert::new(
+                    &format!("index_deviation:{}:{}", deviation.exchange, deviation.symbol),
+                    AlertSeverity::Warning,
+                    "Exchange price deviates from index",
+                    &format!(
+                        "{} {} at {:.8} is {:.2}% from index {:.8}",
+                        deviation.exchange, deviation.symbol, deviation.price, deviation.deviation_pct, deviation.index_price
+                    ),
+                    "index_price",
+                ))
+                .await;
+        }
+    }
+
+    /// Update the index from the snapshot stream and raise deviation alerts
+    pub fn spawn(self: Arc<Self>, mut snapshots: broadcast::Receiver<NormalizedSnapshot>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match snapshots.recv().await {
+                    Ok(snapshot) => {
+                        for deviation in self.on_snapshot(&snapshot) {
+                            self.raise(&deviation).await;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Index price service lagged, skipped {} snapshots", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+}
+
+impl Default for IndexPriceService {
+    fn default() -> Self {
+        Self::new(IndexPriceConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::testing::sized_book;
+
+    fn book(exchange: &str, mid: f64, qty: f64, ts: u64) -> OrderBook {
+        sized_book(exchange, "BTCUSDT", (mid - 1.0, qty), (mid + 1.0, qty), ts)
+    }
+
+    #[test]
+    fn test_volume_weighted_index_and_deviation() {
+        let service = IndexPriceService::default();
+        let now = 10_000_000_000;
+        assert!(service.on_book(&book("binance", 100.0, 3.0, now)).is_empty());
+        service.on_book(&book("okx", 104.0, 1.0, now));
+        let index = service.index_price("BTCUSDT", now).unwrap();
+        assert!((index.price - 101.0).abs() < 1e-9);
+        assert_eq!(index.exchanges, 2);
+
+        // Bybit prints far from the index
+        let deviations = service.on_book(&book("bybit", 120.0, 0.5, now));
+        assert!(deviations.iter().any(|d| d.exchange == "bybit"));
+        assert!(service.on_book(&book("bybit", 120.0, 0.5, now)).is_empty(), "alert once per excursion");
+
+        // Stale exchanges drop out of the index
+        let later = now + 6_000_000_000;
+        service.on_book(&book("binance", 100.0, 3.0, later));
+        assert_eq!(service.index_price("BTCUSDT", later).unwrap().exchanges, 1);
+    }
+}
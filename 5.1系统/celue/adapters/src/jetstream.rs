@@ -0,0 +1,212 @@
+//! JetStream durable consumers
+//!
+//! Opportunity, execution and alert events are consumed through durable
+//! pull consumers with explicit acks, so a restarted consumer resumes from
+//! its last acknowledged message instead of losing everything published
+//! while it was down. Failed messages are redelivered with backoff; after
+//! `max_deliver` attempts they are moved to a dead-letter subject and
+//! terminated. With subject metrics attached, every ack records the lag
+//! since publish and the consumer's remaining backlog.
+
+use crate::nats::ConsumerConfig;
+use crate::subject_metrics::SubjectMetrics;
+use crate::{AdapterError, AdapterResult};
+use async_nats::jetstream::{self, consumer::pull, AckKind};
+use futures_util::StreamExt;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// Processes messages delivered to a durable consumer
+#[async_trait::async_trait]
+pub trait JetStreamHandler: Send + Sync {
+    async fn handle(&self, subject: &str, payload: &[u8]) -> AdapterResult<()>;
+}
+
+/// What to do with a message after the handler ran
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Disposition {
+    Ack,
+    /// Redeliver after the delay
+    Retry(Duration),
+    /// Publish to the dead-letter subject and stop redelivering
+    DeadLetter,
+}
+
+/// Decide the disposition for attempt number `delivered` (1-based)
+pub fn disposition(succeeded: bool, delivered: i64, max_deliver: i32, has_dead_letter: bool) -> Disposition {
+    if succeeded {
+        return Disposition::Ack;
+    }
+    if max_deliver > 0 && delivered >= max_deliver as i64 {
+        return if has_dead_letter { Disposition::DeadLetter } else { Disposition::Ack };
+    }
+    // 100ms, 200ms, 400ms ... capped at 10s
+    let backoff_ms = 100u64.saturating_mul(1 << (delivered.clamp(1, 10) - 1) as u32).min(10_000);
+    Disposition::Retry(Duration::from_millis(backoff_ms))
+}
+
+fn js_error(e: impl std::fmt::Display) -> AdapterError {
+    AdapterError::Generic { message: e.to_string() }
+}
+
+/// Durable pull consumer bound to one stream
+pub struct DurableConsumer {
+    config: ConsumerConfig,
+    context: jetstream::Context,
+    consumer: jetstream::consumer::Consumer<pull::Config>,
+    metrics: Option<Arc<SubjectMetrics>>,
+}
+
+impl DurableConsumer {
+    /// Create the consumer, or bind to it if it already exists
+    pub async fn create(context: &jetstream::Context, config: ConsumerConfig) -> AdapterResult<Self> {
+        let stream = context.get_stream(&config.stream_name).await.map_err(js_error)?;
+        let durable = config.durable_name.clone().unwrap_or_else(|| config.name.clone());
+        let consumer = stream
+            .get_or_create_consumer(&durable, Self::pull_config(&config, Some(durable.clone())))
+            .await
+            .map_err(js_error)?;
+        Ok(Self { config, context: context.clone(), consumer, metrics: None })
+    }
+
+    /// Record ack lag and backlog into `metrics`
+    pub fn with_metrics(mut self, metrics: Arc<SubjectMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    fn pull_config(config: &ConsumerConfig, durable_name: Option<String>) -> pull::Config {
+        pull::Config {
+            durable_name,
+            ack_policy: match config.ack_policy.as_str() {
+                "none" => jetstream::consumer::AckPolicy::None,
+                "all" => jetstream::consumer::AckPolicy::All,
+                _ => jetstream::consumer::AckPolicy::Explicit,
+            },
+            ack_wait: config.ack_wait,
+            max_deliver: config.max_deliver as i64,
+            filter_subject: config.filter_subject.clone().unwrap_or_default(),
+            deliver_policy: match config.start_sequence {
+                Some(start_sequence) => jetstream::consumer::DeliverPolicy::ByStartSequence { start_sequence },
+                None => jetstream::consumer::DeliverPolicy::All,
+            },
+            ..Default::default()
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    /// Consume until the stream ends or errors
+    pub async fn run(&self, handler: Arc<dyn JetStreamHandler>) -> AdapterResult<()> {
+        let messages = self.consumer.messages().await.map_err(js_error)?;
+        info!("JetStream consumer '{}' running", self.config.name);
+        self.consume(messages, handler).await
+    }
+
+    /// Replay the stream from `start_sequence` through an ephemeral consumer,
+    /// leaving the durable consumer's position untouched
+    pub async fn replay_from(&self, start_sequence: u64, handler: Arc<dyn JetStreamHandler>) -> AdapterResult<()> {
+        let stream = self.context.get_stream(&self.config.stream_name).await.map_err(js_error)?;
+        let config = ConsumerConfig { start_sequence: Some(start_sequence), ..self.config.clone() };
+        let consumer = stream.create_consumer(Self::pull_config(&config, None)).await.map_err(js_error)?;
+        let messages = consumer.messages().await.map_err(js_error)?;
+        info!("Replaying '{}' from sequence {}", self.config.stream_name, start_sequence);
+        self.consume(messages, handler).await
+    }
+
+    async fn consume(&self, mut messages: pull::Stream, handler: Arc<dyn JetStreamHandler>) -> AdapterResult<()> {
+        while let Some(message) = messages.next().await {
+            let message = match message {
+                Ok(message) => message,
+                Err(e) => {
+                    warn!("JetStream consumer '{}' receive error: {}", self.config.name, e);
+                    continue;
+                }
+            };
+            let subject = message.subject.to_string();
+            let (delivered, published_at_ns, pending) = message
+                .info()
+                .map(|i| (i.delivered, i.published.unix_timestamp_nanos().max(0) as u64, i.pending))
+                .unwrap_or((1, 0, 0));
+            let result = handler.handle(&subject, &message.payload).await;
+            if let Err(e) = &result {
+                warn!("Handler failed for '{}' (attempt {}): {}", subject, delivered, e);
+            }
+
+            let decision = disposition(
+                result.is_ok(),
+                delivered,
+                self.config.max_deliver,
+                self.config.dead_letter_subject.is_some(),
+            );
+            let ack = match decision {
+                Disposition::Ack => message.ack().await,
+                Disposition::Retry(delay) => message.ack_with(AckKind::Nak(Some(delay))).await,
+                Disposition::DeadLetter => {
+                    let error = result.err().map(|e| e.to_string()).unwrap_or_default();
+                    self.dead_letter(&message, &subject, delivered, &error).await;
+                    message.ack_with(AckKind::Term).await
+                }
+            };
+            match ack {
+                Ok(()) => {
+                    if let Some(metrics) = &self.metrics {
+                        metrics.record_consumer_pending(&self.config.name, pending);
+                        if decision == Disposition::Ack && published_at_ns > 0 {
+                            let acked_at_ns = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64;
+                            metrics.record_ack(&subject, published_at_ns, acked_at_ns);
+                        }
+                    }
+                }
+                Err(e) => error!("Failed to ack '{}' on consumer '{}': {}", subject, self.config.name, e),
+            }
+        }
+        Ok(())
+    }
+
+    async fn dead_letter(&self, message: &jetstream::Message, subject: &str, delivered: i64, error: &str) {
+        let Some(dead_letter_subject) = &self.config.dead_letter_subject else { return };
+        let mut headers = async_nats::HeaderMap::new();
+        headers.insert("Celue-Original-Subject", subject);
+        headers.insert("Celue-Consumer", self.config.name.as_str());
+        headers.insert("Celue-Deliveries", delivered.to_string().as_str());
+        headers.insert("Celue-Error", error);
+        if let Ok(info) = message.info() {
+            headers.insert("Celue-Stream-Sequence", info.stream_sequence.to_string().as_str());
+        }
+        let published = self
+            .context
+            .publish_with_headers(dead_letter_subject.clone(), headers, message.payload.clone())
+            .await;
+        match published {
+            Ok(ack) => {
+                if let Err(e) = ack.await {
+                    error!("Dead-letter publish for '{}' not acknowledged: {}", subject, e);
+                } else {
+                    warn!("Moved '{}' to {} after {} attempts", subject, dead_letter_subject, delivered);
+                }
+            }
+            Err(e) => error!("Failed to dead-letter '{}': {}", subject, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disposition_retries_then_dead_letters() {
+        assert_eq!(disposition(true, 3, 3, true), Disposition::Ack);
+        assert_eq!(disposition(false, 1, 3, true), Disposition::Retry(Duration::from_millis(100)));
+        assert_eq!(disposition(false, 2, 3, true), Disposition::Retry(Duration::from_millis(200)));
+        assert_eq!(disposition(false, 3, 3, true), Disposition::DeadLetter);
+        // Without a dead-letter subject the message is dropped after the last attempt
+        assert_eq!(disposition(false, 3, 3, false), Disposition::Ack);
+        // Unlimited deliveries never dead-letter
+        assert!(matches!(disposition(false, 50, -1, true), Disposition::Retry(_)));
+    }
+}
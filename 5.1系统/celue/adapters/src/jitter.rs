@@ -0,0 +1,131 @@
+//! Controlled randomization of order timing and sizing
+//! Keeps execution patterns less predictable while staying inside risk bounds
+
+use common::precision::FixedQuantity;
+use parking_lot::Mutex;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Jitter configuration for the execution adapter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JitterConfig {
+    /// Enable timing/size jitter
+    pub enabled: bool,
+    /// RNG seed for this run; a random seed is drawn (and logged) when absent
+    pub seed: Option<u64>,
+    /// Maximum extra delay before order submission (microseconds)
+    pub max_delay_us: u64,
+    /// Maximum relative size reduction applied to an order (0.0 - 1.0)
+    pub max_size_reduction_pct: f64,
+    /// Size rounding step in quantity units (0 disables rounding)
+    pub size_step: f64,
+}
+
+impl Default for JitterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            seed: None,
+            max_delay_us: 2_000,
+            max_size_reduction_pct: 0.05,
+            size_step: 0.0,
+        }
+    }
+}
+
+/// Seeded jitter source shared by an execution adapter
+pub struct ExecutionJitter {
+    config: JitterConfig,
+    seed: u64,
+    rng: Mutex<StdRng>,
+}
+
+impl ExecutionJitter {
+    /// Create a jitter source, drawing a seed if the config does not pin one
+    pub fn new(config: JitterConfig) -> Self {
+        let seed = config.seed.unwrap_or_else(rand::random);
+        tracing::info!("execution jitter seed: {} (enabled: {})", seed, config.enabled);
+        Self {
+            config,
+            seed,
+            rng: Mutex::new(StdRng::seed_from_u64(seed)),
+        }
+    }
+
+    /// Seed used for this run, recorded for audit and A/B evaluation
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Whether jitter is active
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Next submission delay
+    pub fn next_delay(&self) -> Duration {
+        if !self.config.enabled || self.config.max_delay_us == 0 {
+            return Duration::ZERO;
+        }
+        let us = self.rng.lock().gen_range(0..=self.config.max_delay_us);
+        Duration::from_micros(us)
+    }
+
+    /// Randomize an order size downwards, never exceeding the original
+    /// (risk-approved) quantity and never dropping below `min_quantity`.
+    pub fn jitter_quantity(&self, quantity: FixedQuantity, min_quantity: f64) -> FixedQuantity {
+        if !self.config.enabled {
+            return quantity;
+        }
+        let original = quantity.to_f64();
+        let max_reduction = self.config.max_size_reduction_pct.clamp(0.0, 1.0);
+        let factor = 1.0 - self.rng.lock().gen_range(0.0..=max_reduction);
+        let mut jittered = original * factor;
+
+        if self.config.size_step > 0.0 {
+            jittered = (jittered / self.config.size_step).floor() * self.config.size_step;
+        }
+
+        let bounded = jittered.max(min_quantity).min(original);
+        FixedQuantity::from_f64(bounded, quantity.scale())
+    }
+}
+
+impl Default for ExecutionJitter {
+    fn default() -> Self {
+        Self::new(JitterConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jitter(seed: u64) -> ExecutionJitter {
+        ExecutionJitter::new(JitterConfig { enabled: true, seed: Some(seed), size_step: 0.001, ..Default::default() })
+    }
+
+    fn draws(jitter: &ExecutionJitter) -> Vec<(Duration, f64)> {
+        (0..16)
+            .map(|_| (jitter.next_delay(), jitter.jitter_quantity(FixedQuantity::from_f64(2.0, 4), 1.95).to_f64()))
+            .collect()
+    }
+
+    #[test]
+    fn test_same_seed_reproduces_jitter_within_bounds() {
+        let (a, b) = (jitter(42), jitter(42));
+        assert_eq!(a.seed(), 42);
+        let run = draws(&a);
+        assert_eq!(run, draws(&b));
+        assert_ne!(run, draws(&jitter(43)));
+        for (delay, quantity) in run {
+            assert!(delay <= Duration::from_micros(2_000));
+            assert!((1.95..=2.0).contains(&quantity), "{} outside risk bounds", quantity);
+        }
+
+        let disabled = ExecutionJitter::new(JitterConfig { seed: Some(42), ..Default::default() });
+        assert_eq!(disabled.next_delay(), Duration::ZERO);
+        assert_eq!(disabled.jitter_quantity(FixedQuantity::from_f64(2.0, 4), 0.0).to_f64(), 2.0);
+    }
+}
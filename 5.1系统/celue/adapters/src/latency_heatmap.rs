@@ -0,0 +1,271 @@
+//! Per-exchange latency heat map
+//!
+//! Book update, REST order and cancel latencies are counted into fixed
+//! latency buckets per exchange and operation, in time slots of
+//! `resolution_secs`. Every sample is also recorded to the
+//! `exchange_operation_latency_ms` histogram. `heatmap` rolls the slots up
+//! to the requested window and slot width and returns a dense grid (one
+//! cell per slot, zero-filled) that a frontend can draw directly.
+
+use common::SharedClock;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// Most slots a single heat map returns; wider requests get wider slots
+const MAX_SLOTS: u64 = 1_440;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LatencyOperation {
+    BookUpdate,
+    RestOrder,
+    Cancel,
+}
+
+impl LatencyOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LatencyOperation::BookUpdate => "book_update",
+            LatencyOperation::RestOrder => "rest_order",
+            LatencyOperation::Cancel => "cancel",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencyHeatmapConfig {
+    /// Upper bounds of the latency buckets in ms; one overflow bucket follows
+    pub bucket_bounds_ms: Vec<f64>,
+    pub resolution_secs: u64,
+    pub retention_secs: u64,
+}
+
+impl Default for LatencyHeatmapConfig {
+    fn default() -> Self {
+        Self {
+            bucket_bounds_ms: vec![1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0, 1_000.0, 2_000.0],
+            resolution_secs: 10,
+            retention_secs: 24 * 3600,
+        }
+    }
+}
+
+/// Heat map request; empty filters select everything
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeatmapQuery {
+    pub window_secs: u64,
+    pub slot_secs: u64,
+    pub exchanges: Vec<String>,
+    pub operations: Vec<LatencyOperation>,
+}
+
+impl Default for HeatmapQuery {
+    fn default() -> Self {
+        Self { window_secs: 3600, slot_secs: 60, exchanges: Vec::new(), operations: Vec::new() }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HeatmapCell {
+    pub start_ms: u64,
+    /// Sample count per bucket, aligned with `bucket_bounds_ms` plus overflow
+    pub counts: Vec<u64>,
+    pub total: u64,
+    /// Bucket upper bounds at which the percentiles fall
+    pub p50_ms: Option<f64>,
+    pub p99_ms: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeatmapRow {
+    pub exchange: String,
+    pub operation: LatencyOperation,
+    pub cells: Vec<HeatmapCell>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencyHeatmap {
+    pub bucket_bounds_ms: Vec<f64>,
+    pub slot_secs: u64,
+    pub from_ms: u64,
+    pub to_ms: u64,
+    pub rows: Vec<HeatmapRow>,
+}
+
+/// (slot start secs, bucket counts), oldest first
+type SlotSeries = VecDeque<(u64, Vec<u64>)>;
+
+/// Time-bucketed latency histograms per exchange and operation
+pub struct LatencyRecorder {
+    config: LatencyHeatmapConfig,
+    /// (exchange, operation) -> time slots
+    series: RwLock<HashMap<(String, LatencyOperation), SlotSeries>>,
+    clock: SharedClock,
+}
+
+impl LatencyRecorder {
+    pub fn new(config: LatencyHeatmapConfig) -> Self {
+        let config = LatencyHeatmapConfig { resolution_secs: config.resolution_secs.max(1), ..config };
+        Self { config, series: RwLock::new(HashMap::new()), clock: common::clock::system_clock() }
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn record(&self, exchange: &str, operation: LatencyOperation, latency_ms: f64) {
+        if !latency_ms.is_finite() || latency_ms < 0.0 {
+            return;
+        }
+        let exchange = exchange.to_lowercase();
+        metrics::histogram!(
+            "exchange_operation_latency_ms",
+            "exchange" => exchange.clone(),
+            "operation" => operation.as_str()
+        )
+        .record(latency_ms);
+
+        let bucket = self.config.bucket_bounds_ms.partition_point(|b| *b < latency_ms);
+        let now_secs = self.clock.now_millis() / 1000;
+        let slot = now_secs / self.config.resolution_secs * self.config.resolution_secs;
+        let buckets = self.config.bucket_bounds_ms.len() + 1;
+
+        let mut series = self.series.write();
+        let slots = series.entry((exchange, operation)).or_default();
+        match slots.back_mut() {
+            Some((start, counts)) if *start == slot => counts[bucket] += 1,
+            _ => {
+                let mut counts = vec![0; buckets];
+                counts[bucket] = 1;
+                slots.push_back((slot, counts));
+            }
+        }
+        let cutoff = now_secs.saturating_sub(self.config.retention_secs);
+        while slots.front().is_some_and(|(start, _)| *start < cutoff) {
+            slots.pop_front();
+        }
+    }
+
+    /// Book update latency from the exchange timestamp to now
+    pub fn record_book_update(&self, exchange: &str, exchange_timestamp_ns: u64) {
+        let now_ns = self.clock.now_ns();
+        if exchange_timestamp_ns > 0 && now_ns >= exchange_timestamp_ns {
+            self.record(exchange, LatencyOperation::BookUpdate, (now_ns - exchange_timestamp_ns) as f64 / 1e6);
+        }
+    }
+
+    /// Roll recorded slots up into a dense grid
+    pub fn heatmap(&self, query: &HeatmapQuery) -> LatencyHeatmap {
+        let resolution = self.config.resolution_secs;
+        let window = query.window_secs.clamp(resolution, self.config.retention_secs.max(resolution));
+        let mut slot_secs = query.slot_secs.max(resolution).div_ceil(resolution) * resolution;
+        if window.div_ceil(slot_secs) > MAX_SLOTS {
+            slot_secs = window.div_ceil(MAX_SLOTS).div_ceil(resolution) * resolution;
+        }
+        let now_secs = self.clock.now_millis() / 1000;
+        let to = (now_secs / slot_secs + 1) * slot_secs;
+        let from = to.saturating_sub(window.div_ceil(slot_secs) * slot_secs);
+        let n_slots = ((to - from) / slot_secs) as usize;
+        let buckets = self.config.bucket_bounds_ms.len() + 1;
+
+        let exchanges: Vec<String> = query.exchanges.iter().map(|e| e.to_lowercase()).collect();
+        let mut rows: Vec<HeatmapRow> = self
+            .series
+            .read()
+            .iter()
+            .filter(|((exchange, op), _)| {
+                (exchanges.is_empty() || exchanges.contains(exchange))
+                    && (query.operations.is_empty() || query.operations.contains(op))
+            })
+            .map(|((exchange, operation), slots)| {
+                let mut grid = vec![vec![0u64; buckets]; n_slots];
+                for (start, counts) in slots.iter().filter(|(start, _)| *start >= from && *start < to) {
+                    let cell = &mut grid[((start - from) / slot_secs) as usize];
+                    for (total, count) in cell.iter_mut().zip(counts) {
+                        *total += count;
+                    }
+                }
+                let cells = grid
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, counts)| {
+                        let total = counts.iter().sum();
+                        HeatmapCell {
+                            start_ms: (from + i as u64 * slot_secs) * 1000,
+                            p50_ms: self.percentile(&counts, total, 0.5),
+                            p99_ms: self.percentile(&counts, total, 0.99),
+                            counts,
+                            total,
+                        }
+                    })
+                    .collect();
+                HeatmapRow { exchange: exchange.clone(), operation: *operation, cells }
+            })
+            .collect();
+        rows.sort_by(|a, b| (&a.exchange, a.operation.as_str()).cmp(&(&b.exchange, b.operation.as_str())));
+
+        LatencyHeatmap {
+            bucket_bounds_ms: self.config.bucket_bounds_ms.clone(),
+            slot_secs,
+            from_ms: from * 1000,
+            to_ms: to * 1000,
+            rows,
+        }
+    }
+
+    fn percentile(&self, counts: &[u64], total: u64, q: f64) -> Option<f64> {
+        if total == 0 {
+            return None;
+        }
+        let rank = (q * total as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        let bounds = &self.config.bucket_bounds_ms;
+        counts.iter().enumerate().find_map(|(i, count)| {
+            seen += count;
+            (seen >= rank).then(|| bounds.get(i).or(bounds.last()).copied().unwrap_or(f64::INFINITY))
+        })
+    }
+}
+
+impl Default for LatencyRecorder {
+    fn default() -> Self {
+        Self::new(LatencyHeatmapConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{Clock, SimulatedClock};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_heatmap_buckets_and_slots() {
+        let clock = Arc::new(SimulatedClock::from_nanos(3_600_000_000_000));
+        let recorder = LatencyRecorder::default().with_clock(clock.clone());
+        for ms in [0.5, 3.0, 3.0, 4.0, 150.0] {
+            recorder.record("Binance", LatencyOperation::RestOrder, ms);
+        }
+        clock.advance(chrono::Duration::seconds(60));
+        recorder.record("okx", LatencyOperation::Cancel, 12.0);
+        recorder.record_book_update("binance", clock.now_ns() - 4_000_000);
+
+        let query = HeatmapQuery { window_secs: 300, slot_secs: 60, ..Default::default() };
+        let map = recorder.heatmap(&query);
+        assert_eq!(map.rows.len(), 3);
+        let orders = map.rows.iter().find(|r| r.operation == LatencyOperation::RestOrder).unwrap();
+        assert_eq!(orders.exchange, "binance");
+        assert_eq!(orders.cells.len(), 5);
+        let busy: Vec<&HeatmapCell> = orders.cells.iter().filter(|c| c.total > 0).collect();
+        assert_eq!(busy.len(), 1);
+        assert_eq!(busy[0].counts[..4], [1, 0, 3, 0]);
+        assert_eq!(busy[0].p50_ms, Some(5.0));
+        assert_eq!(busy[0].p99_ms, Some(200.0));
+
+        let only_okx = HeatmapQuery { exchanges: vec!["OKX".into()], ..query };
+        assert_eq!(recorder.heatmap(&only_okx).rows.len(), 1);
+    }
+}
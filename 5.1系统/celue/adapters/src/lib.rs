@@ -10,12 +10,68 @@
 //! - Funds management for balance and limits
 
 pub mod nats;
+pub mod jetstream;
 pub mod market_data;
 pub mod error;
 pub mod risk;
 pub mod funds;
 pub mod metrics;
 pub mod execution;
+pub mod jitter;
+pub mod funding;
+pub mod staleness;
+pub mod rebalance;
+pub mod execution_policy;
+pub mod execution_preview;
+pub mod approval;
+pub mod markout;
+pub mod slicing;
+pub mod order_matching;
+pub mod quota;
+pub mod replay;
+pub mod halt;
+pub mod subject_metrics;
+pub mod virtual_account;
+pub mod attribution;
+pub mod alerting;
+pub mod venue_scorecard;
+pub mod escalation;
+pub mod ws_gateway;
+pub mod symbols;
+pub mod trading_calendar;
+pub mod retry;
+pub mod health;
+pub mod venue_selector;
+pub mod order_books;
+pub mod index_price;
+pub mod reference_data;
+pub mod analytics;
+pub mod opportunity_ttl;
+pub mod conflict;
+pub mod trading_mode;
+pub mod microstructure;
+pub mod batch_orders;
+pub mod price_history;
+pub mod candles;
+pub mod scheduler;
+pub mod self_monitor;
+pub mod capacity;
+pub mod portfolio_risk;
+pub mod depeg;
+pub mod slippage_guard;
+pub mod latency_heatmap;
+pub mod settlement;
+pub mod venue;
+pub mod fee_oracle;
+pub mod fee_prediction;
+pub mod maintenance;
+pub mod price_cache;
+pub mod symbol_controls;
+pub mod ws_compression;
+#[cfg(feature = "chaos")]
+pub mod chaos;
+#[cfg(feature = "dex")]
+pub mod dex;
 
 // Re-export key types
 pub use error::{AdapterError, AdapterResult};
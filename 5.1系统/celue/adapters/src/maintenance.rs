@@ -0,0 +1,433 @@
+//! Exchange maintenance auto-detection
+//!
+//! Exchanges rarely announce maintenance on the API. Order and feed paths
+//! report what they see (HTTP errors, exchange error codes, WebSocket close
+//! frames) and the detector matches them against maintenance signatures:
+//! the `Maintenance` class of the retry taxonomy (HTTP 503, OKX `50001`,
+//! Bybit `10016`, "maintenance" messages), WS close codes 1012/1013 and
+//! per-exchange message patterns. A venue in maintenance is reported
+//! `Unhealthy` as component `exchange:<name>`, every strategy touching it
+//! is paused with an exchange halt, and a probe checks for recovery with
+//! exponential backoff. After `recovery_probes` consecutive successful
+//! probes the halt is released and the venue reported healthy again.
+
+use crate::alerting::{Alert, AlertManager, AlertSeverity};
+use crate::halt::{HaltRegistry, HaltScope};
+use crate::health::{ComponentHealth, HealthSnapshot, HealthStatus};
+use crate::retry::{classify, ErrorClass};
+use crate::{AdapterError, AdapterResult};
+use common::SharedClock;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+const OPERATOR: &str = "maintenance_detector";
+
+/// Something an exchange connection observed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ExchangeSignal {
+    Http { status: u16, body: String },
+    ExchangeError { code: String, message: String },
+    WsClose { code: u16, reason: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceConfig {
+    /// WebSocket close codes meaning the venue is restarting or overloaded
+    pub ws_close_codes: Vec<u16>,
+    /// Extra lowercase message fragments per exchange; "*" applies to all
+    pub message_patterns: HashMap<String, Vec<String>>,
+    pub initial_probe_secs: u64,
+    pub max_probe_secs: u64,
+    /// Consecutive successful probes before the venue is released
+    pub recovery_probes: u32,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            ws_close_codes: vec![1012, 1013],
+            message_patterns: HashMap::from([
+                ("*".to_string(), vec!["under maintenance".to_string(), "service unavailable".to_string()]),
+                ("huobi".to_string(), vec!["system-maintenance".to_string()]),
+                ("gateio".to_string(), vec!["server_maintenance".to_string()]),
+            ]),
+            initial_probe_secs: 10,
+            max_probe_secs: 600,
+            recovery_probes: 2,
+        }
+    }
+}
+
+/// Checks whether an exchange is back
+#[async_trait::async_trait]
+pub trait MaintenanceProbe: Send + Sync {
+    /// Ok when the venue answers normally
+    async fn probe(&self, exchange: &str) -> AdapterResult<()>;
+}
+
+/// Probes a public REST endpoint (ping or server time) per exchange
+pub struct HttpMaintenanceProbe {
+    endpoints: HashMap<String, String>,
+    http: reqwest::Client,
+}
+
+impl HttpMaintenanceProbe {
+    pub fn new(endpoints: HashMap<String, String>) -> Self {
+        let endpoints = endpoints.into_iter().map(|(e, url)| (e.to_lowercase(), url)).collect();
+        Self { endpoints, http: reqwest::Client::new() }
+    }
+}
+
+#[async_trait::async_trait]
+impl MaintenanceProbe for HttpMaintenanceProbe {
+    async fn probe(&self, exchange: &str) -> AdapterResult<()> {
+        let url = self
+            .endpoints
+            .get(&exchange.to_lowercase())
+            .ok_or_else(|| AdapterError::Configuration(format!("no probe endpoint for {}", exchange)))?;
+        let response = self.http.get(url).send().await.map_err(|e| AdapterError::Connection(e.to_string()))?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        Err(AdapterError::Exchange {
+            exchange: exchange.to_string(),
+            code: status.as_u16().to_string(),
+            message: response.text().await.unwrap_or_default(),
+        })
+    }
+}
+
+/// A venue currently in maintenance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceStatus {
+    pub exchange: String,
+    /// What matched, e.g. "http 503" or "ws close 1012"
+    pub signature: String,
+    pub since_ms: u64,
+    pub next_probe_ms: u64,
+    pub backoff_secs: u64,
+    pub probes: u32,
+    pub consecutive_ok: u32,
+}
+
+/// Maintenance detection and recovery probing
+pub struct MaintenanceDetector {
+    config: MaintenanceConfig,
+    health: Arc<HealthSnapshot>,
+    halts: Arc<HaltRegistry>,
+    probe: Option<Arc<dyn MaintenanceProbe>>,
+    alerts: Option<Arc<AlertManager>>,
+    venues: RwLock<HashMap<String, MaintenanceStatus>>,
+    clock: SharedClock,
+}
+
+impl MaintenanceDetector {
+    pub fn new(config: MaintenanceConfig, health: Arc<HealthSnapshot>, halts: Arc<HaltRegistry>) -> Self {
+        Self {
+            config,
+            health,
+            halts,
+            probe: None,
+            alerts: None,
+            venues: RwLock::new(HashMap::new()),
+            clock: common::clock::system_clock(),
+        }
+    }
+
+    pub fn with_probe(mut self, probe: Arc<dyn MaintenanceProbe>) -> Self {
+        self.probe = Some(probe);
+        self
+    }
+
+    pub fn with_alerts(mut self, alerts: Arc<AlertManager>) -> Self {
+        self.alerts = Some(alerts);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Maintenance signature matched by a signal, if any
+    pub fn signature(&self, exchange: &str, signal: &ExchangeSignal) -> Option<String> {
+        let (matched, message) = match signal {
+            ExchangeSignal::Http { status, body } => (
+                classify(exchange, &status.to_string(), body) == ErrorClass::Maintenance,
+                body.as_str(),
+            ),
+            ExchangeSignal::ExchangeError { code, message } => {
+                (classify(exchange, code, message) == ErrorClass::Maintenance, message.as_str())
+            }
+            ExchangeSignal::WsClose { code, reason } => (self.config.ws_close_codes.contains(code), reason.as_str()),
+        };
+        let message = message.to_lowercase();
+        let pattern = ["*", &exchange.to_lowercase()]
+            .iter()
+            .filter_map(|key| self.config.message_patterns.get(*key))
+            .flatten()
+            .find(|p| message.contains(p.as_str()));
+        if !matched && pattern.is_none() {
+            return None;
+        }
+        Some(match signal {
+            ExchangeSignal::Http { status, .. } => format!("http {}", status),
+            ExchangeSignal::ExchangeError { code, .. } => format!("error {}", code),
+            ExchangeSignal::WsClose { code, .. } => format!("ws close {}", code),
+        })
+    }
+
+    pub fn in_maintenance(&self, exchange: &str) -> bool {
+        self.venues.read().contains_key(&exchange.to_lowercase())
+    }
+
+    pub fn active(&self) -> Vec<MaintenanceStatus> {
+        let mut active: Vec<MaintenanceStatus> = self.venues.read().values().cloned().collect();
+        active.sort_by(|a, b| a.exchange.cmp(&b.exchange));
+        active
+    }
+
+    /// Feed a signal; returns true when it put the venue into maintenance
+    pub async fn observe(&self, exchange: &str, signal: &ExchangeSignal) -> bool {
+        let Some(signature) = self.signature(exchange, signal) else { return false };
+        let exchange = exchange.to_lowercase();
+        let now_ms = self.clock.now_millis();
+        {
+            let mut venues = self.venues.write();
+            if venues.contains_key(&exchange) {
+                return false;
+            }
+            venues.insert(exchange.clone(), MaintenanceStatus {
+                exchange: exchange.clone(),
+                signature: signature.clone(),
+                since_ms: now_ms,
+                next_probe_ms: now_ms + self.config.initial_probe_secs * 1000,
+                backoff_secs: self.config.initial_probe_secs,
+                probes: 0,
+                consecutive_ok: 0,
+            });
+        }
+        warn!("{} entered maintenance ({})", exchange, signature);
+        metrics::counter!("exchange_maintenance_detected_total", "exchange" => exchange.clone()).increment(1);
+        metrics::gauge!("exchange_in_maintenance", "exchange" => exchange.clone()).set(1.0);
+
+        self.report_health(&exchange, HealthStatus::Unhealthy, Some(&signature));
+        let reason = format!("maintenance detected: {}", signature);
+        if let Err(e) = self.halts.halt(HaltScope::Exchange(exchange.clone()), &reason, OPERATOR).await {
+            warn!("Failed to pause {} during maintenance: {}", exchange, e);
+        }
+        self.raise(Alert::new(
+            &format!("exchange_maintenance:{}", exchange),
+            AlertSeverity::Warning,
+            &format!("{} in maintenance", exchange),
+            &format!("Detected {}; strategies on {} paused until probes recover", signature, exchange),
+            &exchange,
+        ));
+        true
+    }
+
+    /// Feed an order or feed error
+    pub async fn observe_error(&self, exchange: &str, error: &AdapterError) -> bool {
+        match error {
+            AdapterError::Exchange { code, message, .. } => {
+                let signal = ExchangeSignal::ExchangeError { code: code.clone(), message: message.clone() };
+                self.observe(exchange, &signal).await
+            }
+            _ => false,
+        }
+    }
+
+    /// Probe every venue whose backoff has elapsed
+    pub async fn probe_due(&self) {
+        let Some(probe) = self.probe.clone() else { return };
+        let now_ms = self.clock.now_millis();
+        let due: Vec<String> = self
+            .venues
+            .read()
+            .values()
+            .filter(|v| v.next_probe_ms <= now_ms)
+            .map(|v| v.exchange.clone())
+            .collect();
+
+        for exchange in due {
+            let result = probe.probe(&exchange).await;
+            let now_ms = self.clock.now_millis();
+            let recovered = {
+                let mut venues = self.venues.write();
+                let Some(venue) = venues.get_mut(&exchange) else { continue };
+                venue.probes += 1;
+                match &result {
+                    Ok(()) => {
+                        venue.consecutive_ok += 1;
+                        // Confirm quickly once the venue answers again
+                        venue.backoff_secs = self.config.initial_probe_secs;
+                    }
+                    Err(_) => {
+                        venue.consecutive_ok = 0;
+                        venue.backoff_secs = (venue.backoff_secs * 2).min(self.config.max_probe_secs);
+                    }
+                }
+                venue.next_probe_ms = now_ms + venue.backoff_secs * 1000;
+                let recovered = venue.consecutive_ok >= self.config.recovery_probes.max(1);
+                recovered.then(|| venues.remove(&exchange)).flatten()
+            };
+            match (recovered, result) {
+                (Some(venue), _) => self.recover(venue),
+                (None, Err(e)) => info!("{} still in maintenance: {}", exchange, e),
+                (None, Ok(())) => {}
+            }
+        }
+    }
+
+    fn recover(&self, venue: MaintenanceStatus) {
+        let duration_secs = self.clock.now_millis().saturating_sub(venue.since_ms) / 1000;
+        info!("{} recovered from maintenance after {}s", venue.exchange, duration_secs);
+        metrics::gauge!("exchange_in_maintenance", "exchange" => venue.exchange.clone()).set(0.0);
+        self.report_health(&venue.exchange, HealthStatus::Healthy, None);
+
+        // Only our own halt is released; halts placed by operators stay
+        self.halts.release(&HaltScope::Exchange(venue.exchange.clone()), OPERATOR);
+        self.raise(Alert::new(
+            &format!("exchange_maintenance:{}", venue.exchange),
+            AlertSeverity::Info,
+            &format!("{} recovered", venue.exchange),
+            &format!("Maintenance ended after {}s ({} probes)", duration_secs, venue.probes),
+            &venue.exchange,
+        ));
+    }
+
+    fn report_health(&self, exchange: &str, status: HealthStatus, signature: Option<&str>) {
+        let component = format!("exchange:{}", exchange);
+        let mut health = self.health.get_component(&component).unwrap_or_else(|| ComponentHealth {
+            component: component.clone(),
+            status,
+            last_check_ns: 0,
+            success_rate: 0.0,
+            avg_latency_us: 0.0,
+            details: HashMap::new(),
+        });
+        health.status = status;
+        health.last_check_ns = self.clock.now_ns();
+        match signature {
+            Some(signature) => {
+                health.details.insert("maintenance".to_string(), signature.to_string());
+            }
+            None => {
+                health.details.remove("maintenance");
+            }
+        }
+        self.health.update_component(health);
+    }
+
+    fn raise(&self, alert: Alert) {
+        if let Some(alerts) = self.alerts.clone() {
+            if let Ok(handle) = tokio::runtime::Handle::try_current() {
+                handle.spawn(async move {
+                    alerts.raise(alert).await;
+                });
+            }
+        }
+    }
+
+    pub fn spawn(self: Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.probe_due().await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::SimulatedClock;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    struct SwitchProbe(AtomicBool);
+
+    #[async_trait::async_trait]
+    impl MaintenanceProbe for SwitchProbe {
+        async fn probe(&self, exchange: &str) -> AdapterResult<()> {
+            if self.0.load(Ordering::SeqCst) {
+                Ok(())
+            } else {
+                Err(AdapterError::Exchange { exchange: exchange.into(), code: "503".into(), message: String::new() })
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_detects_maintenance_and_recovers_with_backoff() {
+        let clock = Arc::new(SimulatedClock::from_nanos(1_000_000_000_000));
+        let health = Arc::new(HealthSnapshot::new());
+        let halts = Arc::new(HaltRegistry::new());
+        let probe = Arc::new(SwitchProbe(AtomicBool::new(false)));
+        let detector = MaintenanceDetector::new(MaintenanceConfig::default(), health.clone(), halts.clone())
+            .with_probe(probe.clone())
+            .with_clock(clock.clone());
+
+        let ok = ExchangeSignal::Http { status: 400, body: "bad symbol".into() };
+        assert!(!detector.observe("binance", &ok).await);
+        let close = ExchangeSignal::WsClose { code: 1012, reason: "service restart".into() };
+        assert!(detector.observe("OKX", &close).await);
+        assert!(!detector.observe("okx", &close).await);
+        let text = ExchangeSignal::ExchangeError { code: "1".into(), message: "System-Maintenance".into() };
+        assert_eq!(detector.signature("huobi", &text).as_deref(), Some("error 1"));
+
+        assert!(detector.in_maintenance("okx"));
+        assert_eq!(health.get_component("exchange:okx").unwrap().status, HealthStatus::Unhealthy);
+        assert_eq!(halts.active()[0].scope, HaltScope::Exchange("okx".into()));
+
+        // Failed probes double the backoff: 10s -> 20s -> 40s
+        clock.advance(chrono::Duration::seconds(10));
+        detector.probe_due().await;
+        assert_eq!(detector.active()[0].backoff_secs, 20);
+        clock.advance(chrono::Duration::seconds(10));
+        detector.probe_due().await;
+        assert_eq!(detector.active()[0].probes, 1);
+        clock.advance(chrono::Duration::seconds(10));
+        detector.probe_due().await;
+        assert_eq!(detector.active()[0].backoff_secs, 40);
+
+        probe.0.store(true, Ordering::SeqCst);
+        for _ in 0..2 {
+            clock.advance(chrono::Duration::seconds(40));
+            detector.probe_due().await;
+        }
+        assert!(!detector.in_maintenance("okx"));
+        assert!(halts.active().is_empty());
+        assert_eq!(health.get_component("exchange:okx").unwrap().status, HealthStatus::Healthy);
+    }
+
+    #[tokio::test]
+    async fn test_recovery_keeps_operator_halt() {
+        let clock = Arc::new(SimulatedClock::from_nanos(1_000_000_000_000));
+        let halts = Arc::new(HaltRegistry::new());
+        let probe = Arc::new(SwitchProbe(AtomicBool::new(true)));
+        let detector = MaintenanceDetector::new(MaintenanceConfig::default(), Arc::new(HealthSnapshot::new()), halts.clone())
+            .with_probe(probe)
+            .with_clock(clock.clone());
+        halts.halt(HaltScope::Exchange("okx".into()), "manual", "ops").await.unwrap();
+
+        let close = ExchangeSignal::WsClose { code: 1012, reason: "service restart".into() };
+        assert!(detector.observe("okx", &close).await);
+        assert_eq!(halts.active().len(), 2);
+        for _ in 0..2 {
+            clock.advance(chrono::Duration::seconds(10));
+            detector.probe_due().await;
+        }
+        assert!(!detector.in_maintenance("okx"));
+        let remaining = halts.active();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].operator, "ops");
+    }
+}
@@ -0,0 +1,389 @@
+//! Post-trade markout analysis
+//!
+//! For every fill, markouts against the consolidated mid are computed at
+//! fixed horizons (1s/5s/30s by default) and aggregated per
+//! strategy/venue/symbol to measure adverse selection. The execution
+//! adapter registers every child-order fill; completed markouts and the
+//! aggregates are persisted to a JSON file and reloaded on start.
+
+use crate::execution_policy::ExecutionPolicy;
+use crate::{AdapterError, AdapterResult};
+use common::{ArbitrageOpportunity, ExecutionResult, NormalizedSnapshot, SharedClock, Side};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+/// A fill to be marked out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fill {
+    pub strategy: String,
+    pub exchange: String,
+    pub symbol: String,
+    pub side: Side,
+    pub price: f64,
+    pub quantity: f64,
+    pub filled_at_ns: u64,
+}
+
+/// Markout of one fill at one horizon
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarkoutRecord {
+    pub fill: Fill,
+    pub horizon_ms: u64,
+    /// Consolidated mid at the horizon
+    pub mid: f64,
+    /// Signed markout in bps; negative means the market moved against us
+    pub markout_bps: f64,
+}
+
+/// Running markout statistics for a (strategy, venue, symbol, horizon)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MarkoutAggregate {
+    pub count: u64,
+    pub sum_bps: f64,
+    pub sum_sq_bps: f64,
+}
+
+impl MarkoutAggregate {
+    pub fn mean_bps(&self) -> f64 {
+        if self.count == 0 { 0.0 } else { self.sum_bps / self.count as f64 }
+    }
+
+    pub fn std_bps(&self) -> f64 {
+        if self.count < 2 {
+            return 0.0;
+        }
+        let mean = self.mean_bps();
+        (self.sum_sq_bps / self.count as f64 - mean * mean).max(0.0).sqrt()
+    }
+}
+
+/// Aggregation key: (strategy, venue, symbol, horizon_ms)
+pub type MarkoutKey = (String, String, String, u64);
+
+struct PendingMarkout {
+    fill: Fill,
+    due_ns: u64,
+    horizon_ms: u64,
+}
+
+/// Markout engine configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarkoutConfig {
+    /// Markout horizons
+    pub horizons: Vec<Duration>,
+    /// Completed records kept in memory
+    pub max_records: usize,
+    /// Records and aggregates are persisted here when set
+    #[serde(default)]
+    pub persist_path: Option<PathBuf>,
+    #[serde(default = "default_persist_interval_secs")]
+    pub persist_interval_secs: u64,
+}
+
+fn default_persist_interval_secs() -> u64 {
+    60
+}
+
+/// On-disk form of completed markouts; pending ones are not kept
+#[derive(Serialize, Deserialize)]
+struct PersistedMarkouts {
+    records: Vec<MarkoutRecord>,
+    aggregates: Vec<(MarkoutKey, MarkoutAggregate)>,
+}
+
+impl Default for MarkoutConfig {
+    fn default() -> Self {
+        Self {
+            horizons: vec![
+                Duration::from_secs(1),
+                Duration::from_secs(5),
+                Duration::from_secs(30),
+            ],
+            max_records: 10_000,
+            persist_path: None,
+            persist_interval_secs: default_persist_interval_secs(),
+        }
+    }
+}
+
+/// Markout engine
+pub struct MarkoutEngine {
+    config: MarkoutConfig,
+    pending: Mutex<Vec<PendingMarkout>>,
+    records: Mutex<VecDeque<MarkoutRecord>>,
+    aggregates: RwLock<HashMap<MarkoutKey, MarkoutAggregate>>,
+    clock: SharedClock,
+}
+
+impl MarkoutEngine {
+    pub fn new(config: MarkoutConfig) -> Self {
+        Self {
+            config,
+            pending: Mutex::new(Vec::new()),
+            records: Mutex::new(VecDeque::new()),
+            aggregates: RwLock::new(HashMap::new()),
+            clock: common::clock::system_clock(),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Register a fill; one pending markout is created per horizon
+    pub fn record_fill(&self, fill: Fill) {
+        let mut pending = self.pending.lock();
+        for horizon in &self.config.horizons {
+            pending.push(PendingMarkout {
+                fill: fill.clone(),
+                due_ns: fill.filled_at_ns + horizon.as_nanos() as u64,
+                horizon_ms: horizon.as_millis() as u64,
+            });
+        }
+    }
+
+    /// Register every child-order fill of an execution, stamped with the
+    /// current time
+    pub fn record_execution(&self, opportunity: &ArbitrageOpportunity, result: &ExecutionResult) {
+        let filled_at_ns = self.clock.now_ns();
+        for slice in &result.slices {
+            let Some(leg) = opportunity.legs.get(slice.leg_index) else { continue };
+            if slice.quantity <= 0.0 {
+                continue;
+            }
+            self.record_fill(Fill {
+                strategy: opportunity.strategy_name.clone(),
+                exchange: leg.exchange.as_str().to_string(),
+                symbol: leg.symbol.as_str().to_string(),
+                side: leg.side,
+                price: slice.price,
+                quantity: slice.quantity,
+                filled_at_ns,
+            });
+        }
+    }
+
+    /// Feed a consolidated snapshot; completes all due markouts for its symbol
+    pub fn on_snapshot(&self, snapshot: &NormalizedSnapshot) {
+        let mid = snapshot.weighted_mid_price.to_f64();
+        if mid > 0.0 {
+            self.on_mid(snapshot.symbol.as_str(), mid, snapshot.timestamp_ns);
+        }
+    }
+
+    /// Feed a consolidated mid for `symbol` observed at `now_ns`
+    pub fn on_mid(&self, symbol: &str, mid: f64, now_ns: u64) {
+        let due: Vec<PendingMarkout> = {
+            let mut pending = self.pending.lock();
+            let (due, rest): (Vec<_>, Vec<_>) = pending
+                .drain(..)
+                .partition(|p| p.fill.symbol == symbol && p.due_ns <= now_ns);
+            *pending = rest;
+            due
+        };
+
+        for p in due {
+            let record = Self::markout(p.fill, p.horizon_ms, mid);
+            self.store(record);
+        }
+    }
+
+    fn markout(fill: Fill, horizon_ms: u64, mid: f64) -> MarkoutRecord {
+        let sign = match fill.side {
+            Side::Buy => 1.0,
+            Side::Sell => -1.0,
+        };
+        let markout_bps = if fill.price > 0.0 {
+            sign * (mid - fill.price) / fill.price * 10_000.0
+        } else {
+            0.0
+        };
+        MarkoutRecord { fill, horizon_ms, mid, markout_bps }
+    }
+
+    fn store(&self, record: MarkoutRecord) {
+        {
+            let key = (
+                record.fill.strategy.clone(),
+                record.fill.exchange.clone(),
+                record.fill.symbol.clone(),
+                record.horizon_ms,
+            );
+            let mut aggregates = self.aggregates.write();
+            let agg = aggregates.entry(key).or_default();
+            agg.count += 1;
+            agg.sum_bps += record.markout_bps;
+            agg.sum_sq_bps += record.markout_bps * record.markout_bps;
+        }
+
+        let mut records = self.records.lock();
+        records.push_back(record);
+        while records.len() > self.config.max_records {
+            records.pop_front();
+        }
+    }
+
+    /// Most recent completed markouts
+    pub fn recent_records(&self, limit: usize) -> Vec<MarkoutRecord> {
+        self.records.lock().iter().rev().take(limit).cloned().collect()
+    }
+
+    /// All aggregates
+    pub fn aggregates(&self) -> HashMap<MarkoutKey, MarkoutAggregate> {
+        self.aggregates.read().clone()
+    }
+
+    /// Average adverse selection (positive bps = cost) for a venue at a horizon,
+    /// across strategies and symbols
+    pub fn adverse_selection_bps(&self, venue: &str, horizon_ms: u64) -> Option<f64> {
+        let aggregates = self.aggregates.read();
+        let (count, sum) = aggregates
+            .iter()
+            .filter(|((_, v, _, h), _)| v == venue && *h == horizon_ms)
+            .fold((0u64, 0.0), |(c, s), (_, a)| (c + a.count, s + a.sum_bps));
+        if count == 0 {
+            None
+        } else {
+            Some((-sum / count as f64).max(0.0))
+        }
+    }
+
+    /// Write completed records and aggregates to the configured file
+    pub fn persist(&self) -> AdapterResult<()> {
+        let Some(path) = &self.config.persist_path else { return Ok(()) };
+        let state = PersistedMarkouts {
+            records: self.records.lock().iter().cloned().collect(),
+            aggregates: self.aggregates.read().iter().map(|(k, a)| (k.clone(), a.clone())).collect(),
+        };
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec(&state)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Load records and aggregates from the configured file, if it exists
+    pub fn restore(&self) -> AdapterResult<usize> {
+        let Some(path) = &self.config.persist_path else { return Ok(0) };
+        if !path.exists() {
+            return Ok(0);
+        }
+        let content = std::fs::read(path)?;
+        let state: PersistedMarkouts = serde_json::from_slice(&content)
+            .map_err(|e| AdapterError::Configuration(format!("invalid markout file {}: {}", path.display(), e)))?;
+        let count = state.records.len();
+        {
+            let mut records = self.records.lock();
+            records.extend(state.records);
+            while records.len() > self.config.max_records {
+                records.pop_front();
+            }
+        }
+        self.aggregates.write().extend(state.aggregates);
+        info!("Restored {} markout records", count);
+        Ok(count)
+    }
+
+    /// Complete markouts from the snapshot stream and persist periodically
+    pub fn spawn(self: Arc<Self>, mut snapshots: broadcast::Receiver<NormalizedSnapshot>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut persist = tokio::time::interval(Duration::from_secs(self.config.persist_interval_secs.max(1)));
+            loop {
+                tokio::select! {
+                    received = snapshots.recv() => match received {
+                        Ok(snapshot) => self.on_snapshot(&snapshot),
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!("Markout engine lagged, skipped {} snapshots", skipped);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                    _ = persist.tick() => {
+                        if let Err(e) = self.persist() {
+                            warn!("Failed to persist markouts: {}", e);
+                        }
+                    }
+                }
+            }
+            if let Err(e) = self.persist() {
+                warn!("Failed to persist markouts: {}", e);
+            }
+        })
+    }
+
+    /// Push per-venue adverse selection at the shortest horizon into the
+    /// maker/taker policy
+    pub fn feed_policy(&self, policy: &ExecutionPolicy) {
+        let Some(horizon) = self.config.horizons.iter().min() else { return };
+        let horizon_ms = horizon.as_millis() as u64;
+        let venues: Vec<String> = self
+            .aggregates
+            .read()
+            .keys()
+            .map(|(_, v, _, _)| v.clone())
+            .collect();
+        for venue in venues {
+            if let Some(bps) = self.adverse_selection_bps(&venue, horizon_ms) {
+                policy.set_adverse_selection_bps(&venue, bps);
+            }
+        }
+    }
+}
+
+impl Default for MarkoutEngine {
+    fn default() -> Self {
+        Self::new(MarkoutConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::testing::{book, inter_exchange_opportunity, snapshot};
+    use common::{SimulatedClock, SliceReport};
+
+    fn slice(leg_index: usize, price: f64) -> SliceReport {
+        SliceReport { leg_index, slice_index: 0, order_id: format!("order_{}", leg_index), quantity: 1.0, price, slippage_bps: 0.0 }
+    }
+
+    #[test]
+    fn test_execution_fills_are_marked_out_and_persisted() {
+        let path = std::env::temp_dir().join(format!("celue_markout_{}.json", std::process::id()));
+        let config = MarkoutConfig {
+            horizons: vec![Duration::from_secs(1), Duration::from_secs(5)],
+            persist_path: Some(path.clone()),
+            ..Default::default()
+        };
+        let clock = Arc::new(SimulatedClock::from_nanos(1_000_000_000));
+        let engine = MarkoutEngine::new(config.clone()).with_clock(clock.clone());
+
+        // Bought on binance at 100, sold on okx at 100
+        let opportunity = inter_exchange_opportunity("inter_exchange", "BTCUSDT", 0.01);
+        let mut result = ExecutionResult::accepted(opportunity.id.to_string(), vec![], None);
+        result.slices = vec![slice(0, 100.0), slice(1, 100.0)];
+        engine.record_execution(&opportunity, &result);
+
+        // Not due yet
+        engine.on_snapshot(&snapshot("BTCUSDT", vec![book("binance", "BTCUSDT", 99.0, 99.0, 1)], 1_500_000_000));
+        assert!(engine.recent_records(10).is_empty());
+
+        // Mid fell to 99 after one second: the buy lost 100 bps, the sell gained 100
+        engine.on_snapshot(&snapshot("BTCUSDT", vec![book("binance", "BTCUSDT", 99.0, 99.0, 1)], 2_000_000_000));
+        let records = engine.recent_records(10);
+        assert_eq!(records.len(), 2);
+        assert!(records.iter().all(|r| r.horizon_ms == 1_000 && (r.markout_bps.abs() - 100.0).abs() < 1e-9));
+        assert!((engine.adverse_selection_bps("binance", 1_000).unwrap() - 100.0).abs() < 1e-9);
+        assert_eq!(engine.adverse_selection_bps("okx", 1_000), Some(0.0));
+
+        engine.persist().unwrap();
+        let restored = MarkoutEngine::new(config);
+        assert_eq!(restored.restore().unwrap(), 2);
+        assert!((restored.adverse_selection_bps("binance", 1_000).unwrap() - 100.0).abs() < 1e-9);
+        let _ = std::fs::remove_file(path);
+    }
+}
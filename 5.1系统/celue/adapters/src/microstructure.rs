@@ -0,0 +1,286 @@
+//! Order book microstructure signals for execution timing
+//!
+//! Per exchange and symbol:
+//! - order book imbalance over the top levels, in [-1, 1] (positive =
+//!   bid-heavy);
+//! - trade-flow toxicity, a VPIN-style estimate: trades are grouped into
+//!   equal-volume buckets and the signal is the mean absolute buy/sell
+//!   volume difference per bucket over the recent buckets;
+//! - quote flicker, the rate of top-of-book price changes per second.
+//!
+//! `assess` turns the signals into a timing decision for an opportunity:
+//! proceed, wait briefly, or skip when adverse selection risk is high.
+
+use common::{ArbitrageOpportunity, NormalizedSnapshot, OrderBook, Side};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::warn;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MicrostructureConfig {
+    /// Book levels counted for imbalance
+    pub imbalance_levels: usize,
+    /// Volume per toxicity bucket (base units)
+    pub bucket_volume: f64,
+    /// Buckets averaged for the toxicity estimate
+    pub toxicity_buckets: usize,
+    /// Window for the flicker rate (ms)
+    pub flicker_window_ms: u64,
+    /// Imbalance against a leg beyond this delays it
+    pub max_adverse_imbalance: f64,
+    /// Toxicity above this skips the opportunity
+    pub max_toxicity: f64,
+    /// Flicker above this (changes per second) delays execution
+    pub max_flicker_per_sec: f64,
+    /// Wait before re-checking a delayed opportunity (ms)
+    pub delay_ms: u64,
+}
+
+impl Default for MicrostructureConfig {
+    fn default() -> Self {
+        Self {
+            imbalance_levels: 5,
+            bucket_volume: 10.0,
+            toxicity_buckets: 50,
+            flicker_window_ms: 1_000,
+            max_adverse_imbalance: 0.7,
+            max_toxicity: 0.6,
+            max_flicker_per_sec: 20.0,
+            delay_ms: 50,
+        }
+    }
+}
+
+/// An executed trade as reported by the exchange
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradePrint {
+    pub exchange: String,
+    pub symbol: String,
+    pub price: f64,
+    pub quantity: f64,
+    /// Aggressor side
+    pub side: Side,
+    pub timestamp_ns: u64,
+}
+
+/// Current signals for one exchange/symbol
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct MicrostructureSignals {
+    pub imbalance: f64,
+    /// None until enough volume has traded to fill a bucket
+    pub toxicity: Option<f64>,
+    pub flicker_per_sec: f64,
+}
+
+/// What the executor should do with an opportunity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TimingDecision {
+    Proceed,
+    Delay { delay_ms: u64, reason: String },
+    Skip { reason: String },
+}
+
+#[derive(Debug, Default)]
+struct SeriesState {
+    imbalance: f64,
+    last_top: Option<(f64, f64)>,
+    top_changes: VecDeque<u64>,
+    bucket_buy: f64,
+    bucket_sell: f64,
+    /// |buy - sell| / bucket volume of completed buckets
+    buckets: VecDeque<f64>,
+}
+
+/// Microstructure signal tracker
+pub struct MicrostructureMonitor {
+    config: MicrostructureConfig,
+    series: RwLock<HashMap<(String, String), SeriesState>>,
+}
+
+impl MicrostructureMonitor {
+    pub fn new(config: MicrostructureConfig) -> Self {
+        Self { config, series: RwLock::new(HashMap::new()) }
+    }
+
+    fn key(exchange: &str, symbol: &str) -> (String, String) {
+        (exchange.to_lowercase(), symbol.to_string())
+    }
+
+    /// Update imbalance and flicker from a book
+    pub fn on_book(&self, book: &OrderBook) {
+        let levels = self.config.imbalance_levels.max(1);
+        let bid: f64 = book.bid_quantities.iter().take(levels).map(|q| q.to_f64()).sum();
+        let ask: f64 = book.ask_quantities.iter().take(levels).map(|q| q.to_f64()).sum();
+        let top = book.best_bid().zip(book.best_ask()).map(|(b, a)| (b.price.to_f64(), a.price.to_f64()));
+        let window_ns = self.config.flicker_window_ms * 1_000_000;
+
+        let mut series = self.series.write();
+        let state = series.entry(Self::key(book.exchange.as_str(), book.symbol.as_str())).or_default();
+        if bid + ask > 0.0 {
+            state.imbalance = (bid - ask) / (bid + ask);
+        }
+        if top.is_some() && state.last_top.is_some() && top != state.last_top {
+            state.top_changes.push_back(book.timestamp_ns);
+        }
+        if top.is_some() {
+            state.last_top = top;
+        }
+        while state.top_changes.front().is_some_and(|t| book.timestamp_ns.saturating_sub(*t) > window_ns) {
+            state.top_changes.pop_front();
+        }
+    }
+
+    pub fn on_snapshot(&self, snapshot: &NormalizedSnapshot) {
+        for book in &snapshot.exchanges {
+            self.on_book(book);
+        }
+    }
+
+    /// Add a trade to the current volume bucket
+    pub fn on_trade(&self, trade: &TradePrint) {
+        let bucket_volume = self.config.bucket_volume.max(f64::EPSILON);
+        let mut series = self.series.write();
+        let state = series.entry(Self::key(&trade.exchange, &trade.symbol)).or_default();
+        let mut remaining = trade.quantity.max(0.0);
+        // A large trade can fill several buckets
+        while remaining > 0.0 {
+            let room = bucket_volume - state.bucket_buy - state.bucket_sell;
+            let fill = remaining.min(room);
+            match trade.side {
+                Side::Buy => state.bucket_buy += fill,
+                Side::Sell => state.bucket_sell += fill,
+            }
+            remaining -= fill;
+            if state.bucket_buy + state.bucket_sell >= bucket_volume - 1e-12 {
+                state.buckets.push_back((state.bucket_buy - state.bucket_sell).abs() / bucket_volume);
+                state.bucket_buy = 0.0;
+                state.bucket_sell = 0.0;
+                while state.buckets.len() > self.config.toxicity_buckets.max(1) {
+                    state.buckets.pop_front();
+                }
+            }
+        }
+    }
+
+    pub fn signals(&self, exchange: &str, symbol: &str) -> Option<MicrostructureSignals> {
+        let series = self.series.read();
+        let state = series.get(&Self::key(exchange, symbol))?;
+        let window_secs = self.config.flicker_window_ms.max(1) as f64 / 1000.0;
+        Some(MicrostructureSignals {
+            imbalance: state.imbalance,
+            toxicity: (!state.buckets.is_empty())
+                .then(|| state.buckets.iter().sum::<f64>() / state.buckets.len() as f64),
+            flicker_per_sec: state.top_changes.len() as f64 / window_secs,
+        })
+    }
+
+    /// Timing decision for one leg
+    pub fn assess_leg(&self, exchange: &str, symbol: &str, side: Side) -> TimingDecision {
+        let Some(signals) = self.signals(exchange, symbol) else {
+            return TimingDecision::Proceed;
+        };
+        if let Some(toxicity) = signals.toxicity.filter(|t| *t > self.config.max_toxicity) {
+            return TimingDecision::Skip {
+                reason: format!("{} {} toxic flow {:.2}", exchange, symbol, toxicity),
+            };
+        }
+        // Buying into an ask-heavy book (or selling into a bid-heavy one)
+        // trades against the pressure
+        let adverse = match side {
+            Side::Buy => -signals.imbalance,
+            Side::Sell => signals.imbalance,
+        };
+        if adverse > self.config.max_adverse_imbalance {
+            return TimingDecision::Delay {
+                delay_ms: self.config.delay_ms,
+                reason: format!("{} {} adverse imbalance {:.2}", exchange, symbol, signals.imbalance),
+            };
+        }
+        if signals.flicker_per_sec > self.config.max_flicker_per_sec {
+            return TimingDecision::Delay {
+                delay_ms: self.config.delay_ms,
+                reason: format!("{} {} quote flicker {:.1}/s", exchange, symbol, signals.flicker_per_sec),
+            };
+        }
+        TimingDecision::Proceed
+    }
+
+    /// Most restrictive decision across the opportunity's legs
+    pub fn assess(&self, opportunity: &ArbitrageOpportunity) -> TimingDecision {
+        let mut decision = TimingDecision::Proceed;
+        for leg in &opportunity.legs {
+            match self.assess_leg(leg.exchange.as_str(), leg.symbol.as_str(), leg.side) {
+                skip @ TimingDecision::Skip { .. } => return skip,
+                delay @ TimingDecision::Delay { .. } if decision == TimingDecision::Proceed => decision = delay,
+                _ => {}
+            }
+        }
+        decision
+    }
+
+    /// Update from the snapshot stream
+    pub fn spawn(self: Arc<Self>, mut snapshots: broadcast::Receiver<NormalizedSnapshot>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match snapshots.recv().await {
+                    Ok(snapshot) => self.on_snapshot(&snapshot),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Microstructure monitor lagged, skipped {} snapshots", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+}
+
+impl Default for MicrostructureMonitor {
+    fn default() -> Self {
+        Self::new(MicrostructureConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::testing::sized_book;
+
+    fn book(bid_qty: f64, ask_qty: f64, bid: f64, ts: u64) -> OrderBook {
+        sized_book("binance", "BTCUSDT", (bid, bid_qty), (bid + 1.0, ask_qty), ts)
+    }
+
+    fn trade(side: Side, quantity: f64) -> TradePrint {
+        TradePrint {
+            exchange: "binance".into(),
+            symbol: "BTCUSDT".into(),
+            price: 100.0,
+            quantity,
+            side,
+            timestamp_ns: 0,
+        }
+    }
+
+    #[test]
+    fn test_imbalance_toxicity_and_flicker() {
+        let monitor = MicrostructureMonitor::default();
+        monitor.on_book(&book(1.0, 9.0, 100.0, 0));
+        let signals = monitor.signals("binance", "BTCUSDT").unwrap();
+        assert!((signals.imbalance + 0.8).abs() < 1e-9);
+        assert!(matches!(monitor.assess_leg("binance", "BTCUSDT", Side::Buy), TimingDecision::Delay { .. }));
+        assert_eq!(monitor.assess_leg("binance", "BTCUSDT", Side::Sell), TimingDecision::Proceed);
+
+        // One-sided flow fills buckets with full imbalance
+        monitor.on_trade(&trade(Side::Buy, 25.0));
+        assert_eq!(monitor.signals("binance", "BTCUSDT").unwrap().toxicity, Some(1.0));
+        assert!(matches!(monitor.assess_leg("binance", "BTCUSDT", Side::Sell), TimingDecision::Skip { .. }));
+
+        let flicker = MicrostructureMonitor::default();
+        for i in 0..30u64 {
+            flicker.on_book(&book(5.0, 5.0, 100.0 + (i % 2) as f64, i * 10_000_000));
+        }
+        assert!(flicker.signals("binance", "BTCUSDT").unwrap().flicker_per_sec > 20.0);
+    }
+}
@@ -3,6 +3,8 @@
 //! Provides reliable, low-latency messaging for system components.
 //! Implements both request/reply and publish/subscribe patterns.
 
+use crate::jetstream::DurableConsumer;
+use crate::subject_metrics::SubjectMetrics;
 use crate::{Adapter, AdapterError, AdapterResult};
 use async_nats::{jetstream, Client, ConnectOptions};
 use futures_util::stream::StreamExt;
@@ -75,6 +77,19 @@ pub struct ConsumerConfig {
     pub ack_policy: String, // "none", "all", "explicit"
     pub max_deliver: i32,
     pub filter_subject: Option<String>,
+    /// Redelivery timeout for unacked messages
+    #[serde(default = "default_ack_wait")]
+    pub ack_wait: Duration,
+    /// Replay from this stream sequence instead of the consumer's position
+    #[serde(default)]
+    pub start_sequence: Option<u64>,
+    /// Where messages go after `max_deliver` failed attempts
+    #[serde(default)]
+    pub dead_letter_subject: Option<String>,
+}
+
+fn default_ack_wait() -> Duration {
+    Duration::from_secs(30)
 }
 
 impl Default for NatsConfig {
@@ -108,6 +123,24 @@ impl Default for NatsConfig {
                     max_age: Duration::from_secs(24 * 60 * 60),
                     replicas: 1,
                 },
+                StreamConfig {
+                    name: "ALERTS".to_string(),
+                    subjects: vec!["alerts.*".to_string()],
+                    retention: "limits".to_string(),
+                    max_msgs: 50000,
+                    max_bytes: 100 * 1024 * 1024, // 100MB
+                    max_age: Duration::from_secs(7 * 24 * 60 * 60),
+                    replicas: 1,
+                },
+                StreamConfig {
+                    name: "DEAD_LETTER".to_string(),
+                    subjects: vec!["dlq.>".to_string()],
+                    retention: "limits".to_string(),
+                    max_msgs: 100000,
+                    max_bytes: 500 * 1024 * 1024, // 500MB
+                    max_age: Duration::from_secs(14 * 24 * 60 * 60),
+                    replicas: 1,
+                },
                 StreamConfig {
                     name: "MARKET_DATA".to_string(),
                     subjects: vec!["market.data.*".to_string()],
@@ -127,6 +160,45 @@ impl Default for NatsConfig {
                     ack_policy: "explicit".to_string(),
                     max_deliver: 3,
                     filter_subject: Some("market.data.normalized.*".to_string()),
+                    ack_wait: default_ack_wait(),
+                    start_sequence: None,
+                    dead_letter_subject: None,
+                },
+                ConsumerConfig {
+                    name: "opportunity-executor".to_string(),
+                    stream_name: "OPPORTUNITIES".to_string(),
+                    deliver_subject: None,
+                    durable_name: Some("opportunity-executor".to_string()),
+                    ack_policy: "explicit".to_string(),
+                    max_deliver: 5,
+                    filter_subject: Some("strategy.opportunities.*".to_string()),
+                    ack_wait: Duration::from_secs(5),
+                    start_sequence: None,
+                    dead_letter_subject: Some("dlq.opportunities".to_string()),
+                },
+                ConsumerConfig {
+                    name: "execution-recorder".to_string(),
+                    stream_name: "EXECUTIONS".to_string(),
+                    deliver_subject: None,
+                    durable_name: Some("execution-recorder".to_string()),
+                    ack_policy: "explicit".to_string(),
+                    max_deliver: 5,
+                    filter_subject: Some("execution.results.*".to_string()),
+                    ack_wait: default_ack_wait(),
+                    start_sequence: None,
+                    dead_letter_subject: Some("dlq.executions".to_string()),
+                },
+                ConsumerConfig {
+                    name: "alert-dispatcher".to_string(),
+                    stream_name: "ALERTS".to_string(),
+                    deliver_subject: None,
+                    durable_name: Some("alert-dispatcher".to_string()),
+                    ack_policy: "explicit".to_string(),
+                    max_deliver: 5,
+                    filter_subject: Some("alerts.*".to_string()),
+                    ack_wait: default_ack_wait(),
+                    start_sequence: None,
+                    dead_letter_subject: Some("dlq.alerts".to_string()),
                 },
             ],
         }
@@ -291,15 +363,18 @@ pub struct NatsAdapter {
     config: Option<NatsConfig>,
     client: Option<Client>,
     jetstream: Option<jetstream::Context>,
+    consumers: Vec<Arc<DurableConsumer>>,
     router: Arc<MessageRouter>,
     shutdown_tx: Option<oneshot::Sender<()>>,
     running: Arc<parking_lot::Mutex<bool>>,
     test_sink: Option<Arc<parking_lot::Mutex<Vec<(String, NatsMessage)>>>>,
+    subject_metrics: Arc<SubjectMetrics>,
 }
 
 impl NatsAdapter {
     pub fn set_test_sink(&mut self, sink: Arc<parking_lot::Mutex<Vec<(String, NatsMessage)>>>) { self.test_sink = Some(sink); }
     pub fn router(&self) -> Arc<MessageRouter> { self.router.clone() }
+    pub fn subject_metrics(&self) -> Arc<SubjectMetrics> { self.subject_metrics.clone() }
     pub fn register_handler(&self, subject_prefix: &str, handler: Arc<dyn MessageHandler>) {
         self.router.register_handler(subject_prefix.to_string(), handler);
     }
@@ -325,7 +400,10 @@ impl NatsAdapter {
         let client = self.client.as_ref().ok_or(AdapterError::NotInitialized)?;
         let content_type = "application/bincode"; // default fast path
         let payload = Self::serialize_message(message, content_type)?;
+        let payload_bytes = payload.len();
+        let started = std::time::Instant::now();
         client.publish(subject.to_string(), payload.into()).await.map_err(|e| AdapterError::NatsPublish(e.to_string()))?;
+        self.subject_metrics.record_publish(subject, payload_bytes, started.elapsed());
         Ok(())
     }
     
@@ -401,9 +479,29 @@ impl NatsAdapter {
             }
         }
         
+        // Durable consumers survive restarts and resume from their last ack
+        let mut consumers = Vec::new();
+        for consumer_config in &config.consumers {
+            let consumer = DurableConsumer::create(&jetstream, consumer_config.clone())
+                .await?
+                .with_metrics(self.subject_metrics.clone());
+            info!("Durable consumer '{}' ready on '{}'", consumer_config.name, consumer_config.stream_name);
+            consumers.push(Arc::new(consumer));
+        }
+
+        self.consumers = consumers;
         self.jetstream = Some(jetstream);
         Ok(())
     }
+
+    pub fn jetstream(&self) -> Option<&jetstream::Context> {
+        self.jetstream.as_ref()
+    }
+
+    /// Durable consumer by name, to be run with a `JetStreamHandler`
+    pub fn consumer(&self, name: &str) -> Option<Arc<DurableConsumer>> {
+        self.consumers.iter().find(|c| c.name() == name).cloned()
+    }
 }
 
 #[async_trait::async_trait]
@@ -513,10 +611,12 @@ impl Default for NatsAdapter {
             config: None,
             client: None,
             jetstream: None,
+            consumers: Vec::new(),
             router: Arc::new(MessageRouter::new()),
             shutdown_tx: None,
             running: Arc::new(parking_lot::Mutex::new(false)),
             test_sink: None,
+            subject_metrics: Arc::new(SubjectMetrics::default()),
         }
     }
 }
@@ -532,7 +632,7 @@ mod tests {
         assert_eq!(config.servers, vec!["nats://localhost:4222"]);
         assert_eq!(config.name, "qingxi-strategy");
         assert!(config.enable_jetstream);
-        assert_eq!(config.streams.len(), 3);
+        assert_eq!(config.streams.len(), 5);
     }
     
     #[tokio::test]
@@ -0,0 +1,444 @@
+//! Latency-aware opportunity expiry
+//!
+//! Opportunities used to get a fixed 150ms TTL wherever they came from.
+//! The TTL model computes expiry from three inputs. The slowest leg's
+//! round-trip latency and book update interval set how long a quote stays
+//! credible without a confirming tick. The symbol's volatility caps how
+//! long its margin can be expected to survive.
+//!
+//! The pool tracks live opportunities. A tick that still shows a leg's
+//! price extends its expiry. A tick that no longer shows the price
+//! expires it at once. Every change is published to the frontend
+//! stream, so the pool and the stream agree on what is live. With a
+//! conflict resolver, overlapping opportunities from different strategies
+//! are deduplicated on admission.
+
+use crate::analytics::MarketAnalytics;
+use crate::conflict::{ConflictResolver, Resolution};
+use crate::venue_selector::VenueSelector;
+use crate::ws_gateway::WsGateway;
+use common::{ArbitrageOpportunity, OrderBook, Side};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::debug;
+use uuid::Uuid;
+
+/// Frontend stream topic for opportunity lifecycle events
+pub const OPPORTUNITY_TOPIC: &str = "opportunities";
+const OPPORTUNITY_SCHEMA: &str = "opportunity_lifecycle.v1";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TtlModelConfig {
+    pub min_ttl_ms: u64,
+    pub max_ttl_ms: u64,
+    /// Used until latency has been observed for a leg's exchange
+    pub default_ttl_ms: u64,
+    /// Multiples of round-trip latency a quote stays credible
+    pub latency_multiplier: f64,
+    /// Multiples of the book update interval a quote stays credible
+    pub update_interval_multiplier: f64,
+    /// Fraction of the expected time for volatility to erase the margin
+    pub volatility_horizon_fraction: f64,
+    /// Weight of the newest book interval in its moving average
+    pub interval_smoothing: f64,
+}
+
+impl Default for TtlModelConfig {
+    fn default() -> Self {
+        Self {
+            min_ttl_ms: 50,
+            max_ttl_ms: 2_000,
+            default_ttl_ms: 150,
+            latency_multiplier: 2.0,
+            update_interval_multiplier: 2.0,
+            volatility_horizon_fraction: 0.5,
+            interval_smoothing: 0.1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct FeedTiming {
+    last_update_ns: u64,
+    interval_ms: Option<f64>,
+}
+
+/// Computes opportunity TTLs from venue latency, feed rate and volatility
+pub struct OpportunityTtlModel {
+    config: TtlModelConfig,
+    /// (exchange, symbol) -> book update timing
+    feeds: RwLock<HashMap<(String, String), FeedTiming>>,
+    venue_selector: Option<Arc<VenueSelector>>,
+    analytics: Option<Arc<MarketAnalytics>>,
+}
+
+impl OpportunityTtlModel {
+    pub fn new(config: TtlModelConfig) -> Self {
+        Self {
+            config,
+            feeds: RwLock::new(HashMap::new()),
+            venue_selector: None,
+            analytics: None,
+        }
+    }
+
+    /// Take round-trip latency from the venue selector
+    pub fn with_venue_selector(mut self, selector: Arc<VenueSelector>) -> Self {
+        self.venue_selector = Some(selector);
+        self
+    }
+
+    /// Cap TTLs by the volatility horizon from market analytics
+    pub fn with_analytics(mut self, analytics: Arc<MarketAnalytics>) -> Self {
+        self.analytics = Some(analytics);
+        self
+    }
+
+    /// Track the update interval of a book feed
+    pub fn on_book(&self, book: &OrderBook) {
+        let alpha = self.config.interval_smoothing.clamp(0.0, 1.0);
+        let mut feeds = self.feeds.write();
+        let feed = feeds
+            .entry((book.exchange.as_str().to_lowercase(), book.symbol.as_str().to_string()))
+            .or_default();
+        if feed.last_update_ns > 0 && book.timestamp_ns > feed.last_update_ns {
+            let interval = (book.timestamp_ns - feed.last_update_ns) as f64 / 1e6;
+            feed.interval_ms = Some(match feed.interval_ms {
+                Some(avg) => avg + alpha * (interval - avg),
+                None => interval,
+            });
+        }
+        feed.last_update_ns = feed.last_update_ns.max(book.timestamp_ns);
+    }
+
+    fn update_interval_ms(&self, exchange: &str, symbol: &str) -> Option<f64> {
+        self.feeds.read().get(&(exchange.to_lowercase(), symbol.to_string())).and_then(|f| f.interval_ms)
+    }
+
+    /// TTL for an opportunity in milliseconds
+    pub fn ttl_ms(&self, opportunity: &ArbitrageOpportunity) -> u64 {
+        let c = &self.config;
+        let mut floor: Option<f64> = None;
+        for leg in &opportunity.legs {
+            let latency = self.venue_selector.as_ref().and_then(|s| s.latency_ms(leg.exchange.as_str()));
+            let interval = self.update_interval_ms(leg.exchange.as_str(), leg.symbol.as_str());
+            if latency.is_none() && interval.is_none() {
+                continue;
+            }
+            let needed = c.latency_multiplier * latency.unwrap_or(0.0)
+                + c.update_interval_multiplier * interval.unwrap_or(0.0);
+            floor = Some(floor.map_or(needed, |f| f.max(needed)));
+        }
+        let Some(floor) = floor else {
+            return c.default_ttl_ms.clamp(c.min_ttl_ms, c.max_ttl_ms);
+        };
+
+        // Time for a one-sigma move to equal the margin: (margin / sigma_per_sec)^2
+        let margin = opportunity.net_profit_pct.to_f64().abs();
+        let horizon_ms = opportunity
+            .legs
+            .first()
+            .and_then(|leg| self.analytics.as_ref()?.daily_volatility(leg.symbol.as_str()))
+            .filter(|vol| *vol > 0.0 && margin > 0.0)
+            .map(|vol| {
+                let sigma_per_sec = vol / 86_400f64.sqrt();
+                (margin / sigma_per_sec).powi(2) * 1_000.0 * c.volatility_horizon_fraction
+            })
+            .unwrap_or(f64::INFINITY);
+
+        (floor.min(horizon_ms).min(c.max_ttl_ms as f64) as u64).clamp(c.min_ttl_ms, c.max_ttl_ms)
+    }
+
+    /// Set the opportunity's TTL from the model
+    pub fn assign(&self, opportunity: &mut ArbitrageOpportunity) -> u64 {
+        let ttl_ms = self.ttl_ms(opportunity);
+        opportunity.ttl_ns = ttl_ms * 1_000_000;
+        ttl_ms
+    }
+}
+
+impl Default for OpportunityTtlModel {
+    fn default() -> Self {
+        Self::new(TtlModelConfig::default())
+    }
+}
+
+/// Why an opportunity left the pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpiryReason {
+    TtlElapsed,
+    /// A tick no longer shows a leg's price
+    Invalidated,
+    Consumed,
+    /// A conflicting opportunity with a better risk-adjusted return replaced it
+    Superseded,
+    /// Trading was disabled for one of its symbols
+    Disabled,
+}
+
+/// Opportunity lifecycle event, as sent on the frontend stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum OpportunityLifecycle {
+    Opened { opportunity: ArbitrageOpportunity, expires_at_ns: u64 },
+    Refreshed { id: Uuid, expires_at_ns: u64 },
+    Expired { id: Uuid, reason: ExpiryReason },
+}
+
+struct PoolEntry {
+    opportunity: ArbitrageOpportunity,
+    expires_at_ns: u64,
+}
+
+/// Live opportunities with TTLs refreshed by confirming ticks
+pub struct OpportunityPool {
+    model: Arc<OpportunityTtlModel>,
+    entries: Mutex<HashMap<Uuid, PoolEntry>>,
+    events: broadcast::Sender<OpportunityLifecycle>,
+    gateway: Option<Arc<WsGateway>>,
+    resolver: Option<Arc<ConflictResolver>>,
+}
+
+impl OpportunityPool {
+    pub fn new(model: Arc<OpportunityTtlModel>) -> Self {
+        let (events, _) = broadcast::channel(1024);
+        Self {
+            model,
+            entries: Mutex::new(HashMap::new()),
+            events,
+            gateway: None,
+            resolver: None,
+        }
+    }
+
+    /// Deduplicate overlapping opportunities on `admit`
+    pub fn with_conflict_resolver(mut self, resolver: Arc<ConflictResolver>) -> Self {
+        self.resolver = Some(resolver);
+        self
+    }
+
+    pub fn conflict_resolver(&self) -> Option<&Arc<ConflictResolver>> {
+        self.resolver.as_ref()
+    }
+
+    /// Mirror lifecycle events to the frontend stream
+    pub fn with_gateway(mut self, gateway: Arc<WsGateway>) -> Self {
+        self.gateway = Some(gateway);
+        self
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<OpportunityLifecycle> {
+        self.events.subscribe()
+    }
+
+    fn emit(&self, event: OpportunityLifecycle) {
+        if let Some(gateway) = &self.gateway {
+            if let Err(e) = gateway.publish(OPPORTUNITY_TOPIC, OPPORTUNITY_SCHEMA, &event) {
+                debug!("Failed to publish opportunity event: {}", e);
+            }
+        }
+        let _ = self.events.send(event);
+    }
+
+    /// Assign a TTL and add the opportunity to the pool
+    pub fn insert(&self, mut opportunity: ArbitrageOpportunity) -> ArbitrageOpportunity {
+        self.model.assign(&mut opportunity);
+        let expires_at_ns = opportunity.created_at_ns.saturating_add(opportunity.ttl_ns);
+        self.entries.lock().insert(
+            opportunity.id,
+            PoolEntry { opportunity: opportunity.clone(), expires_at_ns },
+        );
+        self.emit(OpportunityLifecycle::Opened { opportunity: opportunity.clone(), expires_at_ns });
+        opportunity
+    }
+
+    /// Insert unless a live conflicting opportunity beats it. Live ones it
+    /// beats are withdrawn as superseded. Returns None when suppressed.
+    pub fn admit(&self, mut opportunity: ArbitrageOpportunity) -> Option<ArbitrageOpportunity> {
+        let Some(resolver) = &self.resolver else {
+            return Some(self.insert(opportunity));
+        };
+        let resolution = {
+            let mut entries = self.entries.lock();
+            let resolution = resolver.resolve(&mut opportunity, entries.values_mut().map(|e| &mut e.opportunity));
+            if let Resolution::Keep { displaced } = &resolution {
+                for id in displaced {
+                    entries.remove(id);
+                }
+            }
+            resolution
+        };
+        match resolution {
+            Resolution::Suppress { by } => {
+                debug!("Opportunity {} suppressed by {}", opportunity.id, by);
+                None
+            }
+            Resolution::Keep { displaced } => {
+                for id in displaced {
+                    self.emit(OpportunityLifecycle::Expired { id, reason: ExpiryReason::Superseded });
+                }
+                Some(self.insert(opportunity))
+            }
+        }
+    }
+
+    /// Live opportunity by id
+    pub fn get(&self, id: &Uuid) -> Option<ArbitrageOpportunity> {
+        self.entries.lock().get(id).map(|e| e.opportunity.clone())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove an opportunity that has been handed to execution
+    pub fn consume(&self, id: &Uuid) -> Option<ArbitrageOpportunity> {
+        let entry = self.entries.lock().remove(id)?;
+        self.emit(OpportunityLifecycle::Expired { id: *id, reason: ExpiryReason::Consumed });
+        Some(entry.opportunity)
+    }
+
+    /// Refresh or invalidate opportunities with a leg on this book
+    pub fn on_book(&self, book: &OrderBook) {
+        self.model.on_book(book);
+        let exchange = book.exchange.as_str();
+        let symbol = book.symbol.as_str();
+        let (best_bid, best_ask) = (book.best_bid().map(|e| e.price), book.best_ask().map(|e| e.price));
+
+        let mut events = Vec::new();
+        {
+            let mut entries = self.entries.lock();
+            entries.retain(|id, entry| {
+                let legs: Vec<_> = entry
+                    .opportunity
+                    .legs
+                    .iter()
+                    .filter(|l| l.exchange.as_str().eq_ignore_ascii_case(exchange) && l.symbol.as_str() == symbol)
+                    .collect();
+                if legs.is_empty() {
+                    return true;
+                }
+                let confirmed = legs.iter().all(|leg| match leg.side {
+                    Side::Buy => best_ask.is_some_and(|ask| ask <= leg.price),
+                    Side::Sell => best_bid.is_some_and(|bid| bid >= leg.price),
+                });
+                if !confirmed {
+                    events.push(OpportunityLifecycle::Expired { id: *id, reason: ExpiryReason::Invalidated });
+                    return false;
+                }
+                let ttl_ns = self.model.ttl_ms(&entry.opportunity) * 1_000_000;
+                let expires_at_ns = book.timestamp_ns.saturating_add(ttl_ns);
+                if expires_at_ns > entry.expires_at_ns {
+                    entry.expires_at_ns = expires_at_ns;
+                    // Keep the opportunity's own TTL consistent with the pool
+                    entry.opportunity.ttl_ns = expires_at_ns.saturating_sub(entry.opportunity.created_at_ns);
+                    events.push(OpportunityLifecycle::Refreshed { id: *id, expires_at_ns });
+                }
+                true
+            });
+        }
+        for event in events {
+            self.emit(event);
+        }
+    }
+
+    /// Withdraw every live opportunity matching `predicate`
+    pub fn cancel_where(
+        &self,
+        predicate: impl Fn(&ArbitrageOpportunity) -> bool,
+        reason: ExpiryReason,
+    ) -> Vec<Uuid> {
+        let mut cancelled = Vec::new();
+        self.entries.lock().retain(|id, entry| {
+            let keep = !predicate(&entry.opportunity);
+            if !keep {
+                cancelled.push(*id);
+            }
+            keep
+        });
+        for id in &cancelled {
+            self.emit(OpportunityLifecycle::Expired { id: *id, reason });
+        }
+        cancelled
+    }
+
+    /// Expire opportunities whose TTL has elapsed
+    pub fn sweep(&self, now_ns: u64) -> Vec<ArbitrageOpportunity> {
+        let mut expired = Vec::new();
+        self.entries.lock().retain(|_, entry| {
+            let live = entry.expires_at_ns > now_ns;
+            if !live {
+                expired.push(entry.opportunity.clone());
+            }
+            live
+        });
+        for opportunity in &expired {
+            self.emit(OpportunityLifecycle::Expired { id: opportunity.id, reason: ExpiryReason::TtlElapsed });
+        }
+        expired
+    }
+
+    /// Spawn the expiry sweep loop
+    pub fn spawn(self: Arc<Self>, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.sweep(chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64);
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::testing::{book, leg};
+    use common::precision::FixedPrice;
+
+    #[test]
+    fn test_ttl_from_feed_rate_and_tick_refresh() {
+        let selector = Arc::new(VenueSelector::default());
+        selector.record_execution("okx", 200.0, true);
+        let model = Arc::new(OpportunityTtlModel::default().with_venue_selector(selector));
+        // Binance updates every 100ms
+        for t in 0..5 {
+            model.on_book(&book("binance", "BTCUSDT", 100.0, 100.5, t * 100_000_000));
+        }
+        let mut opportunity = ArbitrageOpportunity::new_inter_exchange(
+            "inter_exchange",
+            leg("binance", "BTCUSDT", Side::Buy, 100.5, 1.0),
+            leg("okx", "BTCUSDT", Side::Sell, 101.0, 1.0),
+            FixedPrice::from_f64(0.4, 2),
+            FixedPrice::from_f64(0.004, 6),
+            1_000_000_000,
+        );
+        // okx: 2 x 200ms latency dominates binance's 2 x 100ms interval
+        assert_eq!(model.ttl_ms(&opportunity), 400);
+
+        let pool = OpportunityPool::new(model);
+        opportunity = pool.insert(opportunity);
+        let mut events = pool.subscribe();
+
+        // A confirming tick extends the expiry
+        pool.on_book(&book("binance", "BTCUSDT", 100.0, 100.5, 1_300_000_000));
+        assert!(matches!(events.try_recv().unwrap(), OpportunityLifecycle::Refreshed { .. }));
+        assert!(pool.sweep(1_500_000_000).is_empty());
+
+        // The sell price disappears on okx
+        pool.on_book(&book("okx", "BTCUSDT", 100.8, 101.2, 1_400_000_000));
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            OpportunityLifecycle::Expired { reason: ExpiryReason::Invalidated, .. }
+        ));
+        assert!(pool.get(&opportunity.id).is_none());
+    }
+}
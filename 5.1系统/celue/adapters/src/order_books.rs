@@ -0,0 +1,334 @@
+//! Gap-free full-depth order books
+//!
+//! Keeps a full-depth book per exchange and symbol, built from a REST
+//! snapshot and kept current by sequenced deltas. A delta that skips a
+//! sequence number marks the book out of sync. While out of sync, incoming
+//! deltas are buffered and the book is hidden from readers. The book is
+//! rebuilt from a fresh snapshot, then the buffered deltas are replayed.
+//! Depth and aggregate-liquidity queries feed opportunity sizing.
+
+use crate::latency_heatmap::LatencyRecorder;
+use crate::AdapterResult;
+use common::precision::{FixedPrice, FixedQuantity};
+use common::types::{Exchange, Symbol};
+use common::{OrderBook, Side};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Price keys are stored as fixed-point integers at this scale
+const PRICE_SCALE: u8 = 8;
+const QTY_SCALE: u8 = 8;
+
+fn price_key(price: f64) -> i64 {
+    (price * 10f64.powi(PRICE_SCALE as i32)).round() as i64
+}
+
+fn key_price(key: i64) -> f64 {
+    key as f64 / 10f64.powi(PRICE_SCALE as i32)
+}
+
+/// Full book from a REST snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookSnapshot {
+    pub exchange: String,
+    pub symbol: String,
+    /// Last update ID included in the snapshot
+    pub sequence: u64,
+    pub bids: Vec<(f64, f64)>,
+    pub asks: Vec<(f64, f64)>,
+    pub timestamp_ns: u64,
+}
+
+/// Incremental update covering sequences `first_seq..=last_seq`; a zero
+/// quantity removes the level
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookDelta {
+    pub exchange: String,
+    pub symbol: String,
+    pub first_seq: u64,
+    pub last_seq: u64,
+    pub bids: Vec<(f64, f64)>,
+    pub asks: Vec<(f64, f64)>,
+    pub timestamp_ns: u64,
+}
+
+/// Result of applying a delta
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeltaOutcome {
+    Applied,
+    /// Already covered by the book; ignored
+    Stale,
+    /// Book is waiting for a snapshot; the delta is kept for replay
+    Buffered,
+    /// Sequence gap; the book needs a new snapshot
+    GapDetected { expected: u64, received: u64 },
+}
+
+/// Fetches a REST depth snapshot for resynchronization
+#[async_trait::async_trait]
+pub trait SnapshotProvider: Send + Sync {
+    async fn fetch_snapshot(&self, exchange: &str, symbol: &str) -> AdapterResult<BookSnapshot>;
+}
+
+#[derive(Default)]
+struct BookState {
+    bids: BTreeMap<i64, f64>,
+    asks: BTreeMap<i64, f64>,
+    last_seq: u64,
+    synced: bool,
+    pending: Vec<BookDelta>,
+    timestamp_ns: u64,
+}
+
+impl BookState {
+    fn apply_levels(&mut self, delta: &BookDelta) {
+        for (levels, updates) in [(&mut self.bids, &delta.bids), (&mut self.asks, &delta.asks)] {
+            for &(price, quantity) in updates {
+                if quantity <= 0.0 {
+                    levels.remove(&price_key(price));
+                } else {
+                    levels.insert(price_key(price), quantity);
+                }
+            }
+        }
+        self.last_seq = delta.last_seq;
+        self.timestamp_ns = delta.timestamp_ns;
+    }
+
+    /// Apply a delta to a synced book
+    fn apply(&mut self, delta: &BookDelta) -> DeltaOutcome {
+        if delta.last_seq <= self.last_seq {
+            return DeltaOutcome::Stale;
+        }
+        if delta.first_seq > self.last_seq + 1 {
+            return DeltaOutcome::GapDetected { expected: self.last_seq + 1, received: delta.first_seq };
+        }
+        self.apply_levels(delta);
+        DeltaOutcome::Applied
+    }
+
+    fn desync(&mut self) {
+        self.synced = false;
+        self.bids.clear();
+        self.asks.clear();
+    }
+}
+
+/// Order book manager for every exchange/symbol
+pub struct OrderBookManager {
+    books: RwLock<HashMap<(String, String), BookState>>,
+    provider: Option<Arc<dyn SnapshotProvider>>,
+    /// Deltas buffered per book while awaiting a snapshot
+    max_buffered: usize,
+    gaps: AtomicU64,
+    latency: Option<Arc<LatencyRecorder>>,
+}
+
+impl OrderBookManager {
+    pub fn new() -> Self {
+        Self {
+            books: RwLock::new(HashMap::new()),
+            provider: None,
+            max_buffered: 10_000,
+            gaps: AtomicU64::new(0),
+            latency: None,
+        }
+    }
+
+    /// Record exchange-to-local book update latency for the latency heat map
+    pub fn with_latency_recorder(mut self, latency: Arc<LatencyRecorder>) -> Self {
+        self.latency = Some(latency);
+        self
+    }
+
+    /// Fetch snapshots automatically when a gap is detected
+    pub fn with_snapshot_provider(mut self, provider: Arc<dyn SnapshotProvider>) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
+    fn key(exchange: &str, symbol: &str) -> (String, String) {
+        (exchange.to_lowercase(), symbol.to_string())
+    }
+
+    /// Gaps detected since start
+    pub fn gap_count(&self) -> u64 {
+        self.gaps.load(Ordering::Relaxed)
+    }
+
+    pub fn is_synced(&self, exchange: &str, symbol: &str) -> bool {
+        self.books.read().get(&Self::key(exchange, symbol)).is_some_and(|b| b.synced)
+    }
+
+    /// Rebuild a book from a snapshot and replay buffered deltas
+    pub fn apply_snapshot(&self, snapshot: BookSnapshot) -> DeltaOutcome {
+        let mut books = self.books.write();
+        let book = books.entry(Self::key(&snapshot.exchange, &snapshot.symbol)).or_default();
+        book.bids = snapshot.bids.iter().filter(|(_, q)| *q > 0.0).map(|&(p, q)| (price_key(p), q)).collect();
+        book.asks = snapshot.asks.iter().filter(|(_, q)| *q > 0.0).map(|&(p, q)| (price_key(p), q)).collect();
+        book.last_seq = snapshot.sequence;
+        book.timestamp_ns = snapshot.timestamp_ns;
+        book.synced = true;
+
+        let pending = std::mem::take(&mut book.pending);
+        for delta in &pending {
+            if let DeltaOutcome::GapDetected { expected, received } = book.apply(delta) {
+                warn!(
+                    "{}:{} snapshot {} does not connect to buffered deltas (expected {}, got {})",
+                    snapshot.exchange, snapshot.symbol, snapshot.sequence, expected, received
+                );
+                book.desync();
+                return DeltaOutcome::GapDetected { expected, received };
+            }
+        }
+        info!("{}:{} book rebuilt at sequence {}", snapshot.exchange, snapshot.symbol, book.last_seq);
+        DeltaOutcome::Applied
+    }
+
+    /// Validate and apply a delta
+    pub fn apply_delta(&self, delta: BookDelta) -> DeltaOutcome {
+        if let Some(latency) = &self.latency {
+            latency.record_book_update(&delta.exchange, delta.timestamp_ns);
+        }
+        let mut books = self.books.write();
+        let book = books.entry(Self::key(&delta.exchange, &delta.symbol)).or_default();
+        if !book.synced {
+            if book.pending.len() >= self.max_buffered {
+                book.pending.remove(0);
+            }
+            book.pending.push(delta);
+            return DeltaOutcome::Buffered;
+        }
+        let outcome = book.apply(&delta);
+        if let DeltaOutcome::GapDetected { expected, received } = outcome {
+            self.gaps.fetch_add(1, Ordering::Relaxed);
+            warn!("{}:{} sequence gap: expected {}, got {}", delta.exchange, delta.symbol, expected, received);
+            book.desync();
+            book.pending.push(delta);
+        }
+        outcome
+    }
+
+    /// Apply a delta and resynchronize from a snapshot on a gap
+    pub async fn handle_delta(&self, delta: BookDelta) -> AdapterResult<DeltaOutcome> {
+        let (exchange, symbol) = (delta.exchange.clone(), delta.symbol.clone());
+        let outcome = self.apply_delta(delta);
+        if matches!(outcome, DeltaOutcome::GapDetected { .. }) {
+            self.resync(&exchange, &symbol).await?;
+        }
+        Ok(outcome)
+    }
+
+    /// Fetch a snapshot and rebuild the book
+    pub async fn resync(&self, exchange: &str, symbol: &str) -> AdapterResult<DeltaOutcome> {
+        let Some(provider) = &self.provider else {
+            return Ok(DeltaOutcome::Buffered);
+        };
+        let snapshot = provider.fetch_snapshot(exchange, symbol).await?;
+        Ok(self.apply_snapshot(snapshot))
+    }
+
+    /// Top `levels` of a synced book
+    pub fn book_depth(&self, exchange: &str, symbol: &str, levels: usize) -> Option<OrderBook> {
+        let books = self.books.read();
+        let book = books.get(&Self::key(exchange, symbol)).filter(|b| b.synced)?;
+        let mut out = OrderBook::new(Exchange::new(exchange), Symbol::new(symbol), book.timestamp_ns, book.last_seq);
+        for (&key, &qty) in book.bids.iter().rev().take(levels) {
+            out.bid_prices.push(FixedPrice::from_raw(key, PRICE_SCALE));
+            out.bid_quantities.push(FixedQuantity::from_f64(qty, QTY_SCALE));
+        }
+        for (&key, &qty) in book.asks.iter().take(levels) {
+            out.ask_prices.push(FixedPrice::from_raw(key, PRICE_SCALE));
+            out.ask_quantities.push(FixedQuantity::from_f64(qty, QTY_SCALE));
+        }
+        Some(out)
+    }
+
+    /// Top `levels` of every synced book for `symbol`, one per exchange
+    pub fn get_depth(&self, symbol: &str, levels: usize) -> Vec<OrderBook> {
+        let exchanges: Vec<String> = self
+            .books
+            .read()
+            .iter()
+            .filter(|((_, s), b)| s == symbol && b.synced)
+            .map(|((e, _), _)| e.clone())
+            .collect();
+        exchanges.iter().filter_map(|e| self.book_depth(e, symbol, levels)).collect()
+    }
+
+    /// Quantity and notional available to a taker on `side` at prices no
+    /// worse than `limit_price` (asks for a buy, bids for a sell)
+    pub fn aggregate_liquidity(&self, exchange: &str, symbol: &str, side: Side, limit_price: f64) -> Option<(f64, f64)> {
+        let books = self.books.read();
+        let book = books.get(&Self::key(exchange, symbol)).filter(|b| b.synced)?;
+        let limit = price_key(limit_price);
+        let levels: Box<dyn Iterator<Item = (&i64, &f64)>> = match side {
+            Side::Buy => Box::new(book.asks.range(..=limit)),
+            Side::Sell => Box::new(book.bids.range(limit..)),
+        };
+        Some(levels.fold((0.0, 0.0), |(qty, notional), (&key, &q)| (qty + q, notional + q * key_price(key))))
+    }
+
+    /// Best-level quantity on the side a maker order on `side` would join
+    /// (bids for a buy, asks for a sell)
+    pub fn passive_top_quantity(&self, exchange: &str, symbol: &str, side: Side) -> Option<f64> {
+        let books = self.books.read();
+        let book = books.get(&Self::key(exchange, symbol)).filter(|b| b.synced)?;
+        match side {
+            Side::Buy => book.bids.values().next_back().copied(),
+            Side::Sell => book.asks.values().next().copied(),
+        }
+    }
+}
+
+impl Default for OrderBookManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delta(first_seq: u64, last_seq: u64, bids: Vec<(f64, f64)>, asks: Vec<(f64, f64)>) -> BookDelta {
+        BookDelta { exchange: "binance".into(), symbol: "BTCUSDT".into(), first_seq, last_seq, bids, asks, timestamp_ns: 0 }
+    }
+
+    #[test]
+    fn test_gap_triggers_rebuild_and_replay() {
+        let books = OrderBookManager::new();
+        // Deltas before the first snapshot are buffered
+        assert_eq!(books.apply_delta(delta(99, 101, vec![(100.0, 1.0)], vec![])), DeltaOutcome::Buffered);
+        let snapshot = BookSnapshot {
+            exchange: "binance".into(),
+            symbol: "BTCUSDT".into(),
+            sequence: 100,
+            bids: vec![(100.0, 2.0), (99.0, 3.0)],
+            asks: vec![(101.0, 1.5), (102.0, 4.0)],
+            timestamp_ns: 0,
+        };
+        assert_eq!(books.apply_snapshot(snapshot.clone()), DeltaOutcome::Applied);
+        assert_eq!(books.book_depth("binance", "BTCUSDT", 1).unwrap().bid_quantities[0].to_f64(), 1.0);
+
+        assert_eq!(books.apply_delta(delta(102, 102, vec![], vec![(101.0, 0.0)])), DeltaOutcome::Applied);
+        assert_eq!(books.passive_top_quantity("binance", "BTCUSDT", Side::Sell), Some(4.0));
+        assert_eq!(books.aggregate_liquidity("binance", "BTCUSDT", Side::Sell, 99.0), Some((4.0, 397.0)));
+
+        // Sequence 103 is missing
+        assert_eq!(
+            books.apply_delta(delta(104, 104, vec![], vec![])),
+            DeltaOutcome::GapDetected { expected: 103, received: 104 }
+        );
+        assert!(books.book_depth("binance", "BTCUSDT", 5).is_none());
+        assert_eq!(books.gap_count(), 1);
+
+        let rebuilt = BookSnapshot { sequence: 103, ..snapshot };
+        assert_eq!(books.apply_snapshot(rebuilt), DeltaOutcome::Applied);
+        assert_eq!(books.get_depth("BTCUSDT", 10)[0].sequence, 104);
+    }
+}
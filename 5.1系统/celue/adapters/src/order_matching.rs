@@ -0,0 +1,556 @@
+//! Shadow order matching for paper trading
+//!
+//! Simulates venue-side order handling against a stream of market prices
+//! so hedging and bracket logic can be exercised in `ExecutionMode::Paper`
+//! without touching a real exchange. Besides market and limit orders it
+//! supports stop-market, stop-limit, trailing-stop and OCO (one-cancels-
+//! other) pairs, triggered from simulated last-trade prices.
+//!
+//! Orders carry time-in-force (GTC / IOC / FOK), post-only and
+//! reduce-only flags with venue semantics: post-only orders that would
+//! cross are rejected, IOC/FOK orders that cannot fill on submission are
+//! cancelled, and reduce-only orders never grow the simulated position.
+
+use crate::{AdapterError, AdapterResult};
+use common::pagination::{paginate, Page, PageQuery, Pageable};
+use common::{SharedClock, Side};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Simulated order type
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ShadowOrderType {
+    Market,
+    Limit { price: f64 },
+    /// Becomes a market order once `stop_price` trades
+    StopMarket { stop_price: f64 },
+    /// Becomes a limit order at `limit_price` once `stop_price` trades
+    StopLimit { stop_price: f64, limit_price: f64 },
+    /// Stop that follows the best price by `trail_bps`, then executes at market
+    TrailingStop { trail_bps: f64 },
+}
+
+/// How long an order stays working
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum TimeInForce {
+    /// Good till cancelled
+    #[default]
+    Gtc,
+    /// Immediate or cancel: fill what is possible now, cancel the rest
+    Ioc,
+    /// Fill or kill: fill entirely now or cancel
+    Fok,
+}
+
+/// Execution flags of an order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct OrderFlags {
+    #[serde(default)]
+    pub time_in_force: TimeInForce,
+    /// Reject instead of taking liquidity
+    #[serde(default)]
+    pub post_only: bool,
+    /// Only reduce an existing position
+    #[serde(default)]
+    pub reduce_only: bool,
+}
+
+impl OrderFlags {
+    pub fn ioc() -> Self {
+        Self { time_in_force: TimeInForce::Ioc, ..Self::default() }
+    }
+
+    pub fn fok() -> Self {
+        Self { time_in_force: TimeInForce::Fok, ..Self::default() }
+    }
+
+    pub fn post_only() -> Self {
+        Self { post_only: true, ..Self::default() }
+    }
+
+    pub fn with_reduce_only(mut self) -> Self {
+        self.reduce_only = true;
+        self
+    }
+
+    /// Reject combinations no venue accepts
+    pub fn validate(&self, order_type: &ShadowOrderType) -> AdapterResult<()> {
+        if self.post_only && self.time_in_force != TimeInForce::Gtc {
+            return Err(AdapterError::Validation { message: "post-only orders must be GTC".to_string() });
+        }
+        if self.post_only && !matches!(order_type, ShadowOrderType::Limit { .. }) {
+            return Err(AdapterError::Validation { message: "post-only requires a limit order".to_string() });
+        }
+        if self.time_in_force != TimeInForce::Gtc
+            && !matches!(order_type, ShadowOrderType::Market | ShadowOrderType::Limit { .. })
+        {
+            return Err(AdapterError::Validation { message: "IOC/FOK apply only to market and limit orders".to_string() });
+        }
+        Ok(())
+    }
+
+    /// Binance `timeInForce` parameter; post-only maps to GTX
+    pub fn binance_time_in_force(&self) -> &'static str {
+        match (self.post_only, self.time_in_force) {
+            (true, _) => "GTX",
+            (false, TimeInForce::Gtc) => "GTC",
+            (false, TimeInForce::Ioc) => "IOC",
+            (false, TimeInForce::Fok) => "FOK",
+        }
+    }
+
+    /// OKX `ordType` for a limit order; OKX encodes TIF in the order type
+    pub fn okx_ord_type(&self) -> &'static str {
+        match (self.post_only, self.time_in_force) {
+            (true, _) => "post_only",
+            (false, TimeInForce::Gtc) => "limit",
+            (false, TimeInForce::Ioc) => "ioc",
+            (false, TimeInForce::Fok) => "fok",
+        }
+    }
+}
+
+/// Lifecycle of a simulated order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShadowOrderStatus {
+    /// Waiting for a stop trigger
+    Pending,
+    /// Resting on the simulated book
+    Open,
+    Filled,
+    Cancelled,
+}
+
+/// A simulated order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShadowOrder {
+    pub id: u64,
+    pub symbol: String,
+    pub side: Side,
+    pub quantity: f64,
+    pub order_type: ShadowOrderType,
+    pub status: ShadowOrderStatus,
+    pub fill_price: Option<f64>,
+    /// Linked OCO leg, cancelled when this order fills
+    pub oco_peer: Option<u64>,
+    /// Current stop level of a trailing stop
+    pub trail_stop: Option<f64>,
+    #[serde(default)]
+    pub flags: OrderFlags,
+    #[serde(default)]
+    pub created_at_ns: u64,
+}
+
+impl Pageable for ShadowOrder {
+    fn timestamp_ns(&self) -> u64 {
+        self.created_at_ns
+    }
+
+    fn record_id(&self) -> String {
+        format!("{:020}", self.id)
+    }
+
+    fn field(&self, name: &str) -> Option<String> {
+        match name {
+            "symbol" => Some(self.symbol.clone()),
+            "side" => Some(format!("{:?}", self.side)),
+            "status" => Some(format!("{:?}", self.status)),
+            _ => None,
+        }
+    }
+}
+
+/// A simulated fill
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShadowFill {
+    pub order_id: u64,
+    pub symbol: String,
+    pub side: Side,
+    pub quantity: f64,
+    pub price: f64,
+}
+
+#[derive(Default)]
+struct BookState {
+    orders: HashMap<u64, ShadowOrder>,
+    last_price: HashMap<String, f64>,
+    /// Net simulated position per symbol, for reduce-only checks
+    positions: HashMap<String, f64>,
+    next_id: u64,
+}
+
+/// Shadow matching engine
+pub struct ShadowMatchingEngine {
+    state: Mutex<BookState>,
+    clock: SharedClock,
+}
+
+impl Default for ShadowMatchingEngine {
+    fn default() -> Self {
+        Self { state: Mutex::default(), clock: common::clock::system_clock() }
+    }
+}
+
+impl ShadowMatchingEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stamp orders from `clock`, e.g. a simulated clock during backtests
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Submit a GTC order; market orders fill immediately at the last price
+    pub fn submit(
+        &self,
+        symbol: &str,
+        side: Side,
+        quantity: f64,
+        order_type: ShadowOrderType,
+    ) -> AdapterResult<(u64, Vec<ShadowFill>)> {
+        self.submit_with_flags(symbol, side, quantity, order_type, OrderFlags::default())
+    }
+
+    /// Submit an order with time-in-force / post-only / reduce-only flags
+    pub fn submit_with_flags(
+        &self,
+        symbol: &str,
+        side: Side,
+        quantity: f64,
+        order_type: ShadowOrderType,
+        flags: OrderFlags,
+    ) -> AdapterResult<(u64, Vec<ShadowFill>)> {
+        flags.validate(&order_type)?;
+        if quantity <= 0.0 {
+            return Err(AdapterError::Validation { message: format!("quantity must be positive: {}", quantity) });
+        }
+        if let ShadowOrderType::TrailingStop { trail_bps } = order_type {
+            if trail_bps <= 0.0 {
+                return Err(AdapterError::Validation { message: "trail_bps must be positive".to_string() });
+            }
+        }
+
+        let mut state = self.state.lock();
+        let last_price = state.last_price.get(symbol).copied();
+        let id = Self::insert(&mut state, symbol, side, quantity, order_type, None, self.clock.now_ns());
+        if let Some(order) = state.orders.get_mut(&id) {
+            order.flags = flags;
+        }
+        // A post-only order that would take liquidity is rejected
+        if let (true, Some(price), ShadowOrderType::Limit { price: limit }) = (flags.post_only, last_price, order_type) {
+            let crosses = match side {
+                Side::Buy => price <= limit,
+                Side::Sell => price >= limit,
+            };
+            if crosses {
+                if let Some(order) = state.orders.get_mut(&id) {
+                    order.status = ShadowOrderStatus::Cancelled;
+                }
+                return Ok((id, Vec::new()));
+            }
+        }
+        let fills = match last_price {
+            Some(price) => Self::match_symbol(&mut state, symbol, price),
+            None => Vec::new(),
+        };
+        // Whatever did not execute immediately does not rest
+        if flags.time_in_force != TimeInForce::Gtc {
+            if let Some(order) = state.orders.get_mut(&id) {
+                if order.status != ShadowOrderStatus::Filled {
+                    order.status = ShadowOrderStatus::Cancelled;
+                }
+            }
+        }
+        Ok((id, fills))
+    }
+
+    /// Submit an OCO pair; whichever fills first cancels the other
+    pub fn submit_oco(
+        &self,
+        symbol: &str,
+        side: Side,
+        quantity: f64,
+        first: ShadowOrderType,
+        second: ShadowOrderType,
+    ) -> AdapterResult<((u64, u64), Vec<ShadowFill>)> {
+        if quantity <= 0.0 {
+            return Err(AdapterError::Validation { message: format!("quantity must be positive: {}", quantity) });
+        }
+        if matches!(first, ShadowOrderType::Market) || matches!(second, ShadowOrderType::Market) {
+            return Err(AdapterError::Validation { message: "OCO legs cannot be market orders".to_string() });
+        }
+
+        let mut state = self.state.lock();
+        let now_ns = self.clock.now_ns();
+        let a = Self::insert(&mut state, symbol, side, quantity, first, None, now_ns);
+        let b = Self::insert(&mut state, symbol, side, quantity, second, Some(a), now_ns);
+        if let Some(order) = state.orders.get_mut(&a) {
+            order.oco_peer = Some(b);
+        }
+        let fills = match state.last_price.get(symbol).copied() {
+            Some(price) => Self::match_symbol(&mut state, symbol, price),
+            None => Vec::new(),
+        };
+        Ok(((a, b), fills))
+    }
+
+    /// Cancel an open or pending order
+    pub fn cancel(&self, order_id: u64) -> bool {
+        let mut state = self.state.lock();
+        match state.orders.get_mut(&order_id) {
+            Some(order) if matches!(order.status, ShadowOrderStatus::Pending | ShadowOrderStatus::Open) => {
+                order.status = ShadowOrderStatus::Cancelled;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Cancel every open or pending order on books matching `book`;
+    /// returns how many were cancelled
+    pub fn cancel_where(&self, book: impl Fn(&str) -> bool) -> usize {
+        let mut state = self.state.lock();
+        let mut cancelled = 0;
+        for order in state.orders.values_mut() {
+            if book(&order.symbol) && matches!(order.status, ShadowOrderStatus::Pending | ShadowOrderStatus::Open) {
+                order.status = ShadowOrderStatus::Cancelled;
+                cancelled += 1;
+            }
+        }
+        cancelled
+    }
+
+    /// Feed a simulated trade price and return the resulting fills
+    pub fn on_price(&self, symbol: &str, price: f64) -> Vec<ShadowFill> {
+        if price <= 0.0 {
+            return Vec::new();
+        }
+        let mut state = self.state.lock();
+        state.last_price.insert(symbol.to_string(), price);
+        Self::match_symbol(&mut state, symbol, price)
+    }
+
+    pub fn order(&self, order_id: u64) -> Option<ShadowOrder> {
+        self.state.lock().orders.get(&order_id).cloned()
+    }
+
+    /// Paged order history, filterable by symbol, side and status
+    pub fn order_history(&self, query: &PageQuery) -> AdapterResult<Page<ShadowOrder>> {
+        let orders: Vec<ShadowOrder> = self.state.lock().orders.values().cloned().collect();
+        paginate(orders, query).map_err(|e| AdapterError::Validation { message: e.to_string() })
+    }
+
+    fn insert(
+        state: &mut BookState,
+        symbol: &str,
+        side: Side,
+        quantity: f64,
+        order_type: ShadowOrderType,
+        oco_peer: Option<u64>,
+        now_ns: u64,
+    ) -> u64 {
+        state.next_id += 1;
+        let id = state.next_id;
+        let status = match order_type {
+            ShadowOrderType::Market | ShadowOrderType::Limit { .. } => ShadowOrderStatus::Open,
+            _ => ShadowOrderStatus::Pending,
+        };
+        state.orders.insert(id, ShadowOrder {
+            id,
+            symbol: symbol.to_string(),
+            side,
+            quantity,
+            order_type,
+            status,
+            fill_price: None,
+            oco_peer,
+            trail_stop: None,
+            flags: OrderFlags::default(),
+            created_at_ns: now_ns,
+        });
+        id
+    }
+
+    fn match_symbol(state: &mut BookState, symbol: &str, price: f64) -> Vec<ShadowFill> {
+        let mut ids: Vec<u64> = state
+            .orders
+            .values()
+            .filter(|o| o.symbol == symbol)
+            .filter(|o| matches!(o.status, ShadowOrderStatus::Pending | ShadowOrderStatus::Open))
+            .map(|o| o.id)
+            .collect();
+        ids.sort_unstable();
+
+        let mut fills = Vec::new();
+        for id in ids {
+            let Some(order) = state.orders.get_mut(&id) else { continue };
+            // An OCO peer may have been cancelled earlier in this pass
+            if !matches!(order.status, ShadowOrderStatus::Pending | ShadowOrderStatus::Open) {
+                continue;
+            }
+            if let Some(fill_price) = Self::evaluate(order, price) {
+                let position = state.positions.get(symbol).copied().unwrap_or_default();
+                let Some(order) = state.orders.get_mut(&id) else { continue };
+                if order.flags.reduce_only {
+                    // Only the part that shrinks the position may execute
+                    let reducible = match order.side {
+                        Side::Buy => (-position).max(0.0),
+                        Side::Sell => position.max(0.0),
+                    };
+                    if reducible <= 0.0 {
+                        order.status = ShadowOrderStatus::Cancelled;
+                        continue;
+                    }
+                    order.quantity = order.quantity.min(reducible);
+                }
+                order.status = ShadowOrderStatus::Filled;
+                order.fill_price = Some(fill_price);
+                let fill = ShadowFill {
+                    order_id: id,
+                    symbol: order.symbol.clone(),
+                    side: order.side,
+                    quantity: order.quantity,
+                    price: fill_price,
+                };
+                let oco_peer = order.oco_peer;
+                let signed = match fill.side {
+                    Side::Buy => fill.quantity,
+                    Side::Sell => -fill.quantity,
+                };
+                *state.positions.entry(symbol.to_string()).or_default() += signed;
+                fills.push(fill);
+                if let Some(peer) = oco_peer {
+                    if let Some(peer_order) = state.orders.get_mut(&peer) {
+                        if peer_order.status != ShadowOrderStatus::Filled {
+                            peer_order.status = ShadowOrderStatus::Cancelled;
+                        }
+                    }
+                }
+            }
+        }
+        fills
+    }
+
+    /// Update triggers for one order and return its fill price if it executes
+    fn evaluate(order: &mut ShadowOrder, price: f64) -> Option<f64> {
+        let stop_hit = |stop: f64| match order.side {
+            Side::Buy => price >= stop,
+            Side::Sell => price <= stop,
+        };
+        let limit_ok = |limit: f64| match order.side {
+            Side::Buy => price <= limit,
+            Side::Sell => price >= limit,
+        };
+
+        match order.order_type {
+            ShadowOrderType::Market => Some(price),
+            ShadowOrderType::Limit { price: limit } => limit_ok(limit).then_some(limit),
+            ShadowOrderType::StopMarket { stop_price } => stop_hit(stop_price).then_some(price),
+            ShadowOrderType::StopLimit { stop_price, limit_price } => {
+                if order.status == ShadowOrderStatus::Pending && stop_hit(stop_price) {
+                    order.status = ShadowOrderStatus::Open;
+                }
+                (order.status == ShadowOrderStatus::Open && limit_ok(limit_price)).then_some(limit_price)
+            }
+            ShadowOrderType::TrailingStop { trail_bps } => {
+                let offset = trail_bps / 10_000.0;
+                let candidate = match order.side {
+                    Side::Sell => price * (1.0 - offset),
+                    Side::Buy => price * (1.0 + offset),
+                };
+                let stop = match (order.trail_stop, order.side) {
+                    (None, _) => candidate,
+                    (Some(s), Side::Sell) => s.max(candidate),
+                    (Some(s), Side::Buy) => s.min(candidate),
+                };
+                let triggered = order.trail_stop.is_some() && stop_hit(stop);
+                order.trail_stop = Some(stop);
+                triggered.then_some(price)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trailing_stop_follows_price() {
+        let engine = ShadowMatchingEngine::new();
+        engine.on_price("BTCUSDT", 100.0);
+        let (id, fills) = engine
+            .submit("BTCUSDT", Side::Sell, 1.0, ShadowOrderType::TrailingStop { trail_bps: 100.0 })
+            .unwrap();
+        assert!(fills.is_empty());
+
+        assert!(engine.on_price("BTCUSDT", 110.0).is_empty());
+        assert!(engine.on_price("BTCUSDT", 109.5).is_empty());
+        let fills = engine.on_price("BTCUSDT", 108.8);
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].order_id, id);
+    }
+
+    #[test]
+    fn test_oco_cancels_peer() {
+        let engine = ShadowMatchingEngine::new();
+        engine.on_price("ETHUSDT", 100.0);
+        let ((take_profit, stop_loss), _) = engine
+            .submit_oco(
+                "ETHUSDT",
+                Side::Sell,
+                1.0,
+                ShadowOrderType::Limit { price: 105.0 },
+                ShadowOrderType::StopMarket { stop_price: 95.0 },
+            )
+            .unwrap();
+
+        let fills = engine.on_price("ETHUSDT", 94.0);
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].order_id, stop_loss);
+        assert_eq!(engine.order(take_profit).unwrap().status, ShadowOrderStatus::Cancelled);
+    }
+
+    #[test]
+    fn test_time_in_force_and_flags() {
+        let engine = ShadowMatchingEngine::new();
+        engine.on_price("BTCUSDT", 100.0);
+
+        // Crossing post-only is rejected, resting post-only stays open
+        let (crossing, _) = engine
+            .submit_with_flags("BTCUSDT", Side::Buy, 1.0, ShadowOrderType::Limit { price: 101.0 }, OrderFlags::post_only())
+            .unwrap();
+        assert_eq!(engine.order(crossing).unwrap().status, ShadowOrderStatus::Cancelled);
+        let (resting, _) = engine
+            .submit_with_flags("BTCUSDT", Side::Buy, 1.0, ShadowOrderType::Limit { price: 99.0 }, OrderFlags::post_only())
+            .unwrap();
+        assert_eq!(engine.order(resting).unwrap().status, ShadowOrderStatus::Open);
+
+        // IOC that cannot fill now is cancelled instead of resting
+        let (ioc, fills) = engine
+            .submit_with_flags("BTCUSDT", Side::Sell, 1.0, ShadowOrderType::Limit { price: 102.0 }, OrderFlags::ioc())
+            .unwrap();
+        assert!(fills.is_empty());
+        assert_eq!(engine.order(ioc).unwrap().status, ShadowOrderStatus::Cancelled);
+
+        // Reduce-only cannot open a position and is capped at its size
+        let (reduce, _) = engine
+            .submit_with_flags("BTCUSDT", Side::Sell, 1.0, ShadowOrderType::Market, OrderFlags::default().with_reduce_only())
+            .unwrap();
+        assert_eq!(engine.order(reduce).unwrap().status, ShadowOrderStatus::Cancelled);
+        engine.submit("BTCUSDT", Side::Buy, 0.4, ShadowOrderType::Market).unwrap();
+        let (_, fills) = engine
+            .submit_with_flags("BTCUSDT", Side::Sell, 1.0, ShadowOrderType::Market, OrderFlags::fok().with_reduce_only())
+            .unwrap();
+        assert!((fills[0].quantity - 0.4).abs() < 1e-12);
+
+        assert!(OrderFlags { post_only: true, ..OrderFlags::ioc() }.validate(&ShadowOrderType::Market).is_err());
+        assert_eq!(OrderFlags::post_only().binance_time_in_force(), "GTX");
+        assert_eq!(OrderFlags::fok().okx_ord_type(), "fok");
+    }
+}
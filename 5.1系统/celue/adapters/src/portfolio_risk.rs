@@ -0,0 +1,478 @@
+//! Portfolio-level value at risk and stress testing
+//!
+//! Exposures are the balances held in `FundsAdapter`, valued at the latest
+//! recorded USD price of each asset (stablecoins default to par). Price
+//! snapshots taken at a fixed cadence give a joint return history, from
+//! which two VaR figures are computed over `horizon_periods`:
+//! - parametric: z * sqrt(w' Σ w), with Σ the sample covariance of returns
+//! - historical: the loss quantile of replaying every past joint return on
+//!   today's exposures
+//!
+//! Stress scenarios apply price shocks, exchange outages and stablecoin
+//! depegs to the current exposures. A scenario whose loss exceeds its limit
+//! raises a Critical alert.
+
+use crate::alerting::{Alert, AlertManager, AlertSeverity};
+use crate::funds::FundsAdapter;
+use crate::nats::{MessageHandler, NatsMessage};
+use crate::AdapterResult;
+use common::SharedClock;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+/// One shock applied by a stress scenario
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Shock {
+    /// Relative price move of an asset, e.g. -0.2
+    Price { asset: String, change: f64 },
+    /// Fraction of the balances held on an exchange that is lost
+    ExchangeOutage { exchange: String, haircut: f64 },
+    /// Stablecoin trades at `price` instead of par
+    Depeg { asset: String, price: f64 },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StressScenario {
+    pub name: String,
+    pub shocks: Vec<Shock>,
+    /// Loss above which the scenario is breached; defaults to the config limit
+    #[serde(default)]
+    pub max_loss_usd: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioRiskConfig {
+    /// VaR confidence level, e.g. 0.99
+    pub confidence: f64,
+    /// VaR horizon in snapshot periods
+    pub horizon_periods: f64,
+    /// Price snapshots kept for the return history
+    pub history_size: usize,
+    /// Snapshots needed before VaR is reported
+    pub min_history: usize,
+    /// Assets valued at par until a price is recorded
+    pub stablecoins: Vec<String>,
+    /// Default stress loss limit
+    pub max_stress_loss_usd: f64,
+    /// Alert when parametric or historical VaR exceeds this
+    pub max_var_usd: Option<f64>,
+    pub scenarios: Vec<StressScenario>,
+}
+
+impl Default for PortfolioRiskConfig {
+    fn default() -> Self {
+        Self {
+            confidence: 0.99,
+            horizon_periods: 1.0,
+            history_size: 1_440,
+            min_history: 30,
+            stablecoins: vec!["USDT".to_string(), "USDC".to_string(), "FDUSD".to_string()],
+            max_stress_loss_usd: 50_000.0,
+            max_var_usd: None,
+            scenarios: vec![
+                StressScenario {
+                    name: "btc_minus_20pct".to_string(),
+                    shocks: vec![Shock::Price { asset: "BTC".to_string(), change: -0.2 }],
+                    max_loss_usd: None,
+                },
+                StressScenario {
+                    name: "binance_outage".to_string(),
+                    shocks: vec![Shock::ExchangeOutage { exchange: "binance".to_string(), haircut: 1.0 }],
+                    max_loss_usd: None,
+                },
+                StressScenario {
+                    name: "usdt_depeg_95".to_string(),
+                    shocks: vec![Shock::Depeg { asset: "USDT".to_string(), price: 0.95 }],
+                    max_loss_usd: None,
+                },
+            ],
+        }
+    }
+}
+
+/// A valued holding
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Exposure {
+    pub exchange: String,
+    pub asset: String,
+    pub quantity: f64,
+    pub price: f64,
+    pub value_usd: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StressResult {
+    pub scenario: String,
+    pub loss_usd: f64,
+    pub loss_pct: f64,
+    pub limit_usd: f64,
+    pub breached: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioRiskReport {
+    pub total_value_usd: f64,
+    pub exposures: Vec<Exposure>,
+    /// None until `min_history` snapshots are recorded
+    pub parametric_var_usd: Option<f64>,
+    pub historical_var_usd: Option<f64>,
+    pub confidence: f64,
+    pub stress: Vec<StressResult>,
+    /// Assets held without a price, excluded from valuation
+    pub unpriced_assets: Vec<String>,
+    pub computed_at_ns: u64,
+}
+
+/// Inverse standard normal CDF (Acklam's rational approximation)
+pub fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [-3.969683028665376e1, 2.209460984245205e2, -2.759285104469687e2, 1.38357751867269e2, -3.066479806614716e1, 2.506628277459239];
+    const B: [f64; 5] = [-5.447609879822406e1, 1.615858368580409e2, -1.556989798598866e2, 6.680131188771972e1, -1.328068155288572e1];
+    const C: [f64; 6] = [-7.784894002430293e-3, -3.223964580411365e-1, -2.400758277161838, -2.549732539343734, 4.374664141464968, 2.938163982698783];
+    const D: [f64; 4] = [7.784695709041462e-3, 3.224671290700398e-1, 2.445134137142996, 3.754408661907416];
+    let p = p.clamp(1e-12, 1.0 - 1e-12);
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    if p < 0.02425 {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - 0.02425 {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+/// Computes VaR and stress losses over the funds held
+pub struct PortfolioRiskEngine {
+    config: PortfolioRiskConfig,
+    funds: Arc<FundsAdapter>,
+    prices: RwLock<HashMap<String, f64>>,
+    snapshots: RwLock<VecDeque<HashMap<String, f64>>>,
+    latest: RwLock<Option<PortfolioRiskReport>>,
+    alerts: Option<Arc<AlertManager>>,
+    clock: SharedClock,
+}
+
+impl PortfolioRiskEngine {
+    pub fn new(config: PortfolioRiskConfig, funds: Arc<FundsAdapter>) -> Self {
+        Self {
+            config,
+            funds,
+            prices: RwLock::new(HashMap::new()),
+            snapshots: RwLock::new(VecDeque::new()),
+            latest: RwLock::new(None),
+            alerts: None,
+            clock: common::clock::system_clock(),
+        }
+    }
+
+    /// Raise Critical alerts for breached scenarios and VaR limits
+    pub fn with_alerts(mut self, alerts: Arc<AlertManager>) -> Self {
+        self.alerts = Some(alerts);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Latest USD price of an asset
+    pub fn update_price(&self, asset: &str, price: f64) {
+        if price.is_finite() && price > 0.0 {
+            self.prices.write().insert(asset.to_uppercase(), price);
+        }
+    }
+
+    /// Append the current prices to the return history; call at a fixed cadence
+    pub fn snapshot_prices(&self) {
+        let prices = self.prices.read().clone();
+        let mut snapshots = self.snapshots.write();
+        snapshots.push_back(prices);
+        while snapshots.len() > self.config.history_size.max(2) {
+            snapshots.pop_front();
+        }
+    }
+
+    fn price_of(&self, asset: &str) -> Option<f64> {
+        let asset = asset.to_uppercase();
+        self.prices
+            .read()
+            .get(&asset)
+            .copied()
+            .or_else(|| self.config.stablecoins.contains(&asset).then_some(1.0))
+    }
+
+    pub fn exposures(&self) -> (Vec<Exposure>, Vec<String>) {
+        let mut exposures = Vec::new();
+        let mut unpriced = Vec::new();
+        for balance in self.funds.balances() {
+            if balance.total == 0.0 {
+                continue;
+            }
+            match self.price_of(&balance.asset) {
+                Some(price) => exposures.push(Exposure {
+                    value_usd: balance.total * price,
+                    exchange: balance.exchange,
+                    asset: balance.asset.to_uppercase(),
+                    quantity: balance.total,
+                    price,
+                }),
+                None => unpriced.push(balance.asset),
+            }
+        }
+        unpriced.sort();
+        unpriced.dedup();
+        (exposures, unpriced)
+    }
+
+    /// Joint simple returns between consecutive snapshots
+    fn returns(&self) -> Vec<HashMap<String, f64>> {
+        let snapshots = self.snapshots.read();
+        snapshots
+            .iter()
+            .zip(snapshots.iter().skip(1))
+            .map(|(prev, next)| {
+                next.iter()
+                    .filter_map(|(asset, price)| prev.get(asset).map(|p| (asset.clone(), price / p - 1.0)))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn value_by_asset(exposures: &[Exposure]) -> HashMap<String, f64> {
+        let mut values = HashMap::new();
+        for e in exposures {
+            *values.entry(e.asset.clone()).or_insert(0.0) += e.value_usd;
+        }
+        values
+    }
+
+    fn parametric_var(&self, values: &HashMap<String, f64>, returns: &[HashMap<String, f64>]) -> f64 {
+        let assets: Vec<&String> = values.keys().collect();
+        let n = returns.len() as f64;
+        let mean = |a: &String| returns.iter().map(|r| r.get(a).copied().unwrap_or(0.0)).sum::<f64>() / n;
+        let means: Vec<f64> = assets.iter().map(|a| mean(a)).collect();
+        let mut variance = 0.0;
+        for (i, a) in assets.iter().enumerate() {
+            for (j, b) in assets.iter().enumerate() {
+                let cov = returns
+                    .iter()
+                    .map(|r| (r.get(*a).copied().unwrap_or(0.0) - means[i]) * (r.get(*b).copied().unwrap_or(0.0) - means[j]))
+                    .sum::<f64>()
+                    / (n - 1.0).max(1.0);
+                variance += values[*a] * values[*b] * cov;
+            }
+        }
+        normal_quantile(self.config.confidence) * variance.max(0.0).sqrt() * self.config.horizon_periods.sqrt()
+    }
+
+    fn historical_var(&self, values: &HashMap<String, f64>, returns: &[HashMap<String, f64>]) -> f64 {
+        let mut losses: Vec<f64> = returns
+            .iter()
+            .map(|r| -values.iter().map(|(asset, v)| v * r.get(asset).copied().unwrap_or(0.0)).sum::<f64>())
+            .collect();
+        losses.sort_by(f64::total_cmp);
+        let index = ((losses.len() as f64) * self.config.confidence).ceil() as usize;
+        let loss = losses[index.saturating_sub(1).min(losses.len() - 1)];
+        loss.max(0.0) * self.config.horizon_periods.sqrt()
+    }
+
+    /// Loss of a scenario on the given exposures
+    pub fn stress_loss(&self, scenario: &StressScenario, exposures: &[Exposure]) -> f64 {
+        exposures
+            .iter()
+            .map(|e| {
+                let mut value = e.value_usd;
+                for shock in &scenario.shocks {
+                    match shock {
+                        Shock::Price { asset, change } if asset.eq_ignore_ascii_case(&e.asset) => value *= 1.0 + change,
+                        Shock::Depeg { asset, price } if asset.eq_ignore_ascii_case(&e.asset) => {
+                            value = value / e.price * price
+                        }
+                        Shock::ExchangeOutage { exchange, haircut } if exchange.eq_ignore_ascii_case(&e.exchange) => {
+                            value *= 1.0 - haircut.clamp(0.0, 1.0)
+                        }
+                        _ => {}
+                    }
+                }
+                e.value_usd - value
+            })
+            .sum()
+    }
+
+    fn stress(&self, scenario: &StressScenario, exposures: &[Exposure], total: f64) -> StressResult {
+        let loss = self.stress_loss(scenario, exposures);
+        let limit = scenario.max_loss_usd.unwrap_or(self.config.max_stress_loss_usd);
+        StressResult {
+            scenario: scenario.name.clone(),
+            loss_usd: loss,
+            loss_pct: if total > 0.0 { loss / total * 100.0 } else { 0.0 },
+            limit_usd: limit,
+            breached: loss > limit,
+        }
+    }
+
+    /// Run an ad-hoc scenario without storing or alerting
+    pub fn run_scenario(&self, scenario: &StressScenario) -> StressResult {
+        let (exposures, _) = self.exposures();
+        let total = exposures.iter().map(|e| e.value_usd).sum();
+        self.stress(scenario, &exposures, total)
+    }
+
+    /// Compute VaR and every configured scenario, alerting on breaches
+    pub fn evaluate(&self) -> PortfolioRiskReport {
+        let (exposures, unpriced_assets) = self.exposures();
+        let total: f64 = exposures.iter().map(|e| e.value_usd).sum();
+        let values = Self::value_by_asset(&exposures);
+        let returns = self.returns();
+        let (parametric, historical) = if returns.len() + 1 >= self.config.min_history.max(2) {
+            (Some(self.parametric_var(&values, &returns)), Some(self.historical_var(&values, &returns)))
+        } else {
+            (None, None)
+        };
+        let stress: Vec<StressResult> = self.config.scenarios.iter().map(|s| self.stress(s, &exposures, total)).collect();
+
+        metrics::gauge!("portfolio_value_usd").set(total);
+        if let Some(var) = parametric {
+            metrics::gauge!("portfolio_var_usd", "method" => "parametric").set(var);
+        }
+        if let Some(var) = historical {
+            metrics::gauge!("portfolio_var_usd", "method" => "historical").set(var);
+        }
+        for result in &stress {
+            metrics::gauge!("portfolio_stress_loss_usd", "scenario" => result.scenario.clone()).set(result.loss_usd);
+            if result.breached {
+                warn!(
+                    "Stress scenario {} loses {:.0} USD ({:.1}%), limit {:.0}",
+                    result.scenario, result.loss_usd, result.loss_pct, result.limit_usd
+                );
+                self.alert(
+                    &format!("portfolio_stress:{}", result.scenario),
+                    &format!("Stress scenario {} breached", result.scenario),
+                    &format!("Loss {:.0} USD ({:.1}% of portfolio) exceeds limit {:.0} USD", result.loss_usd, result.loss_pct, result.limit_usd),
+                );
+            }
+        }
+        if let Some(limit) = self.config.max_var_usd {
+            let worst = parametric.into_iter().chain(historical).fold(0.0, f64::max);
+            if worst > limit {
+                self.alert(
+                    "portfolio_var",
+                    "Portfolio VaR above limit",
+                    &format!("{:.0}% VaR {:.0} USD exceeds limit {:.0} USD", self.config.confidence * 100.0, worst, limit),
+                );
+            }
+        }
+
+        let report = PortfolioRiskReport {
+            total_value_usd: total,
+            exposures,
+            parametric_var_usd: parametric,
+            historical_var_usd: historical,
+            confidence: self.config.confidence,
+            stress,
+            unpriced_assets,
+            computed_at_ns: self.clock.now_ns(),
+        };
+        *self.latest.write() = Some(report.clone());
+        report
+    }
+
+    fn alert(&self, key: &str, title: &str, message: &str) {
+        let Some(alerts) = self.alerts.clone() else { return };
+        let alert = Alert::new(key, AlertSeverity::Critical, title, message, "portfolio_risk");
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            handle.spawn(async move {
+                alerts.raise(alert).await;
+            });
+        }
+    }
+
+    pub fn latest(&self) -> Option<PortfolioRiskReport> {
+        self.latest.read().clone()
+    }
+
+    /// Snapshot prices and re-evaluate every `interval`
+    pub fn spawn(self: Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.snapshot_prices();
+                self.evaluate();
+            }
+        })
+    }
+}
+
+/// Any request is answered with a fresh evaluation
+#[async_trait::async_trait]
+impl MessageHandler for PortfolioRiskEngine {
+    async fn handle_message(&self, _message: NatsMessage, _reply_subject: Option<String>) -> AdapterResult<Option<NatsMessage>> {
+        let report = self.evaluate();
+        Ok(Some(NatsMessage::AuditEvent {
+            component: "portfolio_risk".to_string(),
+            payload: serde_json::to_value(&report)?,
+            timestamp: report.computed_at_ns,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::funds::{AssetBalance, FundsConfig};
+
+    fn balance(exchange: &str, asset: &str, total: f64) -> AssetBalance {
+        AssetBalance {
+            asset: asset.to_string(),
+            exchange: exchange.to_string(),
+            free: total,
+            locked: 0.0,
+            total,
+            updated_ns: 0,
+        }
+    }
+
+    #[test]
+    fn test_var_and_stress_scenarios() {
+        let funds = Arc::new(FundsAdapter::new(FundsConfig::default()));
+        funds.update_balance(balance("binance", "BTC", 2.0));
+        funds.update_balance(balance("okx", "USDT", 100_000.0));
+        let engine = PortfolioRiskEngine::new(
+            PortfolioRiskConfig { min_history: 10, max_stress_loss_usd: 30_000.0, ..Default::default() },
+            funds,
+        );
+
+        // BTC alternates +-1% around 50k
+        for i in 0..50 {
+            engine.update_price("BTC", if i % 2 == 0 { 50_000.0 } else { 50_500.0 });
+            engine.snapshot_prices();
+        }
+        engine.update_price("BTC", 50_000.0);
+        let report = engine.evaluate();
+        assert_eq!(report.total_value_usd, 200_000.0);
+
+        // Returns are +1% / -0.99%: historical VaR ~ 0.99% of 100k
+        let historical = report.historical_var_usd.unwrap();
+        assert!((historical - 990.1).abs() < 1.0, "{}", historical);
+        let parametric = report.parametric_var_usd.unwrap();
+        assert!(parametric > 2_000.0 && parametric < 2_500.0, "{}", parametric);
+
+        let by_name: HashMap<_, _> = report.stress.iter().map(|s| (s.scenario.as_str(), s)).collect();
+        assert_eq!(by_name["btc_minus_20pct"].loss_usd, 20_000.0);
+        assert!(!by_name["btc_minus_20pct"].breached);
+        assert!(by_name["binance_outage"].breached);
+        assert!((by_name["usdt_depeg_95"].loss_usd - 5_000.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,315 @@
+//! Sharded cache of per-exchange top-of-book prices
+//!
+//! Symbols are spread over N shards by hash, each shard owning its own
+//! lock, so updates for different symbols proceed on different cores
+//! instead of serialising on one global map. Each shard also has a bounded
+//! ingestion queue drained by its own worker task, keeping book parsing
+//! threads off the locks entirely. The optimal price of a symbol is the
+//! best bid and best ask across exchanges with fresh quotes.
+//!
+//! Per-shard statistics expose update/read counts, lock contention (a
+//! write that could not take the lock immediately), queue depth and
+//! drops. `benches/price_cache_contention.rs` compares one shard (the
+//! global-lock layout) with N shards under concurrent load.
+
+use common::OrderBook;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceCacheConfig {
+    /// Rounded up to a power of two
+    pub shards: usize,
+    /// Per-shard ingestion queue
+    pub queue_capacity: usize,
+    /// Quotes older than this are ignored for optimal prices (ms)
+    pub stale_after_ms: u64,
+}
+
+impl Default for PriceCacheConfig {
+    fn default() -> Self {
+        Self {
+            shards: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(8) * 2,
+            queue_capacity: 4096,
+            stale_after_ms: 5_000,
+        }
+    }
+}
+
+/// Top of book on one exchange
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ExchangeQuote {
+    pub bid: f64,
+    pub bid_quantity: f64,
+    pub ask: f64,
+    pub ask_quantity: f64,
+    pub timestamp_ns: u64,
+}
+
+/// Best bid and ask across exchanges
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OptimalPrice {
+    pub symbol: String,
+    pub best_bid: f64,
+    pub best_bid_exchange: String,
+    pub best_ask: f64,
+    pub best_ask_exchange: String,
+    pub exchanges: usize,
+    pub timestamp_ns: u64,
+}
+
+impl OptimalPrice {
+    /// Negative when the best bid crosses the best ask
+    pub fn spread_bps(&self) -> f64 {
+        let mid = (self.best_bid + self.best_ask) / 2.0;
+        if mid > 0.0 {
+            (self.best_ask - self.best_bid) / mid * 10_000.0
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShardStats {
+    pub shard: usize,
+    pub symbols: usize,
+    pub updates: u64,
+    pub reads: u64,
+    /// Writes that had to wait for the lock
+    pub contended: u64,
+    pub queue_depth: u64,
+    /// Books dropped because the ingestion queue was full
+    pub dropped: u64,
+}
+
+#[derive(Default)]
+struct ShardCounters {
+    updates: AtomicU64,
+    reads: AtomicU64,
+    contended: AtomicU64,
+    queued: AtomicU64,
+    dropped: AtomicU64,
+}
+
+struct Shard {
+    /// symbol -> exchange -> quote
+    symbols: RwLock<HashMap<String, HashMap<String, ExchangeQuote>>>,
+    counters: ShardCounters,
+    sender: mpsc::Sender<OrderBook>,
+    receiver: Mutex<Option<mpsc::Receiver<OrderBook>>>,
+}
+
+/// Top-of-book cache sharded by symbol hash
+pub struct ShardedPriceCache {
+    config: PriceCacheConfig,
+    shards: Vec<Shard>,
+    mask: usize,
+}
+
+impl ShardedPriceCache {
+    pub fn new(config: PriceCacheConfig) -> Self {
+        let count = config.shards.max(1).next_power_of_two();
+        let shards = (0..count)
+            .map(|_| {
+                let (sender, receiver) = mpsc::channel(config.queue_capacity.max(1));
+                Shard {
+                    symbols: RwLock::new(HashMap::new()),
+                    counters: ShardCounters::default(),
+                    sender,
+                    receiver: Mutex::new(Some(receiver)),
+                }
+            })
+            .collect();
+        Self { config, shards, mask: count - 1 }
+    }
+
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    pub fn shard_for(&self, symbol: &str) -> usize {
+        let mut hasher = DefaultHasher::new();
+        symbol.hash(&mut hasher);
+        hasher.finish() as usize & self.mask
+    }
+
+    /// Apply a quote on the caller's thread
+    pub fn update(&self, exchange: &str, symbol: &str, quote: ExchangeQuote) {
+        let shard = &self.shards[self.shard_for(symbol)];
+        let mut symbols = match shard.symbols.try_write() {
+            Some(guard) => guard,
+            None => {
+                shard.counters.contended.fetch_add(1, Ordering::Relaxed);
+                shard.symbols.write()
+            }
+        };
+        let quotes = match symbols.get_mut(symbol) {
+            Some(quotes) => quotes,
+            None => symbols.entry(symbol.to_string()).or_default(),
+        };
+        match quotes.get_mut(exchange) {
+            Some(existing) if existing.timestamp_ns > quote.timestamp_ns => {}
+            Some(existing) => *existing = quote,
+            None => {
+                quotes.insert(exchange.to_string(), quote);
+            }
+        }
+        drop(symbols);
+        shard.counters.updates.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Apply a book's top of book; books without both sides are ignored
+    pub fn on_book(&self, book: &OrderBook) {
+        let (Some(bid), Some(ask)) = (book.best_bid(), book.best_ask()) else { return };
+        self.update(
+            &book.exchange.as_str().to_lowercase(),
+            book.symbol.as_str(),
+            ExchangeQuote {
+                bid: bid.price.to_f64(),
+                bid_quantity: bid.quantity.to_f64(),
+                ask: ask.price.to_f64(),
+                ask_quantity: ask.quantity.to_f64(),
+                timestamp_ns: book.timestamp_ns,
+            },
+        );
+    }
+
+    /// Queue a book for the shard's worker; false if the queue is full
+    pub fn submit(&self, book: OrderBook) -> bool {
+        let shard = &self.shards[self.shard_for(book.symbol.as_str())];
+        match shard.sender.try_send(book) {
+            Ok(()) => {
+                shard.counters.queued.fetch_add(1, Ordering::Relaxed);
+                true
+            }
+            Err(_) => {
+                shard.counters.dropped.fetch_add(1, Ordering::Relaxed);
+                false
+            }
+        }
+    }
+
+    pub fn quote(&self, exchange: &str, symbol: &str) -> Option<ExchangeQuote> {
+        let shard = &self.shards[self.shard_for(symbol)];
+        shard.counters.reads.fetch_add(1, Ordering::Relaxed);
+        shard.symbols.read().get(symbol)?.get(exchange).copied()
+    }
+
+    /// Best bid and ask over quotes fresh at `now_ns`
+    pub fn optimal(&self, symbol: &str, now_ns: u64) -> Option<OptimalPrice> {
+        let shard = &self.shards[self.shard_for(symbol)];
+        shard.counters.reads.fetch_add(1, Ordering::Relaxed);
+        let symbols = shard.symbols.read();
+        let stale_ns = self.config.stale_after_ms * 1_000_000;
+        let fresh: Vec<_> =
+            symbols.get(symbol)?.iter().filter(|(_, q)| now_ns.saturating_sub(q.timestamp_ns) <= stale_ns).collect();
+        let (bid_exchange, bid) = fresh.iter().max_by(|a, b| a.1.bid.total_cmp(&b.1.bid))?;
+        let (ask_exchange, ask) = fresh.iter().min_by(|a, b| a.1.ask.total_cmp(&b.1.ask))?;
+        Some(OptimalPrice {
+            symbol: symbol.to_string(),
+            best_bid: bid.bid,
+            best_bid_exchange: bid_exchange.to_string(),
+            best_ask: ask.ask,
+            best_ask_exchange: ask_exchange.to_string(),
+            exchanges: fresh.len(),
+            timestamp_ns: fresh.iter().map(|(_, q)| q.timestamp_ns).max().unwrap_or(now_ns),
+        })
+    }
+
+    pub fn shard_stats(&self) -> Vec<ShardStats> {
+        self.shards
+            .iter()
+            .enumerate()
+            .map(|(i, shard)| {
+                let stats = ShardStats {
+                    shard: i,
+                    symbols: shard.symbols.read().len(),
+                    updates: shard.counters.updates.load(Ordering::Relaxed),
+                    reads: shard.counters.reads.load(Ordering::Relaxed),
+                    contended: shard.counters.contended.load(Ordering::Relaxed),
+                    queue_depth: shard.counters.queued.load(Ordering::Relaxed),
+                    dropped: shard.counters.dropped.load(Ordering::Relaxed),
+                };
+                let label = i.to_string();
+                metrics::gauge!("price_cache_shard_symbols", "shard" => label.clone()).set(stats.symbols as f64);
+                metrics::gauge!("price_cache_shard_queue_depth", "shard" => label.clone()).set(stats.queue_depth as f64);
+                metrics::gauge!("price_cache_shard_contended", "shard" => label).set(stats.contended as f64);
+                stats
+            })
+            .collect()
+    }
+
+    /// One worker per shard draining its ingestion queue
+    pub fn spawn_workers(self: Arc<Self>) -> Vec<tokio::task::JoinHandle<()>> {
+        (0..self.shards.len())
+            .filter_map(|i| {
+                let Some(mut receiver) = self.shards[i].receiver.lock().take() else {
+                    warn!("Price cache shard {} worker already running", i);
+                    return None;
+                };
+                let cache = self.clone();
+                Some(tokio::spawn(async move {
+                    while let Some(book) = receiver.recv().await {
+                        cache.shards[i].counters.queued.fetch_sub(1, Ordering::Relaxed);
+                        cache.on_book(&book);
+                    }
+                }))
+            })
+            .collect()
+    }
+}
+
+impl Default for ShardedPriceCache {
+    fn default() -> Self {
+        Self::new(PriceCacheConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::testing::book;
+
+    #[tokio::test]
+    async fn test_sharded_optimal_price_and_workers() {
+        let cache = Arc::new(ShardedPriceCache::new(PriceCacheConfig { shards: 6, ..Default::default() }));
+        assert_eq!(cache.shard_count(), 8);
+        let now = 10_000_000_000;
+
+        cache.on_book(&book("binance", "BTCUSDT", 100.0, 101.0, now));
+        cache.on_book(&book("okx", "BTCUSDT", 100.5, 101.5, now));
+        // Stale quote with the best ask is ignored
+        cache.on_book(&book("bybit", "BTCUSDT", 99.0, 100.2, now - 6_000_000_000));
+        // Out-of-order update does not overwrite a newer quote
+        cache.on_book(&book("okx", "BTCUSDT", 90.0, 95.0, now - 1));
+
+        let optimal = cache.optimal("BTCUSDT", now).unwrap();
+        assert_eq!((optimal.best_bid_exchange.as_str(), optimal.best_ask_exchange.as_str()), ("okx", "binance"));
+        assert_eq!((optimal.best_bid, optimal.best_ask, optimal.exchanges), (100.5, 101.0, 2));
+
+        let workers = cache.clone().spawn_workers();
+        assert_eq!(workers.len(), 8);
+        for i in 0..64 {
+            assert!(cache.submit(book("binance", &format!("SYM{}USDT", i), 10.0, 11.0, now)));
+        }
+        for _ in 0..100 {
+            if cache.shard_stats().iter().map(|s| s.queue_depth).sum::<u64>() == 0 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        let stats = cache.shard_stats();
+        assert_eq!(stats.iter().map(|s| s.symbols).sum::<usize>(), 65);
+        assert_eq!(stats.iter().map(|s| s.updates).sum::<u64>(), 68);
+        assert!(stats.iter().filter(|s| s.symbols > 0).count() > 1, "symbols spread over shards");
+        assert_eq!(cache.quote("binance", "SYM3USDT").unwrap().ask_quantity, 1.0);
+    }
+}
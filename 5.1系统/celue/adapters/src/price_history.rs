@@ -0,0 +1,395 @@
+//! Tiered price history with compression
+//!
+//! Raw ticks accumulate in an open segment per exchange and symbol. When
+//! a tick crosses the segment boundary the segment is sealed: its ticks
+//! are stored zstd-compressed in the raw tier and rolled up into 1s and
+//! 1m OHLCV bars for the longer-lived tiers. Each tier has its own
+//! retention, so raw ticks are kept for hours while minute bars cover
+//! weeks. `query` picks the finest tier that still covers the requested
+//! range. Sealed segments are also written to disk when a directory is
+//! configured.
+
+use crate::microstructure::TradePrint;
+use crate::{AdapterError, AdapterResult};
+use common::NormalizedSnapshot;
+use parking_lot::RwLock;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::warn;
+
+const NS_PER_SEC: u64 = 1_000_000_000;
+const NS_PER_HOUR: u64 = 3_600 * NS_PER_SEC;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    /// Raw ticks are kept this long
+    pub raw_retention_hours: u64,
+    /// 1s bars are kept this long
+    pub second_retention_hours: u64,
+    /// 1m bars are kept this long
+    pub minute_retention_hours: u64,
+    /// Length of a sealed segment; a multiple of 60 keeps minute bars whole
+    pub segment_secs: u64,
+    pub compression_level: i32,
+    /// Sealed segments are written below this directory when set
+    pub dir: Option<PathBuf>,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            raw_retention_hours: 6,
+            second_retention_hours: 72,
+            minute_retention_hours: 30 * 24,
+            segment_secs: 300,
+            compression_level: 3,
+            dir: None,
+        }
+    }
+}
+
+/// A price observation (trade, or book mid with zero quantity)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Tick {
+    pub timestamp_ns: u64,
+    pub price: f64,
+    pub quantity: f64,
+}
+
+/// One OHLCV bar
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Ohlcv {
+    pub start_ns: u64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+    pub ticks: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Tier {
+    Raw,
+    Second,
+    Minute,
+}
+
+impl Tier {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Raw => "raw",
+            Self::Second => "1s",
+            Self::Minute => "1m",
+        }
+    }
+
+    fn bar_ns(self) -> u64 {
+        match self {
+            Self::Raw => 0,
+            Self::Second => NS_PER_SEC,
+            Self::Minute => 60 * NS_PER_SEC,
+        }
+    }
+}
+
+/// Query result from whichever tier served it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "tier", rename_all = "snake_case")]
+pub enum PriceSeries {
+    Raw { ticks: Vec<Tick> },
+    Second { bars: Vec<Ohlcv> },
+    Minute { bars: Vec<Ohlcv> },
+}
+
+/// Aggregate ticks (sorted by time) into bars of `bar_ns`
+pub fn aggregate(ticks: &[Tick], bar_ns: u64) -> Vec<Ohlcv> {
+    let mut bars: Vec<Ohlcv> = Vec::new();
+    for tick in ticks {
+        let start_ns = tick.timestamp_ns - tick.timestamp_ns % bar_ns;
+        match bars.last_mut() {
+            Some(bar) if bar.start_ns == start_ns => {
+                bar.high = bar.high.max(tick.price);
+                bar.low = bar.low.min(tick.price);
+                bar.close = tick.price;
+                bar.volume += tick.quantity;
+                bar.ticks += 1;
+            }
+            _ => bars.push(Ohlcv {
+                start_ns,
+                open: tick.price,
+                high: tick.price,
+                low: tick.price,
+                close: tick.price,
+                volume: tick.quantity,
+                ticks: 1,
+            }),
+        }
+    }
+    bars
+}
+
+/// A sealed, compressed block of records
+struct Segment {
+    start_ns: u64,
+    end_ns: u64,
+    data: Vec<u8>,
+}
+
+impl Segment {
+    fn seal<T: Serialize>(start_ns: u64, end_ns: u64, records: &[T], level: i32) -> AdapterResult<Self> {
+        let encoded = bincode::serialize(records).map_err(|e| AdapterError::Generic { message: e.to_string() })?;
+        Ok(Self { start_ns, end_ns, data: zstd::encode_all(&encoded[..], level)? })
+    }
+
+    fn open<T: DeserializeOwned>(&self) -> AdapterResult<Vec<T>> {
+        let decoded = zstd::decode_all(&self.data[..])?;
+        bincode::deserialize(&decoded).map_err(|e| AdapterError::Generic { message: e.to_string() })
+    }
+}
+
+#[derive(Default)]
+struct SeriesState {
+    segment_start_ns: u64,
+    open: Vec<Tick>,
+    tiers: HashMap<Tier, VecDeque<Segment>>,
+}
+
+/// Tiered price history store
+pub struct PriceHistory {
+    config: RetentionConfig,
+    series: RwLock<HashMap<(String, String), SeriesState>>,
+}
+
+impl PriceHistory {
+    pub fn new(config: RetentionConfig) -> Self {
+        Self { config, series: RwLock::new(HashMap::new()) }
+    }
+
+    fn segment_ns(&self) -> u64 {
+        self.config.segment_secs.max(60) * NS_PER_SEC
+    }
+
+    fn retention_ns(&self, tier: Tier) -> u64 {
+        NS_PER_HOUR
+            * match tier {
+                Tier::Raw => self.config.raw_retention_hours,
+                Tier::Second => self.config.second_retention_hours,
+                Tier::Minute => self.config.minute_retention_hours,
+            }
+    }
+
+    /// Record a tick, sealing the open segment when it rolls over
+    pub fn record(&self, exchange: &str, symbol: &str, tick: Tick) -> AdapterResult<()> {
+        let segment_ns = self.segment_ns();
+        let key = (exchange.to_lowercase(), symbol.to_string());
+        let mut series = self.series.write();
+        let state = series.entry(key.clone()).or_default();
+        let segment_start = tick.timestamp_ns - tick.timestamp_ns % segment_ns;
+        if state.open.is_empty() {
+            state.segment_start_ns = state.segment_start_ns.max(segment_start);
+        } else if segment_start > state.segment_start_ns {
+            self.seal(&key, state)?;
+            state.segment_start_ns = segment_start;
+        }
+        // Sealed segments are immutable, so ticks arriving after their rollover are dropped
+        if segment_start < state.segment_start_ns {
+            return Ok(());
+        }
+        let at = state.open.partition_point(|t| t.timestamp_ns <= tick.timestamp_ns);
+        state.open.insert(at, tick);
+        Ok(())
+    }
+
+    fn seal(&self, key: &(String, String), state: &mut SeriesState) -> AdapterResult<()> {
+        let ticks = std::mem::take(&mut state.open);
+        let start = state.segment_start_ns;
+        let end = start + self.segment_ns();
+        let level = self.config.compression_level;
+        let sealed = [
+            (Tier::Raw, Segment::seal(start, end, &ticks, level)?),
+            (Tier::Second, Segment::seal(start, end, &aggregate(&ticks, Tier::Second.bar_ns()), level)?),
+            (Tier::Minute, Segment::seal(start, end, &aggregate(&ticks, Tier::Minute.bar_ns()), level)?),
+        ];
+        for (tier, segment) in sealed {
+            if let Some(dir) = &self.config.dir {
+                let dir = dir.join(tier.as_str()).join(&key.0).join(&key.1);
+                let written = std::fs::create_dir_all(&dir)
+                    .and_then(|_| std::fs::write(dir.join(format!("{}.bin.zst", start)), &segment.data));
+                if let Err(e) = written {
+                    warn!("Failed to write {} segment for {} {}: {}", tier.as_str(), key.0, key.1, e);
+                }
+            }
+            let segments = state.tiers.entry(tier).or_default();
+            segments.push_back(segment);
+            let cutoff = end.saturating_sub(self.retention_ns(tier));
+            while segments.front().is_some_and(|s| s.end_ns <= cutoff) {
+                segments.pop_front();
+            }
+        }
+        Ok(())
+    }
+
+    /// Finest tier whose retention still covers `from_ns`
+    pub fn tier_for(&self, from_ns: u64, now_ns: u64) -> Tier {
+        [Tier::Raw, Tier::Second]
+            .into_iter()
+            .find(|tier| now_ns.saturating_sub(self.retention_ns(*tier)) <= from_ns)
+            .unwrap_or(Tier::Minute)
+    }
+
+    /// History over [from_ns, to_ns) from the tier chosen for the range
+    pub fn query(&self, exchange: &str, symbol: &str, from_ns: u64, to_ns: u64, now_ns: u64) -> AdapterResult<PriceSeries> {
+        let tier = self.tier_for(from_ns, now_ns);
+        let series = self.series.read();
+        let Some(state) = series.get(&(exchange.to_lowercase(), symbol.to_string())) else {
+            return Ok(match tier {
+                Tier::Raw => PriceSeries::Raw { ticks: Vec::new() },
+                Tier::Second => PriceSeries::Second { bars: Vec::new() },
+                Tier::Minute => PriceSeries::Minute { bars: Vec::new() },
+            });
+        };
+        let overlapping = state
+            .tiers
+            .get(&tier)
+            .into_iter()
+            .flatten()
+            .filter(|s| s.end_ns > from_ns && s.start_ns < to_ns);
+        let open = state.open.iter().copied().filter(|t| t.timestamp_ns >= from_ns && t.timestamp_ns < to_ns);
+
+        Ok(match tier {
+            Tier::Raw => {
+                let mut ticks = Vec::new();
+                for segment in overlapping {
+                    ticks.extend(segment.open::<Tick>()?.into_iter().filter(|t| t.timestamp_ns >= from_ns && t.timestamp_ns < to_ns));
+                }
+                ticks.extend(open);
+                PriceSeries::Raw { ticks }
+            }
+            bar_tier => {
+                let mut bars: BTreeMap<u64, Ohlcv> = BTreeMap::new();
+                for segment in overlapping {
+                    for bar in segment.open::<Ohlcv>()? {
+                        bars.insert(bar.start_ns, bar);
+                    }
+                }
+                for bar in aggregate(&open.collect::<Vec<_>>(), bar_tier.bar_ns()) {
+                    bars.insert(bar.start_ns, bar);
+                }
+                let bar_ns = bar_tier.bar_ns();
+                let bars: Vec<Ohlcv> = bars.into_values().filter(|b| b.start_ns + bar_ns > from_ns && b.start_ns < to_ns).collect();
+                if bar_tier == Tier::Second {
+                    PriceSeries::Second { bars }
+                } else {
+                    PriceSeries::Minute { bars }
+                }
+            }
+        })
+    }
+
+    /// Compressed bytes held per tier, across all series
+    pub fn stored_bytes(&self) -> HashMap<Tier, usize> {
+        let mut bytes = HashMap::new();
+        for state in self.series.read().values() {
+            for (tier, segments) in &state.tiers {
+                *bytes.entry(*tier).or_default() += segments.iter().map(|s| s.data.len()).sum::<usize>();
+            }
+        }
+        bytes
+    }
+
+    pub fn on_trade(&self, trade: &TradePrint) -> AdapterResult<()> {
+        self.record(
+            &trade.exchange,
+            &trade.symbol,
+            Tick { timestamp_ns: trade.timestamp_ns, price: trade.price, quantity: trade.quantity },
+        )
+    }
+
+    /// Record each book's mid price
+    pub fn on_snapshot(&self, snapshot: &NormalizedSnapshot) -> AdapterResult<()> {
+        for book in &snapshot.exchanges {
+            if let (Some(bid), Some(ask)) = (book.best_bid(), book.best_ask()) {
+                let tick = Tick {
+                    timestamp_ns: book.timestamp_ns,
+                    price: (bid.price.to_f64() + ask.price.to_f64()) / 2.0,
+                    quantity: 0.0,
+                };
+                self.record(book.exchange.as_str(), book.symbol.as_str(), tick)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Record mids from the snapshot stream
+    pub fn spawn(self: Arc<Self>, mut snapshots: broadcast::Receiver<NormalizedSnapshot>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match snapshots.recv().await {
+                    Ok(snapshot) => {
+                        if let Err(e) = self.on_snapshot(&snapshot) {
+                            warn!("Failed to record price history: {}", e);
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Price history lagged, skipped {} snapshots", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+}
+
+impl Default for PriceHistory {
+    fn default() -> Self {
+        Self::new(RetentionConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rollover_tiers_and_query() {
+        let history = PriceHistory::new(RetentionConfig {
+            raw_retention_hours: 1,
+            second_retention_hours: 2,
+            minute_retention_hours: 24,
+            segment_secs: 60,
+            ..Default::default()
+        });
+        // One tick every 500ms for three hours
+        let end = 3 * NS_PER_HOUR;
+        let mut ts = 0;
+        while ts < end {
+            let price = 100.0 + (ts / NS_PER_SEC % 10) as f64;
+            history.record("binance", "BTCUSDT", Tick { timestamp_ns: ts, price, quantity: 1.0 }).unwrap();
+            ts += NS_PER_SEC / 2;
+        }
+
+        let recent = history.query("binance", "BTCUSDT", end - 10 * NS_PER_SEC, end, end).unwrap();
+        assert!(matches!(&recent, PriceSeries::Raw { ticks } if ticks.len() == 20));
+
+        let PriceSeries::Second { bars } = history.query("binance", "BTCUSDT", end - 90 * 60 * NS_PER_SEC, end - 89 * 60 * NS_PER_SEC, end).unwrap() else {
+            panic!("expected 1s bars");
+        };
+        assert_eq!(bars.len(), 60);
+        assert_eq!(bars[0].ticks, 2);
+        assert_eq!(bars[0].volume, 2.0);
+
+        // Raw ticks older than an hour are gone; the minute tier still covers them
+        let PriceSeries::Minute { bars } = history.query("binance", "BTCUSDT", 0, 10 * 60 * NS_PER_SEC, end).unwrap() else {
+            panic!("expected 1m bars");
+        };
+        assert_eq!(bars.len(), 10);
+        assert_eq!((bars[0].open, bars[0].high, bars[0].low), (100.0, 109.0, 100.0));
+        assert_eq!(bars[0].ticks, 120);
+        assert!(history.stored_bytes()[&Tier::Raw] > 0);
+    }
+}
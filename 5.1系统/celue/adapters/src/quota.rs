@@ -0,0 +1,248 @@
+//! Exchange API quota and cost tracking
+//!
+//! Tracks REST request weight and WebSocket subscription usage against
+//! each exchange's documented limits, exposes remaining-quota gauges,
+//! predicts when a window will be exhausted at the current burn rate and
+//! sheds non-critical calls (stats polling, reconciliation) once the
+//! headroom reserved for execution-critical traffic runs low.
+
+use metrics::gauge;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Documented limits for one exchange
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExchangeQuotaLimits {
+    /// REST weight allowed per window
+    pub rest_weight_per_window: u64,
+    /// Length of the REST weight window (ms)
+    pub window_ms: u64,
+    /// Maximum concurrent WebSocket subscriptions
+    pub max_ws_subscriptions: u64,
+}
+
+/// Quota tracker configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaConfig {
+    pub limits: HashMap<String, ExchangeQuotaLimits>,
+    /// Fraction of each window reserved for execution-critical calls
+    pub critical_reserve_fraction: f64,
+}
+
+impl Default for QuotaConfig {
+    fn default() -> Self {
+        let mut limits = HashMap::new();
+        limits.insert("binance".to_string(), ExchangeQuotaLimits {
+            rest_weight_per_window: 6000,
+            window_ms: 60_000,
+            max_ws_subscriptions: 1024,
+        });
+        limits.insert("okx".to_string(), ExchangeQuotaLimits {
+            rest_weight_per_window: 20,
+            window_ms: 2_000,
+            max_ws_subscriptions: 480,
+        });
+        limits.insert("bybit".to_string(), ExchangeQuotaLimits {
+            rest_weight_per_window: 600,
+            window_ms: 5_000,
+            max_ws_subscriptions: 500,
+        });
+        limits.insert("huobi".to_string(), ExchangeQuotaLimits {
+            rest_weight_per_window: 100,
+            window_ms: 10_000,
+            max_ws_subscriptions: 100,
+        });
+        limits.insert("gateio".to_string(), ExchangeQuotaLimits {
+            rest_weight_per_window: 200,
+            window_ms: 10_000,
+            max_ws_subscriptions: 100,
+        });
+        Self {
+            limits,
+            critical_reserve_fraction: 0.2,
+        }
+    }
+}
+
+/// Call priority
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CallPriority {
+    /// Order placement, cancellation, hedging
+    Critical,
+    /// Stats polling, reconciliation, metadata refresh
+    NonCritical,
+}
+
+/// Point-in-time view of one exchange's quota
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaSnapshot {
+    pub exchange: String,
+    pub rest_used: u64,
+    pub rest_limit: u64,
+    pub rest_remaining: u64,
+    pub ws_subscriptions: u64,
+    pub ws_limit: u64,
+    /// Predicted time until the window is exhausted at the current rate (ms)
+    pub predicted_exhaustion_ms: Option<u64>,
+    pub throttled_calls: u64,
+}
+
+#[derive(Default)]
+struct ExchangeUsage {
+    /// (time, weight) of calls in the current window
+    calls: VecDeque<(Instant, u64)>,
+    used: u64,
+    ws_subscriptions: u64,
+    throttled: u64,
+}
+
+impl ExchangeUsage {
+    fn evict(&mut self, now: Instant, window: Duration) {
+        while let Some((at, weight)) = self.calls.front().copied() {
+            if now.duration_since(at) < window {
+                break;
+            }
+            self.calls.pop_front();
+            self.used = self.used.saturating_sub(weight);
+        }
+    }
+}
+
+/// API quota tracker
+pub struct QuotaTracker {
+    config: QuotaConfig,
+    usage: Mutex<HashMap<String, ExchangeUsage>>,
+}
+
+impl QuotaTracker {
+    pub fn new(config: QuotaConfig) -> Self {
+        Self {
+            config,
+            usage: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Reserve `weight` for a REST call; returns false if the call should be
+    /// deferred. Unknown exchanges are not tracked and always allowed.
+    pub fn try_acquire(&self, exchange: &str, weight: u64, priority: CallPriority) -> bool {
+        let Some(limits) = self.config.limits.get(exchange) else { return true };
+        let window = Duration::from_millis(limits.window_ms);
+        let now = Instant::now();
+
+        let mut usage = self.usage.lock();
+        let entry = usage.entry(exchange.to_string()).or_default();
+        entry.evict(now, window);
+
+        let budget = match priority {
+            CallPriority::Critical => limits.rest_weight_per_window,
+            CallPriority::NonCritical => {
+                let reserve = limits.rest_weight_per_window as f64 * self.config.critical_reserve_fraction;
+                limits.rest_weight_per_window.saturating_sub(reserve.ceil() as u64)
+            }
+        };
+        if entry.used + weight > budget {
+            entry.throttled += 1;
+            return false;
+        }
+
+        entry.calls.push_back((now, weight));
+        entry.used += weight;
+        let remaining = limits.rest_weight_per_window.saturating_sub(entry.used);
+        gauge!("api_quota_rest_remaining", "exchange" => exchange.to_string()).set(remaining as f64);
+        true
+    }
+
+    /// Sync with the used weight reported by the exchange (e.g. `X-MBX-USED-WEIGHT`)
+    pub fn observe_reported_usage(&self, exchange: &str, used_weight: u64) {
+        let mut usage = self.usage.lock();
+        let entry = usage.entry(exchange.to_string()).or_default();
+        if used_weight > entry.used {
+            entry.calls.push_back((Instant::now(), used_weight - entry.used));
+            entry.used = used_weight;
+        }
+    }
+
+    /// Register a WebSocket subscription; false if the limit is reached
+    pub fn subscribe(&self, exchange: &str) -> bool {
+        let limit = self.config.limits.get(exchange).map(|l| l.max_ws_subscriptions);
+        let mut usage = self.usage.lock();
+        let entry = usage.entry(exchange.to_string()).or_default();
+        if limit.is_some_and(|l| entry.ws_subscriptions >= l) {
+            return false;
+        }
+        entry.ws_subscriptions += 1;
+        gauge!("api_quota_ws_subscriptions", "exchange" => exchange.to_string()).set(entry.ws_subscriptions as f64);
+        true
+    }
+
+    pub fn unsubscribe(&self, exchange: &str) {
+        if let Some(entry) = self.usage.lock().get_mut(exchange) {
+            entry.ws_subscriptions = entry.ws_subscriptions.saturating_sub(1);
+        }
+    }
+
+    /// Current quota state for one exchange
+    pub fn snapshot(&self, exchange: &str) -> Option<QuotaSnapshot> {
+        let limits = self.config.limits.get(exchange)?;
+        let window = Duration::from_millis(limits.window_ms);
+        let now = Instant::now();
+
+        let mut usage = self.usage.lock();
+        let entry = usage.entry(exchange.to_string()).or_default();
+        entry.evict(now, window);
+
+        let remaining = limits.rest_weight_per_window.saturating_sub(entry.used);
+        let predicted_exhaustion_ms = entry.calls.front().and_then(|(first, _)| {
+            let elapsed_ms = now.duration_since(*first).as_millis().max(1) as f64;
+            let rate_per_ms = entry.used as f64 / elapsed_ms;
+            (rate_per_ms > 0.0).then(|| (remaining as f64 / rate_per_ms) as u64)
+        });
+
+        Some(QuotaSnapshot {
+            exchange: exchange.to_string(),
+            rest_used: entry.used,
+            rest_limit: limits.rest_weight_per_window,
+            rest_remaining: remaining,
+            ws_subscriptions: entry.ws_subscriptions,
+            ws_limit: limits.max_ws_subscriptions,
+            predicted_exhaustion_ms,
+            throttled_calls: entry.throttled,
+        })
+    }
+
+    /// Quota state for every configured exchange
+    pub fn snapshots(&self) -> Vec<QuotaSnapshot> {
+        let mut exchanges: Vec<&String> = self.config.limits.keys().collect();
+        exchanges.sort();
+        exchanges.into_iter().filter_map(|e| self.snapshot(e)).collect()
+    }
+}
+
+impl Default for QuotaTracker {
+    fn default() -> Self {
+        Self::new(QuotaConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_non_critical_calls_leave_critical_headroom() {
+        let tracker = QuotaTracker::default();
+        // okx: 20 per window, 20% reserved -> 16 for non-critical calls
+        for _ in 0..16 {
+            assert!(tracker.try_acquire("okx", 1, CallPriority::NonCritical));
+        }
+        assert!(!tracker.try_acquire("okx", 1, CallPriority::NonCritical));
+        assert!(tracker.try_acquire("okx", 4, CallPriority::Critical));
+        assert!(!tracker.try_acquire("okx", 1, CallPriority::Critical));
+
+        let snapshot = tracker.snapshot("okx").unwrap();
+        assert_eq!(snapshot.rest_remaining, 0);
+        assert_eq!(snapshot.throttled_calls, 2);
+    }
+}
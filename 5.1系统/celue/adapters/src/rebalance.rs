@@ -0,0 +1,402 @@
+//! Cross-exchange rebalancing planner for the funds module
+//! Models withdrawal fees, chain confirmation time and minimums, and
+//! recommends transfers that keep per-exchange balances within bands.
+//! With a fee oracle, live network costs replace the static congestion
+//! multiplier. With `auto_schedule`, authorized transfers are handed to a
+//! `TransferScheduler` such as the withdrawal worker's `TransferQueue`.
+
+use crate::fee_oracle::FeeOracle;
+use crate::funds::AssetBalance;
+use crate::venue_scorecard::VenueScorecard;
+use crate::{AdapterError, AdapterResult};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tracing::{debug, info};
+
+/// Transfer characteristics of one withdrawal network for an asset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainTransferProfile {
+    /// Network name (e.g. "TRC20", "ERC20")
+    pub chain: String,
+    /// Flat withdrawal fee in asset units
+    pub withdrawal_fee: f64,
+    /// Minimum withdrawal amount in asset units
+    pub min_withdrawal: f64,
+    /// Typical confirmation time in seconds
+    pub confirmation_time_secs: u64,
+    /// Congestion multiplier applied to fee and confirmation time (1.0 = normal)
+    #[serde(default = "default_congestion")]
+    pub congestion_multiplier: f64,
+}
+
+fn default_congestion() -> f64 {
+    1.0
+}
+
+impl ChainTransferProfile {
+    /// Fee adjusted for current congestion
+    pub fn effective_fee(&self) -> f64 {
+        self.withdrawal_fee * self.congestion_multiplier.max(1.0)
+    }
+
+    /// Confirmation time adjusted for current congestion
+    pub fn effective_confirmation_secs(&self) -> f64 {
+        self.confirmation_time_secs as f64 * self.congestion_multiplier.max(1.0)
+    }
+
+    /// Withdrawal fee for `amount` of `asset`: the configured fee, raised to
+    /// the live network cost when the oracle has a fresh estimate, otherwise
+    /// the congestion-adjusted fee
+    pub fn estimated_fee(&self, oracle: Option<&FeeOracle>, exchange: &str, asset: &str, amount: f64) -> f64 {
+        match oracle.and_then(|o| o.estimate_for(exchange, &self.chain, asset, amount)).and_then(|e| e.fee_in_asset) {
+            Some(network_fee) => self.withdrawal_fee.max(network_fee),
+            None => self.effective_fee(),
+        }
+    }
+}
+
+/// Allowed share of an asset's total balance held on one exchange
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceBand {
+    /// Lower bound (fraction of total, 0.0 - 1.0)
+    pub min_share: f64,
+    /// Target share after rebalancing
+    pub target_share: f64,
+    /// Upper bound (fraction of total, 0.0 - 1.0)
+    pub max_share: f64,
+}
+
+/// Rebalancing configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RebalanceConfig {
+    /// Automatically schedule recommended transfers
+    #[serde(default)]
+    pub auto_schedule: bool,
+    /// Bands per exchange per asset: exchange -> asset -> band
+    #[serde(default)]
+    pub bands: HashMap<String, HashMap<String, BalanceBand>>,
+    /// Withdrawal networks per exchange per asset: exchange -> asset -> chains
+    #[serde(default)]
+    pub withdrawal_fees: HashMap<String, HashMap<String, Vec<ChainTransferProfile>>>,
+    /// Reject transfers whose fee exceeds this fraction of the amount
+    #[serde(default = "default_max_fee_ratio")]
+    pub max_fee_ratio: f64,
+    /// Cost charged per hour of confirmation time, as a fraction of amount
+    #[serde(default)]
+    pub time_cost_per_hour: f64,
+}
+
+fn default_max_fee_ratio() -> f64 {
+    0.01
+}
+
+/// A recommended (or scheduled) transfer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferRecommendation {
+    pub asset: String,
+    pub from_exchange: String,
+    pub to_exchange: String,
+    pub chain: String,
+    /// Amount withdrawn from the source exchange
+    pub amount: f64,
+    /// Expected fee in asset units
+    pub fee: f64,
+    /// Expected arrival time in seconds
+    pub eta_secs: u64,
+    /// Whether the transfer was scheduled automatically
+    pub scheduled: bool,
+}
+
+/// Receiver of automatically scheduled transfers
+pub trait TransferScheduler: Send + Sync {
+    fn schedule(&self, transfer: &TransferRecommendation) -> AdapterResult<()>;
+}
+
+/// Scheduled transfers waiting for the withdrawal worker
+#[derive(Default)]
+pub struct TransferQueue {
+    pending: Mutex<VecDeque<TransferRecommendation>>,
+}
+
+impl TransferQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take every queued transfer, oldest first
+    pub fn drain(&self) -> Vec<TransferRecommendation> {
+        self.pending.lock().drain(..).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.lock().is_empty()
+    }
+}
+
+impl TransferScheduler for TransferQueue {
+    fn schedule(&self, transfer: &TransferRecommendation) -> AdapterResult<()> {
+        self.pending.lock().push_back(transfer.clone());
+        Ok(())
+    }
+}
+
+/// Rebalancing planner
+pub struct RebalancePlanner {
+    config: RebalanceConfig,
+    scorecard: Option<Arc<VenueScorecard>>,
+    fee_oracle: Option<Arc<FeeOracle>>,
+    scheduler: Option<Arc<dyn TransferScheduler>>,
+}
+
+impl RebalancePlanner {
+    pub fn new(config: RebalanceConfig) -> Self {
+        Self { config, scorecard: None, fee_oracle: None, scheduler: None }
+    }
+
+    /// Tilt target shares toward better-scoring venues (kept inside each band)
+    pub fn with_scorecard(mut self, scorecard: Arc<VenueScorecard>) -> Self {
+        self.scorecard = Some(scorecard);
+        self
+    }
+
+    /// Price withdrawals from live gas estimates
+    pub fn with_fee_oracle(mut self, oracle: Arc<FeeOracle>) -> Self {
+        self.fee_oracle = Some(oracle);
+        self
+    }
+
+    /// Where `auto_schedule` sends authorized transfers
+    pub fn with_scheduler(mut self, scheduler: Arc<dyn TransferScheduler>) -> Self {
+        self.scheduler = Some(scheduler);
+        self
+    }
+
+    /// Whether recommended transfers should be scheduled without review
+    pub fn auto_schedule(&self) -> bool {
+        self.config.auto_schedule
+    }
+
+    /// Hand an authorized transfer to the scheduler and mark it scheduled
+    pub fn schedule(&self, transfer: &mut TransferRecommendation) -> AdapterResult<()> {
+        let scheduler = self.scheduler.as_ref().ok_or_else(|| {
+            AdapterError::Configuration("auto_schedule is set but no transfer scheduler is configured".to_string())
+        })?;
+        scheduler.schedule(transfer)?;
+        transfer.scheduled = true;
+        info!(
+            "Scheduled rebalance: {} {} {} -> {} via {}",
+            transfer.amount, transfer.asset, transfer.from_exchange, transfer.to_exchange, transfer.chain
+        );
+        Ok(())
+    }
+
+    /// Plan transfers that bring every exchange back inside its band
+    pub fn plan(&self, balances: &[AssetBalance]) -> Vec<TransferRecommendation> {
+        let mut by_asset: HashMap<&str, Vec<&AssetBalance>> = HashMap::new();
+        for balance in balances {
+            by_asset.entry(balance.asset.as_str()).or_default().push(balance);
+        }
+
+        let mut recommendations = Vec::new();
+        for (asset, holdings) in by_asset {
+            recommendations.extend(self.plan_asset(asset, &holdings));
+        }
+        recommendations
+    }
+
+    fn plan_asset(&self, asset: &str, holdings: &[&AssetBalance]) -> Vec<TransferRecommendation> {
+        let total: f64 = holdings.iter().map(|b| b.total).sum();
+        if total <= 0.0 {
+            return Vec::new();
+        }
+
+        let venue_weights = self.scorecard.as_ref().map(|s| s.relative_weights()).unwrap_or_default();
+
+        // Positive = surplus above target, negative = deficit below target
+        let mut surpluses = Vec::new();
+        let mut deficits = Vec::new();
+        for holding in holdings {
+            let Some(band) = self.band(&holding.exchange, asset) else { continue };
+            let share = holding.total / total;
+            let weight = venue_weights.get(&holding.exchange).copied().unwrap_or(1.0);
+            let target = (band.target_share * weight).clamp(band.min_share, band.max_share) * total;
+            if share > band.max_share {
+                // Only free balance can be withdrawn
+                let movable = (holding.total - target).min(holding.free);
+                if movable > 0.0 {
+                    surpluses.push((holding.exchange.clone(), movable));
+                }
+            } else if share < band.min_share {
+                deficits.push((holding.exchange.clone(), target - holding.total));
+            }
+        }
+
+        surpluses.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        deficits.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+        let mut recommendations = Vec::new();
+        for (to_exchange, mut needed) in deficits {
+            for (from_exchange, available) in surpluses.iter_mut() {
+                if needed <= 0.0 {
+                    break;
+                }
+                if *available <= 0.0 {
+                    continue;
+                }
+                let amount = needed.min(*available);
+                match self.cheapest_route(from_exchange, asset, amount) {
+                    Some(chain) => {
+                        let fee = chain.estimated_fee(self.fee_oracle.as_deref(), from_exchange, asset, amount);
+                        recommendations.push(TransferRecommendation {
+                            asset: asset.to_string(),
+                            from_exchange: from_exchange.clone(),
+                            to_exchange: to_exchange.clone(),
+                            chain: chain.chain.clone(),
+                            amount,
+                            fee,
+                            eta_secs: chain.effective_confirmation_secs() as u64,
+                            scheduled: false,
+                        });
+                        *available -= amount;
+                        needed -= amount - fee;
+                    }
+                    None => debug!(
+                        "No viable route for {} {} from {}",
+                        amount, asset, from_exchange
+                    ),
+                }
+            }
+        }
+        recommendations
+    }
+
+    fn band(&self, exchange: &str, asset: &str) -> Option<&BalanceBand> {
+        self.config.bands.get(exchange).and_then(|assets| assets.get(asset))
+    }
+
+    /// Pick the chain with the lowest fee plus time cost that satisfies minimums
+    fn cheapest_route(&self, exchange: &str, asset: &str, amount: f64) -> Option<&ChainTransferProfile> {
+        let chains = self.config.withdrawal_fees.get(exchange)?.get(asset)?;
+        chains
+            .iter()
+            .filter(|c| amount >= c.min_withdrawal)
+            .filter(|c| {
+                c.estimated_fee(self.fee_oracle.as_deref(), exchange, asset, amount) <= amount * self.config.max_fee_ratio
+            })
+            .min_by(|a, b| {
+                self.route_cost(a, exchange, asset, amount)
+                    .partial_cmp(&self.route_cost(b, exchange, asset, amount))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+    }
+
+    fn route_cost(&self, chain: &ChainTransferProfile, exchange: &str, asset: &str, amount: f64) -> f64 {
+        let hours = chain.effective_confirmation_secs() / 3600.0;
+        chain.estimated_fee(self.fee_oracle.as_deref(), exchange, asset, amount) + amount * self.config.time_cost_per_hour * hours
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::funds::{FundsAdapter, FundsConfig};
+    use crate::trading_mode::{TradingMode, TradingModeController};
+
+    fn balance(exchange: &str, total: f64) -> AssetBalance {
+        AssetBalance {
+            asset: "USDT".to_string(),
+            exchange: exchange.to_string(),
+            free: total,
+            locked: 0.0,
+            total,
+            updated_ns: 0,
+        }
+    }
+
+    fn config(auto_schedule: bool) -> RebalanceConfig {
+        let band = BalanceBand { min_share: 0.4, target_share: 0.5, max_share: 0.6 };
+        let trc20 = ChainTransferProfile {
+            chain: "TRC20".to_string(),
+            withdrawal_fee: 1.0,
+            min_withdrawal: 10.0,
+            confirmation_time_secs: 60,
+            congestion_multiplier: 1.0,
+        };
+        RebalanceConfig {
+            auto_schedule,
+            bands: ["binance", "okx"]
+                .iter()
+                .map(|e| (e.to_string(), HashMap::from([("USDT".to_string(), band.clone())])))
+                .collect(),
+            withdrawal_fees: HashMap::from([(
+                "binance".to_string(),
+                HashMap::from([("USDT".to_string(), vec![trc20])]),
+            )]),
+            max_fee_ratio: default_max_fee_ratio(),
+            time_cost_per_hour: 0.0,
+        }
+    }
+
+    fn funds(mode: TradingMode) -> FundsAdapter {
+        let funds = FundsAdapter::new(FundsConfig::default())
+            .with_trading_mode(Arc::new(TradingModeController::new(mode)));
+        funds.update_balance(balance("binance", 8_000.0));
+        funds.update_balance(balance("okx", 2_000.0));
+        funds
+    }
+
+    #[test]
+    fn test_plan_moves_surplus_back_to_target() {
+        let transfers = RebalancePlanner::new(config(false)).plan(&[balance("binance", 8_000.0), balance("okx", 2_000.0)]);
+        assert_eq!(transfers.len(), 1);
+        let transfer = &transfers[0];
+        assert_eq!((transfer.from_exchange.as_str(), transfer.to_exchange.as_str()), ("binance", "okx"));
+        assert_eq!(transfer.chain, "TRC20");
+        assert_eq!(transfer.amount, 3_000.0);
+        assert_eq!(transfer.fee, 1.0);
+        assert!(!transfer.scheduled);
+    }
+
+    #[test]
+    fn test_auto_schedule_queues_authorized_transfers() {
+        let queue = Arc::new(TransferQueue::new());
+        let planner = RebalancePlanner::new(config(true)).with_scheduler(queue.clone());
+
+        // Outside live mode nothing reaches the queue
+        let transfers = funds(TradingMode::DryRun).plan_rebalance(&planner);
+        assert_eq!(transfers.len(), 1);
+        assert!(!transfers[0].scheduled);
+        assert!(queue.is_empty());
+
+        let transfers = funds(TradingMode::Live).plan_rebalance(&planner);
+        assert!(transfers[0].scheduled);
+        let queued = queue.drain();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].amount, 3_000.0);
+        assert!(queue.is_empty());
+
+        // Without an explicit controller the adapter stays in dry-run and refuses transfers
+        let unconfigured = FundsAdapter::new(FundsConfig::default());
+        unconfigured.update_balance(balance("binance", 8_000.0));
+        unconfigured.update_balance(balance("okx", 2_000.0));
+        assert!(!unconfigured.plan_rebalance(&planner)[0].scheduled);
+        assert!(unconfigured.authorize_transfer(&transfers[0]).is_err());
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_auto_schedule_without_scheduler_leaves_recommendations() {
+        let planner = RebalancePlanner::new(config(true));
+        let transfers = funds(TradingMode::Live).plan_rebalance(&planner);
+        assert_eq!(transfers.len(), 1);
+        assert!(!transfers[0].scheduled);
+        let mut transfer = transfers[0].clone();
+        assert!(matches!(planner.schedule(&mut transfer), Err(AdapterError::Configuration(_))));
+    }
+}
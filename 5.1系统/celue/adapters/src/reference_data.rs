@@ -0,0 +1,388 @@
+//! Third-party reference data (CoinGecko / Coinglass)
+//!
+//! Polls public aggregator APIs on a schedule for venue-independent
+//! reference prices (CoinGecko) and derivatives positioning — open interest
+//! and recent long/short liquidations (Coinglass). Values are normalized to
+//! canonical asset codes and kept with their observation time; lookups only
+//! return values younger than the configured maximum age for their kind, so
+//! strategies and risk checks never act on a feed that has silently stopped.
+
+use crate::{AdapterError, AdapterResult};
+use common::SharedClock;
+use parking_lot::RwLock;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Quote assets treated as USD when comparing against reference prices
+const USD_QUOTES: [&str; 5] = ["USDT", "USDC", "BUSD", "FDUSD", "USD"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceSource {
+    CoinGecko,
+    Coinglass,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceKind {
+    /// Aggregated USD price
+    Price,
+    /// Open interest across venues (USD)
+    OpenInterest,
+    /// Long positions liquidated over the last hour (USD)
+    LongLiquidations,
+    /// Short positions liquidated over the last hour (USD)
+    ShortLiquidations,
+}
+
+impl ReferenceKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReferenceKind::Price => "price",
+            ReferenceKind::OpenInterest => "open_interest",
+            ReferenceKind::LongLiquidations => "long_liquidations",
+            ReferenceKind::ShortLiquidations => "short_liquidations",
+        }
+    }
+}
+
+/// One normalized observation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReferenceValue {
+    pub source: ReferenceSource,
+    /// Canonical asset code, e.g. "BTC"
+    pub asset: String,
+    pub kind: ReferenceKind,
+    pub value: f64,
+    pub observed_at_ns: u64,
+}
+
+/// Age of the latest value per asset and kind
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReferenceFreshness {
+    pub asset: String,
+    pub kind: ReferenceKind,
+    pub source: ReferenceSource,
+    pub age_ms: u64,
+    pub fresh: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReferenceDataConfig {
+    pub poll_interval: Duration,
+    pub coingecko_url: String,
+    /// Canonical asset -> CoinGecko coin id
+    pub coingecko_ids: HashMap<String, String>,
+    pub coinglass_url: String,
+    /// Coinglass requests are skipped without a key
+    pub coinglass_api_key: Option<String>,
+    pub coinglass_assets: Vec<String>,
+    /// Reference prices older than this are ignored
+    pub price_max_age_ms: u64,
+    /// Open interest and liquidations older than this are ignored
+    pub derivatives_max_age_ms: u64,
+}
+
+impl Default for ReferenceDataConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(60),
+            coingecko_url: "https://api.coingecko.com/api/v3".to_string(),
+            coingecko_ids: HashMap::from([
+                ("BTC".to_string(), "bitcoin".to_string()),
+                ("ETH".to_string(), "ethereum".to_string()),
+            ]),
+            coinglass_url: "https://open-api.coinglass.com/public/v2".to_string(),
+            coinglass_api_key: None,
+            coinglass_assets: vec!["BTC".to_string(), "ETH".to_string()],
+            price_max_age_ms: 180_000,
+            derivatives_max_age_ms: 900_000,
+        }
+    }
+}
+
+/// Scheduled third-party data collection with freshness tracking
+pub struct ReferenceDataService {
+    config: ReferenceDataConfig,
+    values: RwLock<HashMap<(String, ReferenceKind), ReferenceValue>>,
+    http_client: Client,
+    clock: SharedClock,
+}
+
+impl ReferenceDataService {
+    pub fn new(config: ReferenceDataConfig) -> Self {
+        Self {
+            config,
+            values: RwLock::new(HashMap::new()),
+            http_client: Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .expect("Failed to create HTTP client"),
+            clock: common::clock::system_clock(),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Store observations, keeping the newest per asset and kind
+    pub fn ingest(&self, values: Vec<ReferenceValue>) {
+        let mut stored = self.values.write();
+        for value in values.into_iter().filter(|v| v.value.is_finite() && v.value >= 0.0) {
+            let key = (canonical_asset(&value.asset), value.kind);
+            if stored.get(&key).is_none_or(|prev| prev.observed_at_ns <= value.observed_at_ns) {
+                stored.insert(key, ReferenceValue { asset: canonical_asset(&value.asset), ..value });
+            }
+        }
+    }
+
+    /// Latest value if it is still fresh
+    pub fn latest(&self, asset: &str, kind: ReferenceKind) -> Option<ReferenceValue> {
+        let value = self.values.read().get(&(canonical_asset(asset), kind)).cloned()?;
+        (self.age_ms(&value) <= self.max_age_ms(kind)).then_some(value)
+    }
+
+    pub fn reference_price(&self, asset: &str) -> Option<f64> {
+        self.latest(asset, ReferenceKind::Price).map(|v| v.value)
+    }
+
+    /// Signed deviation (%) of a USD-quoted symbol's price from the fresh
+    /// reference price of its base asset
+    pub fn deviation_pct(&self, symbol: &str, price: f64) -> Option<f64> {
+        let base = usd_base_asset(symbol)?;
+        let reference = self.reference_price(&base).filter(|p| *p > 0.0)?;
+        Some((price - reference) / reference * 100.0)
+    }
+
+    /// Age of every stored value; also exported as gauges
+    pub fn freshness(&self) -> Vec<ReferenceFreshness> {
+        let mut report: Vec<ReferenceFreshness> = self
+            .values
+            .read()
+            .values()
+            .map(|v| {
+                let age_ms = self.age_ms(v);
+                ReferenceFreshness {
+                    asset: v.asset.clone(),
+                    kind: v.kind,
+                    source: v.source,
+                    age_ms,
+                    fresh: age_ms <= self.max_age_ms(v.kind),
+                }
+            })
+            .collect();
+        report.sort_by(|a, b| (&a.asset, a.kind.as_str()).cmp(&(&b.asset, b.kind.as_str())));
+        for f in &report {
+            metrics::gauge!("reference_data_age_ms", "asset" => f.asset.clone(), "kind" => f.kind.as_str())
+                .set(f.age_ms as f64);
+        }
+        report
+    }
+
+    /// Poll every configured source once
+    pub async fn poll_once(&self) -> AdapterResult<usize> {
+        let now_ns = self.clock.now_ns();
+        let mut values = Vec::new();
+        if !self.config.coingecko_ids.is_empty() {
+            match self.fetch_coingecko(now_ns).await {
+                Ok(prices) => values.extend(prices),
+                Err(e) => warn!("CoinGecko poll failed: {}", e),
+            }
+        }
+        if self.config.coinglass_api_key.is_some() {
+            for asset in &self.config.coinglass_assets {
+                match self.fetch_coinglass(asset, now_ns).await {
+                    Ok(signals) => values.extend(signals),
+                    Err(e) => warn!("Coinglass poll failed for {}: {}", asset, e),
+                }
+            }
+        }
+        let collected = values.len();
+        debug!("reference data poll collected {} values", collected);
+        self.ingest(values);
+        self.freshness();
+        Ok(collected)
+    }
+
+    /// Spawn the polling loop
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.config.poll_interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.poll_once().await {
+                    warn!("reference data poll cycle failed: {}", e);
+                }
+            }
+        })
+    }
+
+    async fn fetch_coingecko(&self, now_ns: u64) -> AdapterResult<Vec<ReferenceValue>> {
+        let mut ids: Vec<&str> = self.config.coingecko_ids.values().map(String::as_str).collect();
+        ids.sort_unstable();
+        let url = format!("{}/simple/price?ids={}&vs_currencies=usd", self.config.coingecko_url, ids.join(","));
+        let body = self.get_json(self.http_client.get(&url)).await?;
+        Ok(parse_coingecko_prices(&body, &self.config.coingecko_ids, now_ns))
+    }
+
+    async fn fetch_coinglass(&self, asset: &str, now_ns: u64) -> AdapterResult<Vec<ReferenceValue>> {
+        let key = self.config.coinglass_api_key.clone().unwrap_or_default();
+        let oi_url = format!("{}/open_interest?symbol={}", self.config.coinglass_url, asset);
+        let body = self.get_json(self.http_client.get(&oi_url).header("coinglassSecret", &key)).await?;
+        let mut values: Vec<ReferenceValue> = parse_coinglass_open_interest(asset, &body, now_ns).into_iter().collect();
+
+        let liq_url = format!("{}/liquidation_info?symbol={}&time_type=h1", self.config.coinglass_url, asset);
+        let body = self.get_json(self.http_client.get(&liq_url).header("coinglassSecret", &key)).await?;
+        values.extend(parse_coinglass_liquidations(asset, &body, now_ns));
+        if values.is_empty() {
+            return Err(AdapterError::Validation { message: format!("unexpected Coinglass payload for {}", asset) });
+        }
+        Ok(values)
+    }
+
+    async fn get_json(&self, request: reqwest::RequestBuilder) -> AdapterResult<serde_json::Value> {
+        request
+            .send()
+            .await
+            .map_err(|e| AdapterError::Connection(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| AdapterError::Connection(e.to_string()))
+    }
+
+    fn age_ms(&self, value: &ReferenceValue) -> u64 {
+        self.clock.now_ns().saturating_sub(value.observed_at_ns) / 1_000_000
+    }
+
+    fn max_age_ms(&self, kind: ReferenceKind) -> u64 {
+        match kind {
+            ReferenceKind::Price => self.config.price_max_age_ms,
+            _ => self.config.derivatives_max_age_ms,
+        }
+    }
+}
+
+/// Parse `/simple/price?vs_currencies=usd`: `{"bitcoin": {"usd": 65000}}`
+pub fn parse_coingecko_prices(body: &serde_json::Value, ids: &HashMap<String, String>, now_ns: u64) -> Vec<ReferenceValue> {
+    ids.iter()
+        .filter_map(|(asset, id)| {
+            let price = json_f64(body.get(id)?.get("usd")?)?;
+            Some(ReferenceValue {
+                source: ReferenceSource::CoinGecko,
+                asset: canonical_asset(asset),
+                kind: ReferenceKind::Price,
+                value: price,
+                observed_at_ns: now_ns,
+            })
+        })
+        .collect()
+}
+
+/// Parse Coinglass open interest; uses the "All" aggregate row when
+/// present, otherwise sums the per-exchange rows
+pub fn parse_coinglass_open_interest(asset: &str, body: &serde_json::Value, now_ns: u64) -> Option<ReferenceValue> {
+    let rows = body.get("data")?.as_array()?;
+    let total = match rows.iter().find(|r| r.get("exchangeName").and_then(|n| n.as_str()) == Some("All")) {
+        Some(all) => json_f64(all.get("openInterest")?)?,
+        None => rows.iter().filter_map(|r| r.get("openInterest").and_then(json_f64)).sum(),
+    };
+    Some(ReferenceValue {
+        source: ReferenceSource::Coinglass,
+        asset: canonical_asset(asset),
+        kind: ReferenceKind::OpenInterest,
+        value: total,
+        observed_at_ns: now_ns,
+    })
+}
+
+/// Parse Coinglass liquidation totals (`longVolUsd` / `shortVolUsd`)
+pub fn parse_coinglass_liquidations(asset: &str, body: &serde_json::Value, now_ns: u64) -> Vec<ReferenceValue> {
+    let Some(data) = body.get("data") else { return Vec::new() };
+    let entry = data.as_array().and_then(|rows| rows.first()).unwrap_or(data);
+    [("longVolUsd", ReferenceKind::LongLiquidations), ("shortVolUsd", ReferenceKind::ShortLiquidations)]
+        .into_iter()
+        .filter_map(|(field, kind)| {
+            Some(ReferenceValue {
+                source: ReferenceSource::Coinglass,
+                asset: canonical_asset(asset),
+                kind,
+                value: json_f64(entry.get(field)?)?,
+                observed_at_ns: now_ns,
+            })
+        })
+        .collect()
+}
+
+fn json_f64(value: &serde_json::Value) -> Option<f64> {
+    match value {
+        serde_json::Value::String(s) => s.parse().ok(),
+        serde_json::Value::Number(n) => n.as_f64(),
+        _ => None,
+    }
+}
+
+fn canonical_asset(asset: &str) -> String {
+    match asset.to_uppercase().as_str() {
+        "XBT" => "BTC".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Base asset of a USD-quoted symbol ("BTC/USDT", "btc-usdt", "BTCUSDT")
+fn usd_base_asset(symbol: &str) -> Option<String> {
+    let upper = symbol.to_uppercase();
+    if let Some((base, quote)) = upper.split_once(['/', '-', '_']) {
+        return USD_QUOTES.contains(&quote).then(|| canonical_asset(base));
+    }
+    USD_QUOTES
+        .iter()
+        .find_map(|quote| upper.strip_suffix(quote).filter(|base| !base.is_empty()))
+        .map(canonical_asset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{Clock, SimulatedClock};
+
+    #[test]
+    fn test_parse_normalize_and_expire() {
+        let clock = Arc::new(SimulatedClock::from_nanos(1_000_000_000_000));
+        let service = ReferenceDataService::new(ReferenceDataConfig::default()).with_clock(clock.clone());
+        let now_ns = clock.now_ns();
+
+        let gecko = serde_json::json!({ "bitcoin": { "usd": 50000.0 }, "ethereum": { "usd": "3000" } });
+        let prices = parse_coingecko_prices(&gecko, &ReferenceDataConfig::default().coingecko_ids, now_ns);
+        assert_eq!(prices.len(), 2);
+        let oi = serde_json::json!({ "data": [
+            { "exchangeName": "Binance", "openInterest": 4.0e9 },
+            { "exchangeName": "OKX", "openInterest": 2.0e9 }
+        ] });
+        let liquidations = serde_json::json!({ "data": { "longVolUsd": "1200000", "shortVolUsd": 300000 } });
+        service.ingest(prices);
+        service.ingest(parse_coinglass_open_interest("xbt", &oi, now_ns).into_iter().collect());
+        service.ingest(parse_coinglass_liquidations("BTC", &liquidations, now_ns));
+
+        assert_eq!(service.reference_price("btc"), Some(50000.0));
+        assert_eq!(service.latest("BTC", ReferenceKind::OpenInterest).unwrap().value, 6.0e9);
+        assert_eq!(service.latest("BTC", ReferenceKind::LongLiquidations).unwrap().value, 1.2e6);
+        assert!((service.deviation_pct("BTC/USDT", 51000.0).unwrap() - 2.0).abs() < 1e-9);
+        assert!((service.deviation_pct("ETHUSDT", 2970.0).unwrap() + 1.0).abs() < 1e-9);
+        assert!(service.deviation_pct("ETH/BTC", 0.06).is_none());
+
+        // Prices expire after 3 minutes, derivatives signals after 15
+        clock.advance(chrono::Duration::minutes(5));
+        assert!(service.reference_price("BTC").is_none());
+        assert!(service.latest("BTC", ReferenceKind::OpenInterest).is_some());
+        let freshness = service.freshness();
+        assert_eq!(freshness.len(), 5);
+        assert!(freshness.iter().any(|f| f.kind == ReferenceKind::Price && !f.fresh && f.age_ms == 300_000));
+    }
+}
@@ -0,0 +1,308 @@
+//! Order-book replay for shadow execution
+//!
+//! Feeds recorded `OrderBook` snapshots through a matching simulation
+//! instead of synthetic prices. Orders become active only after an
+//! injected latency, match against the historical book with price-time
+//! priority (each snapshot's liquidity is shared between competing shadow
+//! orders) and may fill partially when the visible depth is insufficient.
+//! Mid prices are forwarded to an optional `ShadowMatchingEngine` so stop,
+//! trailing-stop and OCO orders trigger on the same replayed stream.
+
+use crate::order_matching::{ShadowFill, ShadowMatchingEngine};
+use crate::{AdapterError, AdapterResult};
+use common::{OrderBook, SimulatedClock, Side};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Replay configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayConfig {
+    /// Order entry latency injected before an order reaches the book (ms)
+    pub latency_ms: u64,
+    /// Allow fills smaller than the order quantity
+    pub partial_fills: bool,
+    /// Book levels considered when matching
+    pub max_levels: usize,
+}
+
+impl Default for ReplayConfig {
+    fn default() -> Self {
+        Self {
+            latency_ms: 5,
+            partial_fills: true,
+            max_levels: 20,
+        }
+    }
+}
+
+/// A shadow order matched against the replayed book
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayOrder {
+    pub id: u64,
+    pub exchange: String,
+    pub symbol: String,
+    pub side: Side,
+    /// Limit price; `None` for market orders
+    pub limit_price: Option<f64>,
+    pub quantity: f64,
+    pub filled: f64,
+    pub submitted_at_ns: u64,
+    pub active_at_ns: u64,
+    /// Resting quantity queued ahead of us at our price level
+    pub queue_ahead: Option<f64>,
+    /// Historical quantity at our price level in the previous snapshot
+    pub last_level_quantity: Option<f64>,
+}
+
+impl ReplayOrder {
+    pub fn remaining(&self) -> f64 {
+        (self.quantity - self.filled).max(0.0)
+    }
+}
+
+/// Replay simulator
+pub struct ReplaySimulator {
+    config: ReplayConfig,
+    orders: Vec<ReplayOrder>,
+    next_id: u64,
+    trigger_engine: Option<Arc<ShadowMatchingEngine>>,
+    clock: Option<Arc<SimulatedClock>>,
+}
+
+impl ReplaySimulator {
+    pub fn new(config: ReplayConfig) -> Self {
+        Self {
+            config,
+            orders: Vec::new(),
+            next_id: 0,
+            trigger_engine: None,
+            clock: None,
+        }
+    }
+
+    /// Advance `clock` to each replayed snapshot's timestamp, so components
+    /// sharing it see replay time instead of wall time
+    pub fn with_clock(mut self, clock: Arc<SimulatedClock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Forward replayed mid prices to a shadow engine for stop/OCO triggers
+    pub fn with_trigger_engine(mut self, engine: Arc<ShadowMatchingEngine>) -> Self {
+        self.trigger_engine = Some(engine);
+        self
+    }
+
+    /// Submit an order at replay time `now_ns`
+    pub fn submit(
+        &mut self,
+        exchange: &str,
+        symbol: &str,
+        side: Side,
+        quantity: f64,
+        limit_price: Option<f64>,
+        now_ns: u64,
+    ) -> AdapterResult<u64> {
+        if quantity <= 0.0 {
+            return Err(AdapterError::Validation { message: format!("quantity must be positive: {}", quantity) });
+        }
+        self.next_id += 1;
+        self.orders.push(ReplayOrder {
+            id: self.next_id,
+            exchange: exchange.to_string(),
+            symbol: symbol.to_string(),
+            side,
+            limit_price,
+            quantity,
+            filled: 0.0,
+            submitted_at_ns: now_ns,
+            active_at_ns: now_ns + self.config.latency_ms * 1_000_000,
+            queue_ahead: None,
+            last_level_quantity: None,
+        });
+        Ok(self.next_id)
+    }
+
+    /// Cancel an order that has not completely filled
+    pub fn cancel(&mut self, order_id: u64) -> bool {
+        let before = self.orders.len();
+        self.orders.retain(|o| o.id != order_id);
+        before != self.orders.len()
+    }
+
+    pub fn open_orders(&self) -> &[ReplayOrder] {
+        &self.orders
+    }
+
+    /// Replay a recorded stream in timestamp order
+    pub fn replay<I: IntoIterator<Item = OrderBook>>(&mut self, books: I) -> Vec<ShadowFill> {
+        let mut books: Vec<OrderBook> = books.into_iter().collect();
+        books.sort_by_key(|b| b.timestamp_ns);
+        books.iter().flat_map(|b| self.on_book(b)).collect()
+    }
+
+    /// Match active orders against one historical book snapshot
+    pub fn on_book(&mut self, book: &OrderBook) -> Vec<ShadowFill> {
+        let exchange = book.exchange.as_str().to_string();
+        let symbol = book.symbol.as_str().to_string();
+        if let Some(clock) = &self.clock {
+            clock.set_nanos(book.timestamp_ns);
+        }
+
+        let mut fills = Vec::new();
+        if let Some(engine) = &self.trigger_engine {
+            if let (Some(bid), Some(ask)) = (book.bid_prices.first(), book.ask_prices.first()) {
+                fills.extend(engine.on_price(&symbol, (bid.to_f64() + ask.to_f64()) / 2.0));
+            }
+        }
+
+        let levels = self.config.max_levels;
+        let mut asks: Vec<(f64, f64)> = book
+            .ask_prices
+            .iter()
+            .zip(&book.ask_quantities)
+            .take(levels)
+            .map(|(p, q)| (p.to_f64(), q.to_f64()))
+            .collect();
+        let mut bids: Vec<(f64, f64)> = book
+            .bid_prices
+            .iter()
+            .zip(&book.bid_quantities)
+            .take(levels)
+            .map(|(p, q)| (p.to_f64(), q.to_f64()))
+            .collect();
+
+        // Price-time priority: most aggressive price first, then earliest submission
+        let mut indices: Vec<usize> = (0..self.orders.len())
+            .filter(|&i| {
+                let o = &self.orders[i];
+                o.exchange == exchange && o.symbol == symbol && o.active_at_ns <= book.timestamp_ns
+            })
+            .collect();
+        indices.sort_by(|&a, &b| {
+            let (oa, ob) = (&self.orders[a], &self.orders[b]);
+            let aggressiveness = |o: &ReplayOrder| match (o.side, o.limit_price) {
+                (_, None) => f64::INFINITY,
+                (Side::Buy, Some(p)) => p,
+                (Side::Sell, Some(p)) => -p,
+            };
+            aggressiveness(ob)
+                .partial_cmp(&aggressiveness(oa))
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(oa.submitted_at_ns.cmp(&ob.submitted_at_ns))
+        });
+
+        for i in indices {
+            let partial_fills = self.config.partial_fills;
+            let order = &mut self.orders[i];
+            let (side, limit_price) = (order.side, order.limit_price);
+            let (opposite, same) = match side {
+                Side::Buy => (&mut asks, &bids),
+                Side::Sell => (&mut bids, &asks),
+            };
+            let crosses = |price: f64| match (side, limit_price) {
+                (_, None) => true,
+                (Side::Buy, Some(limit)) => price <= limit,
+                (Side::Sell, Some(limit)) => price >= limit,
+            };
+
+            let available: f64 = opposite.iter().take_while(|(p, _)| crosses(*p)).map(|(_, q)| q).sum();
+            if available > 0.0 && (partial_fills || available + 1e-12 >= order.remaining()) {
+                // Aggressive: sweep crossing levels
+                let mut remaining = order.remaining();
+                for level in opposite.iter_mut() {
+                    if remaining <= 0.0 || !crosses(level.0) {
+                        break;
+                    }
+                    let take = remaining.min(level.1);
+                    if take <= 0.0 {
+                        continue;
+                    }
+                    level.1 -= take;
+                    remaining -= take;
+                    order.filled += take;
+                    fills.push(ShadowFill {
+                        order_id: order.id,
+                        symbol: symbol.clone(),
+                        side,
+                        quantity: take,
+                        price: level.0,
+                    });
+                }
+                continue;
+            }
+
+            // Passive: join the back of the queue at our level; decreases in the
+            // historical level quantity consume the queue ahead first, then us
+            let Some(limit) = limit_price else { continue };
+            let level_qty = same
+                .iter()
+                .find(|(p, _)| (p - limit).abs() < 1e-9)
+                .map(|(_, q)| *q)
+                .unwrap_or(0.0);
+            let (Some(ahead), Some(previous)) = (order.queue_ahead, order.last_level_quantity) else {
+                order.queue_ahead = Some(level_qty);
+                order.last_level_quantity = Some(level_qty);
+                continue;
+            };
+            let traded = (previous - level_qty).max(0.0);
+            order.queue_ahead = Some((ahead - traded).max(0.0));
+            order.last_level_quantity = Some(level_qty);
+
+            let mut fill = (traded - ahead).max(0.0).min(order.remaining());
+            if !partial_fills && fill + 1e-12 < order.remaining() {
+                fill = 0.0;
+            }
+            if fill > 0.0 {
+                order.filled += fill;
+                fills.push(ShadowFill {
+                    order_id: order.id,
+                    symbol: symbol.clone(),
+                    side,
+                    quantity: fill,
+                    price: limit,
+                });
+            }
+        }
+
+        self.orders.retain(|o| o.remaining() > 1e-12);
+        fills
+    }
+}
+
+impl Default for ReplaySimulator {
+    fn default() -> Self {
+        Self::new(ReplayConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{Exchange, FixedPrice, FixedQuantity, Symbol};
+
+    fn book(ts_ns: u64, asks: &[(f64, f64)], bids: &[(f64, f64)]) -> OrderBook {
+        let mut book = OrderBook::new(Exchange::new("binance"), Symbol::new("BTCUSDT"), ts_ns, ts_ns);
+        book.ask_prices = asks.iter().map(|(p, _)| FixedPrice::from_f64(*p, 2)).collect();
+        book.ask_quantities = asks.iter().map(|(_, q)| FixedQuantity::from_f64(*q, 4)).collect();
+        book.bid_prices = bids.iter().map(|(p, _)| FixedPrice::from_f64(*p, 2)).collect();
+        book.bid_quantities = bids.iter().map(|(_, q)| FixedQuantity::from_f64(*q, 4)).collect();
+        book
+    }
+
+    #[test]
+    fn test_latency_and_partial_fill_with_priority() {
+        let mut sim = ReplaySimulator::default();
+        let first = sim.submit("binance", "BTCUSDT", Side::Buy, 1.0, None, 0).unwrap();
+        let second = sim.submit("binance", "BTCUSDT", Side::Buy, 1.0, None, 1).unwrap();
+
+        // Not yet active: latency is 5ms
+        assert!(sim.on_book(&book(1_000_000, &[(100.0, 5.0)], &[(99.0, 1.0)])).is_empty());
+
+        let fills = sim.on_book(&book(10_000_000, &[(100.0, 1.5)], &[(99.0, 1.0)]));
+        let filled = |id| fills.iter().filter(|f| f.order_id == id).map(|f| f.quantity).sum::<f64>();
+        assert!((filled(first) - 1.0).abs() < 1e-9);
+        assert!((filled(second) - 0.5).abs() < 1e-9);
+        assert_eq!(sim.open_orders().len(), 1);
+    }
+}
@@ -0,0 +1,276 @@
+//! Exchange error taxonomy and retry policy
+//!
+//! Exchange-specific error codes (Binance `-1003`, OKX `50011`, HTTP 429,
+//! ...) are mapped onto a small set of error classes. Each class has its
+//! own retry policy in `RetryConfig`: rate limits back off, nonce errors
+//! retry at once, maintenance and insufficient balance abort. Every
+//! classified error is counted per exchange and class.
+
+use crate::{AdapterError, AdapterResult};
+use metrics::counter;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+use tracing::warn;
+
+/// Common error taxonomy across exchanges
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorClass {
+    RateLimited,
+    InsufficientBalance,
+    /// Nonce or timestamp outside the accepted window
+    InvalidNonce,
+    Maintenance,
+    Network,
+    Timeout,
+    /// Rejected parameters, unknown symbol, bad signature
+    InvalidRequest,
+    Unknown,
+}
+
+impl ErrorClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorClass::RateLimited => "rate_limited",
+            ErrorClass::InsufficientBalance => "insufficient_balance",
+            ErrorClass::InvalidNonce => "invalid_nonce",
+            ErrorClass::Maintenance => "maintenance",
+            ErrorClass::Network => "network",
+            ErrorClass::Timeout => "timeout",
+            ErrorClass::InvalidRequest => "invalid_request",
+            ErrorClass::Unknown => "unknown",
+        }
+    }
+}
+
+/// Exchange error codes with a known class
+const CODE_TABLE: &[(&str, &str, ErrorClass)] = &[
+    ("binance", "-1003", ErrorClass::RateLimited),
+    ("binance", "-1015", ErrorClass::RateLimited),
+    ("binance", "-2010", ErrorClass::InsufficientBalance),
+    ("binance", "-1021", ErrorClass::InvalidNonce),
+    ("binance", "-1001", ErrorClass::Network),
+    ("binance", "-1007", ErrorClass::Timeout),
+    ("binance", "-1013", ErrorClass::InvalidRequest),
+    ("binance", "-1121", ErrorClass::InvalidRequest),
+    ("binance", "-1022", ErrorClass::InvalidRequest),
+    ("okx", "50011", ErrorClass::RateLimited),
+    ("okx", "50061", ErrorClass::RateLimited),
+    ("okx", "51008", ErrorClass::InsufficientBalance),
+    ("okx", "50102", ErrorClass::InvalidNonce),
+    ("okx", "50112", ErrorClass::InvalidNonce),
+    ("okx", "50001", ErrorClass::Maintenance),
+    ("okx", "50004", ErrorClass::Timeout),
+    ("okx", "51001", ErrorClass::InvalidRequest),
+    ("bybit", "10006", ErrorClass::RateLimited),
+    ("bybit", "10018", ErrorClass::RateLimited),
+    ("bybit", "110007", ErrorClass::InsufficientBalance),
+    ("bybit", "10002", ErrorClass::InvalidNonce),
+    ("bybit", "10016", ErrorClass::Maintenance),
+    ("bybit", "10001", ErrorClass::InvalidRequest),
+];
+
+/// Map an exchange error code (or HTTP status) and message to a class
+pub fn classify(exchange: &str, code: &str, message: &str) -> ErrorClass {
+    if let Some((_, _, class)) = CODE_TABLE
+        .iter()
+        .find(|(e, c, _)| e.eq_ignore_ascii_case(exchange) && *c == code)
+    {
+        return *class;
+    }
+    match code {
+        "429" | "418" => return ErrorClass::RateLimited,
+        "503" => return ErrorClass::Maintenance,
+        "502" | "504" => return ErrorClass::Network,
+        _ => {}
+    }
+    let message = message.to_lowercase();
+    if message.contains("too many requests") || message.contains("rate limit") {
+        ErrorClass::RateLimited
+    } else if message.contains("insufficient") {
+        ErrorClass::InsufficientBalance
+    } else if message.contains("nonce") || message.contains("recvwindow") || message.contains("timestamp") {
+        ErrorClass::InvalidNonce
+    } else if message.contains("maintenance") || message.contains("system upgrade") {
+        ErrorClass::Maintenance
+    } else {
+        ErrorClass::Unknown
+    }
+}
+
+impl AdapterError {
+    /// Error class used by the retry policy
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            AdapterError::Exchange { exchange, code, message } => classify(exchange, code, message),
+            AdapterError::Timeout { .. } => ErrorClass::Timeout,
+            AdapterError::Connection(_) | AdapterError::Nats(_) | AdapterError::Io(_) => ErrorClass::Network,
+            AdapterError::Validation { .. } | AdapterError::Configuration(_) => ErrorClass::InvalidRequest,
+            _ => ErrorClass::Unknown,
+        }
+    }
+}
+
+/// Retry behaviour for one error class
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassPolicy {
+    /// Retries after the first attempt; 0 aborts immediately
+    pub max_retries: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    pub multiplier: f64,
+}
+
+impl ClassPolicy {
+    pub fn abort() -> Self {
+        Self { max_retries: 0, initial_backoff_ms: 0, max_backoff_ms: 0, multiplier: 1.0 }
+    }
+
+    pub fn backoff(max_retries: u32, initial_backoff_ms: u64, max_backoff_ms: u64) -> Self {
+        Self { max_retries, initial_backoff_ms, max_backoff_ms, multiplier: 2.0 }
+    }
+}
+
+/// Per-class retry configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryConfig {
+    pub policies: HashMap<ErrorClass, ClassPolicy>,
+    /// Used for classes without an explicit policy
+    pub default_policy: ClassPolicy,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        let policies = HashMap::from([
+            (ErrorClass::RateLimited, ClassPolicy::backoff(5, 500, 10_000)),
+            (ErrorClass::InvalidNonce, ClassPolicy::backoff(2, 10, 50)),
+            (ErrorClass::Network, ClassPolicy::backoff(3, 200, 2_000)),
+            (ErrorClass::Timeout, ClassPolicy::backoff(2, 200, 1_000)),
+            (ErrorClass::InsufficientBalance, ClassPolicy::abort()),
+            (ErrorClass::Maintenance, ClassPolicy::abort()),
+            (ErrorClass::InvalidRequest, ClassPolicy::abort()),
+        ]);
+        Self { policies, default_policy: ClassPolicy::backoff(1, 200, 200) }
+    }
+}
+
+/// Drives retries and counts error classes per exchange
+pub struct RetryPolicyEngine {
+    config: RetryConfig,
+    counts: RwLock<HashMap<(String, ErrorClass), u64>>,
+}
+
+impl RetryPolicyEngine {
+    pub fn new(config: RetryConfig) -> Self {
+        Self { config, counts: RwLock::new(HashMap::new()) }
+    }
+
+    pub fn policy(&self, class: ErrorClass) -> &ClassPolicy {
+        self.config.policies.get(&class).unwrap_or(&self.config.default_policy)
+    }
+
+    /// Delay before retry number `retry` (1-based), or None to abort
+    pub fn next_delay(&self, class: ErrorClass, retry: u32) -> Option<Duration> {
+        let policy = self.policy(class);
+        if retry == 0 || retry > policy.max_retries {
+            return None;
+        }
+        let backoff = policy.initial_backoff_ms as f64 * policy.multiplier.powi(retry as i32 - 1);
+        Some(Duration::from_millis((backoff as u64).min(policy.max_backoff_ms)))
+    }
+
+    /// Count an error against its exchange and class
+    pub fn record(&self, exchange: &str, class: ErrorClass) {
+        *self.counts.write().entry((exchange.to_string(), class)).or_default() += 1;
+        counter!("exchange_errors_total", "exchange" => exchange.to_string(), "class" => class.as_str())
+            .increment(1);
+    }
+
+    /// Error counts per (exchange, class)
+    pub fn error_counts(&self) -> HashMap<(String, ErrorClass), u64> {
+        self.counts.read().clone()
+    }
+
+    /// Run `operation`, retrying according to the class of each failure
+    pub async fn run<T, F, Fut>(&self, exchange: &str, mut operation: F) -> AdapterResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = AdapterResult<T>>,
+    {
+        let mut retry = 0;
+        loop {
+            let error = match operation().await {
+                Ok(value) => return Ok(value),
+                Err(e) => e,
+            };
+            let class = error.class();
+            self.record(exchange, class);
+            retry += 1;
+            match self.next_delay(class, retry) {
+                Some(delay) => {
+                    warn!("{} {} error (retry {} in {:?}): {}", exchange, class.as_str(), retry, delay, error);
+                    tokio::time::sleep(delay).await;
+                }
+                None => return Err(error),
+            }
+        }
+    }
+}
+
+impl Default for RetryPolicyEngine {
+    fn default() -> Self {
+        Self::new(RetryConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn exchange_error(exchange: &str, code: &str, message: &str) -> AdapterError {
+        AdapterError::Exchange { exchange: exchange.into(), code: code.into(), message: message.into() }
+    }
+
+    #[tokio::test]
+    async fn test_classification_drives_retries() {
+        assert_eq!(classify("binance", "-1003", ""), ErrorClass::RateLimited);
+        assert_eq!(classify("okx", "51008", ""), ErrorClass::InsufficientBalance);
+        assert_eq!(classify("kraken", "429", ""), ErrorClass::RateLimited);
+        assert_eq!(classify("kraken", "EAPI", "EAPI:Invalid nonce"), ErrorClass::InvalidNonce);
+
+        let engine = RetryPolicyEngine::default();
+        assert_eq!(engine.next_delay(ErrorClass::RateLimited, 3), Some(Duration::from_millis(2_000)));
+        assert_eq!(engine.next_delay(ErrorClass::Maintenance, 1), None);
+
+        // Nonce errors retry and then succeed
+        let attempts = AtomicU32::new(0);
+        let result = engine
+            .run("binance", || async {
+                match attempts.fetch_add(1, Ordering::SeqCst) {
+                    0 => Err(exchange_error("binance", "-1021", "Timestamp outside recvWindow")),
+                    _ => Ok(42),
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap(), 42);
+
+        // Insufficient balance aborts on the first failure
+        let attempts = AtomicU32::new(0);
+        let result: AdapterResult<()> = engine
+            .run("okx", || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(exchange_error("okx", "51008", "Insufficient balance"))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+        let counts = engine.error_counts();
+        assert_eq!(counts[&("binance".to_string(), ErrorClass::InvalidNonce)], 1);
+        assert_eq!(counts[&("okx".to_string(), ErrorClass::InsufficientBalance)], 1);
+    }
+}
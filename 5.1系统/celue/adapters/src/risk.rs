@@ -1,7 +1,9 @@
 //! Risk management adapter
 
+use crate::index_price::IndexPriceService;
+use crate::reference_data::ReferenceDataService;
 use crate::{Adapter, AdapterError, AdapterResult};
-use common::ArbitrageOpportunity;
+use common::{ArbitrageOpportunity, SharedClock};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::collections::HashMap;
@@ -87,6 +89,12 @@ pub struct RiskAdapter {
     stats: Arc<RwLock<RiskStats>>,
     /// 交易所风控状态
     exchange_risk_states: Arc<RwLock<HashMap<String, ExchangeRiskState>>>,
+    /// 指数价格参考，用于下单前价格合理性检查
+    index_prices: Option<Arc<IndexPriceService>>,
+    /// 第三方参考价格（CoinGecko 等），只使用未过期的数据
+    reference_data: Option<Arc<ReferenceDataService>>,
+    /// 时间来源，回测时注入模拟时钟
+    clock: SharedClock,
 }
 
 #[derive(Debug, Clone)]
@@ -122,6 +130,9 @@ impl RiskAdapter {
             running: Arc::new(parking_lot::Mutex::new(false)),
             stats: Arc::new(RwLock::new(RiskStats::default())),
             exchange_risk_states: Arc::new(RwLock::new(HashMap::new())),
+            index_prices: None,
+            reference_data: None,
+            clock: common::clock::system_clock(),
         }
     }
     
@@ -131,9 +142,33 @@ impl RiskAdapter {
             running: Arc::new(parking_lot::Mutex::new(false)),
             stats: Arc::new(RwLock::new(RiskStats::default())),
             exchange_risk_states: Arc::new(RwLock::new(HashMap::new())),
+            index_prices: None,
+            reference_data: None,
+            clock: common::clock::system_clock(),
         }
     }
     
+    /// 使用指数价格作为价格合理性参考
+    pub fn with_index_prices(mut self, index_prices: Arc<IndexPriceService>) -> Self {
+        self.index_prices = Some(index_prices);
+        self
+    }
+
+    /// 使用第三方参考价格做腿价格合理性检查
+    pub fn with_reference_data(mut self, reference_data: Arc<ReferenceDataService>) -> Self {
+        self.reference_data = Some(reference_data);
+        self
+    }
+
+    /// 使用指定时钟，日统计重置与交易所暂停到期都按该时钟计算
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        if let Ok(mut stats) = self.stats.try_write() {
+            stats.last_reset_date = clock.now();
+        }
+        self.clock = clock;
+        self
+    }
+
     /// 实时风控检查 - 策略联动的核心
     pub async fn check_risk(&self, opportunity: &ArbitrageOpportunity) -> AdapterResult<RiskDecision> {
         // 1. 基础风控检查
@@ -207,7 +242,7 @@ impl RiskAdapter {
             if let Some(state) = exchange_states.get(&leg.exchange.to_string()) {
                 if state.is_suspended {
                     if let Some(until) = state.suspension_until {
-                        if Utc::now() < until {
+                        if self.clock.now() < until {
                             return Ok(Some(RiskDecision {
                                 approved: false,
                                 reason: Some(format!(
@@ -218,7 +253,7 @@ impl RiskAdapter {
                                 )),
                                 max_quantity: None,
                                 risk_level: 4,
-                                suggested_wait_time: Some((until - Utc::now()).num_seconds() as u64),
+                                suggested_wait_time: Some((until - self.clock.now()).num_seconds() as u64),
                             }));
                         }
                     }
@@ -246,7 +281,7 @@ impl RiskAdapter {
         let config = self.config.read().await;
         
         // 重置日统计（如果是新的一天）
-        let now = Utc::now();
+        let now = self.clock.now();
         if now.date_naive() != stats.last_reset_date.date_naive() {
             stats.daily_trades = 0;
             stats.daily_pnl = 0.0;
@@ -307,6 +342,43 @@ impl RiskAdapter {
                 suggested_wait_time: Some(120), // 2分钟等待
             }));
         }
+
+        // 腿价格偏离指数价格过大
+        if let Some(index_prices) = &self.index_prices {
+            let now_ns = self.clock.now_ns();
+            if let Some(d) = index_prices.check_opportunity(opportunity, now_ns).first() {
+                return Ok(Some(RiskDecision {
+                    approved: false,
+                    reason: Some(format!(
+                        "{} {} price {:.8} deviates {:.2}% from index {:.8}",
+                        d.exchange, d.symbol, d.price, d.deviation_pct, d.index_price
+                    )),
+                    max_quantity: None,
+                    risk_level: 5,
+                    suggested_wait_time: Some(30),
+                }));
+            }
+        }
+
+        // 腿价格偏离第三方参考价格过大
+        if let Some(reference_data) = &self.reference_data {
+            for leg in &opportunity.legs {
+                let price = leg.price.to_f64();
+                let Some(deviation_pct) = reference_data.deviation_pct(leg.symbol.as_str(), price) else { continue };
+                if deviation_pct.abs() > config.abnormal_price_deviation_pct {
+                    return Ok(Some(RiskDecision {
+                        approved: false,
+                        reason: Some(format!(
+                            "{} {} price {:.8} deviates {:.2}% from reference price",
+                            leg.exchange.as_str(), leg.symbol.as_str(), price, deviation_pct
+                        )),
+                        max_quantity: None,
+                        risk_level: 5,
+                        suggested_wait_time: Some(30),
+                    }));
+                }
+            }
+        }
         
         Ok(None)
     }
@@ -372,7 +444,7 @@ impl RiskAdapter {
             stats.consecutive_failures = 0;
         } else {
             stats.consecutive_failures += 1;
-            stats.last_failure_time = Some(Utc::now());
+            stats.last_failure_time = Some(self.clock.now());
         }
         
         // 更新交易所风控状态
@@ -388,7 +460,7 @@ impl RiskAdapter {
         for leg in &opportunity.legs {
             let state = exchange_states.entry(leg.exchange.to_string()).or_insert_with(ExchangeRiskState::default);
             
-            state.last_health_check = Utc::now();
+            state.last_health_check = self.clock.now();
             
             if !success {
                 // 增加错误率
@@ -398,7 +470,7 @@ impl RiskAdapter {
                 if state.recent_error_rate > 0.2 && !state.is_suspended {
                     state.is_suspended = true;
                     state.suspension_reason = Some("High error rate detected".to_string());
-                    state.suspension_until = Some(Utc::now() + Duration::minutes(30));
+                    state.suspension_until = Some(self.clock.now() + Duration::minutes(30));
                 }
             } else {
                 // 降低错误率
@@ -407,7 +479,7 @@ impl RiskAdapter {
                 // 如果错误率恢复正常，解除暂停
                 if state.recent_error_rate < 0.05 && state.is_suspended {
                     if let Some(until) = state.suspension_until {
-                        if Utc::now() > until {
+                        if self.clock.now() > until {
                             state.is_suspended = false;
                             state.suspension_reason = None;
                             state.suspension_until = None;
@@ -0,0 +1,281 @@
+//! Priority scheduling of pending opportunities
+//!
+//! Pending opportunities are ordered by a composite score of their
+//! priority, expected profit in bps and how close they are to expiry,
+//! recomputed at dequeue time because urgency grows as the TTL runs
+//! down. Expired entries are dropped instead of executed. When the queue
+//! is full or the capital it would commit exceeds what is available,
+//! lower-priority entries are preempted to make room for a more urgent
+//! one.
+
+use crate::execution::ExecutionAdapter;
+use common::{ArbitrageOpportunity, ExecutionResult, Side};
+use metrics::{counter, gauge};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::Notify;
+use tracing::{debug, warn};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchedulerConfig {
+    /// Score per priority level
+    pub priority_weight: f64,
+    /// Score per bp of expected profit
+    pub profit_weight: f64,
+    /// Score for an opportunity about to expire (scaled by elapsed TTL fraction)
+    pub urgency_weight: f64,
+    pub max_queue_len: usize,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            priority_weight: 100.0,
+            profit_weight: 1.0,
+            urgency_weight: 10.0,
+            max_queue_len: 256,
+        }
+    }
+}
+
+/// Why an opportunity left the queue without executing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Displaced {
+    /// Evicted for a higher-priority opportunity
+    Preempted { opportunity_id: String, by: String },
+    /// Rejected on arrival: the queue could not make room for it
+    Rejected { opportunity_id: String },
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueueStats {
+    pub depth: usize,
+    pub oldest_age_ms: u64,
+    pub committed_capital: f64,
+    pub preempted_total: u64,
+    pub expired_total: u64,
+}
+
+struct Entry {
+    opportunity: ArbitrageOpportunity,
+    enqueued_at_ns: u64,
+    capital: f64,
+}
+
+/// Capital the opportunity's buy legs commit
+pub fn required_capital(opportunity: &ArbitrageOpportunity) -> f64 {
+    opportunity
+        .legs
+        .iter()
+        .filter(|l| l.side == Side::Buy)
+        .map(|l| l.cost.to_f64().abs())
+        .sum()
+}
+
+#[derive(Default)]
+struct QueueState {
+    entries: Vec<Entry>,
+    preempted_total: u64,
+    expired_total: u64,
+}
+
+/// Priority queue of opportunities awaiting execution
+pub struct OpportunityScheduler {
+    config: SchedulerConfig,
+    state: Mutex<QueueState>,
+    notify: Notify,
+}
+
+impl OpportunityScheduler {
+    pub fn new(config: SchedulerConfig) -> Self {
+        Self { config, state: Mutex::new(QueueState::default()), notify: Notify::new() }
+    }
+
+    /// Composite score at `now_ns`; higher runs first
+    pub fn score(&self, opportunity: &ArbitrageOpportunity, now_ns: u64) -> f64 {
+        // net_profit_pct is a fraction for every strategy (0.001 = 10 bps)
+        let profit_bps = opportunity.net_profit_pct.to_f64() * 10_000.0;
+        let urgency = if opportunity.ttl_ns == 0 {
+            0.0
+        } else {
+            (now_ns.saturating_sub(opportunity.created_at_ns) as f64 / opportunity.ttl_ns as f64).min(1.0)
+        };
+        self.config.priority_weight * opportunity.priority as f64
+            + self.config.profit_weight * profit_bps
+            + self.config.urgency_weight * urgency
+    }
+
+    fn expired(opportunity: &ArbitrageOpportunity, now_ns: u64) -> bool {
+        opportunity.ttl_ns > 0 && now_ns >= opportunity.created_at_ns.saturating_add(opportunity.ttl_ns)
+    }
+
+    /// Queue an opportunity, preempting lower-priority entries when the
+    /// queue is full or `available_capital` cannot cover everything queued
+    pub fn push(&self, opportunity: ArbitrageOpportunity, now_ns: u64, available_capital: f64) -> Vec<Displaced> {
+        let mut state = self.state.lock();
+        self.drop_expired(&mut state, now_ns);
+        let capital = required_capital(&opportunity);
+        let id = opportunity.id.to_string();
+        let mut committed: f64 = state.entries.iter().map(|e| e.capital).sum();
+        let mut displaced = Vec::new();
+
+        while state.entries.len() >= self.config.max_queue_len.max(1) || committed + capital > available_capital {
+            // Lowest-scoring entry with a lower priority than the newcomer
+            let victim = state
+                .entries
+                .iter()
+                .enumerate()
+                .filter(|(_, e)| e.opportunity.priority < opportunity.priority)
+                .min_by(|(_, a), (_, b)| self.score(&a.opportunity, now_ns).total_cmp(&self.score(&b.opportunity, now_ns)))
+                .map(|(i, _)| i);
+            let Some(victim) = victim else {
+                // Only a full queue rejects outright; capital may free up before dequeue
+                if state.entries.len() >= self.config.max_queue_len.max(1) {
+                    counter!("execution_queue_rejected_total").increment(1);
+                    self.publish_gauges(&state, now_ns);
+                    displaced.push(Displaced::Rejected { opportunity_id: id });
+                    return displaced;
+                }
+                break;
+            };
+            let evicted = state.entries.swap_remove(victim);
+            committed -= evicted.capital;
+            state.preempted_total += 1;
+            counter!("execution_queue_preempted_total").increment(1);
+            debug!("Opportunity {} preempted by {}", evicted.opportunity.id, id);
+            displaced.push(Displaced::Preempted { opportunity_id: evicted.opportunity.id.to_string(), by: id.clone() });
+        }
+
+        state.entries.push(Entry { opportunity, enqueued_at_ns: now_ns, capital });
+        self.publish_gauges(&state, now_ns);
+        drop(state);
+        self.notify.notify_one();
+        displaced
+    }
+
+    /// Highest-scoring live opportunity whose capital fits `available_capital`
+    pub fn pop(&self, now_ns: u64, available_capital: f64) -> Option<ArbitrageOpportunity> {
+        let mut state = self.state.lock();
+        self.drop_expired(&mut state, now_ns);
+        let best = state
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.capital <= available_capital)
+            .max_by(|(_, a), (_, b)| self.score(&a.opportunity, now_ns).total_cmp(&self.score(&b.opportunity, now_ns)))
+            .map(|(i, _)| i)?;
+        let entry = state.entries.swap_remove(best);
+        self.publish_gauges(&state, now_ns);
+        Some(entry.opportunity)
+    }
+
+    fn drop_expired(&self, state: &mut QueueState, now_ns: u64) {
+        let before = state.entries.len();
+        state.entries.retain(|e| !Self::expired(&e.opportunity, now_ns));
+        let expired = (before - state.entries.len()) as u64;
+        if expired > 0 {
+            state.expired_total += expired;
+            counter!("execution_queue_expired_total").increment(expired);
+        }
+    }
+
+    fn stats_locked(state: &QueueState, now_ns: u64) -> QueueStats {
+        QueueStats {
+            depth: state.entries.len(),
+            oldest_age_ms: state
+                .entries
+                .iter()
+                .map(|e| now_ns.saturating_sub(e.enqueued_at_ns) / 1_000_000)
+                .max()
+                .unwrap_or(0),
+            committed_capital: state.entries.iter().map(|e| e.capital).sum(),
+            preempted_total: state.preempted_total,
+            expired_total: state.expired_total,
+        }
+    }
+
+    fn publish_gauges(&self, state: &QueueState, now_ns: u64) {
+        let stats = Self::stats_locked(state, now_ns);
+        gauge!("execution_queue_depth").set(stats.depth as f64);
+        gauge!("execution_queue_oldest_age_ms").set(stats.oldest_age_ms as f64);
+    }
+
+    pub fn stats(&self, now_ns: u64) -> QueueStats {
+        Self::stats_locked(&self.state.lock(), now_ns)
+    }
+
+    /// Execute queued opportunities in score order as capital allows
+    pub fn spawn_worker<F>(self: Arc<Self>, adapter: Arc<ExecutionAdapter>, available_capital: F) -> tokio::task::JoinHandle<()>
+    where
+        F: Fn() -> f64 + Send + Sync + 'static,
+    {
+        tokio::spawn(async move {
+            loop {
+                let now_ns = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64;
+                let Some(opportunity) = self.pop(now_ns, available_capital()) else {
+                    // Woken by the next push; re-check periodically as capital frees up
+                    let _ = tokio::time::timeout(std::time::Duration::from_millis(50), self.notify.notified()).await;
+                    continue;
+                };
+                let result: Result<ExecutionResult, _> = adapter.execute(&opportunity).await;
+                if let Err(e) = result {
+                    warn!("Scheduled execution of {} failed: {}", opportunity.id, e);
+                }
+            }
+        })
+    }
+}
+
+impl Default for OpportunityScheduler {
+    fn default() -> Self {
+        Self::new(SchedulerConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::testing::{leg, opportunity_with_legs};
+
+    fn opportunity(priority: u8, profit_pct: f64, cost: f64) -> ArbitrageOpportunity {
+        let legs = vec![
+            leg("binance", "BTCUSDT", Side::Buy, 100.0, cost / 100.0),
+            leg("okx", "BTCUSDT", Side::Sell, 100.0, cost / 100.0),
+        ];
+        let mut opp = opportunity_with_legs("inter_exchange", legs, profit_pct, 0);
+        opp.priority = priority;
+        opp
+    }
+
+    #[test]
+    fn test_ordering_preemption_and_expiry() {
+        let scheduler = OpportunityScheduler::new(SchedulerConfig { max_queue_len: 3, ..Default::default() });
+        let low = opportunity(0, 0.0005, 400.0);
+        let rich = opportunity(0, 0.0009, 400.0);
+        let low_id = low.id.to_string();
+        assert!(scheduler.push(low, 0, 1_000.0).is_empty());
+        assert!(scheduler.push(rich.clone(), 0, 1_000.0).is_empty());
+
+        // Not enough capital for a third: the weakest lower-priority entry goes
+        let urgent = opportunity(2, 0.0001, 400.0);
+        let displaced = scheduler.push(urgent.clone(), 0, 1_000.0);
+        assert!(matches!(&displaced[..], [Displaced::Preempted { opportunity_id, .. }] if *opportunity_id == low_id));
+        assert_eq!(scheduler.stats(0).preempted_total, 1);
+
+        assert_eq!(scheduler.pop(1, 1_000.0).unwrap().id, urgent.id);
+        assert_eq!(scheduler.pop(1, 1_000.0).unwrap().id, rich.id);
+
+        scheduler.push(opportunity(0, 0.0005, 10.0), 0, 1_000.0);
+        assert!(scheduler.pop(200_000_000, 1_000.0).is_none());
+        assert_eq!(scheduler.stats(200_000_000).expired_total, 1);
+    }
+
+    #[test]
+    fn test_profit_term_is_in_bps() {
+        let scheduler = OpportunityScheduler::new(SchedulerConfig { urgency_weight: 0.0, ..Default::default() });
+        // 0.25% is 25 bps whichever strategy produced it
+        assert!((scheduler.score(&opportunity(0, 0.0025, 100.0), 0) - 25.0).abs() < 1e-9);
+        assert!((scheduler.score(&opportunity(1, 0.0025, 100.0), 0) - 125.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,286 @@
+//! Anomaly detection on the system's own metrics
+//!
+//! Components report latency, error rate and throughput samples. Each
+//! (component, metric) series keeps an exponentially weighted mean and
+//! variance. A sample whose z-score against that baseline exceeds the
+//! threshold produces a `PerformanceInsight` carrying the recent series, so
+//! the reader sees the spike in context. Latency and error rate are only
+//! flagged when they rise; throughput is flagged in both directions.
+//! Anomalous samples are folded into the baseline at a reduced weight, so
+//! a sustained shift becomes the new normal instead of alerting forever.
+
+use crate::alerting::{Alert, AlertManager, AlertSeverity};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tracing::warn;
+
+/// What a series measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricKind {
+    Latency,
+    ErrorRate,
+    Throughput,
+}
+
+impl MetricKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MetricKind::Latency => "latency",
+            MetricKind::ErrorRate => "error_rate",
+            MetricKind::Throughput => "throughput",
+        }
+    }
+}
+
+/// Kind of finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InsightKind {
+    LatencySpike,
+    ErrorRateSpike,
+    ThroughputDrop,
+    ThroughputSurge,
+    /// Projected resource exhaustion, see `capacity`
+    CapacityPlanning,
+}
+
+/// A finding about the system's own behaviour
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerformanceInsight {
+    pub component: String,
+    pub metric: String,
+    pub kind: InsightKind,
+    pub severity: AlertSeverity,
+    pub description: String,
+    /// Observed value and the baseline it deviated from
+    pub value: f64,
+    pub baseline_mean: f64,
+    pub baseline_std: f64,
+    pub z_score: f64,
+    pub detected_at_ns: u64,
+    /// Recent (timestamp_ns, value) samples including the anomalous one
+    pub series: Vec<(u64, f64)>,
+    /// Suggested configuration changes, keyed by setting name
+    #[serde(default)]
+    pub recommendations: HashMap<String, f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfMonitorConfig {
+    /// Weight of the newest sample in the baseline
+    pub alpha: f64,
+    /// Weight of an anomalous sample, as a fraction of `alpha`
+    pub anomaly_weight: f64,
+    /// Samples needed before a series can be flagged
+    pub warmup_samples: u64,
+    pub warning_z: f64,
+    pub critical_z: f64,
+    /// Floor on the standard deviation, relative to the mean, so a
+    /// perfectly flat series does not flag every tiny wobble
+    pub min_relative_std: f64,
+    /// Samples attached to an insight
+    pub series_len: usize,
+    pub history_size: usize,
+}
+
+impl Default for SelfMonitorConfig {
+    fn default() -> Self {
+        Self {
+            alpha: 0.05,
+            anomaly_weight: 0.2,
+            warmup_samples: 30,
+            warning_z: 4.0,
+            critical_z: 8.0,
+            min_relative_std: 0.02,
+            series_len: 60,
+            history_size: 500,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct SeriesState {
+    samples: u64,
+    mean: f64,
+    variance: f64,
+    recent: VecDeque<(u64, f64)>,
+}
+
+impl SeriesState {
+    fn update(&mut self, value: f64, alpha: f64) {
+        if self.samples == 0 {
+            self.mean = value;
+            self.variance = 0.0;
+        } else {
+            let diff = value - self.mean;
+            let incr = alpha * diff;
+            self.mean += incr;
+            self.variance = (1.0 - alpha) * (self.variance + diff * incr);
+        }
+        self.samples += 1;
+    }
+}
+
+/// Online z-score detector over component metrics
+pub struct SelfMonitor {
+    config: SelfMonitorConfig,
+    series: Mutex<HashMap<(String, MetricKind), SeriesState>>,
+    insights: Mutex<VecDeque<PerformanceInsight>>,
+    alerts: Option<Arc<AlertManager>>,
+}
+
+impl SelfMonitor {
+    pub fn new(config: SelfMonitorConfig) -> Self {
+        Self {
+            config,
+            series: Mutex::new(HashMap::new()),
+            insights: Mutex::new(VecDeque::new()),
+            alerts: None,
+        }
+    }
+
+    /// Raise an alert for every insight
+    pub fn with_alerts(mut self, alerts: Arc<AlertManager>) -> Self {
+        self.alerts = Some(alerts);
+        self
+    }
+
+    /// Feed one sample; returns the insight if it is anomalous
+    pub fn observe(&self, component: &str, kind: MetricKind, value: f64, timestamp_ns: u64) -> Option<PerformanceInsight> {
+        if !value.is_finite() {
+            return None;
+        }
+        let c = &self.config;
+        let insight = {
+            let mut series = self.series.lock();
+            let state = series.entry((component.to_string(), kind)).or_default();
+            state.recent.push_back((timestamp_ns, value));
+            while state.recent.len() > c.series_len.max(1) {
+                state.recent.pop_front();
+            }
+
+            let std = state.variance.sqrt().max(state.mean.abs() * c.min_relative_std).max(f64::EPSILON);
+            let z = (value - state.mean) / std;
+            let flagged = state.samples >= c.warmup_samples
+                && match kind {
+                    MetricKind::Latency | MetricKind::ErrorRate => z >= c.warning_z,
+                    MetricKind::Throughput => z.abs() >= c.warning_z,
+                };
+            let insight = flagged.then(|| {
+                let insight_kind = match kind {
+                    MetricKind::Latency => InsightKind::LatencySpike,
+                    MetricKind::ErrorRate => InsightKind::ErrorRateSpike,
+                    MetricKind::Throughput if z < 0.0 => InsightKind::ThroughputDrop,
+                    MetricKind::Throughput => InsightKind::ThroughputSurge,
+                };
+                let severity = if z.abs() >= c.critical_z { AlertSeverity::Critical } else { AlertSeverity::Warning };
+                PerformanceInsight {
+                    component: component.to_string(),
+                    metric: kind.as_str().to_string(),
+                    kind: insight_kind,
+                    severity,
+                    description: format!(
+                        "{} {} {:.3} deviates {:.1} sigma from baseline {:.3}",
+                        component,
+                        kind.as_str(),
+                        value,
+                        z,
+                        state.mean
+                    ),
+                    value,
+                    baseline_mean: state.mean,
+                    baseline_std: std,
+                    z_score: z,
+                    detected_at_ns: timestamp_ns,
+                    series: state.recent.iter().copied().collect(),
+                    recommendations: HashMap::new(),
+                }
+            });
+            let alpha = if insight.is_some() { c.alpha * c.anomaly_weight } else { c.alpha };
+            state.update(value, alpha);
+            insight
+        }?;
+
+        metrics::counter!(
+            "self_monitor_anomalies_total",
+            "component" => insight.component.clone(),
+            "metric" => insight.metric.clone()
+        )
+        .increment(1);
+        warn!("{}", insight.description);
+        self.record(insight.clone());
+        Some(insight)
+    }
+
+    /// Store an insight produced elsewhere, e.g. by the capacity forecaster
+    pub fn record(&self, insight: PerformanceInsight) {
+        if let Some(alerts) = &self.alerts {
+            let alerts = alerts.clone();
+            let alert = Alert::new(
+                &format!("self_monitor:{}:{}", insight.component, insight.metric),
+                insight.severity,
+                &format!("{:?} on {}", insight.kind, insight.component),
+                &insight.description,
+                &insight.component,
+            );
+            if let Ok(handle) = tokio::runtime::Handle::try_current() {
+                handle.spawn(async move {
+                    alerts.raise(alert).await;
+                });
+            }
+        }
+        let mut insights = self.insights.lock();
+        insights.push_back(insight);
+        while insights.len() > self.config.history_size.max(1) {
+            insights.pop_front();
+        }
+    }
+
+    /// Most recent insights, newest first
+    pub fn insights(&self, limit: usize) -> Vec<PerformanceInsight> {
+        self.insights.lock().iter().rev().take(limit).cloned().collect()
+    }
+
+    /// Current baseline (mean, std) of a series
+    pub fn baseline(&self, component: &str, kind: MetricKind) -> Option<(f64, f64)> {
+        self.series
+            .lock()
+            .get(&(component.to_string(), kind))
+            .map(|s| (s.mean, s.variance.sqrt()))
+    }
+}
+
+impl Default for SelfMonitor {
+    fn default() -> Self {
+        Self::new(SelfMonitorConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_spike_and_throughput_drop() {
+        let monitor = SelfMonitor::new(SelfMonitorConfig { warmup_samples: 20, ..Default::default() });
+        for i in 0..100u64 {
+            let jitter = (i % 5) as f64 * 0.2;
+            assert!(monitor.observe("executor", MetricKind::Latency, 10.0 + jitter, i).is_none());
+            assert!(monitor.observe("executor", MetricKind::Throughput, 500.0 + jitter * 10.0, i).is_none());
+        }
+
+        // A latency dip is not interesting; a spike is
+        assert!(monitor.observe("executor", MetricKind::Latency, 2.0, 100).is_none());
+        let spike = monitor.observe("executor", MetricKind::Latency, 40.0, 101).unwrap();
+        assert_eq!(spike.kind, InsightKind::LatencySpike);
+        assert_eq!(spike.severity, AlertSeverity::Critical);
+        assert_eq!(spike.series.last(), Some(&(101, 40.0)));
+
+        let drop = monitor.observe("executor", MetricKind::Throughput, 200.0, 102).unwrap();
+        assert_eq!(drop.kind, InsightKind::ThroughputDrop);
+        assert_eq!(monitor.insights(10).len(), 2);
+    }
+}
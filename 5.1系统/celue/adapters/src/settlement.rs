@@ -0,0 +1,423 @@
+//! End-of-day settlement and PnL statements
+//!
+//! Realized PnL, fees, funding and transfer costs are booked as ledger
+//! entries during the day; unrealized PnL is marked per strategy and
+//! exchange. At the daily cutoff the job freezes everything booked since
+//! the previous cutoff into a `DailyStatement`, broken down per strategy
+//! and per exchange, and stores it. Stored statements can be exported as
+//! JSON or CSV and summarized for the dashboard.
+
+use crate::attribution::{ReportFormat, TradeRecord};
+use crate::{AdapterError, AdapterResult};
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDate, NaiveTime, Utc};
+use common::SharedClock;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerKind {
+    RealizedPnl,
+    Fee,
+    Funding,
+    Transfer,
+}
+
+/// One booked amount; costs are positive, funding received is negative
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub kind: LedgerKind,
+    pub strategy: String,
+    pub exchange: String,
+    pub amount_usd: f64,
+    pub timestamp_ns: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PnlBreakdown {
+    pub realized: f64,
+    pub unrealized: f64,
+    pub fees: f64,
+    pub funding: f64,
+    pub transfer_costs: f64,
+}
+
+impl PnlBreakdown {
+    pub fn net(&self) -> f64 {
+        self.realized + self.unrealized - self.fees - self.funding - self.transfer_costs
+    }
+
+    fn add(&mut self, kind: LedgerKind, amount: f64) {
+        match kind {
+            LedgerKind::RealizedPnl => self.realized += amount,
+            LedgerKind::Fee => self.fees += amount,
+            LedgerKind::Funding => self.funding += amount,
+            LedgerKind::Transfer => self.transfer_costs += amount,
+        }
+    }
+}
+
+/// Frozen results for one trading day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyStatement {
+    pub date: NaiveDate,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub generated_at: DateTime<Utc>,
+    pub entries: usize,
+    pub by_strategy: BTreeMap<String, PnlBreakdown>,
+    pub by_exchange: BTreeMap<String, PnlBreakdown>,
+    pub total: PnlBreakdown,
+}
+
+/// Storage backend for settled statements
+pub trait StatementStore: Send + Sync {
+    fn save(&self, statement: &DailyStatement) -> AdapterResult<()>;
+    fn load(&self, date: NaiveDate) -> AdapterResult<Option<DailyStatement>>;
+    /// Most recent first
+    fn recent(&self, limit: usize) -> AdapterResult<Vec<DailyStatement>>;
+}
+
+#[derive(Default)]
+pub struct InMemoryStatementStore {
+    statements: RwLock<BTreeMap<NaiveDate, DailyStatement>>,
+}
+
+impl StatementStore for InMemoryStatementStore {
+    fn save(&self, statement: &DailyStatement) -> AdapterResult<()> {
+        self.statements.write().insert(statement.date, statement.clone());
+        Ok(())
+    }
+
+    fn load(&self, date: NaiveDate) -> AdapterResult<Option<DailyStatement>> {
+        Ok(self.statements.read().get(&date).cloned())
+    }
+
+    fn recent(&self, limit: usize) -> AdapterResult<Vec<DailyStatement>> {
+        Ok(self.statements.read().values().rev().take(limit).cloned().collect())
+    }
+}
+
+/// One `YYYY-MM-DD.json` file per statement
+pub struct FileStatementStore {
+    dir: PathBuf,
+}
+
+impl FileStatementStore {
+    pub fn new(dir: impl Into<PathBuf>) -> AdapterResult<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, date: NaiveDate) -> PathBuf {
+        self.dir.join(format!("{}.json", date))
+    }
+}
+
+impl StatementStore for FileStatementStore {
+    fn save(&self, statement: &DailyStatement) -> AdapterResult<()> {
+        let tmp = self.path(statement.date).with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(statement)?)?;
+        std::fs::rename(tmp, self.path(statement.date))?;
+        Ok(())
+    }
+
+    fn load(&self, date: NaiveDate) -> AdapterResult<Option<DailyStatement>> {
+        match std::fs::read(self.path(date)) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn recent(&self, limit: usize) -> AdapterResult<Vec<DailyStatement>> {
+        let mut dates: Vec<NaiveDate> = std::fs::read_dir(&self.dir)?
+            .filter_map(|e| e.ok())
+            .filter_map(|e| e.file_name().to_str()?.strip_suffix(".json")?.parse().ok())
+            .collect();
+        dates.sort_unstable_by(|a, b| b.cmp(a));
+        dates.into_iter().take(limit).filter_map(|d| self.load(d).transpose()).collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettlementConfig {
+    /// Daily cutoff (UTC); a statement covers the 24h ending at the cutoff
+    pub cutoff: NaiveTime,
+}
+
+impl Default for SettlementConfig {
+    fn default() -> Self {
+        Self { cutoff: NaiveTime::MIN }
+    }
+}
+
+/// One dashboard row per settled day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettlementSummaryRow {
+    pub date: NaiveDate,
+    pub net: f64,
+    pub total: PnlBreakdown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettlementSummary {
+    /// Oldest first
+    pub days: Vec<SettlementSummaryRow>,
+    pub cumulative_net: f64,
+    pub best_strategy: Option<String>,
+    pub worst_strategy: Option<String>,
+}
+
+/// Books ledger entries and settles them at the daily cutoff
+pub struct SettlementJob {
+    config: SettlementConfig,
+    store: Arc<dyn StatementStore>,
+    pending: RwLock<Vec<LedgerEntry>>,
+    /// (strategy, exchange) -> latest unrealized PnL mark
+    unrealized: RwLock<HashMap<(String, String), f64>>,
+    clock: SharedClock,
+}
+
+impl SettlementJob {
+    pub fn new(config: SettlementConfig, store: Arc<dyn StatementStore>) -> Self {
+        Self {
+            config,
+            store,
+            pending: RwLock::new(Vec::new()),
+            unrealized: RwLock::new(HashMap::new()),
+            clock: common::clock::system_clock(),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn book(&self, entry: LedgerEntry) {
+        self.pending.write().push(entry);
+    }
+
+    /// Book a completed trade; cross-exchange results are split evenly
+    /// between the two venues
+    pub fn book_trade(&self, trade: &TradeRecord) {
+        let strategy = format!("{:?}", trade.strategy);
+        let venues: Vec<&String> = if trade.buy_exchange == trade.sell_exchange {
+            vec![&trade.buy_exchange]
+        } else {
+            vec![&trade.buy_exchange, &trade.sell_exchange]
+        };
+        let share = 1.0 / venues.len() as f64;
+        for exchange in venues {
+            for (kind, amount) in [(LedgerKind::RealizedPnl, trade.gross_profit), (LedgerKind::Fee, trade.fees)] {
+                self.book(LedgerEntry {
+                    kind,
+                    strategy: strategy.clone(),
+                    exchange: exchange.clone(),
+                    amount_usd: amount * share,
+                    timestamp_ns: trade.timestamp_ns,
+                });
+            }
+        }
+    }
+
+    /// Latest mark-to-market PnL of open positions
+    pub fn mark_unrealized(&self, strategy: &str, exchange: &str, pnl_usd: f64) {
+        self.unrealized.write().insert((strategy.to_string(), exchange.to_string()), pnl_usd);
+    }
+
+    /// First cutoff strictly after `now`
+    pub fn next_cutoff(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let today = now.date_naive().and_time(self.config.cutoff).and_utc();
+        if today > now {
+            today
+        } else {
+            today + ChronoDuration::days(1)
+        }
+    }
+
+    /// Freeze everything booked before `cutoff` into the statement for the
+    /// day ending at `cutoff`
+    pub fn settle(&self, cutoff: DateTime<Utc>) -> AdapterResult<DailyStatement> {
+        let date = (cutoff - ChronoDuration::nanoseconds(1)).date_naive();
+        if self.store.load(date)?.is_some() {
+            return Err(AdapterError::Validation { message: format!("{} is already settled", date) });
+        }
+        let cutoff_ns = cutoff.timestamp_nanos_opt().unwrap_or_default().max(0) as u64;
+        let entries: Vec<LedgerEntry> = {
+            let mut pending = self.pending.write();
+            let (settled, later) = pending.drain(..).partition(|e| e.timestamp_ns < cutoff_ns);
+            *pending = later;
+            settled
+        };
+
+        let mut by_strategy: BTreeMap<String, PnlBreakdown> = BTreeMap::new();
+        let mut by_exchange: BTreeMap<String, PnlBreakdown> = BTreeMap::new();
+        let mut total = PnlBreakdown::default();
+        for entry in &entries {
+            by_strategy.entry(entry.strategy.clone()).or_default().add(entry.kind, entry.amount_usd);
+            by_exchange.entry(entry.exchange.clone()).or_default().add(entry.kind, entry.amount_usd);
+            total.add(entry.kind, entry.amount_usd);
+        }
+        for ((strategy, exchange), pnl) in self.unrealized.read().iter() {
+            by_strategy.entry(strategy.clone()).or_default().unrealized += pnl;
+            by_exchange.entry(exchange.clone()).or_default().unrealized += pnl;
+            total.unrealized += pnl;
+        }
+
+        let statement = DailyStatement {
+            date,
+            period_start: cutoff - ChronoDuration::days(1),
+            period_end: cutoff,
+            generated_at: self.clock.now(),
+            entries: entries.len(),
+            by_strategy,
+            by_exchange,
+            total,
+        };
+        self.store.save(&statement)?;
+        info!("Settled {}: {} entries, net {:.2} USD", date, statement.entries, statement.total.net());
+        metrics::gauge!("settlement_net_pnl_usd").set(statement.total.net());
+        Ok(statement)
+    }
+
+    pub fn statement(&self, date: NaiveDate) -> AdapterResult<Option<DailyStatement>> {
+        self.store.load(date)
+    }
+
+    /// Render a stored statement; HTML is not supported for statements
+    pub fn export(&self, date: NaiveDate, format: ReportFormat) -> AdapterResult<String> {
+        let statement = self
+            .store
+            .load(date)?
+            .ok_or_else(|| AdapterError::Validation { message: format!("no statement for {}", date) })?;
+        match format {
+            ReportFormat::Json => Ok(serde_json::to_string_pretty(&statement)?),
+            ReportFormat::Csv => Ok(render_csv(&statement)),
+            ReportFormat::Html => Err(AdapterError::Validation { message: "statements export as JSON or CSV".to_string() }),
+        }
+    }
+
+    /// Net PnL of the last `days` settled days
+    pub fn summary(&self, days: usize) -> AdapterResult<SettlementSummary> {
+        let mut statements = self.store.recent(days)?;
+        statements.reverse();
+        let mut per_strategy: BTreeMap<String, f64> = BTreeMap::new();
+        for statement in &statements {
+            for (strategy, pnl) in &statement.by_strategy {
+                *per_strategy.entry(strategy.clone()).or_default() += pnl.net();
+            }
+        }
+        let ranked = |best: bool| {
+            per_strategy
+                .iter()
+                .max_by(|a, b| if best { a.1.total_cmp(b.1) } else { b.1.total_cmp(a.1) })
+                .map(|(s, _)| s.clone())
+        };
+        Ok(SettlementSummary {
+            cumulative_net: statements.iter().map(|s| s.total.net()).sum(),
+            best_strategy: ranked(true),
+            worst_strategy: ranked(false),
+            days: statements
+                .into_iter()
+                .map(|s| SettlementSummaryRow { date: s.date, net: s.total.net(), total: s.total })
+                .collect(),
+        })
+    }
+
+    /// Settle at every cutoff
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                let now = self.clock.now();
+                let cutoff = self.next_cutoff(now);
+                let wait = (cutoff - now).to_std().unwrap_or_default();
+                tokio::time::sleep(wait).await;
+                if let Err(e) = self.settle(cutoff) {
+                    warn!("Settlement for cutoff {} failed: {}", cutoff, e);
+                }
+            }
+        })
+    }
+}
+
+const COLUMNS: [&str; 9] = [
+    "date", "scope", "name", "realized", "unrealized", "fees", "funding", "transfer_costs", "net",
+];
+
+fn csv_row(date: NaiveDate, scope: &str, name: &str, p: &PnlBreakdown) -> String {
+    format!(
+        "{},{},{},{:.4},{:.4},{:.4},{:.4},{:.4},{:.4}\n",
+        date, scope, name, p.realized, p.unrealized, p.fees, p.funding, p.transfer_costs, p.net()
+    )
+}
+
+/// Strategy rows, exchange rows and a total row
+fn render_csv(statement: &DailyStatement) -> String {
+    let mut out = COLUMNS.join(",");
+    out.push('\n');
+    for (name, pnl) in &statement.by_strategy {
+        out.push_str(&csv_row(statement.date, "strategy", name, pnl));
+    }
+    for (name, pnl) in &statement.by_exchange {
+        out.push_str(&csv_row(statement.date, "exchange", name, pnl));
+    }
+    out.push_str(&csv_row(statement.date, "total", "*", &statement.total));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use common::types::StrategyKind;
+
+    #[test]
+    fn test_settle_export_and_summary() {
+        let job = SettlementJob::new(SettlementConfig::default(), Arc::new(InMemoryStatementStore::default()));
+        let cutoff = Utc.with_ymd_and_hms(2024, 3, 2, 0, 0, 0).unwrap();
+        let before = (cutoff - ChronoDuration::hours(3)).timestamp_nanos_opt().unwrap() as u64;
+        assert_eq!(job.next_cutoff(cutoff - ChronoDuration::hours(3)), cutoff);
+
+        job.book_trade(&TradeRecord {
+            strategy: StrategyKind::InterExchange,
+            buy_exchange: "binance".into(),
+            sell_exchange: "okx".into(),
+            gross_profit: 20.0,
+            fees: 4.0,
+            slippage_bps: 0.0,
+            capital_used: 1_000.0,
+            timestamp_ns: before,
+        });
+        let funding = |ts| LedgerEntry {
+            kind: LedgerKind::Funding,
+            strategy: "Triangular".into(),
+            exchange: "bybit".into(),
+            amount_usd: 1.5,
+            timestamp_ns: ts,
+        };
+        job.book(funding(before));
+        job.book(funding(before + 4 * 3_600_000_000_000));
+        job.mark_unrealized("InterExchange", "okx", -2.0);
+
+        let statement = job.settle(cutoff).unwrap();
+        assert_eq!(statement.date, NaiveDate::from_ymd_opt(2024, 3, 1).unwrap());
+        assert_eq!(statement.entries, 5);
+        assert_eq!(statement.by_exchange["binance"].realized, 10.0);
+        assert!((statement.total.net() - (20.0 - 4.0 - 1.5 - 2.0)).abs() < 1e-9);
+        assert!(job.settle(cutoff).is_err());
+
+        let csv = job.export(statement.date, ReportFormat::Csv).unwrap();
+        assert_eq!(csv.lines().count(), 1 + 2 + 3 + 1);
+        let summary = job.summary(30).unwrap();
+        assert_eq!(summary.days.len(), 1);
+        assert_eq!(summary.best_strategy.as_deref(), Some("InterExchange"));
+        assert_eq!(summary.worst_strategy.as_deref(), Some("Triangular"));
+    }
+}
@@ -0,0 +1,362 @@
+//! Smart order slicing (TWAP / iceberg) for large legs
+//!
+//! A leg whose quantity exceeds a configurable fraction of top-of-book
+//! depth is split into child orders. Child fills are aggregated back into
+//! a single ExecutionResult with slippage attributed per slice.
+
+use crate::batch_orders;
+use crate::{AdapterError, AdapterResult};
+use common::{ArbitrageLeg, ExecutionResult, FixedPrice, FixedQuantity, Side, SliceReport};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::warn;
+
+/// Slicing algorithm
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SliceMode {
+    /// Equal child orders spaced by a fixed interval
+    Twap { slices: usize, interval_ms: u64 },
+    /// Child orders sized to a visible fraction of top-of-book depth
+    Iceberg { visible_depth_fraction: f64 },
+}
+
+/// Slicing configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlicingConfig {
+    pub enabled: bool,
+    /// Slice when quantity exceeds this fraction of top-of-book depth
+    pub max_top_depth_fraction: f64,
+    pub mode: SliceMode,
+    /// Smallest child order quantity
+    pub min_slice_quantity: f64,
+    /// Upper bound on child orders per leg
+    pub max_slices: usize,
+}
+
+impl Default for SlicingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_top_depth_fraction: 0.3,
+            mode: SliceMode::Twap { slices: 4, interval_ms: 20 },
+            min_slice_quantity: 0.0,
+            max_slices: 20,
+        }
+    }
+}
+
+/// Venue-side child order placement
+#[async_trait::async_trait]
+pub trait SliceExecutor: Send + Sync {
+    /// Place one child order; returns (order_id, fill_price)
+    async fn execute_slice(&self, leg: &ArbitrageLeg, quantity: f64) -> AdapterResult<(String, f64)>;
+
+    /// Largest batch accepted for `leg`'s venue and market, None if orders must go one at a time
+    fn batch_limit(&self, _leg: &ArbitrageLeg) -> Option<usize> {
+        None
+    }
+
+    /// Place several child orders of one leg in a single request.
+    ///
+    /// The outer error means the whole request failed; inner errors are
+    /// per-order rejections, in the order of `quantities`. Only a
+    /// `Validation` error guarantees nothing was sent.
+    async fn execute_batch(&self, leg: &ArbitrageLeg, quantities: &[f64]) -> AdapterResult<Vec<AdapterResult<(String, f64)>>> {
+        let mut results = Vec::with_capacity(quantities.len());
+        for quantity in quantities {
+            results.push(self.execute_slice(leg, *quantity).await);
+        }
+        Ok(results)
+    }
+}
+
+/// Result of executing one (possibly sliced) leg
+#[derive(Debug, Clone, Default)]
+pub struct LegFills {
+    pub order_ids: Vec<String>,
+    pub slices: Vec<SliceReport>,
+    pub filled_quantity: f64,
+    pub notional: f64,
+}
+
+impl LegFills {
+    pub fn average_price(&self) -> Option<f64> {
+        (self.filled_quantity > 0.0).then(|| self.notional / self.filled_quantity)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn record(&mut self, leg_index: usize, slice_index: usize, side: Side, reference: f64, size: f64, order_id: String, price: f64) {
+        self.order_ids.push(order_id.clone());
+        self.filled_quantity += size;
+        self.notional += size * price;
+        self.slices.push(SliceReport {
+            leg_index,
+            slice_index,
+            order_id,
+            quantity: size,
+            price,
+            slippage_bps: slippage_bps(side, reference, price),
+        });
+    }
+}
+
+/// Slicing engine
+pub struct SlicingEngine {
+    config: SlicingConfig,
+}
+
+impl SlicingEngine {
+    pub fn new(config: SlicingConfig) -> Self {
+        Self { config }
+    }
+
+    /// Child order sizes for `quantity`, or a single slice if no slicing is needed
+    pub fn plan(&self, quantity: f64, top_depth: Option<f64>) -> Vec<f64> {
+        let depth = match top_depth {
+            Some(d) if d > 0.0 => d,
+            _ => return vec![quantity],
+        };
+        if !self.config.enabled || quantity <= depth * self.config.max_top_depth_fraction {
+            return vec![quantity];
+        }
+
+        let child = match &self.config.mode {
+            SliceMode::Twap { slices, .. } => quantity / (*slices).max(1) as f64,
+            SliceMode::Iceberg { visible_depth_fraction } => depth * visible_depth_fraction,
+        };
+        let max_slices = self.config.max_slices.max(1);
+        let child = child
+            .max(self.config.min_slice_quantity)
+            .max(quantity / max_slices as f64);
+        if child <= 0.0 || child >= quantity {
+            return vec![quantity];
+        }
+
+        // Whole child orders with the remainder in the last one; the
+        // tolerance keeps 3.0 / (3.0 / 7.0) at 7 slices rather than 8
+        let count = ((quantity / child - 1e-9).ceil() as usize).clamp(1, max_slices);
+        let mut sizes = vec![child; count - 1];
+        sizes.push(quantity - child * (count - 1) as f64);
+        sizes
+    }
+
+    fn interval(&self) -> Duration {
+        match self.config.mode {
+            SliceMode::Twap { interval_ms, .. } => Duration::from_millis(interval_ms),
+            SliceMode::Iceberg { .. } => Duration::ZERO,
+        }
+    }
+
+    /// Execute one leg, slicing if necessary
+    pub async fn execute_leg(
+        &self,
+        leg_index: usize,
+        leg: &ArbitrageLeg,
+        top_depth: Option<f64>,
+        executor: &dyn SliceExecutor,
+    ) -> AdapterResult<LegFills> {
+        let sizes = self.plan(leg.quantity.to_f64(), top_depth);
+        let reference = leg.price.to_f64();
+        let interval = self.interval();
+        let mut fills = LegFills::default();
+
+        // Without a TWAP interval the children can go out together
+        let batch_limit = executor.batch_limit(leg).filter(|n| *n > 1);
+        if let (Some(limit), true, true) = (batch_limit, interval.is_zero(), sizes.len() > 1) {
+            let limit = limit.min(batch_orders::MAX_ORDER_BATCH_SIZE);
+            for (chunk_index, chunk) in sizes.chunks(limit).enumerate() {
+                let results: Vec<Option<(String, f64)>> = match executor.execute_batch(leg, chunk).await {
+                    Ok(results) if results.len() == chunk.len() => results
+                        .into_iter()
+                        .map(|r| r.map_err(|e| warn!("Batch item to {} rejected: {}", leg.exchange.as_str(), e)).ok())
+                        .collect(),
+                    // Some orders may be live; resubmitting could double the position
+                    Ok(results) => {
+                        return Err(AdapterError::Exchange {
+                            exchange: leg.exchange.as_str().to_string(),
+                            code: "batch_result_mismatch".to_string(),
+                            message: format!("batch returned {} results for {} orders", results.len(), chunk.len()),
+                        });
+                    }
+                    // Rejected before sending, so nothing is live yet
+                    Err(AdapterError::Validation { message }) => {
+                        warn!("Batch to {} rejected ({}), submitting one at a time", leg.exchange.as_str(), message);
+                        vec![None; chunk.len()]
+                    }
+                    Err(e) => return Err(e),
+                };
+                for (offset, (size, result)) in chunk.iter().zip(results).enumerate() {
+                    // Rejected items are retried individually
+                    let (order_id, price) = match result {
+                        Some(fill) => fill,
+                        None => executor.execute_slice(leg, *size).await?,
+                    };
+                    fills.record(leg_index, chunk_index * limit + offset, leg.side, reference, *size, order_id, price);
+                }
+            }
+            return Ok(fills);
+        }
+
+        for (slice_index, size) in sizes.iter().enumerate() {
+            if slice_index > 0 && !interval.is_zero() {
+                tokio::time::sleep(interval).await;
+            }
+            let (order_id, price) = executor.execute_slice(leg, *size).await?;
+            fills.record(leg_index, slice_index, leg.side, reference, *size, order_id, price);
+        }
+        Ok(fills)
+    }
+
+    /// Aggregate per-leg fills into a single execution result
+    pub fn aggregate(&self, opportunity_id: String, legs: &[ArbitrageLeg], fills: Vec<LegFills>) -> ExecutionResult {
+        let complete = legs
+            .iter()
+            .zip(&fills)
+            .all(|(leg, f)| f.filled_quantity + 1e-12 >= leg.quantity.to_f64());
+
+        let order_ids: Vec<String> = fills.iter().flat_map(|f| f.order_ids.clone()).collect();
+        let mut result = if complete {
+            ExecutionResult::accepted(opportunity_id, order_ids, None)
+        } else {
+            ExecutionResult::partial(opportunity_id, order_ids, "partially filled".to_string(), None)
+        };
+
+        // Executed quantity / average price refer to the first leg
+        if let (Some(leg), Some(first)) = (legs.first(), fills.first()) {
+            result.executed_quantity = Some(FixedQuantity::from_f64(first.filled_quantity, leg.quantity.scale()));
+            result.average_price = first
+                .average_price()
+                .map(|p| FixedPrice::from_f64(p, leg.price.scale()));
+        }
+        result.slices = fills.into_iter().flat_map(|f| f.slices).collect();
+        result
+    }
+}
+
+/// Slippage vs reference in bps; positive means a worse price than expected
+pub fn slippage_bps(side: Side, reference: f64, fill: f64) -> f64 {
+    if reference <= 0.0 {
+        return 0.0;
+    }
+    let diff = match side {
+        Side::Buy => fill - reference,
+        Side::Sell => reference - fill,
+    };
+    diff / reference * 10_000.0
+}
+
+impl Default for SlicingEngine {
+    fn default() -> Self {
+        Self::new(SlicingConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::testing::leg;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn engine(mode: SliceMode) -> SlicingEngine {
+        SlicingEngine::new(SlicingConfig { enabled: true, mode, ..SlicingConfig::default() })
+    }
+
+    fn assert_sums_to(sizes: &[f64], quantity: f64) {
+        assert!((sizes.iter().sum::<f64>() - quantity).abs() < 1e-12, "{:?}", sizes);
+    }
+
+    #[test]
+    fn test_plan_uses_whole_slice_counts() {
+        let iceberg = engine(SliceMode::Iceberg { visible_depth_fraction: 0.1 });
+        let sizes = iceberg.plan(1.0, Some(1.0));
+        assert_eq!(sizes.len(), 10);
+        assert_sums_to(&sizes, 1.0);
+
+        let twap = engine(SliceMode::Twap { slices: 7, interval_ms: 0 });
+        let sizes = twap.plan(3.0, Some(1.0));
+        assert_eq!(sizes.len(), 7);
+        assert_sums_to(&sizes, 3.0);
+        assert!(sizes.iter().all(|s| (s - 3.0 / 7.0).abs() < 1e-12));
+    }
+
+    #[test]
+    fn test_plan_puts_remainder_in_last_slice() {
+        let iceberg = engine(SliceMode::Iceberg { visible_depth_fraction: 0.25 });
+        let sizes = iceberg.plan(1.1, Some(1.0));
+        assert_eq!(sizes.len(), 5);
+        assert!(sizes[..4].iter().all(|s| *s == 0.25));
+        assert!((sizes[4] - 0.1).abs() < 1e-12);
+
+        // Small legs and unknown depth go out whole
+        assert_eq!(iceberg.plan(0.2, Some(1.0)), vec![0.2]);
+        assert_eq!(iceberg.plan(5.0, None), vec![5.0]);
+    }
+
+    /// Whole-batch result for a batch of the given size
+    type BatchResponse = fn(usize) -> AdapterResult<Vec<AdapterResult<(String, f64)>>>;
+
+    /// Batches of up to 5 that answer with a fixed outcome; single orders always fill
+    struct BatchOutcome {
+        outcome: BatchResponse,
+        singles: AtomicUsize,
+    }
+
+    impl BatchOutcome {
+        fn new(outcome: BatchResponse) -> Self {
+            Self { outcome, singles: AtomicUsize::new(0) }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl SliceExecutor for BatchOutcome {
+        async fn execute_slice(&self, leg: &ArbitrageLeg, _quantity: f64) -> AdapterResult<(String, f64)> {
+            let n = self.singles.fetch_add(1, Ordering::SeqCst);
+            Ok((format!("single_{}", n), leg.price.to_f64()))
+        }
+
+        fn batch_limit(&self, _leg: &ArbitrageLeg) -> Option<usize> {
+            Some(5)
+        }
+
+        async fn execute_batch(&self, _leg: &ArbitrageLeg, quantities: &[f64]) -> AdapterResult<Vec<AdapterResult<(String, f64)>>> {
+            (self.outcome)(quantities.len())
+        }
+    }
+
+    async fn execute(executor: &BatchOutcome) -> AdapterResult<LegFills> {
+        let iceberg = engine(SliceMode::Iceberg { visible_depth_fraction: 0.25 });
+        iceberg.execute_leg(0, &leg("okx", "BTCUSDT", Side::Buy, 100.0, 1.0), Some(1.0), executor).await
+    }
+
+    #[tokio::test]
+    async fn test_batch_retries_only_rejected_items() {
+        let executor = BatchOutcome::new(|n| {
+            Ok((0..n)
+                .map(|i| match i {
+                    1 => Err(AdapterError::Exchange { exchange: "okx".into(), code: "51008".into(), message: "insufficient".into() }),
+                    _ => Ok((format!("batch_{}", i), 100.0)),
+                })
+                .collect())
+        });
+        let fills = execute(&executor).await.unwrap();
+        assert_eq!(fills.order_ids, vec!["batch_0", "single_0", "batch_2", "batch_3"]);
+        assert_eq!(fills.filled_quantity, 1.0);
+
+        // Rejected before sending: every child goes out on its own
+        let executor = BatchOutcome::new(|_| Err(AdapterError::Validation { message: "too many orders".into() }));
+        assert_eq!(execute(&executor).await.unwrap().order_ids.len(), 4);
+        assert_eq!(executor.singles.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_batch_failure_after_sending_is_not_resubmitted() {
+        let executor = BatchOutcome::new(|_| Err(AdapterError::Timeout { duration_ms: 500 }));
+        assert!(matches!(execute(&executor).await, Err(AdapterError::Timeout { .. })));
+        assert_eq!(executor.singles.load(Ordering::SeqCst), 0);
+
+        let executor = BatchOutcome::new(|n| Ok((1..n).map(|i| Ok((format!("batch_{}", i), 100.0))).collect()));
+        assert!(matches!(execute(&executor).await, Err(AdapterError::Exchange { code, .. }) if code == "batch_result_mismatch"));
+        assert_eq!(executor.singles.load(Ordering::SeqCst), 0);
+    }
+}
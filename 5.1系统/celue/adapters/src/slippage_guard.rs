@@ -0,0 +1,302 @@
+//! Slippage circuit breaker per strategy
+//!
+//! Every fill reports the slippage the strategy expected when it sized the
+//! trade and the slippage actually realized. A fill whose realized slippage
+//! exceeds the estimate by more than `tolerance_bps` is a breach; a fill
+//! within tolerance resets the streak. After `max_consecutive_breaches` in
+//! a row the strategy's slippage model is treated as broken: a `Strategy`
+//! halt blocks its new opportunities and a Critical alert is raised. The
+//! strategy stays paused until an operator re-enables it or, when
+//! `cooldown_secs` is non-zero, the cool-down elapses.
+//!
+//! The execution adapter feeds every execution result in: realized slippage
+//! is the quantity-weighted slippage of its child fills, and the estimate is
+//! the `estimated_slippage_bps` tag the strategy put on the opportunity
+//! (zero when the strategy priced in none).
+
+use crate::alerting::{Alert, AlertManager, AlertSeverity};
+use crate::halt::{HaltRegistry, HaltScope};
+use common::{ArbitrageOpportunity, ExecutionResult, SharedClock, SliceReport};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+const OPERATOR: &str = "slippage_guard";
+
+/// Opportunity tag carrying the slippage the strategy priced in (bps)
+pub const ESTIMATED_SLIPPAGE_TAG: &str = "estimated_slippage_bps";
+
+/// Quantity-weighted slippage of a set of fills; None without fills
+pub fn realized_slippage_bps(slices: &[SliceReport]) -> Option<f64> {
+    let quantity: f64 = slices.iter().map(|s| s.quantity).sum();
+    (quantity > 0.0).then(|| slices.iter().map(|s| s.slippage_bps * s.quantity).sum::<f64>() / quantity)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlippageGuardConfig {
+    /// Realized minus estimated slippage that counts as a breach (bps)
+    pub tolerance_bps: f64,
+    pub max_consecutive_breaches: u32,
+    /// Automatic re-enable after this long; 0 requires an operator
+    pub cooldown_secs: u64,
+}
+
+impl Default for SlippageGuardConfig {
+    fn default() -> Self {
+        Self { tolerance_bps: 5.0, max_consecutive_breaches: 3, cooldown_secs: 1_800 }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StrategySlippage {
+    pub strategy: String,
+    pub fills: u64,
+    pub breaches: u64,
+    pub consecutive_breaches: u32,
+    /// Realized minus estimated slippage of the last fill (bps)
+    pub last_excess_bps: f64,
+    pub paused: bool,
+    pub paused_at_ns: Option<u64>,
+    /// When the cool-down re-enables the strategy
+    pub resume_at_ns: Option<u64>,
+}
+
+/// Per-strategy slippage feedback guard
+pub struct SlippageGuard {
+    config: SlippageGuardConfig,
+    strategies: RwLock<HashMap<String, StrategySlippage>>,
+    halts: Arc<HaltRegistry>,
+    alerts: Option<Arc<AlertManager>>,
+    clock: SharedClock,
+}
+
+impl SlippageGuard {
+    pub fn new(config: SlippageGuardConfig, halts: Arc<HaltRegistry>) -> Self {
+        Self {
+            config,
+            strategies: RwLock::new(HashMap::new()),
+            halts,
+            alerts: None,
+            clock: common::clock::system_clock(),
+        }
+    }
+
+    pub fn with_alerts(mut self, alerts: Arc<AlertManager>) -> Self {
+        self.alerts = Some(alerts);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Record a fill; returns true when it paused the strategy
+    pub async fn record_fill(&self, strategy: &str, estimated_bps: f64, realized_bps: f64) -> bool {
+        if !estimated_bps.is_finite() || !realized_bps.is_finite() {
+            return false;
+        }
+        let excess = realized_bps - estimated_bps;
+        let now_ns = self.clock.now_ns();
+        let tripped = {
+            let mut strategies = self.strategies.write();
+            let entry = strategies
+                .entry(strategy.to_string())
+                .or_insert_with(|| StrategySlippage { strategy: strategy.to_string(), ..Default::default() });
+            entry.fills += 1;
+            entry.last_excess_bps = excess;
+            if excess > self.config.tolerance_bps {
+                entry.breaches += 1;
+                entry.consecutive_breaches += 1;
+            } else {
+                entry.consecutive_breaches = 0;
+            }
+            metrics::gauge!("slippage_excess_bps", "strategy" => strategy.to_string()).set(excess);
+
+            let trip = !entry.paused && entry.consecutive_breaches >= self.config.max_consecutive_breaches.max(1);
+            if trip {
+                entry.paused = true;
+                entry.paused_at_ns = Some(now_ns);
+                entry.resume_at_ns =
+                    (self.config.cooldown_secs > 0).then(|| now_ns + self.config.cooldown_secs * 1_000_000_000);
+            }
+            trip.then_some(entry.consecutive_breaches)
+        };
+        let Some(streak) = tripped else { return false };
+
+        let reason = format!(
+            "{} consecutive fills exceeded estimated slippage by more than {:.1} bps (last {:.1} bps)",
+            streak, self.config.tolerance_bps, excess
+        );
+        error!("Pausing strategy {}: {}", strategy, reason);
+        metrics::counter!("slippage_guard_trips_total", "strategy" => strategy.to_string()).increment(1);
+        if let Err(e) = self.halts.halt(HaltScope::Strategy(strategy.to_string()), &reason, OPERATOR).await {
+            warn!("Failed to halt strategy {}: {}", strategy, e);
+        }
+        let resume = if self.config.cooldown_secs > 0 {
+            format!("re-enabled automatically after {}s unless an operator does so earlier", self.config.cooldown_secs)
+        } else {
+            "requires operator re-enable".to_string()
+        };
+        self.alert(strategy, AlertSeverity::Critical, "paused: slippage model breached", &format!("{}; {}", reason, resume))
+            .await;
+        true
+    }
+
+    /// Record the fills of an executed opportunity; returns true when it
+    /// paused the strategy
+    pub async fn record_execution(&self, opportunity: &ArbitrageOpportunity, result: &ExecutionResult) -> bool {
+        let Some(realized) = realized_slippage_bps(&result.slices) else { return false };
+        let estimated = opportunity
+            .tags
+            .get(ESTIMATED_SLIPPAGE_TAG)
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.0);
+        self.record_fill(&opportunity.strategy_name, estimated, realized).await
+    }
+
+    /// Operator re-enable; false if the strategy was not paused
+    pub async fn re_enable(&self, strategy: &str, operator: &str) -> bool {
+        if !self.resume(strategy) {
+            return false;
+        }
+        info!("Strategy {} re-enabled by {} after slippage pause", strategy, operator);
+        self.alert(strategy, AlertSeverity::Info, "re-enabled", &format!("re-enabled by {}", operator)).await;
+        true
+    }
+
+    /// Re-enable strategies whose cool-down has elapsed
+    pub async fn evaluate(&self) -> Vec<String> {
+        let now_ns = self.clock.now_ns();
+        let due: Vec<String> = self
+            .strategies
+            .read()
+            .values()
+            .filter(|s| s.paused && s.resume_at_ns.is_some_and(|at| now_ns >= at))
+            .map(|s| s.strategy.clone())
+            .collect();
+        for strategy in &due {
+            if self.resume(strategy) {
+                info!("Strategy {} re-enabled after slippage cool-down", strategy);
+                self.alert(strategy, AlertSeverity::Info, "re-enabled", "slippage cool-down elapsed").await;
+            }
+        }
+        due
+    }
+
+    fn resume(&self, strategy: &str) -> bool {
+        {
+            let mut strategies = self.strategies.write();
+            let Some(entry) = strategies.get_mut(strategy).filter(|s| s.paused) else { return false };
+            entry.paused = false;
+            entry.paused_at_ns = None;
+            entry.resume_at_ns = None;
+            entry.consecutive_breaches = 0;
+        }
+        self.halts.release(&HaltScope::Strategy(strategy.to_string()), OPERATOR);
+        true
+    }
+
+    async fn alert(&self, strategy: &str, severity: AlertSeverity, what: &str, message: &str) {
+        let Some(alerts) = &self.alerts else { return };
+        let alert = Alert::new(&format!("slippage_guard:{}", strategy), severity, &format!("Strategy {} {}", strategy, what), message, OPERATOR);
+        alerts.raise(alert).await;
+    }
+
+    pub fn status(&self) -> Vec<StrategySlippage> {
+        let mut status: Vec<StrategySlippage> = self.strategies.read().values().cloned().collect();
+        status.sort_by(|a, b| a.strategy.cmp(&b.strategy));
+        status
+    }
+
+    pub fn is_paused(&self, strategy: &str) -> bool {
+        self.strategies.read().get(strategy).is_some_and(|s| s.paused)
+    }
+
+    pub fn spawn(self: Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.evaluate().await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::testing::inter_exchange_opportunity;
+    use common::SimulatedClock;
+
+    #[tokio::test]
+    async fn test_pauses_after_consecutive_breaches_until_cooldown() {
+        let clock = Arc::new(SimulatedClock::from_nanos(1_000_000_000));
+        let halts = Arc::new(HaltRegistry::new());
+        let guard = SlippageGuard::new(SlippageGuardConfig::default(), halts.clone()).with_clock(clock.clone());
+
+        assert!(!guard.record_fill("inter_exchange", 2.0, 10.0).await);
+        assert!(!guard.record_fill("inter_exchange", 2.0, 12.0).await);
+        // A fill within tolerance resets the streak
+        assert!(!guard.record_fill("inter_exchange", 2.0, 4.0).await);
+        assert!(!guard.record_fill("inter_exchange", 2.0, 10.0).await);
+        assert!(!guard.record_fill("inter_exchange", 2.0, 10.0).await);
+        assert!(guard.record_fill("inter_exchange", 2.0, 10.0).await);
+        assert!(guard.is_paused("inter_exchange"));
+        assert!(halts.blocking_halt(&inter_exchange_opportunity("inter_exchange", "BTCUSDT", 0.01)).is_some());
+        assert!(halts.blocking_halt(&inter_exchange_opportunity("triangular", "BTCUSDT", 0.01)).is_none());
+        assert!(!guard.record_fill("inter_exchange", 2.0, 10.0).await, "already paused");
+
+        assert!(guard.evaluate().await.is_empty());
+        clock.advance(chrono::Duration::seconds(1_801));
+        assert_eq!(guard.evaluate().await, vec!["inter_exchange".to_string()]);
+        assert!(halts.blocking_halt(&inter_exchange_opportunity("inter_exchange", "BTCUSDT", 0.01)).is_none());
+        let status = guard.status();
+        assert_eq!((status[0].fills, status[0].breaches, status[0].consecutive_breaches), (7, 6, 0));
+
+        let manual = SlippageGuard::new(SlippageGuardConfig { cooldown_secs: 0, ..Default::default() }, halts.clone());
+        for _ in 0..3 {
+            manual.record_fill("triangular", 0.0, 20.0).await;
+        }
+        assert!(manual.status()[0].resume_at_ns.is_none());
+        assert!(manual.re_enable("triangular", "alice").await);
+        assert!(!manual.re_enable("triangular", "alice").await);
+        assert!(halts.blocking_halt(&inter_exchange_opportunity("triangular", "BTCUSDT", 0.01)).is_none());
+    }
+
+    fn slice(quantity: f64, slippage_bps: f64) -> SliceReport {
+        SliceReport { leg_index: 0, slice_index: 0, order_id: "1".into(), quantity, price: 100.0, slippage_bps }
+    }
+
+    #[tokio::test]
+    async fn test_record_execution_uses_weighted_slices_and_keeps_operator_halt() {
+        let halts = Arc::new(HaltRegistry::new());
+        let guard = SlippageGuard::new(SlippageGuardConfig { cooldown_secs: 0, ..Default::default() }, halts.clone());
+        let mut opportunity = inter_exchange_opportunity("inter_exchange", "BTCUSDT", 0.01);
+        opportunity.tags.insert(ESTIMATED_SLIPPAGE_TAG.to_string(), "2.0".to_string());
+        let mut result = ExecutionResult::accepted(opportunity.id.to_string(), vec![], None);
+
+        assert!(!guard.record_execution(&opportunity, &result).await, "no fills, nothing recorded");
+        assert!(guard.status().is_empty());
+
+        // (1 * 4 + 3 * 12) / 4 = 10 bps realized against 2 bps estimated
+        result.slices = vec![slice(1.0, 4.0), slice(3.0, 12.0)];
+        assert_eq!(realized_slippage_bps(&result.slices), Some(10.0));
+        halts.halt(HaltScope::Strategy("inter_exchange".into()), "manual", "ops").await.unwrap();
+        for _ in 0..2 {
+            assert!(!guard.record_execution(&opportunity, &result).await);
+        }
+        assert!(guard.record_execution(&opportunity, &result).await);
+        assert_eq!(guard.status()[0].last_excess_bps, 8.0);
+
+        // Re-enabling lifts only the guard's own halt, never the operator's
+        assert!(guard.re_enable("inter_exchange", "alice").await);
+        let remaining = halts.active();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].operator, "ops");
+    }
+}
@@ -0,0 +1,113 @@
+//! Quote staleness enforcement at claim time
+//! Rejects opportunities built on quotes older than the configured bound
+
+use common::ArbitrageOpportunity;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Per-strategy claim statistics
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StalenessStats {
+    /// Opportunities accepted for execution
+    pub claimed: u64,
+    /// Opportunities refused because of stale quotes
+    pub rejected: u64,
+    /// Largest quote age seen at rejection (ms)
+    pub max_rejected_age_ms: u64,
+    /// Opportunities claimed without a known quote watermark
+    #[serde(default)]
+    pub unknown_watermark: u64,
+}
+
+impl StalenessStats {
+    /// Fraction of claim attempts rejected as stale (0.0 - 1.0)
+    pub fn rejection_rate(&self) -> f64 {
+        let total = self.claimed + self.rejected;
+        if total == 0 {
+            0.0
+        } else {
+            self.rejected as f64 / total as f64
+        }
+    }
+}
+
+/// Claim-time staleness guard
+pub struct StalenessGuard {
+    max_quote_staleness_ms: u64,
+    stats: RwLock<HashMap<String, StalenessStats>>,
+}
+
+impl StalenessGuard {
+    /// Create a guard; a bound of 0 disables the check
+    pub fn new(max_quote_staleness_ms: u64) -> Self {
+        Self {
+            max_quote_staleness_ms,
+            stats: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Try to claim an opportunity at `now_ns`.
+    /// Returns `Err(age_ms)` when the quote watermark is too old; an unknown
+    /// (zero) watermark is claimed and counted, never treated as infinitely old.
+    pub fn try_claim(&self, opportunity: &ArbitrageOpportunity, now_ns: u64) -> Result<(), u64> {
+        let mut stats = self.stats.write();
+        let entry = stats.entry(opportunity.strategy_name.clone()).or_default();
+        let Some(age_ns) = opportunity.quote_age_ns(now_ns) else {
+            entry.unknown_watermark += 1;
+            entry.claimed += 1;
+            return Ok(());
+        };
+        let age_ms = age_ns / 1_000_000;
+
+        if self.max_quote_staleness_ms > 0 && age_ms > self.max_quote_staleness_ms {
+            entry.rejected += 1;
+            entry.max_rejected_age_ms = entry.max_rejected_age_ms.max(age_ms);
+            return Err(age_ms);
+        }
+
+        entry.claimed += 1;
+        Ok(())
+    }
+
+    /// Configured staleness bound (ms)
+    pub fn max_quote_staleness_ms(&self) -> u64 {
+        self.max_quote_staleness_ms
+    }
+
+    /// Stats for a single strategy
+    pub fn strategy_stats(&self, strategy: &str) -> Option<StalenessStats> {
+        self.stats.read().get(strategy).cloned()
+    }
+
+    /// Stats for all strategies
+    pub fn all_stats(&self) -> HashMap<String, StalenessStats> {
+        self.stats.read().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::testing::inter_exchange_opportunity;
+
+    const MS: u64 = 1_000_000;
+
+    #[test]
+    fn test_claims_fresh_and_unknown_rejects_stale() {
+        let guard = StalenessGuard::new(100);
+        let fresh = inter_exchange_opportunity("inter_exchange", "BTCUSDT", 0.01).with_quote_watermark([1_000 * MS]);
+        assert_eq!(guard.try_claim(&fresh, 1_050 * MS), Ok(()));
+        assert_eq!(guard.try_claim(&fresh, 1_250 * MS), Err(250));
+
+        // The fixture is created at 0 and carries no quote time
+        let unknown = inter_exchange_opportunity("inter_exchange", "BTCUSDT", 0.01);
+        assert_eq!(unknown.quote_watermark_ns, 0);
+        assert_eq!(guard.try_claim(&unknown, 1_250 * MS), Ok(()));
+
+        let stats = guard.strategy_stats("inter_exchange").unwrap();
+        assert_eq!((stats.claimed, stats.rejected, stats.unknown_watermark), (2, 1, 1));
+        assert_eq!(stats.max_rejected_age_ms, 250);
+        assert!(StalenessGuard::new(0).try_claim(&fresh, u64::MAX).is_ok());
+    }
+}
@@ -0,0 +1,156 @@
+//! Per-subject NATS metrics
+//!
+//! Records payload size, publish latency and consumer ack lag for every
+//! subject and exports them to Prometheus with a `subject` label, so
+//! oversized snapshots and slow consumers show up directly on the
+//! bundled Grafana dashboard (`configs/grafana/dashboards/nats_subjects_dashboard.json`).
+
+use metrics::{describe_gauge, describe_histogram, gauge, histogram};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Pre-built Grafana dashboard for the metrics below
+pub const DASHBOARD_JSON: &str = include_str!("../../configs/grafana/dashboards/nats_subjects_dashboard.json");
+
+/// Aggregated statistics for one subject
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SubjectStats {
+    pub published: u64,
+    pub total_bytes: u64,
+    pub max_payload_bytes: usize,
+    pub total_publish_latency_us: u64,
+    pub max_publish_latency_us: u64,
+    pub acked: u64,
+    pub last_ack_lag_ms: f64,
+    pub max_ack_lag_ms: f64,
+}
+
+impl SubjectStats {
+    pub fn avg_payload_bytes(&self) -> f64 {
+        if self.published == 0 { 0.0 } else { self.total_bytes as f64 / self.published as f64 }
+    }
+
+    pub fn avg_publish_latency_us(&self) -> f64 {
+        if self.published == 0 { 0.0 } else { self.total_publish_latency_us as f64 / self.published as f64 }
+    }
+}
+
+/// Subject-level metrics registry
+pub struct SubjectMetrics {
+    stats: RwLock<HashMap<String, SubjectStats>>,
+    /// Last reported backlog per JetStream consumer
+    pending: RwLock<HashMap<String, u64>>,
+    /// Subject tokens kept in the label; deeper tokens collapse to `*`
+    label_depth: usize,
+}
+
+impl SubjectMetrics {
+    pub fn new(label_depth: usize) -> Self {
+        describe_histogram!("nats_publish_latency_seconds", "NATS publish latency per subject");
+        describe_histogram!("nats_payload_bytes", "NATS payload size per subject");
+        describe_gauge!("nats_consumer_ack_lag_seconds", "Delay between publish and consumer ack per subject");
+        describe_gauge!("nats_consumer_pending_messages", "Pending messages per JetStream consumer");
+        Self {
+            stats: RwLock::new(HashMap::new()),
+            pending: RwLock::new(HashMap::new()),
+            label_depth: label_depth.max(1),
+        }
+    }
+
+    /// Bounded-cardinality label for a subject
+    pub fn subject_label(&self, subject: &str) -> String {
+        let tokens: Vec<&str> = subject.split('.').collect();
+        if tokens.len() <= self.label_depth {
+            return subject.to_string();
+        }
+        let mut label = tokens[..self.label_depth].join(".");
+        label.push_str(".*");
+        label
+    }
+
+    /// Record one publish
+    pub fn record_publish(&self, subject: &str, payload_bytes: usize, latency: Duration) {
+        let label = self.subject_label(subject);
+        let latency_us = latency.as_micros() as u64;
+        histogram!("nats_publish_latency_seconds", "subject" => label.clone()).record(latency.as_secs_f64());
+        histogram!("nats_payload_bytes", "subject" => label.clone()).record(payload_bytes as f64);
+
+        let mut stats = self.stats.write();
+        let entry = stats.entry(label).or_default();
+        entry.published += 1;
+        entry.total_bytes += payload_bytes as u64;
+        entry.max_payload_bytes = entry.max_payload_bytes.max(payload_bytes);
+        entry.total_publish_latency_us += latency_us;
+        entry.max_publish_latency_us = entry.max_publish_latency_us.max(latency_us);
+    }
+
+    /// Record a consumer ack for a message published at `published_at_ns`
+    pub fn record_ack(&self, subject: &str, published_at_ns: u64, acked_at_ns: u64) {
+        let label = self.subject_label(subject);
+        let lag_ms = acked_at_ns.saturating_sub(published_at_ns) as f64 / 1_000_000.0;
+        gauge!("nats_consumer_ack_lag_seconds", "subject" => label.clone()).set(lag_ms / 1000.0);
+
+        let mut stats = self.stats.write();
+        let entry = stats.entry(label).or_default();
+        entry.acked += 1;
+        entry.last_ack_lag_ms = lag_ms;
+        entry.max_ack_lag_ms = entry.max_ack_lag_ms.max(lag_ms);
+    }
+
+    /// Record JetStream consumer backlog
+    pub fn record_consumer_pending(&self, consumer: &str, pending: u64) {
+        gauge!("nats_consumer_pending_messages", "consumer" => consumer.to_string()).set(pending as f64);
+        self.pending.write().insert(consumer.to_string(), pending);
+    }
+
+    /// Last reported backlog per consumer
+    pub fn consumer_pending(&self) -> HashMap<String, u64> {
+        self.pending.read().clone()
+    }
+
+    pub fn snapshot(&self) -> HashMap<String, SubjectStats> {
+        self.stats.read().clone()
+    }
+
+    /// Subjects sorted by average payload size, largest first
+    pub fn hotspots(&self, limit: usize) -> Vec<(String, SubjectStats)> {
+        let mut all: Vec<(String, SubjectStats)> = self.snapshot().into_iter().collect();
+        all.sort_by(|a, b| {
+            b.1.avg_payload_bytes()
+                .partial_cmp(&a.1.avg_payload_bytes())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        all.truncate(limit);
+        all
+    }
+}
+
+impl Default for SubjectMetrics {
+    fn default() -> Self {
+        Self::new(3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ack_lag_and_backlog_per_subject() {
+        let metrics = SubjectMetrics::new(2);
+        metrics.record_publish("celue.events.opportunity", 512, Duration::from_micros(80));
+        metrics.record_ack("celue.events.opportunity", 1_000_000_000, 1_250_000_000);
+        metrics.record_ack("celue.events.alert", 1_000_000_000, 1_050_000_000);
+        metrics.record_consumer_pending("opportunity_processor", 7);
+        metrics.record_consumer_pending("opportunity_processor", 3);
+
+        let stats = metrics.snapshot();
+        let events = &stats["celue.events.*"];
+        assert_eq!((events.published, events.acked), (1, 2));
+        assert_eq!(events.last_ack_lag_ms, 50.0);
+        assert_eq!(events.max_ack_lag_ms, 250.0);
+        assert_eq!(metrics.consumer_pending()["opportunity_processor"], 3);
+    }
+}
@@ -0,0 +1,219 @@
+//! Per-symbol trading controls
+//!
+//! A finer kill switch than halts: detection and/or execution can be
+//! disabled for one symbol on every exchange or for a single
+//! exchange+symbol pair. Detection controls drop the affected books from
+//! market snapshots before strategies see them; execution controls refuse
+//! opportunities with a leg on the pair. Disabling withdraws matching live
+//! opportunities from the opportunity pool. Controls are written to a JSON
+//! file on every change and loaded on start, so they survive restarts.
+
+use crate::halt::normalize_symbol;
+use crate::opportunity_ttl::{ExpiryReason, OpportunityPool};
+use crate::AdapterResult;
+use chrono::{DateTime, Utc};
+use common::{ArbitrageOpportunity, NormalizedSnapshot};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Symbol on every exchange, or on one exchange
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct SymbolKey {
+    #[serde(default)]
+    pub exchange: Option<String>,
+    pub symbol: String,
+}
+
+impl SymbolKey {
+    pub fn new(exchange: Option<&str>, symbol: &str) -> Self {
+        Self { exchange: exchange.map(str::to_lowercase), symbol: normalize_symbol(symbol) }
+    }
+
+    /// Separators and case are ignored
+    pub fn matches(&self, exchange: &str, symbol: &str) -> bool {
+        self.exchange.as_deref().is_none_or(|e| e.eq_ignore_ascii_case(exchange))
+            && normalize_symbol(symbol) == self.symbol
+    }
+
+    fn matches_opportunity(&self, opportunity: &ArbitrageOpportunity) -> bool {
+        opportunity.legs.iter().any(|l| self.matches(l.exchange.as_str(), l.symbol.as_str()))
+    }
+}
+
+/// What is switched off
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlScope {
+    Detection,
+    Execution,
+    Both,
+}
+
+impl ControlScope {
+    fn detection(&self) -> bool {
+        matches!(self, ControlScope::Detection | ControlScope::Both)
+    }
+
+    fn execution(&self) -> bool {
+        matches!(self, ControlScope::Execution | ControlScope::Both)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolControl {
+    pub key: SymbolKey,
+    pub scope: ControlScope,
+    pub reason: String,
+    pub operator: String,
+    pub disabled_at: DateTime<Utc>,
+}
+
+/// Disabled symbols and pairs
+pub struct SymbolControls {
+    controls: RwLock<BTreeMap<SymbolKey, SymbolControl>>,
+    path: Option<PathBuf>,
+    pool: Option<Arc<OpportunityPool>>,
+}
+
+impl SymbolControls {
+    pub fn new() -> Self {
+        Self { controls: RwLock::new(BTreeMap::new()), path: None, pool: None }
+    }
+
+    /// Persist to `path`, loading controls saved there by a previous run
+    pub fn with_persistence(mut self, path: impl Into<PathBuf>) -> AdapterResult<Self> {
+        let path = path.into();
+        if path.exists() {
+            let saved: Vec<SymbolControl> = serde_json::from_slice(&std::fs::read(&path)?)?;
+            info!("Loaded {} symbol controls from {}", saved.len(), path.display());
+            *self.controls.get_mut() = saved.into_iter().map(|c| (c.key.clone(), c)).collect();
+        }
+        self.path = Some(path);
+        Ok(self)
+    }
+
+    /// Withdraw pooled opportunities when a symbol is disabled
+    pub fn with_pool(mut self, pool: Arc<OpportunityPool>) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    /// Disable a symbol or pair; returns the number of withdrawn opportunities
+    pub fn disable(&self, key: SymbolKey, scope: ControlScope, reason: &str, operator: &str) -> AdapterResult<usize> {
+        let key = SymbolKey::new(key.exchange.as_deref(), &key.symbol);
+        let control = SymbolControl {
+            key: key.clone(),
+            scope,
+            reason: reason.to_string(),
+            operator: operator.to_string(),
+            disabled_at: Utc::now(),
+        };
+        self.controls.write().insert(key.clone(), control);
+        self.persist()?;
+        warn!("Trading controls: {:?} disabled for {:?} by {}: {}", scope, key, operator, reason);
+
+        let cancelled = match &self.pool {
+            Some(pool) => pool.cancel_where(|o| key.matches_opportunity(o), ExpiryReason::Disabled).len(),
+            None => 0,
+        };
+        metrics::gauge!("symbol_controls_active").set(self.controls.read().len() as f64);
+        Ok(cancelled)
+    }
+
+    /// Re-enable a symbol or pair; returns false if it was not disabled
+    pub fn enable(&self, key: &SymbolKey, operator: &str) -> AdapterResult<bool> {
+        let key = SymbolKey::new(key.exchange.as_deref(), &key.symbol);
+        let removed = self.controls.write().remove(&key).is_some();
+        if removed {
+            self.persist()?;
+            info!("Trading controls: {:?} re-enabled by {}", key, operator);
+            metrics::gauge!("symbol_controls_active").set(self.controls.read().len() as f64);
+        }
+        Ok(removed)
+    }
+
+    pub fn list(&self) -> Vec<SymbolControl> {
+        self.controls.read().values().cloned().collect()
+    }
+
+    pub fn detection_disabled(&self, exchange: &str, symbol: &str) -> bool {
+        self.controls.read().values().any(|c| c.scope.detection() && c.key.matches(exchange, symbol))
+    }
+
+    /// Control refusing execution of the opportunity, if any
+    pub fn blocking_control(&self, opportunity: &ArbitrageOpportunity) -> Option<SymbolControl> {
+        self.controls
+            .read()
+            .values()
+            .find(|c| c.scope.execution() && c.key.matches_opportunity(opportunity))
+            .cloned()
+    }
+
+    /// Snapshot without the books disabled for detection; None if nothing is removed
+    pub fn filter_snapshot(&self, snapshot: &NormalizedSnapshot) -> Option<NormalizedSnapshot> {
+        if !snapshot.exchanges.iter().any(|b| self.detection_disabled(b.exchange.as_str(), b.symbol.as_str())) {
+            return None;
+        }
+        let mut filtered = snapshot.clone();
+        filtered.exchanges.retain(|b| !self.detection_disabled(b.exchange.as_str(), b.symbol.as_str()));
+        Some(filtered)
+    }
+
+    fn persist(&self) -> AdapterResult<()> {
+        let Some(path) = &self.path else { return Ok(()) };
+        let bytes = serde_json::to_vec_pretty(&self.list())?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // Write then rename so a crash never leaves a truncated file
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, bytes)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+impl Default for SymbolControls {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::testing::inter_exchange_opportunity;
+    use crate::opportunity_ttl::OpportunityTtlModel;
+
+    #[test]
+    fn test_disable_cancels_pool_and_persists() {
+        let path = std::env::temp_dir().join(format!("symbol_controls_{}.json", uuid::Uuid::new_v4()));
+        let pool = Arc::new(OpportunityPool::new(Arc::new(OpportunityTtlModel::default())));
+        pool.insert(inter_exchange_opportunity("inter_exchange", "BTC/USDT", 0.01));
+        pool.insert(inter_exchange_opportunity("inter_exchange", "ETHUSDT", 0.01));
+
+        let controls = SymbolControls::new().with_persistence(&path).unwrap().with_pool(pool.clone());
+        let cancelled = controls
+            .disable(SymbolKey::new(Some("OKX"), "btc-usdt"), ControlScope::Both, "bad prints", "alice")
+            .unwrap();
+        assert_eq!(cancelled, 1);
+        assert_eq!(pool.len(), 1);
+        assert!(controls.blocking_control(&inter_exchange_opportunity("inter_exchange", "BTCUSDT", 0.01)).is_some());
+        assert!(controls.detection_disabled("okx", "BTC_USDT"));
+        assert!(!controls.detection_disabled("binance", "BTCUSDT"));
+
+        controls.disable(SymbolKey::new(None, "ETHUSDT"), ControlScope::Detection, "", "alice").unwrap();
+        assert!(controls.blocking_control(&inter_exchange_opportunity("inter_exchange", "ETHUSDT", 0.01)).is_none());
+
+        let reloaded = SymbolControls::new().with_persistence(&path).unwrap();
+        assert_eq!(reloaded.list().len(), 2);
+        assert!(reloaded.enable(&SymbolKey::new(Some("okx"), "BTCUSDT"), "bob").unwrap());
+        assert!(!reloaded.enable(&SymbolKey::new(Some("okx"), "BTCUSDT"), "bob").unwrap());
+        assert_eq!(SymbolControls::new().with_persistence(&path).unwrap().list().len(), 1);
+        let _ = std::fs::remove_file(path);
+    }
+}
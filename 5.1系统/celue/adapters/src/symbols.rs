@@ -0,0 +1,202 @@
+//! Symbol metadata service
+//!
+//! Periodically pulls exchangeInfo-style instrument listings from each
+//! configured venue and loads them into the shared `SymbolRegistry`, which
+//! the execution adapter uses to translate, round and validate orders.
+
+use crate::{AdapterError, AdapterResult};
+use common::symbols::{SymbolRegistry, SymbolSpec};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolMetadataConfig {
+    pub refresh_interval: Duration,
+    pub exchanges: Vec<String>,
+}
+
+impl Default for SymbolMetadataConfig {
+    fn default() -> Self {
+        Self {
+            refresh_interval: Duration::from_secs(3600),
+            exchanges: vec!["binance".to_string(), "okx".to_string(), "bybit".to_string()],
+        }
+    }
+}
+
+/// Keeps the symbol registry in sync with the exchanges
+pub struct SymbolMetadataService {
+    config: SymbolMetadataConfig,
+    registry: Arc<SymbolRegistry>,
+    http_client: Client,
+}
+
+impl SymbolMetadataService {
+    pub fn new(config: SymbolMetadataConfig, registry: Arc<SymbolRegistry>) -> Self {
+        Self {
+            config,
+            registry,
+            http_client: Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .expect("Failed to create HTTP client"),
+        }
+    }
+
+    pub fn registry(&self) -> Arc<SymbolRegistry> {
+        self.registry.clone()
+    }
+
+    /// Refresh every configured exchange; a failed venue keeps its previous rules
+    pub async fn refresh_once(&self) -> AdapterResult<usize> {
+        let mut loaded = 0;
+        for exchange in &self.config.exchanges {
+            match self.fetch(exchange).await {
+                Ok(specs) if !specs.is_empty() => {
+                    loaded += specs.len();
+                    info!("Loaded {} symbols for {}", specs.len(), exchange);
+                    self.registry.replace_exchange(exchange, specs);
+                }
+                Ok(_) => warn!("Symbol refresh for {} returned no symbols, keeping previous rules", exchange),
+                Err(e) => warn!("Symbol refresh failed for {}: {}", exchange, e),
+            }
+        }
+        Ok(loaded)
+    }
+
+    /// Spawn the periodic refresh loop
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.config.refresh_interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.refresh_once().await {
+                    warn!("symbol refresh cycle failed: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Fetch one venue's current instrument listing
+    pub async fn fetch(&self, exchange: &str) -> AdapterResult<Vec<SymbolSpec>> {
+        let url = match exchange {
+            "binance" => "https://api.binance.com/api/v3/exchangeInfo",
+            "okx" => "https://www.okx.com/api/v5/public/instruments?instType=SPOT",
+            "bybit" => "https://api.bybit.com/v5/market/instruments-info?category=spot",
+            other => {
+                return Err(AdapterError::Configuration(format!(
+                    "symbol metadata not supported for exchange {}",
+                    other
+                )))
+            }
+        };
+        let body: Value = self
+            .http_client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| AdapterError::Connection(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| AdapterError::Generic { message: e.to_string() })?;
+        Ok(parse_exchange_info(exchange, &body))
+    }
+}
+
+fn num(value: &Value) -> f64 {
+    match value {
+        Value::String(s) => s.parse().unwrap_or(0.0),
+        other => other.as_f64().unwrap_or(0.0),
+    }
+}
+
+fn text<'a>(value: &'a Value, key: &str) -> &'a str {
+    value[key].as_str().unwrap_or_default()
+}
+
+/// Parse a venue's instrument listing into trading rules
+pub fn parse_exchange_info(exchange: &str, body: &Value) -> Vec<SymbolSpec> {
+    let empty = Vec::new();
+    match exchange {
+        "binance" => body["symbols"]
+            .as_array()
+            .unwrap_or(&empty)
+            .iter()
+            .filter(|s| text(s, "status") == "TRADING")
+            .map(|s| {
+                let filter = |kind: &str| {
+                    s["filters"]
+                        .as_array()
+                        .and_then(|f| f.iter().find(|f| text(f, "filterType") == kind))
+                        .cloned()
+                        .unwrap_or(Value::Null)
+                };
+                let min_notional = match filter("NOTIONAL") {
+                    Value::Null => num(&filter("MIN_NOTIONAL")["minNotional"]),
+                    notional => num(&notional["minNotional"]),
+                };
+                SymbolSpec::new(exchange, text(s, "symbol"), text(s, "baseAsset"), text(s, "quoteAsset")).with_rules(
+                    num(&filter("PRICE_FILTER")["tickSize"]),
+                    num(&filter("LOT_SIZE")["stepSize"]),
+                    min_notional,
+                )
+            })
+            .collect(),
+        "okx" => body["data"]
+            .as_array()
+            .unwrap_or(&empty)
+            .iter()
+            .filter(|s| text(s, "state") == "live")
+            .map(|s| {
+                SymbolSpec::new(exchange, text(s, "instId"), text(s, "baseCcy"), text(s, "quoteCcy"))
+                    .with_rules(num(&s["tickSz"]), num(&s["lotSz"]), 0.0)
+            })
+            .collect(),
+        "bybit" => body["result"]["list"]
+            .as_array()
+            .unwrap_or(&empty)
+            .iter()
+            .filter(|s| text(s, "status") == "Trading")
+            .map(|s| {
+                SymbolSpec::new(exchange, text(s, "symbol"), text(s, "baseCoin"), text(s, "quoteCoin")).with_rules(
+                    num(&s["priceFilter"]["tickSize"]),
+                    num(&s["lotSizeFilter"]["basePrecision"]),
+                    num(&s["lotSizeFilter"]["minOrderAmt"]),
+                )
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_binance_and_okx_listings() {
+        let binance = serde_json::json!({"symbols": [
+            {"symbol": "BTCUSDT", "status": "TRADING", "baseAsset": "BTC", "quoteAsset": "USDT", "filters": [
+                {"filterType": "PRICE_FILTER", "tickSize": "0.01000000"},
+                {"filterType": "LOT_SIZE", "stepSize": "0.00001000"},
+                {"filterType": "NOTIONAL", "minNotional": "5.00000000"}
+            ]},
+            {"symbol": "OLDUSDT", "status": "BREAK", "baseAsset": "OLD", "quoteAsset": "USDT", "filters": []}
+        ]});
+        let specs = parse_exchange_info("binance", &binance);
+        assert_eq!(specs.len(), 1);
+        assert_eq!(specs[0].canonical, "BTC/USDT");
+        assert_eq!((specs[0].tick_size, specs[0].lot_size, specs[0].min_notional), (0.01, 0.00001, 5.0));
+
+        let okx = serde_json::json!({"data": [
+            {"instId": "ETH-USDT", "baseCcy": "ETH", "quoteCcy": "USDT", "tickSz": "0.01", "lotSz": "0.000001", "state": "live"}
+        ]});
+        let registry = SymbolRegistry::new();
+        registry.replace_exchange("okx", parse_exchange_info("okx", &okx));
+        assert_eq!(registry.to_canonical("okx", "ETH-USDT").unwrap().as_str(), "ETH/USDT");
+    }
+}
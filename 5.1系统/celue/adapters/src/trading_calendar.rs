@@ -0,0 +1,275 @@
+//! Trading calendar and session guard
+//!
+//! Loads per-exchange maintenance, settlement and closed-session windows
+//! and answers whether an exchange/symbol is tradeable at a given time.
+//! The strategy engine and the executor consult it before claiming or
+//! sending orders. A warning is broadcast once per window occurrence
+//! shortly before it starts.
+
+use common::ArbitrageOpportunity;
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Utc, Weekday};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::AdapterResult;
+
+/// Why trading is paused during a window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowKind {
+    Maintenance,
+    /// Futures funding/expiry settlement
+    Settlement,
+    /// Venue closed (e.g. regional exchange outside trading hours)
+    Closed,
+}
+
+/// When a window recurs; times are UTC
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Schedule {
+    Once { start: DateTime<Utc>, end: DateTime<Utc> },
+    Daily { start: NaiveTime, duration_secs: i64 },
+    Weekly { weekday: Weekday, start: NaiveTime, duration_secs: i64 },
+}
+
+impl Schedule {
+    /// Occurrences overlapping the day before and after `now`, in order
+    fn occurrences(&self, now: DateTime<Utc>) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+        match self {
+            Schedule::Once { start, end } => vec![(*start, *end)],
+            Schedule::Daily { start, duration_secs } => (-1..=1)
+                .map(|offset| {
+                    let begin = (now.date_naive() + Duration::days(offset)).and_time(*start).and_utc();
+                    (begin, begin + Duration::seconds(*duration_secs))
+                })
+                .collect(),
+            Schedule::Weekly { weekday, start, duration_secs } => {
+                let days_back = (now.weekday().num_days_from_monday() as i64
+                    - weekday.num_days_from_monday() as i64)
+                    .rem_euclid(7);
+                let this_week = now.date_naive() - Duration::days(days_back);
+                [-7, 0, 7]
+                    .into_iter()
+                    .map(|offset| {
+                        let begin = (this_week + Duration::days(offset)).and_time(*start).and_utc();
+                        (begin, begin + Duration::seconds(*duration_secs))
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// The occurrence containing `now`
+    pub fn active_at(&self, now: DateTime<Utc>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        self.occurrences(now).into_iter().find(|(start, end)| *start <= now && now < *end)
+    }
+
+    /// The next occurrence starting after `now`
+    pub fn next_start(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.occurrences(now).into_iter().map(|(start, _)| start).find(|start| *start > now)
+    }
+}
+
+/// A window during which an exchange (or some of its symbols) is not tradeable
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionWindow {
+    pub exchange: String,
+    /// Affected symbols; empty means the whole exchange. Separators and
+    /// case are ignored
+    #[serde(default)]
+    pub symbols: Vec<String>,
+    pub kind: WindowKind,
+    pub schedule: Schedule,
+    #[serde(default)]
+    pub reason: String,
+}
+
+impl SessionWindow {
+    fn covers(&self, exchange: &str, symbol: &str) -> bool {
+        self.exchange.eq_ignore_ascii_case(exchange)
+            && (self.symbols.is_empty() || self.symbols.iter().any(|s| normalize_symbol(s) == normalize_symbol(symbol)))
+    }
+}
+
+fn normalize_symbol(symbol: &str) -> String {
+    symbol
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradingCalendarConfig {
+    /// Warn this long before a window starts
+    pub warning_lead_secs: i64,
+    /// Treat the window as started this long before its scheduled start,
+    /// so no order is in flight when the venue goes down
+    pub pre_window_buffer_secs: i64,
+}
+
+impl Default for TradingCalendarConfig {
+    fn default() -> Self {
+        Self {
+            warning_lead_secs: 900,
+            pre_window_buffer_secs: 30,
+        }
+    }
+}
+
+/// Advance notice of an upcoming window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowWarning {
+    pub window: SessionWindow,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+}
+
+/// Per-exchange maintenance/settlement schedule
+pub struct TradingCalendar {
+    config: TradingCalendarConfig,
+    windows: RwLock<Vec<SessionWindow>>,
+    /// (window index, occurrence start) already warned about
+    warned: Mutex<HashSet<(usize, i64)>>,
+    warnings: broadcast::Sender<WindowWarning>,
+}
+
+impl TradingCalendar {
+    pub fn new(config: TradingCalendarConfig) -> Self {
+        let (warnings, _) = broadcast::channel(64);
+        Self {
+            config,
+            windows: RwLock::new(Vec::new()),
+            warned: Mutex::new(HashSet::new()),
+            warnings,
+        }
+    }
+
+    /// Replace the schedule
+    pub fn set_windows(&self, windows: Vec<SessionWindow>) {
+        *self.windows.write() = windows;
+        self.warned.lock().clear();
+    }
+
+    /// Load the schedule from a JSON file
+    pub fn load_file(&self, path: impl AsRef<Path>) -> AdapterResult<usize> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| crate::AdapterError::Configuration(e.to_string()))?;
+        let windows: Vec<SessionWindow> = serde_json::from_str(&content)?;
+        let count = windows.len();
+        self.set_windows(windows);
+        Ok(count)
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<WindowWarning> {
+        self.warnings.subscribe()
+    }
+
+    /// The window blocking `exchange`/`symbol` at `now`, including the pre-window buffer
+    pub fn blocking_window(&self, exchange: &str, symbol: &str, now: DateTime<Utc>) -> Option<SessionWindow> {
+        let probe = now + Duration::seconds(self.config.pre_window_buffer_secs);
+        self.windows
+            .read()
+            .iter()
+            .find(|w| w.covers(exchange, symbol) && (w.schedule.active_at(now).is_some() || w.schedule.active_at(probe).is_some()))
+            .cloned()
+    }
+
+    pub fn is_tradeable(&self, exchange: &str, symbol: &str, now: DateTime<Utc>) -> bool {
+        self.blocking_window(exchange, symbol, now).is_none()
+    }
+
+    /// The first window blocking any leg of the opportunity
+    pub fn blocking_window_for(&self, opportunity: &ArbitrageOpportunity, now: DateTime<Utc>) -> Option<SessionWindow> {
+        opportunity
+            .legs
+            .iter()
+            .find_map(|l| self.blocking_window(l.exchange.as_str(), l.symbol.as_str(), now))
+    }
+
+    /// Broadcast warnings for windows starting within the lead time;
+    /// each occurrence is warned about once
+    pub fn check_warnings(&self, now: DateTime<Utc>) -> Vec<WindowWarning> {
+        let horizon = now + Duration::seconds(self.config.warning_lead_secs);
+        let windows = self.windows.read();
+        let mut warned = self.warned.lock();
+        let mut issued = Vec::new();
+        for (index, window) in windows.iter().enumerate() {
+            let Some(starts_at) = window.schedule.next_start(now).filter(|s| *s <= horizon) else { continue };
+            if !warned.insert((index, starts_at.timestamp())) {
+                continue;
+            }
+            let ends_at = window.schedule.active_at(starts_at).map(|(_, end)| end).unwrap_or(starts_at);
+            tracing::warn!(
+                "{:?} window on {} {:?} starts at {} ({})",
+                window.kind, window.exchange, window.symbols, starts_at, window.reason
+            );
+            let warning = WindowWarning { window: window.clone(), starts_at, ends_at };
+            let _ = self.warnings.send(warning.clone());
+            issued.push(warning);
+        }
+        // Forget occurrences that have passed
+        warned.retain(|(_, start)| *start > now.timestamp() - 86_400 * 7);
+        issued
+    }
+
+    /// Spawn the warning loop
+    pub fn spawn(self: Arc<Self>, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.check_warnings(Utc::now());
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_daily_settlement_blocks_and_warns() {
+        let calendar = TradingCalendar::new(TradingCalendarConfig { warning_lead_secs: 600, pre_window_buffer_secs: 30 });
+        calendar.set_windows(vec![
+            SessionWindow {
+                exchange: "okx".into(),
+                symbols: vec!["BTC-USDT-SWAP".into()],
+                kind: WindowKind::Settlement,
+                schedule: Schedule::Daily { start: NaiveTime::from_hms_opt(8, 0, 0).unwrap(), duration_secs: 60 },
+                reason: "funding settlement".into(),
+            },
+            SessionWindow {
+                exchange: "binance".into(),
+                symbols: vec![],
+                kind: WindowKind::Maintenance,
+                schedule: Schedule::Weekly {
+                    weekday: Weekday::Wed,
+                    start: NaiveTime::from_hms_opt(2, 0, 0).unwrap(),
+                    duration_secs: 3600,
+                },
+                reason: "weekly maintenance".into(),
+            },
+        ]);
+
+        let at = |h, m, s| Utc.with_ymd_and_hms(2024, 1, 3, h, m, s).unwrap(); // a Wednesday
+        assert!(!calendar.is_tradeable("okx", "BTC/USDT/SWAP", at(8, 0, 30)));
+        assert!(!calendar.is_tradeable("okx", "BTC-USDT-SWAP", at(7, 59, 45)), "pre-window buffer");
+        assert!(calendar.is_tradeable("okx", "ETH-USDT-SWAP", at(8, 0, 30)));
+        assert!(calendar.is_tradeable("okx", "BTC-USDT-SWAP", at(8, 1, 0)));
+        assert!(!calendar.is_tradeable("binance", "ETHUSDT", at(2, 30, 0)));
+        assert!(calendar.is_tradeable("binance", "ETHUSDT", at(3, 0, 0)));
+
+        let warnings = calendar.check_warnings(at(7, 52, 0));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].starts_at, at(8, 0, 0));
+        assert!(calendar.check_warnings(at(7, 55, 0)).is_empty(), "warned once per occurrence");
+    }
+}
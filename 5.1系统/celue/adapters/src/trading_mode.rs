@@ -0,0 +1,258 @@
+//! System-wide trading mode
+//!
+//! One switch decides whether anything touches real money:
+//!
+//! - `DryRun`: order placements are routed to the shadow matching engine
+//!   and tagged with `DRY_RUN_ORDER_PREFIX`; transfers are never scheduled.
+//! - `Shadow`: orders go only to exchanges whose credentials are in
+//!   sandbox mode; transfers are never scheduled.
+//! - `Live`: real orders and transfers.
+//!
+//! Stepping down takes effect at once. Going live takes two calls:
+//! `request_live` returns a confirmation token, and `confirm_live` must
+//! echo it before the confirmation window closes.
+
+use crate::execution::ExchangeCredentials;
+use crate::order_matching::{ShadowMatchingEngine, ShadowOrderType};
+use crate::{AdapterError, AdapterResult};
+use chrono::{DateTime, Utc};
+use common::{ArbitrageOpportunity, ExecutionResult, SharedClock, SliceReport};
+use metrics::counter;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Prefix of order ids filled by the shadow engine
+pub const DRY_RUN_ORDER_PREFIX: &str = "dryrun_";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TradingMode {
+    /// Simulated fills only
+    #[default]
+    DryRun,
+    /// Exchange sandboxes only
+    Shadow,
+    /// Real orders and transfers
+    Live,
+}
+
+impl TradingMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::DryRun => "dry_run",
+            Self::Shadow => "shadow",
+            Self::Live => "live",
+        }
+    }
+}
+
+/// First step of a switch to live, waiting for confirmation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingLiveSwitch {
+    pub token: String,
+    pub requested_by: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Holder of the current mode, shared by execution, funds and the engine
+pub struct TradingModeController {
+    mode: RwLock<TradingMode>,
+    pending: Mutex<Option<PendingLiveSwitch>>,
+    confirm_window: chrono::Duration,
+    shadow: Arc<ShadowMatchingEngine>,
+    clock: SharedClock,
+}
+
+impl TradingModeController {
+    pub fn new(mode: TradingMode) -> Self {
+        info!("Trading mode: {}", mode.as_str());
+        Self {
+            mode: RwLock::new(mode),
+            pending: Mutex::new(None),
+            confirm_window: chrono::Duration::seconds(60),
+            shadow: Arc::new(ShadowMatchingEngine::new()),
+            clock: common::clock::system_clock(),
+        }
+    }
+
+    /// Time allowed between `request_live` and `confirm_live`
+    pub fn with_confirm_window(mut self, window: chrono::Duration) -> Self {
+        self.confirm_window = window;
+        self
+    }
+
+    /// Time source for the live confirmation window
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Route dry-run orders to an existing shadow engine
+    pub fn with_shadow_engine(mut self, shadow: Arc<ShadowMatchingEngine>) -> Self {
+        self.shadow = shadow;
+        self
+    }
+
+    pub fn mode(&self) -> TradingMode {
+        *self.mode.read()
+    }
+
+    pub fn is_live(&self) -> bool {
+        self.mode() == TradingMode::Live
+    }
+
+    pub fn shadow_engine(&self) -> Arc<ShadowMatchingEngine> {
+        self.shadow.clone()
+    }
+
+    /// Step down to dry-run or shadow; going live needs `request_live`
+    pub fn set_mode(&self, mode: TradingMode) -> AdapterResult<()> {
+        if mode == TradingMode::Live {
+            return Err(AdapterError::Validation {
+                message: "switching to live requires request_live and confirm_live".to_string(),
+            });
+        }
+        self.pending.lock().take();
+        let previous = std::mem::replace(&mut *self.mode.write(), mode);
+        if previous != mode {
+            warn!("Trading mode {} -> {}", previous.as_str(), mode.as_str());
+            counter!("trading_mode_switches_total", "to" => mode.as_str()).increment(1);
+        }
+        Ok(())
+    }
+
+    /// First step of going live: returns the token `confirm_live` expects
+    pub fn request_live(&self, requested_by: &str) -> AdapterResult<PendingLiveSwitch> {
+        if self.is_live() {
+            return Err(AdapterError::Validation { message: "already live".to_string() });
+        }
+        let pending = PendingLiveSwitch {
+            token: uuid::Uuid::new_v4().to_string(),
+            requested_by: requested_by.to_string(),
+            expires_at: self.clock.now() + self.confirm_window,
+        };
+        warn!("{} requested switch to live, confirm before {}", requested_by, pending.expires_at);
+        *self.pending.lock() = Some(pending.clone());
+        Ok(pending)
+    }
+
+    /// Second step of going live
+    pub fn confirm_live(&self, token: &str, confirmed_by: &str) -> AdapterResult<()> {
+        let mut pending = self.pending.lock();
+        let Some(request) = pending.as_ref() else {
+            return Err(AdapterError::Validation { message: "no pending switch to live".to_string() });
+        };
+        if self.clock.now() > request.expires_at {
+            pending.take();
+            return Err(AdapterError::Validation { message: "switch to live confirmation expired".to_string() });
+        }
+        if request.token != token {
+            return Err(AdapterError::Validation { message: "invalid confirmation token".to_string() });
+        }
+        let request = pending.take().expect("checked above");
+        let previous = std::mem::replace(&mut *self.mode.write(), TradingMode::Live);
+        warn!(
+            "Trading mode {} -> live (requested by {}, confirmed by {})",
+            previous.as_str(), request.requested_by, confirmed_by
+        );
+        counter!("trading_mode_switches_total", "to" => TradingMode::Live.as_str()).increment(1);
+        Ok(())
+    }
+
+    pub fn pending_live_switch(&self) -> Option<PendingLiveSwitch> {
+        self.pending.lock().clone()
+    }
+
+    /// Whether an order may be sent to an exchange with these credentials
+    pub fn check_exchange(&self, exchange: &str, credentials: Option<&ExchangeCredentials>) -> AdapterResult<()> {
+        match self.mode() {
+            TradingMode::Live => Ok(()),
+            TradingMode::Shadow if credentials.is_some_and(|c| c.sandbox) => Ok(()),
+            mode => Err(AdapterError::Validation {
+                message: format!("orders to {} refused in {} mode", exchange, mode.as_str()),
+            }),
+        }
+    }
+
+    /// Whether funds may be moved between exchanges
+    pub fn authorize_transfer(&self) -> AdapterResult<()> {
+        match self.mode() {
+            TradingMode::Live => Ok(()),
+            mode => Err(AdapterError::Validation {
+                message: format!("transfers are disabled in {} mode", mode.as_str()),
+            }),
+        }
+    }
+
+    /// Fill every leg on the shadow engine at its quoted price
+    pub fn route_to_shadow(&self, opportunity: &ArbitrageOpportunity) -> AdapterResult<ExecutionResult> {
+        let mut order_ids = Vec::with_capacity(opportunity.legs.len());
+        let mut slices = Vec::with_capacity(opportunity.legs.len());
+        for (i, leg) in opportunity.legs.iter().enumerate() {
+            // Venues are simulated independently
+            let book = format!("{}:{}", leg.exchange.as_str(), leg.symbol.as_str());
+            let price = leg.price.to_f64();
+            let (id, mut fills) = self.shadow.submit(
+                &book,
+                leg.side,
+                leg.quantity.to_f64(),
+                ShadowOrderType::Limit { price },
+            )?;
+            fills.extend(self.shadow.on_price(&book, price));
+            let fill = fills.into_iter().find(|f| f.order_id == id);
+            let order_id = format!("{}{}", DRY_RUN_ORDER_PREFIX, id);
+            slices.push(SliceReport {
+                leg_index: i,
+                slice_index: 0,
+                order_id: order_id.clone(),
+                quantity: fill.as_ref().map(|f| f.quantity).unwrap_or_default(),
+                price: fill.as_ref().map(|f| f.price).unwrap_or(price),
+                slippage_bps: 0.0,
+            });
+            order_ids.push(order_id);
+        }
+        counter!("dry_run_orders_total", "strategy" => opportunity.strategy_name.clone()).increment(order_ids.len() as u64);
+        info!("[DRY_RUN] {} routed {} legs to shadow engine", opportunity.id, order_ids.len());
+        let mut result = ExecutionResult::accepted(opportunity.id.to_string(), order_ids, None);
+        result.details = "[DRY_RUN] filled on shadow engine".to_string();
+        result.slices = slices;
+        Ok(result)
+    }
+}
+
+impl Default for TradingModeController {
+    fn default() -> Self {
+        Self::new(TradingMode::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_live_requires_confirmation() {
+        let controller = TradingModeController::new(TradingMode::DryRun);
+        assert!(controller.set_mode(TradingMode::Live).is_err());
+        assert!(controller.authorize_transfer().is_err());
+
+        let pending = controller.request_live("alice").unwrap();
+        assert!(controller.confirm_live("wrong", "bob").is_err());
+        assert_eq!(controller.mode(), TradingMode::DryRun);
+        controller.confirm_live(&pending.token, "bob").unwrap();
+        assert!(controller.is_live());
+        assert!(controller.authorize_transfer().is_ok());
+
+        controller.set_mode(TradingMode::Shadow).unwrap();
+        let sandbox = ExchangeCredentials {
+            api_key: String::new(),
+            api_secret: String::new(),
+            passphrase: None,
+            sandbox: true,
+        };
+        assert!(controller.check_exchange("binance", Some(&sandbox)).is_ok());
+        assert!(controller.check_exchange("okx", None).is_err());
+    }
+}
@@ -0,0 +1,333 @@
+//! Execution venues: order connector plus settlement model
+//!
+//! An `ExecutionVenue` pairs how orders reach a venue (`VenueConnector`:
+//! quote and place) with how trades settle (`SettlementModel`): custodial
+//! exchange balances charging maker/taker fees, or on-chain swaps paying
+//! gas and price impact. `VenueRegistry` maps exchange names to venues and
+//! `ExecutionAdapter::with_venues` routes legs on registered exchanges
+//! through their connector; every other exchange keeps the existing order
+//! path. The reference on-chain venue lives in `dex` (feature `dex`).
+
+use crate::slicing::SliceExecutor;
+use crate::{AdapterError, AdapterResult};
+use common::{ArbitrageLeg, Side};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VenueKind {
+    Centralized,
+    OnChain,
+}
+
+/// Executable price for a given size
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VenueQuote {
+    /// Average execution price for `quantity`
+    pub price: f64,
+    pub quantity: f64,
+    /// Distance of `price` from the venue mid, always positive (bps)
+    pub price_impact_bps: f64,
+    /// Network gas price at quote time, on-chain venues only
+    pub gas_price_gwei: Option<f64>,
+}
+
+/// Expected all-in cost of settling a trade (USD)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SettlementCost {
+    pub fee_usd: f64,
+    pub gas_usd: f64,
+    /// Price impact against the leg's reference price
+    pub slippage_usd: f64,
+    /// Time until the trade is final
+    pub finality_ms: u64,
+}
+
+impl SettlementCost {
+    pub fn total_usd(&self) -> f64 {
+        self.fee_usd + self.gas_usd + self.slippage_usd
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VenueFill {
+    /// Exchange order id or transaction hash
+    pub order_id: String,
+    pub price: f64,
+    pub quantity: f64,
+}
+
+/// Order entry for one venue
+#[async_trait::async_trait]
+pub trait VenueConnector: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn quote(&self, leg: &ArbitrageLeg, quantity: f64) -> AdapterResult<VenueQuote>;
+
+    /// Place an order for `quantity` against a fresh `quote`
+    async fn place(&self, leg: &ArbitrageLeg, quantity: f64, quote: &VenueQuote) -> AdapterResult<VenueFill>;
+
+    /// Cancel every open order placed through this connector; returns how
+    /// many were cancelled. Venues whose orders never rest (swaps) have none
+    async fn cancel_all(&self) -> AdapterResult<usize> {
+        Ok(0)
+    }
+}
+
+/// How trades on a venue settle and what that costs
+pub trait SettlementModel: Send + Sync {
+    fn kind(&self) -> VenueKind;
+
+    fn cost(&self, leg: &ArbitrageLeg, quote: &VenueQuote) -> SettlementCost;
+}
+
+/// Price impact of a quote against the leg's reference price, in USD
+fn slippage_usd(leg: &ArbitrageLeg, quote: &VenueQuote) -> f64 {
+    let reference = leg.price.to_f64();
+    let worse = match leg.side {
+        Side::Buy => quote.price - reference,
+        Side::Sell => reference - quote.price,
+    };
+    worse.max(0.0) * quote.quantity
+}
+
+/// Exchange-held balances; trades are final on fill
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustodialSettlement {
+    pub taker_fee_bps: f64,
+}
+
+impl Default for CustodialSettlement {
+    fn default() -> Self {
+        Self { taker_fee_bps: 10.0 }
+    }
+}
+
+impl SettlementModel for CustodialSettlement {
+    fn kind(&self) -> VenueKind {
+        VenueKind::Centralized
+    }
+
+    fn cost(&self, leg: &ArbitrageLeg, quote: &VenueQuote) -> SettlementCost {
+        SettlementCost {
+            fee_usd: quote.price * quote.quantity * self.taker_fee_bps / 10_000.0,
+            gas_usd: 0.0,
+            slippage_usd: slippage_usd(leg, quote),
+            finality_ms: 0,
+        }
+    }
+}
+
+/// Swaps settled on a blockchain: gas per trade, impact priced into the quote
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnChainSettlement {
+    pub chain: String,
+    /// Gas consumed by one swap
+    pub gas_units: u64,
+    /// USD price of the chain's native gas token
+    pub native_price_usd: f64,
+    pub confirmations: u32,
+    pub block_time_ms: u64,
+}
+
+impl SettlementModel for OnChainSettlement {
+    fn kind(&self) -> VenueKind {
+        VenueKind::OnChain
+    }
+
+    fn cost(&self, leg: &ArbitrageLeg, quote: &VenueQuote) -> SettlementCost {
+        let gas_gwei = quote.gas_price_gwei.unwrap_or_default();
+        SettlementCost {
+            // Pool fees are already in the quoted price
+            fee_usd: 0.0,
+            gas_usd: self.gas_units as f64 * gas_gwei * 1e-9 * self.native_price_usd,
+            slippage_usd: slippage_usd(leg, quote),
+            finality_ms: self.confirmations as u64 * self.block_time_ms,
+        }
+    }
+}
+
+/// A connector and its settlement model
+pub struct ExecutionVenue {
+    connector: Arc<dyn VenueConnector>,
+    settlement: Arc<dyn SettlementModel>,
+    /// Orders whose quote impact exceeds this are refused (0 disables)
+    max_price_impact_bps: f64,
+}
+
+impl ExecutionVenue {
+    pub fn new(connector: Arc<dyn VenueConnector>, settlement: Arc<dyn SettlementModel>) -> Self {
+        Self { connector, settlement, max_price_impact_bps: 0.0 }
+    }
+
+    pub fn with_max_price_impact_bps(mut self, bps: f64) -> Self {
+        self.max_price_impact_bps = bps;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        self.connector.name()
+    }
+
+    pub fn kind(&self) -> VenueKind {
+        self.settlement.kind()
+    }
+
+    /// Quote `quantity` and price its settlement
+    pub async fn estimate(&self, leg: &ArbitrageLeg, quantity: f64) -> AdapterResult<(VenueQuote, SettlementCost)> {
+        let quote = self.connector.quote(leg, quantity).await?;
+        let cost = self.settlement.cost(leg, &quote);
+        Ok((quote, cost))
+    }
+
+    pub async fn execute(&self, leg: &ArbitrageLeg, quantity: f64) -> AdapterResult<VenueFill> {
+        let (quote, cost) = self.estimate(leg, quantity).await?;
+        if self.max_price_impact_bps > 0.0 && quote.price_impact_bps > self.max_price_impact_bps {
+            return Err(AdapterError::Validation {
+                message: format!(
+                    "{}: price impact {:.1}bps exceeds {:.1}bps",
+                    self.name(),
+                    quote.price_impact_bps,
+                    self.max_price_impact_bps
+                ),
+            });
+        }
+        metrics::histogram!("venue_settlement_cost_usd", "venue" => self.name().to_string()).record(cost.total_usd());
+        self.connector.place(leg, quantity, &quote).await
+    }
+
+    pub async fn cancel_all(&self) -> AdapterResult<usize> {
+        self.connector.cancel_all().await
+    }
+}
+
+/// Venues by exchange name
+#[derive(Default)]
+pub struct VenueRegistry {
+    venues: RwLock<HashMap<String, Arc<ExecutionVenue>>>,
+}
+
+impl VenueRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&self, exchange: &str, venue: ExecutionVenue) {
+        self.venues.write().insert(exchange.to_lowercase(), Arc::new(venue));
+    }
+
+    pub fn get(&self, exchange: &str) -> Option<Arc<ExecutionVenue>> {
+        self.venues.read().get(&exchange.to_lowercase()).cloned()
+    }
+
+    pub fn exchanges(&self) -> Vec<String> {
+        let mut exchanges: Vec<String> = self.venues.read().keys().cloned().collect();
+        exchanges.sort();
+        exchanges
+    }
+}
+
+/// Routes child orders to registered venues, everything else to `fallback`
+pub struct VenueSliceExecutor<E> {
+    venues: Arc<VenueRegistry>,
+    fallback: E,
+}
+
+impl<E: SliceExecutor> VenueSliceExecutor<E> {
+    pub fn new(venues: Arc<VenueRegistry>, fallback: E) -> Self {
+        Self { venues, fallback }
+    }
+}
+
+#[async_trait::async_trait]
+impl<E: SliceExecutor> SliceExecutor for VenueSliceExecutor<E> {
+    async fn execute_slice(&self, leg: &ArbitrageLeg, quantity: f64) -> AdapterResult<(String, f64)> {
+        match self.venues.get(leg.exchange.as_str()) {
+            Some(venue) => {
+                let fill = venue.execute(leg, quantity).await?;
+                Ok((fill.order_id, fill.price))
+            }
+            None => self.fallback.execute_slice(leg, quantity).await,
+        }
+    }
+
+    fn batch_limit(&self, leg: &ArbitrageLeg) -> Option<usize> {
+        match self.venues.get(leg.exchange.as_str()) {
+            Some(_) => None,
+            None => self.fallback.batch_limit(leg),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::testing::leg;
+
+    /// Fills at a fixed premium over the leg price
+    struct FixedSpreadConnector;
+
+    #[async_trait::async_trait]
+    impl VenueConnector for FixedSpreadConnector {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        async fn quote(&self, leg: &ArbitrageLeg, quantity: f64) -> AdapterResult<VenueQuote> {
+            Ok(VenueQuote {
+                price: leg.price.to_f64() * (1.0 + quantity * 0.001),
+                quantity,
+                price_impact_bps: quantity * 10.0,
+                gas_price_gwei: Some(20.0),
+            })
+        }
+
+        async fn place(&self, _leg: &ArbitrageLeg, quantity: f64, quote: &VenueQuote) -> AdapterResult<VenueFill> {
+            Ok(VenueFill { order_id: "0xabc".into(), price: quote.price, quantity })
+        }
+    }
+
+    struct Unreachable;
+
+    #[async_trait::async_trait]
+    impl SliceExecutor for Unreachable {
+        async fn execute_slice(&self, _leg: &ArbitrageLeg, _quantity: f64) -> AdapterResult<(String, f64)> {
+            Ok(("cex".into(), 0.0))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_settlement_costs_and_routing() {
+        let eth = |exchange: &str| leg(exchange, "ETHUSDT", Side::Buy, 2_000.0, 1.0);
+        let onchain = OnChainSettlement {
+            chain: "eth".into(),
+            gas_units: 150_000,
+            native_price_usd: 2_000.0,
+            confirmations: 12,
+            block_time_ms: 12_000,
+        };
+        let venue = ExecutionVenue::new(Arc::new(FixedSpreadConnector), Arc::new(onchain)).with_max_price_impact_bps(15.0);
+        let (_, cost) = venue.estimate(&eth("uniswap"), 1.0).await.unwrap();
+        assert!((cost.gas_usd - 6.0).abs() < 1e-9);
+        assert!((cost.slippage_usd - 2.0).abs() < 1e-6);
+        assert_eq!(cost.finality_ms, 144_000);
+
+        let custodial = CustodialSettlement::default().cost(&eth("binance"), &VenueQuote {
+            price: 2_000.0,
+            quantity: 1.0,
+            price_impact_bps: 0.0,
+            gas_price_gwei: None,
+        });
+        assert!((custodial.total_usd() - 2.0).abs() < 1e-9);
+
+        let venues = Arc::new(VenueRegistry::new());
+        venues.register("Uniswap", venue);
+        let executor = VenueSliceExecutor::new(venues, Unreachable);
+        assert_eq!(executor.execute_slice(&eth("uniswap"), 1.0).await.unwrap().0, "0xabc");
+        assert_eq!(executor.execute_slice(&eth("binance"), 1.0).await.unwrap().0, "cex");
+        assert!(executor.execute_slice(&eth("uniswap"), 2.0).await.is_err());
+    }
+}
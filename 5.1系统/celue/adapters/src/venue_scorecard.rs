@@ -0,0 +1,275 @@
+//! Long-run venue scorecard
+//!
+//! Tracks per-exchange feed uptime, order reject rate, realized slippage
+//! versus quote, fee competitiveness and withdrawal reliability, and folds
+//! them into a single 0-1 score. The scorecard is persisted as JSON so the
+//! history survives restarts, answers queries over NATS, feeds routing
+//! tie-breaks through exchange weights and scales rebalancing targets.
+
+use crate::nats::{MessageHandler, NatsMessage};
+use crate::AdapterResult;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Raw counters for one venue
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VenueStats {
+    pub feed_up_ms: u64,
+    pub feed_down_ms: u64,
+    pub orders_submitted: u64,
+    pub orders_rejected: u64,
+    pub slippage_samples: u64,
+    /// Sum of realized-vs-quote slippage; positive is worse (bps)
+    pub slippage_sum_bps: f64,
+    /// Current taker fee (bps)
+    pub taker_fee_bps: Option<f64>,
+    pub withdrawals_requested: u64,
+    pub withdrawals_completed: u64,
+}
+
+impl VenueStats {
+    pub fn uptime(&self) -> f64 {
+        let total = self.feed_up_ms + self.feed_down_ms;
+        if total == 0 { 1.0 } else { self.feed_up_ms as f64 / total as f64 }
+    }
+
+    pub fn reject_rate(&self) -> f64 {
+        if self.orders_submitted == 0 { 0.0 } else { self.orders_rejected as f64 / self.orders_submitted as f64 }
+    }
+
+    pub fn avg_slippage_bps(&self) -> f64 {
+        if self.slippage_samples == 0 { 0.0 } else { self.slippage_sum_bps / self.slippage_samples as f64 }
+    }
+
+    pub fn withdrawal_success_rate(&self) -> f64 {
+        if self.withdrawals_requested == 0 {
+            1.0
+        } else {
+            self.withdrawals_completed as f64 / self.withdrawals_requested as f64
+        }
+    }
+}
+
+/// Weights of each component in the composite score
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScorecardWeights {
+    pub uptime: f64,
+    pub reject_rate: f64,
+    pub slippage: f64,
+    pub fees: f64,
+    pub withdrawals: f64,
+    /// Slippage at which the slippage component reaches zero (bps)
+    pub slippage_floor_bps: f64,
+}
+
+impl Default for ScorecardWeights {
+    fn default() -> Self {
+        Self {
+            uptime: 0.3,
+            reject_rate: 0.2,
+            slippage: 0.2,
+            fees: 0.15,
+            withdrawals: 0.15,
+            slippage_floor_bps: 20.0,
+        }
+    }
+}
+
+/// Scored view of one venue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VenueScore {
+    pub exchange: String,
+    pub uptime: f64,
+    pub reject_rate: f64,
+    pub avg_slippage_bps: f64,
+    pub taker_fee_bps: Option<f64>,
+    pub withdrawal_success_rate: f64,
+    /// Composite score, 0 (worst) to 1 (best)
+    pub score: f64,
+}
+
+/// Persistent venue scorecard
+pub struct VenueScorecard {
+    weights: ScorecardWeights,
+    venues: RwLock<HashMap<String, VenueStats>>,
+}
+
+impl VenueScorecard {
+    pub fn new(weights: ScorecardWeights) -> Self {
+        Self {
+            weights,
+            venues: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Load persisted counters; a missing file starts an empty scorecard
+    pub fn load(weights: ScorecardWeights, path: impl AsRef<Path>) -> AdapterResult<Self> {
+        let scorecard = Self::new(weights);
+        if path.as_ref().exists() {
+            let content = std::fs::read_to_string(path)?;
+            *scorecard.venues.write() = serde_json::from_str(&content)?;
+        }
+        Ok(scorecard)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> AdapterResult<()> {
+        let content = serde_json::to_string_pretty(&*self.venues.read())?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    pub fn record_feed(&self, exchange: &str, up: bool, duration_ms: u64) {
+        let mut venues = self.venues.write();
+        let stats = venues.entry(exchange.to_string()).or_default();
+        if up {
+            stats.feed_up_ms += duration_ms;
+        } else {
+            stats.feed_down_ms += duration_ms;
+        }
+    }
+
+    pub fn record_order(&self, exchange: &str, rejected: bool) {
+        let mut venues = self.venues.write();
+        let stats = venues.entry(exchange.to_string()).or_default();
+        stats.orders_submitted += 1;
+        if rejected {
+            stats.orders_rejected += 1;
+        }
+    }
+
+    /// Record realized slippage versus the quoted price (bps, positive is worse)
+    pub fn record_slippage(&self, exchange: &str, slippage_bps: f64) {
+        let mut venues = self.venues.write();
+        let stats = venues.entry(exchange.to_string()).or_default();
+        stats.slippage_samples += 1;
+        stats.slippage_sum_bps += slippage_bps;
+    }
+
+    pub fn set_taker_fee(&self, exchange: &str, taker_fee_bps: f64) {
+        self.venues.write().entry(exchange.to_string()).or_default().taker_fee_bps = Some(taker_fee_bps);
+    }
+
+    pub fn record_withdrawal(&self, exchange: &str, completed: bool) {
+        let mut venues = self.venues.write();
+        let stats = venues.entry(exchange.to_string()).or_default();
+        stats.withdrawals_requested += 1;
+        if completed {
+            stats.withdrawals_completed += 1;
+        }
+    }
+
+    /// Scores for every known venue, best first
+    pub fn scores(&self) -> Vec<VenueScore> {
+        let venues = self.venues.read();
+        let fees: Vec<f64> = venues.values().filter_map(|s| s.taker_fee_bps).collect();
+        let min_fee = fees.iter().cloned().fold(f64::INFINITY, f64::min);
+        let max_fee = fees.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+
+        let w = &self.weights;
+        let total_weight = (w.uptime + w.reject_rate + w.slippage + w.fees + w.withdrawals).max(f64::EPSILON);
+        let mut scores: Vec<VenueScore> = venues
+            .iter()
+            .map(|(exchange, stats)| {
+                // Cheapest venue scores 1, most expensive 0; unknown fees are neutral
+                let fee_component = match stats.taker_fee_bps {
+                    Some(fee) if max_fee > min_fee => (max_fee - fee) / (max_fee - min_fee),
+                    Some(_) => 1.0,
+                    None => 0.5,
+                };
+                let slippage_component =
+                    1.0 - (stats.avg_slippage_bps().max(0.0) / w.slippage_floor_bps.max(f64::EPSILON)).min(1.0);
+                let score = (w.uptime * stats.uptime()
+                    + w.reject_rate * (1.0 - stats.reject_rate())
+                    + w.slippage * slippage_component
+                    + w.fees * fee_component
+                    + w.withdrawals * stats.withdrawal_success_rate())
+                    / total_weight;
+                VenueScore {
+                    exchange: exchange.clone(),
+                    uptime: stats.uptime(),
+                    reject_rate: stats.reject_rate(),
+                    avg_slippage_bps: stats.avg_slippage_bps(),
+                    taker_fee_bps: stats.taker_fee_bps,
+                    withdrawal_success_rate: stats.withdrawal_success_rate(),
+                    score,
+                }
+            })
+            .collect();
+        scores.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        scores
+    }
+
+    pub fn score(&self, exchange: &str) -> Option<f64> {
+        self.scores().into_iter().find(|s| s.exchange == exchange).map(|s| s.score)
+    }
+
+    /// Score relative to the average venue (1.0 = average); used to tilt
+    /// routing weights and rebalancing targets
+    pub fn relative_weights(&self) -> HashMap<String, f64> {
+        let scores = self.scores();
+        let mean = scores.iter().map(|s| s.score).sum::<f64>() / scores.len().max(1) as f64;
+        scores
+            .into_iter()
+            .map(|s| {
+                let weight = if mean > 0.0 { s.score / mean } else { 1.0 };
+                (s.exchange, weight)
+            })
+            .collect()
+    }
+}
+
+impl Default for VenueScorecard {
+    fn default() -> Self {
+        Self::new(ScorecardWeights::default())
+    }
+}
+
+/// Fee updates feed fee competitiveness; any other request is answered
+/// with the current scores
+#[async_trait::async_trait]
+impl MessageHandler for VenueScorecard {
+    async fn handle_message(&self, message: NatsMessage, _reply_subject: Option<String>) -> AdapterResult<Option<NatsMessage>> {
+        if let NatsMessage::FeeUpdate { exchange, taker_bps, .. } = message {
+            self.set_taker_fee(&exchange, taker_bps);
+            return Ok(None);
+        }
+        Ok(Some(NatsMessage::AuditEvent {
+            component: "venue_scorecard".to_string(),
+            payload: serde_json::to_value(self.scores())?,
+            timestamp: chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reliable_cheap_venue_ranks_first() {
+        let scorecard = VenueScorecard::default();
+        scorecard.record_feed("binance", true, 60_000);
+        scorecard.set_taker_fee("binance", 7.5);
+        scorecard.record_order("binance", false);
+        scorecard.record_slippage("binance", 1.0);
+
+        scorecard.record_feed("huobi", true, 50_000);
+        scorecard.record_feed("huobi", false, 10_000);
+        scorecard.set_taker_fee("huobi", 20.0);
+        scorecard.record_order("huobi", true);
+        scorecard.record_withdrawal("huobi", false);
+
+        let scores = scorecard.scores();
+        assert_eq!(scores[0].exchange, "binance");
+        let weights = scorecard.relative_weights();
+        assert!(weights["binance"] > 1.0 && weights["huobi"] < 1.0);
+
+        let path = std::env::temp_dir().join(format!("venue_scorecard_{}.json", std::process::id()));
+        scorecard.save(&path).unwrap();
+        let restored = VenueScorecard::load(ScorecardWeights::default(), &path).unwrap();
+        assert_eq!(restored.scores().len(), 2);
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,199 @@
+//! Latency-weighted venue selection
+//!
+//! Where the scorecard tracks long-run venue quality, the selector keeps a
+//! short rolling window of order round-trip latency and fill outcomes per
+//! exchange, plus the feed latency reported by health monitoring. Its
+//! relative weights break near-ties between exchange pairs in favour of
+//! the faster, more reliable venue.
+
+use crate::health::{ComponentHealth, HealthSnapshot};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// Health components named "exchange:<name>" report a venue's feed
+const HEALTH_COMPONENT_PREFIX: &str = "exchange:";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VenueSelectorConfig {
+    /// Samples kept per venue
+    pub window: usize,
+    pub latency_weight: f64,
+    pub reliability_weight: f64,
+    /// Latency at which the latency component reaches zero (ms)
+    pub latency_ceiling_ms: f64,
+}
+
+impl Default for VenueSelectorConfig {
+    fn default() -> Self {
+        Self {
+            window: 200,
+            latency_weight: 0.5,
+            reliability_weight: 0.5,
+            latency_ceiling_ms: 500.0,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct VenueWindow {
+    order_latency_ms: VecDeque<f64>,
+    fills: VecDeque<bool>,
+    feed_latency_ms: Option<f64>,
+    feed_success_rate: Option<f64>,
+}
+
+/// Rolling score for one venue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VenueLatencyScore {
+    pub exchange: String,
+    pub avg_order_latency_ms: Option<f64>,
+    pub feed_latency_ms: Option<f64>,
+    pub fill_rate: f64,
+    /// 0 (worst) to 1 (best)
+    pub score: f64,
+}
+
+/// Rolling latency/reliability scorer
+pub struct VenueSelector {
+    config: VenueSelectorConfig,
+    venues: RwLock<HashMap<String, VenueWindow>>,
+}
+
+impl VenueSelector {
+    pub fn new(config: VenueSelectorConfig) -> Self {
+        Self {
+            config,
+            venues: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Record an order round trip and whether it filled
+    pub fn record_execution(&self, exchange: &str, latency_ms: f64, filled: bool) {
+        let window = self.config.window.max(1);
+        let mut venues = self.venues.write();
+        let venue = venues.entry(exchange.to_string()).or_default();
+        venue.order_latency_ms.push_back(latency_ms);
+        venue.fills.push_back(filled);
+        while venue.order_latency_ms.len() > window {
+            venue.order_latency_ms.pop_front();
+        }
+        while venue.fills.len() > window {
+            venue.fills.pop_front();
+        }
+    }
+
+    /// Take feed latency and success rate from a venue health report
+    pub fn record_health(&self, exchange: &str, health: &ComponentHealth) {
+        let mut venues = self.venues.write();
+        let venue = venues.entry(exchange.to_string()).or_default();
+        venue.feed_latency_ms = Some(health.avg_latency_us / 1000.0);
+        venue.feed_success_rate = Some(health.success_rate);
+    }
+
+    /// Ingest every "exchange:<name>" component of a health snapshot
+    pub fn ingest_health(&self, snapshot: &HealthSnapshot) {
+        for component in snapshot.all_components() {
+            if let Some(exchange) = component.component.strip_prefix(HEALTH_COMPONENT_PREFIX) {
+                self.record_health(exchange, &component);
+            }
+        }
+    }
+
+    /// Average order round trip, or the feed latency when no orders have
+    /// been sent (ms)
+    pub fn latency_ms(&self, exchange: &str) -> Option<f64> {
+        let venues = self.venues.read();
+        let venue = venues.get(exchange)?;
+        if venue.order_latency_ms.is_empty() {
+            venue.feed_latency_ms
+        } else {
+            Some(venue.order_latency_ms.iter().sum::<f64>() / venue.order_latency_ms.len() as f64)
+        }
+    }
+
+    /// Scores for every known venue, best first
+    pub fn scores(&self) -> Vec<VenueLatencyScore> {
+        let c = &self.config;
+        let total_weight = (c.latency_weight + c.reliability_weight).max(f64::EPSILON);
+        let mut scores: Vec<VenueLatencyScore> = self
+            .venues
+            .read()
+            .iter()
+            .map(|(exchange, venue)| {
+                let avg_order_latency_ms = (!venue.order_latency_ms.is_empty())
+                    .then(|| venue.order_latency_ms.iter().sum::<f64>() / venue.order_latency_ms.len() as f64);
+                // Order latency when we have it, otherwise the feed latency; unknown is neutral
+                let latency_component = match avg_order_latency_ms.or(venue.feed_latency_ms) {
+                    Some(ms) => 1.0 - (ms.max(0.0) / c.latency_ceiling_ms.max(f64::EPSILON)).min(1.0),
+                    None => 0.5,
+                };
+                let fill_rate = if venue.fills.is_empty() {
+                    venue.feed_success_rate.unwrap_or(1.0)
+                } else {
+                    venue.fills.iter().filter(|f| **f).count() as f64 / venue.fills.len() as f64
+                };
+                let score = (c.latency_weight * latency_component + c.reliability_weight * fill_rate) / total_weight;
+                VenueLatencyScore {
+                    exchange: exchange.clone(),
+                    avg_order_latency_ms,
+                    feed_latency_ms: venue.feed_latency_ms,
+                    fill_rate,
+                    score,
+                }
+            })
+            .collect();
+        scores.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        scores
+    }
+
+    /// Score relative to the average venue (1.0 = average)
+    pub fn relative_weights(&self) -> HashMap<String, f64> {
+        let scores = self.scores();
+        let mean = scores.iter().map(|s| s.score).sum::<f64>() / scores.len().max(1) as f64;
+        scores
+            .into_iter()
+            .map(|s| {
+                let weight = if mean > 0.0 { s.score / mean } else { 1.0 };
+                (s.exchange, weight)
+            })
+            .collect()
+    }
+}
+
+impl Default for VenueSelector {
+    fn default() -> Self {
+        Self::new(VenueSelectorConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_faster_reliable_venue_scores_higher() {
+        let selector = VenueSelector::default();
+        for _ in 0..10 {
+            selector.record_execution("binance", 20.0, true);
+            selector.record_execution("okx", 250.0, true);
+        }
+        selector.record_execution("okx", 250.0, false);
+
+        let weights = selector.relative_weights();
+        assert!(weights["binance"] > 1.0);
+        assert!(weights["okx"] < 1.0);
+
+        // Feed latency stands in until orders have been sent
+        let health = ComponentHealth {
+            component: "exchange:bybit".into(),
+            status: crate::health::HealthStatus::Healthy,
+            last_check_ns: 0,
+            success_rate: 1.0,
+            avg_latency_us: 5_000.0,
+            details: HashMap::new(),
+        };
+        selector.record_health("bybit", &health);
+        assert_eq!(selector.scores()[0].exchange, "bybit");
+    }
+}
@@ -0,0 +1,332 @@
+//! Virtual account for shadow trading
+//!
+//! Multi-currency paper account that applies shadow fills and supports
+//! margin: borrowable assets with per-asset caps and hourly interest,
+//! leverage limits, maintenance margin and simulated liquidation when the
+//! margin level breaches the maintenance requirement. Lets futures-spot
+//! and leveraged strategies be validated without a live margin account.
+
+use crate::order_matching::ShadowFill;
+use crate::{AdapterError, AdapterResult};
+use common::Side;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const NANOS_PER_HOUR: f64 = 3_600_000_000_000.0;
+
+/// Borrowing terms for one asset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BorrowTerms {
+    /// Maximum outstanding principal
+    pub max_borrow: f64,
+    /// Interest rate charged per hour on principal
+    pub hourly_interest_rate: f64,
+}
+
+/// Margin configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarginConfig {
+    /// Asset all values are expressed in
+    pub quote_asset: String,
+    /// Maximum gross assets / equity
+    pub max_leverage: f64,
+    /// Equity required as a fraction of liabilities before liquidation
+    pub maintenance_margin_rate: f64,
+    /// Fee charged on every asset sold or bought back during liquidation
+    pub liquidation_fee_rate: f64,
+    pub borrowable: HashMap<String, BorrowTerms>,
+}
+
+impl Default for MarginConfig {
+    fn default() -> Self {
+        let mut borrowable = HashMap::new();
+        borrowable.insert("USDT".to_string(), BorrowTerms {
+            max_borrow: 100_000.0,
+            hourly_interest_rate: 0.000_004,
+        });
+        borrowable.insert("BTC".to_string(), BorrowTerms {
+            max_borrow: 2.0,
+            hourly_interest_rate: 0.000_002,
+        });
+        borrowable.insert("ETH".to_string(), BorrowTerms {
+            max_borrow: 30.0,
+            hourly_interest_rate: 0.000_003,
+        });
+        Self {
+            quote_asset: "USDT".to_string(),
+            max_leverage: 3.0,
+            maintenance_margin_rate: 0.1,
+            liquidation_fee_rate: 0.005,
+            borrowable,
+        }
+    }
+}
+
+/// Outstanding loan for one asset
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Loan {
+    pub principal: f64,
+    pub accrued_interest: f64,
+}
+
+impl Loan {
+    pub fn total(&self) -> f64 {
+        self.principal + self.accrued_interest
+    }
+}
+
+/// Result of a simulated liquidation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidationEvent {
+    pub timestamp_ns: u64,
+    pub margin_level: f64,
+    pub equity_before: f64,
+    pub equity_after: f64,
+    pub fees_paid: f64,
+}
+
+/// Point-in-time margin view
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarginSnapshot {
+    pub assets_value: f64,
+    pub liabilities_value: f64,
+    pub equity: f64,
+    /// Assets / liabilities; infinite when nothing is borrowed
+    pub margin_level: f64,
+    pub leverage: f64,
+    pub maintenance_margin: f64,
+}
+
+/// Shadow trading virtual account
+pub struct VirtualAccount {
+    config: MarginConfig,
+    balances: HashMap<String, f64>,
+    loans: HashMap<String, Loan>,
+    /// Asset prices in `quote_asset`
+    prices: HashMap<String, f64>,
+    last_accrual_ns: Option<u64>,
+    liquidations: Vec<LiquidationEvent>,
+}
+
+impl VirtualAccount {
+    pub fn new(config: MarginConfig) -> Self {
+        Self {
+            config,
+            balances: HashMap::new(),
+            loans: HashMap::new(),
+            prices: HashMap::new(),
+            last_accrual_ns: None,
+            liquidations: Vec::new(),
+        }
+    }
+
+    pub fn deposit(&mut self, asset: &str, amount: f64) {
+        *self.balances.entry(asset.to_string()).or_default() += amount;
+    }
+
+    pub fn balance(&self, asset: &str) -> f64 {
+        self.balances.get(asset).copied().unwrap_or(0.0)
+    }
+
+    pub fn loan(&self, asset: &str) -> Option<&Loan> {
+        self.loans.get(asset)
+    }
+
+    /// Update the mark price of `asset` in the quote asset
+    pub fn set_price(&mut self, asset: &str, price: f64) {
+        self.prices.insert(asset.to_string(), price);
+    }
+
+    fn price(&self, asset: &str) -> f64 {
+        if asset == self.config.quote_asset {
+            1.0
+        } else {
+            self.prices.get(asset).copied().unwrap_or(0.0)
+        }
+    }
+
+    /// Borrow `amount` of `asset`, credited to the free balance
+    pub fn borrow(&mut self, asset: &str, amount: f64) -> AdapterResult<()> {
+        if amount <= 0.0 {
+            return Err(AdapterError::Validation { message: format!("borrow amount must be positive: {}", amount) });
+        }
+        let terms = self.config.borrowable.get(asset).ok_or_else(|| AdapterError::Validation {
+            message: format!("{} is not borrowable", asset),
+        })?;
+        let outstanding = self.loans.get(asset).map(|l| l.principal).unwrap_or(0.0);
+        if outstanding + amount > terms.max_borrow {
+            return Err(AdapterError::Validation {
+                message: format!("borrow cap exceeded for {}: {} + {} > {}", asset, outstanding, amount, terms.max_borrow),
+            });
+        }
+
+        // Leverage after the borrow: equity is unchanged, gross assets grow
+        let snapshot = self.snapshot();
+        let added_value = amount * self.price(asset);
+        if snapshot.equity <= 0.0 || (snapshot.assets_value + added_value) / snapshot.equity > self.config.max_leverage {
+            return Err(AdapterError::Validation {
+                message: format!("borrowing {} {} would exceed max leverage {}", amount, asset, self.config.max_leverage),
+            });
+        }
+
+        self.loans.entry(asset.to_string()).or_default().principal += amount;
+        self.deposit(asset, amount);
+        Ok(())
+    }
+
+    /// Repay interest first, then principal; returns the amount repaid
+    pub fn repay(&mut self, asset: &str, amount: f64) -> f64 {
+        let available = self.balance(asset).min(amount).max(0.0);
+        let Some(loan) = self.loans.get_mut(asset) else { return 0.0 };
+        let to_interest = available.min(loan.accrued_interest);
+        loan.accrued_interest -= to_interest;
+        let to_principal = (available - to_interest).min(loan.principal);
+        loan.principal -= to_principal;
+        let repaid = to_interest + to_principal;
+        if loan.total() <= 1e-12 {
+            self.loans.remove(asset);
+        }
+        *self.balances.entry(asset.to_string()).or_default() -= repaid;
+        repaid
+    }
+
+    /// Accrue interest on all loans up to `now_ns`
+    pub fn accrue_interest(&mut self, now_ns: u64) {
+        let Some(last) = self.last_accrual_ns.replace(now_ns) else { return };
+        let hours = now_ns.saturating_sub(last) as f64 / NANOS_PER_HOUR;
+        for (asset, loan) in self.loans.iter_mut() {
+            if let Some(terms) = self.config.borrowable.get(asset) {
+                loan.accrued_interest += loan.principal * terms.hourly_interest_rate * hours;
+            }
+        }
+    }
+
+    /// Apply a shadow fill on `base`/`quote`; fills that would overdraw a
+    /// balance are rejected, so shorts must borrow first
+    pub fn apply_fill(&mut self, fill: &ShadowFill, base: &str, quote: &str, fee_rate: f64) -> AdapterResult<()> {
+        let notional = fill.quantity * fill.price;
+        let (spend_asset, spend, receive_asset, receive) = match fill.side {
+            Side::Buy => (quote, notional, base, fill.quantity * (1.0 - fee_rate)),
+            Side::Sell => (base, fill.quantity, quote, notional * (1.0 - fee_rate)),
+        };
+        if self.balance(spend_asset) + 1e-12 < spend {
+            return Err(AdapterError::Validation {
+                message: format!("insufficient {} for fill {}: need {}, have {}", spend_asset, fill.order_id, spend, self.balance(spend_asset)),
+            });
+        }
+        *self.balances.entry(spend_asset.to_string()).or_default() -= spend;
+        self.deposit(receive_asset, receive);
+        Ok(())
+    }
+
+    /// Equity required to stay above the liquidation threshold
+    pub fn maintenance_margin(&self) -> f64 {
+        self.liabilities_value() * self.config.maintenance_margin_rate
+    }
+
+    fn liabilities_value(&self) -> f64 {
+        self.loans.iter().map(|(a, l)| l.total() * self.price(a)).sum()
+    }
+
+    pub fn snapshot(&self) -> MarginSnapshot {
+        let assets_value: f64 = self.balances.iter().map(|(a, b)| b * self.price(a)).sum();
+        let liabilities_value = self.liabilities_value();
+        let equity = assets_value - liabilities_value;
+        MarginSnapshot {
+            assets_value,
+            liabilities_value,
+            equity,
+            margin_level: if liabilities_value > 0.0 { assets_value / liabilities_value } else { f64::INFINITY },
+            leverage: if equity > 0.0 { assets_value / equity } else { f64::INFINITY },
+            maintenance_margin: self.maintenance_margin(),
+        }
+    }
+
+    /// Liquidate if equity has fallen below maintenance margin: every asset is
+    /// sold to the quote asset and all loans are bought back and repaid, with
+    /// the liquidation fee charged on both sides
+    pub fn check_liquidation(&mut self, now_ns: u64) -> Option<LiquidationEvent> {
+        self.accrue_interest(now_ns);
+        let before = self.snapshot();
+        if self.loans.is_empty() || before.equity >= before.maintenance_margin {
+            return None;
+        }
+
+        let quote = self.config.quote_asset.clone();
+        let fee_rate = self.config.liquidation_fee_rate;
+        let mut fees_paid = 0.0;
+
+        let assets: Vec<(String, f64)> = self.balances.drain().collect();
+        let mut quote_balance = 0.0;
+        for (asset, amount) in assets {
+            let value = amount * self.price(&asset);
+            if asset == quote {
+                quote_balance += value;
+            } else {
+                fees_paid += value * fee_rate;
+                quote_balance += value * (1.0 - fee_rate);
+            }
+        }
+        for (asset, loan) in self.loans.drain().collect::<Vec<_>>() {
+            let cost = loan.total() * self.price(&asset);
+            let fee = if asset == quote { 0.0 } else { cost * fee_rate };
+            fees_paid += fee;
+            quote_balance -= cost + fee;
+        }
+        self.balances.insert(quote, quote_balance);
+
+        let event = LiquidationEvent {
+            timestamp_ns: now_ns,
+            margin_level: before.margin_level,
+            equity_before: before.equity,
+            equity_after: quote_balance,
+            fees_paid,
+        };
+        tracing::error!(
+            "Virtual account liquidated: margin level {:.3}, equity {:.2} -> {:.2}",
+            event.margin_level, event.equity_before, event.equity_after
+        );
+        self.liquidations.push(event.clone());
+        Some(event)
+    }
+
+    pub fn liquidations(&self) -> &[LiquidationEvent] {
+        &self.liquidations
+    }
+}
+
+impl Default for VirtualAccount {
+    fn default() -> Self {
+        Self::new(MarginConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_leveraged_long_is_liquidated_on_drawdown() {
+        let mut account = VirtualAccount::default();
+        account.deposit("USDT", 10_000.0);
+        account.set_price("BTC", 50_000.0);
+        account.accrue_interest(0);
+
+        // 3x cap: 10k equity supports at most 30k gross assets
+        assert!(account.borrow("USDT", 25_000.0).is_err());
+        account.borrow("USDT", 20_000.0).unwrap();
+        let fill = ShadowFill { order_id: 1, symbol: "BTCUSDT".into(), side: Side::Buy, quantity: 0.6, price: 50_000.0 };
+        account.apply_fill(&fill, "BTC", "USDT", 0.0).unwrap();
+
+        account.accrue_interest(NANOS_PER_HOUR as u64);
+        assert!(account.loan("USDT").unwrap().accrued_interest > 0.0);
+        assert!(account.check_liquidation(NANOS_PER_HOUR as u64).is_none());
+
+        // 30k of BTC falls to ~21k against ~20k of debt
+        account.set_price("BTC", 35_000.0);
+        let event = account.check_liquidation(2 * NANOS_PER_HOUR as u64).unwrap();
+        assert!(event.equity_after < event.equity_before);
+        assert!(account.loan("USDT").is_none());
+        assert_eq!(account.balance("BTC"), 0.0);
+    }
+}
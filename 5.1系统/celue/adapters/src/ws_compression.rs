@@ -0,0 +1,439 @@
+//! Per-connection compression for the frontend WebSocket gateway
+//!
+//! Two independent savings, both negotiated per connection:
+//! - permessage-deflate (RFC 7692): the server answers the client's
+//!   `Sec-WebSocket-Extensions` offer and compresses each text frame with a
+//!   raw deflate stream, keeping the sliding window between messages unless
+//!   the client asked for no context takeover;
+//! - delta encoding: for keyed topics (opportunity updates by id) a
+//!   repeated update only carries the top-level fields that changed since
+//!   the previous message for the same key. Delta payloads use schema id
+//!   `<schema>+delta`; a full payload is sent every `full_every` updates,
+//!   whenever the delta would not be smaller, and after a resync.
+//!
+//! Every session reports raw, delta-encoded and on-the-wire byte counts.
+
+use crate::ws_gateway::JsonEnvelope;
+use crate::{AdapterError, AdapterResult};
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+const EXTENSION: &str = "permessage-deflate";
+/// Trailer removed from every compressed message (RFC 7692 7.2.1)
+const SYNC_TRAILER: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompressionConfig {
+    pub deflate: bool,
+    /// zlib level 0-9
+    pub level: u32,
+    /// Smaller messages are sent uncompressed
+    pub min_size: usize,
+    /// Topic -> JSON pointers tried in order to find the delta key
+    pub delta_topics: HashMap<String, Vec<String>>,
+    /// Send a full payload after this many deltas for the same key
+    pub full_every: u32,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            deflate: true,
+            level: 6,
+            min_size: 128,
+            delta_topics: HashMap::from([(
+                "opportunities".to_string(),
+                vec!["/opportunity/id".to_string(), "/id".to_string()],
+            )]),
+            full_every: 20,
+        }
+    }
+}
+
+/// Agreed permessage-deflate parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeflateParams {
+    pub server_no_context_takeover: bool,
+    pub client_no_context_takeover: bool,
+}
+
+impl DeflateParams {
+    /// Value for the `Sec-WebSocket-Extensions` response header
+    pub fn response_header(&self) -> String {
+        let mut header = EXTENSION.to_string();
+        if self.server_no_context_takeover {
+            header.push_str("; server_no_context_takeover");
+        }
+        if self.client_no_context_takeover {
+            header.push_str("; client_no_context_takeover");
+        }
+        header
+    }
+}
+
+/// Accept the first permessage-deflate offer we can honour.
+///
+/// Window sizes below 15 bits cannot be produced by the deflate backend, so
+/// offers requiring a smaller server window are declined.
+pub fn negotiate_deflate(offer: &str) -> Option<DeflateParams> {
+    offer.split(',').find_map(|extension| {
+        let mut parts = extension.split(';').map(str::trim);
+        if !parts.next()?.eq_ignore_ascii_case(EXTENSION) {
+            return None;
+        }
+        let mut params = DeflateParams { server_no_context_takeover: false, client_no_context_takeover: false };
+        for part in parts {
+            let (name, value) = part.split_once('=').map(|(n, v)| (n.trim(), Some(v.trim().trim_matches('"')))).unwrap_or((part, None));
+            match (name, value) {
+                ("server_no_context_takeover", None) => params.server_no_context_takeover = true,
+                ("client_no_context_takeover", None) => params.client_no_context_takeover = true,
+                // We never compress client-bound frames with a smaller window
+                ("server_max_window_bits", Some(bits)) if bits.parse::<u8>().ok()? < 15 => return None,
+                ("server_max_window_bits", Some(_)) | ("client_max_window_bits", _) => {}
+                _ => return None,
+            }
+        }
+        Some(params)
+    })
+}
+
+/// Byte counters of one connection
+#[derive(Debug, Default)]
+pub struct SessionStats {
+    pub session_id: u64,
+    messages: AtomicU64,
+    delta_messages: AtomicU64,
+    compressed_messages: AtomicU64,
+    /// Serialized envelopes as published
+    raw_bytes: AtomicU64,
+    /// After delta encoding
+    encoded_bytes: AtomicU64,
+    /// Frame payloads actually sent
+    wire_bytes: AtomicU64,
+}
+
+/// Point-in-time view of `SessionStats`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompressionStats {
+    pub session_id: u64,
+    pub deflate: bool,
+    pub messages: u64,
+    pub delta_messages: u64,
+    pub compressed_messages: u64,
+    pub raw_bytes: u64,
+    pub encoded_bytes: u64,
+    pub wire_bytes: u64,
+    /// wire / raw, 1.0 without savings
+    pub ratio: f64,
+}
+
+impl SessionStats {
+    fn snapshot(&self, deflate: bool) -> CompressionStats {
+        let raw_bytes = self.raw_bytes.load(Ordering::Relaxed);
+        let wire_bytes = self.wire_bytes.load(Ordering::Relaxed);
+        CompressionStats {
+            session_id: self.session_id,
+            deflate,
+            messages: self.messages.load(Ordering::Relaxed),
+            delta_messages: self.delta_messages.load(Ordering::Relaxed),
+            compressed_messages: self.compressed_messages.load(Ordering::Relaxed),
+            raw_bytes,
+            encoded_bytes: self.encoded_bytes.load(Ordering::Relaxed),
+            wire_bytes,
+            ratio: if raw_bytes == 0 { 1.0 } else { wire_bytes as f64 / raw_bytes as f64 },
+        }
+    }
+}
+
+/// Shared handle the gateway keeps for reporting
+pub struct SessionHandle {
+    pub(crate) stats: SessionStats,
+    pub(crate) deflate: bool,
+}
+
+impl SessionHandle {
+    pub fn stats(&self) -> CompressionStats {
+        self.stats.snapshot(self.deflate)
+    }
+}
+
+/// One outgoing WebSocket frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutboundFrame {
+    pub payload: Vec<u8>,
+    /// Set RSV1: the payload is deflate-compressed
+    pub compressed: bool,
+}
+
+struct DeltaState {
+    seq: u64,
+    fields: Map<String, Value>,
+    deltas_since_full: u32,
+}
+
+/// Encoder state of one connection
+pub struct WsSession {
+    config: Arc<CompressionConfig>,
+    params: Option<DeflateParams>,
+    compress: Option<Compress>,
+    decompress: Option<Decompress>,
+    /// (topic, key) -> last state sent
+    delta: HashMap<(String, String), DeltaState>,
+    handle: Arc<SessionHandle>,
+}
+
+impl WsSession {
+    pub(crate) fn new(session_id: u64, config: Arc<CompressionConfig>, params: Option<DeflateParams>) -> Self {
+        let params = params.filter(|_| config.deflate);
+        let handle = Arc::new(SessionHandle {
+            stats: SessionStats { session_id, ..Default::default() },
+            deflate: params.is_some(),
+        });
+        Self {
+            compress: params.map(|_| Compress::new(Compression::new(config.level.min(9)), false)),
+            decompress: params.map(|_| Decompress::new(false)),
+            config,
+            params,
+            delta: HashMap::new(),
+            handle,
+        }
+    }
+
+    pub fn deflate_params(&self) -> Option<DeflateParams> {
+        self.params
+    }
+
+    pub fn handle(&self) -> Arc<SessionHandle> {
+        self.handle.clone()
+    }
+
+    pub fn stats(&self) -> CompressionStats {
+        self.handle.stats()
+    }
+
+    /// Forget delta state so the next update of every key is sent in full
+    pub fn reset_delta(&mut self, topic: Option<&str>) {
+        match topic {
+            Some(topic) => self.delta.retain(|(t, _), _| t != topic),
+            None => self.delta.clear(),
+        }
+    }
+
+    /// Encode an envelope for this connection
+    pub fn encode(&mut self, envelope: &JsonEnvelope) -> AdapterResult<OutboundFrame> {
+        let raw = serde_json::to_vec(envelope)?;
+        let encoded = match self.delta_encode(envelope) {
+            Some(delta) => {
+                let bytes = serde_json::to_vec(&delta)?;
+                if bytes.len() < raw.len() {
+                    self.handle.stats.delta_messages.fetch_add(1, Ordering::Relaxed);
+                    bytes
+                } else {
+                    self.mark_full(envelope);
+                    raw.clone()
+                }
+            }
+            None => raw.clone(),
+        };
+
+        let frame = match &mut self.compress {
+            Some(compress) if encoded.len() >= self.config.min_size => {
+                if self.params.is_some_and(|p| p.server_no_context_takeover) {
+                    compress.reset();
+                }
+                let payload = deflate(compress, &encoded)?;
+                self.handle.stats.compressed_messages.fetch_add(1, Ordering::Relaxed);
+                OutboundFrame { payload, compressed: true }
+            }
+            _ => OutboundFrame { payload: encoded.clone(), compressed: false },
+        };
+
+        let stats = &self.handle.stats;
+        stats.messages.fetch_add(1, Ordering::Relaxed);
+        stats.raw_bytes.fetch_add(raw.len() as u64, Ordering::Relaxed);
+        stats.encoded_bytes.fetch_add(encoded.len() as u64, Ordering::Relaxed);
+        stats.wire_bytes.fetch_add(frame.payload.len() as u64, Ordering::Relaxed);
+        metrics::counter!("ws_gateway_raw_bytes_total").increment(raw.len() as u64);
+        metrics::counter!("ws_gateway_wire_bytes_total").increment(frame.payload.len() as u64);
+        Ok(frame)
+    }
+
+    /// Decompress a client frame sent with RSV1
+    pub fn decode(&mut self, payload: &[u8]) -> AdapterResult<Vec<u8>> {
+        let decompress = self
+            .decompress
+            .as_mut()
+            .ok_or_else(|| AdapterError::Validation { message: "permessage-deflate not negotiated".into() })?;
+        if self.params.is_some_and(|p| p.client_no_context_takeover) {
+            decompress.reset(false);
+        }
+        inflate(decompress, payload)
+    }
+
+    fn delta_key(&self, envelope: &JsonEnvelope) -> Option<String> {
+        let pointers = self.config.delta_topics.get(&envelope.topic)?;
+        pointers.iter().find_map(|p| match envelope.payload.pointer(p)? {
+            Value::String(s) => Some(s.clone()),
+            other => Some(other.to_string()),
+        })
+    }
+
+    /// Delta against the last state of the same key, recording the new state
+    fn delta_encode(&mut self, envelope: &JsonEnvelope) -> Option<JsonEnvelope> {
+        let key = self.delta_key(envelope)?;
+        let fields = envelope.payload.as_object()?.clone();
+        let full_every = self.config.full_every.max(1);
+        let state_key = (envelope.topic.clone(), key.clone());
+
+        let delta = match self.delta.get_mut(&state_key) {
+            Some(state) if state.deltas_since_full < full_every => {
+                let set: Map<String, Value> =
+                    fields.iter().filter(|(k, v)| state.fields.get(*k) != Some(*v)).map(|(k, v)| (k.clone(), v.clone())).collect();
+                let unset: Vec<&String> = state.fields.keys().filter(|k| !fields.contains_key(*k)).collect();
+                let payload = serde_json::json!({ "key": key, "base_seq": state.seq, "set": set, "unset": unset });
+                state.deltas_since_full += 1;
+                Some(JsonEnvelope { schema_id: format!("{}+delta", envelope.schema_id), payload, ..envelope.clone() })
+            }
+            _ => None,
+        };
+        let deltas_since_full = if delta.is_some() { self.delta[&state_key].deltas_since_full } else { 0 };
+        self.delta.insert(state_key, DeltaState { seq: envelope.seq, fields, deltas_since_full });
+        delta
+    }
+
+    fn mark_full(&mut self, envelope: &JsonEnvelope) {
+        if let Some(key) = self.delta_key(envelope) {
+            if let Some(state) = self.delta.get_mut(&(envelope.topic.clone(), key)) {
+                state.deltas_since_full = 0;
+            }
+        }
+    }
+}
+
+/// Raw deflate with a sync flush, trailer stripped
+fn deflate(compress: &mut Compress, input: &[u8]) -> AdapterResult<Vec<u8>> {
+    let start = compress.total_in();
+    let mut out = Vec::with_capacity(input.len() / 2 + 64);
+    loop {
+        if out.capacity() - out.len() < 64 {
+            out.reserve(out.capacity().max(64));
+        }
+        let consumed = (compress.total_in() - start) as usize;
+        compress
+            .compress_vec(&input[consumed..], &mut out, FlushCompress::Sync)
+            .map_err(|e| AdapterError::Generic { message: format!("deflate failed: {}", e) })?;
+        let consumed = (compress.total_in() - start) as usize;
+        // Spare output capacity after consuming everything means the flush completed
+        if consumed == input.len() && out.len() < out.capacity() {
+            break;
+        }
+    }
+    if out.ends_with(&SYNC_TRAILER) {
+        out.truncate(out.len() - SYNC_TRAILER.len());
+    }
+    Ok(out)
+}
+
+fn inflate(decompress: &mut Decompress, payload: &[u8]) -> AdapterResult<Vec<u8>> {
+    let input = [payload, &SYNC_TRAILER].concat();
+    let start = decompress.total_in();
+    let mut out = Vec::with_capacity(payload.len() * 4 + 64);
+    loop {
+        if out.capacity() - out.len() < 64 {
+            out.reserve(out.capacity().max(64));
+        }
+        let consumed = (decompress.total_in() - start) as usize;
+        decompress
+            .decompress_vec(&input[consumed..], &mut out, FlushDecompress::Sync)
+            .map_err(|e| AdapterError::Validation { message: format!("inflate failed: {}", e) })?;
+        let consumed = (decompress.total_in() - start) as usize;
+        if consumed == input.len() && out.len() < out.capacity() {
+            break;
+        }
+    }
+    Ok(out)
+}
+
+/// Live sessions of a gateway, for per-connection statistics
+#[derive(Default)]
+pub(crate) struct SessionRegistry {
+    next_id: AtomicU64,
+    sessions: Mutex<Vec<std::sync::Weak<SessionHandle>>>,
+}
+
+impl SessionRegistry {
+    pub(crate) fn open(&self, config: Arc<CompressionConfig>, offer: Option<&str>) -> WsSession {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let session = WsSession::new(id, config, offer.and_then(negotiate_deflate));
+        let mut sessions = self.sessions.lock();
+        sessions.retain(|s| s.strong_count() > 0);
+        sessions.push(Arc::downgrade(&session.handle));
+        session
+    }
+
+    pub(crate) fn stats(&self) -> Vec<CompressionStats> {
+        let mut sessions = self.sessions.lock();
+        sessions.retain(|s| s.strong_count() > 0);
+        sessions.iter().filter_map(|s| s.upgrade()).map(|h| h.stats()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ws_gateway::WsGateway;
+
+    #[test]
+    fn test_deflate_and_delta_round_trip() {
+        assert_eq!(
+            negotiate_deflate("x-webkit-deflate-frame, permessage-deflate; client_max_window_bits"),
+            Some(DeflateParams { server_no_context_takeover: false, client_no_context_takeover: false })
+        );
+        assert_eq!(negotiate_deflate("permessage-deflate; server_max_window_bits=10"), None);
+        let params = negotiate_deflate("permessage-deflate; server_max_window_bits=10, permessage-deflate; client_no_context_takeover").unwrap();
+        assert_eq!(params.response_header(), "permessage-deflate; client_no_context_takeover");
+
+        let gateway = WsGateway::new(16).with_compression(CompressionConfig::default());
+        let (mut session, header) = gateway.open_session(Some("permessage-deflate"));
+        assert_eq!(header.as_deref(), Some("permessage-deflate"));
+
+        let opportunity = |price: f64| {
+            serde_json::json!({
+                "event": "opened",
+                "opportunity": { "id": "abc", "symbol": "BTCUSDT", "price": price, "legs": ["binance", "okx"] },
+                "expires_at_ns": 1_000
+            })
+        };
+        let first = gateway.publish("opportunities", "opportunity_lifecycle.v1", &opportunity(100.0)).unwrap();
+        let second = gateway.publish("opportunities", "opportunity_lifecycle.v1", &serde_json::json!({
+            "event": "opened",
+            "opportunity": { "id": "abc", "symbol": "BTCUSDT", "price": 100.0, "legs": ["binance", "okx"] },
+            "expires_at_ns": 2_000
+        })).unwrap();
+
+        let mut client = Decompress::new(false);
+        let frame = session.encode(&first).unwrap();
+        assert!(frame.compressed);
+        let decoded: JsonEnvelope = serde_json::from_slice(&inflate(&mut client, &frame.payload).unwrap()).unwrap();
+        assert_eq!(decoded, first);
+
+        // Second frame decodes with the shared window and only carries the TTL
+        let frame = session.encode(&second).unwrap();
+        let delta: JsonEnvelope = serde_json::from_slice(&inflate(&mut client, &frame.payload).unwrap()).unwrap();
+        assert_eq!(delta.schema_id, "opportunity_lifecycle.v1+delta");
+        assert_eq!(delta.payload["base_seq"], 1);
+        assert_eq!(delta.payload["set"], serde_json::json!({ "expires_at_ns": 2_000 }));
+
+        let stats = gateway.compression_stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].delta_messages, 1);
+        assert!(stats[0].wire_bytes < stats[0].raw_bytes);
+        drop(session);
+        assert!(gateway.compression_stats().is_empty());
+    }
+}
@@ -0,0 +1,154 @@
+//! WebSocket gateway sequencing for the frontend protocol
+//!
+//! Wraps outgoing payloads in `WsEnvelope`s with per-topic monotonically
+//! increasing sequence numbers and keeps a bounded replay buffer per topic
+//! so clients that detect a gap can resync without a full reload.
+//! Sessions opened through `open_session` negotiate permessage-deflate and
+//! delta encoding (see `ws_compression`).
+
+use crate::ws_compression::{CompressionConfig, CompressionStats, SessionRegistry, WsSession};
+use common::envelope::{ResyncRequest, ResyncResponse, WsEnvelope, ENVELOPE_VERSION};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// Envelope with a JSON payload as sent over the wire
+pub type JsonEnvelope = WsEnvelope<serde_json::Value>;
+
+#[derive(Default)]
+struct TopicState {
+    seq: u64,
+    replay: VecDeque<JsonEnvelope>,
+}
+
+/// Per-topic sequencer and fan-out for frontend WebSocket sessions
+pub struct WsGateway {
+    topics: Mutex<HashMap<String, TopicState>>,
+    replay_capacity: usize,
+    sender: broadcast::Sender<JsonEnvelope>,
+    compression: Arc<CompressionConfig>,
+    sessions: SessionRegistry,
+}
+
+impl WsGateway {
+    pub fn new(replay_capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(4096);
+        Self {
+            topics: Mutex::new(HashMap::new()),
+            replay_capacity: replay_capacity.max(1),
+            sender,
+            compression: Arc::new(CompressionConfig::default()),
+            sessions: SessionRegistry::default(),
+        }
+    }
+
+    pub fn with_compression(mut self, config: CompressionConfig) -> Self {
+        self.compression = Arc::new(config);
+        self
+    }
+
+    /// Encoder for a new connection from the client's
+    /// `Sec-WebSocket-Extensions` offer, with the response header to send
+    pub fn open_session(&self, extensions: Option<&str>) -> (WsSession, Option<String>) {
+        let session = self.sessions.open(self.compression.clone(), extensions);
+        let header = session.deflate_params().map(|p| p.response_header());
+        (session, header)
+    }
+
+    /// Compression statistics of every open connection
+    pub fn compression_stats(&self) -> Vec<CompressionStats> {
+        self.sessions.stats()
+    }
+
+    /// Sequence a payload on `topic` and fan it out to connected sessions
+    pub fn publish<T: Serialize>(&self, topic: &str, schema_id: &str, payload: &T) -> crate::AdapterResult<JsonEnvelope> {
+        let payload = serde_json::to_value(payload)?;
+        let envelope = {
+            let mut topics = self.topics.lock();
+            let state = topics.entry(topic.to_string()).or_default();
+            state.seq += 1;
+            let envelope = WsEnvelope {
+                version: ENVELOPE_VERSION,
+                topic: topic.to_string(),
+                seq: state.seq,
+                server_ts_ns: chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64,
+                schema_id: schema_id.to_string(),
+                payload,
+            };
+            state.replay.push_back(envelope.clone());
+            while state.replay.len() > self.replay_capacity {
+                state.replay.pop_front();
+            }
+            envelope
+        };
+        // No connected sessions is not an error
+        let _ = self.sender.send(envelope.clone());
+        Ok(envelope)
+    }
+
+    /// Stream of envelopes for a new WebSocket session
+    pub fn subscribe(&self) -> broadcast::Receiver<JsonEnvelope> {
+        self.sender.subscribe()
+    }
+
+    pub fn current_seq(&self, topic: &str) -> u64 {
+        self.topics.lock().get(topic).map(|s| s.seq).unwrap_or(0)
+    }
+
+    /// Replay messages after `last_seq`, or ask for a snapshot when they
+    /// have already left the replay buffer
+    pub fn resync(&self, request: &ResyncRequest) -> ResyncResponse<serde_json::Value> {
+        let topics = self.topics.lock();
+        let Some(state) = topics.get(&request.topic) else {
+            return ResyncResponse::SnapshotRequired { topic: request.topic.clone(), current_seq: 0 };
+        };
+        let oldest = state.replay.front().map(|e| e.seq).unwrap_or(state.seq + 1);
+        if request.last_seq + 1 < oldest {
+            return ResyncResponse::SnapshotRequired { topic: request.topic.clone(), current_seq: state.seq };
+        }
+        ResyncResponse::Replay {
+            messages: state.replay.iter().filter(|e| e.seq > request.last_seq).cloned().collect(),
+        }
+    }
+}
+
+impl Default for WsGateway {
+    fn default() -> Self {
+        Self::new(1024)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::envelope::{SequenceCheck, SequenceTracker};
+
+    #[test]
+    fn test_client_recovers_gap_via_resync() {
+        let gateway = WsGateway::new(3);
+        let mut tracker = SequenceTracker::new();
+        let first = gateway.publish("risk", "risk_status.v1", &1).unwrap();
+        assert_eq!(tracker.observe(&first), SequenceCheck::InOrder);
+
+        gateway.publish("risk", "risk_status.v1", &2).unwrap();
+        let third = gateway.publish("risk", "risk_status.v1", &3).unwrap();
+        assert_eq!(gateway.publish("other", "x.v1", &0).unwrap().seq, 1);
+
+        // Client missed seq 2
+        let SequenceCheck::Gap { expected, .. } = tracker.observe(&third) else { panic!("expected gap") };
+        match gateway.resync(&tracker.resync_request("risk", expected - 1)) {
+            ResyncResponse::Replay { messages } => assert_eq!(messages.iter().map(|m| m.seq).collect::<Vec<_>>(), vec![2, 3]),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // Older than the replay buffer
+        gateway.publish("risk", "risk_status.v1", &4).unwrap();
+        gateway.publish("risk", "risk_status.v1", &5).unwrap();
+        assert!(matches!(
+            gateway.resync(&tracker.resync_request("risk", 0)),
+            ResyncResponse::SnapshotRequired { current_seq: 5, .. }
+        ));
+    }
+}
@@ -0,0 +1,37 @@
+//! Gateway credentials and token refresh
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// How the client authenticates with the gateway
+#[derive(Debug, Clone)]
+pub enum Auth {
+    None,
+    /// Static bearer token
+    Bearer(String),
+    /// Static API key sent as `X-Api-Key`
+    ApiKey(String),
+    /// Exchange an API key for short-lived tokens at `/api/auth/token`,
+    /// refreshed shortly before expiry and after a 401
+    Refreshing { api_key: String },
+}
+
+impl Auth {
+    pub(crate) fn refreshes(&self) -> bool {
+        matches!(self, Auth::Refreshing { .. })
+    }
+}
+
+/// Token issued by `/api/auth/token`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenGrant {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl TokenGrant {
+    /// Whether the token is still good for at least `margin`
+    pub fn valid_for(&self, margin: Duration, now: DateTime<Utc>) -> bool {
+        self.expires_at - margin > now
+    }
+}
@@ -0,0 +1,42 @@
+//! Client error type
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// Boxed to keep `ClientResult` small
+    #[error("WebSocket error: {0}")]
+    WebSocket(Box<tokio_tungstenite::tungstenite::Error>),
+
+    #[error("Decode error: {0}")]
+    Decode(#[from] serde_json::Error),
+
+    /// The gateway answered with an unsuccessful `ApiResponse`
+    #[error("API error {status} [{code}]: {message}")]
+    Api { status: u16, code: String, message: String },
+
+    #[error("Authentication failed: {0}")]
+    Auth(String),
+
+    #[error("Invalid configuration: {0}")]
+    Configuration(String),
+
+    /// The gap on `topic` is older than the server's replay buffer; refetch
+    /// a full snapshot before consuming further messages
+    #[error("Snapshot required for topic {topic} (server at seq {current_seq})")]
+    SnapshotRequired { topic: String, current_seq: u64 },
+
+    #[error("Retries exhausted after {attempts} attempts: {last_error}")]
+    RetriesExhausted { attempts: u32, last_error: String },
+}
+
+pub type ClientResult<T> = Result<T, ClientError>;
+
+impl From<tokio_tungstenite::tungstenite::Error> for ClientError {
+    fn from(error: tokio_tungstenite::tungstenite::Error) -> Self {
+        Self::WebSocket(Box::new(error))
+    }
+}
@@ -0,0 +1,366 @@
+//! Typed Rust client for the gateway HTTP/WebSocket API
+//!
+//! Wraps the REST endpoints served by the orchestrator gateway with typed
+//! methods that unwrap the `ApiResponse` envelope, and exposes topic
+//! subscriptions as a stream of sequenced `WsEnvelope`s. Short-lived
+//! tokens are refreshed automatically and transient failures are retried
+//! with exponential backoff.
+
+mod auth;
+mod error;
+mod stream;
+pub mod types;
+
+pub use auth::{Auth, TokenGrant};
+pub use error::{ClientError, ClientResult};
+pub use stream::Subscription;
+pub use types::{Alert, AlertSeverity};
+
+use std::time::Duration;
+
+use common::{ApiResponse, ArbitrageOpportunity, ExecutionResult, Page, PageQuery, SortOrder};
+use rand::Rng;
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+/// Retry behaviour for transient failures
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Exponential backoff for `attempt` (0-based) with up to 50% jitter
+pub(crate) fn backoff_delay(policy: &RetryPolicy, attempt: u32) -> Duration {
+    let base = policy
+        .initial_backoff
+        .saturating_mul(1u32 << attempt.min(16))
+        .min(policy.max_backoff);
+    let jitter = rand::thread_rng().gen_range(0.0..0.5);
+    base + base.mul_f64(jitter)
+}
+
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    /// Gateway base URL, e.g. "http://localhost:8080"
+    pub base_url: String,
+    pub timeout: Duration,
+    pub retry: RetryPolicy,
+    /// Refresh tokens this long before they expire
+    pub refresh_margin: Duration,
+}
+
+impl ClientConfig {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            timeout: Duration::from_secs(10),
+            retry: RetryPolicy::default(),
+            refresh_margin: Duration::from_secs(30),
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+}
+
+/// Typed gateway client
+pub struct TaoliClient {
+    config: ClientConfig,
+    http: reqwest::Client,
+    auth: Auth,
+    token: Mutex<Option<TokenGrant>>,
+}
+
+impl TaoliClient {
+    pub fn new(config: ClientConfig, auth: Auth) -> ClientResult<Self> {
+        if !config.base_url.starts_with("http://") && !config.base_url.starts_with("https://") {
+            return Err(ClientError::Configuration(format!("base URL must be http(s): {}", config.base_url)));
+        }
+        let http = reqwest::Client::builder().timeout(config.timeout).build()?;
+        Ok(Self { config, http, auth, token: Mutex::new(None) })
+    }
+
+    /// Current credential as a header, refreshing the token if needed
+    async fn credentials(&self) -> ClientResult<Option<(&'static str, String)>> {
+        Ok(match &self.auth {
+            Auth::None => None,
+            Auth::Bearer(token) => Some(("Authorization", format!("Bearer {}", token))),
+            Auth::ApiKey(key) => Some(("X-Api-Key", key.clone())),
+            Auth::Refreshing { api_key } => {
+                let mut cached = self.token.lock().await;
+                let margin = chrono::Duration::from_std(self.config.refresh_margin).unwrap_or_default();
+                let valid = cached.as_ref().is_some_and(|t| t.valid_for(margin, chrono::Utc::now()));
+                if !valid {
+                    *cached = Some(self.fetch_token(api_key).await?);
+                }
+                cached.as_ref().map(|t| ("Authorization", format!("Bearer {}", t.token)))
+            }
+        })
+    }
+
+    async fn fetch_token(&self, api_key: &str) -> ClientResult<TokenGrant> {
+        debug!("Refreshing gateway token");
+        let response = self
+            .http
+            .post(format!("{}/api/auth/token", self.config.base_url))
+            .header("X-Api-Key", api_key)
+            .send()
+            .await?;
+        let status = response.status();
+        let body: ApiResponse<TokenGrant> = response.json().await?;
+        match body {
+            ApiResponse { success: true, data: Some(grant), .. } => Ok(grant),
+            ApiResponse { error, .. } => Err(ClientError::Auth(format!(
+                "token refresh failed ({}): {}",
+                status,
+                error.unwrap_or_default()
+            ))),
+        }
+    }
+
+    async fn invalidate_token(&self) {
+        self.token.lock().await.take();
+    }
+
+    fn authorize(request: RequestBuilder, credentials: Option<(&'static str, String)>) -> RequestBuilder {
+        match credentials {
+            Some((name, value)) => request.header(name, value),
+            None => request,
+        }
+    }
+
+    /// Send a request and unwrap the `ApiResponse` envelope. Only
+    /// idempotent requests are retried on timeouts and 5xx/429 answers;
+    /// everything is retried when the connection could not be made.
+    async fn send<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&Value>,
+    ) -> ClientResult<ApiResponse<T>> {
+        let idempotent = method == Method::GET;
+        let url = format!("{}{}", self.config.base_url, path);
+        let retry = &self.config.retry;
+        let mut attempt = 0;
+        let mut refreshed = false;
+
+        loop {
+            let mut request = Self::authorize(self.http.request(method.clone(), &url), self.credentials().await?);
+            if let Some(body) = body {
+                request = request.json(body);
+            }
+            let last_error = match request.send().await {
+                Ok(response) => {
+                    let status = response.status();
+                    if status == StatusCode::UNAUTHORIZED && self.auth.refreshes() && !refreshed {
+                        // Token revoked or clock skew; fetch a new one once
+                        self.invalidate_token().await;
+                        refreshed = true;
+                        continue;
+                    }
+                    let transient = status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS;
+                    if !(transient && idempotent) || attempt >= retry.max_retries {
+                        return Self::unwrap_envelope(status, response.text().await?);
+                    }
+                    format!("{} {}", method, status)
+                }
+                Err(e) if e.is_connect() || (idempotent && e.is_timeout()) => {
+                    if attempt >= retry.max_retries {
+                        return Err(ClientError::RetriesExhausted { attempts: attempt + 1, last_error: e.to_string() });
+                    }
+                    e.to_string()
+                }
+                Err(e) => return Err(e.into()),
+            };
+            let delay = backoff_delay(retry, attempt);
+            warn!("{} {} failed ({}), retrying in {:?}", method, path, last_error, delay);
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    fn unwrap_envelope<T: DeserializeOwned>(status: StatusCode, body: String) -> ClientResult<ApiResponse<T>> {
+        let envelope: ApiResponse<T> = serde_json::from_str(&body).map_err(|e| {
+            if status.is_success() {
+                ClientError::Decode(e)
+            } else {
+                ClientError::Api { status: status.as_u16(), code: "unknown".to_string(), message: body.clone() }
+            }
+        })?;
+        if !envelope.success || !status.is_success() {
+            return Err(ClientError::Api {
+                status: status.as_u16(),
+                code: envelope.error_code.unwrap_or_else(|| "unknown".to_string()),
+                message: envelope.error.unwrap_or_else(|| status.to_string()),
+            });
+        }
+        Ok(envelope)
+    }
+
+    async fn data<T: DeserializeOwned>(&self, method: Method, path: &str, body: Option<&Value>) -> ClientResult<T> {
+        let envelope = self.send::<T>(method, path, body).await?;
+        envelope
+            .data
+            .ok_or_else(|| ClientError::Api { status: 200, code: "empty".to_string(), message: "response has no data".to_string() })
+    }
+
+    async fn page<T: DeserializeOwned>(&self, path: &str, query: &PageQuery) -> ClientResult<Page<T>> {
+        let mut params = vec![format!(
+            "order={}",
+            match query.order {
+                SortOrder::Asc => "asc",
+                SortOrder::Desc => "desc",
+            }
+        )];
+        if let Some(limit) = query.limit {
+            params.push(format!("limit={}", limit));
+        }
+        if let Some(cursor) = &query.cursor {
+            params.push(format!("cursor={}", cursor));
+        }
+        if let Some(from_ns) = query.from_ns {
+            params.push(format!("from_ns={}", from_ns));
+        }
+        if let Some(to_ns) = query.to_ns {
+            params.push(format!("to_ns={}", to_ns));
+        }
+        for (field, value) in &query.filters {
+            params.push(format!("{}={}", field, value));
+        }
+        let envelope = self.send::<Vec<T>>(Method::GET, &format!("{}?{}", path, params.join("&")), None).await?;
+        Ok(Page { items: envelope.data.unwrap_or_default(), next_cursor: envelope.next_cursor })
+    }
+
+    /// System status summary
+    pub async fn status(&self) -> ClientResult<Value> {
+        self.data(Method::GET, "/api/status", None).await
+    }
+
+    /// Detected opportunities, paged
+    pub async fn opportunities(&self, query: &PageQuery) -> ClientResult<Page<ArbitrageOpportunity>> {
+        self.page("/api/opportunities", query).await
+    }
+
+    /// Order execution history, paged
+    pub async fn orders(&self, query: &PageQuery) -> ClientResult<Page<ExecutionResult>> {
+        self.page("/api/orders", query).await
+    }
+
+    pub async fn alerts(&self, active_only: bool, limit: usize) -> ClientResult<Vec<Alert>> {
+        self.data(Method::GET, &format!("/api/alerts?active={}&limit={}", active_only, limit), None).await
+    }
+
+    pub async fn acknowledge_alert(&self, key: &str) -> ClientResult<()> {
+        self.send::<Value>(Method::POST, &format!("/api/alerts/{}/ack", key), Some(&json!({}))).await?;
+        Ok(())
+    }
+
+    pub async fn positions(&self, exchange: Option<&str>) -> ClientResult<Value> {
+        let path = match exchange {
+            Some(exchange) => format!("/api/positions?exchange={}", exchange),
+            None => "/api/positions".to_string(),
+        };
+        self.data(Method::GET, &path, None).await
+    }
+
+    /// Running configuration, decoded as the caller's config type
+    pub async fn config<T: DeserializeOwned>(&self) -> ClientResult<T> {
+        self.data(Method::GET, "/api/config", None).await
+    }
+
+    pub async fn update_config<T: Serialize>(&self, config: &T) -> ClientResult<Value> {
+        let body = serde_json::to_value(config)?;
+        self.data(Method::PUT, "/api/config", Some(&body)).await
+    }
+
+    /// Validate a TOML config on the server without applying it
+    pub async fn validate_config(&self, toml: &str) -> ClientResult<Value> {
+        let body = json!({ "format": "toml", "content": toml });
+        self.data(Method::POST, "/api/config/validate", Some(&body)).await
+    }
+
+    /// Subscribe to gateway topics, e.g. "opportunities" or "candles.binance.BTCUSDT.1m"
+    pub async fn subscribe(&self, topics: &[&str]) -> ClientResult<Subscription> {
+        let url = format!(
+            "{}/ws",
+            self.config.base_url.replacen("https://", "wss://", 1).replacen("http://", "ws://", 1)
+        );
+        let credentials = self.credentials().await?;
+        Subscription::connect(url, credentials, topics.iter().map(|t| t.to_string()).collect(), self.config.retry.clone())
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_token_refresh_and_retry() {
+        let server = MockServer::start().await;
+        let grant = TokenGrant { token: "t1".to_string(), expires_at: chrono::Utc::now() + chrono::Duration::hours(1) };
+        Mock::given(method("POST"))
+            .and(path("/api/auth/token"))
+            .and(header("X-Api-Key", "key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(ApiResponse::ok(grant)))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/status"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/status"))
+            .and(header("Authorization", "Bearer t1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(ApiResponse::ok(json!({ "running": true }))))
+            .mount(&server)
+            .await;
+
+        let retry = RetryPolicy { initial_backoff: Duration::from_millis(1), ..Default::default() };
+        let client =
+            TaoliClient::new(ClientConfig::new(&server.uri()).with_retry(retry), Auth::Refreshing { api_key: "key".to_string() })
+                .unwrap();
+        assert_eq!(client.status().await.unwrap()["running"], true);
+
+        Mock::given(method("GET"))
+            .and(path("/api/config"))
+            .respond_with(ResponseTemplate::new(403).set_body_json(ApiResponse::<()> {
+                error_code: Some("forbidden".to_string()),
+                ..ApiResponse::err("missing role")
+            }))
+            .mount(&server)
+            .await;
+        assert!(matches!(
+            client.config::<Value>().await,
+            Err(ClientError::Api { status: 403, ref code, .. }) if code == "forbidden"
+        ));
+    }
+}
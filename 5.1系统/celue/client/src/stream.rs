@@ -0,0 +1,202 @@
+//! Streaming topic subscriptions over the gateway WebSocket
+//!
+//! Incoming envelopes are checked against a per-topic `SequenceTracker`;
+//! on a gap the subscription asks the server to replay the missed range
+//! and delivers the replayed messages before anything newer. Dropped
+//! connections are re-established with backoff, re-subscribing and
+//! resyncing every topic from its last processed sequence.
+
+use std::collections::{HashMap, VecDeque};
+
+use common::envelope::{ResyncRequest, ResyncResponse, SequenceCheck, SequenceTracker, WsEnvelope};
+use futures_util::{SinkExt, StreamExt};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+use tracing::{debug, warn};
+
+use crate::{backoff_delay, ClientError, ClientResult, RetryPolicy};
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// A live subscription to one or more gateway topics
+pub struct Subscription {
+    url: String,
+    /// Header name and value used to authenticate the upgrade request
+    credentials: Option<(&'static str, String)>,
+    topics: Vec<String>,
+    retry: RetryPolicy,
+    socket: Option<Socket>,
+    tracker: SequenceTracker,
+    /// Outstanding resyncs per topic: (last processed, first seq received after the gap)
+    gaps: HashMap<String, (u64, u64)>,
+    pending: VecDeque<WsEnvelope<Value>>,
+    failures: u32,
+}
+
+impl Subscription {
+    pub(crate) async fn connect(
+        url: String,
+        credentials: Option<(&'static str, String)>,
+        topics: Vec<String>,
+        retry: RetryPolicy,
+    ) -> ClientResult<Self> {
+        let mut subscription = Self {
+            url,
+            credentials,
+            topics,
+            retry,
+            socket: None,
+            tracker: SequenceTracker::new(),
+            gaps: HashMap::new(),
+            pending: VecDeque::new(),
+            failures: 0,
+        };
+        subscription.open().await?;
+        Ok(subscription)
+    }
+
+    pub fn topics(&self) -> &[String] {
+        &self.topics
+    }
+
+    /// Last sequence delivered on `topic`
+    pub fn last_seq(&self, topic: &str) -> u64 {
+        self.tracker.last_seq(topic)
+    }
+
+    /// Continue `topic` from `current_seq` after applying a full snapshot
+    pub fn reset(&mut self, topic: &str, current_seq: u64) {
+        self.tracker.reset(topic, current_seq);
+        self.gaps.remove(topic);
+    }
+
+    async fn open(&mut self) -> ClientResult<()> {
+        let mut request = self.url.as_str().into_client_request()?;
+        if let Some((name, value)) = &self.credentials {
+            let value = HeaderValue::from_str(value).map_err(|e| ClientError::Auth(e.to_string()))?;
+            request.headers_mut().insert(*name, value);
+        }
+        let (mut socket, _) = connect_async(request).await?;
+        let subscribe = json!({ "op": "subscribe", "topics": self.topics });
+        socket.send(Message::Text(subscribe.to_string())).await?;
+        // Pick up where we left off on reconnect
+        for topic in &self.topics {
+            let last_seq = self.tracker.last_seq(topic);
+            if last_seq > 0 {
+                let request = self.tracker.resync_request(topic, last_seq);
+                socket.send(Message::Text(resync_message(&request))).await?;
+            }
+        }
+        self.socket = Some(socket);
+        self.failures = 0;
+        Ok(())
+    }
+
+    /// Next envelope on any subscribed topic, decoded as `T`. Returns
+    /// `None` once the subscription has been closed.
+    pub async fn next<T: DeserializeOwned>(&mut self) -> Option<ClientResult<WsEnvelope<T>>> {
+        loop {
+            if let Some(envelope) = self.pending.pop_front() {
+                return Some(decode(envelope));
+            }
+            let Some(socket) = self.socket.as_mut() else {
+                if self.topics.is_empty() {
+                    return None;
+                }
+                tokio::time::sleep(backoff_delay(&self.retry, self.failures)).await;
+                if let Err(e) = self.open().await {
+                    self.failures += 1;
+                    warn!("WebSocket reconnect attempt {} failed: {}", self.failures, e);
+                    if self.failures >= self.retry.max_retries {
+                        self.failures = 0;
+                        return Some(Err(e));
+                    }
+                }
+                continue;
+            };
+
+            let text = match socket.next().await {
+                Some(Ok(Message::Text(text))) => text,
+                Some(Ok(Message::Close(_))) | None => {
+                    debug!("WebSocket closed, reconnecting");
+                    self.socket = None;
+                    continue;
+                }
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => {
+                    warn!("WebSocket error, reconnecting: {}", e);
+                    self.socket = None;
+                    continue;
+                }
+            };
+
+            if let Ok(envelope) = serde_json::from_str::<WsEnvelope<Value>>(&text) {
+                match self.tracker.observe(&envelope) {
+                    SequenceCheck::InOrder => return Some(decode(envelope)),
+                    SequenceCheck::Duplicate => continue,
+                    SequenceCheck::Gap { expected, received } => {
+                        let request = self.tracker.resync_request(&envelope.topic, expected - 1);
+                        self.gaps.insert(envelope.topic.clone(), (expected - 1, received));
+                        if let Err(e) = socket.send(Message::Text(resync_message(&request))).await {
+                            warn!("Resync request for {} failed: {}", envelope.topic, e);
+                        }
+                        return Some(decode(envelope));
+                    }
+                }
+            }
+
+            match serde_json::from_str::<ResyncResponse<Value>>(&text) {
+                Ok(ResyncResponse::Replay { messages }) => {
+                    // The tracker already moved past the gap; deliver only the missed range
+                    for message in messages {
+                        let in_gap = self
+                            .gaps
+                            .get(&message.topic)
+                            .is_some_and(|(after, before)| message.seq > *after && message.seq < *before);
+                        if in_gap {
+                            self.pending.push_back(message);
+                        }
+                    }
+                    let topics: Vec<String> = self.pending.iter().map(|m| m.topic.clone()).collect();
+                    for topic in topics {
+                        self.gaps.remove(&topic);
+                    }
+                }
+                Ok(ResyncResponse::SnapshotRequired { topic, current_seq }) => {
+                    self.reset(&topic, current_seq);
+                    return Some(Err(ClientError::SnapshotRequired { topic, current_seq }));
+                }
+                Err(_) => debug!("Ignoring unrecognised WebSocket frame"),
+            }
+        }
+    }
+
+    /// Close the connection; `next` returns `None` afterwards
+    pub async fn close(&mut self) -> ClientResult<()> {
+        self.topics.clear();
+        if let Some(mut socket) = self.socket.take() {
+            socket.close(None).await?;
+        }
+        Ok(())
+    }
+}
+
+fn resync_message(request: &ResyncRequest) -> String {
+    json!({ "op": "resync", "topic": request.topic, "last_seq": request.last_seq }).to_string()
+}
+
+fn decode<T: DeserializeOwned>(envelope: WsEnvelope<Value>) -> ClientResult<WsEnvelope<T>> {
+    Ok(WsEnvelope {
+        version: envelope.version,
+        topic: envelope.topic,
+        seq: envelope.seq,
+        server_ts_ns: envelope.server_ts_ns,
+        schema_id: envelope.schema_id,
+        payload: serde_json::from_value(envelope.payload)?,
+    })
+}
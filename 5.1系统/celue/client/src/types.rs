@@ -0,0 +1,24 @@
+//! Wire types for gateway endpoints that have no counterpart in `common`
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Alert severity, ordered from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum AlertSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+/// Alert as returned by `/api/alerts`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Alert {
+    /// Deduplication key, e.g. "exchange_disconnected:binance"
+    pub key: String,
+    pub severity: AlertSeverity,
+    pub title: String,
+    pub message: String,
+    pub source: String,
+    pub timestamp: DateTime<Utc>,
+}
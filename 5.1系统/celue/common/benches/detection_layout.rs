@@ -0,0 +1,69 @@
+//! Cross-exchange top-of-book scan: per-book AoS access vs the SoA `TopOfBook`
+//!
+//! Run with `cargo bench -p common --bench detection_layout`. For cache
+//! behaviour, run the bench binary under `perf stat -e cache-misses,L1-dcache-load-misses`.
+
+use common::market_data::{NormalizedSnapshot, OrderBook, TopOfBook};
+use common::{Exchange, FixedPrice, FixedQuantity, Symbol};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+fn snapshot(exchanges: usize) -> NormalizedSnapshot {
+    let books = (0..exchanges)
+        .map(|i| {
+            let mut book = OrderBook::new(Exchange::new(format!("ex{}", i)), Symbol::new("BTCUSDT"), 0, 0);
+            for level in 0..20 {
+                let offset = level as f64 * 0.5 + (i % 7) as f64 * 0.01;
+                book.add_bid(FixedPrice::from_f64(50_000.0 - offset, 2), FixedQuantity::from_f64(1.0, 8));
+                book.add_ask(FixedPrice::from_f64(50_001.0 + offset, 2), FixedQuantity::from_f64(1.0, 8));
+            }
+            book
+        })
+        .collect();
+    NormalizedSnapshot {
+        symbol: Symbol::new("BTCUSDT"),
+        timestamp_ns: 0,
+        exchanges: books,
+        weighted_mid_price: FixedPrice::from_f64(50_000.5, 2),
+        total_bid_volume: FixedQuantity::from_f64(0.0, 8),
+        total_ask_volume: FixedQuantity::from_f64(0.0, 8),
+        quality_score: 1.0,
+        sequence: None,
+    }
+}
+
+fn aos_has_cross(snapshot: &NormalizedSnapshot) -> bool {
+    for a in &snapshot.exchanges {
+        for b in &snapshot.exchanges {
+            if std::ptr::eq(a, b) {
+                continue;
+            }
+            if let (Some(bid), Some(ask)) = (a.best_bid(), b.best_ask()) {
+                if bid.price > ask.price {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+fn bench_cross_scan(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cross_scan");
+    for exchanges in [4usize, 16, 64] {
+        let snap = snapshot(exchanges);
+        let top = TopOfBook::from_books(&snap.exchanges);
+        group.bench_with_input(BenchmarkId::new("aos_pairwise", exchanges), &snap, |b, s| {
+            b.iter(|| aos_has_cross(black_box(s)))
+        });
+        group.bench_with_input(BenchmarkId::new("soa_build_and_scan", exchanges), &snap, |b, s| {
+            b.iter(|| black_box(s).top_of_book().has_cross())
+        });
+        group.bench_with_input(BenchmarkId::new("soa_scan", exchanges), &top, |b, t| {
+            b.iter(|| black_box(t).has_cross())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_cross_scan);
+criterion_main!(benches);
@@ -13,11 +13,25 @@ pub enum Side {
     Sell,
 }
 
+/// The market a leg trades on; the same symbol (e.g. Binance "BTCUSDT")
+/// can name both a spot pair and a perpetual contract.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketType {
+    #[default]
+    Spot,
+    Perpetual,
+    /// Dated (delivery) futures
+    Delivery,
+}
+
 /// Represents one leg of an arbitrage trade.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ArbitrageLeg {
     pub exchange: Exchange,
     pub symbol: Symbol,
+    #[serde(default)]
+    pub market: MarketType,
     pub side: Side,
     pub price: FixedPrice,
     pub quantity: FixedQuantity,
@@ -34,12 +48,20 @@ pub struct ArbitrageOpportunity {
     pub gross_profit: FixedPrice,
     /// The estimated net profit after deducting fees.
     pub net_profit: FixedPrice,
-    /// The estimated net profit as a percentage of the total investment.
+    /// The estimated net profit as a fraction of the total investment
+    /// (0.001 = 0.1%), in the same unit for every strategy.
     pub net_profit_pct: FixedPrice,
     /// The timestamp (in ns) when the opportunity was created.
     pub created_at_ns: u64,
     /// Time-to-live for this opportunity in nanoseconds.
     pub ttl_ns: u64,
+    /// The oldest source-quote timestamp (in ns) used to build this opportunity;
+    /// 0 means unknown (e.g. payloads from before the field existed).
+    #[serde(default)]
+    pub quote_watermark_ns: u64,
+    /// Scheduling priority; higher is executed first.
+    #[serde(default)]
+    pub priority: u8,
     /// Arbitrary metadata for audit/tracing
     pub tags: HashMap<String, String>,
 }
@@ -70,6 +92,8 @@ impl ArbitrageOpportunity {
             net_profit_pct,
             created_at_ns: clock_time_ns,
             ttl_ns: 150_000_000, // 150ms, as per documentation
+            quote_watermark_ns: clock_time_ns,
+            priority: 0,
             tags: HashMap::new(),
         }
     }
@@ -100,7 +124,42 @@ impl ArbitrageOpportunity {
             net_profit_pct,
             created_at_ns: clock_time_ns,
             ttl_ns: 150_000_000,
+            quote_watermark_ns: clock_time_ns,
+            priority: 0,
             tags: HashMap::new(),
         }
     }
+
+    /// Sets the quote watermark to the oldest non-zero quote timestamp given.
+    pub fn with_quote_watermark(mut self, quote_timestamps_ns: impl IntoIterator<Item = u64>) -> Self {
+        if let Some(oldest) = quote_timestamps_ns.into_iter().filter(|ts| *ts > 0).min() {
+            self.quote_watermark_ns = oldest;
+        }
+        self
+    }
+
+    /// Age of the oldest quote behind this opportunity at `now_ns`; None when
+    /// the watermark is unknown.
+    pub fn quote_age_ns(&self, now_ns: u64) -> Option<u64> {
+        (self.quote_watermark_ns > 0).then(|| now_ns.saturating_sub(self.quote_watermark_ns))
+    }
+}
+
+impl crate::pagination::Pageable for ArbitrageOpportunity {
+    fn timestamp_ns(&self) -> u64 {
+        self.created_at_ns
+    }
+
+    fn record_id(&self) -> String {
+        self.id.to_string()
+    }
+
+    fn field(&self, name: &str) -> Option<String> {
+        match name {
+            "strategy" => Some(self.strategy_name.clone()),
+            "symbol" => self.legs.first().map(|l| l.symbol.to_string()),
+            "exchange" => self.legs.first().map(|l| l.exchange.to_string()),
+            _ => self.tags.get(name).cloned(),
+        }
+    }
 }
@@ -0,0 +1,123 @@
+//! Versioned WebSocket message envelope for the frontend protocol
+//!
+//! Every server push carries its topic, a per-topic monotonically
+//! increasing sequence number, the server timestamp and a payload schema
+//! id. Clients track the last sequence per topic and request a resync when
+//! they detect a gap.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Current envelope protocol version
+pub const ENVELOPE_VERSION: u16 = 1;
+
+/// Envelope wrapping every WebSocket message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WsEnvelope<T> {
+    pub version: u16,
+    pub topic: String,
+    /// Per-topic sequence, starting at 1
+    pub seq: u64,
+    pub server_ts_ns: u64,
+    /// Payload schema identifier, e.g. "opportunity.v2"
+    pub schema_id: String,
+    pub payload: T,
+}
+
+/// Client request to recover messages after a gap
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResyncRequest {
+    pub topic: String,
+    /// Last sequence the client processed
+    pub last_seq: u64,
+}
+
+/// Server answer to a resync request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResyncResponse<T> {
+    /// Missed messages, in order
+    Replay { messages: Vec<WsEnvelope<T>> },
+    /// Gap is older than the replay buffer; refetch a full snapshot and
+    /// continue from `current_seq`
+    SnapshotRequired { topic: String, current_seq: u64 },
+}
+
+/// Result of checking an incoming envelope against the client's state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceCheck {
+    InOrder,
+    /// Already seen; drop it
+    Duplicate,
+    /// Messages between `expected` and `received` were missed
+    Gap { expected: u64, received: u64 },
+}
+
+/// Client-side per-topic sequence tracking
+#[derive(Debug, Clone, Default)]
+pub struct SequenceTracker {
+    last_seq: HashMap<String, u64>,
+}
+
+impl SequenceTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check an envelope and advance the topic's sequence. On a gap the
+    /// sequence still advances; the caller should issue `resync_request`
+    /// for the returned range.
+    pub fn observe<T>(&mut self, envelope: &WsEnvelope<T>) -> SequenceCheck {
+        let last = self.last_seq.entry(envelope.topic.clone()).or_insert(0);
+        let expected = *last + 1;
+        if envelope.seq < expected {
+            return SequenceCheck::Duplicate;
+        }
+        *last = envelope.seq;
+        if envelope.seq == expected {
+            SequenceCheck::InOrder
+        } else {
+            SequenceCheck::Gap { expected, received: envelope.seq }
+        }
+    }
+
+    /// Resync request for messages after `last_seq` on `topic`
+    pub fn resync_request(&self, topic: &str, last_seq: u64) -> ResyncRequest {
+        ResyncRequest { topic: topic.to_string(), last_seq }
+    }
+
+    /// Reset a topic after applying a full snapshot
+    pub fn reset(&mut self, topic: &str, current_seq: u64) {
+        self.last_seq.insert(topic.to_string(), current_seq);
+    }
+
+    pub fn last_seq(&self, topic: &str) -> u64 {
+        self.last_seq.get(topic).copied().unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn envelope(seq: u64) -> WsEnvelope<()> {
+        WsEnvelope {
+            version: ENVELOPE_VERSION,
+            topic: "opportunities".into(),
+            seq,
+            server_ts_ns: 0,
+            schema_id: "opportunity.v1".into(),
+            payload: (),
+        }
+    }
+
+    #[test]
+    fn test_gap_and_duplicate_detection() {
+        let mut tracker = SequenceTracker::new();
+        assert_eq!(tracker.observe(&envelope(1)), SequenceCheck::InOrder);
+        assert_eq!(tracker.observe(&envelope(2)), SequenceCheck::InOrder);
+        assert_eq!(tracker.observe(&envelope(2)), SequenceCheck::Duplicate);
+        assert_eq!(tracker.observe(&envelope(5)), SequenceCheck::Gap { expected: 3, received: 5 });
+        assert_eq!(tracker.last_seq("opportunities"), 5);
+    }
+}
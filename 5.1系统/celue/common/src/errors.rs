@@ -55,3 +55,164 @@ pub enum OrchestratorError {
 
 pub type StrategyResult<T> = Result<T, StrategyError>;
 pub type OrchestratorResult<T> = Result<T, OrchestratorError>;
+
+/// Market data failures
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum DataError {
+    #[error("No market data for {symbol} on {exchange}")]
+    Unavailable { symbol: String, exchange: String },
+
+    #[error("Stale market data for {symbol}: {age_ms}ms old")]
+    Stale { symbol: String, age_ms: u64 },
+
+    #[error("Malformed market data: {0}")]
+    Malformed(String),
+
+    #[error("Market data feed disconnected: {0}")]
+    Disconnected(String),
+}
+
+/// Order placement failures
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ExecutionError {
+    #[error("Order rejected by {exchange} ({code}): {message}")]
+    Exchange { exchange: String, code: String, message: String },
+
+    #[error("Execution timed out after {timeout_ms}ms")]
+    Timeout { timeout_ms: u64 },
+
+    #[error("Opportunity {0} expired before execution")]
+    Expired(String),
+
+    #[error("Execution refused: {0}")]
+    Refused(String),
+}
+
+/// Pre-trade risk failures
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum RiskError {
+    #[error("Risk limit breached: {0}")]
+    LimitBreached(String),
+
+    #[error("Insufficient funds: required {required}, available {available}")]
+    InsufficientFunds { required: f64, available: f64 },
+
+    #[error("Trading halted: {0}")]
+    Halted(String),
+
+    #[error("Unknown profile: {0}")]
+    UnknownProfile(String),
+}
+
+/// Persistence failures
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum StorageError {
+    #[error("Record not found: {0}")]
+    NotFound(String),
+
+    #[error("Storage backend error: {0}")]
+    Backend(String),
+
+    #[error("Stored data is corrupt: {0}")]
+    Corrupt(String),
+}
+
+/// Top-level error surfaced by public APIs; every variant maps to a stable
+/// error code for `ApiResponse`
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum SystemError {
+    #[error(transparent)]
+    Data(#[from] DataError),
+
+    #[error(transparent)]
+    Execution(#[from] ExecutionError),
+
+    #[error(transparent)]
+    Risk(#[from] RiskError),
+
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+
+    #[error("Invalid configuration: {0}")]
+    Configuration(String),
+
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Service unavailable: {0}")]
+    Unavailable(String),
+
+    #[error("Rate limited, retry after {retry_after_secs}s")]
+    RateLimited { retry_after_secs: u64 },
+
+    #[error("Internal error: {0}")]
+    Internal(String),
+}
+
+impl SystemError {
+    /// Stable machine-readable code
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Data(DataError::Unavailable { .. }) => "DATA_UNAVAILABLE",
+            Self::Data(DataError::Stale { .. }) => "DATA_STALE",
+            Self::Data(DataError::Malformed(_)) => "DATA_MALFORMED",
+            Self::Data(DataError::Disconnected(_)) => "DATA_DISCONNECTED",
+            Self::Execution(ExecutionError::Exchange { .. }) => "EXEC_EXCHANGE_REJECTED",
+            Self::Execution(ExecutionError::Timeout { .. }) => "EXEC_TIMEOUT",
+            Self::Execution(ExecutionError::Expired(_)) => "EXEC_EXPIRED",
+            Self::Execution(ExecutionError::Refused(_)) => "EXEC_REFUSED",
+            Self::Risk(RiskError::LimitBreached(_)) => "RISK_LIMIT_BREACHED",
+            Self::Risk(RiskError::InsufficientFunds { .. }) => "RISK_INSUFFICIENT_FUNDS",
+            Self::Risk(RiskError::Halted(_)) => "RISK_HALTED",
+            Self::Risk(RiskError::UnknownProfile(_)) => "RISK_UNKNOWN_PROFILE",
+            Self::Storage(StorageError::NotFound(_)) => "STORAGE_NOT_FOUND",
+            Self::Storage(StorageError::Backend(_)) => "STORAGE_BACKEND",
+            Self::Storage(StorageError::Corrupt(_)) => "STORAGE_CORRUPT",
+            Self::Configuration(_) => "CONFIGURATION",
+            Self::InvalidRequest(_) => "INVALID_REQUEST",
+            Self::Unauthorized(_) => "UNAUTHORIZED",
+            Self::Forbidden(_) => "FORBIDDEN",
+            Self::Unavailable(_) => "UNAVAILABLE",
+            Self::RateLimited { .. } => "RATE_LIMITED",
+            Self::Internal(_) => "INTERNAL",
+        }
+    }
+
+    /// Whether retrying the same call may succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::Data(DataError::Stale { .. } | DataError::Disconnected(_))
+                | Self::Execution(ExecutionError::Timeout { .. })
+                | Self::Storage(StorageError::Backend(_))
+                | Self::Unavailable(_)
+                | Self::RateLimited { .. }
+        )
+    }
+}
+
+pub type SystemResult<T> = Result<T, SystemError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pagination::ApiResponse;
+
+    #[test]
+    fn test_error_codes_reach_api_response() {
+        let error: SystemError = RiskError::Halted("binance".into()).into();
+        assert_eq!(error.code(), "RISK_HALTED");
+        assert!(!error.is_retryable());
+
+        let response: ApiResponse<()> = error.into();
+        assert!(!response.success);
+        assert_eq!(response.error_code.as_deref(), Some("RISK_HALTED"));
+        assert_eq!(response.error.as_deref(), Some("Trading halted: binance"));
+    }
+}
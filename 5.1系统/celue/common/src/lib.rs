@@ -1,9 +1,20 @@
 pub mod arbitrage;
+pub mod clock;
+pub mod envelope;
+pub mod errors;
 pub mod market_data;
+pub mod number_profile;
+pub mod pagination;
 pub mod precision;
+pub mod symbols;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod types;
 
-pub use arbitrage::{ArbitrageOpportunity, ArbitrageLeg, Side};
+pub use arbitrage::{ArbitrageOpportunity, ArbitrageLeg, MarketType, Side};
+pub use clock::{Clock, SharedClock, SimulatedClock, SystemClock};
+pub use errors::{DataError, ExecutionError, RiskError, StorageError, SystemError, SystemResult};
 pub use market_data::{NormalizedSnapshot, OrderBook};
+pub use pagination::{ApiResponse, Page, PageQuery, Pageable, SortOrder};
 pub use precision::{FixedPrice, FixedQuantity};
-pub use types::{Exchange, Symbol, ExecutionResult, TraceId, IdempotencyKey};
+pub use types::{Exchange, Symbol, ExecutionResult, LegExecutionMode, SliceReport, TraceId, IdempotencyKey};
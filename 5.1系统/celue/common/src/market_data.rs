@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 
 /// Order book entry with fixed-point precision
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[repr(C)]
 pub struct OrderBookEntry {
     pub price: FixedPrice,
     pub quantity: FixedQuantity,
@@ -56,6 +57,119 @@ pub struct NormalizedSnapshot {
     pub sequence: Option<u64>,
 }
 
+/// Top-of-book quotes of every exchange in a snapshot, one array per field
+///
+/// Detection scans best bid/ask across exchanges far more often than it
+/// walks depth. Keeping the four quote fields in contiguous `f64` arrays
+/// lets those scans stay in a few cache lines and auto-vectorize, instead
+/// of chasing one heap-allocated `Vec` per `OrderBook`.
+#[derive(Debug, Clone, Default)]
+#[repr(C)]
+pub struct TopOfBook {
+    pub bid_prices: Vec<f64>,
+    pub bid_quantities: Vec<f64>,
+    pub ask_prices: Vec<f64>,
+    pub ask_quantities: Vec<f64>,
+    /// Index into `NormalizedSnapshot::exchanges` for each row
+    pub book_index: Vec<u32>,
+}
+
+impl TopOfBook {
+    /// Build from books that have both a bid and an ask
+    pub fn from_books(books: &[OrderBook]) -> Self {
+        let mut top = Self {
+            bid_prices: Vec::with_capacity(books.len()),
+            bid_quantities: Vec::with_capacity(books.len()),
+            ask_prices: Vec::with_capacity(books.len()),
+            ask_quantities: Vec::with_capacity(books.len()),
+            book_index: Vec::with_capacity(books.len()),
+        };
+        for (i, book) in books.iter().enumerate() {
+            if let Some(next) = books.get(i + 1) {
+                prefetch_read(next);
+            }
+            let (Some(bid), Some(ask)) = (book.best_bid_entry(), book.best_ask_entry()) else { continue };
+            top.bid_prices.push(bid.price.to_f64());
+            top.bid_quantities.push(bid.quantity.to_f64());
+            top.ask_prices.push(ask.price.to_f64());
+            top.ask_quantities.push(ask.quantity.to_f64());
+            top.book_index.push(i as u32);
+        }
+        top
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.book_index.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.book_index.is_empty()
+    }
+
+    /// Highest bid and lowest ask across exchanges as (row, price)
+    #[inline]
+    pub fn extremes(&self) -> Option<((usize, f64), (usize, f64))> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut best_bid = (0, self.bid_prices[0]);
+        let mut best_ask = (0, self.ask_prices[0]);
+        for (i, (&bid, &ask)) in self.bid_prices.iter().zip(&self.ask_prices).enumerate().skip(1) {
+            if bid > best_bid.1 {
+                best_bid = (i, bid);
+            }
+            if ask < best_ask.1 {
+                best_ask = (i, ask);
+            }
+        }
+        Some((best_bid, best_ask))
+    }
+
+    /// Whether any exchange's bid crosses another exchange's ask
+    #[inline]
+    pub fn has_cross(&self) -> bool {
+        let Some(((bid_row, bid), (ask_row, ask))) = self.extremes() else { return false };
+        if bid_row != ask_row {
+            return bid > ask;
+        }
+        // Best bid and ask on the same venue: compare against the runners-up
+        let second_bid = self
+            .bid_prices
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != bid_row)
+            .fold(f64::NEG_INFINITY, |best, (_, &p)| best.max(p));
+        let second_ask = self
+            .ask_prices
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != ask_row)
+            .fold(f64::INFINITY, |best, (_, &p)| best.min(p));
+        second_bid > ask || bid > second_ask
+    }
+}
+
+/// Hint the CPU to start loading `value` into cache
+#[inline(always)]
+pub fn prefetch_read<T>(value: &T) {
+    #[cfg(target_arch = "x86_64")]
+    // SAFETY: prefetch is a hint and never faults, even for invalid addresses
+    unsafe {
+        std::arch::x86_64::_mm_prefetch(value as *const T as *const i8, std::arch::x86_64::_MM_HINT_T0);
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    let _ = value;
+}
+
+impl NormalizedSnapshot {
+    /// Struct-of-arrays view of every exchange's top of book
+    pub fn top_of_book(&self) -> TopOfBook {
+        TopOfBook::from_books(&self.exchanges)
+    }
+}
+
 impl OrderBook {
     /// Create a new empty order book
     pub fn new(exchange: Exchange, symbol: Symbol, timestamp_ns: u64, sequence: u64) -> Self {
@@ -90,6 +204,7 @@ impl OrderBook {
     }
 
     /// Get best bid entry (highest buy price) - returns None if no bids
+    #[inline]
     pub fn best_bid_entry(&self) -> Option<OrderBookEntry> {
         if self.bid_prices.is_empty() {
             return None;
@@ -101,6 +216,7 @@ impl OrderBook {
     }
     
     /// Get best ask entry (lowest sell price) - returns None if no asks
+    #[inline]
     pub fn best_ask_entry(&self) -> Option<OrderBookEntry> {
         if self.ask_prices.is_empty() {
             return None;
@@ -112,11 +228,13 @@ impl OrderBook {
     }
     
     /// Get best bid (backward compatibility)
+    #[inline]
     pub fn best_bid(&self) -> Option<OrderBookEntry> {
         self.best_bid_entry()
     }
 
     /// Get best ask (backward compatibility)
+    #[inline]
     pub fn best_ask(&self) -> Option<OrderBookEntry> {
         self.best_ask_entry()
     }
@@ -160,6 +278,23 @@ mod tests {
         assert_eq!(ob.best_bid().unwrap().quantity, qty);
     }
 
+    #[test]
+    fn test_top_of_book_cross_detection() {
+        let book = |exchange: &str, bid: f64, ask: f64| {
+            let mut ob = OrderBook::new(Exchange::new(exchange), Symbol::new("BTCUSDT"), 0, 0);
+            ob.add_bid(FixedPrice::from_f64(bid, 2), FixedQuantity::from_f64(1.0, 8));
+            ob.add_ask(FixedPrice::from_f64(ask, 2), FixedQuantity::from_f64(1.0, 8));
+            ob
+        };
+
+        let top = TopOfBook::from_books(&[book("binance", 100.0, 100.5), book("okx", 100.2, 100.7)]);
+        assert_eq!(top.len(), 2);
+        assert!(!top.has_cross());
+
+        let top = TopOfBook::from_books(&[book("binance", 100.0, 100.5), book("okx", 100.8, 101.0)]);
+        assert!(top.has_cross());
+    }
+
     #[test]
     fn test_spread_calculation() {
         let exchange = Exchange::new("binance");
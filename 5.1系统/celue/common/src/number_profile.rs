@@ -0,0 +1,244 @@
+//! Decimal-safe serialization profile for API payloads
+//!
+//! By default payloads are plain JSON and money fields are f64 numbers,
+//! which the frontend renders with 0.1+0.2 style artifacts. Clients can
+//! opt into the decimal profile, either with the
+//! `application/vnd.celue.decimal+json` media type (or a `numbers=string`
+//! media-type parameter) in `Accept`, or with a `numbers=string` query
+//! parameter. Under that profile money fields are emitted as strings,
+//! rounded through the fixed-point types at the precision configured for
+//! the object's exchange, and `FixedPrice`/`FixedQuantity` values are
+//! emitted exactly at their own scale. Consumers that ask for nothing keep
+//! getting the existing JSON.
+
+use crate::pagination::ApiResponse;
+use crate::precision::FixedPrice;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// Media type selecting the decimal profile
+pub const DECIMAL_MEDIA_TYPE: &str = "application/vnd.celue.decimal+json";
+
+/// Fields emitted as decimal strings under the decimal profile
+const MONEY_FIELDS: [&str; 24] = [
+    "price", "amount", "quantity", "notional", "fee", "fees", "cost", "profit", "gross_profit",
+    "net_profit", "pnl", "net", "realized", "unrealized", "funding", "transfer_costs", "balance",
+    "free", "locked", "total", "min_notional", "tick_size", "lot_size", "value",
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NumberProfile {
+    /// Numbers as JSON numbers (existing behaviour)
+    #[default]
+    Float,
+    /// Money fields as decimal strings
+    DecimalString,
+}
+
+impl NumberProfile {
+    /// Profile requested by an `Accept` header
+    pub fn from_accept(accept: &str) -> Self {
+        let wants_decimal = accept.split(',').any(|range| {
+            let mut parts = range.split(';').map(str::trim);
+            let media = parts.next().unwrap_or_default();
+            media.eq_ignore_ascii_case(DECIMAL_MEDIA_TYPE)
+                || parts.any(|p| p.replace(' ', "").eq_ignore_ascii_case("numbers=string"))
+        });
+        if wants_decimal {
+            NumberProfile::DecimalString
+        } else {
+            NumberProfile::Float
+        }
+    }
+
+    /// Profile requested by a query string such as `limit=10&numbers=string`
+    pub fn from_query(query: &str) -> Option<Self> {
+        query.trim_start_matches('?').split('&').find_map(|pair| {
+            let (key, value) = pair.split_once('=')?;
+            (key == "numbers").then_some(match value {
+                "string" | "decimal" => NumberProfile::DecimalString,
+                _ => NumberProfile::Float,
+            })
+        })
+    }
+
+    /// The query parameter wins over the `Accept` header
+    pub fn negotiate(accept: Option<&str>, query: Option<&str>) -> Self {
+        query
+            .and_then(Self::from_query)
+            .or_else(|| accept.map(Self::from_accept))
+            .unwrap_or_default()
+    }
+}
+
+/// Which fields are money and at what precision they are emitted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecimalPolicy {
+    pub money_fields: HashSet<String>,
+    /// Fields ending in `_usd` are always treated as money
+    pub usd_suffix: bool,
+    pub default_scale: u8,
+    /// Precision for objects carrying an `exchange` field
+    pub exchange_scales: HashMap<String, u8>,
+    /// Per-field overrides, taking precedence over the exchange precision
+    pub field_scales: HashMap<String, u8>,
+}
+
+impl Default for DecimalPolicy {
+    fn default() -> Self {
+        Self {
+            money_fields: MONEY_FIELDS.iter().map(|f| f.to_string()).collect(),
+            usd_suffix: true,
+            default_scale: 8,
+            exchange_scales: HashMap::new(),
+            field_scales: HashMap::new(),
+        }
+    }
+}
+
+impl DecimalPolicy {
+    pub fn with_exchange_scale(mut self, exchange: &str, scale: u8) -> Self {
+        self.exchange_scales.insert(exchange.to_lowercase(), scale);
+        self
+    }
+
+    pub fn with_field_scale(mut self, field: &str, scale: u8) -> Self {
+        self.field_scales.insert(field.to_string(), scale);
+        self
+    }
+
+    fn is_money(&self, field: &str) -> bool {
+        self.money_fields.contains(field) || (self.usd_suffix && field.ends_with("_usd"))
+    }
+
+    fn scale(&self, field: &str, exchange: Option<&str>) -> u8 {
+        self.field_scales
+            .get(field)
+            .or_else(|| exchange.and_then(|e| self.exchange_scales.get(&e.to_lowercase())))
+            .copied()
+            .unwrap_or(self.default_scale)
+    }
+
+    /// Rewrite a serialized payload for the decimal profile
+    pub fn apply(&self, value: &mut Value) {
+        self.rewrite(value, None, None);
+    }
+
+    fn rewrite(&self, value: &mut Value, field: Option<&str>, exchange: Option<&str>) {
+        match value {
+            Value::Object(map) => {
+                if let Some(exact) = fixed_point_string(map) {
+                    *value = Value::String(exact);
+                    return;
+                }
+                let exchange = map
+                    .get("exchange")
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+                    .or_else(|| exchange.map(str::to_string));
+                for (key, child) in map.iter_mut() {
+                    self.rewrite(child, Some(key), exchange.as_deref());
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.rewrite(item, field, exchange);
+                }
+            }
+            Value::Number(n) => {
+                let Some(field) = field.filter(|f| self.is_money(f)) else { return };
+                let Some(v) = n.as_f64() else { return };
+                let scale = self.scale(field, exchange);
+                let fixed = FixedPrice::from_f64(v, scale);
+                *value = Value::String(format_fixed(fixed.raw(), fixed.scale()));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `{"raw": .., "scale": ..}` as produced by the fixed-point types
+fn fixed_point_string(map: &serde_json::Map<String, Value>) -> Option<String> {
+    if map.len() != 2 {
+        return None;
+    }
+    let raw = map.get("raw")?.as_i64()?;
+    let scale = u8::try_from(map.get("scale")?.as_u64()?).ok()?;
+    Some(format_fixed(raw, scale))
+}
+
+/// Exact decimal rendering of a fixed-point value
+pub fn format_fixed(raw: i64, scale: u8) -> String {
+    let digits = raw.unsigned_abs().to_string();
+    let scale = scale as usize;
+    let sign = if raw < 0 { "-" } else { "" };
+    if scale == 0 {
+        return format!("{}{}", sign, digits);
+    }
+    let padded = format!("{:0>width$}", digits, width = scale + 1);
+    let (int, frac) = padded.split_at(padded.len() - scale);
+    format!("{}{}.{}", sign, int, frac)
+}
+
+impl<T: Serialize> ApiResponse<T> {
+    /// Serialize for the negotiated profile
+    pub fn to_json_profiled(&self, profile: NumberProfile, policy: &DecimalPolicy) -> serde_json::Result<Vec<u8>> {
+        match profile {
+            NumberProfile::Float => serde_json::to_vec(self),
+            NumberProfile::DecimalString => {
+                let mut value = serde_json::to_value(self)?;
+                if let Some(data) = value.get_mut("data") {
+                    policy.apply(data);
+                }
+                serde_json::to_vec(&value)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::precision::FixedQuantity;
+
+    #[derive(Serialize)]
+    struct Fill {
+        exchange: &'static str,
+        price: f64,
+        quantity: FixedQuantity,
+        profit_usd: f64,
+        trades: u32,
+    }
+
+    #[test]
+    fn test_negotiation_and_decimal_strings() {
+        assert_eq!(NumberProfile::negotiate(Some("application/json"), None), NumberProfile::Float);
+        assert_eq!(NumberProfile::negotiate(Some(DECIMAL_MEDIA_TYPE), None), NumberProfile::DecimalString);
+        assert_eq!(NumberProfile::negotiate(Some("application/json; numbers=string"), None), NumberProfile::DecimalString);
+        assert_eq!(NumberProfile::negotiate(Some(DECIMAL_MEDIA_TYPE), Some("numbers=float")), NumberProfile::Float);
+        assert_eq!(NumberProfile::negotiate(None, Some("?page=2&numbers=string")), NumberProfile::DecimalString);
+
+        let response = ApiResponse::ok(vec![Fill {
+            exchange: "okx",
+            price: 0.1 + 0.2,
+            quantity: FixedQuantity::from_raw(-1_500, 4),
+            profit_usd: 12.345,
+            trades: 3,
+        }]);
+        let policy = DecimalPolicy::default().with_exchange_scale("OKX", 2).with_field_scale("profit_usd", 3);
+
+        let plain: Value = serde_json::from_slice(&response.to_json_profiled(NumberProfile::Float, &policy).unwrap()).unwrap();
+        assert_eq!(plain["data"][0]["price"], serde_json::json!(0.30000000000000004));
+
+        let decimal: Value =
+            serde_json::from_slice(&response.to_json_profiled(NumberProfile::DecimalString, &policy).unwrap()).unwrap();
+        let fill = &decimal["data"][0];
+        assert_eq!(fill["price"], "0.30");
+        assert_eq!(fill["quantity"], "-0.1500");
+        assert_eq!(fill["profit_usd"], "12.345");
+        assert_eq!(fill["trades"], 3);
+        assert_eq!(decimal["success"], true);
+    }
+}
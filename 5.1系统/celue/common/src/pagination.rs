@@ -0,0 +1,246 @@
+//! Cursor-based pagination for history endpoints
+//!
+//! History queries (fees, opportunities, orders, alerts) take a
+//! `PageQuery` with an opaque cursor, a page size, a time-ordered sort
+//! direction, an optional time range and exact-match field filters. Pages
+//! use keyset pagination on (timestamp, id), so results stay stable while
+//! new records are appended. `ApiResponse` carries the next cursor back to
+//! the caller.
+
+use crate::errors::SystemError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// Page size used when the query does not specify one
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+/// Upper bound on the page size
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum PaginationError {
+    #[error("Invalid cursor: {0}")]
+    InvalidCursor(String),
+}
+
+/// Sort direction by record time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    /// Newest first
+    #[default]
+    Desc,
+}
+
+/// Query parameters shared by history endpoints
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PageQuery {
+    /// `next_cursor` from the previous page
+    #[serde(default)]
+    pub cursor: Option<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub order: SortOrder,
+    /// Inclusive lower bound on record time (ns)
+    #[serde(default)]
+    pub from_ns: Option<u64>,
+    /// Exclusive upper bound on record time (ns)
+    #[serde(default)]
+    pub to_ns: Option<u64>,
+    /// Exact-match filters on record fields, e.g. {"exchange": "binance"}
+    #[serde(default)]
+    pub filters: BTreeMap<String, String>,
+}
+
+impl PageQuery {
+    pub fn with_filter(mut self, field: &str, value: &str) -> Self {
+        self.filters.insert(field.to_string(), value.to_string());
+        self
+    }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn with_order(mut self, order: SortOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn page_size(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+    }
+}
+
+/// A record that can be paged through
+pub trait Pageable {
+    /// Record time in nanoseconds
+    fn timestamp_ns(&self) -> u64;
+    /// Tie-breaker for records with the same timestamp
+    fn record_id(&self) -> String;
+    /// Value of a filterable field, None if the field is unknown
+    fn field(&self, name: &str) -> Option<String>;
+}
+
+/// One page of results
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Cursor for the following page, None on the last page
+    pub next_cursor: Option<String>,
+}
+
+fn encode_cursor(timestamp_ns: u64, id: &str) -> String {
+    format!("{}:{}", timestamp_ns, id)
+}
+
+fn decode_cursor(cursor: &str) -> Result<(u64, String), PaginationError> {
+    let (ts, id) = cursor
+        .split_once(':')
+        .ok_or_else(|| PaginationError::InvalidCursor(cursor.to_string()))?;
+    let ts = ts.parse().map_err(|_| PaginationError::InvalidCursor(cursor.to_string()))?;
+    Ok((ts, id.to_string()))
+}
+
+/// Filter, sort and slice `records` according to `query`
+pub fn paginate<T: Pageable>(
+    records: impl IntoIterator<Item = T>,
+    query: &PageQuery,
+) -> Result<Page<T>, PaginationError> {
+    let after = query.cursor.as_deref().map(decode_cursor).transpose()?;
+    let mut matched: Vec<(u64, String, T)> = records
+        .into_iter()
+        .filter(|r| query.from_ns.is_none_or(|from| r.timestamp_ns() >= from))
+        .filter(|r| query.to_ns.is_none_or(|to| r.timestamp_ns() < to))
+        .filter(|r| query.filters.iter().all(|(k, v)| r.field(k).as_deref() == Some(v.as_str())))
+        .map(|r| (r.timestamp_ns(), r.record_id(), r))
+        .collect();
+    matched.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+    if query.order == SortOrder::Desc {
+        matched.reverse();
+    }
+    if let Some((ts, id)) = after {
+        let key = (ts, id);
+        matched.retain(|(t, i, _)| match query.order {
+            SortOrder::Asc => (*t, i) > (key.0, &key.1),
+            SortOrder::Desc => (*t, i) < (key.0, &key.1),
+        });
+    }
+
+    let size = query.page_size();
+    let has_more = matched.len() > size;
+    matched.truncate(size);
+    let next_cursor = if has_more {
+        matched.last().map(|(t, i, _)| encode_cursor(*t, i))
+    } else {
+        None
+    };
+    Ok(Page {
+        items: matched.into_iter().map(|(_, _, r)| r).collect(),
+        next_cursor,
+    })
+}
+
+/// Standard response body for API endpoints
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiResponse<T> {
+    pub success: bool,
+    pub data: Option<T>,
+    pub error: Option<String>,
+    /// Machine-readable code from `SystemError::code`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+    /// Set on paged responses that have more results
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+impl<T> ApiResponse<T> {
+    pub fn ok(data: T) -> Self {
+        Self { success: true, data: Some(data), error: None, error_code: None, next_cursor: None }
+    }
+
+    pub fn err(error: impl ToString) -> Self {
+        Self { success: false, data: None, error: Some(error.to_string()), error_code: None, next_cursor: None }
+    }
+}
+
+impl<T> From<SystemError> for ApiResponse<T> {
+    fn from(error: SystemError) -> Self {
+        Self { error_code: Some(error.code().to_string()), ..Self::err(error) }
+    }
+}
+
+impl<T> From<Result<T, SystemError>> for ApiResponse<T> {
+    fn from(result: Result<T, SystemError>) -> Self {
+        match result {
+            Ok(data) => Self::ok(data),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl<T> ApiResponse<Vec<T>> {
+    pub fn page(page: Page<T>) -> Self {
+        Self { success: true, data: Some(page.items), error: None, error_code: None, next_cursor: page.next_cursor }
+    }
+}
+
+impl<T> From<Result<Page<T>, PaginationError>> for ApiResponse<Vec<T>> {
+    fn from(result: Result<Page<T>, PaginationError>) -> Self {
+        match result {
+            Ok(page) => Self::page(page),
+            Err(e) => SystemError::InvalidRequest(e.to_string()).into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Fee {
+        ts: u64,
+        exchange: &'static str,
+    }
+
+    impl Pageable for Fee {
+        fn timestamp_ns(&self) -> u64 {
+            self.ts
+        }
+        fn record_id(&self) -> String {
+            self.exchange.to_string()
+        }
+        fn field(&self, name: &str) -> Option<String> {
+            (name == "exchange").then(|| self.exchange.to_string())
+        }
+    }
+
+    #[test]
+    fn test_cursor_walks_filtered_pages() {
+        let fees: Vec<Fee> = (1..=5)
+            .flat_map(|ts| [Fee { ts, exchange: "binance" }, Fee { ts, exchange: "okx" }])
+            .collect();
+        let query = PageQuery::default().with_filter("exchange", "binance").with_limit(2);
+
+        let first = paginate(fees.clone(), &query).unwrap();
+        assert_eq!(first.items.iter().map(|f| f.ts).collect::<Vec<_>>(), vec![5, 4]);
+        let cursor = first.next_cursor.clone().unwrap();
+
+        let second = paginate(fees.clone(), &PageQuery { cursor: Some(cursor), ..query.clone() }).unwrap();
+        assert_eq!(second.items.iter().map(|f| f.ts).collect::<Vec<_>>(), vec![3, 2]);
+
+        let last = paginate(fees.clone(), &PageQuery { cursor: second.next_cursor, ..query.clone() }).unwrap();
+        assert_eq!(last.items.len(), 1);
+        assert!(last.next_cursor.is_none());
+
+        let response: ApiResponse<Vec<Fee>> = paginate(fees.clone(), &query.with_order(SortOrder::Asc)).into();
+        assert_eq!(response.data.unwrap()[0].ts, 1);
+        assert!(response.next_cursor.is_some());
+        assert!(paginate(fees, &PageQuery { cursor: Some("bogus".into()), ..Default::default() }).is_err());
+    }
+}
@@ -0,0 +1,265 @@
+//! Symbol metadata and per-exchange normalization
+//!
+//! Internally every instrument is named by its canonical symbol
+//! ("BTC/USDT"); exchanges use their own spellings (BTCUSDT, BTC-USDT,
+//! XBT/USD). The registry maps between the two and holds each venue's
+//! trading rules — tick size, lot size and minimum notional — so orders
+//! can be rounded and validated before they are sent.
+
+use crate::types::Symbol;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Asset codes some venues use instead of the common one
+const ASSET_ALIASES: [(&str, &str); 3] = [("XBT", "BTC"), ("XDG", "DOGE"), ("BCC", "BCH")];
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum SymbolError {
+    #[error("Unknown symbol {symbol} on {exchange}")]
+    Unknown { exchange: String, symbol: String },
+    #[error("Quantity {quantity} is below the lot size {lot_size} for {symbol}")]
+    BelowLotSize { symbol: String, quantity: f64, lot_size: f64 },
+    #[error("Notional {notional} is below the minimum {min_notional} for {symbol}")]
+    BelowMinNotional { symbol: String, notional: f64, min_notional: f64 },
+}
+
+/// Trading rules for one symbol on one exchange
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SymbolSpec {
+    pub exchange: String,
+    /// Canonical "BASE/QUOTE"
+    pub canonical: String,
+    /// Exchange-native name, e.g. "BTCUSDT"
+    pub native: String,
+    pub base: String,
+    pub quote: String,
+    pub tick_size: f64,
+    pub lot_size: f64,
+    pub min_notional: f64,
+}
+
+impl SymbolSpec {
+    pub fn new(exchange: &str, native: &str, base: &str, quote: &str) -> Self {
+        let base = canonical_asset(base);
+        let quote = canonical_asset(quote);
+        Self {
+            exchange: exchange.to_lowercase(),
+            canonical: format!("{}/{}", base, quote),
+            native: native.to_string(),
+            base,
+            quote,
+            tick_size: 0.0,
+            lot_size: 0.0,
+            min_notional: 0.0,
+        }
+    }
+
+    pub fn with_rules(mut self, tick_size: f64, lot_size: f64, min_notional: f64) -> Self {
+        self.tick_size = tick_size;
+        self.lot_size = lot_size;
+        self.min_notional = min_notional;
+        self
+    }
+
+    /// Round a price to the nearest tick
+    pub fn round_price(&self, price: f64) -> f64 {
+        round_to_step(price, self.tick_size, f64::round)
+    }
+
+    /// Round a quantity down to a whole number of lots
+    pub fn round_quantity(&self, quantity: f64) -> f64 {
+        round_to_step(quantity, self.lot_size, f64::floor)
+    }
+
+    /// Check an already rounded order against lot size and minimum notional
+    pub fn validate(&self, price: f64, quantity: f64) -> Result<(), SymbolError> {
+        if quantity <= 0.0 || (self.lot_size > 0.0 && quantity < self.lot_size) {
+            return Err(SymbolError::BelowLotSize {
+                symbol: self.canonical.clone(),
+                quantity,
+                lot_size: self.lot_size,
+            });
+        }
+        let notional = price * quantity;
+        if notional < self.min_notional {
+            return Err(SymbolError::BelowMinNotional {
+                symbol: self.canonical.clone(),
+                notional,
+                min_notional: self.min_notional,
+            });
+        }
+        Ok(())
+    }
+}
+
+fn round_to_step(value: f64, step: f64, round: fn(f64) -> f64) -> f64 {
+    if step <= 0.0 {
+        return value;
+    }
+    // Small epsilon keeps 0.3 / 0.1 from flooring to 2 lots
+    let steps = round(value / step + 1e-9);
+    // Re-derive the decimals from the step to avoid 0.30000000000000004
+    let decimals = (-step.log10()).ceil().max(0.0) as i32;
+    let factor = 10f64.powi(decimals);
+    (steps * step * factor).round() / factor
+}
+
+fn canonical_asset(asset: &str) -> String {
+    let upper = asset.to_uppercase();
+    ASSET_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == upper)
+        .map(|(_, canonical)| canonical.to_string())
+        .unwrap_or(upper)
+}
+
+/// Strip separators and case so "btc-usdt", "BTC_USDT" and "BTCUSDT" compare equal
+fn alias_key(symbol: &str) -> String {
+    symbol
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_uppercase()
+}
+
+#[derive(Default)]
+struct RegistryState {
+    /// (exchange, canonical) -> spec
+    specs: HashMap<(String, String), SymbolSpec>,
+    /// (exchange, alias key) -> canonical
+    aliases: HashMap<(String, String), String>,
+}
+
+/// Canonical symbols, per-exchange aliases and trading rules
+#[derive(Default)]
+pub struct SymbolRegistry {
+    state: RwLock<RegistryState>,
+}
+
+impl SymbolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or update a single symbol
+    pub fn register(&self, spec: SymbolSpec) {
+        let mut state = self.state.write();
+        Self::insert(&mut state, spec);
+    }
+
+    /// Replace every symbol of an exchange, e.g. after an exchangeInfo refresh
+    pub fn replace_exchange(&self, exchange: &str, specs: Vec<SymbolSpec>) {
+        let exchange = exchange.to_lowercase();
+        let mut state = self.state.write();
+        state.specs.retain(|(e, _), _| *e != exchange);
+        state.aliases.retain(|(e, _), _| *e != exchange);
+        for spec in specs {
+            Self::insert(&mut state, SymbolSpec { exchange: exchange.clone(), ..spec });
+        }
+    }
+
+    fn insert(state: &mut RegistryState, spec: SymbolSpec) {
+        let exchange = spec.exchange.clone();
+        for alias in [&spec.native, &spec.canonical] {
+            state.aliases.insert((exchange.clone(), alias_key(alias)), spec.canonical.clone());
+        }
+        // Accept the venue's asset aliases as well, e.g. XBTUSD for BTC/USD
+        for (alias, canonical) in ASSET_ALIASES {
+            if spec.base == canonical {
+                let key = alias_key(&format!("{}{}", alias, spec.quote));
+                state.aliases.insert((exchange.clone(), key), spec.canonical.clone());
+            }
+        }
+        state.specs.insert((exchange, spec.canonical.clone()), spec);
+    }
+
+    /// Canonical symbol for an exchange-native name
+    pub fn to_canonical(&self, exchange: &str, native: &str) -> Option<Symbol> {
+        self.state
+            .read()
+            .aliases
+            .get(&(exchange.to_lowercase(), alias_key(native)))
+            .map(|c| Symbol::new(c.clone()))
+    }
+
+    /// Exchange-native name for a canonical symbol
+    pub fn to_native(&self, exchange: &str, canonical: &str) -> Option<String> {
+        self.spec(exchange, canonical).map(|s| s.native)
+    }
+
+    /// Rules for a symbol; accepts either the canonical or the native name
+    pub fn spec(&self, exchange: &str, symbol: &str) -> Option<SymbolSpec> {
+        let exchange = exchange.to_lowercase();
+        let state = self.state.read();
+        let canonical = state.aliases.get(&(exchange.clone(), alias_key(symbol)))?;
+        state.specs.get(&(exchange, canonical.clone())).cloned()
+    }
+
+    /// Round price and quantity to the venue's rules and validate the result
+    pub fn prepare_order(&self, exchange: &str, symbol: &str, price: f64, quantity: f64) -> Result<(f64, f64), SymbolError> {
+        let spec = self.spec(exchange, symbol).ok_or_else(|| SymbolError::Unknown {
+            exchange: exchange.to_string(),
+            symbol: symbol.to_string(),
+        })?;
+        let price = spec.round_price(price);
+        let quantity = spec.round_quantity(quantity);
+        spec.validate(price, quantity)?;
+        Ok((price, quantity))
+    }
+
+    /// Exchanges listing a canonical symbol
+    pub fn exchanges_for(&self, canonical: &str) -> Vec<String> {
+        let mut exchanges: Vec<String> = self
+            .state
+            .read()
+            .specs
+            .keys()
+            .filter(|(_, c)| c == canonical)
+            .map(|(e, _)| e.clone())
+            .collect();
+        exchanges.sort();
+        exchanges
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.read().specs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aliases_rounding_and_validation() {
+        let registry = SymbolRegistry::new();
+        registry.register(SymbolSpec::new("binance", "BTCUSDT", "BTC", "USDT").with_rules(0.01, 0.001, 5.0));
+        registry.register(SymbolSpec::new("okx", "BTC-USDT", "BTC", "USDT").with_rules(0.1, 0.0001, 1.0));
+        registry.register(SymbolSpec::new("kraken", "XBT/USD", "XBT", "USD").with_rules(0.1, 0.0001, 1.0));
+
+        assert_eq!(registry.to_canonical("binance", "BTCUSDT").unwrap().as_str(), "BTC/USDT");
+        assert_eq!(registry.to_canonical("okx", "btc-usdt").unwrap().as_str(), "BTC/USDT");
+        assert_eq!(registry.to_canonical("kraken", "XBTUSD").unwrap().as_str(), "BTC/USD");
+        assert_eq!(registry.to_native("okx", "BTC/USDT").as_deref(), Some("BTC-USDT"));
+        assert_eq!(registry.exchanges_for("BTC/USDT"), vec!["binance", "okx"]);
+
+        assert_eq!(registry.prepare_order("binance", "BTC/USDT", 50_000.004, 0.0029).unwrap(), (50_000.0, 0.002));
+        assert!(matches!(
+            registry.prepare_order("binance", "BTC/USDT", 50_000.0, 0.0009),
+            Err(SymbolError::BelowLotSize { .. })
+        ));
+        assert!(matches!(
+            registry.prepare_order("binance", "BTC/USDT", 1_000.0, 0.001),
+            Err(SymbolError::BelowMinNotional { .. })
+        ));
+
+        registry.replace_exchange("okx", vec![]);
+        assert!(registry.spec("okx", "BTC/USDT").is_none());
+    }
+}
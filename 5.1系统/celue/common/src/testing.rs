@@ -0,0 +1,95 @@
+//! Shared test fixtures for legs, opportunities, order books and snapshots.
+//!
+//! Compiled for this crate's own tests and, through the `testing` feature,
+//! for the dev-dependencies of the other workspace crates.
+
+use crate::arbitrage::{ArbitrageLeg, ArbitrageOpportunity, MarketType, Side};
+use crate::market_data::{NormalizedSnapshot, OrderBook};
+use crate::precision::{FixedPrice, FixedQuantity};
+use crate::types::{Exchange, Symbol};
+
+/// A leg trading `quantity` at `price`; `cost` is their product.
+pub fn leg(exchange: &str, symbol: &str, side: Side, price: f64, quantity: f64) -> ArbitrageLeg {
+    ArbitrageLeg {
+        exchange: Exchange::new(exchange),
+        symbol: Symbol::new(symbol),
+        market: MarketType::Spot,
+        side,
+        price: FixedPrice::from_f64(price, 2),
+        quantity: FixedQuantity::from_f64(quantity, 4),
+        cost: FixedPrice::from_f64(price * quantity, 2),
+    }
+}
+
+/// Buy one unit of `symbol` on binance and sell it on okx, both at 100.
+pub fn inter_exchange_opportunity(strategy: &str, symbol: &str, net_profit_pct: f64) -> ArbitrageOpportunity {
+    ArbitrageOpportunity::new_inter_exchange(
+        strategy,
+        leg("binance", symbol, Side::Buy, 100.0, 1.0),
+        leg("okx", symbol, Side::Sell, 100.0, 1.0),
+        FixedPrice::from_f64(1.0, 2),
+        FixedPrice::from_f64(net_profit_pct, 6),
+        0,
+    )
+}
+
+/// An opportunity over arbitrary legs with a net profit of 1.
+pub fn opportunity_with_legs(strategy: &str, legs: Vec<ArbitrageLeg>, net_profit_pct: f64, created_at_ns: u64) -> ArbitrageOpportunity {
+    ArbitrageOpportunity::new_with_legs(
+        strategy,
+        legs,
+        FixedPrice::from_f64(1.0, 2),
+        FixedPrice::from_f64(net_profit_pct, 6),
+        created_at_ns,
+    )
+}
+
+/// A one-level book with unit size on both sides.
+pub fn book(exchange: &str, symbol: &str, bid: f64, ask: f64, timestamp_ns: u64) -> OrderBook {
+    sized_book(exchange, symbol, (bid, 1.0), (ask, 1.0), timestamp_ns)
+}
+
+/// A one-level book with the given `(price, size)` on each side.
+pub fn sized_book(exchange: &str, symbol: &str, bid: (f64, f64), ask: (f64, f64), timestamp_ns: u64) -> OrderBook {
+    let mut book = OrderBook::new(Exchange::new(exchange), Symbol::new(symbol), timestamp_ns, 0);
+    book.add_bid(FixedPrice::from_f64(bid.0, 2), FixedQuantity::from_f64(bid.1, 4));
+    book.add_ask(FixedPrice::from_f64(ask.0, 2), FixedQuantity::from_f64(ask.1, 4));
+    book
+}
+
+/// A snapshot of `books` for `symbol`, weighted mid and volumes taken from their top levels.
+pub fn snapshot(symbol: &str, books: Vec<OrderBook>, timestamp_ns: u64) -> NormalizedSnapshot {
+    let price = |levels: &[FixedPrice]| levels.first().map(|p| p.to_f64()).unwrap_or(0.0);
+    let size = |levels: &[FixedQuantity]| levels.first().map(|q| q.to_f64()).unwrap_or(0.0);
+    let count = books.len().max(1) as f64;
+    let mid = books.iter().map(|b| (price(&b.bid_prices) + price(&b.ask_prices)) / 2.0).sum::<f64>() / count;
+    let bid_volume: f64 = books.iter().map(|b| size(&b.bid_quantities)).sum();
+    let ask_volume: f64 = books.iter().map(|b| size(&b.ask_quantities)).sum();
+    NormalizedSnapshot {
+        symbol: Symbol::new(symbol),
+        timestamp_ns,
+        weighted_mid_price: FixedPrice::from_f64(mid, 2),
+        total_bid_volume: FixedQuantity::from_f64(bid_volume, 4),
+        total_ask_volume: FixedQuantity::from_f64(ask_volume, 4),
+        exchanges: books,
+        quality_score: 1.0,
+        sequence: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_aggregates_top_of_book() {
+        let snapshot = snapshot(
+            "BTCUSDT",
+            vec![book("binance", "BTCUSDT", 100.0, 101.0, 1), book("okx", "BTCUSDT", 102.0, 103.0, 1)],
+            1,
+        );
+        assert_eq!(snapshot.weighted_mid_price.to_f64(), 101.5);
+        assert_eq!(snapshot.total_bid_volume.to_f64(), 2.0);
+        assert_eq!(inter_exchange_opportunity("inter_exchange", "BTCUSDT", 0.01).legs[0].cost.to_f64(), 100.0);
+    }
+}
@@ -10,12 +10,41 @@ pub struct ExecutionResult {
     pub order_ids: Vec<String>,
     pub opportunity_id: String,
     pub trace_id: Option<String>,
+    /// Execution mode chosen for each leg, in leg order
+    #[serde(default)]
+    pub leg_modes: Vec<LegExecutionMode>,
+    /// Child-order fills when a leg was sliced
+    #[serde(default)]
+    pub slices: Vec<SliceReport>,
 }
 
 impl ExecutionResult {
-    pub fn accepted(opportunity_id: String, order_ids: Vec<String>, trace_id: Option<String>) -> Self { Self { success: true, details: "accepted".into(), executed_quantity: None, average_price: None, order_ids, opportunity_id, trace_id } }
-    pub fn rejected(opportunity_id: String, reason: String, trace_id: Option<String>) -> Self { Self { success: false, details: reason, executed_quantity: None, average_price: None, order_ids: vec![], opportunity_id, trace_id } }
-    pub fn partial(opportunity_id: String, order_ids: Vec<String>, details: String, trace_id: Option<String>) -> Self { Self { success: false, details, executed_quantity: None, average_price: None, order_ids, opportunity_id, trace_id } }
+    pub fn accepted(opportunity_id: String, order_ids: Vec<String>, trace_id: Option<String>) -> Self { Self { success: true, details: "accepted".into(), executed_quantity: None, average_price: None, order_ids, opportunity_id, trace_id, leg_modes: vec![], slices: vec![] } }
+    pub fn rejected(opportunity_id: String, reason: String, trace_id: Option<String>) -> Self { Self { success: false, details: reason, executed_quantity: None, average_price: None, order_ids: vec![], opportunity_id, trace_id, leg_modes: vec![], slices: vec![] } }
+    pub fn partial(opportunity_id: String, order_ids: Vec<String>, details: String, trace_id: Option<String>) -> Self { Self { success: false, details, executed_quantity: None, average_price: None, order_ids, opportunity_id, trace_id, leg_modes: vec![], slices: vec![] } }
+}
+
+/// Fill of one child order of a sliced leg
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SliceReport {
+    pub leg_index: usize,
+    pub slice_index: usize,
+    pub order_id: String,
+    pub quantity: f64,
+    pub price: f64,
+    /// Slippage against the leg's reference price in bps (positive = worse)
+    pub slippage_bps: f64,
+}
+
+/// How a single leg was executed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LegExecutionMode {
+    /// Passive limit order resting on the book
+    Maker,
+    /// Aggressive market/IOC order
+    Taker,
+    /// Maker order that timed out and was crossed as taker
+    MakerFallbackTaker,
 }
 
 /// Exchange identifier
@@ -90,7 +119,7 @@ pub enum ExecutionMode {
 }
 
 /// Strategy type classification
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum StrategyKind {
     InterExchange,
     Triangular,
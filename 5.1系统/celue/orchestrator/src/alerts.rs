@@ -0,0 +1,520 @@
+//! 告警处置流程
+//!
+//! 告警按去重键归并为处置单（case）：同一键重复触发只累加次数。处置单
+//! 可以确认（附操作人与备注）、指派给处理人、在到期前静默，或附根因
+//! 标签关闭。已关闭的告警再次触发时重新打开，静默到期后自动恢复为待
+//! 处理。处置单持久化到可插拔存储，查询服务为看板提供按严重级别的
+//! 未关闭告警数。变更操作需要 `AcknowledgeAlert` 权限，配置了审计日志
+//! 时一并落审计。
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use adapters::alerting::{Alert, AlertManager, AlertSeverity};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::audit::AuditLog;
+use crate::auth::{AuthService, ControlAction, Principal};
+use crate::error::storage_error;
+use crate::nats::{encode_reply, NatsManager};
+use common::{ApiResponse, StorageError, SystemError, SystemResult};
+
+/// 处置状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaseStatus {
+    Open,
+    Acknowledged,
+    Snoozed,
+    Resolved,
+}
+
+/// 处置备注
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaseNote {
+    pub actor: String,
+    pub text: String,
+    pub at: DateTime<Utc>,
+}
+
+/// 状态变更记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaseTransition {
+    pub actor: String,
+    pub from: CaseStatus,
+    pub to: CaseStatus,
+    pub at: DateTime<Utc>,
+}
+
+/// 告警处置单
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertCase {
+    /// 告警去重键
+    pub key: String,
+    pub severity: AlertSeverity,
+    pub title: String,
+    pub source: String,
+    pub last_message: String,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    pub occurrences: u64,
+    pub status: CaseStatus,
+    pub assignee: Option<String>,
+    pub acknowledged_by: Option<String>,
+    pub snoozed_until: Option<DateTime<Utc>>,
+    /// 关闭时填写的根因标签，如 "exchange_outage"、"config_error"
+    pub root_cause: Option<String>,
+    pub notes: Vec<CaseNote>,
+    pub transitions: Vec<CaseTransition>,
+}
+
+impl AlertCase {
+    fn open(alert: &Alert) -> Self {
+        Self {
+            key: alert.key.clone(),
+            severity: alert.severity,
+            title: alert.title.clone(),
+            source: alert.source.clone(),
+            last_message: alert.message.clone(),
+            first_seen: alert.timestamp,
+            last_seen: alert.timestamp,
+            occurrences: 1,
+            status: CaseStatus::Open,
+            assignee: None,
+            acknowledged_by: None,
+            snoozed_until: None,
+            root_cause: None,
+            notes: Vec::new(),
+            transitions: Vec::new(),
+        }
+    }
+
+    /// 静默到期的处置单视为待处理
+    pub fn effective_status(&self, now: DateTime<Utc>) -> CaseStatus {
+        match (self.status, self.snoozed_until) {
+            (CaseStatus::Snoozed, Some(until)) if until <= now => CaseStatus::Open,
+            (status, _) => status,
+        }
+    }
+
+    fn transition(&mut self, actor: &str, to: CaseStatus, note: Option<String>, at: DateTime<Utc>) {
+        self.transitions.push(CaseTransition { actor: actor.to_string(), from: self.status, to, at });
+        self.status = to;
+        if let Some(text) = note.filter(|n| !n.trim().is_empty()) {
+            self.notes.push(CaseNote { actor: actor.to_string(), text, at });
+        }
+    }
+}
+
+/// 处置单查询条件
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AlertCaseQuery {
+    pub status: Option<CaseStatus>,
+    pub severity: Option<AlertSeverity>,
+    pub assignee: Option<String>,
+    pub source: Option<String>,
+    pub limit: Option<usize>,
+}
+
+impl AlertCaseQuery {
+    fn matches(&self, case: &AlertCase, now: DateTime<Utc>) -> bool {
+        self.status.is_none_or(|s| case.effective_status(now) == s)
+            && self.severity.is_none_or(|s| case.severity == s)
+            && self.assignee.as_ref().is_none_or(|a| case.assignee.as_ref() == Some(a))
+            && self.source.as_ref().is_none_or(|s| &case.source == s)
+    }
+}
+
+/// 处置单存储后端
+#[async_trait]
+pub trait AlertCaseStore: Send + Sync {
+    async fn upsert(&self, case: &AlertCase) -> Result<()>;
+    async fn get(&self, key: &str) -> Result<Option<AlertCase>>;
+    async fn all(&self) -> Result<Vec<AlertCase>>;
+}
+
+/// 内存存储
+#[derive(Default)]
+pub struct MemoryAlertCaseStore {
+    cases: RwLock<HashMap<String, AlertCase>>,
+}
+
+impl MemoryAlertCaseStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl AlertCaseStore for MemoryAlertCaseStore {
+    async fn upsert(&self, case: &AlertCase) -> Result<()> {
+        self.cases.write().insert(case.key.clone(), case.clone());
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<AlertCase>> {
+        Ok(self.cases.read().get(key).cloned())
+    }
+
+    async fn all(&self) -> Result<Vec<AlertCase>> {
+        Ok(self.cases.read().values().cloned().collect())
+    }
+}
+
+/// 本地文件存储：启动时加载，每次变更整体写回
+pub struct FileAlertCaseStore {
+    path: PathBuf,
+    cases: tokio::sync::Mutex<Option<HashMap<String, AlertCase>>>,
+}
+
+impl FileAlertCaseStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), cases: tokio::sync::Mutex::new(None) }
+    }
+
+    async fn load(&self) -> Result<HashMap<String, AlertCase>> {
+        match tokio::fs::read(&self.path).await {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[async_trait]
+impl AlertCaseStore for FileAlertCaseStore {
+    async fn upsert(&self, case: &AlertCase) -> Result<()> {
+        let mut guard = self.cases.lock().await;
+        if guard.is_none() {
+            *guard = Some(self.load().await?);
+        }
+        let cases = guard.get_or_insert_with(HashMap::new);
+        cases.insert(case.key.clone(), case.clone());
+        if let Some(dir) = self.path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        let tmp = self.path.with_extension("tmp");
+        tokio::fs::write(&tmp, serde_json::to_vec(cases)?).await?;
+        tokio::fs::rename(&tmp, &self.path).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<AlertCase>> {
+        let mut guard = self.cases.lock().await;
+        if guard.is_none() {
+            *guard = Some(self.load().await?);
+        }
+        Ok(guard.as_ref().and_then(|c| c.get(key).cloned()))
+    }
+
+    async fn all(&self) -> Result<Vec<AlertCase>> {
+        let mut guard = self.cases.lock().await;
+        if guard.is_none() {
+            *guard = Some(self.load().await?);
+        }
+        Ok(guard.as_ref().map(|c| c.values().cloned().collect()).unwrap_or_default())
+    }
+}
+
+/// 看板摘要
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AlertSummary {
+    /// 按严重级别的未关闭告警数（不含静默中的）
+    pub unresolved: BTreeMap<AlertSeverity, usize>,
+    pub snoozed: usize,
+    /// 未关闭且未指派
+    pub unassigned: usize,
+}
+
+/// 处置请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum AlertCommand {
+    List { query: AlertCaseQuery },
+    Get { key: String },
+    Summary,
+    Acknowledge { key: String, note: Option<String> },
+    Assign { key: String, assignee: String, note: Option<String> },
+    Snooze { key: String, until: DateTime<Utc>, note: Option<String> },
+    Resolve { key: String, root_cause: String, note: Option<String> },
+}
+
+impl AlertCommand {
+    fn action(&self) -> ControlAction {
+        match self {
+            AlertCommand::List { .. } | AlertCommand::Get { .. } | AlertCommand::Summary => ControlAction::ViewDashboard,
+            _ => ControlAction::AcknowledgeAlert,
+        }
+    }
+}
+
+/// 处置响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AlertReply {
+    Cases { cases: Vec<AlertCase> },
+    Case { case: Box<AlertCase> },
+    Summary { summary: AlertSummary },
+}
+
+/// 告警处置服务
+pub struct AlertWorkflow {
+    store: Arc<dyn AlertCaseStore>,
+    auth: Option<Arc<AuthService>>,
+    audit: Option<Arc<AuditLog>>,
+}
+
+impl AlertWorkflow {
+    pub fn new(store: Arc<dyn AlertCaseStore>) -> Self {
+        Self { store, auth: None, audit: None }
+    }
+
+    pub fn with_auth(mut self, auth: Arc<AuthService>) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    pub fn with_audit(mut self, audit: Arc<AuditLog>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// 登记一次告警：新建、累加次数，或重新打开已关闭的处置单
+    pub async fn observe(&self, alert: &Alert) -> SystemResult<AlertCase> {
+        let case = match self.store.get(&alert.key).await.map_err(storage_error)? {
+            None => AlertCase::open(alert),
+            Some(mut case) => {
+                case.occurrences += 1;
+                case.last_seen = alert.timestamp;
+                case.last_message = alert.message.clone();
+                case.severity = case.severity.max(alert.severity);
+                if case.effective_status(alert.timestamp) == CaseStatus::Open && case.status == CaseStatus::Snoozed {
+                    case.snoozed_until = None;
+                    case.transition("system", CaseStatus::Open, None, alert.timestamp);
+                }
+                if case.status == CaseStatus::Resolved {
+                    info!("🔁 已关闭的告警再次触发: {}", case.key);
+                    case.root_cause = None;
+                    case.acknowledged_by = None;
+                    case.severity = alert.severity;
+                    case.transition("system", CaseStatus::Open, None, alert.timestamp);
+                }
+                case
+            }
+        };
+        self.store.upsert(&case).await.map_err(storage_error)?;
+        Ok(case)
+    }
+
+    /// 订阅告警管理器，持续登记告警
+    pub fn track(self: Arc<Self>, alerts: &AlertManager) -> tokio::task::JoinHandle<()> {
+        let mut raised = alerts.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match raised.recv().await {
+                    Ok(alert) => {
+                        if let Err(e) = self.observe(&alert).await {
+                            warn!("告警处置单登记失败 {}: {}", alert.key, e);
+                        }
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                        warn!("告警处置订阅落后，丢失 {} 条告警", n);
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    async fn load(&self, key: &str) -> SystemResult<AlertCase> {
+        self.store
+            .get(key)
+            .await
+            .map_err(storage_error)?
+            .ok_or_else(|| StorageError::NotFound(format!("alert {}", key)).into())
+    }
+
+    pub async fn acknowledge(&self, actor: &str, key: &str, note: Option<String>) -> SystemResult<AlertCase> {
+        let mut case = self.load(key).await?;
+        if case.status == CaseStatus::Resolved {
+            return Err(SystemError::InvalidRequest(format!("告警 {} 已关闭", key)));
+        }
+        case.acknowledged_by = Some(actor.to_string());
+        case.snoozed_until = None;
+        case.transition(actor, CaseStatus::Acknowledged, note, Utc::now());
+        self.store.upsert(&case).await.map_err(storage_error)?;
+        Ok(case)
+    }
+
+    /// 指派处理人，不改变状态
+    pub async fn assign(&self, actor: &str, key: &str, assignee: &str, note: Option<String>) -> SystemResult<AlertCase> {
+        let mut case = self.load(key).await?;
+        case.assignee = Some(assignee.to_string());
+        let status = case.status;
+        let note = Some(match note {
+            Some(text) => format!("指派给 {}: {}", assignee, text),
+            None => format!("指派给 {}", assignee),
+        });
+        case.transition(actor, status, note, Utc::now());
+        self.store.upsert(&case).await.map_err(storage_error)?;
+        Ok(case)
+    }
+
+    pub async fn snooze(&self, actor: &str, key: &str, until: DateTime<Utc>, note: Option<String>) -> SystemResult<AlertCase> {
+        let now = Utc::now();
+        if until <= now {
+            return Err(SystemError::InvalidRequest("静默截止时间必须晚于当前时间".to_string()));
+        }
+        let mut case = self.load(key).await?;
+        if case.status == CaseStatus::Resolved {
+            return Err(SystemError::InvalidRequest(format!("告警 {} 已关闭", key)));
+        }
+        case.snoozed_until = Some(until);
+        case.transition(actor, CaseStatus::Snoozed, note, now);
+        self.store.upsert(&case).await.map_err(storage_error)?;
+        Ok(case)
+    }
+
+    pub async fn resolve(&self, actor: &str, key: &str, root_cause: &str, note: Option<String>) -> SystemResult<AlertCase> {
+        if root_cause.trim().is_empty() {
+            return Err(SystemError::InvalidRequest("关闭告警需要根因标签".to_string()));
+        }
+        let mut case = self.load(key).await?;
+        case.root_cause = Some(root_cause.to_string());
+        case.snoozed_until = None;
+        case.transition(actor, CaseStatus::Resolved, note, Utc::now());
+        self.store.upsert(&case).await.map_err(storage_error)?;
+        Ok(case)
+    }
+
+    /// 按最近触发时间倒序
+    pub async fn query(&self, query: &AlertCaseQuery) -> SystemResult<Vec<AlertCase>> {
+        let now = Utc::now();
+        let mut cases: Vec<AlertCase> = self
+            .store
+            .all()
+            .await
+            .map_err(storage_error)?
+            .into_iter()
+            .filter(|c| query.matches(c, now))
+            .collect();
+        cases.sort_by_key(|c| std::cmp::Reverse(c.last_seen));
+        cases.truncate(query.limit.unwrap_or(usize::MAX));
+        Ok(cases)
+    }
+
+    pub async fn summary(&self) -> SystemResult<AlertSummary> {
+        let now = Utc::now();
+        let mut summary = AlertSummary::default();
+        for case in self.store.all().await.map_err(storage_error)? {
+            match case.effective_status(now) {
+                CaseStatus::Resolved => continue,
+                CaseStatus::Snoozed => summary.snoozed += 1,
+                CaseStatus::Open | CaseStatus::Acknowledged => {
+                    *summary.unresolved.entry(case.severity).or_insert(0) += 1;
+                    if case.assignee.is_none() {
+                        summary.unassigned += 1;
+                    }
+                }
+            }
+        }
+        Ok(summary)
+    }
+
+    async fn handle(&self, principal: Option<&Principal>, command: &AlertCommand) -> SystemResult<AlertReply> {
+        let actor = principal.map_or("anonymous", |p| p.subject.as_str());
+        let case = match command {
+            AlertCommand::List { query } => return Ok(AlertReply::Cases { cases: self.query(query).await? }),
+            AlertCommand::Get { key } => self.load(key).await?,
+            AlertCommand::Summary => return Ok(AlertReply::Summary { summary: self.summary().await? }),
+            AlertCommand::Acknowledge { key, note } => self.acknowledge(actor, key, note.clone()).await?,
+            AlertCommand::Assign { key, assignee, note } => self.assign(actor, key, assignee, note.clone()).await?,
+            AlertCommand::Snooze { key, until, note } => self.snooze(actor, key, *until, note.clone()).await?,
+            AlertCommand::Resolve { key, root_cause, note } => self.resolve(actor, key, root_cause, note.clone()).await?,
+        };
+        Ok(AlertReply::Case { case: Box::new(case) })
+    }
+
+    /// 处置服务：请求体为 `AlertCommand` JSON，响应为带错误码的 `ApiResponse`
+    pub async fn serve(self: Arc<Self>, nats: &NatsManager, subject: &str) -> Result<()> {
+        let mut subscriber = nats.subscribe(subject).await?;
+        info!("🔔 告警处置服务已启动: {}", subject);
+
+        while let Some(message) = subscriber.next().await {
+            let Some(reply) = message.reply.clone() else { continue };
+            let result = async {
+                let command = serde_json::from_slice::<AlertCommand>(&message.payload)
+                    .map_err(|e| SystemError::InvalidRequest(e.to_string()))?;
+                let action = command.action();
+                let principal = match &self.auth {
+                    Some(auth) => Some(auth.authorize_nats(&message, action)?),
+                    None => None,
+                };
+                let reply = self.handle(principal.as_ref(), &command).await?;
+                if let (ControlAction::AcknowledgeAlert, Some(audit), Some(principal)) = (action, &self.audit, &principal) {
+                    let details = serde_json::to_value(&command).unwrap_or_default();
+                    if let AlertReply::Case { case } = &reply {
+                        audit.record(principal, action, &case.key, details).await?;
+                    }
+                }
+                Ok::<_, SystemError>(reply)
+            }
+            .await;
+            let response: ApiResponse<AlertReply> = result.into();
+            if let Err(e) = nats
+                .get_client()
+                .publish(reply, encode_reply(&message, &response)?.into())
+                .await
+            {
+                warn!("告警处置响应发送失败: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_case_lifecycle_and_summary() {
+        let workflow = AlertWorkflow::new(Arc::new(MemoryAlertCaseStore::new()));
+        let disconnect = Alert::new("exchange_disconnected:okx", AlertSeverity::Critical, "okx 断线", "ws closed", "okx");
+        let lag = Alert::new("feed_lag:binance", AlertSeverity::Warning, "行情延迟", "lag 800ms", "binance");
+        workflow.observe(&disconnect).await.unwrap();
+        workflow.observe(&disconnect).await.unwrap();
+        workflow.observe(&lag).await.unwrap();
+
+        let case = workflow.acknowledge("alice", &disconnect.key, Some("查看中".into())).await.unwrap();
+        assert_eq!(case.occurrences, 2);
+        assert_eq!(case.status, CaseStatus::Acknowledged);
+        assert_eq!(case.notes[0].actor, "alice");
+        workflow.assign("alice", &disconnect.key, "bob", None).await.unwrap();
+        workflow.snooze("alice", &lag.key, Utc::now() + chrono::Duration::hours(1), None).await.unwrap();
+
+        let summary = workflow.summary().await.unwrap();
+        assert_eq!(summary.unresolved.get(&AlertSeverity::Critical), Some(&1));
+        assert_eq!(summary.unresolved.get(&AlertSeverity::Warning), None);
+        assert_eq!(summary.snoozed, 1);
+        assert_eq!(summary.unassigned, 0);
+
+        assert!(workflow.resolve("bob", &disconnect.key, " ", None).await.is_err());
+        workflow.resolve("bob", &disconnect.key, "exchange_outage", None).await.unwrap();
+        assert!(workflow.summary().await.unwrap().unresolved.is_empty());
+
+        // 关闭后再次触发重新打开
+        let reopened = workflow.observe(&disconnect).await.unwrap();
+        assert_eq!(reopened.status, CaseStatus::Open);
+        assert_eq!(reopened.root_cause, None);
+        let query = AlertCaseQuery { status: Some(CaseStatus::Open), ..Default::default() };
+        assert_eq!(workflow.query(&query).await.unwrap().len(), 1);
+    }
+}
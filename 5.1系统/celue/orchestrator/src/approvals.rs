@@ -0,0 +1,149 @@
+//! 大额机会人工审批服务
+//!
+//! 所需资金超过阈值的机会由引擎放入审批队列，看板通过本服务查询待审批
+//! 机会并批准或拒绝；审批事件同时推送到 WebSocket `approvals` 主题。
+//! 批准/拒绝需要 `ApproveExecutions` 权限并落审计，查询需要
+//! `ViewDashboard`；未配置鉴权时拒绝批准/拒绝请求。超过截止时间未执行的
+//! 审批自动失效。
+
+use std::sync::Arc;
+
+use adapters::approval::{ApprovalGate, ApprovalRequest};
+use anyhow::Result;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::audit::AuditLog;
+use crate::auth::{AuthService, ControlAction};
+use crate::nats::{encode_reply, NatsManager};
+use common::{ApiResponse, StorageError, SystemError, SystemResult};
+
+/// 审批请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum ApprovalCommand {
+    /// 待审批及已批准待执行的机会
+    Pending,
+    /// 最近已决的审批
+    History {
+        #[serde(default = "default_history_limit")]
+        limit: usize,
+    },
+    Get { opportunity_id: Uuid },
+    Approve { opportunity_id: Uuid },
+    Reject {
+        opportunity_id: Uuid,
+        #[serde(default)]
+        reason: Option<String>,
+    },
+}
+
+fn default_history_limit() -> usize {
+    50
+}
+
+impl ApprovalCommand {
+    fn action(&self) -> ControlAction {
+        match self {
+            ApprovalCommand::Approve { .. } | ApprovalCommand::Reject { .. } => ControlAction::ApproveExecutions,
+            _ => ControlAction::ViewDashboard,
+        }
+    }
+}
+
+/// 审批响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ApprovalReply {
+    Requests { requests: Vec<ApprovalRequest> },
+    Request { request: Box<ApprovalRequest> },
+}
+
+/// 人工审批服务
+pub struct ApprovalService {
+    gate: Arc<ApprovalGate>,
+    auth: Option<Arc<AuthService>>,
+    audit: Option<Arc<AuditLog>>,
+}
+
+impl ApprovalService {
+    pub fn new(gate: Arc<ApprovalGate>) -> Self {
+        Self { gate, auth: None, audit: None }
+    }
+
+    pub fn with_auth(mut self, auth: Arc<AuthService>) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    pub fn with_audit(mut self, audit: Arc<AuditLog>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    fn handle(&self, operator: &str, command: &ApprovalCommand) -> SystemResult<ApprovalReply> {
+        Ok(match command {
+            ApprovalCommand::Pending => ApprovalReply::Requests { requests: self.gate.pending() },
+            ApprovalCommand::History { limit } => ApprovalReply::Requests { requests: self.gate.history(*limit) },
+            ApprovalCommand::Get { opportunity_id } => ApprovalReply::Request {
+                request: Box::new(
+                    self.gate
+                        .get(opportunity_id)
+                        .ok_or_else(|| StorageError::NotFound(format!("approval {}", opportunity_id)))?,
+                ),
+            },
+            ApprovalCommand::Approve { opportunity_id } => {
+                ApprovalReply::Request { request: Box::new(self.gate.approve(opportunity_id, operator)?) }
+            }
+            ApprovalCommand::Reject { opportunity_id, reason } => {
+                ApprovalReply::Request { request: Box::new(self.gate.reject(opportunity_id, operator, reason.clone())?) }
+            }
+        })
+    }
+
+    /// 审批服务：请求体为 `ApprovalCommand` JSON，响应为带错误码的 `ApiResponse`
+    pub async fn serve(self: Arc<Self>, nats: &NatsManager, subject: &str) -> Result<()> {
+        let mut subscriber = nats.subscribe(subject).await?;
+        info!("✋ 人工审批服务已启动: {}", subject);
+
+        while let Some(message) = subscriber.next().await {
+            let Some(reply) = message.reply.clone() else { continue };
+            let result = async {
+                let command = serde_json::from_slice::<ApprovalCommand>(&message.payload)
+                    .map_err(|e| SystemError::InvalidRequest(e.to_string()))?;
+                let action = command.action();
+                let principal = match (&self.auth, action) {
+                    (Some(auth), _) => Some(auth.authorize_nats(&message, action)?),
+                    // 审批决定必须能追溯到操作人，未配置鉴权时只开放查询
+                    (None, ControlAction::ApproveExecutions) => {
+                        return Err(SystemError::Unauthorized("approval decisions require authentication".to_string()))
+                    }
+                    (None, _) => None,
+                };
+                let operator = principal.as_ref().map(|p| p.subject.as_str()).unwrap_or("anonymous");
+                let reply = self.handle(operator, &command)?;
+                if let (ControlAction::ApproveExecutions, Some(audit), Some(principal)) = (action, &self.audit, &principal) {
+                    let target = match &reply {
+                        ApprovalReply::Request { request } => request.opportunity.id.to_string(),
+                        ApprovalReply::Requests { .. } => String::new(),
+                    };
+                    let details = serde_json::to_value(&command).unwrap_or_default();
+                    audit.record(principal, action, &target, details).await?;
+                }
+                Ok::<_, SystemError>(reply)
+            }
+            .await;
+            let response: ApiResponse<ApprovalReply> = result.into();
+            if let Err(e) = nats
+                .get_client()
+                .publish(reply, encode_reply(&message, &response)?.into())
+                .await
+            {
+                warn!("人工审批响应发送失败: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,423 @@
+//! 管理面审计日志
+//!
+//! 记录每一次变更类管理调用（配置更新、策略启停、手动撤单、告警确认等）
+//! 的操作人、操作、对象与时间。每条记录携带前一条记录的哈希，构成
+//! blake3 哈希链，任何篡改或删除都会在校验时暴露。存储可插拔：内存实现
+//! 用于测试，PostgreSQL 实现需启用 `postgres` feature。查询服务按操作人、
+//! 操作类型和时间范围过滤。
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::auth::{AuthService, ControlAction, Credentials, Principal};
+use crate::error::storage_error;
+use crate::nats::{encode_reply, NatsManager};
+use crate::rate_limit::{apply_rejection_headers, RateLimiter};
+use common::{ApiResponse, SystemError, SystemResult};
+
+/// 链首记录的 prev_hash
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// 一条审计记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// 链内序号，从 1 开始
+    pub seq: u64,
+    pub timestamp_ns: i64,
+    pub actor: String,
+    pub action: ControlAction,
+    /// 操作对象，如策略名、订单ID、配置段
+    pub target: String,
+    /// 变更内容（新旧值等）
+    pub details: serde_json::Value,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+impl AuditEntry {
+    /// 按记录内容与前一哈希计算本条哈希；变长字段带长度前缀，
+    /// 字段边界移动（如 actor 尾部挪到 target 头部）也会改变哈希
+    pub fn compute_hash(&self) -> String {
+        let mut hasher = blake3::Hasher::new();
+        let mut field = |bytes: &[u8]| {
+            hasher.update(&(bytes.len() as u64).to_le_bytes());
+            hasher.update(bytes);
+        };
+        field(self.prev_hash.as_bytes());
+        field(&self.seq.to_le_bytes());
+        field(&self.timestamp_ns.to_le_bytes());
+        field(self.actor.as_bytes());
+        field(action_name(self.action).as_bytes());
+        field(self.target.as_bytes());
+        field(self.details.to_string().as_bytes());
+        hasher.finalize().to_hex().to_string()
+    }
+}
+
+/// 审计查询条件
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuditQuery {
+    pub actor: Option<String>,
+    pub action: Option<ControlAction>,
+    pub from_ns: Option<i64>,
+    pub to_ns: Option<i64>,
+    pub limit: Option<usize>,
+}
+
+impl AuditQuery {
+    fn matches(&self, entry: &AuditEntry) -> bool {
+        self.actor.as_ref().is_none_or(|a| &entry.actor == a)
+            && self.action.is_none_or(|a| entry.action == a)
+            && self.from_ns.is_none_or(|t| entry.timestamp_ns >= t)
+            && self.to_ns.is_none_or(|t| entry.timestamp_ns <= t)
+    }
+}
+
+/// 审计存储后端
+#[async_trait]
+pub trait AuditStore: Send + Sync {
+    async fn append(&self, entry: &AuditEntry) -> Result<()>;
+    /// 链尾记录
+    async fn last(&self) -> Result<Option<AuditEntry>>;
+    /// 按序号升序返回匹配记录
+    async fn query(&self, query: &AuditQuery) -> Result<Vec<AuditEntry>>;
+}
+
+/// 内存存储
+#[derive(Default)]
+pub struct MemoryAuditStore {
+    entries: RwLock<Vec<AuditEntry>>,
+}
+
+impl MemoryAuditStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl AuditStore for MemoryAuditStore {
+    async fn append(&self, entry: &AuditEntry) -> Result<()> {
+        self.entries.write().push(entry.clone());
+        Ok(())
+    }
+
+    async fn last(&self) -> Result<Option<AuditEntry>> {
+        Ok(self.entries.read().last().cloned())
+    }
+
+    async fn query(&self, query: &AuditQuery) -> Result<Vec<AuditEntry>> {
+        Ok(self
+            .entries
+            .read()
+            .iter()
+            .filter(|e| query.matches(e))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect())
+    }
+}
+
+/// PostgreSQL 存储
+#[cfg(feature = "postgres")]
+pub struct PostgresAuditStore {
+    client: tokio_postgres::Client,
+}
+
+#[cfg(feature = "postgres")]
+impl PostgresAuditStore {
+    /// 连接并建表，例如 `host=localhost user=celue dbname=celue`
+    pub async fn connect(config: &str) -> Result<Self> {
+        let (client, connection) = tokio_postgres::connect(config, tokio_postgres::NoTls).await?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                tracing::error!("审计库连接断开: {}", e);
+            }
+        });
+        client
+            .batch_execute(
+                "CREATE TABLE IF NOT EXISTS control_audit_log (
+                    seq BIGINT PRIMARY KEY,
+                    timestamp_ns BIGINT NOT NULL,
+                    actor TEXT NOT NULL,
+                    action TEXT NOT NULL,
+                    target TEXT NOT NULL,
+                    details JSONB NOT NULL,
+                    prev_hash TEXT NOT NULL,
+                    hash TEXT NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS control_audit_log_actor_ts ON control_audit_log (actor, timestamp_ns);
+                CREATE INDEX IF NOT EXISTS control_audit_log_action_ts ON control_audit_log (action, timestamp_ns);",
+            )
+            .await?;
+        Ok(Self { client })
+    }
+
+    fn from_row(row: &tokio_postgres::Row) -> Result<AuditEntry> {
+        let action: String = row.get("action");
+        Ok(AuditEntry {
+            seq: row.get::<_, i64>("seq") as u64,
+            timestamp_ns: row.get("timestamp_ns"),
+            actor: row.get("actor"),
+            action: serde_json::from_value(serde_json::Value::String(action))?,
+            target: row.get("target"),
+            details: serde_json::from_str(row.get::<_, &str>("details"))?,
+            prev_hash: row.get("prev_hash"),
+            hash: row.get("hash"),
+        })
+    }
+}
+
+#[cfg(feature = "postgres")]
+#[async_trait]
+impl AuditStore for PostgresAuditStore {
+    async fn append(&self, entry: &AuditEntry) -> Result<()> {
+        self.client
+            .execute(
+                "INSERT INTO control_audit_log (seq, timestamp_ns, actor, action, target, details, prev_hash, hash)
+                 VALUES ($1, $2, $3, $4, $5, $6::TEXT::JSONB, $7, $8)",
+                &[
+                    &(entry.seq as i64),
+                    &entry.timestamp_ns,
+                    &entry.actor,
+                    &action_name(entry.action),
+                    &entry.target,
+                    &entry.details.to_string(),
+                    &entry.prev_hash,
+                    &entry.hash,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn last(&self) -> Result<Option<AuditEntry>> {
+        let row = self
+            .client
+            .query_opt(
+                "SELECT seq, timestamp_ns, actor, action, target, details::TEXT AS details, prev_hash, hash
+                 FROM control_audit_log ORDER BY seq DESC LIMIT 1",
+                &[],
+            )
+            .await?;
+        row.as_ref().map(Self::from_row).transpose()
+    }
+
+    async fn query(&self, query: &AuditQuery) -> Result<Vec<AuditEntry>> {
+        let action = query.action.map(action_name);
+        let limit = query.limit.map(|l| l as i64);
+        let rows = self
+            .client
+            .query(
+                "SELECT seq, timestamp_ns, actor, action, target, details::TEXT AS details, prev_hash, hash
+                 FROM control_audit_log
+                 WHERE ($1::TEXT IS NULL OR actor = $1)
+                   AND ($2::TEXT IS NULL OR action = $2)
+                   AND ($3::BIGINT IS NULL OR timestamp_ns >= $3)
+                   AND ($4::BIGINT IS NULL OR timestamp_ns <= $4)
+                 ORDER BY seq ASC
+                 LIMIT $5",
+                &[&query.actor, &action, &query.from_ns, &query.to_ns, &limit],
+            )
+            .await?;
+        rows.iter().map(Self::from_row).collect()
+    }
+}
+
+fn action_name(action: ControlAction) -> String {
+    serde_json::to_value(action)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// 校验哈希链，返回第一条被篡改或断链的记录序号
+pub fn verify_chain(entries: &[AuditEntry]) -> Option<u64> {
+    let mut prev_hash = GENESIS_HASH.to_string();
+    for (expected_seq, entry) in (1..).zip(entries) {
+        if entry.seq != expected_seq || entry.prev_hash != prev_hash || entry.hash != entry.compute_hash() {
+            return Some(entry.seq);
+        }
+        prev_hash = entry.hash.clone();
+    }
+    None
+}
+
+/// 审计日志
+pub struct AuditLog {
+    store: Arc<dyn AuditStore>,
+    /// 链尾 (seq, hash)，首次写入时从存储加载
+    head: tokio::sync::Mutex<Option<(u64, String)>>,
+    auth: Option<Arc<AuthService>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+}
+
+impl AuditLog {
+    pub fn new(store: Arc<dyn AuditStore>) -> Self {
+        Self {
+            store,
+            head: tokio::sync::Mutex::new(None),
+            auth: None,
+            rate_limiter: None,
+        }
+    }
+
+    /// 查询服务按调用方限流（需同时配置鉴权）
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// 查询服务要求调用方具备看板权限
+    pub fn with_auth(mut self, auth: Arc<AuthService>) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// 记录一次变更操作
+    pub async fn record(
+        &self,
+        principal: &Principal,
+        action: ControlAction,
+        target: &str,
+        details: serde_json::Value,
+    ) -> SystemResult<AuditEntry> {
+        let mut head = self.head.lock().await;
+        if head.is_none() {
+            *head = Some(match self.store.last().await.map_err(storage_error)? {
+                Some(last) => (last.seq, last.hash),
+                None => (0, GENESIS_HASH.to_string()),
+            });
+        }
+        let (last_seq, prev_hash) = head.clone().unwrap_or((0, GENESIS_HASH.to_string()));
+
+        let mut entry = AuditEntry {
+            seq: last_seq + 1,
+            timestamp_ns: chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default(),
+            actor: principal.subject.clone(),
+            action,
+            target: target.to_string(),
+            details,
+            prev_hash,
+            hash: String::new(),
+        };
+        entry.hash = entry.compute_hash();
+        self.store.append(&entry).await.map_err(storage_error)?;
+        *head = Some((entry.seq, entry.hash.clone()));
+        info!("📝 审计: {} {:?} {}", entry.actor, entry.action, entry.target);
+        Ok(entry)
+    }
+
+    /// 鉴权并记录：管理接口处理变更请求前调用，拒绝的请求不落审计
+    pub async fn authorize_and_record(
+        &self,
+        auth: &AuthService,
+        credentials: Option<&Credentials>,
+        action: ControlAction,
+        target: &str,
+        details: serde_json::Value,
+    ) -> SystemResult<Principal> {
+        let principal = auth.authorize(credentials, action)?;
+        self.record(&principal, action, target, details).await?;
+        Ok(principal)
+    }
+
+    pub async fn query(&self, query: &AuditQuery) -> SystemResult<Vec<AuditEntry>> {
+        self.store.query(query).await.map_err(storage_error)
+    }
+
+    /// 校验完整哈希链
+    pub async fn verify(&self) -> SystemResult<Option<u64>> {
+        Ok(verify_chain(&self.query(&AuditQuery::default()).await?))
+    }
+
+    /// 审计查询服务：请求体为 `AuditQuery` JSON，响应为带错误码的 `ApiResponse`
+    pub async fn serve(self: Arc<Self>, nats: &NatsManager, subject: &str) -> Result<()> {
+        let mut subscriber = nats.subscribe(subject).await?;
+        info!("📝 审计查询服务已启动: {}", subject);
+
+        while let Some(message) = subscriber.next().await {
+            let Some(reply) = message.reply.clone() else { continue };
+            let mut headers = async_nats::HeaderMap::new();
+            let result = async {
+                match (&self.auth, &self.rate_limiter) {
+                    (Some(auth), Some(limiter)) => {
+                        let (_, usage) = limiter.guard_nats(auth, &message, ControlAction::ViewDashboard)?;
+                        headers = usage.nats_headers();
+                    }
+                    (Some(auth), None) => {
+                        auth.authorize_nats(&message, ControlAction::ViewDashboard)?;
+                    }
+                    _ => {}
+                }
+                let query = serde_json::from_slice::<AuditQuery>(&message.payload)
+                    .map_err(|e| SystemError::InvalidRequest(e.to_string()))?;
+                self.query(&query).await
+            }
+            .await;
+            if let Err(e) = &result {
+                apply_rejection_headers(&mut headers, e);
+            }
+            let response: ApiResponse<Vec<AuditEntry>> = result.into();
+            if let Err(e) = nats
+                .get_client()
+                .publish_with_headers(reply, headers, encode_reply(&message, &response)?.into())
+                .await
+            {
+                warn!("审计查询响应发送失败: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Role;
+
+    #[tokio::test]
+    async fn test_chain_query_and_tamper_detection() {
+        let store = Arc::new(MemoryAuditStore::new());
+        let log = AuditLog::new(store.clone());
+        let alice = Principal { subject: "alice".into(), roles: vec![Role::Admin], scopes: None, profiles: None };
+        let bob = Principal { subject: "bob".into(), roles: vec![Role::Operator], scopes: None, profiles: None };
+
+        log.record(&alice, ControlAction::UpdateConfig, "risk", serde_json::json!({ "max_daily_loss_usd": 5000 })).await.unwrap();
+        log.record(&bob, ControlAction::ToggleStrategy, "triangular", serde_json::json!({ "enabled": false })).await.unwrap();
+        log.record(&bob, ControlAction::CancelOrders, "binance:42", serde_json::Value::Null).await.unwrap();
+        assert_eq!(log.verify().await.unwrap(), None);
+
+        let by_bob = log.query(&AuditQuery { actor: Some("bob".into()), ..Default::default() }).await.unwrap();
+        assert_eq!(by_bob.len(), 2);
+        let cancels = log.query(&AuditQuery { action: Some(ControlAction::CancelOrders), ..Default::default() }).await.unwrap();
+        assert_eq!(cancels[0].target, "binance:42");
+
+        // 篡改第二条记录
+        store.entries.write()[1].details = serde_json::json!({ "enabled": true });
+        assert_eq!(log.verify().await.unwrap(), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_shifted_field_boundary_breaks_chain() {
+        let store = Arc::new(MemoryAuditStore::new());
+        let log = AuditLog::new(store.clone());
+        let admin = Principal { subject: "ops-admin".into(), roles: vec![Role::Admin], scopes: None, profiles: None };
+        log.record(&admin, ControlAction::ToggleStrategy, "triangular", serde_json::Value::Null).await.unwrap();
+        assert_eq!(log.verify().await.unwrap(), None);
+
+        // 拼接后的字节不变，但 actor/target 边界移动
+        {
+            let mut entries = store.entries.write();
+            entries[0].actor = "ops-admintri".into();
+            entries[0].target = "angular".into();
+        }
+        assert_eq!(log.verify().await.unwrap(), Some(1));
+    }
+}
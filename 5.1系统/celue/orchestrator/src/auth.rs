@@ -0,0 +1,461 @@
+//! 管理接口鉴权 - 基于角色的访问控制（RBAC）
+//!
+//! 角色分为 viewer / operator / risk-officer / admin。每个管理操作声明所需
+//! 权限：看板与状态查询只读，启停、配置更新、熔断复位等危险操作需要更高
+//! 角色。调用方可使用 JWT（HS256，`roles` 声明）或带作用域的 API Key；
+//! API Key 只保存 blake3 哈希，明文仅在签发时返回一次；配置了
+//! `ApiKeyStore` 时哈希与记录随签发/吊销写回存储，启动时重新加载。
+//! 多租户部署中，凭证可限定可访问的交易 profile。
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// 角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Role {
+    Viewer,
+    Operator,
+    RiskOfficer,
+    Admin,
+}
+
+/// 管理操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlAction {
+    /// 看板、状态、指标查询
+    ViewDashboard,
+    /// 启动/停止引擎或模块
+    StartStop,
+    /// 启用/禁用策略
+    ToggleStrategy,
+    /// 手动撤单
+    CancelOrders,
+    /// 确认告警
+    AcknowledgeAlert,
+    /// 更新运行配置
+    UpdateConfig,
+    /// 激活/解除局部熔断
+    ManageHalts,
+    /// 复位全局熔断（kill switch）
+    ResetKillSwitch,
+    /// 签发/吊销 API Key
+    ManageApiKeys,
+    /// 切换全局交易模式（干跑/影子/实盘）
+    SwitchTradingMode,
+    /// 启动/停止混沌实验
+    RunChaosExperiments,
+    /// 导出历史数据集
+    ExportData,
+    /// 发送操作员心跳（dead man's switch）
+    Heartbeat,
+    /// 向内部事件总线注入外部事件
+    InjectEvents,
+    /// 批准/拒绝需人工审批的大额机会
+    ApproveExecutions,
+}
+
+impl Role {
+    /// 角色拥有的操作权限
+    pub fn allows(&self, action: ControlAction) -> bool {
+        use ControlAction::*;
+        match self {
+            Role::Admin => true,
+            Role::Viewer => matches!(action, ViewDashboard),
+            Role::Operator => matches!(
+                action,
+                ViewDashboard | StartStop | ToggleStrategy | CancelOrders | AcknowledgeAlert | Heartbeat
+                    | ApproveExecutions
+            ),
+            Role::RiskOfficer => matches!(
+                action,
+                ViewDashboard | CancelOrders | AcknowledgeAlert | UpdateConfig | ManageHalts | ResetKillSwitch | ExportData
+                    | Heartbeat | ApproveExecutions
+            ),
+        }
+    }
+}
+
+/// 鉴权错误
+#[derive(Debug, thiserror::Error)]
+pub enum AuthError {
+    #[error("缺少凭证")]
+    MissingCredentials,
+    #[error("凭证无效: {0}")]
+    InvalidCredentials(String),
+    #[error("凭证已过期")]
+    Expired,
+    #[error("{subject} 无权执行 {action:?}")]
+    Forbidden { subject: String, action: ControlAction },
+    #[error("{subject} 无权访问 profile {profile}")]
+    ProfileForbidden { subject: String, profile: String },
+}
+
+/// JWT 声明
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub roles: Vec<Role>,
+    pub exp: i64,
+    /// 可访问的 profile；缺省表示不限制
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profiles: Option<HashSet<String>>,
+}
+
+/// 已认证的调用方
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Principal {
+    pub subject: String,
+    pub roles: Vec<Role>,
+    /// API Key 作用域；None 表示不额外限制
+    pub scopes: Option<HashSet<ControlAction>>,
+    /// 可访问的 profile；None 表示不限制
+    pub profiles: Option<HashSet<String>>,
+}
+
+impl Principal {
+    pub fn can_access_profile(&self, profile: &str) -> bool {
+        self.profiles.as_ref().is_none_or(|p| p.contains(profile))
+    }
+
+    pub fn can(&self, action: ControlAction) -> bool {
+        self.roles.iter().any(|r| r.allows(action))
+            && match &self.scopes {
+                Some(scopes) => scopes.contains(&action),
+                None => true,
+            }
+    }
+}
+
+/// 调用凭证
+#[derive(Debug, Clone)]
+pub enum Credentials {
+    Bearer(String),
+    ApiKey(String),
+}
+
+impl Credentials {
+    /// 从请求头解析：`Authorization: Bearer <jwt>` 或 `X-Api-Key: <key>`
+    pub fn from_headers(authorization: Option<&str>, api_key: Option<&str>) -> Option<Self> {
+        if let Some(token) = authorization.and_then(|h| h.strip_prefix("Bearer ")) {
+            return Some(Credentials::Bearer(token.trim().to_string()));
+        }
+        api_key.map(|k| Credentials::ApiKey(k.trim().to_string()))
+    }
+}
+
+/// API Key 记录（只保存哈希）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyRecord {
+    pub id: String,
+    pub name: String,
+    pub roles: Vec<Role>,
+    pub scopes: Option<HashSet<ControlAction>>,
+    #[serde(default)]
+    pub profiles: Option<HashSet<String>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// API Key 存储：blake3(key) -> 记录，从不保存明文
+pub trait ApiKeyStore: Send + Sync {
+    fn load(&self) -> anyhow::Result<HashMap<String, ApiKeyRecord>>;
+    fn save(&self, keys: &HashMap<String, ApiKeyRecord>) -> anyhow::Result<()>;
+}
+
+/// JSON 文件存储，写临时文件后 rename 保证原子性
+pub struct FileApiKeyStore {
+    path: PathBuf,
+}
+
+impl FileApiKeyStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl ApiKeyStore for FileApiKeyStore {
+    fn load(&self) -> anyhow::Result<HashMap<String, ApiKeyRecord>> {
+        match std::fs::read(&self.path) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save(&self, keys: &HashMap<String, ApiKeyRecord>) -> anyhow::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec(keys)?)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+/// 鉴权服务
+pub struct AuthService {
+    jwt_secret: Vec<u8>,
+    /// blake3(key) -> 记录
+    api_keys: RwLock<HashMap<String, ApiKeyRecord>>,
+    store: Option<Box<dyn ApiKeyStore>>,
+}
+
+impl AuthService {
+    pub fn new(jwt_secret: &[u8]) -> Self {
+        Self {
+            jwt_secret: jwt_secret.to_vec(),
+            api_keys: RwLock::new(HashMap::new()),
+            store: None,
+        }
+    }
+
+    /// 从存储加载已签发的 API Key，之后的签发/吊销写回存储
+    pub fn with_api_key_store(mut self, store: Box<dyn ApiKeyStore>) -> anyhow::Result<Self> {
+        let keys = store.load()?;
+        info!("🔑 已加载 {} 个 API Key", keys.len());
+        self.api_keys = RwLock::new(keys);
+        self.store = Some(store);
+        Ok(self)
+    }
+
+    /// 从 CELUE_JWT_SECRET 创建；设置了 CELUE_API_KEY_STORE 时从该文件加载 API Key
+    pub fn from_env() -> anyhow::Result<Self> {
+        let secret = std::env::var("CELUE_JWT_SECRET")
+            .map_err(|_| anyhow::anyhow!("CELUE_JWT_SECRET 未设置"))?;
+        let auth = Self::new(secret.as_bytes());
+        match std::env::var("CELUE_API_KEY_STORE") {
+            Ok(path) => auth.with_api_key_store(Box::new(FileApiKeyStore::new(path))),
+            Err(_) => Ok(auth),
+        }
+    }
+
+    fn persist(&self, keys: &HashMap<String, ApiKeyRecord>) -> anyhow::Result<()> {
+        match &self.store {
+            Some(store) => store.save(keys),
+            None => Ok(()),
+        }
+    }
+
+    /// 签发 JWT
+    pub fn issue_token(&self, subject: &str, roles: Vec<Role>, ttl: chrono::Duration) -> anyhow::Result<String> {
+        self.issue_profile_token(subject, roles, None, ttl)
+    }
+
+    /// 签发限定 profile 的 JWT
+    pub fn issue_profile_token(
+        &self,
+        subject: &str,
+        roles: Vec<Role>,
+        profiles: Option<HashSet<String>>,
+        ttl: chrono::Duration,
+    ) -> anyhow::Result<String> {
+        let claims = Claims { sub: subject.to_string(), roles, exp: (Utc::now() + ttl).timestamp(), profiles };
+        Ok(encode(&Header::new(Algorithm::HS256), &claims, &EncodingKey::from_secret(&self.jwt_secret))?)
+    }
+
+    /// 签发 API Key，返回 (明文 key, 记录)；明文不落盘
+    pub fn issue_api_key(
+        &self,
+        name: &str,
+        roles: Vec<Role>,
+        scopes: Option<HashSet<ControlAction>>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<(String, ApiKeyRecord)> {
+        let key = format!("celue_{}", uuid::Uuid::new_v4().simple());
+        let record = ApiKeyRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            roles,
+            scopes,
+            profiles: None,
+            expires_at,
+            created_at: Utc::now(),
+        };
+        let mut keys = self.api_keys.write();
+        keys.insert(hash_key(&key), record.clone());
+        if let Err(e) = self.persist(&keys) {
+            // 未能落盘的 Key 重启后会失效，不交给调用方
+            keys.remove(&hash_key(&key));
+            return Err(e);
+        }
+        info!("🔑 API Key 已签发: {} ({})", record.name, record.id);
+        Ok((key, record))
+    }
+
+    /// 吊销 API Key
+    pub fn revoke_api_key(&self, id: &str) -> anyhow::Result<bool> {
+        let mut keys = self.api_keys.write();
+        let before = keys.len();
+        keys.retain(|_, r| r.id != id);
+        if before == keys.len() {
+            return Ok(false);
+        }
+        self.persist(&keys)?;
+        Ok(true)
+    }
+
+    /// 限定 API Key 可访问的 profile
+    pub fn restrict_api_key_profiles(&self, id: &str, profiles: HashSet<String>) -> anyhow::Result<bool> {
+        let mut keys = self.api_keys.write();
+        match keys.values_mut().find(|r| r.id == id) {
+            Some(record) => {
+                record.profiles = Some(profiles);
+                self.persist(&keys)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    pub fn api_keys(&self) -> Vec<ApiKeyRecord> {
+        self.api_keys.read().values().cloned().collect()
+    }
+
+    /// 校验凭证
+    pub fn authenticate(&self, credentials: &Credentials) -> Result<Principal, AuthError> {
+        match credentials {
+            Credentials::Bearer(token) => {
+                let data = decode::<Claims>(
+                    token,
+                    &DecodingKey::from_secret(&self.jwt_secret),
+                    &Validation::new(Algorithm::HS256),
+                )
+                .map_err(|e| match e.kind() {
+                    jsonwebtoken::errors::ErrorKind::ExpiredSignature => AuthError::Expired,
+                    _ => AuthError::InvalidCredentials(e.to_string()),
+                })?;
+                Ok(Principal {
+                    subject: data.claims.sub,
+                    roles: data.claims.roles,
+                    scopes: None,
+                    profiles: data.claims.profiles,
+                })
+            }
+            Credentials::ApiKey(key) => {
+                let keys = self.api_keys.read();
+                let record = keys
+                    .get(&hash_key(key))
+                    .ok_or_else(|| AuthError::InvalidCredentials("未知 API Key".to_string()))?;
+                if record.expires_at.is_some_and(|t| t <= Utc::now()) {
+                    return Err(AuthError::Expired);
+                }
+                Ok(Principal {
+                    subject: format!("api_key:{}", record.name),
+                    roles: record.roles.clone(),
+                    scopes: record.scopes.clone(),
+                    profiles: record.profiles.clone(),
+                })
+            }
+        }
+    }
+
+    /// 鉴权中间件入口：校验凭证并检查操作权限
+    pub fn authorize(&self, credentials: Option<&Credentials>, action: ControlAction) -> Result<Principal, AuthError> {
+        let principal = self.authenticate(credentials.ok_or(AuthError::MissingCredentials)?)?;
+        if !principal.can(action) {
+            warn!("🚫 {} 尝试执行 {:?} 被拒绝", principal.subject, action);
+            return Err(AuthError::Forbidden { subject: principal.subject, action });
+        }
+        Ok(principal)
+    }
+
+    /// 鉴权并检查调用方可访问目标 profile
+    pub fn authorize_profile(
+        &self,
+        credentials: Option<&Credentials>,
+        action: ControlAction,
+        profile: &str,
+    ) -> Result<Principal, AuthError> {
+        let principal = self.authorize(credentials, action)?;
+        if !principal.can_access_profile(profile) {
+            warn!("🚫 {} 尝试访问 profile {} 被拒绝", principal.subject, profile);
+            return Err(AuthError::ProfileForbidden { subject: principal.subject, profile: profile.to_string() });
+        }
+        Ok(principal)
+    }
+
+    /// 从 NATS 请求头鉴权
+    pub fn authorize_nats(&self, message: &async_nats::Message, action: ControlAction) -> Result<Principal, AuthError> {
+        let header = |name: &str| {
+            message
+                .headers
+                .as_ref()
+                .and_then(|h| h.get(name))
+                .map(|v| v.as_str().to_string())
+        };
+        let authorization = header("Authorization");
+        let api_key = header("X-Api-Key");
+        let credentials = Credentials::from_headers(authorization.as_deref(), api_key.as_deref());
+        self.authorize(credentials.as_ref(), action)
+    }
+}
+
+fn hash_key(key: &str) -> String {
+    blake3::hash(key.as_bytes()).to_hex().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roles_and_scoped_keys() {
+        let auth = AuthService::new(b"test-secret");
+
+        let token = auth.issue_token("alice", vec![Role::Viewer], chrono::Duration::minutes(5)).unwrap();
+        let bearer = Credentials::Bearer(token);
+        assert!(auth.authorize(Some(&bearer), ControlAction::ViewDashboard).is_ok());
+        assert!(matches!(
+            auth.authorize(Some(&bearer), ControlAction::ResetKillSwitch),
+            Err(AuthError::Forbidden { .. })
+        ));
+
+        let scopes = HashSet::from([ControlAction::ViewDashboard, ControlAction::CancelOrders]);
+        let (key, _) = auth.issue_api_key("bot", vec![Role::Admin], Some(scopes), None).unwrap();
+        let api_key = Credentials::ApiKey(key);
+        assert!(auth.authorize(Some(&api_key), ControlAction::CancelOrders).is_ok());
+        assert!(auth.authorize(Some(&api_key), ControlAction::UpdateConfig).is_err());
+
+        assert!(matches!(auth.authorize(None, ControlAction::ViewDashboard), Err(AuthError::MissingCredentials)));
+
+        let desk_a = HashSet::from(["desk-a".to_string()]);
+        let token = auth
+            .issue_profile_token("carol", vec![Role::Operator], Some(desk_a), chrono::Duration::minutes(5))
+            .unwrap();
+        let bearer = Credentials::Bearer(token);
+        assert!(auth.authorize_profile(Some(&bearer), ControlAction::StartStop, "desk-a").is_ok());
+        assert!(matches!(
+            auth.authorize_profile(Some(&bearer), ControlAction::StartStop, "desk-b"),
+            Err(AuthError::ProfileForbidden { .. })
+        ));
+    }
+
+    #[test]
+    fn test_api_keys_survive_restart_as_hashes() {
+        let path = std::env::temp_dir().join(format!("celue_api_keys_{}.json", uuid::Uuid::new_v4()));
+        let auth = AuthService::new(b"test-secret").with_api_key_store(Box::new(FileApiKeyStore::new(&path))).unwrap();
+        let (kept, _) = auth.issue_api_key("bot", vec![Role::Operator], None, None).unwrap();
+        let (_, revoked) = auth.issue_api_key("old-bot", vec![Role::Operator], None, None).unwrap();
+        assert!(auth.revoke_api_key(&revoked.id).unwrap());
+
+        // 存储里只有哈希
+        let stored = std::fs::read_to_string(&path).unwrap();
+        assert!(!stored.contains(&kept));
+        assert!(stored.contains(&hash_key(&kept)));
+
+        let restarted = AuthService::new(b"test-secret").with_api_key_store(Box::new(FileApiKeyStore::new(&path))).unwrap();
+        assert_eq!(restarted.api_keys().len(), 1);
+        let principal = restarted.authorize(Some(&Credentials::ApiKey(kept)), ControlAction::CancelOrders).unwrap();
+        assert_eq!(principal.subject, "api_key:bot");
+        let _ = std::fs::remove_file(&path);
+    }
+}
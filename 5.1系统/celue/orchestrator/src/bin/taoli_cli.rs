@@ -0,0 +1,407 @@
+//! taoli-cli - 运维管理命令行工具
+//!
+//! 通过 HTTP 调用网关的管理接口：查看状态、启停模块、告警列表与确认、
+//! 持仓、实时机会流、触发参数优化、配置导出与校验、报表导出。
+//! 默认以表格输出，`--json` 输出原始 JSON 便于脚本处理。
+//!
+//! 网关地址取 `--gateway` 或 `CELUE_GATEWAY_URL`，凭证取 `--token`
+//! （JWT）/ `--api-key` 或 `CELUE_API_TOKEN` / `CELUE_API_KEY`。
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use common::ApiResponse;
+use orchestrator::config::SystemConfig;
+use orchestrator::crash_dump::{self, CrashDumpConfig};
+use serde_json::{json, Value};
+
+#[derive(Parser)]
+#[command(name = "taoli-cli", version, about = "套利系统管理工具")]
+struct Cli {
+    /// 网关地址
+    #[arg(long, env = "CELUE_GATEWAY_URL", default_value = "http://127.0.0.1:8080")]
+    gateway: String,
+    /// JWT 令牌
+    #[arg(long, env = "CELUE_API_TOKEN", hide_env_values = true)]
+    token: Option<String>,
+    /// API Key
+    #[arg(long, env = "CELUE_API_KEY", hide_env_values = true)]
+    api_key: Option<String>,
+    /// 以 JSON 输出
+    #[arg(long, global = true)]
+    json: bool,
+    /// 请求超时（秒）
+    #[arg(long, default_value_t = 10)]
+    timeout: u64,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 系统与各模块状态
+    Status,
+    /// 启动模块
+    Start { module: String },
+    /// 停止模块
+    Stop { module: String },
+    /// 告警管理
+    Alerts {
+        #[command(subcommand)]
+        action: AlertCommand,
+    },
+    /// 当前持仓
+    Positions {
+        #[arg(long)]
+        exchange: Option<String>,
+    },
+    /// 持续输出新的套利机会
+    Tail {
+        /// 轮询间隔（毫秒）
+        #[arg(long, default_value_t = 1000)]
+        interval_ms: u64,
+        #[arg(long)]
+        strategy: Option<String>,
+    },
+    /// 触发参数优化
+    Optimize {
+        #[arg(long)]
+        strategy: Option<String>,
+    },
+    /// 配置管理
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+    /// 导出报表
+    Report {
+        #[arg(value_enum)]
+        kind: ReportKind,
+        #[arg(long)]
+        from: Option<String>,
+        #[arg(long)]
+        to: Option<String>,
+        /// 写入文件；缺省输出到标准输出
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum AlertCommand {
+    /// 列出告警
+    List {
+        /// 只显示未确认的告警
+        #[arg(long)]
+        active: bool,
+        #[arg(long, default_value_t = 50)]
+        limit: usize,
+    },
+    /// 确认告警
+    Ack { id: String },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// 导出当前运行配置
+    Dump,
+    /// 本地校验配置文件；加 `--remote` 时交由网关校验
+    Validate {
+        path: PathBuf,
+        #[arg(long)]
+        remote: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ReportKind {
+    Pnl,
+    Executions,
+    Fees,
+    Risk,
+}
+
+impl ReportKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Pnl => "pnl",
+            Self::Executions => "executions",
+            Self::Fees => "fees",
+            Self::Risk => "risk",
+        }
+    }
+}
+
+struct GatewayClient {
+    base: String,
+    http: reqwest::Client,
+    token: Option<String>,
+    api_key: Option<String>,
+}
+
+impl GatewayClient {
+    fn new(cli: &Cli) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(cli.timeout))
+            .build()?;
+        Ok(Self {
+            base: cli.gateway.trim_end_matches('/').to_string(),
+            http,
+            token: cli.token.clone(),
+            api_key: cli.api_key.clone(),
+        })
+    }
+
+    async fn send(&self, method: reqwest::Method, path: &str, body: Option<Value>) -> Result<ApiResponse<Value>> {
+        let url = format!("{}{}", self.base, path);
+        let mut request = self.http.request(method, &url);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        } else if let Some(key) = &self.api_key {
+            request = request.header("X-Api-Key", key);
+        }
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        let response = request.send().await.with_context(|| format!("无法连接网关 {}", url))?;
+        let status = response.status();
+        let envelope: ApiResponse<Value> = response
+            .json()
+            .await
+            .with_context(|| format!("网关返回非预期响应 (HTTP {})", status))?;
+        if !envelope.success {
+            let code = envelope.error_code.as_deref().unwrap_or("UNKNOWN");
+            bail!("[{}] {} (HTTP {})", code, envelope.error.unwrap_or_else(|| "未知错误".to_string()), status);
+        }
+        Ok(envelope)
+    }
+
+    async fn get(&self, path: &str) -> Result<Value> {
+        Ok(self.send(reqwest::Method::GET, path, None).await?.data.unwrap_or(Value::Null))
+    }
+
+    async fn post(&self, path: &str, body: Value) -> Result<Value> {
+        Ok(self.send(reqwest::Method::POST, path, Some(body)).await?.data.unwrap_or(Value::Null))
+    }
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => "-".to_string(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// 对象数组按表格输出，列取首行的键；其他值按键值对输出
+fn print_table(value: &Value) {
+    match value {
+        Value::Array(rows) if rows.iter().all(Value::is_object) => {
+            let Some(Value::Object(first)) = rows.first() else {
+                println!("(空)");
+                return;
+            };
+            let columns: Vec<&String> = first.keys().collect();
+            let table: Vec<Vec<String>> = rows
+                .iter()
+                .map(|row| columns.iter().map(|c| cell(&row[c.as_str()])).collect())
+                .collect();
+            let widths: Vec<usize> = columns
+                .iter()
+                .enumerate()
+                .map(|(i, c)| table.iter().map(|r| r[i].chars().count()).chain([c.chars().count()]).max().unwrap_or(0))
+                .collect();
+            let line = |cells: Vec<String>| {
+                cells
+                    .iter()
+                    .zip(&widths)
+                    .map(|(c, w)| format!("{:<width$}", c, width = *w))
+                    .collect::<Vec<_>>()
+                    .join("  ")
+            };
+            println!("{}", line(columns.iter().map(|c| c.to_uppercase()).collect()));
+            for row in table {
+                println!("{}", line(row));
+            }
+        }
+        Value::Object(map) => {
+            let width = map.keys().map(|k| k.chars().count()).max().unwrap_or(0);
+            for (key, value) in map {
+                match value {
+                    Value::Object(_) | Value::Array(_) => {
+                        println!("{}:", key);
+                        print_table(value);
+                    }
+                    _ => println!("{:<width$}  {}", key, cell(value), width = width),
+                }
+            }
+        }
+        other => println!("{}", cell(other)),
+    }
+}
+
+fn render(value: &Value, as_json: bool) -> Result<()> {
+    if as_json {
+        println!("{}", serde_json::to_string_pretty(value)?);
+    } else {
+        print_table(value);
+    }
+    Ok(())
+}
+
+fn opportunities_path(cursor: Option<&str>, strategy: Option<&str>) -> String {
+    let mut path = "/api/opportunities?order=asc&limit=200".to_string();
+    if let Some(cursor) = cursor {
+        path.push_str(&format!("&cursor={}", cursor));
+    }
+    if let Some(strategy) = strategy {
+        path.push_str(&format!("&strategy={}", strategy));
+    }
+    path
+}
+
+/// 最后一页没有 next_cursor，从最后一条记录续接；空页时沿用原游标
+fn next_cursor(next: Option<String>, items: &[Value]) -> Option<String> {
+    next.or_else(|| items.last().map(|last| format!("{}:{}", cell(&last["created_at_ns"]), cell(&last["id"]))))
+}
+
+fn report_path(kind: ReportKind, from: Option<String>, to: Option<String>) -> String {
+    let mut path = format!("/api/reports/{}", kind.as_str());
+    let params: Vec<String> = [("from", from), ("to", to)]
+        .into_iter()
+        .filter_map(|(k, v)| v.map(|v| format!("{}={}", k, v)))
+        .collect();
+    if !params.is_empty() {
+        path.push('?');
+        path.push_str(&params.join("&"));
+    }
+    path
+}
+
+async fn tail(client: &GatewayClient, interval_ms: u64, strategy: Option<String>, as_json: bool) -> Result<()> {
+    let mut cursor: Option<String> = None;
+    let mut ticker = tokio::time::interval(Duration::from_millis(interval_ms.max(100)));
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+            _ = ticker.tick() => {}
+        }
+        let path = opportunities_path(cursor.as_deref(), strategy.as_deref());
+        let page = client.send(reqwest::Method::GET, &path, None).await?;
+        let items = page.data.as_ref().and_then(Value::as_array).cloned().unwrap_or_default();
+        cursor = next_cursor(page.next_cursor, &items).or(cursor);
+        for item in items {
+            if as_json {
+                println!("{}", serde_json::to_string(&item)?);
+            } else {
+                println!(
+                    "{}  {:<16} {:<12} {:>10}  {}",
+                    cell(&item["created_at_ns"]),
+                    cell(&item["strategy_name"]),
+                    cell(&item["legs"][0]["symbol"]),
+                    cell(&item["net_profit_pct"]),
+                    cell(&item["id"]),
+                );
+            }
+        }
+    }
+}
+
+fn validate_local(path: &PathBuf) -> Result<Value> {
+    let content = std::fs::read_to_string(path).with_context(|| format!("读取 {} 失败", path.display()))?;
+    let config: SystemConfig = toml::from_str(&content).map_err(|e| anyhow!("解析失败: {}", e))?;
+    config.validate()?;
+    Ok(json!({ "path": path.display().to_string(), "valid": true }))
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    crash_dump::init_tracing(CrashDumpConfig::default());
+    let cli = Cli::parse();
+    let client = GatewayClient::new(&cli)?;
+    let as_json = cli.json;
+
+    let result = match cli.command {
+        Command::Status => client.get("/api/status").await?,
+        Command::Start { module } => client.post(&format!("/api/modules/{}/start", module), json!({})).await?,
+        Command::Stop { module } => client.post(&format!("/api/modules/{}/stop", module), json!({})).await?,
+        Command::Alerts { action: AlertCommand::List { active, limit } } => {
+            client.get(&format!("/api/alerts?active={}&limit={}", active, limit)).await?
+        }
+        Command::Alerts { action: AlertCommand::Ack { id } } => {
+            client.post(&format!("/api/alerts/{}/ack", id), json!({})).await?
+        }
+        Command::Positions { exchange } => match exchange {
+            Some(exchange) => client.get(&format!("/api/positions?exchange={}", exchange)).await?,
+            None => client.get("/api/positions").await?,
+        },
+        Command::Tail { interval_ms, strategy } => return tail(&client, interval_ms, strategy, as_json).await,
+        Command::Optimize { strategy } => client.post("/api/optimization/run", json!({ "strategy": strategy })).await?,
+        Command::Config { action: ConfigCommand::Dump } => client.get("/api/config").await?,
+        Command::Config { action: ConfigCommand::Validate { path, remote: false } } => validate_local(&path)?,
+        Command::Config { action: ConfigCommand::Validate { path, remote: true } } => {
+            let content = std::fs::read_to_string(&path).with_context(|| format!("读取 {} 失败", path.display()))?;
+            client.post("/api/config/validate", json!({ "format": "toml", "content": content })).await?
+        }
+        Command::Report { kind, from, to, output } => {
+            let report = client.get(&report_path(kind, from, to)).await?;
+            if let Some(output) = output {
+                std::fs::write(&output, serde_json::to_string_pretty(&report)?)?;
+                println!("报表已写入 {}", output.display());
+                return Ok(());
+            }
+            report
+        }
+    };
+    render(&result, as_json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_parses_subcommands() {
+        Cli::command().debug_assert();
+
+        let cli = Cli::try_parse_from(["taoli-cli", "--gateway", "http://gw:9000/", "alerts", "list", "--active", "--json"]).unwrap();
+        assert!(cli.json);
+        assert!(matches!(cli.command, Command::Alerts { action: AlertCommand::List { active: true, limit: 50 } }));
+        assert_eq!(GatewayClient::new(&cli).unwrap().base, "http://gw:9000");
+
+        let cli = Cli::try_parse_from(["taoli-cli", "report", "fees", "--from", "2024-01-01"]).unwrap();
+        let Command::Report { kind, from, to, output } = cli.command else { panic!("expected report") };
+        assert!(output.is_none());
+        assert_eq!(report_path(kind, from, to), "/api/reports/fees?from=2024-01-01");
+        assert_eq!(report_path(ReportKind::Pnl, None, None), "/api/reports/pnl");
+
+        assert!(Cli::try_parse_from(["taoli-cli", "report", "unknown"]).is_err());
+        assert!(Cli::try_parse_from(["taoli-cli", "start"]).is_err());
+    }
+
+    #[test]
+    fn test_tail_cursor_resumes_from_last_item() {
+        assert_eq!(
+            opportunities_path(Some("5:abc"), Some("triangular")),
+            "/api/opportunities?order=asc&limit=200&cursor=5:abc&strategy=triangular"
+        );
+        let items = vec![json!({ "created_at_ns": 1, "id": "a" }), json!({ "created_at_ns": 2, "id": "b" })];
+        assert_eq!(next_cursor(Some("9:z".to_string()), &items).as_deref(), Some("9:z"));
+        assert_eq!(next_cursor(None, &items).as_deref(), Some("2:b"));
+        assert_eq!(next_cursor(None, &[]), None);
+    }
+
+    #[test]
+    fn test_validate_local_rejects_unparseable_config() {
+        let path = std::env::temp_dir().join(format!("taoli_cli_{}.toml", std::process::id()));
+        std::fs::write(&path, "not = [valid").unwrap();
+        assert!(validate_local(&path).unwrap_err().to_string().contains("解析失败"));
+        std::fs::remove_file(&path).unwrap();
+        assert!(validate_local(&path).is_err());
+        assert_eq!(cell(&Value::Null), "-");
+        assert_eq!(cell(&json!("x")), "x");
+    }
+}
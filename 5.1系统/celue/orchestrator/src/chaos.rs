@@ -0,0 +1,138 @@
+//! 混沌实验控制接口（需启用 `chaos` feature）
+//!
+//! 通过 NATS 请求启动/停止 `adapters::chaos` 中配置的故障场景，并查询
+//! 当前实验与历史报告。启停属于管理操作：需要 `RunChaosExperiments`
+//! 权限，配置了审计日志时一并落审计。
+
+use std::sync::Arc;
+
+use adapters::chaos::{ChaosController, ChaosScenario, ExperimentReport};
+use anyhow::Result;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::audit::AuditLog;
+use crate::auth::{AuthService, ControlAction};
+use crate::nats::{encode_reply, NatsManager};
+use crate::rate_limit::{apply_rejection_headers, RateLimiter};
+use common::{ApiResponse, SystemError, SystemResult};
+
+/// 控制请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum ChaosCommand {
+    /// 列出已配置场景
+    List,
+    /// 启动场景
+    Start { scenario: String },
+    /// 停止当前实验
+    Stop,
+    /// 当前实验报告
+    Status,
+    /// 历史实验报告
+    History,
+}
+
+/// 控制响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChaosReply {
+    Scenarios { scenarios: Vec<ChaosScenario> },
+    Started { experiment_id: String },
+    Report { report: Option<ExperimentReport> },
+    History { reports: Vec<ExperimentReport> },
+}
+
+/// 混沌实验控制服务
+pub struct ChaosService {
+    controller: Arc<ChaosController>,
+    auth: Option<Arc<AuthService>>,
+    audit: Option<Arc<AuditLog>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+}
+
+impl ChaosService {
+    pub fn new(controller: Arc<ChaosController>) -> Self {
+        Self { controller, auth: None, audit: None, rate_limiter: None }
+    }
+
+    pub fn with_auth(mut self, auth: Arc<AuthService>) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    pub fn with_audit(mut self, audit: Arc<AuditLog>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// 按调用方限流（需同时配置鉴权）
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// 执行一条控制命令（不鉴权）
+    pub fn handle(&self, command: &ChaosCommand) -> SystemResult<ChaosReply> {
+        Ok(match command {
+            ChaosCommand::List => ChaosReply::Scenarios { scenarios: self.controller.scenarios() },
+            ChaosCommand::Start { scenario } => {
+                warn!("🧪 启动混沌实验: {}", scenario);
+                ChaosReply::Started { experiment_id: self.controller.start(scenario)? }
+            }
+            ChaosCommand::Stop => {
+                let report = self.controller.stop();
+                info!("🧪 混沌实验已停止: {:?}", report.as_ref().map(|r| &r.id));
+                ChaosReply::Report { report }
+            }
+            ChaosCommand::Status => ChaosReply::Report { report: self.controller.current() },
+            ChaosCommand::History => ChaosReply::History { reports: self.controller.history() },
+        })
+    }
+
+    /// 控制服务：请求体为 `ChaosCommand` JSON，响应为带错误码的 `ApiResponse`
+    pub async fn serve(self: Arc<Self>, nats: &NatsManager, subject: &str) -> Result<()> {
+        let mut subscriber = nats.subscribe(subject).await?;
+        info!("🧪 混沌实验控制服务已启动: {}", subject);
+
+        while let Some(message) = subscriber.next().await {
+            let Some(reply) = message.reply.clone() else { continue };
+            let mut headers = async_nats::HeaderMap::new();
+            let result = async {
+                let command = serde_json::from_slice::<ChaosCommand>(&message.payload)
+                    .map_err(|e| SystemError::InvalidRequest(e.to_string()))?;
+                let mutating = matches!(command, ChaosCommand::Start { .. } | ChaosCommand::Stop);
+                let action = if mutating { ControlAction::RunChaosExperiments } else { ControlAction::ViewDashboard };
+                let principal = match (&self.auth, &self.rate_limiter) {
+                    (Some(auth), Some(limiter)) => {
+                        let (principal, usage) = limiter.guard_nats(auth, &message, action)?;
+                        headers = usage.nats_headers();
+                        Some(principal)
+                    }
+                    (Some(auth), None) => Some(auth.authorize_nats(&message, action)?),
+                    _ => None,
+                };
+                let reply = self.handle(&command)?;
+                if let (true, Some(audit), Some(principal)) = (mutating, &self.audit, &principal) {
+                    let details = serde_json::to_value(&command).unwrap_or_default();
+                    audit.record(principal, action, "chaos", details).await?;
+                }
+                Ok::<_, SystemError>(reply)
+            }
+            .await;
+            if let Err(e) = &result {
+                apply_rejection_headers(&mut headers, e);
+            }
+            let response: ApiResponse<ChaosReply> = result.into();
+            if let Err(e) = nats
+                .get_client()
+                .publish_with_headers(reply, headers, encode_reply(&message, &response)?.into())
+                .await
+            {
+                warn!("混沌实验控制响应发送失败: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
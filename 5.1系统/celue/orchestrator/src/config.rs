@@ -17,6 +17,8 @@ use adapters::nats::NatsConfig;
 use adapters::funds::{FundsConfig, FundLimits};
 use adapters::market_data::MarketDataConfig;
 use adapters::execution::ExchangeCredentials;
+use crate::tenants::TradingProfile;
+use adapters::trading_mode::TradingMode;
 use common::precision::FixedPrice;
 
 #[derive(Debug, thiserror::Error)]
@@ -41,6 +43,7 @@ pub enum ConfigChangeEvent {
     RiskConfigChanged,
     SystemConfigChanged,
     NatsConfigChanged,
+    ProfilesChanged,
 }
 
 /// Hot reload configuration manager
@@ -148,6 +151,10 @@ impl HotReloadConfigManager {
         if new_config.nats != old_config.nats {
             changes.push(ConfigChangeEvent::NatsConfigChanged);
         }
+
+        if new_config.profiles != old_config.profiles {
+            changes.push(ConfigChangeEvent::ProfilesChanged);
+        }
         
         // Update configuration
         {
@@ -207,6 +214,14 @@ pub struct SystemConfig {
     
     /// Logging configuration
     pub logging: LoggingConfig,
+
+    /// Trading profiles (tenants), each with its own credentials, strategies and limits
+    #[serde(default)]
+    pub profiles: Vec<TradingProfile>,
+
+    /// System-wide trading mode at startup (dry_run | shadow | live)
+    #[serde(default)]
+    pub trading_mode: TradingMode,
 }
 
 /// Strategy configuration section
@@ -550,6 +565,8 @@ impl SystemConfig {
                     max_files: 10,
                 },
             },
+            profiles: Vec::new(),
+            trading_mode: TradingMode::default(),
         }
     }
 }
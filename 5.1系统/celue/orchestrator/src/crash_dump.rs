@@ -0,0 +1,277 @@
+//! 崩溃转储 - 有界内存日志环形缓冲区
+//!
+//! 通过 tracing Layer 保留最近的结构化日志记录，在 panic 或 SIGSEGV/SIGABRT
+//! 时将其连同 backtrace 和运行清单写入崩溃转储文件，便于事后分析。
+//! 进程入口通过 `init_tracing` 安装。
+
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// 崩溃转储配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashDumpConfig {
+    /// 环形缓冲区容量（日志条数）
+    pub capacity: usize,
+    /// 转储文件目录
+    pub dump_dir: PathBuf,
+}
+
+impl Default for CrashDumpConfig {
+    fn default() -> Self {
+        Self {
+            capacity: std::env::var("CELUE_CRASH_LOG_CAPACITY")
+                .ok().and_then(|s| s.parse().ok())
+                .unwrap_or(4096),
+            dump_dir: std::env::var("CELUE_CRASH_DUMP_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| PathBuf::from("crash_dumps")),
+        }
+    }
+}
+
+/// 单条日志记录
+#[derive(Debug, Clone, Serialize)]
+pub struct LogRecord {
+    pub timestamp_ns: u64,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    pub fields: Vec<(String, String)>,
+}
+
+/// 运行清单（写入转储头部）
+#[derive(Debug, Clone, Serialize)]
+pub struct RunManifest {
+    pub version: String,
+    pub pid: u32,
+    pub started_at: String,
+    pub args: Vec<String>,
+}
+
+impl RunManifest {
+    pub fn current() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            pid: std::process::id(),
+            started_at: chrono::Utc::now().to_rfc3339(),
+            args: std::env::args().collect(),
+        }
+    }
+}
+
+/// 有界日志环形缓冲区
+#[derive(Debug)]
+pub struct LogRingBuffer {
+    records: Mutex<VecDeque<LogRecord>>,
+    capacity: usize,
+}
+
+impl LogRingBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            records: Mutex::new(VecDeque::with_capacity(capacity.max(1))),
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub fn push(&self, record: LogRecord) {
+        let mut records = self.records.lock();
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    /// 获取当前缓冲区快照（最旧的在前）
+    pub fn snapshot(&self) -> Vec<LogRecord> {
+        self.records.lock().iter().cloned().collect()
+    }
+
+    /// 非阻塞获取快照，用于崩溃路径避免死锁
+    fn try_snapshot(&self) -> Option<Vec<LogRecord>> {
+        self.records.try_lock().map(|r| r.iter().cloned().collect())
+    }
+}
+
+/// 将事件写入环形缓冲区的 tracing Layer
+pub struct RingBufferLayer {
+    buffer: Arc<LogRingBuffer>,
+}
+
+impl RingBufferLayer {
+    pub fn new(buffer: Arc<LogRingBuffer>) -> Self {
+        Self { buffer }
+    }
+}
+
+#[derive(Default)]
+struct FieldCollector {
+    message: String,
+    fields: Vec<(String, String)>,
+}
+
+impl Visit for FieldCollector {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            self.fields.push((field.name().to_string(), format!("{:?}", value)));
+        }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for RingBufferLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut collector = FieldCollector::default();
+        event.record(&mut collector);
+        let metadata = event.metadata();
+        self.buffer.push(LogRecord {
+            timestamp_ns: chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64,
+            level: metadata.level().to_string(),
+            target: metadata.target().to_string(),
+            message: collector.message,
+            fields: collector.fields,
+        });
+    }
+}
+
+struct CrashDumpState {
+    buffer: Arc<LogRingBuffer>,
+    manifest: RunManifest,
+    dump_dir: PathBuf,
+}
+
+static CRASH_STATE: OnceLock<CrashDumpState> = OnceLock::new();
+
+/// 安装崩溃转储：返回需要注册到 subscriber 的 Layer
+///
+/// 只应调用一次；重复调用时沿用首次安装的缓冲区。
+pub fn install(config: CrashDumpConfig) -> RingBufferLayer {
+    let state = CRASH_STATE.get_or_init(|| CrashDumpState {
+        buffer: Arc::new(LogRingBuffer::new(config.capacity)),
+        manifest: RunManifest::current(),
+        dump_dir: config.dump_dir.clone(),
+    });
+
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let reason = format!("panic: {}", info);
+        if let Some(path) = write_dump(&reason) {
+            eprintln!("💥 崩溃转储已写入: {}", path.display());
+        }
+        previous(info);
+    }));
+
+    install_signal_handlers();
+    RingBufferLayer::new(state.buffer.clone())
+}
+
+/// 初始化全局日志：stderr 输出（RUST_LOG 过滤）+ 崩溃转储环形缓冲区
+///
+/// 进程启动时调用一次；已存在全局 subscriber 时只安装崩溃钩子。
+pub fn init_tracing(config: CrashDumpConfig) {
+    use tracing_subscriber::prelude::*;
+
+    let ring = install(config);
+    let _ = tracing_subscriber::registry()
+        .with(tracing_subscriber::EnvFilter::from_default_env())
+        .with(tracing_subscriber::fmt::layer().with_target(false).with_writer(std::io::stderr))
+        .with(ring)
+        .try_init();
+}
+
+/// 将当前缓冲区写入崩溃转储文件
+pub fn write_dump(reason: &str) -> Option<PathBuf> {
+    CRASH_STATE.get()?.write(reason)
+}
+
+impl CrashDumpState {
+    fn write(&self, reason: &str) -> Option<PathBuf> {
+        let records = self.buffer.try_snapshot().unwrap_or_default();
+        let backtrace = std::backtrace::Backtrace::force_capture();
+
+        let mut out = String::new();
+        let _ = writeln!(out, "reason: {}", reason);
+        let _ = writeln!(out, "dumped_at: {}", chrono::Utc::now().to_rfc3339());
+        let _ = writeln!(
+            out,
+            "manifest: {}",
+            serde_json::to_string(&self.manifest).unwrap_or_default()
+        );
+        let _ = writeln!(out, "\n=== backtrace ===\n{}", backtrace);
+        let _ = writeln!(out, "=== last {} log records ===", records.len());
+        for record in &records {
+            let _ = writeln!(out, "{}", serde_json::to_string(record).unwrap_or_default());
+        }
+
+        std::fs::create_dir_all(&self.dump_dir).ok()?;
+        let path = self.dump_dir.join(format!(
+            "crash-{}-{}.log",
+            self.manifest.pid,
+            chrono::Utc::now().format("%Y%m%dT%H%M%S%.3f")
+        ));
+        std::fs::write(&path, out).ok()?;
+        Some(path)
+    }
+}
+
+#[cfg(unix)]
+fn install_signal_handlers() {
+    extern "C" fn on_fatal_signal(signal: libc::c_int) {
+        // 尽力而为：信号上下文中写文件并非异步信号安全，但进程本就即将终止
+        let _ = write_dump(&format!("fatal signal {}", signal));
+        unsafe {
+            libc::signal(signal, libc::SIG_DFL);
+            libc::raise(signal);
+        }
+    }
+
+    let handler = on_fatal_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    unsafe {
+        libc::signal(libc::SIGSEGV, handler);
+        libc::signal(libc::SIGABRT, handler);
+        libc::signal(libc::SIGBUS, handler);
+    }
+}
+
+#[cfg(not(unix))]
+fn install_signal_handlers() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::prelude::*;
+
+    #[test]
+    fn test_dump_contains_reason_manifest_and_latest_records() {
+        let buffer = Arc::new(LogRingBuffer::new(2));
+        let subscriber = tracing_subscriber::registry().with(RingBufferLayer::new(buffer.clone()));
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("第一条");
+            tracing::warn!(order_id = "o-2", "第二条");
+            tracing::error!("第三条");
+        });
+        // 容量为 2，最旧的记录被淘汰
+        let records = buffer.snapshot();
+        assert_eq!(records.iter().map(|r| r.message.as_str()).collect::<Vec<_>>(), ["第二条", "第三条"]);
+        assert_eq!(records[0].fields, vec![("order_id".to_string(), "\"o-2\"".to_string())]);
+
+        let dump_dir = std::env::temp_dir().join(format!("celue_crash_dump_{}", std::process::id()));
+        let state = CrashDumpState { buffer, manifest: RunManifest::current(), dump_dir: dump_dir.clone() };
+        let path = state.write("panic: 测试").unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("reason: panic: 测试\n"));
+        assert!(content.contains(&format!("\"pid\":{}", std::process::id())));
+        assert!(content.contains("=== last 2 log records ==="));
+        assert!(content.contains("第三条") && !content.contains("第一条"));
+        let _ = std::fs::remove_dir_all(dump_dir);
+    }
+}
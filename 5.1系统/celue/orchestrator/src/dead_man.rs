@@ -0,0 +1,438 @@
+//! 无人值守保护 - Dead man's switch
+//!
+//! 实盘无人值守时，操作员（或外部监控系统）必须在 `interval_secs` 内
+//! 通过控制接口发送一次心跳。超过 `warn_after_secs` 未收到心跳时发
+//! Warning 告警提醒；超过 `interval_secs` 则自动降风险：
+//! - 激活全局熔断（`HaltScope::Global`），停止开新仓并撤销挂单；
+//! - 配置了 `flatten_on_trip` 且注册了平仓器时，平掉现有持仓；
+//! - 发 Critical 告警。
+//!
+//! 触发后心跳不会自动恢复交易，需要具备 `ResetKillSwitch` 权限的人
+//! 员显式重新武装（`rearm`，未配置鉴权时拒绝），同时解除本开关激活的
+//! 全局熔断；操作员或其他组件另行激活的全局熔断不受影响。
+
+use std::sync::Arc;
+
+use adapters::alerting::{Alert, AlertManager, AlertSeverity};
+use adapters::halt::{HaltRegistry, HaltScope};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use common::SharedClock;
+use futures_util::StreamExt;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::audit::AuditLog;
+use crate::auth::{AuthService, ControlAction};
+use crate::nats::{encode_reply, NatsManager};
+use common::{ApiResponse, SystemError, SystemResult};
+
+const OPERATOR: &str = "dead_man_switch";
+
+/// 配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadMansSwitchConfig {
+    pub enabled: bool,
+    /// 心跳最长间隔
+    pub interval_secs: u64,
+    /// 超过该时长未收到心跳先发 Warning 告警（0 表示不预警）
+    pub warn_after_secs: u64,
+    /// 触发时是否平仓
+    pub flatten_on_trip: bool,
+    /// 检查周期
+    pub check_interval_secs: u64,
+}
+
+impl Default for DeadMansSwitchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 900,
+            warn_after_secs: 600,
+            flatten_on_trip: false,
+            check_interval_secs: 5,
+        }
+    }
+}
+
+/// 触发时平掉现有持仓
+#[async_trait]
+pub trait PositionFlattener: Send + Sync {
+    /// 返回提交的平仓订单数
+    async fn flatten(&self, reason: &str) -> Result<usize>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum SwitchState {
+    Armed,
+    /// 已预警，尚未触发
+    Warned,
+    Tripped { at: DateTime<Utc>, flattened_orders: Option<usize> },
+}
+
+/// 当前状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwitchStatus {
+    pub enabled: bool,
+    pub state: SwitchState,
+    pub last_heartbeat: DateTime<Utc>,
+    pub last_heartbeat_from: String,
+    /// 距离触发的剩余秒数，已触发为 0
+    pub remaining_secs: u64,
+    pub interval_secs: u64,
+}
+
+struct Inner {
+    state: SwitchState,
+    last_heartbeat_ms: u64,
+    last_heartbeat_from: String,
+}
+
+/// Dead man's switch
+pub struct DeadMansSwitch {
+    config: DeadMansSwitchConfig,
+    halts: Arc<HaltRegistry>,
+    flattener: Option<Arc<dyn PositionFlattener>>,
+    alerts: Option<Arc<AlertManager>>,
+    clock: SharedClock,
+    inner: RwLock<Inner>,
+}
+
+impl DeadMansSwitch {
+    pub fn new(config: DeadMansSwitchConfig, halts: Arc<HaltRegistry>) -> Self {
+        let clock = common::clock::system_clock();
+        let inner = Inner {
+            state: SwitchState::Armed,
+            last_heartbeat_ms: clock.now_millis(),
+            last_heartbeat_from: "startup".to_string(),
+        };
+        Self { config, halts, flattener: None, alerts: None, clock, inner: RwLock::new(inner) }
+    }
+
+    pub fn with_flattener(mut self, flattener: Arc<dyn PositionFlattener>) -> Self {
+        self.flattener = Some(flattener);
+        self
+    }
+
+    pub fn with_alerts(mut self, alerts: Arc<AlertManager>) -> Self {
+        self.alerts = Some(alerts);
+        self
+    }
+
+    /// 测试用时钟；启动时刻按新时钟重新计算
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.inner.get_mut().last_heartbeat_ms = clock.now_millis();
+        self.clock = clock;
+        self
+    }
+
+    /// 记录心跳；已触发时只更新时间，不恢复交易
+    pub fn heartbeat(&self, from: &str) -> SwitchStatus {
+        {
+            let mut inner = self.inner.write();
+            inner.last_heartbeat_ms = self.clock.now_millis();
+            inner.last_heartbeat_from = from.to_string();
+            if inner.state == SwitchState::Warned {
+                inner.state = SwitchState::Armed;
+            }
+        }
+        metrics::counter!("dead_man_heartbeats_total", 1);
+        self.status()
+    }
+
+    /// 触发后重新武装，只解除本开关激活的全局熔断
+    pub fn rearm(&self, operator: &str) -> SystemResult<SwitchStatus> {
+        {
+            let mut inner = self.inner.write();
+            if !matches!(inner.state, SwitchState::Tripped { .. }) {
+                return Err(SystemError::InvalidRequest("dead man's switch 未触发".to_string()));
+            }
+            inner.state = SwitchState::Armed;
+            inner.last_heartbeat_ms = self.clock.now_millis();
+            inner.last_heartbeat_from = operator.to_string();
+        }
+        self.halts.release(&HaltScope::Global, OPERATOR);
+        info!("✅ Dead man's switch 已由 {} 重新武装", operator);
+        Ok(self.status())
+    }
+
+    pub fn status(&self) -> SwitchStatus {
+        let inner = self.inner.read();
+        let silent_secs = self.clock.now_millis().saturating_sub(inner.last_heartbeat_ms) / 1000;
+        let remaining_secs = match inner.state {
+            SwitchState::Tripped { .. } => 0,
+            _ => self.config.interval_secs.saturating_sub(silent_secs),
+        };
+        SwitchStatus {
+            enabled: self.config.enabled,
+            state: inner.state.clone(),
+            last_heartbeat: DateTime::<Utc>::from_timestamp_millis(inner.last_heartbeat_ms as i64).unwrap_or_default(),
+            last_heartbeat_from: inner.last_heartbeat_from.clone(),
+            remaining_secs,
+            interval_secs: self.config.interval_secs,
+        }
+    }
+
+    /// 检查心跳间隔，必要时预警或触发
+    pub async fn check(&self) -> SwitchState {
+        if !self.config.enabled {
+            return self.inner.read().state.clone();
+        }
+        let (silent_secs, state) = {
+            let inner = self.inner.read();
+            (self.clock.now_millis().saturating_sub(inner.last_heartbeat_ms) / 1000, inner.state.clone())
+        };
+        metrics::gauge!("dead_man_silent_seconds", silent_secs as f64);
+
+        match state {
+            SwitchState::Tripped { .. } => {}
+            _ if silent_secs >= self.config.interval_secs => self.trip(silent_secs).await,
+            SwitchState::Armed if self.config.warn_after_secs > 0 && silent_secs >= self.config.warn_after_secs => {
+                self.inner.write().state = SwitchState::Warned;
+                warn!("⚠️ {}s 未收到操作员心跳", silent_secs);
+                self.raise(Alert::new(
+                    "dead_man_switch",
+                    AlertSeverity::Warning,
+                    "操作员心跳即将超时",
+                    &format!(
+                        "{}s 未收到心跳，{}s 后自动降风险",
+                        silent_secs,
+                        self.config.interval_secs.saturating_sub(silent_secs)
+                    ),
+                    OPERATOR,
+                ));
+            }
+            _ => {}
+        }
+        self.inner.read().state.clone()
+    }
+
+    async fn trip(&self, silent_secs: u64) {
+        let reason = format!("{}s 未收到操作员心跳", silent_secs);
+        {
+            let mut inner = self.inner.write();
+            if matches!(inner.state, SwitchState::Tripped { .. }) {
+                return;
+            }
+            inner.state = SwitchState::Tripped { at: self.clock.now(), flattened_orders: None };
+        }
+        error!("🚨 Dead man's switch 触发: {}", reason);
+        metrics::counter!("dead_man_trips_total", 1);
+
+        let cancelled = match self.halts.halt(HaltScope::Global, &reason, OPERATOR).await {
+            Ok(cancelled) => cancelled,
+            Err(e) => {
+                error!("全局熔断激活失败: {}", e);
+                0
+            }
+        };
+
+        let mut flattened = None;
+        if let (true, Some(flattener)) = (self.config.flatten_on_trip, &self.flattener) {
+            match flattener.flatten(&reason).await {
+                Ok(orders) => flattened = Some(orders),
+                Err(e) => error!("自动平仓失败: {}", e),
+            }
+            if let SwitchState::Tripped { flattened_orders, .. } = &mut self.inner.write().state {
+                *flattened_orders = flattened;
+            }
+        }
+
+        let flatten_note = match flattened {
+            Some(orders) => format!("，已提交 {} 笔平仓单", orders),
+            None if self.config.flatten_on_trip => "，自动平仓失败".to_string(),
+            None => String::new(),
+        };
+        self.raise(Alert::new(
+            "dead_man_switch",
+            AlertSeverity::Critical,
+            "Dead man's switch 已触发",
+            &format!("{}：已停止开新仓，撤单 {} 笔{}", reason, cancelled, flatten_note),
+            OPERATOR,
+        ));
+    }
+
+    fn raise(&self, alert: Alert) {
+        if let Some(alerts) = self.alerts.clone() {
+            if let Ok(handle) = tokio::runtime::Handle::try_current() {
+                handle.spawn(async move {
+                    alerts.raise(alert).await;
+                });
+            }
+        }
+    }
+
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let period = std::time::Duration::from_secs(self.config.check_interval_secs.max(1));
+            let mut ticker = tokio::time::interval(period);
+            loop {
+                ticker.tick().await;
+                self.check().await;
+            }
+        })
+    }
+}
+
+/// 控制请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum DeadManCommand {
+    Heartbeat,
+    Status,
+    Rearm,
+}
+
+impl DeadManCommand {
+    fn action(&self) -> ControlAction {
+        match self {
+            DeadManCommand::Heartbeat => ControlAction::Heartbeat,
+            DeadManCommand::Status => ControlAction::ViewDashboard,
+            DeadManCommand::Rearm => ControlAction::ResetKillSwitch,
+        }
+    }
+}
+
+/// 控制服务
+pub struct DeadManService {
+    switch: Arc<DeadMansSwitch>,
+    auth: Option<Arc<AuthService>>,
+    audit: Option<Arc<AuditLog>>,
+}
+
+impl DeadManService {
+    pub fn new(switch: Arc<DeadMansSwitch>) -> Self {
+        Self { switch, auth: None, audit: None }
+    }
+
+    pub fn with_auth(mut self, auth: Arc<AuthService>) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    pub fn with_audit(mut self, audit: Arc<AuditLog>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// 心跳网关：请求体为 `DeadManCommand` JSON，响应为 `ApiResponse<SwitchStatus>`
+    pub async fn serve(self: Arc<Self>, nats: &NatsManager, subject: &str) -> Result<()> {
+        let mut subscriber = nats.subscribe(subject).await?;
+        info!("💓 心跳网关已启动: {}", subject);
+
+        while let Some(message) = subscriber.next().await {
+            let Some(reply) = message.reply.clone() else { continue };
+            let result = async {
+                let command = serde_json::from_slice::<DeadManCommand>(&message.payload)
+                    .map_err(|e| SystemError::InvalidRequest(e.to_string()))?;
+                let action = command.action();
+                let principal = match (&self.auth, action) {
+                    (Some(auth), _) => Some(auth.authorize_nats(&message, action)?),
+                    // 重新武装会恢复交易，未配置鉴权时不允许匿名执行
+                    (None, ControlAction::ResetKillSwitch) => {
+                        return Err(SystemError::Unauthorized("rearming requires authentication".to_string()))
+                    }
+                    (None, _) => None,
+                };
+                let caller = principal.as_ref().map(|p| p.subject.as_str()).unwrap_or("anonymous");
+                let status = match command {
+                    DeadManCommand::Heartbeat => self.switch.heartbeat(caller),
+                    DeadManCommand::Status => self.switch.status(),
+                    DeadManCommand::Rearm => {
+                        let status = self.switch.rearm(caller)?;
+                        if let (Some(audit), Some(principal)) = (&self.audit, &principal) {
+                            audit.record(principal, action, "dead_man_switch", serde_json::json!({})).await?;
+                        }
+                        status
+                    }
+                };
+                Ok::<_, SystemError>(status)
+            }
+            .await;
+            let response: ApiResponse<SwitchStatus> = result.into();
+            if let Err(e) = nats
+                .get_client()
+                .publish(reply, encode_reply(&message, &response)?.into())
+                .await
+            {
+                warn!("心跳网关响应发送失败: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::SimulatedClock;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct CountingFlattener(AtomicUsize);
+
+    #[async_trait]
+    impl PositionFlattener for CountingFlattener {
+        async fn flatten(&self, _reason: &str) -> Result<usize> {
+            Ok(self.0.fetch_add(1, Ordering::SeqCst) + 3)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_trips_without_heartbeat_and_rearms() {
+        let clock = Arc::new(SimulatedClock::from_nanos(1_000_000_000_000));
+        let halts = Arc::new(HaltRegistry::new());
+        let flattener = Arc::new(CountingFlattener(AtomicUsize::new(0)));
+        let config = DeadMansSwitchConfig {
+            enabled: true,
+            interval_secs: 60,
+            warn_after_secs: 40,
+            flatten_on_trip: true,
+            ..Default::default()
+        };
+        let switch = DeadMansSwitch::new(config, halts.clone())
+            .with_flattener(flattener.clone())
+            .with_clock(clock.clone());
+
+        clock.advance(chrono::Duration::seconds(45));
+        assert_eq!(switch.check().await, SwitchState::Warned);
+        switch.heartbeat("alice");
+        assert_eq!(switch.status().remaining_secs, 60);
+
+        clock.advance(chrono::Duration::seconds(61));
+        let SwitchState::Tripped { flattened_orders, .. } = switch.check().await else { panic!("expected trip") };
+        assert_eq!(flattened_orders, Some(3));
+        assert_eq!(halts.active()[0].scope, HaltScope::Global);
+
+        // 触发后心跳不恢复交易，重复检查也不会再次平仓
+        switch.heartbeat("alice");
+        assert!(matches!(switch.check().await, SwitchState::Tripped { .. }));
+        assert_eq!(flattener.0.load(Ordering::SeqCst), 1);
+
+        switch.rearm("bob").unwrap();
+        assert!(halts.active().is_empty());
+        assert_eq!(switch.check().await, SwitchState::Armed);
+        assert!(switch.rearm("bob").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rearm_keeps_operator_global_halt() {
+        let clock = Arc::new(SimulatedClock::from_nanos(1_000_000_000_000));
+        let halts = Arc::new(HaltRegistry::new());
+        let config = DeadMansSwitchConfig { enabled: true, interval_secs: 60, warn_after_secs: 0, ..Default::default() };
+        let switch = DeadMansSwitch::new(config, halts.clone()).with_clock(clock.clone());
+        halts.halt(HaltScope::Global, "incident", "alice").await.unwrap();
+
+        clock.advance(chrono::Duration::seconds(61));
+        assert!(matches!(switch.check().await, SwitchState::Tripped { .. }));
+        assert_eq!(halts.active().len(), 2);
+
+        // 重新武装不得解除操作员自己的全局熔断
+        switch.rearm("alice").unwrap();
+        let remaining = halts.active();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].operator, "alice");
+    }
+}
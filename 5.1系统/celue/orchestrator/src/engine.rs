@@ -9,10 +9,27 @@ use serde::{Serialize, Deserialize};
 use tracing::{info, warn, error, debug};
 use anyhow::Result;
 
-use strategy::{StrategyContext, traits::{ArbitrageStrategy, ExecutionResult}};
+use strategy::{StrategyContext, scoring::{net_profit_bps, OpportunityScorer}, staleness_guard::StaleDataGuard, traits::{ArbitrageStrategy, ExecutionResult, StrategyError}};
+use strategy::admission::{AdmissionController, BudgetUtilization};
 use common::{ArbitrageOpportunity, market_data::OrderBook};
 use crate::config::SystemConfig;
 use crate::risk::{DynamicRiskController, StrategyRiskInterface};
+use crate::inference::{InferenceSource, RiskFeatures, RiskInference};
+use adapters::funding::{apply_funding_cost, FundingRateStore};
+use adapters::halt::HaltRegistry;
+use adapters::trading_calendar::TradingCalendar;
+use adapters::venue_scorecard::VenueScorecard;
+use adapters::venue_selector::VenueSelector;
+use adapters::opportunity_ttl::OpportunityPool;
+use adapters::symbol_controls::SymbolControls;
+use crate::shutdown::{GracefulShutdown, ShutdownGate};
+use crate::leader::LeaderElector;
+use adapters::trading_mode::{TradingMode, TradingModeController};
+use crate::journal::{EventJournal, JournalEvent};
+use adapters::approval::ApprovalGate;
+
+/// 待审批机会上记录所属策略注册名的标签
+const APPROVAL_STRATEGY_TAG: &str = "approval_strategy";
 
 /// 配置驱动的套利引擎
 // Debug trait removed due to complex inner types
@@ -27,6 +44,40 @@ pub struct ConfigurableArbitrageEngine {
     config: Arc<RwLock<EngineConfig>>,
     /// 执行统计
     stats: Arc<RwLock<EngineStats>>,
+    /// 资金费率存储（用于期现/跨期策略的持仓成本估算）
+    funding_store: Option<Arc<dyn FundingRateStore>>,
+    /// 机会评分器（置信度/风险分数）
+    scorer: Option<Arc<OpportunityScorer>>,
+    /// AI风控模型推理
+    risk_inference: Option<Arc<RiskInference>>,
+    /// 局部熔断（交易所/币对/策略）
+    halts: Option<Arc<HaltRegistry>>,
+    /// 币对级交易开关（禁止检测/执行）
+    symbol_controls: Option<Arc<SymbolControls>>,
+    /// 交易日历（维护/结算窗口）
+    trading_calendar: Option<Arc<TradingCalendar>>,
+    /// 交易所长期评分（路由权重）
+    venue_scorecard: Option<Arc<VenueScorecard>>,
+    /// 交易所近期延迟与成交可靠性评分
+    venue_selector: Option<Arc<VenueSelector>>,
+    /// 机会池（按交易所延迟计算TTL，随行情刷新/失效）
+    opportunity_pool: Option<Arc<OpportunityPool>>,
+    /// 停机准入闸门
+    shutdown_gate: Option<Arc<ShutdownGate>>,
+    /// 停机协调器：排空、撤单、落盘、逆序停止模块
+    graceful_shutdown: Option<Arc<GracefulShutdown>>,
+    /// 主备选举；跟随者只检测不执行
+    leader: Option<Arc<LeaderElector>>,
+    /// 全局交易模式；干跑时订单路由到影子撮合引擎
+    trading_mode: Arc<TradingModeController>,
+    /// 事件溯源日志
+    journal: Option<Arc<EventJournal>>,
+    /// 陈旧行情保护：检测前剔除陈旧订单簿
+    stale_guard: Option<Arc<StaleDataGuard>>,
+    /// 策略资源预算准入与API限流
+    admission: Option<Arc<AdmissionController>>,
+    /// 大额机会人工审批闸门
+    approval_gate: Option<Arc<ApprovalGate>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +90,12 @@ pub struct EngineConfig {
     pub enable_risk_check: bool,
     /// 机会检测间隔（毫秒）
     pub opportunity_check_interval_ms: u64,
+    /// 预期持仓时长（毫秒），用于估算资金费率成本
+    #[serde(default = "default_expected_holding_period_ms")]
+    pub expected_holding_period_ms: u64,
+    /// 模型风险分数上限，超过则拒绝机会
+    #[serde(default = "default_max_model_risk_score")]
+    pub max_model_risk_score: f32,
 }
 
 impl Default for EngineConfig {
@@ -56,10 +113,24 @@ impl Default for EngineConfig {
             opportunity_check_interval_ms: std::env::var("CELUE_OPPORTUNITY_CHECK_INTERVAL_MS")
                 .ok().and_then(|s| s.parse().ok())
                 .unwrap_or(100),
+            expected_holding_period_ms: default_expected_holding_period_ms(),
+            max_model_risk_score: default_max_model_risk_score(),
         }
     }
 }
 
+fn default_expected_holding_period_ms() -> u64 {
+    std::env::var("CELUE_EXPECTED_HOLDING_PERIOD_MS")
+        .ok().and_then(|s| s.parse().ok())
+        .unwrap_or(8 * 60 * 60 * 1000)
+}
+
+fn default_max_model_risk_score() -> f32 {
+    std::env::var("CELUE_MAX_MODEL_RISK_SCORE")
+        .ok().and_then(|s| s.parse().ok())
+        .unwrap_or(0.8)
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct EngineStats {
     pub strategies_registered: usize,
@@ -68,6 +139,9 @@ pub struct EngineStats {
     pub total_pnl: f64,
     pub avg_execution_time_ms: f64,
     pub success_rate: f64,
+    /// 各策略资源预算使用情况
+    #[serde(default)]
+    pub budgets: Vec<BudgetUtilization>,
 }
 
 impl ConfigurableArbitrageEngine {
@@ -85,15 +159,156 @@ impl ConfigurableArbitrageEngine {
             strategy_context,
             config: Arc::new(RwLock::new(engine_config)),
             stats: Arc::new(RwLock::new(EngineStats::default())),
+            funding_store: None,
+            scorer: None,
+            risk_inference: None,
+            halts: None,
+            symbol_controls: None,
+            trading_calendar: None,
+            venue_scorecard: None,
+            venue_selector: None,
+            opportunity_pool: None,
+            shutdown_gate: None,
+            graceful_shutdown: None,
+            leader: None,
+            trading_mode: Arc::new(TradingModeController::new(system_config.trading_mode)),
+            journal: None,
+            stale_guard: None,
+            admission: None,
+            approval_gate: None,
+        }
+    }
+
+    /// 启用资金费率感知的利润计算
+    pub fn with_funding_store(mut self, store: Arc<dyn FundingRateStore>) -> Self {
+        self.funding_store = Some(store);
+        self
+    }
+
+    /// 启用基于特征模型的机会评分
+    pub fn with_scorer(mut self, scorer: Arc<OpportunityScorer>) -> Self {
+        self.scorer = Some(scorer);
+        self
+    }
+
+    /// 启用AI风控模型推理
+    pub fn with_risk_inference(mut self, inference: Arc<RiskInference>) -> Self {
+        self.risk_inference = Some(inference);
+        self
+    }
+
+    /// 启用局部熔断检查
+    pub fn with_halts(mut self, halts: Arc<HaltRegistry>) -> Self {
+        self.halts = Some(halts);
+        self
+    }
+
+    /// 启用币对级交易开关：禁止检测的币对不进入策略，禁止执行的机会不认领
+    pub fn with_symbol_controls(mut self, controls: Arc<SymbolControls>) -> Self {
+        self.symbol_controls = Some(controls);
+        self
+    }
+
+    /// 启用交易日历：维护/结算窗口内（含窗口前缓冲）不认领涉及该交易所/币对的机会
+    pub fn with_trading_calendar(mut self, calendar: Arc<TradingCalendar>) -> Self {
+        self.trading_calendar = Some(calendar);
+        self
+    }
+
+    /// 启用交易所评分：评分作为路由权重写入策略上下文，用于同等利润下的路由选择
+    pub fn with_venue_scorecard(mut self, scorecard: Arc<VenueScorecard>) -> Self {
+        self.venue_scorecard = Some(scorecard);
+        self
+    }
+
+    /// 启用延迟加权的交易所选择：利润接近的交易所对优先选择延迟低、成交可靠的交易所
+    pub fn with_venue_selector(mut self, selector: Arc<VenueSelector>) -> Self {
+        self.venue_selector = Some(selector);
+        self
+    }
+
+    /// 启用机会池：TTL按交易所往返延迟、行情更新频率与波动率计算，确认行情到达时续期
+    pub fn with_opportunity_pool(mut self, pool: Arc<OpportunityPool>) -> Self {
+        self.opportunity_pool = Some(pool);
+        self
+    }
+
+    /// 接入停机闸门：闸门关闭后不再认领新机会，执行中的机会计入排空
+    pub fn with_shutdown_gate(mut self, gate: Arc<ShutdownGate>) -> Self {
+        self.shutdown_gate = Some(gate);
+        self
+    }
+
+    /// 停机时按协调器流程排空并撤单，同时共用其准入闸门
+    pub fn with_graceful_shutdown(mut self, shutdown: Arc<GracefulShutdown>) -> Self {
+        self.shutdown_gate = Some(shutdown.gate());
+        self.graceful_shutdown = Some(shutdown);
+        self
+    }
+
+    /// 启用主备模式：非领导者以影子模式运行，只检测不执行
+    pub fn with_leader_elector(mut self, leader: Arc<LeaderElector>) -> Self {
+        self.leader = Some(leader);
+        self
+    }
+
+    /// 与执行、资金模块共享同一交易模式开关
+    pub fn with_trading_mode(mut self, trading_mode: Arc<TradingModeController>) -> Self {
+        self.trading_mode = trading_mode;
+        self
+    }
+
+    pub fn trading_mode(&self) -> Arc<TradingModeController> {
+        self.trading_mode.clone()
+    }
+
+    /// 将机会、风控决策、下单与熔断事件写入事件日志
+    pub fn with_journal(mut self, journal: Arc<EventJournal>) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// 检测前剔除超过最大时延的交易所订单簿
+    pub fn with_stale_data_guard(mut self, guard: Arc<StaleDataGuard>) -> Self {
+        self.stale_guard = Some(guard);
+        self
+    }
+
+    /// 所需资金超过阈值的机会需人工批准后执行
+    pub fn with_approval_gate(mut self, gate: Arc<ApprovalGate>) -> Self {
+        self.approval_gate = Some(gate);
+        self
+    }
+
+    pub fn approval_gate(&self) -> Option<Arc<ApprovalGate>> {
+        self.approval_gate.clone()
+    }
+
+    /// 注册策略前按声明的资源需求准入，执行时按API速率预算限流
+    pub fn with_admission_controller(mut self, admission: Arc<AdmissionController>) -> Self {
+        self.admission = Some(admission);
+        self
+    }
+
+    fn journal(&self, event: JournalEvent) {
+        if let Some(journal) = &self.journal {
+            journal.record(event);
         }
     }
 
+    fn journal_decision(&self, opportunity: &ArbitrageOpportunity, approved: bool, reason: Option<String>) {
+        self.journal(JournalEvent::RiskDecision { opportunity_id: opportunity.id.to_string(), approved, reason });
+    }
+
     /// 注册策略
     pub async fn register_strategy(
         &self,
         name: String,
         strategy: Arc<dyn ArbitrageStrategy + Send + Sync>,
     ) -> Result<()> {
+        if let Some(admission) = &self.admission {
+            admission.admit(&name, &strategy.resource_requirements())?;
+        }
         let mut strategies = self.strategies.write().await;
         strategies.insert(name.clone(), strategy);
         
@@ -107,82 +322,240 @@ impl ConfigurableArbitrageEngine {
 
     /// 检测机会并执行策略（风险集成）
     pub async fn detect_and_execute(&self, market_snapshot: &common::market_data::NormalizedSnapshot) -> Result<Vec<ExecutionResult>> {
+        let _in_flight = match &self.shutdown_gate {
+            Some(gate) => match gate.try_enter() {
+                Some(guard) => Some(guard),
+                None => {
+                    debug!("🛑 引擎停机中，跳过机会检测");
+                    return Ok(vec![]);
+                }
+            },
+            None => None,
+        };
         let config = self.config.read().await;
         
         // 风险检查
         if config.enable_risk_check {
             if !self.risk_controller.perform_risk_check().await? {
                 warn!("🚫 风险检查失败，停止策略执行");
+                self.journal(JournalEvent::BreakerTripped { scope: "global".to_string(), reason: "风险检查失败".to_string() });
                 return Ok(vec![]);
             }
         }
 
+        // 长期评分与近期延迟评分相乘作为路由权重
+        let mut weights: HashMap<String, f64> = HashMap::new();
+        for source in [
+            self.venue_scorecard.as_ref().map(|s| s.relative_weights()),
+            self.venue_selector.as_ref().map(|s| s.relative_weights()),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            for (exchange, weight) in source {
+                *weights.entry(exchange).or_insert(1.0) *= weight;
+            }
+        }
+        for (exchange, weight) in weights {
+            self.strategy_context.set_exchange_weight(&exchange, weight);
+        }
+
+        // 用最新行情刷新或失效池中的机会
+        if let Some(pool) = &self.opportunity_pool {
+            for book in &market_snapshot.exchanges {
+                pool.on_book(book);
+            }
+        }
+
+        // 按币对开关剔除禁止检测的订单簿
+        let controlled = self.symbol_controls.as_ref().and_then(|c| c.filter_snapshot(market_snapshot));
+        let market_snapshot = controlled.as_ref().unwrap_or(market_snapshot);
+
+        // 以快照时间为基准剔除陈旧订单簿
+        let guarded = match &self.stale_guard {
+            Some(guard) => {
+                let guarded = guard.filter(market_snapshot, market_snapshot.timestamp_ns);
+                guard.notify(&guarded.transitions).await;
+                Some(guarded)
+            }
+            None => None,
+        };
+        let market_snapshot = guarded.as_ref().map(|g| &g.snapshot).unwrap_or(market_snapshot);
+
         let strategies = self.strategies.read().await;
         let mut results = Vec::new();
         let mut opportunities_count = 0u64;
 
-        // 遍历所有注册的策略
-        for (strategy_name, strategy) in strategies.iter() {
-            // 检测机会
-            if let Some(opportunity) = strategy.detect(&self.strategy_context, market_snapshot) {
-                opportunities_count += 1;
-
-                // 策略级风险检查
+        // 已批准的大额机会（审批期限不超过机会TTL）：重新检查熔断、币对开关、交易日历与风控后执行
+        if let Some(gate) = &self.approval_gate {
+            for opportunity in gate.take_approved() {
+                let strategy_name = opportunity
+                    .tags
+                    .get(APPROVAL_STRATEGY_TAG)
+                    .cloned()
+                    .unwrap_or_else(|| opportunity.strategy_name.clone());
+                let Some(strategy) = strategies.get(&strategy_name) else {
+                    warn!("⚠️ 已批准机会 {} 的策略 {} 未注册，放弃执行", opportunity.id, strategy_name);
+                    continue;
+                };
+                if let Some(halt) = self.halts.as_ref().and_then(|h| h.blocking_halt(&opportunity)) {
+                    warn!("⛔ 已批准机会 {} 被局部熔断阻止: {:?}", opportunity.id, halt.scope);
+                    self.journal_decision(&opportunity, false, Some(format!("halted: {:?}", halt.scope)));
+                    continue;
+                }
+                if let Some(control) = self.symbol_controls.as_ref().and_then(|c| c.blocking_control(&opportunity)) {
+                    warn!("⛔ 已批准机会 {} 涉及已禁用币对 {}", opportunity.id, control.key.symbol);
+                    self.journal_decision(&opportunity, false, Some(format!("symbol disabled: {}", control.key.symbol)));
+                    continue;
+                }
+                if let Some(window) = self.trading_calendar.as_ref().and_then(|c| c.blocking_window_for(&opportunity, chrono::Utc::now())) {
+                    warn!("🕒 已批准机会 {} 处于 {} 的 {:?} 窗口: {}", opportunity.id, window.exchange, window.kind, window.reason);
+                    self.journal_decision(&opportunity, false, Some(format!("calendar window: {}", window.reason)));
+                    continue;
+                }
                 if config.enable_risk_check {
                     let expected_profit = opportunity.net_profit.to_f64();
-                    let can_execute = self.risk_controller
-                        .can_execute_strategy(strategy_name, expected_profit)
-                        .await;
-                    
-                    if !can_execute {
-                        warn!("🚫 策略 {} 被风控阻止，预期利润: ${:.2}", strategy_name, expected_profit);
+                    if !self.risk_controller.can_execute_strategy(&strategy_name, expected_profit).await {
+                        warn!("🚫 已批准机会 {} 被风控阻止，预期利润: ${:.2}", opportunity.id, expected_profit);
+                        self.journal_decision(&opportunity, false, Some("strategy risk limit".to_string()));
                         continue;
                     }
                 }
+                info!("✅ 执行已人工批准的机会 {}", opportunity.id);
+                if let Some(result) = self.execute_opportunity(&strategy_name, strategy, opportunity).await {
+                    results.push(result);
+                }
+            }
+        }
 
-                // 执行策略
-                let execution_start = std::time::Instant::now();
-                let result = strategy.execute(&self.strategy_context, &opportunity).await;
-                let execution_time = execution_start.elapsed().as_millis() as f64;
+        // 检测各策略机会并评分（置信度/风险写入标签，供去重使用），按期望收益从高到低处理
+        let mut detected = Vec::new();
+        for (strategy_name, strategy) in strategies.iter() {
+            if let Some(mut opportunity) = strategy.detect(&self.strategy_context, market_snapshot) {
+                let rank = match &self.scorer {
+                    Some(scorer) => {
+                        let score = scorer.score(&mut opportunity, market_snapshot);
+                        debug!("🎯 策略 {} 评分: 置信度 {:.3}, 风险 {:.3}", strategy_name, score.confidence, score.risk);
+                        score.expected_edge_bps(net_profit_bps(&opportunity))
+                    }
+                    None => net_profit_bps(&opportunity),
+                };
+                detected.push((rank, strategy_name, strategy, opportunity));
+            }
+        }
+        detected.sort_by(|a, b| b.0.total_cmp(&a.0));
 
-                match result {
-                    Ok(exec_result) => {
-                                // 计算实际利润（基于机会的预期利润和执行状态）
-        let profit = if exec_result.accepted { 
-            opportunity.net_profit.to_f64() 
-        } else { 
-            -opportunity.net_profit.to_f64().abs() * 0.1 // 失败时的小幅损失
-        };
-                        self.risk_controller
-                            .report_strategy_result(
-                                strategy_name,
-                                profit,
-                                exec_result.accepted,
-                            )
-                            .await;
+        for (_, strategy_name, strategy, mut opportunity) in detected {
+            opportunities_count += 1;
+            if let Some(guarded) = &guarded {
+                guarded.mark(&mut opportunity);
+            }
+            self.journal(JournalEvent::OpportunityDetected {
+                opportunity_id: opportunity.id.to_string(),
+                strategy: strategy_name.clone(),
+                symbol: opportunity.legs.first().map(|l| l.symbol.to_string()).unwrap_or_default(),
+                net_profit: opportunity.net_profit.to_f64(),
+            });
 
-                        // 更新统计
-                        self.update_stats(&exec_result, execution_time).await;
-                        
-                        results.push(exec_result.clone());
-                        
-                        if exec_result.accepted {
-                            info!("✅ 策略 {} 执行成功，订单ID: {:?}", 
-                                  strategy_name, exec_result.order_ids);
-                        } else {
-                            warn!("❌ 策略 {} 执行失败: {}", 
-                                  strategy_name, exec_result.reason.as_deref().unwrap_or("未知原因"));
-                        }
-                    }
-                    Err(e) => {
-                        error!("💥 策略 {} 执行异常: {}", strategy_name, e);
-                        
-                        // 报告失败给风险控制器
-                        self.risk_controller
-                            .report_strategy_result(strategy_name, 0.0, false)
-                            .await;
+            // 局部熔断：交易所/币对/策略被暂停时不认领该机会
+            if let Some(halt) = self.halts.as_ref().and_then(|h| h.blocking_halt(&opportunity)) {
+                warn!("⛔ 策略 {} 机会被局部熔断阻止: {:?} ({})", strategy_name, halt.scope, halt.reason);
+                self.journal_decision(&opportunity, false, Some(format!("halted: {:?}", halt.scope)));
+                continue;
+            }
+
+            // 币对开关：禁止执行的币对不认领
+            if let Some(control) = self.symbol_controls.as_ref().and_then(|c| c.blocking_control(&opportunity)) {
+                warn!("⛔ 策略 {} 机会涉及已禁用币对 {} ({})", strategy_name, control.key.symbol, control.reason);
+                self.journal_decision(&opportunity, false, Some(format!("symbol disabled: {}", control.key.symbol)));
+                continue;
+            }
+
+            // 交易日历：维护/结算窗口内不认领
+            if let Some(window) = self.trading_calendar.as_ref().and_then(|c| c.blocking_window_for(&opportunity, chrono::Utc::now())) {
+                warn!("🕒 策略 {} 机会处于 {} 的 {:?} 窗口: {}", strategy_name, window.exchange, window.kind, window.reason);
+                continue;
+            }
+
+            // 按交易所延迟设置TTL并加入机会池
+            // 与其他策略重叠的机会按风险调整收益去重
+            if let Some(pool) = &self.opportunity_pool {
+                let id = opportunity.id;
+                match pool.admit(opportunity) {
+                    Some(admitted) => opportunity = admitted,
+                    None => {
+                        debug!("🔁 策略 {} 机会 {} 与已有机会重叠，已抑制", strategy_name, id);
+                        continue;
                     }
                 }
+                debug!("⏱️ 策略 {} 机会TTL: {}ms", strategy_name, opportunity.ttl_ns / 1_000_000);
+            }
+
+            // 扣除预计持仓期内的资金费率成本
+            if let Some(store) = &self.funding_store {
+                let funding_cost = apply_funding_cost(
+                    &mut opportunity,
+                    store.as_ref(),
+                    config.expected_holding_period_ms,
+                );
+                if funding_cost != 0.0 {
+                    debug!("💸 策略 {} 资金费率成本: ${:.4}", strategy_name, funding_cost);
+                }
+                if !opportunity.net_profit.is_positive() {
+                    debug!("🚫 策略 {} 扣除资金费率后无利润", strategy_name);
+                    continue;
+                }
+            }
+
+            // AI风控模型评分
+            if let Some(inference) = &self.risk_inference {
+                let status = self.risk_controller.get_risk_status().await;
+                let now_ns = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64;
+                let features = RiskFeatures::from_opportunity(
+                    &opportunity,
+                    now_ns,
+                    status.daily_pnl,
+                    status.consecutive_failures,
+                );
+                let outcome = inference.infer(&features).await;
+                if outcome.source == InferenceSource::Fallback {
+                    debug!("🧠 策略 {} 使用规则风控评分", strategy_name);
+                }
+                if outcome.risk_score > config.max_model_risk_score {
+                    warn!("🚫 策略 {} 被AI风控阻止: 风险分数 {:.3} ({})", strategy_name, outcome.risk_score, outcome.model_version);
+                    self.journal_decision(&opportunity, false, Some(format!("model risk score {:.3}", outcome.risk_score)));
+                    continue;
+                }
+            }
+
+            // 策略级风险检查
+            if config.enable_risk_check {
+                let expected_profit = opportunity.net_profit.to_f64();
+                let can_execute = self.risk_controller
+                    .can_execute_strategy(strategy_name, expected_profit)
+                    .await;
+                
+                if !can_execute {
+                    warn!("🚫 策略 {} 被风控阻止，预期利润: ${:.2}", strategy_name, expected_profit);
+                    self.journal_decision(&opportunity, false, Some("strategy risk limit".to_string()));
+                    continue;
+                }
+            }
+            self.journal_decision(&opportunity, true, None);
+
+            // 所需资金超过名义金额阈值的机会转人工审批，批准后在后续轮次执行
+            if let Some(gate) = self.approval_gate.as_ref().filter(|g| g.requires_approval(&opportunity)) {
+                opportunity.tags.insert(APPROVAL_STRATEGY_TAG.to_string(), strategy_name.clone());
+                let request = gate.submit(opportunity);
+                info!(
+                    "✋ 策略 {} 机会 {} 所需资金 ${:.2} 超过阈值，等待人工审批",
+                    strategy_name, request.opportunity.id, request.required_funds
+                );
+                continue;
+            }
+
+            if let Some(result) = self.execute_opportunity(strategy_name, strategy, opportunity).await {
+                results.push(result);
             }
         }
 
@@ -193,6 +566,106 @@ impl ConfigurableArbitrageEngine {
         Ok(results)
     }
 
+    /// 执行已通过全部检查的机会：领导者校验、API预算、移出机会池后下单
+    async fn execute_opportunity(
+        &self,
+        strategy_name: &str,
+        strategy: &Arc<dyn ArbitrageStrategy + Send + Sync>,
+        mut opportunity: ArbitrageOpportunity,
+    ) -> Option<ExecutionResult> {
+        // 主备模式：只有持有有效 fencing token 的领导者执行；执行适配器下单前逐笔复核
+        if let Some(leader) = &self.leader {
+            match leader.fencing_token() {
+                Some(token) => {
+                    opportunity.tags.insert(adapters::execution::FENCING_TOKEN_TAG.to_string(), token.to_string());
+                }
+                None => {
+                    debug!("👥 策略 {} 影子模式（非领导者），跳过执行", strategy_name);
+                    return None;
+                }
+            }
+        }
+
+        // API速率预算：每条腿计一次下单调用
+        if let Some(admission) = &self.admission {
+            let now_ms = chrono::Utc::now().timestamp_millis().max(0) as u64;
+            if !admission.try_acquire_api(strategy_name, opportunity.legs.len().max(1) as u32, now_ms) {
+                warn!("🐢 策略 {} 超出API速率预算，本次不执行", strategy_name);
+                return None;
+            }
+        }
+
+        // 执行前移出机会池
+        if let Some(pool) = &self.opportunity_pool {
+            pool.consume(&opportunity.id);
+        }
+
+        // 执行策略；干跑模式下订单只进影子撮合引擎
+        let mode = self.trading_mode.mode();
+        opportunity.tags.insert("trading_mode".to_string(), mode.as_str().to_string());
+        let execution_start = std::time::Instant::now();
+        let result = if mode == TradingMode::DryRun {
+            self.trading_mode
+                .route_to_shadow(&opportunity)
+                .map(|r| ExecutionResult { accepted: r.success, reason: Some(r.details), order_ids: r.order_ids })
+                .map_err(|e| StrategyError::ExecutionFailed(e.to_string()))
+        } else {
+            strategy.execute(&self.strategy_context, &opportunity).await
+        };
+        let execution_time = execution_start.elapsed().as_millis() as f64;
+
+        match result {
+            Ok(exec_result) => {
+                // 计算实际利润（基于机会的预期利润和执行状态）
+                let profit = if exec_result.accepted {
+                    opportunity.net_profit.to_f64()
+                } else {
+                    -opportunity.net_profit.to_f64().abs() * 0.1 // 失败时的小幅损失
+                };
+                self.risk_controller
+                    .report_strategy_result(
+                        strategy_name,
+                        profit,
+                        exec_result.accepted,
+                    )
+                    .await;
+
+                // 更新统计
+                self.update_stats(&exec_result, execution_time).await;
+                
+                for order_id in &exec_result.order_ids {
+                    self.journal(JournalEvent::OrderSubmitted {
+                        order_id: order_id.clone(),
+                        opportunity_id: opportunity.id.to_string(),
+                        strategy: strategy_name.to_string(),
+                    });
+                }
+                
+                if exec_result.accepted {
+                    // 只有实盘成交计入交易所的30天成交额
+                    if mode == TradingMode::Live {
+                        self.strategy_context.record_traded_volume(&opportunity, &[]);
+                    }
+                    info!("✅ 策略 {} 执行成功，订单ID: {:?}",
+                          strategy_name, exec_result.order_ids);
+                } else {
+                    warn!("❌ 策略 {} 执行失败: {}", 
+                          strategy_name, exec_result.reason.as_deref().unwrap_or("未知原因"));
+                }
+                Some(exec_result)
+            }
+            Err(e) => {
+                error!("💥 策略 {} 执行异常: {}", strategy_name, e);
+                
+                // 报告失败给风险控制器
+                self.risk_controller
+                    .report_strategy_result(strategy_name, 0.0, false)
+                    .await;
+                None
+            }
+        }
+    }
+
     /// 更新引擎统计
     async fn update_stats(&self, result: &ExecutionResult, execution_time_ms: f64) {
         let mut stats = self.stats.write().await;
@@ -224,7 +697,18 @@ impl ConfigurableArbitrageEngine {
 
     /// 获取引擎统计
     pub async fn get_stats(&self) -> EngineStats {
-        self.stats.read().await.clone()
+        let mut stats = self.stats.read().await.clone();
+        if let Some(admission) = &self.admission {
+            stats.budgets = admission.utilization();
+        }
+        stats
+    }
+
+    /// 从快照恢复统计（热重启）
+    pub async fn restore_stats(&self, stats: EngineStats) {
+        let mut current = self.stats.write().await;
+        let strategies_registered = current.strategies_registered;
+        *current = EngineStats { strategies_registered, ..stats };
     }
 
     /// 动态更新配置
@@ -273,7 +757,22 @@ impl ConfigurableArbitrageEngine {
     /// 优雅停机
     pub async fn shutdown(&self) -> Result<()> {
         info!("🛑 套利引擎正在关闭...");
-        
+
+        // 先停止认领并排空执行中的机会，再撤销所有交易所挂单
+        match &self.graceful_shutdown {
+            Some(shutdown) => {
+                let report = shutdown.run().await;
+                if !report.is_clean() {
+                    warn!("⚠️ 停机未完全干净: 排空={} 撤单 {} 笔", report.drained, report.orders_cancelled);
+                }
+            }
+            None => {
+                if let Some(gate) = &self.shutdown_gate {
+                    gate.close();
+                }
+            }
+        }
+
         // 获取最终统计
         let stats = self.get_stats().await;
         let risk_status = self.get_risk_status().await;
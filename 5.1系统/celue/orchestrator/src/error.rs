@@ -33,4 +33,36 @@ pub enum OrchestratorError {
     Generic(String),
 }
 
-pub type Result<T> = std::result::Result<T, OrchestratorError>; 
\ No newline at end of file
+pub type Result<T> = std::result::Result<T, OrchestratorError>; 
+impl From<OrchestratorError> for common::SystemError {
+    fn from(error: OrchestratorError) -> Self {
+        use common::{DataError, ExecutionError, RiskError, StorageError, SystemError};
+        match error {
+            OrchestratorError::Configuration(message) => SystemError::Configuration(message),
+            OrchestratorError::NatsConnection(e) => SystemError::Unavailable(e.to_string()),
+            OrchestratorError::MarketData(message) => DataError::Malformed(message).into(),
+            OrchestratorError::Execution(message) => ExecutionError::Refused(message).into(),
+            OrchestratorError::RiskManagement(message) => RiskError::LimitBreached(message).into(),
+            OrchestratorError::Io(e) => StorageError::Backend(e.to_string()).into(),
+            OrchestratorError::Serialization(e) => SystemError::InvalidRequest(e.to_string()),
+            e @ (OrchestratorError::Strategy(_) | OrchestratorError::Metrics(_) | OrchestratorError::Generic(_)) => {
+                SystemError::Internal(e.to_string())
+            }
+        }
+    }
+}
+
+impl From<crate::auth::AuthError> for common::SystemError {
+    fn from(error: crate::auth::AuthError) -> Self {
+        use crate::auth::AuthError;
+        match error {
+            AuthError::Forbidden { .. } | AuthError::ProfileForbidden { .. } => Self::Forbidden(error.to_string()),
+            _ => Self::Unauthorized(error.to_string()),
+        }
+    }
+}
+
+/// 存储后端返回的 anyhow 错误转为 `StorageError`
+pub(crate) fn storage_error(error: anyhow::Error) -> common::SystemError {
+    common::StorageError::Backend(error.to_string()).into()
+}
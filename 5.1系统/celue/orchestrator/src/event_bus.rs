@@ -0,0 +1,499 @@
+//! 强类型事件总线与主题 schema 注册表
+//!
+//! 每个主题绑定唯一的 Rust 类型和 schema 版本（`TopicEvent`），进程内
+//! 发布/订阅按类型在编译期检查；同一主题被注册为不同类型或版本时直接
+//! 报错。外部注入的事件（NATS 入口）以 JSON 信封到达，按注册表校验
+//! 主题、版本和负载结构后才会进入总线。每个主题自动上报发布速率、
+//! 拒绝数和消费者积压。
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use parking_lot::{Mutex, RwLock};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{info, warn};
+
+use crate::auth::{AuthService, ControlAction};
+use crate::nats::{encode_reply, NatsManager};
+use common::{ApiResponse, SystemError, SystemResult};
+
+/// 绑定到主题的事件类型
+pub trait TopicEvent: Serialize + DeserializeOwned + Clone + Send + Sync + 'static {
+    const TOPIC: &'static str;
+    const SCHEMA_VERSION: u32;
+
+    /// 外部注入事件的业务校验，结构校验由反序列化完成
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// 总线上传递的事件信封
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventEnvelope<T> {
+    pub topic: String,
+    pub schema_version: u32,
+    pub published_at: DateTime<Utc>,
+    pub source: String,
+    pub payload: T,
+}
+
+/// 注册表条目
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TopicSchema {
+    pub topic: String,
+    pub type_name: String,
+    pub schema_version: u32,
+}
+
+/// 主题统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopicStats {
+    pub topic: String,
+    pub schema_version: u32,
+    pub published: u64,
+    pub rejected: u64,
+    /// 最近 60 秒平均每秒发布数
+    pub rate_per_sec: f64,
+    pub subscribers: usize,
+    /// 最慢订阅者尚未读取的事件数
+    pub max_consumer_lag: usize,
+    /// 订阅者因落后而丢失的事件总数
+    pub lagged: u64,
+}
+
+const RATE_WINDOW_SECS: usize = 60;
+
+/// 按秒分桶的滑动窗口计数
+struct RateWindow {
+    buckets: [u64; RATE_WINDOW_SECS],
+    last_sec: u64,
+}
+
+impl RateWindow {
+    fn advance(&mut self, now_sec: u64) {
+        if now_sec > self.last_sec {
+            let clear_to = now_sec.min(self.last_sec + RATE_WINDOW_SECS as u64);
+            for sec in self.last_sec + 1..=clear_to {
+                self.buckets[sec as usize % RATE_WINDOW_SECS] = 0;
+            }
+            self.last_sec = now_sec;
+        }
+    }
+
+    fn record(&mut self, now_sec: u64) {
+        self.advance(now_sec);
+        self.buckets[now_sec as usize % RATE_WINDOW_SECS] += 1;
+    }
+
+    fn rate(&mut self, now_sec: u64) -> f64 {
+        self.advance(now_sec);
+        self.buckets.iter().sum::<u64>() as f64 / RATE_WINDOW_SECS as f64
+    }
+}
+
+struct TopicCounters {
+    published: AtomicU64,
+    rejected: AtomicU64,
+    lagged: AtomicU64,
+    rate: Mutex<RateWindow>,
+    started: Instant,
+}
+
+impl TopicCounters {
+    fn now_sec(&self) -> u64 {
+        self.started.elapsed().as_secs()
+    }
+}
+
+type Injector = Box<dyn Fn(&str, serde_json::Value) -> SystemResult<usize> + Send + Sync>;
+
+struct TopicEntry {
+    schema: TopicSchema,
+    type_id: TypeId,
+    /// `broadcast::Sender<Arc<EventEnvelope<E>>>`
+    sender: Box<dyn Any + Send + Sync>,
+    injector: Injector,
+    queued: Box<dyn Fn() -> (usize, usize) + Send + Sync>,
+    counters: Arc<TopicCounters>,
+}
+
+fn send_typed<E: TopicEvent>(
+    sender: &broadcast::Sender<Arc<EventEnvelope<E>>>,
+    counters: &TopicCounters,
+    source: &str,
+    payload: E,
+) -> usize {
+    let envelope = EventEnvelope {
+        topic: E::TOPIC.to_string(),
+        schema_version: E::SCHEMA_VERSION,
+        published_at: Utc::now(),
+        source: source.to_string(),
+        payload,
+    };
+    counters.published.fetch_add(1, Ordering::Relaxed);
+    counters.rate.lock().record(counters.now_sec());
+    metrics::counter!("event_bus_published_total", 1, "topic" => E::TOPIC);
+    // 无订阅者不视为错误
+    sender.send(Arc::new(envelope)).unwrap_or(0)
+}
+
+/// 强类型事件总线
+pub struct TypedEventBus {
+    topics: RwLock<HashMap<String, TopicEntry>>,
+    capacity: usize,
+    source: String,
+}
+
+impl TypedEventBus {
+    pub fn new(source: &str, capacity: usize) -> Self {
+        Self { topics: RwLock::new(HashMap::new()), capacity: capacity.max(1), source: source.to_string() }
+    }
+
+    /// 注册主题类型；重复注册同一类型是幂等的
+    pub fn register<E: TopicEvent>(&self) -> Result<()> {
+        if let Some(entry) = self.topics.read().get(E::TOPIC) {
+            return Self::check_entry::<E>(entry);
+        }
+        let mut topics = self.topics.write();
+        if let Some(entry) = topics.get(E::TOPIC) {
+            return Self::check_entry::<E>(entry);
+        }
+
+        let (sender, _) = broadcast::channel::<Arc<EventEnvelope<E>>>(self.capacity);
+        let counters = Arc::new(TopicCounters {
+            published: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+            lagged: AtomicU64::new(0),
+            rate: Mutex::new(RateWindow { buckets: [0; RATE_WINDOW_SECS], last_sec: 0 }),
+            started: Instant::now(),
+        });
+        let injector: Injector = {
+            let sender = sender.clone();
+            let counters = counters.clone();
+            Box::new(move |source, payload| {
+                let event: E = serde_json::from_value(payload)
+                    .map_err(|e| SystemError::InvalidRequest(format!("{} 负载不符合 schema: {}", E::TOPIC, e)))?;
+                event.validate().map_err(|e| SystemError::InvalidRequest(format!("{} 校验失败: {}", E::TOPIC, e)))?;
+                Ok(send_typed(&sender, &counters, source, event))
+            })
+        };
+        let queued = {
+            let sender = sender.clone();
+            Box::new(move || (sender.receiver_count(), sender.len())) as Box<dyn Fn() -> (usize, usize) + Send + Sync>
+        };
+        let schema = TopicSchema {
+            topic: E::TOPIC.to_string(),
+            type_name: std::any::type_name::<E>().to_string(),
+            schema_version: E::SCHEMA_VERSION,
+        };
+        info!("🧾 事件主题已注册: {} -> {} v{}", schema.topic, schema.type_name, schema.schema_version);
+        topics.insert(
+            E::TOPIC.to_string(),
+            TopicEntry { schema, type_id: TypeId::of::<E>(), sender: Box::new(sender), injector, queued, counters },
+        );
+        Ok(())
+    }
+
+    fn check_entry<E: TopicEvent>(entry: &TopicEntry) -> Result<()> {
+        if entry.type_id != TypeId::of::<E>() || entry.schema.schema_version != E::SCHEMA_VERSION {
+            bail!(
+                "主题 {} 已注册为 {} v{}，不能再注册为 {} v{}",
+                E::TOPIC,
+                entry.schema.type_name,
+                entry.schema.schema_version,
+                std::any::type_name::<E>(),
+                E::SCHEMA_VERSION
+            );
+        }
+        Ok(())
+    }
+
+    fn with_sender<E: TopicEvent, R>(
+        &self,
+        f: impl FnOnce(&broadcast::Sender<Arc<EventEnvelope<E>>>, &Arc<TopicCounters>) -> R,
+    ) -> Result<R> {
+        self.register::<E>()?;
+        let topics = self.topics.read();
+        let entry = &topics[E::TOPIC];
+        let sender = entry
+            .sender
+            .downcast_ref::<broadcast::Sender<Arc<EventEnvelope<E>>>>()
+            .expect("主题类型已校验");
+        Ok(f(sender, &entry.counters))
+    }
+
+    /// 发布事件，返回收到事件的订阅者数
+    pub fn publish<E: TopicEvent>(&self, event: E) -> Result<usize> {
+        self.with_sender::<E, _>(|sender, counters| send_typed(sender, counters, &self.source, event))
+    }
+
+    pub fn subscribe<E: TopicEvent>(&self, subscriber: &str) -> Result<TypedSubscriber<E>> {
+        self.with_sender::<E, _>(|sender, counters| TypedSubscriber {
+            name: subscriber.to_string(),
+            receiver: sender.subscribe(),
+            counters: counters.clone(),
+        })
+    }
+
+    /// 注入外部事件：信封 JSON 按注册表校验主题、版本和负载
+    pub fn inject(&self, raw: &[u8]) -> SystemResult<usize> {
+        let envelope: EventEnvelope<serde_json::Value> =
+            serde_json::from_slice(raw).map_err(|e| SystemError::InvalidRequest(format!("事件信封格式错误: {}", e)))?;
+        let topics = self.topics.read();
+        let Some(entry) = topics.get(&envelope.topic) else {
+            metrics::counter!("event_bus_rejected_total", 1, "topic" => "unknown", "reason" => "unknown_topic");
+            return Err(SystemError::InvalidRequest(format!("未注册的事件主题: {}", envelope.topic)));
+        };
+        let reject = |reason: &'static str, error: SystemError| {
+            entry.counters.rejected.fetch_add(1, Ordering::Relaxed);
+            metrics::counter!("event_bus_rejected_total", 1, "topic" => envelope.topic.clone(), "reason" => reason);
+            Err(error)
+        };
+        if envelope.schema_version != entry.schema.schema_version {
+            return reject(
+                "schema_version",
+                SystemError::InvalidRequest(format!(
+                    "主题 {} 需要 schema v{}，收到 v{}",
+                    envelope.topic, entry.schema.schema_version, envelope.schema_version
+                )),
+            );
+        }
+        match (entry.injector)(&envelope.source, envelope.payload.clone()) {
+            Ok(receivers) => Ok(receivers),
+            Err(e) => reject("payload", e),
+        }
+    }
+
+    pub fn schemas(&self) -> Vec<TopicSchema> {
+        let mut schemas: Vec<_> = self.topics.read().values().map(|e| e.schema.clone()).collect();
+        schemas.sort_by(|a, b| a.topic.cmp(&b.topic));
+        schemas
+    }
+
+    /// 各主题统计，同时刷新速率与积压指标
+    pub fn stats(&self) -> Vec<TopicStats> {
+        let mut stats: Vec<_> = self
+            .topics
+            .read()
+            .values()
+            .map(|entry| {
+                let (subscribers, max_consumer_lag) = (entry.queued)();
+                let counters = &entry.counters;
+                let rate_per_sec = counters.rate.lock().rate(counters.now_sec());
+                let topic = entry.schema.topic.clone();
+                metrics::gauge!("event_bus_publish_rate", rate_per_sec, "topic" => topic.clone());
+                metrics::gauge!("event_bus_max_consumer_lag", max_consumer_lag as f64, "topic" => topic.clone());
+                TopicStats {
+                    topic,
+                    schema_version: entry.schema.schema_version,
+                    published: counters.published.load(Ordering::Relaxed),
+                    rejected: counters.rejected.load(Ordering::Relaxed),
+                    rate_per_sec,
+                    subscribers,
+                    max_consumer_lag,
+                    lagged: counters.lagged.load(Ordering::Relaxed),
+                }
+            })
+            .collect();
+        stats.sort_by(|a, b| a.topic.cmp(&b.topic));
+        stats
+    }
+
+    /// 定期刷新主题指标
+    pub fn spawn_metrics(self: Arc<Self>, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.stats();
+            }
+        })
+    }
+}
+
+/// 强类型订阅者
+pub struct TypedSubscriber<E: TopicEvent> {
+    name: String,
+    receiver: broadcast::Receiver<Arc<EventEnvelope<E>>>,
+    counters: Arc<TopicCounters>,
+}
+
+impl<E: TopicEvent> TypedSubscriber<E> {
+    /// 接收下一个事件；落后丢失的事件计入指标，总线关闭返回 None
+    pub async fn recv(&mut self) -> Option<Arc<EventEnvelope<E>>> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(envelope) => {
+                    metrics::gauge!("event_bus_consumer_lag", self.receiver.len() as f64, "topic" => E::TOPIC, "subscriber" => self.name.clone());
+                    return Some(envelope);
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("⚠️ 事件订阅者 {} 在主题 {} 上落后，丢失 {} 个事件", self.name, E::TOPIC, skipped);
+                    self.counters.lagged.fetch_add(skipped, Ordering::Relaxed);
+                    metrics::counter!("event_bus_lagged_total", skipped, "topic" => E::TOPIC, "subscriber" => self.name.clone());
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// 尚未读取的事件数
+    pub fn lag(&self) -> usize {
+        self.receiver.len()
+    }
+}
+
+/// 外部事件入口与注册表查询
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum EventBusCommand {
+    /// 注入信封形式的外部事件
+    Inject { envelope: serde_json::Value },
+    Schemas,
+    Stats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EventBusReply {
+    Injected { receivers: usize },
+    Schemas { schemas: Vec<TopicSchema> },
+    Stats { topics: Vec<TopicStats> },
+}
+
+/// 事件总线 NATS 服务
+pub struct EventBusService {
+    bus: Arc<TypedEventBus>,
+    auth: Option<Arc<AuthService>>,
+}
+
+impl EventBusService {
+    pub fn new(bus: Arc<TypedEventBus>) -> Self {
+        Self { bus, auth: None }
+    }
+
+    pub fn with_auth(mut self, auth: Arc<AuthService>) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    fn handle(&self, command: &EventBusCommand) -> SystemResult<EventBusReply> {
+        Ok(match command {
+            EventBusCommand::Inject { envelope } => {
+                let raw = serde_json::to_vec(envelope).map_err(|e| SystemError::InvalidRequest(e.to_string()))?;
+                EventBusReply::Injected { receivers: self.bus.inject(&raw)? }
+            }
+            EventBusCommand::Schemas => EventBusReply::Schemas { schemas: self.bus.schemas() },
+            EventBusCommand::Stats => EventBusReply::Stats { topics: self.bus.stats() },
+        })
+    }
+
+    /// 请求体为 `EventBusCommand` JSON，注入需要 `InjectEvents` 权限
+    pub async fn serve(self: Arc<Self>, nats: &NatsManager, subject: &str) -> Result<()> {
+        let mut subscriber = nats.subscribe(subject).await?;
+        info!("🧾 事件总线服务已启动: {}", subject);
+
+        while let Some(message) = subscriber.next().await {
+            let Some(reply) = message.reply.clone() else { continue };
+            let result = (|| {
+                let command = serde_json::from_slice::<EventBusCommand>(&message.payload)
+                    .map_err(|e| SystemError::InvalidRequest(e.to_string()))?;
+                let action = match command {
+                    EventBusCommand::Inject { .. } => ControlAction::InjectEvents,
+                    _ => ControlAction::ViewDashboard,
+                };
+                if let Some(auth) = &self.auth {
+                    auth.authorize_nats(&message, action)?;
+                }
+                self.handle(&command)
+            })();
+            let response: ApiResponse<EventBusReply> = result.into();
+            if let Err(e) = nats
+                .get_client()
+                .publish(reply, encode_reply(&message, &response)?.into())
+                .await
+            {
+                warn!("事件总线响应发送失败: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct SpreadAlert {
+        symbol: String,
+        spread_bps: f64,
+    }
+
+    impl TopicEvent for SpreadAlert {
+        const TOPIC: &'static str = "spread.alert";
+        const SCHEMA_VERSION: u32 = 2;
+
+        fn validate(&self) -> Result<(), String> {
+            if self.spread_bps < 0.0 {
+                return Err("spread_bps 不能为负".to_string());
+            }
+            Ok(())
+        }
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Impostor;
+
+    impl TopicEvent for Impostor {
+        const TOPIC: &'static str = "spread.alert";
+        const SCHEMA_VERSION: u32 = 2;
+    }
+
+    #[tokio::test]
+    async fn test_typed_publish_and_validated_injection() {
+        let bus = TypedEventBus::new("orchestrator", 2);
+        let mut subscriber = bus.subscribe::<SpreadAlert>("detector").unwrap();
+        assert!(bus.register::<Impostor>().is_err());
+
+        bus.publish(SpreadAlert { symbol: "BTCUSDT".into(), spread_bps: 12.0 }).unwrap();
+        let envelope = subscriber.recv().await.unwrap();
+        assert_eq!((envelope.schema_version, envelope.payload.spread_bps), (2, 12.0));
+
+        let inject = |version: u32, payload: serde_json::Value| {
+            let raw = serde_json::json!({
+                "topic": "spread.alert",
+                "schema_version": version,
+                "published_at": Utc::now(),
+                "source": "ops",
+                "payload": payload,
+            });
+            bus.inject(&serde_json::to_vec(&raw).unwrap())
+        };
+        assert_eq!(inject(2, serde_json::json!({ "symbol": "ETHUSDT", "spread_bps": 7.5 })).unwrap(), 1);
+        assert!(inject(1, serde_json::json!({ "symbol": "ETHUSDT", "spread_bps": 7.5 })).is_err());
+        assert!(inject(2, serde_json::json!({ "symbol": "ETHUSDT" })).is_err());
+        assert!(inject(2, serde_json::json!({ "symbol": "ETHUSDT", "spread_bps": -1.0 })).is_err());
+        assert_eq!(subscriber.recv().await.unwrap().source, "ops");
+
+        // 容量 2，落后的订阅者丢失最旧事件
+        for i in 0..3 {
+            bus.publish(SpreadAlert { symbol: "BTCUSDT".into(), spread_bps: i as f64 }).unwrap();
+        }
+        assert_eq!(subscriber.recv().await.unwrap().payload.spread_bps, 1.0);
+        let stats = &bus.stats()[0];
+        assert_eq!((stats.published, stats.rejected, stats.lagged), (5, 3, 1));
+        assert_eq!((stats.subscribers, stats.max_consumer_lag), (1, 1));
+        assert!(stats.rate_per_sec > 0.0);
+    }
+}
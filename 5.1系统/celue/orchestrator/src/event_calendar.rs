@@ -0,0 +1,294 @@
+//! 高波动事件日历与预备仓位
+//!
+//! 导入 CPI、FOMC、代币解锁等计划事件，在事件前若干分钟自动进入预备状态：
+//! 收紧风险限额、预热交易所连接、提升受影响交易对的订阅档位并提高风险
+//! 监控频率；事件窗口结束后恢复原有配置。
+
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::risk::{DynamicRiskConfig, DynamicRiskController};
+
+/// 事件类型
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Cpi,
+    Fomc,
+    TokenUnlock,
+    Other(String),
+}
+
+/// 计划中的高波动事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledEvent {
+    pub id: String,
+    pub kind: EventKind,
+    pub scheduled_at: DateTime<Utc>,
+    /// 受影响的交易对，空表示全市场
+    #[serde(default)]
+    pub affected_symbols: Vec<String>,
+}
+
+/// 事件日历
+#[derive(Default)]
+pub struct EventCalendar {
+    events: RwLock<Vec<ScheduledEvent>>,
+}
+
+impl EventCalendar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 导入事件，同 id 的事件会被覆盖
+    pub fn ingest(&self, events: Vec<ScheduledEvent>) {
+        let mut current = self.events.write();
+        for event in events {
+            current.retain(|e| e.id != event.id);
+            current.push(event);
+        }
+        current.sort_by_key(|e| e.scheduled_at);
+    }
+
+    /// 从 JSON 文件导入事件列表
+    pub fn load_file(&self, path: impl AsRef<Path>) -> Result<usize> {
+        let content = std::fs::read_to_string(path)?;
+        let events: Vec<ScheduledEvent> = serde_json::from_str(&content)?;
+        let count = events.len();
+        self.ingest(events);
+        Ok(count)
+    }
+
+    /// 清理已结束超过 `retention` 的事件
+    pub fn prune(&self, now: DateTime<Utc>, retention: Duration) {
+        self.events.write().retain(|e| e.scheduled_at + retention > now);
+    }
+
+    /// 处于 [事件前 lead, 事件后 cooldown] 窗口内的事件
+    pub fn active(&self, now: DateTime<Utc>, lead: Duration, cooldown: Duration) -> Vec<ScheduledEvent> {
+        self.events
+            .read()
+            .iter()
+            .filter(|e| now >= e.scheduled_at - lead && now <= e.scheduled_at + cooldown)
+            .cloned()
+            .collect()
+    }
+
+    pub fn upcoming(&self, now: DateTime<Utc>) -> Vec<ScheduledEvent> {
+        self.events.read().iter().filter(|e| e.scheduled_at > now).cloned().collect()
+    }
+}
+
+/// 预备仓位配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrePositioningConfig {
+    /// 事件前提前进入预备状态（秒）
+    pub lead_time_secs: i64,
+    /// 事件后保持预备状态（秒）
+    pub cooldown_secs: i64,
+    /// 风险限额缩放系数（0-1）
+    pub risk_limit_scale: f64,
+    /// 风险检查间隔缩放系数（0-1）
+    pub monitoring_interval_scale: f64,
+}
+
+impl Default for PrePositioningConfig {
+    fn default() -> Self {
+        Self {
+            lead_time_secs: std::env::var("CELUE_EVENT_LEAD_TIME_SECS")
+                .ok().and_then(|s| s.parse().ok())
+                .unwrap_or(600),
+            cooldown_secs: std::env::var("CELUE_EVENT_COOLDOWN_SECS")
+                .ok().and_then(|s| s.parse().ok())
+                .unwrap_or(900),
+            risk_limit_scale: std::env::var("CELUE_EVENT_RISK_LIMIT_SCALE")
+                .ok().and_then(|s| s.parse().ok())
+                .unwrap_or(0.5),
+            monitoring_interval_scale: std::env::var("CELUE_EVENT_MONITORING_SCALE")
+                .ok().and_then(|s| s.parse().ok())
+                .unwrap_or(0.25),
+        }
+    }
+}
+
+/// 订阅档位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SubscriptionTier {
+    /// 常规深度与推送频率
+    Standard,
+    /// 全深度、最高推送频率
+    HighFrequency,
+}
+
+/// 行情/连接侧的预备动作，由数据接入层实现
+#[async_trait::async_trait]
+pub trait EventReadiness: Send + Sync {
+    /// 预热交易所连接（建连、鉴权、时钟同步）
+    async fn prewarm_connections(&self, symbols: &[String]) -> Result<()>;
+
+    /// 调整交易对订阅档位，symbols 为空表示全部
+    async fn set_subscription_tier(&self, symbols: &[String], tier: SubscriptionTier) -> Result<()>;
+}
+
+/// 事件预备仓位调度器
+pub struct PrePositioner {
+    calendar: Arc<EventCalendar>,
+    risk_controller: Arc<DynamicRiskController>,
+    readiness: Option<Arc<dyn EventReadiness>>,
+    config: PrePositioningConfig,
+    /// 进入预备状态前的风险配置，用于恢复
+    baseline: tokio::sync::Mutex<Option<DynamicRiskConfig>>,
+    prepared_symbols: RwLock<Vec<String>>,
+}
+
+impl PrePositioner {
+    pub fn new(
+        calendar: Arc<EventCalendar>,
+        risk_controller: Arc<DynamicRiskController>,
+        config: PrePositioningConfig,
+    ) -> Self {
+        Self {
+            calendar,
+            risk_controller,
+            readiness: None,
+            config,
+            baseline: tokio::sync::Mutex::new(None),
+            prepared_symbols: RwLock::new(Vec::new()),
+        }
+    }
+
+    pub fn with_readiness(mut self, readiness: Arc<dyn EventReadiness>) -> Self {
+        self.readiness = Some(readiness);
+        self
+    }
+
+    /// 是否处于预备状态
+    pub async fn is_prepared(&self) -> bool {
+        self.baseline.lock().await.is_some()
+    }
+
+    /// 根据当前时间进入或退出预备状态
+    pub async fn tick(&self, now: DateTime<Utc>) -> Result<()> {
+        let active = self.calendar.active(
+            now,
+            Duration::seconds(self.config.lead_time_secs),
+            Duration::seconds(self.config.cooldown_secs),
+        );
+        let mut baseline = self.baseline.lock().await;
+
+        if active.is_empty() {
+            if let Some(original) = baseline.take() {
+                self.risk_controller.update_config(original).await?;
+                let symbols = std::mem::take(&mut *self.prepared_symbols.write());
+                if let Some(readiness) = &self.readiness {
+                    readiness.set_subscription_tier(&symbols, SubscriptionTier::Standard).await?;
+                }
+                info!("✅ 事件窗口结束，风险配置已恢复");
+            }
+            return Ok(());
+        }
+
+        let mut symbols: Vec<String> = active.iter().flat_map(|e| e.affected_symbols.clone()).collect();
+        symbols.sort();
+        symbols.dedup();
+        // 任一事件未指定交易对即视为全市场
+        if active.iter().any(|e| e.affected_symbols.is_empty()) {
+            symbols.clear();
+        }
+
+        let entering = baseline.is_none();
+        if entering {
+            let original = self.risk_controller.get_config().await;
+            self.risk_controller.update_config(self.tightened(&original)).await?;
+            *baseline = Some(original);
+            for event in &active {
+                warn!("⚠️ 高波动事件 {} ({:?}) 将于 {} 发生，进入预备状态", event.id, event.kind, event.scheduled_at);
+            }
+        }
+
+        let symbols_changed = *self.prepared_symbols.read() != symbols;
+        if entering || symbols_changed {
+            if let Some(readiness) = &self.readiness {
+                if let Err(e) = readiness.prewarm_connections(&symbols).await {
+                    warn!("连接预热失败: {}", e);
+                }
+                readiness.set_subscription_tier(&symbols, SubscriptionTier::HighFrequency).await?;
+            }
+            *self.prepared_symbols.write() = symbols;
+        }
+        Ok(())
+    }
+
+    /// 收紧后的风险配置
+    fn tightened(&self, original: &DynamicRiskConfig) -> DynamicRiskConfig {
+        let scale = self.config.risk_limit_scale.clamp(0.0, 1.0);
+        let mut config = original.clone();
+        config.max_daily_loss_usd *= scale;
+        config.max_single_loss_pct *= scale;
+        for limit in config.position_limits.values_mut() {
+            *limit *= scale;
+        }
+        config.monitoring.check_interval_ms = ((config.monitoring.check_interval_ms as f64
+            * self.config.monitoring_interval_scale.clamp(0.0, 1.0)) as u64)
+            .max(50);
+        config
+    }
+
+    /// 周期性检查事件日历
+    pub async fn run(self: Arc<Self>, interval: std::time::Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let now = Utc::now();
+            if let Err(e) = self.tick(now).await {
+                warn!("事件预备检查失败: {}", e);
+            }
+            self.calendar.prune(now, Duration::seconds(self.config.cooldown_secs));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_tightens_before_event_and_restores_after() {
+        let calendar = Arc::new(EventCalendar::new());
+        let event_at = Utc::now() + Duration::hours(1);
+        calendar.ingest(vec![ScheduledEvent {
+            id: "fomc-1".into(),
+            kind: EventKind::Fomc,
+            scheduled_at: event_at,
+            affected_symbols: vec![],
+        }]);
+
+        let risk = Arc::new(DynamicRiskController::new(DynamicRiskConfig::default()));
+        let original = risk.get_config().await;
+        let positioner = PrePositioner::new(calendar, risk.clone(), PrePositioningConfig {
+            lead_time_secs: 600,
+            cooldown_secs: 900,
+            risk_limit_scale: 0.5,
+            monitoring_interval_scale: 0.25,
+        });
+
+        positioner.tick(event_at - Duration::minutes(20)).await.unwrap();
+        assert!(!positioner.is_prepared().await);
+
+        positioner.tick(event_at - Duration::minutes(5)).await.unwrap();
+        assert!(positioner.is_prepared().await);
+        let tightened = risk.get_config().await;
+        assert!((tightened.max_daily_loss_usd - original.max_daily_loss_usd * 0.5).abs() < 1e-9);
+
+        positioner.tick(event_at + Duration::minutes(20)).await.unwrap();
+        assert!(!positioner.is_prepared().await);
+        assert_eq!(risk.get_config().await.max_daily_loss_usd, original.max_daily_loss_usd);
+    }
+}
@@ -0,0 +1,715 @@
+//! 历史数据批量导出 - Parquet / S3
+//!
+//! 将指定时间段内的机会（事件日志中的机会检测与风控决策）、执行（下单
+//! 与成交汇总）和费用历史（成交记录）导出为 Parquet 文件，按
+//! `数据集/date=YYYY-MM-DD/strategy=xxx/` 分区写入本地目录，可选再上传
+//! 到 S3 兼容存储（需启用 `s3` feature）。导出由 NATS 接口或每日定时
+//! 任务触发，在后台运行，任务进度（已完成分区数、行数、文件列表）可随时
+//! 查询。发起导出需要 `ExportData` 权限，配置了审计日志时一并落审计。
+//! 整个模块在 `parquet-export` feature 之后，核心引擎不依赖 arrow。
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use adapters::attribution::PerformanceAnalyzer;
+use anyhow::{anyhow, Result};
+use arrow::array::{ArrayRef, Float64Array, StringArray, UInt64Array};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration as ChronoDuration, NaiveTime, Utc};
+use futures_util::StreamExt;
+use parking_lot::RwLock;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::audit::AuditLog;
+use crate::auth::{AuthService, ControlAction};
+use crate::journal::{JournalEvent, JournalStore};
+use crate::nats::{encode_reply, NatsManager};
+use common::{ApiResponse, StorageError, SystemError, SystemResult};
+
+/// 可导出的数据集
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportDataset {
+    Opportunities,
+    Executions,
+    Fees,
+}
+
+impl ExportDataset {
+    pub const ALL: [ExportDataset; 3] = [ExportDataset::Opportunities, ExportDataset::Executions, ExportDataset::Fees];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportDataset::Opportunities => "opportunities",
+            ExportDataset::Executions => "executions",
+            ExportDataset::Fees => "fees",
+        }
+    }
+
+    fn columns(&self) -> &'static [(&'static str, DataType)] {
+        use DataType::{Float64, UInt64, Utf8};
+        match self {
+            ExportDataset::Opportunities => &[
+                ("timestamp_ns", UInt64),
+                ("seq", UInt64),
+                ("opportunity_id", Utf8),
+                ("strategy", Utf8),
+                ("symbol", Utf8),
+                ("net_profit", Float64),
+                ("risk_decision", Utf8),
+            ],
+            ExportDataset::Executions => &[
+                ("submitted_ns", UInt64),
+                ("order_id", Utf8),
+                ("opportunity_id", Utf8),
+                ("strategy", Utf8),
+                ("fills", UInt64),
+                ("filled_quantity", Float64),
+                ("avg_price", Float64),
+            ],
+            ExportDataset::Fees => &[
+                ("timestamp_ns", UInt64),
+                ("strategy", Utf8),
+                ("buy_exchange", Utf8),
+                ("sell_exchange", Utf8),
+                ("gross_profit", Float64),
+                ("fees", Float64),
+                ("slippage_bps", Float64),
+                ("capital_used", Float64),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Cell {
+    U64(u64),
+    F64(Option<f64>),
+    Str(Option<String>),
+}
+
+/// 一行数据及其分区键
+struct ExportRow {
+    timestamp_ns: u64,
+    strategy: String,
+    cells: Vec<Cell>,
+}
+
+/// 导出请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportRequest {
+    pub from_ns: u64,
+    pub to_ns: u64,
+    /// 为空表示全部数据集
+    #[serde(default)]
+    pub datasets: Vec<ExportDataset>,
+    /// 写入本地后是否上传到对象存储
+    #[serde(default)]
+    pub upload: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+}
+
+/// 导出任务及进度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportJob {
+    pub id: u64,
+    pub request: ExportRequest,
+    pub status: ExportStatus,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub total_partitions: usize,
+    pub partitions_done: usize,
+    pub rows_written: u64,
+    /// 本地路径或对象存储 URL
+    pub files: Vec<String>,
+    pub error: Option<String>,
+}
+
+impl ExportJob {
+    pub fn progress(&self) -> f64 {
+        match (self.status, self.total_partitions) {
+            (ExportStatus::Completed, _) => 1.0,
+            (_, 0) => 0.0,
+            (_, total) => self.partitions_done as f64 / total as f64,
+        }
+    }
+}
+
+/// 导出文件的存放位置
+#[async_trait]
+pub trait ExportSink: Send + Sync {
+    /// 写入对象，返回其位置
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<String>;
+}
+
+/// 本地目录
+pub struct LocalExportSink {
+    root: PathBuf,
+}
+
+impl LocalExportSink {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+#[async_trait]
+impl ExportSink for LocalExportSink {
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<String> {
+        let path = self.root.join(key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&path, bytes).await?;
+        Ok(path.display().to_string())
+    }
+}
+
+/// S3 兼容存储配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct S3ExportConfig {
+    pub bucket: String,
+    pub region: String,
+    /// MinIO 等自建存储的地址；缺省为 AWS
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    #[serde(default)]
+    pub prefix: String,
+}
+
+/// S3 兼容存储
+#[cfg(feature = "s3")]
+pub struct S3ExportSink {
+    store: object_store::aws::AmazonS3,
+    config: S3ExportConfig,
+}
+
+#[cfg(feature = "s3")]
+impl S3ExportSink {
+    pub fn new(config: S3ExportConfig) -> Result<Self> {
+        let mut builder = object_store::aws::AmazonS3Builder::new()
+            .with_bucket_name(&config.bucket)
+            .with_region(&config.region)
+            .with_access_key_id(&config.access_key_id)
+            .with_secret_access_key(&config.secret_access_key);
+        if let Some(endpoint) = &config.endpoint {
+            builder = builder.with_endpoint(endpoint).with_allow_http(endpoint.starts_with("http://"));
+        }
+        Ok(Self { store: builder.build()?, config })
+    }
+}
+
+#[cfg(feature = "s3")]
+#[async_trait]
+impl ExportSink for S3ExportSink {
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<String> {
+        use object_store::ObjectStore;
+        let key = match self.config.prefix.trim_matches('/') {
+            "" => key.to_string(),
+            prefix => format!("{}/{}", prefix, key),
+        };
+        self.store
+            .put(&object_store::path::Path::from(key.as_str()), bytes::Bytes::from(bytes).into())
+            .await?;
+        Ok(format!("s3://{}/{}", self.config.bucket, key))
+    }
+}
+
+/// 历史数据导出器
+pub struct DataExporter {
+    journal: Arc<dyn JournalStore>,
+    trades: Option<Arc<PerformanceAnalyzer>>,
+    local: Arc<dyn ExportSink>,
+    upload: Option<Arc<dyn ExportSink>>,
+    jobs: RwLock<BTreeMap<u64, ExportJob>>,
+    next_id: AtomicU64,
+}
+
+impl DataExporter {
+    pub fn new(journal: Arc<dyn JournalStore>, local: Arc<dyn ExportSink>) -> Self {
+        Self {
+            journal,
+            trades: None,
+            local,
+            upload: None,
+            jobs: RwLock::new(BTreeMap::new()),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// 费用历史来源
+    pub fn with_trades(mut self, trades: Arc<PerformanceAnalyzer>) -> Self {
+        self.trades = Some(trades);
+        self
+    }
+
+    /// 请求 `upload` 时的对象存储
+    pub fn with_upload_sink(mut self, sink: Arc<dyn ExportSink>) -> Self {
+        self.upload = Some(sink);
+        self
+    }
+
+    pub fn job(&self, id: u64) -> Option<ExportJob> {
+        self.jobs.read().get(&id).cloned()
+    }
+
+    /// 最近的任务在前
+    pub fn jobs(&self) -> Vec<ExportJob> {
+        self.jobs.read().values().rev().cloned().collect()
+    }
+
+    /// 校验并登记任务，后台执行
+    pub fn start(self: &Arc<Self>, request: ExportRequest) -> SystemResult<ExportJob> {
+        if request.to_ns <= request.from_ns {
+            return Err(SystemError::InvalidRequest("导出时间段为空".to_string()));
+        }
+        if request.upload && self.upload.is_none() {
+            return Err(SystemError::Unavailable("未配置对象存储".to_string()));
+        }
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let job = ExportJob {
+            id,
+            request,
+            status: ExportStatus::Queued,
+            created_at: Utc::now(),
+            finished_at: None,
+            total_partitions: 0,
+            partitions_done: 0,
+            rows_written: 0,
+            files: Vec::new(),
+            error: None,
+        };
+        self.jobs.write().insert(id, job.clone());
+        let exporter = self.clone();
+        tokio::spawn(async move {
+            exporter.run(id).await;
+        });
+        Ok(job)
+    }
+
+    /// 执行任务并更新进度
+    pub async fn run(&self, id: u64) {
+        let Some(request) = self.update(id, |job| job.status = ExportStatus::Running).map(|j| j.request) else {
+            return;
+        };
+        match self.export(id, &request).await {
+            Ok(()) => {
+                if let Some(job) = self.update(id, |job| {
+                    job.status = ExportStatus::Completed;
+                    job.finished_at = Some(Utc::now());
+                }) {
+                    info!("📦 导出任务 {} 完成: {} 个文件, {} 行", id, job.files.len(), job.rows_written);
+                }
+            }
+            Err(e) => {
+                error!("❌ 导出任务 {} 失败: {}", id, e);
+                self.update(id, |job| {
+                    job.status = ExportStatus::Failed;
+                    job.finished_at = Some(Utc::now());
+                    job.error = Some(e.to_string());
+                });
+            }
+        }
+    }
+
+    fn update(&self, id: u64, f: impl FnOnce(&mut ExportJob)) -> Option<ExportJob> {
+        let mut jobs = self.jobs.write();
+        let job = jobs.get_mut(&id)?;
+        f(job);
+        Some(job.clone())
+    }
+
+    async fn export(&self, id: u64, request: &ExportRequest) -> Result<()> {
+        let datasets = if request.datasets.is_empty() { ExportDataset::ALL.to_vec() } else { request.datasets.clone() };
+
+        let mut partitions = Vec::new();
+        for dataset in datasets {
+            let rows = self.rows(dataset, request.from_ns, request.to_ns).await?;
+            let mut grouped: BTreeMap<(String, String), Vec<ExportRow>> = BTreeMap::new();
+            for row in rows {
+                grouped.entry(partition_key(&row)).or_default().push(row);
+            }
+            partitions.extend(grouped.into_iter().map(|(key, rows)| (dataset, key, rows)));
+        }
+        self.update(id, |job| job.total_partitions = partitions.len());
+
+        for (dataset, (date, strategy), rows) in partitions {
+            let bytes = to_parquet(dataset, &rows)?;
+            let key = format!("{}/date={}/strategy={}/export-{}.parquet", dataset.as_str(), date, strategy, id);
+            let mut locations = vec![self.local.put(&key, bytes.clone()).await?];
+            if let (true, Some(upload)) = (request.upload, &self.upload) {
+                locations.push(upload.put(&key, bytes).await?);
+            }
+            self.update(id, |job| {
+                job.partitions_done += 1;
+                job.rows_written += rows.len() as u64;
+                job.files.extend(locations);
+            });
+        }
+        Ok(())
+    }
+
+    async fn rows(&self, dataset: ExportDataset, from_ns: u64, to_ns: u64) -> Result<Vec<ExportRow>> {
+        Ok(match dataset {
+            ExportDataset::Opportunities => {
+                let records = self.journal.range(from_ns, to_ns).await?;
+                let decisions: HashMap<&str, &'static str> = records
+                    .iter()
+                    .filter_map(|r| match &r.event {
+                        JournalEvent::RiskDecision { opportunity_id, approved, .. } => {
+                            Some((opportunity_id.as_str(), if *approved { "approved" } else { "rejected" }))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                records
+                    .iter()
+                    .filter_map(|r| match &r.event {
+                        JournalEvent::OpportunityDetected { opportunity_id, strategy, symbol, net_profit } => Some(ExportRow {
+                            timestamp_ns: r.timestamp_ns,
+                            strategy: strategy.clone(),
+                            cells: vec![
+                                Cell::U64(r.timestamp_ns),
+                                Cell::U64(r.seq),
+                                Cell::Str(Some(opportunity_id.clone())),
+                                Cell::Str(Some(strategy.clone())),
+                                Cell::Str(Some(symbol.clone())),
+                                Cell::F64(Some(*net_profit)),
+                                Cell::Str(decisions.get(opportunity_id.as_str()).map(|d| d.to_string())),
+                            ],
+                        }),
+                        _ => None,
+                    })
+                    .collect()
+            }
+            ExportDataset::Executions => {
+                let records = self.journal.range(from_ns, to_ns).await?;
+                // order_id -> (提交记录, 成交笔数, 成交量, 成交额)
+                let mut orders: BTreeMap<&str, (u64, &str, &str, u64, f64, f64)> = BTreeMap::new();
+                for record in &records {
+                    match &record.event {
+                        JournalEvent::OrderSubmitted { order_id, opportunity_id, strategy } => {
+                            orders.insert(order_id, (record.timestamp_ns, opportunity_id, strategy, 0, 0.0, 0.0));
+                        }
+                        JournalEvent::OrderFilled { order_id, quantity, price } => {
+                            if let Some(order) = orders.get_mut(order_id.as_str()) {
+                                order.3 += 1;
+                                order.4 += quantity;
+                                order.5 += quantity * price;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                orders
+                    .into_iter()
+                    .map(|(order_id, (submitted_ns, opportunity_id, strategy, fills, quantity, notional))| ExportRow {
+                        timestamp_ns: submitted_ns,
+                        strategy: strategy.to_string(),
+                        cells: vec![
+                            Cell::U64(submitted_ns),
+                            Cell::Str(Some(order_id.to_string())),
+                            Cell::Str(Some(opportunity_id.to_string())),
+                            Cell::Str(Some(strategy.to_string())),
+                            Cell::U64(fills),
+                            Cell::F64(Some(quantity)),
+                            Cell::F64((quantity > 0.0).then(|| notional / quantity)),
+                        ],
+                    })
+                    .collect()
+            }
+            ExportDataset::Fees => {
+                let Some(trades) = &self.trades else {
+                    warn!("未配置成交记录来源，跳过费用历史导出");
+                    return Ok(Vec::new());
+                };
+                trades
+                    .trades_between(from_ns, to_ns)
+                    .into_iter()
+                    .map(|t| {
+                        let strategy = format!("{:?}", t.strategy);
+                        ExportRow {
+                            timestamp_ns: t.timestamp_ns,
+                            strategy: strategy.clone(),
+                            cells: vec![
+                                Cell::U64(t.timestamp_ns),
+                                Cell::Str(Some(strategy)),
+                                Cell::Str(Some(t.buy_exchange)),
+                                Cell::Str(Some(t.sell_exchange)),
+                                Cell::F64(Some(t.gross_profit)),
+                                Cell::F64(Some(t.fees)),
+                                Cell::F64(Some(t.slippage_bps)),
+                                Cell::F64(Some(t.capital_used)),
+                            ],
+                        }
+                    })
+                    .collect()
+            }
+        })
+    }
+
+    /// 每天 `at`（UTC）导出前一天的全部数据集
+    pub fn spawn_daily(self: Arc<Self>, at: NaiveTime, upload: bool) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                let now = Utc::now();
+                let mut next = now.date_naive().and_time(at).and_utc();
+                if next <= now {
+                    next += ChronoDuration::days(1);
+                }
+                tokio::time::sleep((next - now).to_std().unwrap_or_default()).await;
+
+                let day_end = next.date_naive().and_time(NaiveTime::MIN).and_utc();
+                let day_start = day_end - ChronoDuration::days(1);
+                let request = ExportRequest {
+                    from_ns: day_start.timestamp_nanos_opt().unwrap_or_default() as u64,
+                    to_ns: day_end.timestamp_nanos_opt().unwrap_or_default() as u64,
+                    datasets: Vec::new(),
+                    upload: upload && self.upload.is_some(),
+                };
+                if let Err(e) = self.start(request) {
+                    warn!("定时导出启动失败: {}", e);
+                }
+            }
+        })
+    }
+}
+
+/// (日期, 策略) 分区，策略名规范化为路径安全的小写形式
+fn partition_key(row: &ExportRow) -> (String, String) {
+    let date = DateTime::<Utc>::from_timestamp_nanos(row.timestamp_ns as i64).date_naive().to_string();
+    let strategy: String = row
+        .strategy
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    (date, if strategy.is_empty() { "unknown".to_string() } else { strategy })
+}
+
+fn to_parquet(dataset: ExportDataset, rows: &[ExportRow]) -> Result<Vec<u8>> {
+    let columns = dataset.columns();
+    let schema = Arc::new(Schema::new(
+        columns
+            .iter()
+            .map(|(name, ty)| Field::new(*name, ty.clone(), !matches!(ty, DataType::UInt64)))
+            .collect::<Vec<_>>(),
+    ));
+    let arrays = columns
+        .iter()
+        .enumerate()
+        .map(|(i, (name, ty))| {
+            let cells = rows.iter().map(|r| r.cells.get(i));
+            let array: ArrayRef = match ty {
+                DataType::UInt64 => Arc::new(
+                    cells
+                        .map(|c| match c {
+                            Some(Cell::U64(v)) => Ok(*v),
+                            _ => Err(anyhow!("column {} expects u64", name)),
+                        })
+                        .collect::<Result<UInt64Array>>()?,
+                ),
+                DataType::Float64 => Arc::new(
+                    cells
+                        .map(|c| match c {
+                            Some(Cell::F64(v)) => Ok(*v),
+                            _ => Err(anyhow!("column {} expects f64", name)),
+                        })
+                        .collect::<Result<Float64Array>>()?,
+                ),
+                _ => Arc::new(
+                    cells
+                        .map(|c| match c {
+                            Some(Cell::Str(v)) => Ok(v.clone()),
+                            _ => Err(anyhow!("column {} expects string", name)),
+                        })
+                        .collect::<Result<StringArray>>()?,
+                ),
+            };
+            Ok(array)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let batch = RecordBatch::try_new(schema.clone(), arrays)?;
+    let props = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+    let mut buffer = Vec::new();
+    let mut writer = ArrowWriter::try_new(&mut buffer, schema, Some(props))?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(buffer)
+}
+
+/// 控制请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum ExportCommand {
+    Start { request: ExportRequest },
+    Status { id: u64 },
+    List,
+}
+
+/// 控制响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ExportReply {
+    Job { job: ExportJob, progress: f64 },
+    Jobs { jobs: Vec<ExportJob> },
+}
+
+/// 导出控制服务
+pub struct ExportService {
+    exporter: Arc<DataExporter>,
+    auth: Option<Arc<AuthService>>,
+    audit: Option<Arc<AuditLog>>,
+}
+
+impl ExportService {
+    pub fn new(exporter: Arc<DataExporter>) -> Self {
+        Self { exporter, auth: None, audit: None }
+    }
+
+    pub fn with_auth(mut self, auth: Arc<AuthService>) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    pub fn with_audit(mut self, audit: Arc<AuditLog>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    fn handle(&self, command: &ExportCommand) -> SystemResult<ExportReply> {
+        let job = match command {
+            ExportCommand::Start { request } => self.exporter.start(request.clone())?,
+            ExportCommand::Status { id } => self
+                .exporter
+                .job(*id)
+                .ok_or_else(|| StorageError::NotFound(format!("export job {}", id)))?,
+            ExportCommand::List => return Ok(ExportReply::Jobs { jobs: self.exporter.jobs() }),
+        };
+        Ok(ExportReply::Job { progress: job.progress(), job })
+    }
+
+    /// 控制服务：请求体为 `ExportCommand` JSON，响应为带错误码的 `ApiResponse`
+    pub async fn serve(self: Arc<Self>, nats: &NatsManager, subject: &str) -> Result<()> {
+        let mut subscriber = nats.subscribe(subject).await?;
+        info!("📦 数据导出服务已启动: {}", subject);
+
+        while let Some(message) = subscriber.next().await {
+            let Some(reply) = message.reply.clone() else { continue };
+            let result = async {
+                let command = serde_json::from_slice::<ExportCommand>(&message.payload)
+                    .map_err(|e| SystemError::InvalidRequest(e.to_string()))?;
+                let starting = matches!(command, ExportCommand::Start { .. });
+                let action = if starting { ControlAction::ExportData } else { ControlAction::ViewDashboard };
+                let principal = match &self.auth {
+                    Some(auth) => Some(auth.authorize_nats(&message, action)?),
+                    None => None,
+                };
+                let reply = self.handle(&command)?;
+                if let (true, Some(audit), Some(principal), ExportReply::Job { job, .. }) =
+                    (starting, &self.audit, &principal, &reply)
+                {
+                    let details = serde_json::to_value(&command).unwrap_or_default();
+                    audit.record(principal, action, &format!("export:{}", job.id), details).await?;
+                }
+                Ok::<_, SystemError>(reply)
+            }
+            .await;
+            let response: ApiResponse<ExportReply> = result.into();
+            if let Err(e) = nats
+                .get_client()
+                .publish(reply, encode_reply(&message, &response)?.into())
+                .await
+            {
+                warn!("数据导出响应发送失败: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::{JournalRecord, MemoryJournalStore};
+    use adapters::attribution::TradeRecord;
+    use common::types::StrategyKind;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    const DAY_NS: u64 = 86_400_000_000_000;
+
+    #[tokio::test]
+    async fn test_export_partitions_by_date_and_strategy() {
+        let journal = Arc::new(MemoryJournalStore::new());
+        let record = |seq, timestamp_ns, event| JournalRecord { seq, timestamp_ns, event };
+        let detected = |id: &str, strategy: &str| JournalEvent::OpportunityDetected {
+            opportunity_id: id.into(),
+            strategy: strategy.into(),
+            symbol: "BTC/USDT".into(),
+            net_profit: 1.5,
+        };
+        journal
+            .append(&[
+                record(1, 10, detected("o1", "triangular")),
+                record(2, 20, JournalEvent::RiskDecision { opportunity_id: "o1".into(), approved: true, reason: None }),
+                record(3, 30, detected("o2", "inter_exchange")),
+                record(4, DAY_NS + 10, detected("o3", "triangular")),
+                record(5, 40, JournalEvent::OrderSubmitted { order_id: "x1".into(), opportunity_id: "o1".into(), strategy: "triangular".into() }),
+                record(6, 50, JournalEvent::OrderFilled { order_id: "x1".into(), quantity: 2.0, price: 10.0 }),
+            ])
+            .await
+            .unwrap();
+        let trades = Arc::new(PerformanceAnalyzer::new());
+        trades.record(&TradeRecord {
+            strategy: StrategyKind::Triangular,
+            buy_exchange: "binance".into(),
+            sell_exchange: "binance".into(),
+            gross_profit: 3.0,
+            fees: 0.5,
+            slippage_bps: 1.0,
+            capital_used: 1_000.0,
+            timestamp_ns: 60,
+        });
+
+        let root = std::env::temp_dir().join(format!("celue_export_{}", uuid::Uuid::new_v4()));
+        let exporter = Arc::new(
+            DataExporter::new(journal, Arc::new(LocalExportSink::new(&root))).with_trades(trades),
+        );
+        let job = exporter.start(ExportRequest { from_ns: 0, to_ns: 2 * DAY_NS, datasets: Vec::new(), upload: false }).unwrap();
+        assert!(exporter.start(ExportRequest { from_ns: 5, to_ns: 5, datasets: Vec::new(), upload: false }).is_err());
+        let job = loop {
+            let job = exporter.job(job.id).unwrap();
+            if job.finished_at.is_some() {
+                break job;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        };
+        assert_eq!(job.status, ExportStatus::Completed);
+        // 机会 3 个分区（两天 × 策略），执行 1 个，费用 1 个
+        assert_eq!(job.total_partitions, 5);
+        assert_eq!(job.rows_written, 5);
+
+        let path = root.join("opportunities/date=1970-01-01/strategy=triangular").join(format!("export-{}.parquet", job.id));
+        let reader = SerializedFileReader::new(std::fs::File::open(path).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 1);
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,402 @@
+//! AI风控模型推理
+//!
+//! 从可配置目录加载 ONNX 模型，对机会/市场特征进行推理。推理受严格的
+//! 延迟预算约束，超时或出错时退回确定性的规则评分。新版本模型先以影子
+//! 模式与现役模型并行评估，偏差达标后才允许提升为现役模型。
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use common::ArbitrageOpportunity;
+
+/// 推理配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferenceConfig {
+    /// 模型目录
+    pub model_dir: PathBuf,
+    /// 现役模型文件名
+    pub active_model: Option<String>,
+    /// 单次推理延迟预算（微秒）
+    pub latency_budget_us: u64,
+    /// 影子评估的最少样本数
+    pub shadow_min_samples: u64,
+    /// 允许提升的最大平均绝对偏差
+    pub promotion_max_divergence: f64,
+}
+
+impl Default for InferenceConfig {
+    fn default() -> Self {
+        Self {
+            model_dir: std::env::var("CELUE_RISK_MODEL_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| PathBuf::from("models/risk")),
+            active_model: std::env::var("CELUE_RISK_MODEL").ok(),
+            latency_budget_us: std::env::var("CELUE_RISK_MODEL_BUDGET_US")
+                .ok().and_then(|s| s.parse().ok())
+                .unwrap_or(500),
+            shadow_min_samples: std::env::var("CELUE_RISK_SHADOW_MIN_SAMPLES")
+                .ok().and_then(|s| s.parse().ok())
+                .unwrap_or(1000),
+            promotion_max_divergence: std::env::var("CELUE_RISK_PROMOTION_MAX_DIVERGENCE")
+                .ok().and_then(|s| s.parse().ok())
+                .unwrap_or(0.05),
+        }
+    }
+}
+
+/// 风控模型输入特征
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RiskFeatures {
+    pub net_profit_pct: f32,
+    pub leg_count: f32,
+    pub quote_age_ms: f32,
+    pub notional_usd: f32,
+    pub daily_pnl_usd: f32,
+    pub consecutive_failures: f32,
+}
+
+impl RiskFeatures {
+    pub fn from_opportunity(opportunity: &ArbitrageOpportunity, now_ns: u64, daily_pnl_usd: f64, consecutive_failures: u64) -> Self {
+        let notional: f64 = opportunity.legs.iter().map(|l| l.cost.to_f64().abs()).sum();
+        Self {
+            net_profit_pct: opportunity.net_profit_pct.to_f64() as f32,
+            leg_count: opportunity.legs.len() as f32,
+            quote_age_ms: opportunity.quote_age_ns(now_ns).map_or(0.0, |age| (age as f64 / 1_000_000.0) as f32),
+            notional_usd: notional as f32,
+            daily_pnl_usd: daily_pnl_usd as f32,
+            consecutive_failures: consecutive_failures as f32,
+        }
+    }
+
+    pub fn to_vec(&self) -> Vec<f32> {
+        vec![
+            self.net_profit_pct,
+            self.leg_count,
+            self.quote_age_ms,
+            self.notional_usd,
+            self.daily_pnl_usd,
+            self.consecutive_failures,
+        ]
+    }
+}
+
+/// 风控模型：输出 0..1 的风险分数
+pub trait RiskModel: Send + Sync {
+    fn version(&self) -> &str;
+    fn infer(&self, features: &[f32]) -> Result<f32>;
+}
+
+/// 确定性规则评分（模型不可用/超时时的后备）
+pub struct RuleBasedRiskModel;
+
+impl RiskModel for RuleBasedRiskModel {
+    fn version(&self) -> &str {
+        "rule-based"
+    }
+
+    fn infer(&self, features: &[f32]) -> Result<f32> {
+        let f = |i: usize| features.get(i).copied().unwrap_or(0.0);
+        let mut score = 0.2;
+        if f(0) < 0.1 {
+            score += 0.2; // 利润过薄
+        }
+        if f(2) > 200.0 {
+            score += 0.2; // 行情陈旧
+        }
+        if f(4) < 0.0 {
+            score += 0.2; // 当日亏损
+        }
+        score += (f(5) * 0.1).min(0.3); // 连续失败
+        Ok(score.clamp(0.0, 1.0))
+    }
+}
+
+/// ONNX 模型（需启用 `onnx` feature）
+#[cfg(feature = "onnx")]
+pub struct OnnxRiskModel {
+    version: String,
+    session: Mutex<ort::session::Session>,
+}
+
+#[cfg(feature = "onnx")]
+impl OnnxRiskModel {
+    pub fn load(path: &Path) -> Result<Self> {
+        let session = ort::session::Session::builder()?
+            .with_optimization_level(ort::session::builder::GraphOptimizationLevel::Level3)?
+            .with_intra_threads(1)?
+            .commit_from_file(path)?;
+        Ok(Self {
+            version: path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+            session: Mutex::new(session),
+        })
+    }
+}
+
+#[cfg(feature = "onnx")]
+impl RiskModel for OnnxRiskModel {
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn infer(&self, features: &[f32]) -> Result<f32> {
+        let input = ort::value::Tensor::from_array(([1usize, features.len()], features.to_vec()))?;
+        let mut session = self.session.lock();
+        let outputs = session.run(ort::inputs![input])?;
+        if outputs.len() == 0 {
+            return Err(anyhow!("模型无输出"));
+        }
+        let (_, scores) = outputs[0].try_extract_tensor::<f32>()?;
+        scores.first().copied().ok_or_else(|| anyhow!("模型输出为空"))
+    }
+}
+
+/// 从文件加载模型
+pub fn load_model(path: &Path) -> Result<Arc<dyn RiskModel>> {
+    #[cfg(feature = "onnx")]
+    {
+        Ok(Arc::new(OnnxRiskModel::load(path)?))
+    }
+    #[cfg(not(feature = "onnx"))]
+    {
+        Err(anyhow!("未启用 onnx feature，无法加载模型: {}", path.display()))
+    }
+}
+
+/// 推理结果来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InferenceSource {
+    Model,
+    Fallback,
+}
+
+/// 单次推理结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferenceOutcome {
+    pub risk_score: f32,
+    pub source: InferenceSource,
+    pub model_version: String,
+    pub latency_us: u64,
+}
+
+/// 影子评估统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShadowStats {
+    pub version: String,
+    pub samples: u64,
+    pub mean_abs_divergence: f64,
+    pub errors: u64,
+}
+
+struct ShadowSlot {
+    model: Arc<dyn RiskModel>,
+    stats: ShadowStats,
+}
+
+/// 风控推理服务：现役模型 + 影子模型 + 确定性后备
+pub struct RiskInference {
+    config: InferenceConfig,
+    active: RwLock<Option<Arc<dyn RiskModel>>>,
+    shadow: Arc<Mutex<Option<ShadowSlot>>>,
+    fallback: Arc<dyn RiskModel>,
+}
+
+impl RiskInference {
+    /// 创建推理服务；配置了现役模型时尝试加载，失败则仅使用后备评分
+    pub fn new(config: InferenceConfig) -> Self {
+        let active = config.active_model.as_ref().and_then(|name| {
+            let path = config.model_dir.join(name);
+            match load_model(&path) {
+                Ok(model) => {
+                    info!("🧠 风控模型已加载: {}", model.version());
+                    Some(model)
+                }
+                Err(e) => {
+                    warn!("⚠️ 风控模型加载失败，使用规则评分: {}", e);
+                    None
+                }
+            }
+        });
+        Self {
+            config,
+            active: RwLock::new(active),
+            shadow: Arc::new(Mutex::new(None)),
+            fallback: Arc::new(RuleBasedRiskModel),
+        }
+    }
+
+    /// 在延迟预算内推理；超时或失败时使用确定性后备
+    pub async fn infer(&self, features: &RiskFeatures) -> InferenceOutcome {
+        let input = features.to_vec();
+        let start = Instant::now();
+        let budget = Duration::from_micros(self.config.latency_budget_us);
+
+        let active = self.active.read().clone();
+        let primary = match active {
+            Some(model) => {
+                let version = model.version().to_string();
+                let task_input = input.clone();
+                let run = tokio::task::spawn_blocking(move || model.infer(&task_input));
+                match tokio::time::timeout(budget, run).await {
+                    Ok(Ok(Ok(score))) => Some((score, version)),
+                    Ok(Ok(Err(e))) => {
+                        warn!("风控模型推理失败: {}", e);
+                        None
+                    }
+                    Ok(Err(e)) => {
+                        warn!("风控模型推理任务异常: {}", e);
+                        None
+                    }
+                    Err(_) => {
+                        warn!("风控模型推理超出预算 {}μs", self.config.latency_budget_us);
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let outcome = match primary {
+            Some((score, version)) => InferenceOutcome {
+                risk_score: score.clamp(0.0, 1.0),
+                source: InferenceSource::Model,
+                model_version: version,
+                latency_us: start.elapsed().as_micros() as u64,
+            },
+            None => InferenceOutcome {
+                risk_score: self.fallback.infer(&input).unwrap_or(1.0),
+                source: InferenceSource::Fallback,
+                model_version: self.fallback.version().to_string(),
+                latency_us: start.elapsed().as_micros() as u64,
+            },
+        };
+
+        self.evaluate_shadow(input, outcome.risk_score);
+        outcome
+    }
+
+    /// 影子评估不占用延迟预算：在阻塞线程池中异步完成，热路径不等待结果
+    fn evaluate_shadow(&self, input: Vec<f32>, reference: f32) {
+        let Some(model) = self.shadow.lock().as_ref().map(|slot| slot.model.clone()) else { return };
+        let shadow = Arc::clone(&self.shadow);
+        tokio::task::spawn_blocking(move || {
+            let result = model.infer(&input);
+            let mut guard = shadow.lock();
+            // 评估期间影子可能已被替换或提升，旧模型的样本不再计入
+            let Some(slot) = guard.as_mut().filter(|slot| Arc::ptr_eq(&slot.model, &model)) else { return };
+            match result {
+                Ok(score) => {
+                    let divergence = (score - reference).abs() as f64;
+                    let stats = &mut slot.stats;
+                    stats.samples += 1;
+                    stats.mean_abs_divergence += (divergence - stats.mean_abs_divergence) / stats.samples as f64;
+                }
+                Err(_) => slot.stats.errors += 1,
+            }
+        });
+    }
+
+    /// 热加载新版本作为影子模型
+    pub fn load_shadow(&self, file_name: &str) -> Result<()> {
+        let model = load_model(&self.config.model_dir.join(file_name))?;
+        self.set_shadow(model);
+        Ok(())
+    }
+
+    /// 设置影子模型（替换已有影子）
+    pub fn set_shadow(&self, model: Arc<dyn RiskModel>) {
+        info!("👥 影子风控模型开始评估: {}", model.version());
+        let stats = ShadowStats {
+            version: model.version().to_string(),
+            ..Default::default()
+        };
+        *self.shadow.lock() = Some(ShadowSlot { model, stats });
+    }
+
+    pub fn shadow_stats(&self) -> Option<ShadowStats> {
+        self.shadow.lock().as_ref().map(|s| s.stats.clone())
+    }
+
+    /// 影子评估达标时提升为现役模型
+    pub fn promote_shadow(&self) -> Result<String> {
+        let mut shadow = self.shadow.lock();
+        let slot = shadow.as_ref().ok_or_else(|| anyhow!("没有影子模型"))?;
+        if slot.stats.samples < self.config.shadow_min_samples {
+            return Err(anyhow!(
+                "影子样本不足: {}/{}",
+                slot.stats.samples,
+                self.config.shadow_min_samples
+            ));
+        }
+        if slot.stats.errors > 0 || slot.stats.mean_abs_divergence > self.config.promotion_max_divergence {
+            return Err(anyhow!(
+                "影子模型未达标: 偏差 {:.4}, 错误 {}",
+                slot.stats.mean_abs_divergence,
+                slot.stats.errors
+            ));
+        }
+        let slot = shadow.take().expect("checked above");
+        let version = slot.model.version().to_string();
+        *self.active.write() = Some(slot.model);
+        info!("✅ 风控模型已提升: {}", version);
+        Ok(version)
+    }
+
+    /// 当前现役模型版本
+    pub fn active_version(&self) -> String {
+        self.active
+            .read()
+            .as_ref()
+            .map(|m| m.version().to_string())
+            .unwrap_or_else(|| self.fallback.version().to_string())
+    }
+}
+
+impl Default for RiskInference {
+    fn default() -> Self {
+        Self::new(InferenceConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct SlowModel;
+
+    impl RiskModel for SlowModel {
+        fn version(&self) -> &str {
+            "slow-shadow"
+        }
+
+        fn infer(&self, _features: &[f32]) -> Result<f32> {
+            std::thread::sleep(Duration::from_millis(50));
+            Ok(0.5)
+        }
+    }
+
+    #[tokio::test]
+    async fn shadow_evaluation_runs_off_the_hot_path() {
+        let inference = RiskInference::new(InferenceConfig {
+            active_model: None,
+            latency_budget_us: 500,
+            ..InferenceConfig::default()
+        });
+        inference.set_shadow(Arc::new(SlowModel));
+
+        let started = Instant::now();
+        let outcome = inference.infer(&RiskFeatures::default()).await;
+        assert!(started.elapsed() < Duration::from_millis(50));
+        assert_eq!(outcome.source, InferenceSource::Fallback);
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let stats = inference.shadow_stats().unwrap();
+        assert_eq!(stats.samples, 1);
+        assert_eq!(stats.errors, 0);
+    }
+}
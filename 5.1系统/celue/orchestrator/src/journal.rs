@@ -0,0 +1,439 @@
+//! 事件溯源系统日志
+//!
+//! 关键领域事件（机会检测、风控决策、下单/成交、配置变更、熔断触发）
+//! 按单调递增序号追加到日志，批量写入存储（ClickHouse 或内存）。排查
+//! 事故时按时间段回放日志，逐分钟重建系统状态。序号在进程内连续，
+//! 重启后从存储中的最大序号继续。
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tracing::{error, info, warn};
+
+use crate::config::ConfigChangeEvent;
+use crate::event_bus::TopicEvent;
+
+const NANOS_PER_MINUTE: u64 = 60_000_000_000;
+
+/// 领域事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JournalEvent {
+    OpportunityDetected {
+        opportunity_id: String,
+        strategy: String,
+        symbol: String,
+        net_profit: f64,
+    },
+    RiskDecision {
+        opportunity_id: String,
+        approved: bool,
+        reason: Option<String>,
+    },
+    OrderSubmitted {
+        order_id: String,
+        opportunity_id: String,
+        strategy: String,
+    },
+    OrderFilled {
+        order_id: String,
+        quantity: f64,
+        price: f64,
+    },
+    ConfigChanged {
+        section: String,
+    },
+    BreakerTripped {
+        scope: String,
+        reason: String,
+    },
+}
+
+impl TopicEvent for JournalEvent {
+    const TOPIC: &'static str = "journal.events";
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+impl JournalEvent {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::OpportunityDetected { .. } => "opportunity_detected",
+            Self::RiskDecision { .. } => "risk_decision",
+            Self::OrderSubmitted { .. } => "order_submitted",
+            Self::OrderFilled { .. } => "order_filled",
+            Self::ConfigChanged { .. } => "config_changed",
+            Self::BreakerTripped { .. } => "breaker_tripped",
+        }
+    }
+}
+
+/// 一条日志记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalRecord {
+    pub seq: u64,
+    pub timestamp_ns: u64,
+    pub event: JournalEvent,
+}
+
+/// 日志存储后端
+#[async_trait]
+pub trait JournalStore: Send + Sync {
+    /// 追加一批记录（按序号升序）
+    async fn append(&self, records: &[JournalRecord]) -> Result<()>;
+    /// 已持久化的最大序号
+    async fn last_seq(&self) -> Result<u64>;
+    /// 按序号升序返回 [from_ns, to_ns) 内的记录
+    async fn range(&self, from_ns: u64, to_ns: u64) -> Result<Vec<JournalRecord>>;
+}
+
+/// 内存存储
+#[derive(Default)]
+pub struct MemoryJournalStore {
+    records: RwLock<Vec<JournalRecord>>,
+}
+
+impl MemoryJournalStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl JournalStore for MemoryJournalStore {
+    async fn append(&self, records: &[JournalRecord]) -> Result<()> {
+        self.records.write().extend_from_slice(records);
+        Ok(())
+    }
+
+    async fn last_seq(&self) -> Result<u64> {
+        Ok(self.records.read().last().map(|r| r.seq).unwrap_or(0))
+    }
+
+    async fn range(&self, from_ns: u64, to_ns: u64) -> Result<Vec<JournalRecord>> {
+        Ok(self
+            .records
+            .read()
+            .iter()
+            .filter(|r| r.timestamp_ns >= from_ns && r.timestamp_ns < to_ns)
+            .cloned()
+            .collect())
+    }
+}
+
+/// ClickHouse 存储，通过 HTTP 接口以 JSONEachRow 读写
+pub struct ClickHouseJournalStore {
+    url: String,
+    table: String,
+    client: reqwest::Client,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ClickHouseRow {
+    seq: u64,
+    timestamp_ns: u64,
+    event_type: String,
+    payload: String,
+}
+
+impl ClickHouseJournalStore {
+    /// 连接并建表，例如 `http://localhost:8123`、`celue.event_journal`
+    pub async fn connect(url: &str, table: &str) -> Result<Self> {
+        let store = Self {
+            url: url.trim_end_matches('/').to_string(),
+            table: table.to_string(),
+            client: reqwest::Client::new(),
+        };
+        store
+            .execute(format!(
+                "CREATE TABLE IF NOT EXISTS {} (
+                    seq UInt64,
+                    timestamp_ns UInt64,
+                    event_type LowCardinality(String),
+                    payload String
+                ) ENGINE = MergeTree ORDER BY seq",
+                store.table
+            ))
+            .await?;
+        Ok(store)
+    }
+
+    async fn execute(&self, sql: String) -> Result<String> {
+        let response = self.client.post(&self.url).body(sql).send().await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(anyhow!("ClickHouse 请求失败 ({}): {}", status, body.trim()));
+        }
+        Ok(body)
+    }
+}
+
+#[async_trait]
+impl JournalStore for ClickHouseJournalStore {
+    async fn append(&self, records: &[JournalRecord]) -> Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+        let mut sql = format!("INSERT INTO {} FORMAT JSONEachRow\n", self.table);
+        for record in records {
+            let row = ClickHouseRow {
+                seq: record.seq,
+                timestamp_ns: record.timestamp_ns,
+                event_type: record.event.kind().to_string(),
+                payload: serde_json::to_string(&record.event)?,
+            };
+            sql.push_str(&serde_json::to_string(&row)?);
+            sql.push('\n');
+        }
+        self.execute(sql).await?;
+        Ok(())
+    }
+
+    async fn last_seq(&self) -> Result<u64> {
+        let body = self.execute(format!("SELECT max(seq) FROM {} FORMAT TabSeparated", self.table)).await?;
+        Ok(body.trim().parse().unwrap_or(0))
+    }
+
+    async fn range(&self, from_ns: u64, to_ns: u64) -> Result<Vec<JournalRecord>> {
+        let body = self
+            .execute(format!(
+                "SELECT seq, timestamp_ns, event_type, payload FROM {} \
+                 WHERE timestamp_ns >= {} AND timestamp_ns < {} ORDER BY seq FORMAT JSONEachRow",
+                self.table, from_ns, to_ns
+            ))
+            .await?;
+        body.lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|line| {
+                let row: ClickHouseRow = serde_json::from_str(line)?;
+                Ok(JournalRecord {
+                    seq: row.seq,
+                    timestamp_ns: row.timestamp_ns,
+                    event: serde_json::from_str(&row.payload)?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// 事件日志：同步追加到缓冲区，后台批量落盘
+pub struct EventJournal {
+    store: Arc<dyn JournalStore>,
+    /// 下一条记录前的序号与待落盘记录
+    buffer: Mutex<(u64, Vec<JournalRecord>)>,
+}
+
+impl EventJournal {
+    /// 从存储中的最大序号继续编号
+    pub async fn open(store: Arc<dyn JournalStore>) -> Result<Self> {
+        let last_seq = store.last_seq().await?;
+        info!("📓 事件日志已打开，起始序号 {}", last_seq + 1);
+        Ok(Self { store, buffer: Mutex::new((last_seq, Vec::new())) })
+    }
+
+    /// 追加事件，返回分配的序号
+    pub fn record(&self, event: JournalEvent) -> u64 {
+        let timestamp_ns = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64;
+        let mut buffer = self.buffer.lock();
+        buffer.0 += 1;
+        let seq = buffer.0;
+        buffer.1.push(JournalRecord { seq, timestamp_ns, event });
+        seq
+    }
+
+    /// 将缓冲区写入存储；失败时记录放回缓冲区下次重试
+    pub async fn flush(&self) -> Result<usize> {
+        let pending = std::mem::take(&mut self.buffer.lock().1);
+        if pending.is_empty() {
+            return Ok(0);
+        }
+        if let Err(e) = self.store.append(&pending).await {
+            let mut buffer = self.buffer.lock();
+            let newer = std::mem::replace(&mut buffer.1, pending);
+            buffer.1.extend(newer);
+            return Err(e);
+        }
+        Ok(pending.len())
+    }
+
+    /// 周期性落盘
+    pub fn spawn(self: Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.flush().await {
+                    error!("❌ 事件日志落盘失败: {}", e);
+                }
+            }
+        })
+    }
+
+    /// 记录配置热更新事件
+    pub fn watch_config(self: Arc<Self>, mut changes: broadcast::Receiver<ConfigChangeEvent>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match changes.recv().await {
+                    Ok(change) => {
+                        self.record(JournalEvent::ConfigChanged { section: format!("{:?}", change) });
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("事件日志丢失 {} 条配置变更通知", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// 读取时间段内已落盘的记录
+    pub async fn replay(&self, from_ns: u64, to_ns: u64) -> Result<Vec<JournalRecord>> {
+        self.store.range(from_ns, to_ns).await
+    }
+
+    /// 逐分钟回放，返回每分钟结束时的系统状态
+    pub async fn replay_by_minute(&self, from_ns: u64, to_ns: u64) -> Result<Vec<StateFrame>> {
+        Ok(ReplayedState::frames(&self.replay(from_ns, to_ns).await?))
+    }
+}
+
+/// 订单回放状态
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReplayedOrder {
+    pub opportunity_id: String,
+    pub strategy: String,
+    pub filled_quantity: f64,
+    pub avg_fill_price: f64,
+}
+
+/// 由日志重建的系统状态
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReplayedState {
+    pub last_seq: u64,
+    pub opportunities_detected: u64,
+    pub opportunities_approved: u64,
+    pub opportunities_rejected: u64,
+    pub orders: HashMap<String, ReplayedOrder>,
+    pub config_changes: Vec<String>,
+    /// 熔断范围 -> 原因
+    pub breakers: BTreeMap<String, String>,
+    /// 检测到但尚未有风控决策的机会
+    pub pending_opportunities: BTreeMap<String, String>,
+}
+
+impl ReplayedState {
+    pub fn apply(&mut self, record: &JournalRecord) {
+        if record.seq <= self.last_seq {
+            return;
+        }
+        if self.last_seq != 0 && record.seq != self.last_seq + 1 {
+            warn!("📓 日志序号不连续: {} -> {}", self.last_seq, record.seq);
+        }
+        self.last_seq = record.seq;
+        match &record.event {
+            JournalEvent::OpportunityDetected { opportunity_id, strategy, .. } => {
+                self.opportunities_detected += 1;
+                self.pending_opportunities.insert(opportunity_id.clone(), strategy.clone());
+            }
+            JournalEvent::RiskDecision { opportunity_id, approved, .. } => {
+                self.pending_opportunities.remove(opportunity_id);
+                if *approved {
+                    self.opportunities_approved += 1;
+                } else {
+                    self.opportunities_rejected += 1;
+                }
+            }
+            JournalEvent::OrderSubmitted { order_id, opportunity_id, strategy } => {
+                self.orders.insert(order_id.clone(), ReplayedOrder {
+                    opportunity_id: opportunity_id.clone(),
+                    strategy: strategy.clone(),
+                    ..Default::default()
+                });
+            }
+            JournalEvent::OrderFilled { order_id, quantity, price } => {
+                let order = self.orders.entry(order_id.clone()).or_default();
+                let total = order.filled_quantity + quantity;
+                if total > 0.0 {
+                    order.avg_fill_price = (order.avg_fill_price * order.filled_quantity + price * quantity) / total;
+                }
+                order.filled_quantity = total;
+            }
+            JournalEvent::ConfigChanged { section } => self.config_changes.push(section.clone()),
+            JournalEvent::BreakerTripped { scope, reason } => {
+                self.breakers.insert(scope.clone(), reason.clone());
+            }
+        }
+    }
+
+    /// 按分钟切片，输出每个有事件的分钟结束时的状态
+    pub fn frames(records: &[JournalRecord]) -> Vec<StateFrame> {
+        let mut state = Self::default();
+        let mut frames: Vec<StateFrame> = Vec::new();
+        for record in records {
+            let minute_start_ns = record.timestamp_ns - record.timestamp_ns % NANOS_PER_MINUTE;
+            state.apply(record);
+            match frames.last_mut() {
+                Some(frame) if frame.minute_start_ns == minute_start_ns => {
+                    frame.events += 1;
+                    frame.state = state.clone();
+                }
+                _ => frames.push(StateFrame { minute_start_ns, events: 1, state: state.clone() }),
+            }
+        }
+        frames
+    }
+}
+
+/// 某一分钟结束时的状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateFrame {
+    pub minute_start_ns: u64,
+    /// 该分钟内的事件数
+    pub events: usize,
+    pub state: ReplayedState,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_append_flush_and_replay() {
+        let store = Arc::new(MemoryJournalStore::new());
+        let journal = EventJournal::open(store.clone()).await.unwrap();
+        journal.record(JournalEvent::OpportunityDetected {
+            opportunity_id: "opp-1".into(),
+            strategy: "inter_exchange".into(),
+            symbol: "BTCUSDT".into(),
+            net_profit: 12.5,
+        });
+        journal.record(JournalEvent::RiskDecision { opportunity_id: "opp-1".into(), approved: true, reason: None });
+        journal.record(JournalEvent::OrderSubmitted {
+            order_id: "o-1".into(),
+            opportunity_id: "opp-1".into(),
+            strategy: "inter_exchange".into(),
+        });
+        journal.record(JournalEvent::OrderFilled { order_id: "o-1".into(), quantity: 0.5, price: 100.0 });
+        journal.record(JournalEvent::OrderFilled { order_id: "o-1".into(), quantity: 0.5, price: 102.0 });
+        assert_eq!(journal.flush().await.unwrap(), 5);
+
+        // 重新打开后序号继续
+        let reopened = EventJournal::open(store).await.unwrap();
+        assert_eq!(reopened.record(JournalEvent::ConfigChanged { section: "RiskConfigChanged".into() }), 6);
+        reopened.flush().await.unwrap();
+
+        let frames = reopened.replay_by_minute(0, u64::MAX).await.unwrap();
+        let state = &frames.last().unwrap().state;
+        assert_eq!(state.last_seq, 6);
+        assert_eq!(state.opportunities_approved, 1);
+        assert!(state.pending_opportunities.is_empty());
+        assert!((state.orders["o-1"].avg_fill_price - 101.0).abs() < 1e-9);
+        assert_eq!(frames.iter().map(|f| f.events).sum::<usize>(), 6);
+    }
+}
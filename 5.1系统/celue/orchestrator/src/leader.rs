@@ -0,0 +1,332 @@
+//! 主备高可用 - 基于租约的领导者选举
+//!
+//! 两个实例竞争同一租约，只有持有租约的领导者执行交易；跟随者继续检测
+//! 机会但只以影子模式运行。租约过期后跟随者在一个 TTL 内接管。每次获得
+//! 租约都会得到单调递增的 fencing token，下单前必须校验：旧领导者即使
+//! 续约失败未察觉，本地租约到期后也拿不到有效 token，无法再下单。
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+/// 选举配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderConfig {
+    /// 租约时长，即最长故障切换窗口
+    pub lease_ttl_ms: u64,
+    /// 续约间隔
+    pub renew_interval_ms: u64,
+    /// 本地提前放弃租约的安全余量，抵消时钟漂移与网络延迟
+    pub safety_margin_ms: u64,
+}
+
+impl Default for LeaderConfig {
+    fn default() -> Self {
+        let lease_ttl_ms = std::env::var("CELUE_LEADER_LEASE_TTL_MS")
+            .ok().and_then(|s| s.parse().ok())
+            .unwrap_or(5_000);
+        Self {
+            lease_ttl_ms,
+            renew_interval_ms: lease_ttl_ms / 3,
+            safety_margin_ms: lease_ttl_ms / 10,
+        }
+    }
+}
+
+/// 租约后端
+#[async_trait]
+pub trait LeaseBackend: Send + Sync {
+    /// 租约空闲或已过期时获取，返回 fencing token
+    async fn try_acquire(&self, node_id: &str, ttl: Duration) -> Result<Option<u64>>;
+    /// 续约；租约已被他人持有时返回 None
+    async fn renew(&self, node_id: &str, token: u64, ttl: Duration) -> Result<Option<u64>>;
+    async fn release(&self, node_id: &str, token: u64) -> Result<()>;
+}
+
+/// 进程内租约（测试与单机部署）
+#[derive(Default)]
+pub struct MemoryLease {
+    state: Mutex<Option<(String, u64, Instant)>>,
+    next_token: AtomicU64,
+}
+
+impl MemoryLease {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl LeaseBackend for MemoryLease {
+    async fn try_acquire(&self, node_id: &str, ttl: Duration) -> Result<Option<u64>> {
+        let mut state = self.state.lock();
+        if let Some((_, _, expires_at)) = state.as_ref() {
+            if *expires_at > Instant::now() {
+                return Ok(None);
+            }
+        }
+        let token = self.next_token.fetch_add(1, Ordering::SeqCst) + 1;
+        *state = Some((node_id.to_string(), token, Instant::now() + ttl));
+        Ok(Some(token))
+    }
+
+    async fn renew(&self, node_id: &str, token: u64, ttl: Duration) -> Result<Option<u64>> {
+        let mut state = self.state.lock();
+        match state.as_mut() {
+            Some((holder, current, expires_at)) if holder == node_id && *current == token => {
+                *expires_at = Instant::now() + ttl;
+                Ok(Some(token))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    async fn release(&self, node_id: &str, token: u64) -> Result<()> {
+        let mut state = self.state.lock();
+        if matches!(state.as_ref(), Some((holder, current, _)) if holder == node_id && *current == token) {
+            *state = None;
+        }
+        Ok(())
+    }
+}
+
+/// NATS KV 租约：键的修订号即 fencing token，桶的 max_age 即租约时长
+pub struct NatsKvLease {
+    store: async_nats::jetstream::kv::Store,
+    key: String,
+}
+
+impl NatsKvLease {
+    pub async fn new(client: async_nats::Client, bucket: &str, key: &str, ttl: Duration) -> Result<Self> {
+        let jetstream = async_nats::jetstream::new(client);
+        let store = match jetstream.get_key_value(bucket).await {
+            Ok(store) => store,
+            Err(_) => {
+                jetstream
+                    .create_key_value(async_nats::jetstream::kv::Config {
+                        bucket: bucket.to_string(),
+                        history: 1,
+                        max_age: ttl,
+                        ..Default::default()
+                    })
+                    .await?
+            }
+        };
+        Ok(Self { store, key: key.to_string() })
+    }
+}
+
+#[async_trait]
+impl LeaseBackend for NatsKvLease {
+    async fn try_acquire(&self, node_id: &str, _ttl: Duration) -> Result<Option<u64>> {
+        // 期望修订号为 0：键不存在（或已随 max_age 过期）时才会成功
+        match self.store.update(&self.key, node_id.as_bytes().to_vec().into(), 0).await {
+            Ok(revision) => Ok(Some(revision)),
+            Err(_) => {
+                // 删除/过期后留下的墓碑需要按修订号覆盖
+                match self.store.entry(&self.key).await? {
+                    Some(entry) if entry.operation != async_nats::jetstream::kv::Operation::Put => Ok(self
+                        .store
+                        .update(&self.key, node_id.as_bytes().to_vec().into(), entry.revision)
+                        .await
+                        .ok()),
+                    _ => Ok(None),
+                }
+            }
+        }
+    }
+
+    async fn renew(&self, node_id: &str, token: u64, _ttl: Duration) -> Result<Option<u64>> {
+        // 以修订号做 CAS，期间被他人抢占则失败
+        Ok(self
+            .store
+            .update(&self.key, node_id.as_bytes().to_vec().into(), token)
+            .await
+            .ok())
+    }
+
+    async fn release(&self, _node_id: &str, token: u64) -> Result<()> {
+        let current = self.store.entry(&self.key).await?.map(|e| e.revision);
+        if current == Some(token) {
+            self.store.delete(&self.key).await?;
+        }
+        Ok(())
+    }
+}
+
+/// 节点角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NodeRole {
+    Leader,
+    /// 只检测、影子执行
+    Follower,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Lease {
+    token: u64,
+    /// 本地认定的有效期（已扣除安全余量）
+    valid_until: Instant,
+}
+
+/// 领导者选举
+pub struct LeaderElector {
+    node_id: String,
+    config: LeaderConfig,
+    backend: Arc<dyn LeaseBackend>,
+    lease: RwLock<Option<Lease>>,
+}
+
+impl LeaderElector {
+    pub fn new(node_id: &str, config: LeaderConfig, backend: Arc<dyn LeaseBackend>) -> Self {
+        Self {
+            node_id: node_id.to_string(),
+            config,
+            backend,
+            lease: RwLock::new(None),
+        }
+    }
+
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    fn ttl(&self) -> Duration {
+        Duration::from_millis(self.config.lease_ttl_ms)
+    }
+
+    fn valid_until(&self, acquired_at: Instant) -> Instant {
+        acquired_at + Duration::from_millis(self.config.lease_ttl_ms.saturating_sub(self.config.safety_margin_ms))
+    }
+
+    /// 当前有效的 fencing token；不是领导者或本地租约已到期时为 None
+    pub fn fencing_token(&self) -> Option<u64> {
+        (*self.lease.read())
+            .filter(|l| l.valid_until > Instant::now())
+            .map(|l| l.token)
+    }
+
+    pub fn role(&self) -> NodeRole {
+        if self.fencing_token().is_some() { NodeRole::Leader } else { NodeRole::Follower }
+    }
+
+    /// 下单前校验：token 必须是本节点当前持有的有效 token
+    pub fn validate(&self, token: u64) -> bool {
+        self.fencing_token() == Some(token)
+    }
+
+    /// 续约或竞选一次
+    pub async fn tick(&self) -> NodeRole {
+        let started = Instant::now();
+        let current = *self.lease.read();
+        let result = match current {
+            Some(lease) => self.backend.renew(&self.node_id, lease.token, self.ttl()).await,
+            None => self.backend.try_acquire(&self.node_id, self.ttl()).await,
+        };
+        match (current, result) {
+            (_, Ok(Some(token))) => {
+                if current.is_none() {
+                    info!("👑 {} 成为领导者 (token {})", self.node_id, token);
+                }
+                *self.lease.write() = Some(Lease { token, valid_until: self.valid_until(started) });
+            }
+            (Some(lease), Ok(None)) => {
+                warn!("⚠️ {} 失去领导权 (token {})，切换为影子模式", self.node_id, lease.token);
+                *self.lease.write() = None;
+            }
+            (None, Ok(None)) => {}
+            (_, Err(e)) => {
+                // 续约失败时不立刻放弃：本地租约到期后 fencing_token 自动失效
+                error!("❌ {} 租约操作失败: {}", self.node_id, e);
+            }
+        }
+        self.role()
+    }
+
+    /// 主动让出领导权（停机时调用）
+    pub async fn resign(&self) -> Result<()> {
+        let lease = self.lease.write().take();
+        if let Some(lease) = lease {
+            self.backend.release(&self.node_id, lease.token).await?;
+            info!("👋 {} 已让出领导权", self.node_id);
+        }
+        Ok(())
+    }
+
+    /// 周期性续约/竞选
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_millis(self.config.renew_interval_ms.max(1)));
+            loop {
+                ticker.tick().await;
+                self.tick().await;
+            }
+        })
+    }
+}
+
+/// 执行适配器在每笔下单前回调，失去领导权后旧 token 立即失效
+impl adapters::execution::FencingValidator for LeaderElector {
+    fn validate(&self, token: u64) -> bool {
+        LeaderElector::validate(self, token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_failover_and_fencing() {
+        let backend: Arc<dyn LeaseBackend> = Arc::new(MemoryLease::new());
+        let config = LeaderConfig { lease_ttl_ms: 60, renew_interval_ms: 20, safety_margin_ms: 10 };
+        let a = LeaderElector::new("a", config.clone(), backend.clone());
+        let b = LeaderElector::new("b", config, backend);
+
+        assert_eq!(a.tick().await, NodeRole::Leader);
+        assert_eq!(b.tick().await, NodeRole::Follower);
+        let old_token = a.fencing_token().unwrap();
+
+        // a 停止续约（例如网络分区），租约过期后 b 接管
+        tokio::time::sleep(Duration::from_millis(70)).await;
+        assert!(!a.validate(old_token));
+        assert_eq!(a.role(), NodeRole::Follower);
+        assert_eq!(b.tick().await, NodeRole::Leader);
+        assert!(b.fencing_token().unwrap() > old_token);
+
+        // a 恢复后续约失败，保持跟随者
+        assert_eq!(a.tick().await, NodeRole::Follower);
+        assert_eq!(b.tick().await, NodeRole::Leader);
+    }
+
+    #[tokio::test]
+    async fn test_lease_expiring_mid_execution_stops_orders() {
+        use adapters::execution::{ExecutionAdapter, ExecutionConfig, FENCING_TOKEN_TAG};
+        use adapters::slicing::{SliceMode, SlicingConfig};
+        use adapters::trading_mode::{TradingMode, TradingModeController};
+        use adapters::Adapter;
+
+        let config = LeaderConfig { lease_ttl_ms: 60, renew_interval_ms: 20, safety_margin_ms: 10 };
+        let leader = Arc::new(LeaderElector::new("a", config, Arc::new(MemoryLease::new())));
+        assert_eq!(leader.tick().await, NodeRole::Leader);
+
+        let mut adapter = ExecutionAdapter::new()
+            .with_trading_mode(Arc::new(TradingModeController::new(TradingMode::Live)))
+            .with_fencing(leader.clone());
+        let slicing = SlicingConfig { enabled: true, mode: SliceMode::Twap { slices: 4, interval_ms: 30 }, ..SlicingConfig::default() };
+        adapter.initialize(ExecutionConfig { slicing, ..ExecutionConfig::default() }).await.unwrap();
+
+        let mut opportunity = common::testing::inter_exchange_opportunity("inter_exchange", "BTCUSDT", 0.01);
+        opportunity.tags.insert(FENCING_TOKEN_TAG.to_string(), leader.fencing_token().unwrap().to_string());
+
+        // 首条腿拆成 4 笔、间隔 30ms；不再续约，本地租约在 50ms 到期，之后的子单不会发出
+        assert!(adapter.execute_with_depth(&opportunity, &[Some(1.0), Some(1.0)]).await.is_err());
+        assert_eq!(leader.role(), NodeRole::Follower);
+    }
+}
@@ -5,6 +5,32 @@ pub mod nats;
 pub mod processor;
 pub mod engine;
 pub mod risk;
+pub mod crash_dump;
+pub mod sensitivity;
+pub mod whatif;
+pub mod inference;
+pub mod lifecycle;
+pub mod event_calendar;
+pub mod auth;
+pub mod rate_limit;
+pub mod audit;
+pub mod alerts;
+pub mod tuning;
+pub mod monitoring;
+#[cfg(feature = "parquet-export")]
+pub mod export;
+pub mod dead_man;
+pub mod trading_controls;
+pub mod approvals;
+pub mod event_bus;
+pub mod shutdown;
+pub mod snapshot;
+pub mod leader;
+pub mod tenants;
+pub mod journal;
+pub mod importer;
+#[cfg(feature = "chaos")]
+pub mod chaos;
 
 pub use config::*;
 pub use error::*;
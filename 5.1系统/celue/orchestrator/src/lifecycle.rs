@@ -0,0 +1,356 @@
+//! 模块依赖图与有序启停
+//!
+//! 将系统模块及其依赖建模为有向无环图，按拓扑序启动、逆序停止，
+//! 每个模块有独立的超时和重试策略。重复注册同名模块会被拒绝，避免
+//! 同一模块被启动两次。依赖图和当前状态可导出为 JSON / Graphviz DOT。
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::nats::NatsManager;
+
+/// 可被编排的系统模块
+#[async_trait]
+pub trait ManagedModule: Send + Sync {
+    /// 模块唯一名称
+    fn name(&self) -> &str;
+    /// 依赖的模块名称
+    fn dependencies(&self) -> Vec<String> {
+        Vec::new()
+    }
+    async fn start(&self) -> Result<()>;
+    async fn stop(&self) -> Result<()>;
+}
+
+/// 单个模块的启停策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModulePolicy {
+    pub start_timeout_ms: u64,
+    pub stop_timeout_ms: u64,
+    pub max_retries: u32,
+    pub retry_backoff_ms: u64,
+}
+
+impl Default for ModulePolicy {
+    fn default() -> Self {
+        Self {
+            start_timeout_ms: std::env::var("CELUE_MODULE_START_TIMEOUT_MS")
+                .ok().and_then(|s| s.parse().ok())
+                .unwrap_or(10_000),
+            stop_timeout_ms: std::env::var("CELUE_MODULE_STOP_TIMEOUT_MS")
+                .ok().and_then(|s| s.parse().ok())
+                .unwrap_or(5_000),
+            max_retries: std::env::var("CELUE_MODULE_MAX_RETRIES")
+                .ok().and_then(|s| s.parse().ok())
+                .unwrap_or(3),
+            retry_backoff_ms: 500,
+        }
+    }
+}
+
+/// 模块运行状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ModuleState {
+    Registered,
+    Starting,
+    Running,
+    Stopping,
+    Stopped,
+    Failed(String),
+}
+
+/// 依赖图中的节点（用于可视化）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleNode {
+    pub name: String,
+    pub dependencies: Vec<String>,
+    pub state: ModuleState,
+}
+
+/// 单个模块的停止结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleStopReport {
+    pub name: String,
+    pub elapsed_ms: u64,
+    pub state: ModuleState,
+}
+
+struct Entry {
+    module: Arc<dyn ManagedModule>,
+    policy: ModulePolicy,
+}
+
+/// 模块依赖图
+#[derive(Default)]
+pub struct ModuleGraph {
+    modules: RwLock<BTreeMap<String, Entry>>,
+    states: RwLock<HashMap<String, ModuleState>>,
+}
+
+impl ModuleGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册模块（同名模块只能注册一次）
+    pub fn register(&self, module: Arc<dyn ManagedModule>, policy: ModulePolicy) -> Result<()> {
+        let name = module.name().to_string();
+        let mut modules = self.modules.write();
+        if modules.contains_key(&name) {
+            return Err(anyhow!("模块重复注册: {}", name));
+        }
+        modules.insert(name.clone(), Entry { module, policy });
+        self.states.write().insert(name, ModuleState::Registered);
+        Ok(())
+    }
+
+    /// 拓扑排序（Kahn 算法），检测缺失依赖和环
+    pub fn start_order(&self) -> Result<Vec<String>> {
+        let modules = self.modules.read();
+        let mut in_degree: BTreeMap<&str, usize> = modules.keys().map(|k| (k.as_str(), 0)).collect();
+        let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+
+        for (name, entry) in modules.iter() {
+            for dep in entry.module.dependencies() {
+                let Some((dep_name, _)) = modules.get_key_value(&dep) else {
+                    return Err(anyhow!("模块 {} 依赖未注册的模块 {}", name, dep));
+                };
+                *in_degree.get_mut(name.as_str()).expect("registered") += 1;
+                dependents.entry(dep_name.as_str()).or_default().push(name.as_str());
+            }
+        }
+
+        let mut ready: VecDeque<&str> = in_degree.iter().filter(|(_, d)| **d == 0).map(|(n, _)| *n).collect();
+        let mut order = Vec::with_capacity(modules.len());
+        while let Some(name) = ready.pop_front() {
+            order.push(name.to_string());
+            for &dependent in dependents.get(name).into_iter().flatten() {
+                let degree = in_degree.get_mut(&dependent).expect("registered");
+                *degree -= 1;
+                if *degree == 0 {
+                    ready.push_back(dependent);
+                }
+            }
+        }
+
+        if order.len() != modules.len() {
+            let cyclic: Vec<&str> = in_degree.iter().filter(|(_, d)| **d > 0).map(|(n, _)| *n).collect();
+            return Err(anyhow!("模块依赖存在环: {:?}", cyclic));
+        }
+        Ok(order)
+    }
+
+    fn set_state(&self, name: &str, state: ModuleState) {
+        self.states.write().insert(name.to_string(), state);
+    }
+
+    fn entry(&self, name: &str) -> Option<(Arc<dyn ManagedModule>, ModulePolicy)> {
+        self.modules
+            .read()
+            .get(name)
+            .map(|e| (e.module.clone(), e.policy.clone()))
+    }
+
+    /// 按拓扑序启动所有模块；任一模块最终失败时逆序停止已启动模块
+    pub async fn start_all(&self) -> Result<()> {
+        let order = self.start_order()?;
+        info!("🚀 模块启动顺序: {}", order.join(" → "));
+
+        let mut started: Vec<String> = Vec::new();
+        for name in &order {
+            if let Err(e) = self.start_module(name).await {
+                error!("❌ 模块 {} 启动失败，回滚已启动模块: {}", name, e);
+                for done in started.iter().rev() {
+                    self.stop_module(done).await;
+                }
+                return Err(e);
+            }
+            started.push(name.clone());
+        }
+        Ok(())
+    }
+
+    async fn start_module(&self, name: &str) -> Result<()> {
+        let (module, policy) = self.entry(name).ok_or_else(|| anyhow!("未知模块: {}", name))?;
+        let timeout = Duration::from_millis(policy.start_timeout_ms);
+
+        let mut attempt = 0;
+        loop {
+            self.set_state(name, ModuleState::Starting);
+            let error = match tokio::time::timeout(timeout, module.start()).await {
+                Ok(Ok(())) => {
+                    self.set_state(name, ModuleState::Running);
+                    info!("✅ 模块已启动: {}", name);
+                    return Ok(());
+                }
+                Ok(Err(e)) => e.to_string(),
+                Err(_) => format!("启动超时 {}ms", policy.start_timeout_ms),
+            };
+
+            attempt += 1;
+            if attempt > policy.max_retries {
+                self.set_state(name, ModuleState::Failed(error.clone()));
+                return Err(anyhow!("模块 {} 启动失败: {}", name, error));
+            }
+            warn!("⚠️ 模块 {} 启动失败（第{}次重试）: {}", name, attempt, error);
+            tokio::time::sleep(Duration::from_millis(policy.retry_backoff_ms * attempt as u64)).await;
+        }
+    }
+
+    async fn stop_module(&self, name: &str) -> ModuleStopReport {
+        let started = std::time::Instant::now();
+        let Some((module, policy)) = self.entry(name) else {
+            return ModuleStopReport { name: name.to_string(), elapsed_ms: 0, state: ModuleState::Stopped };
+        };
+        self.set_state(name, ModuleState::Stopping);
+        let state = match tokio::time::timeout(Duration::from_millis(policy.stop_timeout_ms), module.stop()).await {
+            Ok(Ok(())) => {
+                info!("🛑 模块已停止: {}", name);
+                ModuleState::Stopped
+            }
+            Ok(Err(e)) => {
+                warn!("⚠️ 模块 {} 停止失败: {}", name, e);
+                ModuleState::Failed(e.to_string())
+            }
+            Err(_) => {
+                warn!("⚠️ 模块 {} 停止超时", name);
+                ModuleState::Failed("停止超时".to_string())
+            }
+        };
+        self.set_state(name, state.clone());
+        ModuleStopReport { name: name.to_string(), elapsed_ms: started.elapsed().as_millis() as u64, state }
+    }
+
+    /// 按拓扑逆序停止所有运行中的模块
+    pub async fn stop_all(&self) -> Result<()> {
+        self.stop_all_with_report().await.map(|_| ())
+    }
+
+    /// 按拓扑逆序停止所有运行中的模块，返回每个模块的停止耗时与结果
+    pub async fn stop_all_with_report(&self) -> Result<Vec<ModuleStopReport>> {
+        let order = self.start_order()?;
+        let mut reports = Vec::new();
+        for name in order.iter().rev() {
+            let running = matches!(
+                self.states.read().get(name),
+                Some(ModuleState::Running | ModuleState::Starting)
+            );
+            if running {
+                reports.push(self.stop_module(name).await);
+            }
+        }
+        Ok(reports)
+    }
+
+    /// 依赖图与当前状态
+    pub fn nodes(&self) -> Vec<ModuleNode> {
+        let states = self.states.read();
+        self.modules
+            .read()
+            .iter()
+            .map(|(name, entry)| ModuleNode {
+                name: name.clone(),
+                dependencies: entry.module.dependencies(),
+                state: states.get(name).cloned().unwrap_or(ModuleState::Registered),
+            })
+            .collect()
+    }
+
+    /// 导出 Graphviz DOT
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph modules {\n");
+        for node in self.nodes() {
+            let color = match node.state {
+                ModuleState::Running => "green",
+                ModuleState::Failed(_) => "red",
+                ModuleState::Starting | ModuleState::Stopping => "orange",
+                _ => "gray",
+            };
+            out.push_str(&format!("  \"{}\" [color={}];\n", node.name, color));
+            for dep in node.dependencies {
+                out.push_str(&format!("  \"{}\" -> \"{}\";\n", dep, node.name));
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// 在 NATS 请求/响应主题上提供依赖图查询（payload 为 "dot" 时返回 DOT）
+    pub async fn serve(self: Arc<Self>, nats: &NatsManager, subject: &str) -> Result<()> {
+        let mut subscriber = nats.subscribe(subject).await?;
+        info!("🗺️ 模块依赖图服务已启动: {}", subject);
+
+        while let Some(message) = subscriber.next().await {
+            let Some(reply) = message.reply.clone() else { continue };
+            let body = if message.payload.as_ref() == b"dot" {
+                self.to_dot().into_bytes()
+            } else {
+                serde_json::to_vec(&self.nodes())?
+            };
+            if let Err(e) = nats.get_client().publish(reply, body.into()).await {
+                warn!("依赖图响应发送失败: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestModule {
+        name: &'static str,
+        deps: Vec<String>,
+    }
+
+    #[async_trait]
+    impl ManagedModule for TestModule {
+        fn name(&self) -> &str {
+            self.name
+        }
+        fn dependencies(&self) -> Vec<String> {
+            self.deps.clone()
+        }
+        async fn start(&self) -> Result<()> {
+            Ok(())
+        }
+        async fn stop(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn module(name: &'static str, deps: &[&str]) -> Arc<dyn ManagedModule> {
+        Arc::new(TestModule {
+            name,
+            deps: deps.iter().map(|d| d.to_string()).collect(),
+        })
+    }
+
+    #[test]
+    fn test_topological_order_and_duplicates() {
+        let graph = ModuleGraph::new();
+        graph.register(module("engine", &["nats", "risk"]), ModulePolicy::default()).unwrap();
+        graph.register(module("risk", &["nats"]), ModulePolicy::default()).unwrap();
+        graph.register(module("nats", &[]), ModulePolicy::default()).unwrap();
+        assert!(graph.register(module("risk", &[]), ModulePolicy::default()).is_err());
+
+        assert_eq!(graph.start_order().unwrap(), vec!["nats", "risk", "engine"]);
+    }
+
+    #[test]
+    fn test_cycle_detected() {
+        let graph = ModuleGraph::new();
+        graph.register(module("a", &["b"]), ModulePolicy::default()).unwrap();
+        graph.register(module("b", &["a"]), ModulePolicy::default()).unwrap();
+        assert!(graph.start_order().is_err());
+    }
+}
@@ -0,0 +1,170 @@
+//! 监控查询服务
+//!
+//! 为看板提供只读监控数据：
+//! - 延迟热力图：按交易所和操作类型（订单簿更新、REST 下单、撤单）
+//!   返回分桶延迟计数，时间窗口和时间槽宽度由请求指定；
+//! - 日终结算：按日汇总净收益，查询单日结算单并导出 CSV/JSON；
+//! - 执行预览：人工批准前，按机会 id 在当前订单簿上模拟执行，返回
+//!   预期成交、滑点和净收益，不触达任何交易所。
+//!
+//! 需要 `ViewDashboard` 权限。
+
+use std::sync::Arc;
+
+use adapters::attribution::ReportFormat;
+use adapters::execution_preview::{ExecutionPreview, ExecutionPreviewer};
+use adapters::latency_heatmap::{HeatmapQuery, LatencyHeatmap, LatencyRecorder};
+use adapters::settlement::{DailyStatement, SettlementJob, SettlementSummary};
+use anyhow::Result;
+use chrono::NaiveDate;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::auth::{AuthService, ControlAction};
+use crate::nats::{encode_reply, NatsManager};
+use common::{ApiResponse, StorageError, SystemError, SystemResult};
+
+fn internal(error: adapters::AdapterError) -> SystemError {
+    SystemError::Internal(error.to_string())
+}
+
+/// 查询请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum MonitoringCommand {
+    LatencyHeatmap {
+        #[serde(default)]
+        query: HeatmapQuery,
+    },
+    SettlementSummary {
+        #[serde(default = "default_summary_days")]
+        days: usize,
+    },
+    Statement { date: NaiveDate },
+    ExportStatement { date: NaiveDate, format: ReportFormat },
+    PreviewExecution { opportunity_id: Uuid },
+}
+
+fn default_summary_days() -> usize {
+    30
+}
+
+/// 查询响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MonitoringReply {
+    LatencyHeatmap { heatmap: LatencyHeatmap },
+    SettlementSummary { summary: SettlementSummary },
+    Statement { statement: DailyStatement },
+    Export { date: NaiveDate, format: ReportFormat, content: String },
+    ExecutionPreview { preview: ExecutionPreview },
+}
+
+/// 监控查询服务
+pub struct MonitoringService {
+    latency: Arc<LatencyRecorder>,
+    settlement: Option<Arc<SettlementJob>>,
+    previewer: Option<Arc<ExecutionPreviewer>>,
+    auth: Option<Arc<AuthService>>,
+}
+
+impl MonitoringService {
+    pub fn new(latency: Arc<LatencyRecorder>) -> Self {
+        Self { latency, settlement: None, previewer: None, auth: None }
+    }
+
+    /// 提供日终结算查询与导出
+    pub fn with_settlement(mut self, settlement: Arc<SettlementJob>) -> Self {
+        self.settlement = Some(settlement);
+        self
+    }
+
+    fn settlement(&self) -> SystemResult<&SettlementJob> {
+        self.settlement
+            .as_deref()
+            .ok_or_else(|| SystemError::Unavailable("未启用日终结算".to_string()))
+    }
+
+    /// 提供执行预览
+    pub fn with_previewer(mut self, previewer: Arc<ExecutionPreviewer>) -> Self {
+        self.previewer = Some(previewer);
+        self
+    }
+
+    pub fn with_auth(mut self, auth: Arc<AuthService>) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    fn handle(&self, command: &MonitoringCommand) -> SystemResult<MonitoringReply> {
+        Ok(match command {
+            MonitoringCommand::LatencyHeatmap { query } => {
+                if query.window_secs == 0 {
+                    return Err(SystemError::InvalidRequest("window_secs 必须大于 0".to_string()));
+                }
+                MonitoringReply::LatencyHeatmap { heatmap: self.latency.heatmap(query) }
+            }
+            MonitoringCommand::SettlementSummary { days } => MonitoringReply::SettlementSummary {
+                summary: self.settlement()?.summary(*days).map_err(internal)?,
+            },
+            MonitoringCommand::Statement { date } => {
+                let statement = self
+                    .settlement()?
+                    .statement(*date)
+                    .map_err(internal)?
+                    .ok_or_else(|| StorageError::NotFound(format!("statement {}", date)))?;
+                MonitoringReply::Statement { statement }
+            }
+            MonitoringCommand::ExportStatement { date, format } => {
+                let settlement = self.settlement()?;
+                if settlement.statement(*date).map_err(internal)?.is_none() {
+                    return Err(StorageError::NotFound(format!("statement {}", date)).into());
+                }
+                let content = settlement
+                    .export(*date, *format)
+                    .map_err(|e| SystemError::InvalidRequest(e.to_string()))?;
+                MonitoringReply::Export { date: *date, format: *format, content }
+            }
+            MonitoringCommand::PreviewExecution { opportunity_id } => {
+                let previewer = self
+                    .previewer
+                    .as_deref()
+                    .ok_or_else(|| SystemError::Unavailable("未启用执行预览".to_string()))?;
+                let preview = previewer
+                    .preview_id(opportunity_id)
+                    .map_err(|e| SystemError::InvalidRequest(e.to_string()))?
+                    .ok_or_else(|| StorageError::NotFound(format!("opportunity {}", opportunity_id)))?;
+                MonitoringReply::ExecutionPreview { preview }
+            }
+        })
+    }
+
+    /// 查询服务：请求体为 `MonitoringCommand` JSON，响应为带错误码的 `ApiResponse`
+    pub async fn serve(self: Arc<Self>, nats: &NatsManager, subject: &str) -> Result<()> {
+        let mut subscriber = nats.subscribe(subject).await?;
+        info!("📊 监控查询服务已启动: {}", subject);
+
+        while let Some(message) = subscriber.next().await {
+            let Some(reply) = message.reply.clone() else { continue };
+            let result = (|| {
+                let command = serde_json::from_slice::<MonitoringCommand>(&message.payload)
+                    .map_err(|e| SystemError::InvalidRequest(e.to_string()))?;
+                if let Some(auth) = &self.auth {
+                    auth.authorize_nats(&message, ControlAction::ViewDashboard)?;
+                }
+                self.handle(&command)
+            })();
+            let response: ApiResponse<MonitoringReply> = result.into();
+            if let Err(e) = nats
+                .get_client()
+                .publish(reply, encode_reply(&message, &response)?.into())
+                .await
+            {
+                warn!("监控查询响应发送失败: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
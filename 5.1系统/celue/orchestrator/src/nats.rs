@@ -1,13 +1,39 @@
+use adapters::subject_metrics::SubjectMetrics;
 use async_nats::{Client, Message, Subscriber};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
 use parking_lot::RwLock;
 use anyhow::Result;
+use common::number_profile::{DecimalPolicy, NumberProfile};
+use common::ApiResponse;
 use serde::{Serialize, Deserialize};
 use tokio::sync::mpsc;
 
+static DECIMAL_POLICY: OnceLock<DecimalPolicy> = OnceLock::new();
+
+/// 设置十进制字符串格式的字段与各交易所精度（启动时调用一次）
+pub fn set_decimal_policy(policy: DecimalPolicy) -> bool {
+    DECIMAL_POLICY.set(policy).is_ok()
+}
+
+/// 按请求头协商数字格式：`Accept` 媒体类型，或等价于 `numbers` 查询参数的
+/// `X-Numbers` 头；未协商时保持原有 JSON 数字格式
+pub fn negotiated_profile(message: &Message) -> NumberProfile {
+    let header = |name: &str| message.headers.as_ref().and_then(|h| h.get(name)).map(|v| v.as_str().to_string());
+    let query = header("X-Numbers").map(|v| format!("numbers={}", v));
+    NumberProfile::negotiate(header("Accept").as_deref(), query.as_deref())
+}
+
+/// 按请求协商的格式序列化响应
+pub fn encode_reply<T: Serialize>(message: &Message, response: &ApiResponse<T>) -> serde_json::Result<Vec<u8>> {
+    let policy = DECIMAL_POLICY.get_or_init(DecimalPolicy::default);
+    response.to_json_profiled(negotiated_profile(message), policy)
+}
+
 pub struct NatsManager {
     client: Client,
     subscribers: Arc<RwLock<Vec<Subscriber>>>,
+    subject_metrics: Arc<SubjectMetrics>,
 }
 
 impl NatsManager {
@@ -16,12 +42,26 @@ impl NatsManager {
         Ok(Self {
             client,
             subscribers: Arc::new(RwLock::new(Vec::new())),
+            subject_metrics: Arc::new(SubjectMetrics::default()),
         })
     }
+
+    /// 与其他组件共享主题级指标
+    pub fn with_subject_metrics(mut self, metrics: Arc<SubjectMetrics>) -> Self {
+        self.subject_metrics = metrics;
+        self
+    }
+
+    pub fn subject_metrics(&self) -> Arc<SubjectMetrics> {
+        self.subject_metrics.clone()
+    }
     
     pub async fn publish<T: Serialize>(&self, subject: &str, message: &T) -> Result<()> {
         let payload = serde_json::to_vec(message)?;
+        let payload_bytes = payload.len();
+        let started = Instant::now();
         self.client.publish(subject.to_string(), payload.into()).await?;
+        self.subject_metrics.record_publish(subject, payload_bytes, started.elapsed());
         Ok(())
     }
     
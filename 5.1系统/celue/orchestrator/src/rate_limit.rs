@@ -0,0 +1,369 @@
+//! 管理接口限流 - 按调用方的令牌桶配额
+//!
+//! 前端轮询失控时会把管理接口压垮，连带影响启停、撤单等关键操作。每个
+//! 调用方（API Key 或 JWT subject，未认证请求共用 anonymous）
+//! 分别持有读、写两个令牌桶：看板查询走读配额，变更操作走写配额，
+//! 大量查询不会耗尽写配额。超出配额返回 429 与 `Retry-After`，
+//! 正常响应在头部附带剩余配额，并导出到指标。
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use common::SystemError;
+use metrics::{counter, gauge};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::auth::{AuthService, ControlAction, Credentials, Principal};
+
+/// 未认证请求共用的调用方标识
+pub const ANONYMOUS_CLIENT: &str = "anonymous";
+
+/// 接口类别，读写分开计配额
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EndpointClass {
+    Read,
+    Write,
+}
+
+impl EndpointClass {
+    pub fn for_action(action: ControlAction) -> Self {
+        match action {
+            ControlAction::ViewDashboard => EndpointClass::Read,
+            _ => EndpointClass::Write,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            EndpointClass::Read => "read",
+            EndpointClass::Write => "write",
+        }
+    }
+}
+
+/// 单个令牌桶配额
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Quota {
+    /// 每分钟补充的请求数
+    pub per_minute: u32,
+    /// 桶容量（允许的突发请求数）
+    pub burst: u32,
+}
+
+/// 调用方的读写配额
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ClientQuota {
+    pub read: Quota,
+    pub write: Quota,
+}
+
+impl ClientQuota {
+    fn for_class(&self, class: EndpointClass) -> Quota {
+        match class {
+            EndpointClass::Read => self.read,
+            EndpointClass::Write => self.write,
+        }
+    }
+}
+
+impl Default for ClientQuota {
+    fn default() -> Self {
+        Self {
+            read: Quota { per_minute: 600, burst: 60 },
+            write: Quota { per_minute: 60, burst: 10 },
+        }
+    }
+}
+
+/// 限流配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    /// 已认证调用方的默认配额
+    pub default_quota: ClientQuota,
+    /// 未认证请求的配额
+    pub anonymous_quota: ClientQuota,
+    /// 按调用方覆盖：键为 API Key 名称或 JWT subject
+    #[serde(default)]
+    pub overrides: HashMap<String, ClientQuota>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            default_quota: ClientQuota::default(),
+            anonymous_quota: ClientQuota {
+                read: Quota { per_minute: 60, burst: 10 },
+                write: Quota { per_minute: 10, burst: 2 },
+            },
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// 默认配额可用 CELUE_RATE_LIMIT_{READ,WRITE}_PER_MIN / _BURST 覆盖
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        let var = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<u32>().ok());
+        if let Some(v) = var("CELUE_RATE_LIMIT_READ_PER_MIN") {
+            config.default_quota.read.per_minute = v;
+        }
+        if let Some(v) = var("CELUE_RATE_LIMIT_READ_BURST") {
+            config.default_quota.read.burst = v;
+        }
+        if let Some(v) = var("CELUE_RATE_LIMIT_WRITE_PER_MIN") {
+            config.default_quota.write.per_minute = v;
+        }
+        if let Some(v) = var("CELUE_RATE_LIMIT_WRITE_BURST") {
+            config.default_quota.write.burst = v;
+        }
+        config
+    }
+
+    fn quota_for(&self, client: &str) -> ClientQuota {
+        if client == ANONYMOUS_CLIENT {
+            return self.anonymous_quota;
+        }
+        let name = client.strip_prefix("api_key:").unwrap_or(client);
+        self.overrides.get(name).copied().unwrap_or(self.default_quota)
+    }
+}
+
+struct TokenBucket {
+    quota: Quota,
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(quota: Quota, now: Instant) -> Self {
+        Self { quota, tokens: quota.burst as f64, updated_at: now }
+    }
+
+    fn refill_per_sec(&self) -> f64 {
+        self.quota.per_minute as f64 / 60.0
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec()).min(self.quota.burst as f64);
+        self.updated_at = now;
+    }
+
+    /// 距离桶内恢复 `needed` 个令牌的秒数
+    fn secs_until(&self, needed: f64) -> u64 {
+        let rate = self.refill_per_sec();
+        if self.tokens >= needed {
+            0
+        } else if rate <= 0.0 {
+            u64::MAX
+        } else {
+            ((needed - self.tokens) / rate).ceil() as u64
+        }
+    }
+}
+
+/// 本次请求后的配额使用情况
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuotaUsage {
+    pub client: String,
+    pub class: EndpointClass,
+    pub limit: u32,
+    pub remaining: u32,
+    /// 桶补满所需秒数
+    pub reset_secs: u64,
+}
+
+impl QuotaUsage {
+    /// 响应头：X-RateLimit-Limit / Remaining / Reset
+    pub fn headers(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("X-RateLimit-Limit", self.limit.to_string()),
+            ("X-RateLimit-Remaining", self.remaining.to_string()),
+            ("X-RateLimit-Reset", self.reset_secs.to_string()),
+        ]
+    }
+
+    pub fn nats_headers(&self) -> async_nats::HeaderMap {
+        let mut headers = async_nats::HeaderMap::new();
+        for (name, value) in self.headers() {
+            headers.insert(name, value.as_str());
+        }
+        headers
+    }
+}
+
+/// 超出配额
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimited {
+    pub usage: QuotaUsage,
+    pub retry_after_secs: u64,
+}
+
+impl RateLimited {
+    pub const STATUS: u16 = 429;
+
+    /// 配额头加 `Retry-After`
+    pub fn headers(&self) -> Vec<(&'static str, String)> {
+        let mut headers = self.usage.headers();
+        headers.push(("Retry-After", self.retry_after_secs.to_string()));
+        headers
+    }
+
+}
+
+impl From<RateLimited> for SystemError {
+    fn from(limited: RateLimited) -> Self {
+        SystemError::RateLimited { retry_after_secs: limited.retry_after_secs }
+    }
+}
+
+/// 被限流时在 NATS 回复头补充 `Retry-After` 与 429 状态
+pub fn apply_rejection_headers(headers: &mut async_nats::HeaderMap, error: &SystemError) {
+    if let SystemError::RateLimited { retry_after_secs } = error {
+        headers.insert("Retry-After", retry_after_secs.to_string().as_str());
+        headers.insert("Status", RateLimited::STATUS.to_string().as_str());
+    }
+}
+
+/// 按调用方和接口类别的令牌桶限流器
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Mutex<HashMap<(String, EndpointClass), TokenBucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self { config, buckets: Mutex::new(HashMap::new()) }
+    }
+
+    /// 消耗一个令牌
+    pub fn check(&self, client: &str, class: EndpointClass) -> Result<QuotaUsage, RateLimited> {
+        self.check_at(client, class, Instant::now())
+    }
+
+    pub fn check_at(&self, client: &str, class: EndpointClass, now: Instant) -> Result<QuotaUsage, RateLimited> {
+        let quota = self.config.quota_for(client).for_class(class);
+        let mut buckets = self.buckets.lock();
+        let bucket = buckets
+            .entry((client.to_string(), class))
+            .or_insert_with(|| TokenBucket::new(quota, now));
+        bucket.refill(now);
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+        let usage = QuotaUsage {
+            client: client.to_string(),
+            class,
+            limit: quota.burst,
+            remaining: bucket.tokens.floor().max(0.0) as u32,
+            reset_secs: bucket.secs_until(quota.burst as f64),
+        };
+        let retry_after_secs = bucket.secs_until(1.0).max(1);
+        drop(buckets);
+
+        counter!("gateway_requests_total", 1, "client" => usage.client.clone(), "class" => class.as_str());
+        gauge!("gateway_quota_remaining", usage.remaining as f64, "client" => usage.client.clone(), "class" => class.as_str());
+        if allowed {
+            Ok(usage)
+        } else {
+            counter!("gateway_rate_limited_total", 1, "client" => usage.client.clone(), "class" => class.as_str());
+            warn!("🚦 {} 超出{}配额，{}s 后重试", usage.client, class.as_str(), retry_after_secs);
+            Err(RateLimited { usage, retry_after_secs })
+        }
+    }
+
+    /// 鉴权与限流：已认证调用方按身份计配额，凭证缺失或无效的请求计入 anonymous
+    ///
+    /// 限流在权限检查前进行，被拒绝的请求同样消耗配额。
+    pub fn guard(
+        &self,
+        auth: &AuthService,
+        credentials: Option<&Credentials>,
+        action: ControlAction,
+    ) -> Result<(Principal, QuotaUsage), SystemError> {
+        let class = EndpointClass::for_action(action);
+        let authenticated = credentials.and_then(|c| auth.authenticate(c).ok());
+        let client = authenticated.as_ref().map_or(ANONYMOUS_CLIENT, |p| p.subject.as_str());
+        let usage = self.check(client, class)?;
+        let principal = auth.authorize(credentials, action)?;
+        Ok((principal, usage))
+    }
+
+    /// 从 NATS 请求头取凭证后调用 `guard`
+    pub fn guard_nats(
+        &self,
+        auth: &AuthService,
+        message: &async_nats::Message,
+        action: ControlAction,
+    ) -> Result<(Principal, QuotaUsage), SystemError> {
+        let header = |name: &str| {
+            message
+                .headers
+                .as_ref()
+                .and_then(|h| h.get(name))
+                .map(|v| v.as_str().to_string())
+        };
+        let authorization = header("Authorization");
+        let api_key = header("X-Api-Key");
+        let credentials = Credentials::from_headers(authorization.as_deref(), api_key.as_deref());
+        self.guard(auth, credentials.as_ref(), action)
+    }
+
+    /// 清理长时间未使用且已补满的令牌桶
+    pub fn prune(&self, idle: Duration) {
+        let now = Instant::now();
+        self.buckets.lock().retain(|_, bucket| {
+            let idle_for = now.saturating_duration_since(bucket.updated_at);
+            bucket.refill(now);
+            idle_for < idle || bucket.tokens < bucket.quota.burst as f64
+        });
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(RateLimitConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_write_buckets_and_retry_after() {
+        let mut config = RateLimitConfig::default();
+        config.overrides.insert(
+            "dashboard".to_string(),
+            ClientQuota {
+                read: Quota { per_minute: 60, burst: 2 },
+                write: Quota { per_minute: 6, burst: 1 },
+            },
+        );
+        let limiter = RateLimiter::new(config);
+        let client = "api_key:dashboard";
+        let start = Instant::now();
+
+        assert_eq!(limiter.check_at(client, EndpointClass::Read, start).unwrap().remaining, 1);
+        assert!(limiter.check_at(client, EndpointClass::Read, start).is_ok());
+        let limited = limiter.check_at(client, EndpointClass::Read, start).unwrap_err();
+        assert_eq!(limited.retry_after_secs, 1);
+        assert!(limited.headers().contains(&("Retry-After", "1".to_string())));
+        assert_eq!(SystemError::from(limited).code(), "RATE_LIMITED");
+
+        // 读配额耗尽不影响写配额
+        let usage = limiter.check_at(client, EndpointClass::Write, start).unwrap();
+        assert_eq!(usage.remaining, 0);
+        assert_eq!(usage.reset_secs, 10);
+        assert_eq!(limiter.check_at(client, EndpointClass::Write, start).unwrap_err().retry_after_secs, 10);
+
+        // 补充一秒后读请求恢复
+        assert!(limiter.check_at(client, EndpointClass::Read, start + Duration::from_secs(1)).is_ok());
+    }
+}
@@ -348,6 +348,11 @@ impl DynamicRiskController {
         Ok(())
     }
 
+    /// 获取当前风险配置
+    pub async fn get_config(&self) -> DynamicRiskConfig {
+        self.config.read().await.clone()
+    }
+
     /// 获取当前风险状态
     pub async fn get_risk_status(&self) -> RiskStatus {
         let config = self.config.read().await;
@@ -0,0 +1,357 @@
+//! 策略参数敏感性分析 API
+//!
+//! 在记录的行情窗口上回放策略，对单个参数在给定区间内扫描，返回
+//! PnL / 命中率曲线，供前端绘制敏感性图表。
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use futures_util::StreamExt;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use common::market_data::NormalizedSnapshot;
+use strategy::{ArbitrageStrategy, StrategyContext};
+
+use crate::nats::NatsManager;
+
+/// 有界行情快照记录器（回测数据源）
+pub struct SnapshotRecorder {
+    snapshots: RwLock<VecDeque<NormalizedSnapshot>>,
+    capacity: usize,
+}
+
+impl SnapshotRecorder {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            snapshots: RwLock::new(VecDeque::with_capacity(capacity.min(65_536))),
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub fn record(&self, snapshot: NormalizedSnapshot) {
+        let mut snapshots = self.snapshots.write();
+        if snapshots.len() == self.capacity {
+            snapshots.pop_front();
+        }
+        snapshots.push_back(snapshot);
+    }
+
+    /// 最近一个快照的时间戳
+    pub fn latest_timestamp_ns(&self) -> Option<u64> {
+        self.snapshots.read().iter().map(|s| s.timestamp_ns).max()
+    }
+
+    /// 取出 [start_ns, end_ns] 区间内的快照
+    pub fn window(&self, start_ns: u64, end_ns: u64) -> Vec<NormalizedSnapshot> {
+        self.snapshots
+            .read()
+            .iter()
+            .filter(|s| s.timestamp_ns >= start_ns && s.timestamp_ns <= end_ns)
+            .cloned()
+            .collect()
+    }
+}
+
+/// 按参数值构建策略实例
+pub trait StrategyFactory: Send + Sync {
+    /// 可扫描的参数名
+    fn parameters(&self) -> Vec<&'static str>;
+
+    /// 以指定参数值构建策略，参数不支持时返回 None
+    fn build(&self, parameter: &str, value: f64) -> Option<Arc<dyn ArbitrageStrategy + Send + Sync>>;
+}
+
+/// 单次回测结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BacktestResult {
+    pub snapshots: usize,
+    pub opportunities: u64,
+    pub profitable: u64,
+    pub total_pnl: f64,
+}
+
+impl BacktestResult {
+    pub fn hit_rate(&self) -> f64 {
+        if self.opportunities == 0 {
+            0.0
+        } else {
+            self.profitable as f64 / self.opportunities as f64
+        }
+    }
+}
+
+/// 最简回测器：按时间顺序回放快照，累计检测到的机会
+pub fn run_backtest(
+    strategy: &dyn ArbitrageStrategy,
+    ctx: &StrategyContext,
+    snapshots: &[NormalizedSnapshot],
+) -> BacktestResult {
+    let mut result = BacktestResult {
+        snapshots: snapshots.len(),
+        ..Default::default()
+    };
+    for snapshot in snapshots {
+        if let Some(opportunity) = strategy.detect(ctx, snapshot) {
+            let pnl = opportunity.net_profit.to_f64();
+            result.opportunities += 1;
+            result.total_pnl += pnl;
+            if pnl > 0.0 {
+                result.profitable += 1;
+            }
+        }
+    }
+    result
+}
+
+/// 敏感性扫描请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SensitivityRequest {
+    pub strategy: String,
+    pub parameter: String,
+    pub window_start_ns: u64,
+    pub window_end_ns: u64,
+    pub from: f64,
+    pub to: f64,
+    pub steps: usize,
+}
+
+/// 曲线上的单个点
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SensitivityPoint {
+    pub value: f64,
+    pub pnl: f64,
+    pub hit_rate: f64,
+    pub opportunities: u64,
+}
+
+/// 敏感性扫描响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SensitivityResponse {
+    pub strategy: String,
+    pub parameter: String,
+    pub snapshots: usize,
+    pub points: Vec<SensitivityPoint>,
+}
+
+/// 敏感性分析服务
+pub struct SensitivityExplorer {
+    recorder: Arc<SnapshotRecorder>,
+    ctx: Arc<StrategyContext>,
+    factories: RwLock<HashMap<String, Arc<dyn StrategyFactory>>>,
+    max_steps: usize,
+}
+
+impl SensitivityExplorer {
+    pub fn new(recorder: Arc<SnapshotRecorder>, ctx: Arc<StrategyContext>) -> Self {
+        Self {
+            recorder,
+            ctx,
+            factories: RwLock::new(HashMap::new()),
+            max_steps: 200,
+        }
+    }
+
+    /// 注册策略工厂
+    pub fn register_factory(&self, strategy: &str, factory: Arc<dyn StrategyFactory>) {
+        self.factories.write().insert(strategy.to_string(), factory);
+    }
+
+    /// 列出可扫描的策略和参数
+    pub fn catalog(&self) -> HashMap<String, Vec<&'static str>> {
+        self.factories
+            .read()
+            .iter()
+            .map(|(name, f)| (name.clone(), f.parameters()))
+            .collect()
+    }
+
+    /// 执行参数扫描
+    pub fn explore(&self, request: &SensitivityRequest) -> Result<SensitivityResponse> {
+        if request.steps < 2 || request.steps > self.max_steps {
+            return Err(anyhow!("steps must be between 2 and {}", self.max_steps));
+        }
+        if request.window_end_ns <= request.window_start_ns {
+            return Err(anyhow!("empty recording window"));
+        }
+        let factory = self
+            .factories
+            .read()
+            .get(&request.strategy)
+            .cloned()
+            .ok_or_else(|| anyhow!("unknown strategy: {}", request.strategy))?;
+
+        let snapshots = self.recorder.window(request.window_start_ns, request.window_end_ns);
+        let step = (request.to - request.from) / (request.steps - 1) as f64;
+
+        let mut points = Vec::with_capacity(request.steps);
+        for i in 0..request.steps {
+            let value = request.from + step * i as f64;
+            let strategy = factory
+                .build(&request.parameter, value)
+                .ok_or_else(|| anyhow!("unsupported parameter: {}", request.parameter))?;
+            let result = run_backtest(strategy.as_ref(), &self.ctx, &snapshots);
+            points.push(SensitivityPoint {
+                value,
+                pnl: result.total_pnl,
+                hit_rate: result.hit_rate(),
+                opportunities: result.opportunities,
+            });
+        }
+
+        Ok(SensitivityResponse {
+            strategy: request.strategy.clone(),
+            parameter: request.parameter.clone(),
+            snapshots: snapshots.len(),
+            points,
+        })
+    }
+
+    /// 在 NATS 请求/响应主题上提供服务
+    pub async fn serve(self: Arc<Self>, nats: &NatsManager, subject: &str) -> Result<()> {
+        let mut subscriber = nats.subscribe(subject).await?;
+        info!("📈 参数敏感性分析服务已启动: {}", subject);
+
+        while let Some(message) = subscriber.next().await {
+            let Some(reply) = message.reply.clone() else { continue };
+            let response = match serde_json::from_slice::<SensitivityRequest>(&message.payload) {
+                Ok(request) => {
+                    let explorer = self.clone();
+                    tokio::task::spawn_blocking(move || explorer.explore(&request))
+                        .await
+                        .map_err(|e| anyhow!(e))
+                        .and_then(|r| r)
+                        .map(|r| serde_json::to_value(r).unwrap_or_default())
+                        .unwrap_or_else(|e| serde_json::json!({ "error": e.to_string() }))
+                }
+                Err(e) => serde_json::json!({ "error": format!("invalid request: {}", e) }),
+            };
+            if let Err(e) = nats
+                .get_client()
+                .publish(reply, serde_json::to_vec(&response)?.into())
+                .await
+            {
+                warn!("敏感性分析响应发送失败: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use common::arbitrage::{ArbitrageOpportunity, Side};
+    use common::precision::FixedPrice;
+    use common::testing::{book, leg, snapshot};
+    use strategy::traits::{StrategyError, StrategyKind};
+    use strategy::{ExecutionResult, FeePrecisionRepoImpl};
+
+    /// 中间价高于阈值时报出机会，收益为中间价减 2.5
+    struct ThresholdStrategy {
+        threshold: f64,
+    }
+
+    #[async_trait]
+    impl ArbitrageStrategy for ThresholdStrategy {
+        fn name(&self) -> &'static str {
+            "threshold"
+        }
+
+        fn kind(&self) -> StrategyKind {
+            StrategyKind::InterExchange
+        }
+
+        fn detect(&self, _ctx: &StrategyContext, snapshot: &NormalizedSnapshot) -> Option<ArbitrageOpportunity> {
+            let mid = snapshot.weighted_mid_price.to_f64();
+            (mid > self.threshold).then(|| {
+                ArbitrageOpportunity::new_with_legs(
+                    self.name(),
+                    vec![leg("binance", "BTCUSDT", Side::Buy, mid, 1.0), leg("okx", "BTCUSDT", Side::Sell, mid, 1.0)],
+                    FixedPrice::from_f64(mid - 2.5, 2),
+                    FixedPrice::from_f64(0.0, 6),
+                    snapshot.timestamp_ns,
+                )
+            })
+        }
+
+        async fn execute(&self, _ctx: &StrategyContext, _opportunity: &ArbitrageOpportunity) -> Result<ExecutionResult, StrategyError> {
+            Ok(ExecutionResult { accepted: true, reason: None, order_ids: vec![] })
+        }
+    }
+
+    struct ThresholdFactory;
+
+    impl StrategyFactory for ThresholdFactory {
+        fn parameters(&self) -> Vec<&'static str> {
+            vec!["threshold"]
+        }
+
+        fn build(&self, parameter: &str, value: f64) -> Option<Arc<dyn ArbitrageStrategy + Send + Sync>> {
+            (parameter == "threshold").then(|| Arc::new(ThresholdStrategy { threshold: value }) as Arc<dyn ArbitrageStrategy + Send + Sync>)
+        }
+    }
+
+    fn explorer() -> SensitivityExplorer {
+        let recorder = Arc::new(SnapshotRecorder::new(100));
+        for ts in 1..=4u64 {
+            let mid = ts as f64;
+            recorder.record(snapshot("BTCUSDT", vec![book("binance", "BTCUSDT", mid, mid, ts)], ts));
+        }
+        let ctx = Arc::new(StrategyContext::new(
+            Arc::new(FeePrecisionRepoImpl::default()),
+            Arc::new(adapters::metrics::AdapterMetrics::new()),
+        ));
+        let explorer = SensitivityExplorer::new(recorder, ctx);
+        explorer.register_factory("threshold", Arc::new(ThresholdFactory));
+        explorer
+    }
+
+    fn request(parameter: &str, window: (u64, u64), steps: usize) -> SensitivityRequest {
+        SensitivityRequest {
+            strategy: "threshold".to_string(),
+            parameter: parameter.to_string(),
+            window_start_ns: window.0,
+            window_end_ns: window.1,
+            from: 0.0,
+            to: 3.0,
+            steps,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sweep_returns_pnl_and_hit_rate_curve() {
+        let explorer = explorer();
+        assert_eq!(explorer.catalog()["threshold"], vec!["threshold"]);
+
+        let response = explorer.explore(&request("threshold", (1, 4), 4)).unwrap();
+        assert_eq!(response.snapshots, 4);
+        let values: Vec<f64> = response.points.iter().map(|p| p.value).collect();
+        assert_eq!(values, vec![0.0, 1.0, 2.0, 3.0]);
+        let opportunities: Vec<u64> = response.points.iter().map(|p| p.opportunities).collect();
+        assert_eq!(opportunities, vec![4, 3, 2, 1]);
+        let pnl: Vec<f64> = response.points.iter().map(|p| p.pnl).collect();
+        assert_eq!(pnl, vec![0.0, 1.5, 2.0, 1.5]);
+        assert!((response.points[1].hit_rate - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(response.points[3].hit_rate, 1.0);
+
+        // 只回放窗口内的快照
+        let response = explorer.explore(&request("threshold", (3, 10), 2)).unwrap();
+        assert_eq!(response.snapshots, 2);
+        assert_eq!(response.points[0].opportunities, 2);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_requests_are_rejected() {
+        let explorer = explorer();
+        assert!(explorer.explore(&request("threshold", (1, 4), 1)).is_err());
+        assert!(explorer.explore(&request("threshold", (1, 4), 201)).is_err());
+        assert!(explorer.explore(&request("threshold", (4, 4), 4)).is_err());
+        assert!(explorer.explore(&request("window", (1, 4), 4)).is_err());
+        let unknown = SensitivityRequest { strategy: "grid".to_string(), ..request("threshold", (1, 4), 4) };
+        assert!(explorer.explore(&unknown).is_err());
+    }
+}
@@ -0,0 +1,419 @@
+//! 优雅停机 - 排空、撤单、落盘、逆序停止模块
+//!
+//! 停机按固定步骤推进：关闭准入不再认领新机会；在截止时间内等待执行中
+//! 的机会完成；撤销所有交易所的剩余挂单；将价格缓存、机会池等内存状态
+//! 落盘；最后按依赖逆序停止模块。每一步的结果写入停机报告，任何一步失败
+//! 都不会阻止后续步骤执行。撤单器由执行适配器按可下单的交易所生成，
+//! 引擎的 `shutdown` 通过 `with_graceful_shutdown` 接入本流程。
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use adapters::execution::ExecutionAdapter;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+use tracing::{error, info, warn};
+
+use crate::lifecycle::{ModuleGraph, ModuleStopReport};
+
+/// 停机配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShutdownConfig {
+    /// 等待执行中机会完成的最长时间
+    pub drain_timeout_ms: u64,
+    /// 单个交易所撤单超时
+    pub cancel_timeout_ms: u64,
+    /// 单个状态落盘超时
+    pub flush_timeout_ms: u64,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            drain_timeout_ms: std::env::var("CELUE_SHUTDOWN_DRAIN_TIMEOUT_MS")
+                .ok().and_then(|s| s.parse().ok())
+                .unwrap_or(10_000),
+            cancel_timeout_ms: std::env::var("CELUE_SHUTDOWN_CANCEL_TIMEOUT_MS")
+                .ok().and_then(|s| s.parse().ok())
+                .unwrap_or(5_000),
+            flush_timeout_ms: std::env::var("CELUE_SHUTDOWN_FLUSH_TIMEOUT_MS")
+                .ok().and_then(|s| s.parse().ok())
+                .unwrap_or(5_000),
+        }
+    }
+}
+
+/// 机会准入闸门：停机时关闭（热重启对账完成前同样保持关闭），并跟踪执行中的机会数
+#[derive(Debug)]
+pub struct ShutdownGate {
+    accepting: AtomicBool,
+    in_flight: AtomicUsize,
+    idle: Notify,
+}
+
+impl Default for ShutdownGate {
+    fn default() -> Self {
+        Self {
+            accepting: AtomicBool::new(true),
+            in_flight: AtomicUsize::new(0),
+            idle: Notify::new(),
+        }
+    }
+}
+
+impl ShutdownGate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 认领一个执行名额；停机后返回 None
+    pub fn try_enter(self: &Arc<Self>) -> Option<InFlightGuard> {
+        if !self.accepting.load(Ordering::Acquire) {
+            return None;
+        }
+        self.in_flight.fetch_add(1, Ordering::AcqRel);
+        // 与 close 竞争时以关闭为准
+        if !self.accepting.load(Ordering::Acquire) {
+            self.leave();
+            return None;
+        }
+        Some(InFlightGuard { gate: self.clone() })
+    }
+
+    pub fn is_accepting(&self) -> bool {
+        self.accepting.load(Ordering::Acquire)
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Acquire)
+    }
+
+    /// 初始即关闭的闸门：热重启完成对账前不允许执行
+    pub fn closed() -> Self {
+        let gate = Self::default();
+        gate.close();
+        gate
+    }
+
+    /// 停止接收新机会
+    pub fn close(&self) {
+        self.accepting.store(false, Ordering::Release);
+    }
+
+    /// 重新开放执行
+    pub fn open(&self) {
+        self.accepting.store(true, Ordering::Release);
+    }
+
+    /// 等待执行中机会清零，超时返回 false
+    pub async fn wait_idle(&self, timeout: Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let notified = self.idle.notified();
+            if self.in_flight() == 0 {
+                return true;
+            }
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                return self.in_flight() == 0;
+            }
+        }
+    }
+
+    fn leave(&self) {
+        if self.in_flight.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.idle.notify_waiters();
+        }
+    }
+}
+
+/// 执行名额，drop 时归还
+#[derive(Debug)]
+pub struct InFlightGuard {
+    gate: Arc<ShutdownGate>,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.gate.leave();
+    }
+}
+
+/// 交易所撤单接口
+#[async_trait]
+pub trait OrderCanceller: Send + Sync {
+    fn exchange(&self) -> &str;
+    /// 撤销全部挂单，返回撤单数量
+    async fn cancel_all_orders(&self) -> Result<usize>;
+}
+
+/// 通过执行适配器撤销某个交易所的挂单（干跑影子挂单与已注册交易场所的订单）
+pub struct ExecutionCanceller {
+    exchange: String,
+    adapter: Arc<ExecutionAdapter>,
+}
+
+impl ExecutionCanceller {
+    pub fn new(exchange: &str, adapter: Arc<ExecutionAdapter>) -> Self {
+        Self { exchange: exchange.to_string(), adapter }
+    }
+}
+
+#[async_trait]
+impl OrderCanceller for ExecutionCanceller {
+    fn exchange(&self) -> &str {
+        &self.exchange
+    }
+
+    async fn cancel_all_orders(&self) -> Result<usize> {
+        Ok(self.adapter.cancel_all_orders(&self.exchange).await?)
+    }
+}
+
+/// 需要在停机时落盘的内存状态（价格缓存、机会池等）
+#[async_trait]
+pub trait StateFlusher: Send + Sync {
+    fn name(&self) -> &str;
+    async fn flush(&self) -> Result<()>;
+}
+
+/// 单个步骤的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepOutcome {
+    pub name: String,
+    pub elapsed_ms: u64,
+    pub error: Option<String>,
+}
+
+/// 停机报告
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShutdownReport {
+    /// 截止时间内是否排空
+    pub drained: bool,
+    /// 超时后仍在执行的机会数
+    pub abandoned_in_flight: usize,
+    pub orders_cancelled: usize,
+    pub cancellations: Vec<StepOutcome>,
+    pub flushes: Vec<StepOutcome>,
+    pub modules: Vec<ModuleStopReport>,
+    pub total_elapsed_ms: u64,
+}
+
+impl ShutdownReport {
+    /// 所有步骤是否无错完成
+    pub fn is_clean(&self) -> bool {
+        self.drained
+            && self.cancellations.iter().all(|c| c.error.is_none())
+            && self.flushes.iter().all(|f| f.error.is_none())
+            && self
+                .modules
+                .iter()
+                .all(|m| m.state == crate::lifecycle::ModuleState::Stopped)
+    }
+}
+
+/// 优雅停机协调器
+pub struct GracefulShutdown {
+    config: ShutdownConfig,
+    gate: Arc<ShutdownGate>,
+    modules: Arc<ModuleGraph>,
+    cancellers: Vec<Arc<dyn OrderCanceller>>,
+    flushers: Vec<Arc<dyn StateFlusher>>,
+}
+
+impl GracefulShutdown {
+    pub fn new(config: ShutdownConfig, gate: Arc<ShutdownGate>, modules: Arc<ModuleGraph>) -> Self {
+        Self {
+            config,
+            gate,
+            modules,
+            cancellers: Vec::new(),
+            flushers: Vec::new(),
+        }
+    }
+
+    pub fn with_canceller(mut self, canceller: Arc<dyn OrderCanceller>) -> Self {
+        self.cancellers.push(canceller);
+        self
+    }
+
+    /// 为执行适配器可下单的每个交易所注册撤单器
+    pub fn with_execution_adapter(mut self, adapter: Arc<ExecutionAdapter>) -> Self {
+        for exchange in adapter.exchanges() {
+            self.cancellers.push(Arc::new(ExecutionCanceller::new(&exchange, adapter.clone())));
+        }
+        self
+    }
+
+    pub fn with_flusher(mut self, flusher: Arc<dyn StateFlusher>) -> Self {
+        self.flushers.push(flusher);
+        self
+    }
+
+    /// 停机时关闭的准入闸门，引擎认领机会时共用
+    pub fn gate(&self) -> Arc<ShutdownGate> {
+        self.gate.clone()
+    }
+
+    /// 执行停机流程
+    pub async fn run(&self) -> ShutdownReport {
+        let started = Instant::now();
+        let mut report = ShutdownReport::default();
+
+        // 1. 停止接收新机会
+        self.gate.close();
+        info!("🛑 停机: 已停止接收新机会，执行中 {} 个", self.gate.in_flight());
+
+        // 2. 等待执行中的机会
+        report.drained = self.gate.wait_idle(Duration::from_millis(self.config.drain_timeout_ms)).await;
+        report.abandoned_in_flight = self.gate.in_flight();
+        if !report.drained {
+            warn!("⚠️ 停机: 排空超时，仍有 {} 个机会在执行", report.abandoned_in_flight);
+        }
+
+        // 3. 撤销所有交易所挂单（并发）
+        let cancel_timeout = Duration::from_millis(self.config.cancel_timeout_ms);
+        let cancels = self.cancellers.iter().map(|canceller| async move {
+            let step = Instant::now();
+            let result = tokio::time::timeout(cancel_timeout, canceller.cancel_all_orders()).await;
+            let (count, error) = match result {
+                Ok(Ok(count)) => (count, None),
+                Ok(Err(e)) => (0, Some(e.to_string())),
+                Err(_) => (0, Some(format!("撤单超时 {}ms", cancel_timeout.as_millis()))),
+            };
+            (count, StepOutcome { name: canceller.exchange().to_string(), elapsed_ms: step.elapsed().as_millis() as u64, error })
+        });
+        for (count, outcome) in futures_util::future::join_all(cancels).await {
+            match &outcome.error {
+                Some(e) => error!("❌ 停机: {} 撤单失败: {}", outcome.name, e),
+                None => info!("✅ 停机: {} 已撤单 {} 笔", outcome.name, count),
+            }
+            report.orders_cancelled += count;
+            report.cancellations.push(outcome);
+        }
+
+        // 4. 内存状态落盘
+        let flush_timeout = Duration::from_millis(self.config.flush_timeout_ms);
+        for flusher in &self.flushers {
+            let step = Instant::now();
+            let error = match tokio::time::timeout(flush_timeout, flusher.flush()).await {
+                Ok(Ok(())) => None,
+                Ok(Err(e)) => Some(e.to_string()),
+                Err(_) => Some(format!("落盘超时 {}ms", flush_timeout.as_millis())),
+            };
+            if let Some(e) = &error {
+                error!("❌ 停机: {} 落盘失败: {}", flusher.name(), e);
+            }
+            report.flushes.push(StepOutcome { name: flusher.name().to_string(), elapsed_ms: step.elapsed().as_millis() as u64, error });
+        }
+
+        // 5. 逆序停止模块
+        match self.modules.stop_all_with_report().await {
+            Ok(modules) => report.modules = modules,
+            Err(e) => error!("❌ 停机: 模块停止失败: {}", e),
+        }
+
+        report.total_elapsed_ms = started.elapsed().as_millis() as u64;
+        info!("🏁 停机完成，耗时 {}ms，撤单 {} 笔", report.total_elapsed_ms, report.orders_cancelled);
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lifecycle::{ManagedModule, ModulePolicy, ModuleState};
+
+    struct Exchange(&'static str, usize);
+
+    #[async_trait]
+    impl OrderCanceller for Exchange {
+        fn exchange(&self) -> &str {
+            self.0
+        }
+        async fn cancel_all_orders(&self) -> Result<usize> {
+            Ok(self.1)
+        }
+    }
+
+    struct Module(&'static str, Vec<String>);
+
+    #[async_trait]
+    impl ManagedModule for Module {
+        fn name(&self) -> &str {
+            self.0
+        }
+        fn dependencies(&self) -> Vec<String> {
+            self.1.clone()
+        }
+        async fn start(&self) -> Result<()> {
+            Ok(())
+        }
+        async fn stop(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_drains_cancels_and_stops_in_reverse_order() {
+        let modules = Arc::new(ModuleGraph::new());
+        modules.register(Arc::new(Module("nats", vec![])), ModulePolicy::default()).unwrap();
+        modules.register(Arc::new(Module("engine", vec!["nats".into()])), ModulePolicy::default()).unwrap();
+        modules.start_all().await.unwrap();
+
+        let gate = Arc::new(ShutdownGate::new());
+        let guard = gate.try_enter().unwrap();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            drop(guard);
+        });
+
+        let config = ShutdownConfig { drain_timeout_ms: 1_000, cancel_timeout_ms: 100, flush_timeout_ms: 100 };
+        let shutdown = GracefulShutdown::new(config, gate.clone(), modules)
+            .with_canceller(Arc::new(Exchange("binance", 3)))
+            .with_canceller(Arc::new(Exchange("okx", 1)));
+        let report = shutdown.run().await;
+
+        assert!(report.drained);
+        assert!(gate.try_enter().is_none());
+        assert_eq!(report.orders_cancelled, 4);
+        assert_eq!(report.modules.iter().map(|m| m.name.as_str()).collect::<Vec<_>>(), vec!["engine", "nats"]);
+        assert!(report.modules.iter().all(|m| m.state == ModuleState::Stopped));
+        assert!(report.is_clean());
+    }
+
+    #[tokio::test]
+    async fn test_execution_adapter_cancels_resting_orders_per_exchange() {
+        use adapters::execution::{ExchangeCredentials, ExecutionConfig};
+        use adapters::order_matching::{ShadowOrderStatus, ShadowOrderType};
+        use adapters::trading_mode::{TradingMode, TradingModeController};
+        use adapters::Adapter;
+        use common::Side;
+
+        let trading_mode = Arc::new(TradingModeController::new(TradingMode::DryRun));
+        let mut adapter = ExecutionAdapter::new().with_trading_mode(trading_mode.clone());
+        let credentials = ExchangeCredentials { api_key: "k".into(), api_secret: "s".into(), passphrase: None, sandbox: true };
+        let config = ExecutionConfig {
+            exchanges: [("Binance".to_string(), credentials)].into_iter().collect(),
+            ..Default::default()
+        };
+        adapter.initialize(config).await.unwrap();
+
+        // 没有成交价的限价单挂在影子引擎上
+        let shadow = trading_mode.shadow_engine();
+        let (binance, _) = shadow.submit("Binance:BTCUSDT", Side::Buy, 1.0, ShadowOrderType::Limit { price: 90.0 }).unwrap();
+        let (okx, _) = shadow.submit("okx:BTCUSDT", Side::Buy, 1.0, ShadowOrderType::Limit { price: 90.0 }).unwrap();
+
+        let config = ShutdownConfig { drain_timeout_ms: 10, cancel_timeout_ms: 100, flush_timeout_ms: 100 };
+        let shutdown = GracefulShutdown::new(config, Arc::new(ShutdownGate::new()), Arc::new(ModuleGraph::new()))
+            .with_execution_adapter(Arc::new(adapter));
+        let report = shutdown.run().await;
+
+        assert_eq!(report.cancellations.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), vec!["binance"]);
+        assert_eq!(report.orders_cancelled, 1);
+        assert_eq!(shadow.order(binance).unwrap().status, ShadowOrderStatus::Cancelled);
+        assert_eq!(shadow.order(okx).unwrap().status, ShadowOrderStatus::Open);
+    }
+}
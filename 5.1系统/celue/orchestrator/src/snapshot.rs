@@ -0,0 +1,527 @@
+//! 崩溃安全的状态快照与热重启
+//!
+//! 周期性地把关键内存状态（最优价格、持仓、挂单、策略滚动统计）写入快照
+//! 存储。重启时先恢复快照，再用交易所 REST 快照对账持仓与挂单（以交易所为
+//! 准），对账完成后才打开执行闸门。文件存储为默认实现，写入采用临时文件
+//! 加 rename 保证原子性；Redis 存储需启用 `redis` feature。策略内部状态
+//! 由 `StrategyStateStore` 单独持久化，本模块提供其 Redis 后端与停机写入。
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::engine::{ConfigurableArbitrageEngine, EngineStats};
+use crate::error::storage_error;
+use common::SystemResult;
+use crate::shutdown::{ShutdownGate, StateFlusher};
+use strategy::state_store::StrategyStateStore;
+#[cfg(feature = "redis")]
+use strategy::state_store::{StateBackend, StateEntry, StateStoreError};
+
+/// 快照格式版本
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// 快照配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotConfig {
+    pub interval_ms: u64,
+    /// 文件存储路径
+    pub path: PathBuf,
+    /// 超过该时长的快照不再恢复
+    pub max_age_ms: u64,
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self {
+            interval_ms: std::env::var("CELUE_SNAPSHOT_INTERVAL_MS")
+                .ok().and_then(|s| s.parse().ok())
+                .unwrap_or(5_000),
+            path: std::env::var("CELUE_SNAPSHOT_PATH")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| PathBuf::from("./state/snapshot.json")),
+            max_age_ms: std::env::var("CELUE_SNAPSHOT_MAX_AGE_MS")
+                .ok().and_then(|s| s.parse().ok())
+                .unwrap_or(60 * 60 * 1000),
+        }
+    }
+}
+
+/// 一次状态快照，按分区保存
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    pub version: u32,
+    pub taken_at_ns: i64,
+    pub sections: BTreeMap<String, serde_json::Value>,
+}
+
+/// 可快照的状态分区
+#[async_trait]
+pub trait StateSection: Send + Sync {
+    fn name(&self) -> &str;
+    async fn capture(&self) -> Result<serde_json::Value>;
+    async fn restore(&self, value: serde_json::Value) -> Result<()>;
+}
+
+/// 快照存储
+#[async_trait]
+pub trait SnapshotStore: Send + Sync {
+    async fn save(&self, snapshot: &StateSnapshot) -> Result<()>;
+    async fn load(&self) -> Result<Option<StateSnapshot>>;
+}
+
+/// 本地文件存储
+pub struct FileSnapshotStore {
+    path: PathBuf,
+}
+
+impl FileSnapshotStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl SnapshotStore for FileSnapshotStore {
+    async fn save(&self, snapshot: &StateSnapshot) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        let tmp = self.path.with_extension("tmp");
+        tokio::fs::write(&tmp, serde_json::to_vec(snapshot)?).await?;
+        tokio::fs::rename(&tmp, &self.path).await?;
+        Ok(())
+    }
+
+    async fn load(&self) -> Result<Option<StateSnapshot>> {
+        match tokio::fs::read(&self.path).await {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Redis 存储
+#[cfg(feature = "redis")]
+pub struct RedisSnapshotStore {
+    client: redis::Client,
+    key: String,
+}
+
+#[cfg(feature = "redis")]
+impl RedisSnapshotStore {
+    pub fn new(url: &str, key: &str) -> Result<Self> {
+        Ok(Self { client: redis::Client::open(url)?, key: key.to_string() })
+    }
+}
+
+#[cfg(feature = "redis")]
+#[async_trait]
+impl SnapshotStore for RedisSnapshotStore {
+    async fn save(&self, snapshot: &StateSnapshot) -> Result<()> {
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        redis::cmd("SET")
+            .arg(&self.key)
+            .arg(serde_json::to_vec(snapshot)?)
+            .query_async::<_, ()>(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn load(&self) -> Result<Option<StateSnapshot>> {
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let bytes: Option<Vec<u8>> = redis::cmd("GET").arg(&self.key).query_async(&mut conn).await?;
+        bytes.map(|b| serde_json::from_slice(&b).map_err(Into::into)).transpose()
+    }
+}
+
+/// 策略状态 Redis 后端：每个策略一个键 `{prefix}:{strategy}`
+#[cfg(feature = "redis")]
+pub struct RedisStateBackend {
+    client: redis::Client,
+    prefix: String,
+}
+
+#[cfg(feature = "redis")]
+impl RedisStateBackend {
+    pub fn new(url: &str, prefix: &str) -> Result<Self> {
+        Ok(Self { client: redis::Client::open(url)?, prefix: prefix.to_string() })
+    }
+}
+
+#[cfg(feature = "redis")]
+#[async_trait]
+impl StateBackend for RedisStateBackend {
+    async fn load(&self, strategy: &str) -> Result<HashMap<String, StateEntry>, StateStoreError> {
+        let backend = |e: redis::RedisError| StateStoreError::Backend(e.to_string());
+        let mut conn = self.client.get_multiplexed_async_connection().await.map_err(backend)?;
+        let bytes: Option<Vec<u8>> = redis::cmd("GET")
+            .arg(format!("{}:{}", self.prefix, strategy))
+            .query_async(&mut conn)
+            .await
+            .map_err(backend)?;
+        bytes
+            .map(|b| serde_json::from_slice(&b).map_err(|e| StateStoreError::Serialization(e.to_string())))
+            .transpose()
+            .map(Option::unwrap_or_default)
+    }
+
+    async fn save(&self, strategy: &str, entries: &HashMap<String, StateEntry>) -> Result<(), StateStoreError> {
+        let backend = |e: redis::RedisError| StateStoreError::Backend(e.to_string());
+        let bytes = serde_json::to_vec(entries).map_err(|e| StateStoreError::Serialization(e.to_string()))?;
+        let mut conn = self.client.get_multiplexed_async_connection().await.map_err(backend)?;
+        redis::cmd("SET")
+            .arg(format!("{}:{}", self.prefix, strategy))
+            .arg(bytes)
+            .query_async::<_, ()>(&mut conn)
+            .await
+            .map_err(backend)
+    }
+}
+
+/// 挂单状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenOrderState {
+    pub exchange: String,
+    pub order_id: String,
+    pub symbol: String,
+    pub side: common::Side,
+    pub price: f64,
+    pub remaining: f64,
+}
+
+/// 交易状态：最优价格、持仓与挂单
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TradingStateData {
+    /// "exchange:symbol" -> 最优价格
+    pub optimal_prices: HashMap<String, f64>,
+    /// "exchange:asset" -> 数量
+    pub positions: HashMap<String, f64>,
+    pub open_orders: Vec<OpenOrderState>,
+}
+
+/// 交易状态分区
+#[derive(Default)]
+pub struct TradingState {
+    data: RwLock<TradingStateData>,
+}
+
+impl TradingState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_price(&self, exchange: &str, symbol: &str, price: f64) {
+        self.data.write().optimal_prices.insert(format!("{}:{}", exchange, symbol), price);
+    }
+
+    pub fn set_position(&self, exchange: &str, asset: &str, quantity: f64) {
+        self.data.write().positions.insert(format!("{}:{}", exchange, asset), quantity);
+    }
+
+    pub fn add_open_order(&self, order: OpenOrderState) {
+        self.data.write().open_orders.push(order);
+    }
+
+    pub fn remove_open_order(&self, exchange: &str, order_id: &str) {
+        self.data.write().open_orders.retain(|o| !(o.exchange == exchange && o.order_id == order_id));
+    }
+
+    pub fn data(&self) -> TradingStateData {
+        self.data.read().clone()
+    }
+}
+
+#[async_trait]
+impl StateSection for TradingState {
+    fn name(&self) -> &str {
+        "trading"
+    }
+
+    async fn capture(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(&*self.data.read())?)
+    }
+
+    async fn restore(&self, value: serde_json::Value) -> Result<()> {
+        *self.data.write() = serde_json::from_value(value)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl StateSection for ConfigurableArbitrageEngine {
+    fn name(&self) -> &str {
+        "engine_stats"
+    }
+
+    async fn capture(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(self.get_stats().await)?)
+    }
+
+    async fn restore(&self, value: serde_json::Value) -> Result<()> {
+        self.restore_stats(serde_json::from_value::<EngineStats>(value)?).await;
+        Ok(())
+    }
+}
+
+/// 交易所 REST 状态快照，用于重启后对账
+#[async_trait]
+pub trait ExchangeStateProvider: Send + Sync {
+    fn exchange(&self) -> &str;
+    /// 资产 -> 余额
+    async fn fetch_balances(&self) -> Result<HashMap<String, f64>>;
+    async fn fetch_open_orders(&self) -> Result<Vec<OpenOrderState>>;
+}
+
+/// 对账差异
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Discrepancy {
+    Position { key: String, snapshot: f64, exchange: f64 },
+    /// 快照中有、交易所已没有（期间成交或被撤）
+    MissingOrder { exchange: String, order_id: String },
+    /// 交易所有、快照中没有（快照之后下的单）
+    UnknownOrder { exchange: String, order_id: String },
+}
+
+/// 热重启报告
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RestoreReport {
+    pub snapshot_taken_at_ns: Option<i64>,
+    pub restored_sections: Vec<String>,
+    pub discrepancies: Vec<Discrepancy>,
+    /// 对账失败的交易所；存在时不开放执行
+    pub failed_exchanges: Vec<String>,
+    pub execution_enabled: bool,
+}
+
+/// 快照与热重启协调器
+pub struct StateSnapshotter {
+    config: SnapshotConfig,
+    store: Arc<dyn SnapshotStore>,
+    sections: Vec<Arc<dyn StateSection>>,
+}
+
+impl StateSnapshotter {
+    pub fn new(config: SnapshotConfig, store: Arc<dyn SnapshotStore>) -> Self {
+        Self { config, store, sections: Vec::new() }
+    }
+
+    pub fn with_section(mut self, section: Arc<dyn StateSection>) -> Self {
+        self.sections.push(section);
+        self
+    }
+
+    /// 采集所有分区
+    pub async fn capture(&self) -> SystemResult<StateSnapshot> {
+        let mut sections = BTreeMap::new();
+        for section in &self.sections {
+            sections.insert(section.name().to_string(), section.capture().await.map_err(storage_error)?);
+        }
+        Ok(StateSnapshot {
+            version: SNAPSHOT_VERSION,
+            taken_at_ns: chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default(),
+            sections,
+        })
+    }
+
+    pub async fn persist(&self) -> SystemResult<()> {
+        let snapshot = self.capture().await?;
+        self.store.save(&snapshot).await.map_err(storage_error)
+    }
+
+    /// 周期性快照
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_millis(self.config.interval_ms));
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.persist().await {
+                    error!("❌ 状态快照失败: {}", e);
+                }
+            }
+        })
+    }
+
+    /// 热重启：恢复快照 → 交易所对账 → 对账成功后开放执行
+    pub async fn warm_restart(
+        &self,
+        trading: &TradingState,
+        providers: &[Arc<dyn ExchangeStateProvider>],
+        gate: &ShutdownGate,
+    ) -> Result<RestoreReport> {
+        gate.close();
+        let mut report = RestoreReport::default();
+
+        if let Some(snapshot) = self.store.load().await? {
+            if snapshot.version != SNAPSHOT_VERSION {
+                return Err(anyhow!("快照版本不兼容: {} (当前 {})", snapshot.version, SNAPSHOT_VERSION));
+            }
+            let age_ms = (chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default() - snapshot.taken_at_ns) / 1_000_000;
+            if age_ms > self.config.max_age_ms as i64 {
+                warn!("⚠️ 快照已过期 {}ms，跳过恢复", age_ms);
+            } else {
+                report.snapshot_taken_at_ns = Some(snapshot.taken_at_ns);
+                for section in &self.sections {
+                    if let Some(value) = snapshot.sections.get(section.name()) {
+                        section.restore(value.clone()).await?;
+                        report.restored_sections.push(section.name().to_string());
+                    }
+                }
+                info!("♻️ 已恢复快照分区: {:?}", report.restored_sections);
+            }
+        }
+
+        for provider in providers {
+            if let Err(e) = reconcile(trading, provider.as_ref(), &mut report.discrepancies).await {
+                error!("❌ {} 对账失败: {}", provider.exchange(), e);
+                report.failed_exchanges.push(provider.exchange().to_string());
+            }
+        }
+        if !report.discrepancies.is_empty() {
+            warn!("⚠️ 对账发现 {} 处差异，已以交易所为准", report.discrepancies.len());
+        }
+
+        report.execution_enabled = report.failed_exchanges.is_empty();
+        if report.execution_enabled {
+            gate.open();
+            info!("✅ 热重启完成，执行已开放");
+        }
+        Ok(report)
+    }
+}
+
+/// 以交易所状态覆盖本地持仓与挂单，记录差异
+async fn reconcile(trading: &TradingState, provider: &dyn ExchangeStateProvider, out: &mut Vec<Discrepancy>) -> Result<()> {
+    let exchange = provider.exchange();
+    let balances = provider.fetch_balances().await?;
+    let orders = provider.fetch_open_orders().await?;
+
+    let mut data = trading.data.write();
+    let prefix = format!("{}:", exchange);
+    let mut keys: Vec<String> = data.positions.keys().filter(|k| k.starts_with(&prefix)).cloned().collect();
+    keys.extend(balances.keys().map(|asset| format!("{}{}", prefix, asset)));
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        let local = data.positions.get(&key).copied().unwrap_or(0.0);
+        let remote = balances.get(&key[prefix.len()..]).copied().unwrap_or(0.0);
+        if (local - remote).abs() > 1e-9 {
+            out.push(Discrepancy::Position { key: key.clone(), snapshot: local, exchange: remote });
+        }
+        data.positions.insert(key, remote);
+    }
+
+    for local in data.open_orders.iter().filter(|o| o.exchange == exchange) {
+        if !orders.iter().any(|o| o.order_id == local.order_id) {
+            out.push(Discrepancy::MissingOrder { exchange: exchange.to_string(), order_id: local.order_id.clone() });
+        }
+    }
+    for remote in &orders {
+        if !data.open_orders.iter().any(|o| o.exchange == exchange && o.order_id == remote.order_id) {
+            out.push(Discrepancy::UnknownOrder { exchange: exchange.to_string(), order_id: remote.order_id.clone() });
+        }
+    }
+    data.open_orders.retain(|o| o.exchange != exchange);
+    data.open_orders.extend(orders);
+    Ok(())
+}
+
+/// 停机时写入最终快照
+#[async_trait]
+impl StateFlusher for StateSnapshotter {
+    fn name(&self) -> &str {
+        "state_snapshot"
+    }
+
+    async fn flush(&self) -> Result<()> {
+        Ok(self.persist().await?)
+    }
+}
+
+/// 停机时写入策略状态
+#[async_trait]
+impl StateFlusher for StrategyStateStore {
+    fn name(&self) -> &str {
+        "strategy_state"
+    }
+
+    async fn flush(&self) -> Result<()> {
+        StrategyStateStore::flush(self).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MockExchange;
+
+    #[async_trait]
+    impl ExchangeStateProvider for MockExchange {
+        fn exchange(&self) -> &str {
+            "binance"
+        }
+        async fn fetch_balances(&self) -> Result<HashMap<String, f64>> {
+            Ok(HashMap::from([("BTC".to_string(), 1.5), ("USDT".to_string(), 10_000.0)]))
+        }
+        async fn fetch_open_orders(&self) -> Result<Vec<OpenOrderState>> {
+            Ok(vec![order("o-2")])
+        }
+    }
+
+    fn order(id: &str) -> OpenOrderState {
+        OpenOrderState {
+            exchange: "binance".into(),
+            order_id: id.into(),
+            symbol: "BTCUSDT".into(),
+            side: common::Side::Buy,
+            price: 43_000.0,
+            remaining: 0.1,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_restore_and_reconcile() {
+        let path = std::env::temp_dir().join(format!("celue_snapshot_{}.json", uuid::Uuid::new_v4()));
+        let store: Arc<dyn SnapshotStore> = Arc::new(FileSnapshotStore::new(&path));
+
+        // 崩溃前
+        let before = Arc::new(TradingState::new());
+        before.set_price("binance", "BTCUSDT", 43_100.0);
+        before.set_position("binance", "BTC", 1.0);
+        before.set_position("binance", "USDT", 10_000.0);
+        before.add_open_order(order("o-1"));
+        StateSnapshotter::new(SnapshotConfig::default(), store.clone())
+            .with_section(before.clone())
+            .persist()
+            .await
+            .unwrap();
+
+        // 重启后
+        let after = Arc::new(TradingState::new());
+        let gate = ShutdownGate::closed();
+        let snapshotter = StateSnapshotter::new(SnapshotConfig::default(), store).with_section(after.clone());
+        let providers: Vec<Arc<dyn ExchangeStateProvider>> = vec![Arc::new(MockExchange)];
+        let report = snapshotter.warm_restart(&after, &providers, &gate).await.unwrap();
+
+        assert_eq!(report.restored_sections, vec!["trading"]);
+        assert!(report.execution_enabled && gate.is_accepting());
+        assert_eq!(after.data().optimal_prices["binance:BTCUSDT"], 43_100.0);
+        assert_eq!(after.data().positions["binance:BTC"], 1.5);
+        assert_eq!(after.data().open_orders, vec![order("o-2")]);
+        assert!(report.discrepancies.contains(&Discrepancy::MissingOrder { exchange: "binance".into(), order_id: "o-1".into() }));
+        assert!(report.discrepancies.contains(&Discrepancy::UnknownOrder { exchange: "binance".into(), order_id: "o-2".into() }));
+        assert_eq!(report.discrepancies.len(), 3);
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,237 @@
+//! 多租户交易配置（profile）
+//!
+//! 一个部署可同时服务多个交易台。每个 profile 拥有独立的交易所凭证、
+//! 策略集合、风控限额与资金限额，运行时各自持有独立的风控与资金适配器，
+//! 资金与风控状态互不影响。指标按 profile 打标签；管理接口通过 RBAC 的
+//! profile 作用域限制调用方只能访问被授权的 profile。
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use adapters::execution::{ExchangeCredentials, ExecutionConfig};
+use adapters::funds::{FundLimits, FundsAdapter, FundsConfig};
+use adapters::risk::{RiskAdapter, RiskConfig, RiskDecision};
+use anyhow::{anyhow, Result};
+use common::{ArbitrageOpportunity, RiskError, SystemResult};
+use metrics::counter;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// 单个交易配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradingProfile {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    /// 该 profile 专用的交易所凭证
+    #[serde(default)]
+    pub exchanges: HashMap<String, ExchangeCredentials>,
+    /// 启用的策略
+    pub strategies: Vec<String>,
+    pub risk: RiskConfig,
+    #[serde(default)]
+    pub funds: FundLimits,
+}
+
+/// profile 运行时：独立的风控与资金状态
+pub struct ProfileRuntime {
+    pub profile: TradingProfile,
+    pub risk: Arc<RiskAdapter>,
+    pub funds: Arc<FundsAdapter>,
+}
+
+impl ProfileRuntime {
+    fn new(profile: TradingProfile) -> Self {
+        let risk = Arc::new(RiskAdapter::with_config(profile.risk.clone()));
+        let funds = Arc::new(FundsAdapter::new(FundsConfig {
+            limits: profile.funds.clone(),
+            ..Default::default()
+        }));
+        Self { profile, risk, funds }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.profile.id
+    }
+
+    pub fn runs_strategy(&self, strategy: &str) -> bool {
+        self.profile.strategies.iter().any(|s| s == strategy)
+    }
+
+    /// 以 profile 凭证替换基础执行配置中的凭证
+    pub fn execution_config(&self, base: &ExecutionConfig) -> ExecutionConfig {
+        ExecutionConfig {
+            exchanges: self.profile.exchanges.clone(),
+            ..base.clone()
+        }
+    }
+
+    /// profile 级准入：策略归属、风控、资金
+    pub async fn check_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<RiskDecision> {
+        let id = self.profile.id.clone();
+        counter!("profile_opportunities_total", 1, "profile" => id.clone());
+        let reject = |reason: String| RiskDecision {
+            approved: false,
+            reason: Some(reason),
+            max_quantity: None,
+            risk_level: 5,
+            suggested_wait_time: None,
+        };
+
+        if !self.runs_strategy(&opportunity.strategy_name) {
+            return Ok(reject(format!("策略 {} 未在 profile {} 中启用", opportunity.strategy_name, id)));
+        }
+        if let Some(leg) = opportunity.legs.iter().find(|l| !self.profile.exchanges.contains_key(l.exchange.as_str())) {
+            return Ok(reject(format!("profile {} 未配置 {} 凭证", id, leg.exchange.as_str())));
+        }
+
+        let decision = self.risk.check_risk(opportunity).await?;
+        if !decision.approved {
+            counter!("profile_opportunities_rejected_total", 1, "profile" => id.clone(), "stage" => "risk");
+            return Ok(decision);
+        }
+
+        let notional: f64 = opportunity
+            .legs
+            .iter()
+            .filter(|l| l.side == common::Side::Buy)
+            .map(|l| l.cost.to_f64().abs())
+            .sum();
+        let symbol = opportunity.legs.first().map(|l| l.symbol.as_str()).unwrap_or_default();
+        let exchange = opportunity.legs.first().map(|l| l.exchange.as_str()).unwrap_or_default();
+        let allocation = self.funds.check_allocation(symbol, exchange, notional);
+        if !allocation.available {
+            counter!("profile_opportunities_rejected_total", 1, "profile" => id, "stage" => "funds");
+            return Ok(reject(allocation.reason.unwrap_or_else(|| "资金不足".to_string())));
+        }
+        Ok(decision)
+    }
+
+    /// 记录执行结果到该 profile 的风控统计
+    pub async fn report_execution(&self, opportunity: &ArbitrageOpportunity, success: bool, pnl: f64) -> Result<()> {
+        let status = if success { "success" } else { "failure" };
+        counter!("profile_executions_total", 1, "profile" => self.profile.id.clone(), "status" => status);
+        self.risk.report_execution_result(opportunity, success, pnl).await?;
+        Ok(())
+    }
+}
+
+/// 所有 profile 的注册表
+#[derive(Default)]
+pub struct ProfileRegistry {
+    profiles: RwLock<HashMap<String, Arc<ProfileRuntime>>>,
+}
+
+impl ProfileRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 从配置构建；id 重复时报错
+    pub fn from_profiles(profiles: Vec<TradingProfile>) -> Result<Self> {
+        let registry = Self::new();
+        registry.apply(profiles)?;
+        Ok(registry)
+    }
+
+    /// 应用新的 profile 列表：未变化的 profile 保留运行时状态，
+    /// 变化的重建，已删除的移除
+    pub fn apply(&self, profiles: Vec<TradingProfile>) -> Result<()> {
+        let mut seen = HashSet::new();
+        for profile in &profiles {
+            if profile.id.is_empty() || !seen.insert(profile.id.clone()) {
+                return Err(anyhow!("profile id 为空或重复: '{}'", profile.id));
+            }
+        }
+        let mut current = self.profiles.write();
+        current.retain(|id, _| seen.contains(id));
+        for profile in profiles {
+            let unchanged = current.get(&profile.id).is_some_and(|r| r.profile == profile);
+            if !unchanged {
+                info!("👥 加载交易 profile: {} ({} 个策略)", profile.id, profile.strategies.len());
+                current.insert(profile.id.clone(), Arc::new(ProfileRuntime::new(profile)));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, id: &str) -> Option<Arc<ProfileRuntime>> {
+        self.profiles.read().get(id).cloned()
+    }
+
+    pub fn ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.profiles.read().keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    /// 启用了该策略的 profile
+    pub fn profiles_for_strategy(&self, strategy: &str) -> Vec<Arc<ProfileRuntime>> {
+        self.profiles.read().values().filter(|p| p.runs_strategy(strategy)).cloned().collect()
+    }
+
+    /// 在指定 profile 下做准入检查
+    pub async fn check_opportunity(&self, profile_id: &str, opportunity: &ArbitrageOpportunity) -> SystemResult<RiskDecision> {
+        let Some(profile) = self.get(profile_id) else {
+            warn!("🚫 未知 profile: {}", profile_id);
+            return Err(RiskError::UnknownProfile(profile_id.to_string()).into());
+        };
+        profile
+            .check_opportunity(opportunity)
+            .await
+            .map_err(|e| RiskError::LimitBreached(e.to_string()).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::testing::inter_exchange_opportunity;
+
+    fn profile(id: &str, strategies: &[&str], exchanges: &[&str]) -> TradingProfile {
+        TradingProfile {
+            id: id.into(),
+            name: id.into(),
+            exchanges: exchanges
+                .iter()
+                .map(|e| {
+                    let credentials = ExchangeCredentials {
+                        api_key: format!("{}-key", id),
+                        api_secret: format!("{}-secret", id),
+                        passphrase: None,
+                        sandbox: true,
+                    };
+                    (e.to_string(), credentials)
+                })
+                .collect(),
+            strategies: strategies.iter().map(|s| s.to_string()).collect(),
+            risk: RiskConfig::default(),
+            funds: FundLimits::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_profiles_are_isolated() {
+        let registry = ProfileRegistry::from_profiles(vec![
+            profile("desk-a", &["inter_exchange"], &["binance", "okx"]),
+            profile("desk-b", &["triangular"], &["binance"]),
+        ])
+        .unwrap();
+        assert!(ProfileRegistry::from_profiles(vec![profile("x", &[], &[]), profile("x", &[], &[])]).is_err());
+
+        let opportunity = inter_exchange_opportunity("inter_exchange", "BTCUSDT", 0.005);
+        assert!(registry.check_opportunity("desk-a", &opportunity).await.unwrap().approved);
+        assert!(!registry.check_opportunity("desk-b", &opportunity).await.unwrap().approved);
+        assert_eq!(registry.profiles_for_strategy("triangular").len(), 1);
+
+        let a = registry.get("desk-a").unwrap();
+        let config = a.execution_config(&ExecutionConfig::default());
+        assert_eq!(config.exchanges["okx"].api_key, "desk-a-key");
+
+        // 未变化的 profile 重新应用时保留运行时状态
+        registry.apply(vec![profile("desk-a", &["inter_exchange"], &["binance", "okx"])]).unwrap();
+        assert!(Arc::ptr_eq(&a, &registry.get("desk-a").unwrap()));
+        assert!(registry.get("desk-b").is_none());
+    }
+}
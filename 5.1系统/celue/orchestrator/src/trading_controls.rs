@@ -0,0 +1,170 @@
+//! 币对级交易开关服务
+//!
+//! 单个币对异常时无需全局或整所熔断：可对某币对（全部交易所）或
+//! 交易所+币对单独禁止检测和/或执行。禁用时机会池中涉及该币对的机会
+//! 立即撤销；开关持久化到文件，重启后仍生效。修改需要 `ManageHalts`
+//! 权限并落审计，查询需要 `ViewDashboard`。
+//!
+//! 滑点熔断暂停的策略也在此查询，并可由操作员提前重新启用。
+
+use std::sync::Arc;
+
+use adapters::slippage_guard::{SlippageGuard, StrategySlippage};
+use adapters::symbol_controls::{ControlScope, SymbolControl, SymbolControls, SymbolKey};
+use anyhow::Result;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::audit::AuditLog;
+use crate::auth::{AuthService, ControlAction};
+use crate::nats::{encode_reply, NatsManager};
+use common::{ApiResponse, SystemError, SystemResult};
+
+/// 控制请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum TradingControlCommand {
+    Disable {
+        /// 缺省表示全部交易所
+        #[serde(default)]
+        exchange: Option<String>,
+        symbol: String,
+        scope: ControlScope,
+        reason: String,
+    },
+    Enable {
+        #[serde(default)]
+        exchange: Option<String>,
+        symbol: String,
+    },
+    List,
+    /// 各策略的滑点熔断状态
+    SlippageStatus,
+    /// 重新启用被滑点熔断暂停的策略
+    ReenableStrategy { strategy: String },
+}
+
+impl TradingControlCommand {
+    fn action(&self) -> ControlAction {
+        match self {
+            TradingControlCommand::List | TradingControlCommand::SlippageStatus => ControlAction::ViewDashboard,
+            _ => ControlAction::ManageHalts,
+        }
+    }
+}
+
+/// 控制响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TradingControlReply {
+    Disabled { key: SymbolKey, cancelled_opportunities: usize },
+    Enabled { key: SymbolKey, changed: bool },
+    Controls { controls: Vec<SymbolControl> },
+    SlippageStatus { strategies: Vec<StrategySlippage> },
+    StrategyReenabled { strategy: String, changed: bool },
+}
+
+/// 币对级交易开关服务
+pub struct TradingControlService {
+    controls: Arc<SymbolControls>,
+    slippage_guard: Option<Arc<SlippageGuard>>,
+    auth: Option<Arc<AuthService>>,
+    audit: Option<Arc<AuditLog>>,
+}
+
+impl TradingControlService {
+    pub fn new(controls: Arc<SymbolControls>) -> Self {
+        Self { controls, slippage_guard: None, auth: None, audit: None }
+    }
+
+    pub fn with_slippage_guard(mut self, guard: Arc<SlippageGuard>) -> Self {
+        self.slippage_guard = Some(guard);
+        self
+    }
+
+    fn slippage_guard(&self) -> SystemResult<&Arc<SlippageGuard>> {
+        self.slippage_guard
+            .as_ref()
+            .ok_or_else(|| SystemError::Unavailable("滑点熔断未启用".to_string()))
+    }
+
+    pub fn with_auth(mut self, auth: Arc<AuthService>) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    pub fn with_audit(mut self, audit: Arc<AuditLog>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    async fn handle(&self, operator: &str, command: &TradingControlCommand) -> SystemResult<TradingControlReply> {
+        Ok(match command {
+            TradingControlCommand::Disable { exchange, symbol, scope, reason } => {
+                if symbol.trim().is_empty() {
+                    return Err(SystemError::InvalidRequest("symbol 不能为空".to_string()));
+                }
+                let key = SymbolKey::new(exchange.as_deref(), symbol);
+                let cancelled_opportunities = self.controls.disable(key.clone(), *scope, reason, operator)?;
+                TradingControlReply::Disabled { key, cancelled_opportunities }
+            }
+            TradingControlCommand::Enable { exchange, symbol } => {
+                let key = SymbolKey::new(exchange.as_deref(), symbol);
+                let changed = self.controls.enable(&key, operator)?;
+                TradingControlReply::Enabled { key, changed }
+            }
+            TradingControlCommand::List => TradingControlReply::Controls { controls: self.controls.list() },
+            TradingControlCommand::SlippageStatus => {
+                TradingControlReply::SlippageStatus { strategies: self.slippage_guard()?.status() }
+            }
+            TradingControlCommand::ReenableStrategy { strategy } => {
+                let changed = self.slippage_guard()?.re_enable(strategy, operator).await;
+                TradingControlReply::StrategyReenabled { strategy: strategy.clone(), changed }
+            }
+        })
+    }
+
+    /// 控制服务：请求体为 `TradingControlCommand` JSON，响应为带错误码的 `ApiResponse`
+    pub async fn serve(self: Arc<Self>, nats: &NatsManager, subject: &str) -> Result<()> {
+        let mut subscriber = nats.subscribe(subject).await?;
+        info!("🎚️ 币对交易开关服务已启动: {}", subject);
+
+        while let Some(message) = subscriber.next().await {
+            let Some(reply) = message.reply.clone() else { continue };
+            let result = async {
+                let command = serde_json::from_slice::<TradingControlCommand>(&message.payload)
+                    .map_err(|e| SystemError::InvalidRequest(e.to_string()))?;
+                let action = command.action();
+                let principal = match &self.auth {
+                    Some(auth) => Some(auth.authorize_nats(&message, action)?),
+                    None => None,
+                };
+                let operator = principal.as_ref().map(|p| p.subject.as_str()).unwrap_or("anonymous");
+                let reply = self.handle(operator, &command).await?;
+                if let (ControlAction::ManageHalts, Some(audit), Some(principal)) = (action, &self.audit, &principal) {
+                    let target = match &reply {
+                        TradingControlReply::Disabled { key, .. } | TradingControlReply::Enabled { key, .. } => {
+                            format!("{}:{}", key.exchange.as_deref().unwrap_or("*"), key.symbol)
+                        }
+                        TradingControlReply::StrategyReenabled { strategy, .. } => format!("strategy:{}", strategy),
+                        TradingControlReply::Controls { .. } | TradingControlReply::SlippageStatus { .. } => String::new(),
+                    };
+                    let details = serde_json::to_value(&command).unwrap_or_default();
+                    audit.record(principal, action, &target, details).await?;
+                }
+                Ok::<_, SystemError>(reply)
+            }
+            .await;
+            let response: ApiResponse<TradingControlReply> = result.into();
+            if let Err(e) = nats
+                .get_client()
+                .publish(reply, encode_reply(&message, &response)?.into())
+                .await
+            {
+                warn!("币对交易开关响应发送失败: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
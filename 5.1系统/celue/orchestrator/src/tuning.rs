@@ -0,0 +1,496 @@
+//! 运行参数在线调优 - 带护栏的灰度发布
+//!
+//! 线程池大小、缓存容量、批大小等参数可以在运行中调整，但一次性全量
+//! 修改风险太大。调优请求先校验参数范围，再只在一个灰度组件上生效；
+//! 观察期结束后对比灰度组件的 KPI 与变更前基线，任一 KPI 恶化超过
+//! `max_acceptable_degradation` 即自动回滚，否则推广到全部组件。每次
+//! 变更（含回滚）记入优化历史。控制接口走 NATS，需要 `UpdateConfig`
+//! 权限，配置了审计日志时一并落审计。
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::audit::AuditLog;
+use crate::auth::{AuthService, ControlAction};
+use crate::nats::{encode_reply, NatsManager};
+use common::{ApiResponse, StorageError, SystemError, SystemResult};
+
+/// 可调参数定义
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TunableParameter {
+    pub name: String,
+    pub min: f64,
+    pub max: f64,
+    pub description: String,
+}
+
+/// 参数落地的目标：各组件持有自己的参数值
+pub trait ParameterTarget: Send + Sync {
+    fn components(&self) -> Vec<String>;
+    fn get(&self, component: &str, parameter: &str) -> Option<f64>;
+    fn apply(&self, component: &str, parameter: &str, value: f64) -> Result<()>;
+}
+
+/// 组件 KPI 来源，如吞吐、p99 延迟、错误率
+pub trait KpiSource: Send + Sync {
+    fn kpis(&self, component: &str) -> HashMap<String, f64>;
+}
+
+/// 调优配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TuningConfig {
+    /// 任一 KPI 相对基线的最大可接受恶化比例
+    pub max_acceptable_degradation: f64,
+    /// 灰度观察期
+    pub canary_duration_secs: u64,
+    /// 越低越好的 KPI（延迟、错误率等），其余视为越高越好
+    pub lower_is_better: Vec<String>,
+}
+
+impl Default for TuningConfig {
+    fn default() -> Self {
+        Self {
+            max_acceptable_degradation: 0.1,
+            canary_duration_secs: 300,
+            lower_is_better: vec!["p99_latency_ms".to_string(), "error_rate".to_string()],
+        }
+    }
+}
+
+/// 变更状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeStatus {
+    Proposed,
+    Canary,
+    Promoted,
+    RolledBack,
+}
+
+/// 一次参数变更，即优化历史中的一条记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParameterChange {
+    pub id: u64,
+    pub parameter: String,
+    pub value: f64,
+    pub canary_component: String,
+    /// 变更前各组件的值，用于回滚
+    pub previous: HashMap<String, f64>,
+    pub reason: String,
+    pub proposed_by: String,
+    pub status: ChangeStatus,
+    pub baseline_kpis: HashMap<String, f64>,
+    pub canary_kpis: HashMap<String, f64>,
+    /// 最差 KPI 的恶化比例
+    pub degradation: Option<f64>,
+    pub proposed_at: DateTime<Utc>,
+    pub canary_started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub outcome: Option<String>,
+}
+
+/// 调优提议
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParameterProposal {
+    pub parameter: String,
+    pub value: f64,
+    /// 缺省取第一个组件
+    #[serde(default)]
+    pub canary_component: Option<String>,
+    #[serde(default)]
+    pub reason: String,
+}
+
+fn not_found(what: String) -> SystemError {
+    StorageError::NotFound(what).into()
+}
+
+/// 参数调优器
+pub struct ParameterTuner {
+    config: TuningConfig,
+    parameters: RwLock<HashMap<String, TunableParameter>>,
+    target: Arc<dyn ParameterTarget>,
+    kpis: Arc<dyn KpiSource>,
+    history: RwLock<Vec<ParameterChange>>,
+}
+
+impl ParameterTuner {
+    pub fn new(config: TuningConfig, target: Arc<dyn ParameterTarget>, kpis: Arc<dyn KpiSource>) -> Self {
+        Self {
+            config,
+            parameters: RwLock::new(HashMap::new()),
+            target,
+            kpis,
+            history: RwLock::new(Vec::new()),
+        }
+    }
+
+    pub fn register(&self, parameter: TunableParameter) {
+        self.parameters.write().insert(parameter.name.clone(), parameter);
+    }
+
+    pub fn parameters(&self) -> Vec<TunableParameter> {
+        self.parameters.read().values().cloned().collect()
+    }
+
+    /// 优化历史，按提议顺序
+    pub fn history(&self) -> Vec<ParameterChange> {
+        self.history.read().clone()
+    }
+
+    pub fn change(&self, id: u64) -> Option<ParameterChange> {
+        self.history.read().iter().find(|c| c.id == id).cloned()
+    }
+
+    /// 登记提议，校验参数范围与灰度组件
+    pub fn propose(&self, proposal: ParameterProposal, proposed_by: &str) -> SystemResult<ParameterChange> {
+        let parameter = self
+            .parameters
+            .read()
+            .get(&proposal.parameter)
+            .cloned()
+            .ok_or_else(|| not_found(format!("parameter {}", proposal.parameter)))?;
+        if !proposal.value.is_finite() || proposal.value < parameter.min || proposal.value > parameter.max {
+            return Err(SystemError::InvalidRequest(format!(
+                "{} = {} 超出范围 [{}, {}]",
+                parameter.name, proposal.value, parameter.min, parameter.max
+            )));
+        }
+        let components = self.target.components();
+        let canary = match proposal.canary_component {
+            Some(component) if components.contains(&component) => component,
+            Some(component) => return Err(not_found(format!("component {}", component))),
+            None => components
+                .first()
+                .cloned()
+                .ok_or_else(|| SystemError::Unavailable("没有可灰度的组件".to_string()))?,
+        };
+        let mut history = self.history.write();
+        if history.iter().any(|c| c.parameter == parameter.name && matches!(c.status, ChangeStatus::Proposed | ChangeStatus::Canary)) {
+            return Err(SystemError::InvalidRequest(format!("{} 已有进行中的变更", parameter.name)));
+        }
+        let change = ParameterChange {
+            id: history.len() as u64 + 1,
+            parameter: parameter.name,
+            value: proposal.value,
+            canary_component: canary,
+            previous: HashMap::new(),
+            reason: proposal.reason,
+            proposed_by: proposed_by.to_string(),
+            status: ChangeStatus::Proposed,
+            baseline_kpis: HashMap::new(),
+            canary_kpis: HashMap::new(),
+            degradation: None,
+            proposed_at: Utc::now(),
+            canary_started_at: None,
+            finished_at: None,
+            outcome: None,
+        };
+        history.push(change.clone());
+        info!("🎛️ 参数调优提议 #{}: {} = {} ({})", change.id, change.parameter, change.value, change.proposed_by);
+        Ok(change)
+    }
+
+    fn update<T>(&self, id: u64, f: impl FnOnce(&mut ParameterChange) -> SystemResult<T>) -> SystemResult<T> {
+        let mut history = self.history.write();
+        let change = history
+            .iter_mut()
+            .find(|c| c.id == id)
+            .ok_or_else(|| not_found(format!("change {}", id)))?;
+        f(change)
+    }
+
+    /// 记录基线 KPI 并在灰度组件上生效
+    pub fn start_canary(&self, id: u64) -> SystemResult<ParameterChange> {
+        self.update(id, |change| {
+            if change.status != ChangeStatus::Proposed {
+                return Err(SystemError::InvalidRequest(format!("变更 #{} 状态为 {:?}", id, change.status)));
+            }
+            change.previous = self
+                .target
+                .components()
+                .into_iter()
+                .filter_map(|c| self.target.get(&c, &change.parameter).map(|v| (c, v)))
+                .collect();
+            change.baseline_kpis = self.kpis.kpis(&change.canary_component);
+            self.target
+                .apply(&change.canary_component, &change.parameter, change.value)
+                .map_err(|e| SystemError::Internal(e.to_string()))?;
+            change.status = ChangeStatus::Canary;
+            change.canary_started_at = Some(Utc::now());
+            info!("🐤 变更 #{} 在 {} 上灰度: {} = {}", id, change.canary_component, change.parameter, change.value);
+            Ok(change.clone())
+        })
+    }
+
+    /// 最差 KPI 的相对恶化比例
+    fn degradation(&self, baseline: &HashMap<String, f64>, observed: &HashMap<String, f64>) -> f64 {
+        baseline
+            .iter()
+            .filter_map(|(name, base)| {
+                let value = observed.get(name)?;
+                if base.abs() < f64::EPSILON {
+                    return None;
+                }
+                let change = (value - base) / base.abs();
+                Some(if self.config.lower_is_better.contains(name) { change } else { -change })
+            })
+            .fold(0.0, f64::max)
+    }
+
+    fn restore(&self, change: &ParameterChange, components: &[String]) {
+        for component in components {
+            if let Some(previous) = change.previous.get(component) {
+                if let Err(e) = self.target.apply(component, &change.parameter, *previous) {
+                    warn!("回滚 {} 的 {} 失败: {}", component, change.parameter, e);
+                }
+            }
+        }
+    }
+
+    /// 评估灰度：恶化超限则回滚，否则推广到全部组件
+    pub fn evaluate(&self, id: u64) -> SystemResult<ParameterChange> {
+        self.update(id, |change| {
+            if change.status != ChangeStatus::Canary {
+                return Err(SystemError::InvalidRequest(format!("变更 #{} 不在灰度中", id)));
+            }
+            change.canary_kpis = self.kpis.kpis(&change.canary_component);
+            let degradation = self.degradation(&change.baseline_kpis, &change.canary_kpis);
+            change.degradation = Some(degradation);
+            change.finished_at = Some(Utc::now());
+            if degradation > self.config.max_acceptable_degradation {
+                self.restore(change, std::slice::from_ref(&change.canary_component));
+                change.status = ChangeStatus::RolledBack;
+                change.outcome = Some(format!(
+                    "KPI 恶化 {:.1}% 超过上限 {:.1}%，已回滚",
+                    degradation * 100.0,
+                    self.config.max_acceptable_degradation * 100.0
+                ));
+                warn!("↩️ 变更 #{} 自动回滚: {}", id, change.outcome.as_deref().unwrap_or_default());
+            } else {
+                for component in self.target.components() {
+                    if component != change.canary_component {
+                        if let Err(e) = self.target.apply(&component, &change.parameter, change.value) {
+                            warn!("推广 {} 到 {} 失败: {}", change.parameter, component, e);
+                        }
+                    }
+                }
+                change.status = ChangeStatus::Promoted;
+                change.outcome = Some(format!("KPI 恶化 {:.1}%，已推广到全部组件", degradation * 100.0));
+                info!("✅ 变更 #{} 已推广: {} = {}", id, change.parameter, change.value);
+            }
+            Ok(change.clone())
+        })
+    }
+
+    /// 手动回滚灰度中或已推广的变更
+    pub fn rollback(&self, id: u64, reason: &str) -> SystemResult<ParameterChange> {
+        self.update(id, |change| {
+            let components: Vec<String> = match change.status {
+                ChangeStatus::Canary => vec![change.canary_component.clone()],
+                ChangeStatus::Promoted => change.previous.keys().cloned().collect(),
+                ChangeStatus::Proposed => Vec::new(),
+                ChangeStatus::RolledBack => {
+                    return Err(SystemError::InvalidRequest(format!("变更 #{} 已回滚", id)));
+                }
+            };
+            self.restore(change, &components);
+            change.status = ChangeStatus::RolledBack;
+            change.finished_at = Some(Utc::now());
+            change.outcome = Some(format!("手动回滚: {}", reason));
+            Ok(change.clone())
+        })
+    }
+
+    /// 灰度观察期到期的变更自动评估
+    pub fn spawn(self: Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let due = chrono::Duration::seconds(self.config.canary_duration_secs as i64);
+                let ready: Vec<u64> = self
+                    .history
+                    .read()
+                    .iter()
+                    .filter(|c| c.status == ChangeStatus::Canary && c.canary_started_at.is_some_and(|t| Utc::now() - t >= due))
+                    .map(|c| c.id)
+                    .collect();
+                for id in ready {
+                    if let Err(e) = self.evaluate(id) {
+                        warn!("变更 #{} 评估失败: {}", id, e);
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// 控制请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum TuningCommand {
+    Parameters,
+    History,
+    /// 提议并立即开始灰度
+    Propose { proposal: ParameterProposal },
+    Rollback { id: u64, reason: String },
+}
+
+/// 控制响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TuningReply {
+    Parameters { parameters: Vec<TunableParameter> },
+    History { changes: Vec<ParameterChange> },
+    Change { change: Box<ParameterChange> },
+}
+
+/// 参数调优控制服务
+pub struct TuningService {
+    tuner: Arc<ParameterTuner>,
+    auth: Option<Arc<AuthService>>,
+    audit: Option<Arc<AuditLog>>,
+}
+
+impl TuningService {
+    pub fn new(tuner: Arc<ParameterTuner>) -> Self {
+        Self { tuner, auth: None, audit: None }
+    }
+
+    pub fn with_auth(mut self, auth: Arc<AuthService>) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    pub fn with_audit(mut self, audit: Arc<AuditLog>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    fn handle(&self, actor: &str, command: &TuningCommand) -> SystemResult<TuningReply> {
+        Ok(match command {
+            TuningCommand::Parameters => TuningReply::Parameters { parameters: self.tuner.parameters() },
+            TuningCommand::History => TuningReply::History { changes: self.tuner.history() },
+            TuningCommand::Propose { proposal } => {
+                let change = self.tuner.propose(proposal.clone(), actor)?;
+                TuningReply::Change { change: Box::new(self.tuner.start_canary(change.id)?) }
+            }
+            TuningCommand::Rollback { id, reason } => TuningReply::Change { change: Box::new(self.tuner.rollback(*id, reason)?) },
+        })
+    }
+
+    /// 控制服务：请求体为 `TuningCommand` JSON，响应为带错误码的 `ApiResponse`
+    pub async fn serve(self: Arc<Self>, nats: &NatsManager, subject: &str) -> Result<()> {
+        let mut subscriber = nats.subscribe(subject).await?;
+        info!("🎛️ 参数调优服务已启动: {}", subject);
+
+        while let Some(message) = subscriber.next().await {
+            let Some(reply) = message.reply.clone() else { continue };
+            let result = async {
+                let command = serde_json::from_slice::<TuningCommand>(&message.payload)
+                    .map_err(|e| SystemError::InvalidRequest(e.to_string()))?;
+                let mutating = matches!(command, TuningCommand::Propose { .. } | TuningCommand::Rollback { .. });
+                let action = if mutating { ControlAction::UpdateConfig } else { ControlAction::ViewDashboard };
+                let principal = match &self.auth {
+                    Some(auth) => Some(auth.authorize_nats(&message, action)?),
+                    None => None,
+                };
+                let actor = principal.as_ref().map_or("anonymous", |p| p.subject.as_str());
+                let reply = self.handle(actor, &command)?;
+                if let (true, Some(audit), Some(principal)) = (mutating, &self.audit, &principal) {
+                    let details = serde_json::to_value(&command).unwrap_or_default();
+                    audit.record(principal, action, "tuning", details).await?;
+                }
+                Ok::<_, SystemError>(reply)
+            }
+            .await;
+            let response: ApiResponse<TuningReply> = result.into();
+            if let Err(e) = nats
+                .get_client()
+                .publish(reply, encode_reply(&message, &response)?.into())
+                .await
+            {
+                warn!("参数调优响应发送失败: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Components {
+        values: RwLock<HashMap<(String, String), f64>>,
+    }
+
+    impl ParameterTarget for Components {
+        fn components(&self) -> Vec<String> {
+            vec!["executor-a".to_string(), "executor-b".to_string()]
+        }
+
+        fn get(&self, component: &str, parameter: &str) -> Option<f64> {
+            Some(*self.values.read().get(&(component.to_string(), parameter.to_string())).unwrap_or(&8.0))
+        }
+
+        fn apply(&self, component: &str, parameter: &str, value: f64) -> Result<()> {
+            self.values.write().insert((component.to_string(), parameter.to_string()), value);
+            Ok(())
+        }
+    }
+
+    /// 批大小超过 32 时延迟恶化
+    struct Kpis(Arc<Components>);
+
+    impl KpiSource for Kpis {
+        fn kpis(&self, component: &str) -> HashMap<String, f64> {
+            let batch = self.0.get(component, "batch_size").unwrap_or(8.0);
+            let latency = if batch > 32.0 { 20.0 } else { 10.0 };
+            HashMap::from([("p99_latency_ms".to_string(), latency), ("throughput".to_string(), 1_000.0 + batch)])
+        }
+    }
+
+    #[test]
+    fn test_canary_promote_and_auto_rollback() {
+        let components = Arc::new(Components::default());
+        let tuner = ParameterTuner::new(TuningConfig::default(), components.clone(), Arc::new(Kpis(components.clone())));
+        tuner.register(TunableParameter { name: "batch_size".into(), min: 1.0, max: 128.0, description: "批大小".into() });
+
+        let bad = ParameterProposal { parameter: "batch_size".into(), value: 500.0, canary_component: None, reason: String::new() };
+        assert!(tuner.propose(bad, "alice").is_err());
+
+        let good = ParameterProposal { parameter: "batch_size".into(), value: 16.0, canary_component: None, reason: "吞吐".into() };
+        let change = tuner.propose(good, "alice").unwrap();
+        tuner.start_canary(change.id).unwrap();
+        assert_eq!(components.get("executor-a", "batch_size"), Some(16.0));
+        assert_eq!(components.get("executor-b", "batch_size"), Some(8.0));
+        assert_eq!(tuner.evaluate(change.id).unwrap().status, ChangeStatus::Promoted);
+        assert_eq!(components.get("executor-b", "batch_size"), Some(16.0));
+
+        let risky = ParameterProposal {
+            parameter: "batch_size".into(),
+            value: 64.0,
+            canary_component: Some("executor-b".into()),
+            reason: String::new(),
+        };
+        let change = tuner.propose(risky, "alice").unwrap();
+        tuner.start_canary(change.id).unwrap();
+        let evaluated = tuner.evaluate(change.id).unwrap();
+        assert_eq!(evaluated.status, ChangeStatus::RolledBack);
+        assert_eq!(evaluated.degradation, Some(1.0));
+        assert_eq!(components.get("executor-b", "batch_size"), Some(16.0));
+        assert_eq!(components.get("executor-a", "batch_size"), Some(16.0));
+        assert_eq!(tuner.history().len(), 2);
+    }
+}
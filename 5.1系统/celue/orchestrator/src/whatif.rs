@@ -0,0 +1,464 @@
+//! 配置变更 what-if 分析
+//!
+//! 在提交配置变更（调整最小利润阈值、停用交易所、调整仓位上限等）前，
+//! 用最近 N 小时记录的行情快照分别按当前配置和拟议配置回放检测流程，
+//! 返回两者的机会数、预期 PnL 和风险指标以及差值。
+//!
+//! 回放只调用策略的 `detect`，不执行任何订单。停用的交易所在检测前从
+//! 快照中剔除；利润阈值、启用策略、单笔仓位和单日交易次数在检测后过滤。
+//! 策略自身在上下文中的最小利润阈值仍然生效，因此把阈值调到低于该值
+//! 不会多出机会。
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use adapters::scheduler::required_capital;
+use anyhow::Result;
+use futures_util::StreamExt;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use common::market_data::NormalizedSnapshot;
+use common::{ApiResponse, SystemError, SystemResult};
+use strategy::{ArbitrageStrategy, StrategyContext};
+
+use crate::auth::{AuthService, ControlAction};
+use crate::config::SystemConfig;
+use crate::nats::{encode_reply, NatsManager};
+use crate::sensitivity::SnapshotRecorder;
+
+const NANOS_PER_HOUR: u64 = 3_600_000_000_000;
+const NANOS_PER_DAY: u64 = 24 * NANOS_PER_HOUR;
+
+/// 回放所用的检测相关配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioConfig {
+    /// 最小净利润率（小数）
+    pub min_profit_threshold: f64,
+    /// 启用的策略，为空表示全部
+    pub enabled_strategies: Vec<String>,
+    pub disabled_exchanges: Vec<String>,
+    /// 单笔最大所需资金
+    pub max_position_size: f64,
+    pub max_daily_trades: u32,
+}
+
+impl ScenarioConfig {
+    pub fn from_system_config(config: &SystemConfig) -> Self {
+        Self {
+            min_profit_threshold: config.strategy.min_profit_threshold,
+            enabled_strategies: config.strategy.enabled_strategies.clone(),
+            disabled_exchanges: Vec::new(),
+            max_position_size: config.risk.max_position_size,
+            max_daily_trades: config.risk.max_daily_trades,
+        }
+    }
+
+    fn strategy_enabled(&self, name: &str) -> bool {
+        self.enabled_strategies.is_empty() || self.enabled_strategies.iter().any(|s| s == name)
+    }
+}
+
+/// 拟议的配置变更，未填写的字段沿用当前配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfigChange {
+    #[serde(default)]
+    pub min_profit_threshold: Option<f64>,
+    #[serde(default)]
+    pub enabled_strategies: Option<Vec<String>>,
+    #[serde(default)]
+    pub disable_exchanges: Vec<String>,
+    #[serde(default)]
+    pub enable_exchanges: Vec<String>,
+    #[serde(default)]
+    pub max_position_size: Option<f64>,
+    #[serde(default)]
+    pub max_daily_trades: Option<u32>,
+}
+
+impl ConfigChange {
+    pub fn apply(&self, base: &ScenarioConfig) -> SystemResult<ScenarioConfig> {
+        if let Some(threshold) = self.min_profit_threshold {
+            if !threshold.is_finite() || threshold < 0.0 {
+                return Err(SystemError::InvalidRequest(format!("invalid min_profit_threshold: {}", threshold)));
+            }
+        }
+        if matches!(self.max_position_size, Some(size) if !size.is_finite() || size <= 0.0) {
+            return Err(SystemError::InvalidRequest("max_position_size must be positive".to_string()));
+        }
+
+        let mut scenario = base.clone();
+        if let Some(threshold) = self.min_profit_threshold {
+            scenario.min_profit_threshold = threshold;
+        }
+        if let Some(strategies) = &self.enabled_strategies {
+            scenario.enabled_strategies = strategies.clone();
+        }
+        if let Some(size) = self.max_position_size {
+            scenario.max_position_size = size;
+        }
+        if let Some(trades) = self.max_daily_trades {
+            scenario.max_daily_trades = trades;
+        }
+        let enable: HashSet<String> = self.enable_exchanges.iter().map(|e| e.to_lowercase()).collect();
+        scenario.disabled_exchanges.retain(|e| !enable.contains(&e.to_lowercase()));
+        for exchange in &self.disable_exchanges {
+            let exchange = exchange.to_lowercase();
+            if !scenario.disabled_exchanges.iter().any(|e| e.to_lowercase() == exchange) {
+                scenario.disabled_exchanges.push(exchange);
+            }
+        }
+        Ok(scenario)
+    }
+}
+
+/// what-if 请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhatIfRequest {
+    #[serde(default)]
+    pub change: ConfigChange,
+    /// 回放最近多少小时的快照
+    #[serde(default = "default_lookback_hours")]
+    pub lookback_hours: u64,
+}
+
+fn default_lookback_hours() -> u64 {
+    24
+}
+
+/// 被过滤掉的机会数
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FilteredCounts {
+    pub below_threshold: u64,
+    pub strategy_disabled: u64,
+    pub position_limit: u64,
+    pub daily_trade_limit: u64,
+}
+
+/// 单个配置下的回放结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScenarioMetrics {
+    pub opportunities: u64,
+    pub profitable: u64,
+    pub hit_rate: f64,
+    pub expected_pnl: f64,
+    /// 单笔最大所需资金
+    pub max_required_capital: f64,
+    pub avg_required_capital: f64,
+    /// 累计 PnL 的最大回撤
+    pub max_drawdown: f64,
+    /// 单笔最差净利润
+    pub worst_pnl: f64,
+    /// 各交易所参与的机会数
+    pub exchange_counts: BTreeMap<String, u64>,
+    pub filtered: FilteredCounts,
+}
+
+/// 拟议配置相对当前配置的变化
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WhatIfDelta {
+    pub opportunities: i64,
+    pub expected_pnl: f64,
+    pub hit_rate: f64,
+    pub max_required_capital: f64,
+    pub max_drawdown: f64,
+}
+
+impl WhatIfDelta {
+    fn between(current: &ScenarioMetrics, proposed: &ScenarioMetrics) -> Self {
+        Self {
+            opportunities: proposed.opportunities as i64 - current.opportunities as i64,
+            expected_pnl: proposed.expected_pnl - current.expected_pnl,
+            hit_rate: proposed.hit_rate - current.hit_rate,
+            max_required_capital: proposed.max_required_capital - current.max_required_capital,
+            max_drawdown: proposed.max_drawdown - current.max_drawdown,
+        }
+    }
+}
+
+/// what-if 报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhatIfReport {
+    pub window_start_ns: u64,
+    pub window_end_ns: u64,
+    pub snapshots: usize,
+    pub current_config: ScenarioConfig,
+    pub proposed_config: ScenarioConfig,
+    pub current: ScenarioMetrics,
+    pub proposed: ScenarioMetrics,
+    pub delta: WhatIfDelta,
+}
+
+/// 配置变更 what-if 分析引擎
+pub struct WhatIfEngine {
+    recorder: Arc<SnapshotRecorder>,
+    ctx: Arc<StrategyContext>,
+    strategies: RwLock<HashMap<String, Arc<dyn ArbitrageStrategy + Send + Sync>>>,
+    current: RwLock<ScenarioConfig>,
+    auth: Option<Arc<AuthService>>,
+}
+
+impl WhatIfEngine {
+    pub fn new(recorder: Arc<SnapshotRecorder>, ctx: Arc<StrategyContext>, current: ScenarioConfig) -> Self {
+        Self {
+            recorder,
+            ctx,
+            strategies: RwLock::new(HashMap::new()),
+            current: RwLock::new(current),
+            auth: None,
+        }
+    }
+
+    pub fn with_auth(mut self, auth: Arc<AuthService>) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    pub fn register_strategy(&self, strategy: Arc<dyn ArbitrageStrategy + Send + Sync>) {
+        self.strategies.write().insert(strategy.name().to_string(), strategy);
+    }
+
+    /// 配置热更新后同步当前配置
+    pub fn set_current(&self, config: ScenarioConfig) {
+        *self.current.write() = config;
+    }
+
+    pub fn current(&self) -> ScenarioConfig {
+        self.current.read().clone()
+    }
+
+    /// 以最近一个快照为窗口终点，分别回放当前配置和拟议配置
+    pub fn evaluate(&self, request: &WhatIfRequest) -> SystemResult<WhatIfReport> {
+        if request.lookback_hours == 0 {
+            return Err(SystemError::InvalidRequest("lookback_hours must be positive".to_string()));
+        }
+        let current = self.current();
+        let proposed = request.change.apply(&current)?;
+        let window_end_ns = self
+            .recorder
+            .latest_timestamp_ns()
+            .ok_or_else(|| SystemError::Unavailable("no recorded snapshots".to_string()))?;
+        let window_start_ns = window_end_ns.saturating_sub(request.lookback_hours.saturating_mul(NANOS_PER_HOUR));
+        let snapshots = self.recorder.window(window_start_ns, window_end_ns);
+
+        let mut strategies: Vec<_> = self.strategies.read().values().cloned().collect();
+        strategies.sort_by_key(|s| s.name());
+        let current_metrics = self.replay(&strategies, &current, &snapshots);
+        let proposed_metrics = self.replay(&strategies, &proposed, &snapshots);
+
+        Ok(WhatIfReport {
+            window_start_ns,
+            window_end_ns,
+            snapshots: snapshots.len(),
+            delta: WhatIfDelta::between(&current_metrics, &proposed_metrics),
+            current_config: current,
+            proposed_config: proposed,
+            current: current_metrics,
+            proposed: proposed_metrics,
+        })
+    }
+
+    fn replay(
+        &self,
+        strategies: &[Arc<dyn ArbitrageStrategy + Send + Sync>],
+        config: &ScenarioConfig,
+        snapshots: &[NormalizedSnapshot],
+    ) -> ScenarioMetrics {
+        let disabled: HashSet<String> = config.disabled_exchanges.iter().map(|e| e.to_lowercase()).collect();
+        let mut metrics = ScenarioMetrics::default();
+        let mut daily_trades: HashMap<u64, u32> = HashMap::new();
+        let (mut cumulative, mut peak, mut capital_sum) = (0.0_f64, 0.0_f64, 0.0);
+
+        for snapshot in snapshots {
+            let filtered;
+            let snapshot = if disabled.is_empty() {
+                snapshot
+            } else {
+                let mut s = snapshot.clone();
+                s.exchanges.retain(|book| !disabled.contains(&book.exchange.as_str().to_lowercase()));
+                filtered = s;
+                &filtered
+            };
+
+            for strategy in strategies {
+                let Some(opportunity) = strategy.detect(&self.ctx, snapshot) else { continue };
+                if !config.strategy_enabled(strategy.name()) {
+                    metrics.filtered.strategy_disabled += 1;
+                    continue;
+                }
+                if opportunity.net_profit_pct.to_f64() < config.min_profit_threshold {
+                    metrics.filtered.below_threshold += 1;
+                    continue;
+                }
+                let capital = required_capital(&opportunity);
+                if capital > config.max_position_size {
+                    metrics.filtered.position_limit += 1;
+                    continue;
+                }
+                let trades = daily_trades.entry(snapshot.timestamp_ns / NANOS_PER_DAY).or_default();
+                if *trades >= config.max_daily_trades {
+                    metrics.filtered.daily_trade_limit += 1;
+                    continue;
+                }
+                *trades += 1;
+
+                let pnl = opportunity.net_profit.to_f64();
+                metrics.opportunities += 1;
+                if pnl > 0.0 {
+                    metrics.profitable += 1;
+                }
+                metrics.worst_pnl = if metrics.opportunities == 1 { pnl } else { metrics.worst_pnl.min(pnl) };
+                cumulative += pnl;
+                peak = peak.max(cumulative);
+                metrics.max_drawdown = metrics.max_drawdown.max(peak - cumulative);
+                metrics.max_required_capital = metrics.max_required_capital.max(capital);
+                capital_sum += capital;
+                for leg in &opportunity.legs {
+                    *metrics.exchange_counts.entry(leg.exchange.as_str().to_string()).or_default() += 1;
+                }
+            }
+        }
+
+        metrics.expected_pnl = cumulative;
+        if metrics.opportunities > 0 {
+            metrics.hit_rate = metrics.profitable as f64 / metrics.opportunities as f64;
+            metrics.avg_required_capital = capital_sum / metrics.opportunities as f64;
+        }
+        metrics
+    }
+
+    /// what-if 服务：请求体为 `WhatIfRequest` JSON，响应为带错误码的 `ApiResponse`
+    pub async fn serve(self: Arc<Self>, nats: &NatsManager, subject: &str) -> Result<()> {
+        let mut subscriber = nats.subscribe(subject).await?;
+        info!("🔮 配置 what-if 分析服务已启动: {}", subject);
+
+        while let Some(message) = subscriber.next().await {
+            let Some(reply) = message.reply.clone() else { continue };
+            let result = async {
+                if let Some(auth) = &self.auth {
+                    auth.authorize_nats(&message, ControlAction::ViewDashboard)?;
+                }
+                let request = serde_json::from_slice::<WhatIfRequest>(&message.payload)
+                    .map_err(|e| SystemError::InvalidRequest(e.to_string()))?;
+                let engine = self.clone();
+                tokio::task::spawn_blocking(move || engine.evaluate(&request))
+                    .await
+                    .map_err(|e| SystemError::Internal(e.to_string()))?
+            }
+            .await;
+            let response: ApiResponse<WhatIfReport> = result.into();
+            if let Err(e) = nats
+                .get_client()
+                .publish(reply, encode_reply(&message, &response)?.into())
+                .await
+            {
+                warn!("what-if 响应发送失败: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::testing::book;
+    use crate::importer::snapshots_from_books;
+    use async_trait::async_trait;
+    use common::arbitrage::{ArbitrageLeg, ArbitrageOpportunity, MarketType, Side};
+    use common::precision::{FixedPrice, FixedQuantity};
+    use common::types::Exchange;
+    use strategy::traits::{StrategyError, StrategyKind};
+    use strategy::{ExecutionResult, FeePrecisionRepoImpl};
+
+    /// 两所同时有盘口时，在 binance 买、okx 卖
+    struct CrossStrategy;
+
+    #[async_trait]
+    impl ArbitrageStrategy for CrossStrategy {
+        fn name(&self) -> &'static str {
+            "inter_exchange"
+        }
+
+        fn kind(&self) -> StrategyKind {
+            StrategyKind::InterExchange
+        }
+
+        fn detect(&self, _ctx: &StrategyContext, snapshot: &NormalizedSnapshot) -> Option<ArbitrageOpportunity> {
+            let book = |name: &str| snapshot.exchanges.iter().find(|b| b.exchange.as_str() == name);
+            let ask = book("binance")?.ask_prices.first()?.to_f64();
+            let bid = book("okx")?.bid_prices.first()?.to_f64();
+            let leg = |exchange: &str, side, price: f64| ArbitrageLeg {
+                exchange: Exchange::new(exchange),
+                symbol: snapshot.symbol.clone(),
+                market: MarketType::Spot,
+                side,
+                price: FixedPrice::from_f64(price, 2),
+                quantity: FixedQuantity::from_f64(1.0, 4),
+                cost: FixedPrice::from_f64(price, 2),
+            };
+            Some(ArbitrageOpportunity::new_inter_exchange(
+                self.name(),
+                leg("binance", Side::Buy, ask),
+                leg("okx", Side::Sell, bid),
+                FixedPrice::from_f64(bid - ask, 2),
+                FixedPrice::from_f64((bid - ask) / ask, 6),
+                snapshot.timestamp_ns,
+            ))
+        }
+
+        async fn execute(&self, _ctx: &StrategyContext, _opportunity: &ArbitrageOpportunity) -> Result<ExecutionResult, StrategyError> {
+            Ok(ExecutionResult { accepted: true, reason: None, order_ids: vec![] })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_whatif_reports_delta_against_current_config() {
+        let recorder = Arc::new(SnapshotRecorder::new(100));
+        let hour = NANOS_PER_HOUR;
+        let books = vec![
+            book("binance", "BTCUSDT", 99.0, 100.0, hour),
+            book("okx", "BTCUSDT", 100.5, 101.0, 2 * hour),
+            book("okx", "BTCUSDT", 101.0, 101.5, 3 * hour),
+        ];
+        for snapshot in snapshots_from_books(books) {
+            recorder.record(snapshot);
+        }
+        let ctx = Arc::new(StrategyContext::new(
+            Arc::new(FeePrecisionRepoImpl::default()),
+            Arc::new(adapters::metrics::AdapterMetrics::new()),
+        ));
+        let current = ScenarioConfig {
+            min_profit_threshold: 0.002,
+            enabled_strategies: vec![],
+            disabled_exchanges: vec![],
+            max_position_size: 1_000.0,
+            max_daily_trades: 100,
+        };
+        let engine = WhatIfEngine::new(recorder, ctx, current);
+        engine.register_strategy(Arc::new(CrossStrategy));
+
+        let report = engine.evaluate(&WhatIfRequest { change: ConfigChange::default(), lookback_hours: 24 }).unwrap();
+        assert_eq!(report.snapshots, 3);
+        assert_eq!(report.current.opportunities, 2);
+        assert!((report.current.expected_pnl - 1.5).abs() < 1e-9);
+        assert_eq!(report.delta.opportunities, 0);
+
+        let raise = ConfigChange { min_profit_threshold: Some(0.008), ..Default::default() };
+        let report = engine.evaluate(&WhatIfRequest { change: raise, lookback_hours: 24 }).unwrap();
+        assert_eq!(report.proposed.opportunities, 1);
+        assert_eq!(report.proposed.filtered.below_threshold, 1);
+        assert!((report.delta.expected_pnl + 0.5).abs() < 1e-9);
+
+        let disable = ConfigChange { disable_exchanges: vec!["OKX".into()], ..Default::default() };
+        let report = engine.evaluate(&WhatIfRequest { change: disable, lookback_hours: 1 }).unwrap();
+        assert_eq!(report.snapshots, 2);
+        assert_eq!(report.proposed.opportunities, 0);
+        assert_eq!(report.delta.opportunities, -2);
+        assert_eq!(report.proposed_config.disabled_exchanges, vec!["okx".to_string()]);
+
+        let bad = ConfigChange { min_profit_threshold: Some(-1.0), ..Default::default() };
+        assert!(engine.evaluate(&WhatIfRequest { change: bad, lookback_hours: 24 }).is_err());
+    }
+}
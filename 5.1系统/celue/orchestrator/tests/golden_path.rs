@@ -0,0 +1,215 @@
+//! 端到端黄金路径测试：检测 → 风控 → 执行 → 持久化
+//!
+//! 用回放模拟器充当交易所，按脚本推进一段价差由闭合到打开的行情，逐环
+//! 断言：机会被检测、通过风控、成交、账本更新、事件落盘、指标产出。
+//! 不依赖 NATS 或真实交易所，本地与 CI 夜间任务均可运行：
+//!
+//! ```text
+//! cargo test -p orchestrator --test golden_path
+//! ```
+
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::sync::Arc;
+use std::time::Duration;
+
+use adapters::attribution::{PerformanceAnalyzer, TradeRecord};
+use adapters::order_matching::ShadowFill;
+use adapters::replay::{ReplayConfig, ReplaySimulator};
+use adapters::subject_metrics::SubjectMetrics;
+use adapters::virtual_account::VirtualAccount;
+use adapters::ws_gateway::WsGateway;
+use common::market_data::{NormalizedSnapshot, OrderBook};
+use common::precision::{FixedPrice, FixedQuantity};
+use common::types::StrategyKind;
+use common::{Exchange, Symbol};
+use orchestrator::{ConfigurableArbitrageEngine, SystemConfig};
+use strategy::plugins::inter_exchange::InterExchangeStrategy;
+use strategy::{ArbitrageStrategy, FeePrecisionRepoImpl, StrategyContext};
+
+const SYMBOL: &str = "BTCUSDT";
+const FEE_RATE: f64 = 0.001;
+
+fn book(exchange: &str, ts_ns: u64, bid: f64, ask: f64, qty: f64) -> OrderBook {
+    let mut book = OrderBook::new(Exchange::new(exchange), Symbol::new(SYMBOL), ts_ns, ts_ns);
+    book.add_bid(FixedPrice::from_f64(bid, 2), FixedQuantity::from_f64(qty, 8));
+    book.add_ask(FixedPrice::from_f64(ask, 2), FixedQuantity::from_f64(qty, 8));
+    book
+}
+
+fn snapshot(ts_ns: u64, books: Vec<OrderBook>) -> NormalizedSnapshot {
+    NormalizedSnapshot {
+        symbol: Symbol::new(SYMBOL),
+        timestamp_ns: ts_ns,
+        exchanges: books,
+        weighted_mid_price: FixedPrice::from_f64(43_300.0, 2),
+        total_bid_volume: FixedQuantity::from_f64(1.0, 8),
+        total_ask_volume: FixedQuantity::from_f64(1.0, 8),
+        quality_score: 0.99,
+        sequence: Some(ts_ns),
+    }
+}
+
+/// 脚本行情：t0 价差闭合，t1 价差打开，t2 行情维持（订单延迟后撮合）
+fn scripted_market() -> Vec<NormalizedSnapshot> {
+    let ms = 1_000_000;
+    vec![
+        snapshot(1_000 * ms, vec![
+            book("binance", 1_000 * ms, 43_290.0, 43_300.0, 0.5),
+            book("okx", 1_000 * ms, 43_295.0, 43_305.0, 0.5),
+        ]),
+        snapshot(1_010 * ms, vec![
+            book("binance", 1_010 * ms, 42_790.0, 42_800.0, 0.5),
+            book("okx", 1_010 * ms, 43_800.0, 43_810.0, 0.5),
+        ]),
+        snapshot(1_030 * ms, vec![
+            book("binance", 1_030 * ms, 42_790.0, 42_800.0, 0.5),
+            book("okx", 1_030 * ms, 43_800.0, 43_810.0, 0.5),
+        ]),
+    ]
+}
+
+#[tokio::test]
+async fn test_golden_path_detect_risk_execute_persist() {
+    // 启动编排引擎
+    let context = Arc::new(StrategyContext::new(
+        Arc::new(FeePrecisionRepoImpl::default()),
+        Arc::new(adapters::metrics::AdapterMetrics::new()),
+    ));
+    let engine = ConfigurableArbitrageEngine::new(&SystemConfig::default(), context.clone());
+    engine
+        .register_strategy("inter_exchange".to_string(), Arc::new(InterExchangeStrategy))
+        .await
+        .unwrap();
+
+    // 交易所模拟器与各交易所账本：binance 持 USDT 买入，okx 持 BTC 卖出
+    let mut simulator = ReplaySimulator::new(ReplayConfig::default());
+    let mut accounts: HashMap<&str, VirtualAccount> = HashMap::new();
+    accounts.entry("binance").or_default().deposit("USDT", 100_000.0);
+    accounts.entry("okx").or_default().deposit("BTC", 2.0);
+
+    let analyzer = PerformanceAnalyzer::new();
+    let gateway = WsGateway::new(64);
+    let subject_metrics = SubjectMetrics::new(2);
+    let event_path = std::env::temp_dir().join(format!("celue_golden_path_{}.jsonl", uuid::Uuid::new_v4()));
+    let mut event_log = std::fs::File::create(&event_path).unwrap();
+    let mut persist = |topic: &str, schema_id: &str, payload: serde_json::Value| {
+        let envelope = gateway.publish(topic, schema_id, &payload).unwrap();
+        let line = serde_json::to_string(&envelope).unwrap();
+        writeln!(event_log, "{}", line).unwrap();
+        subject_metrics.record_publish(&format!("celue.events.{}", topic), line.len(), Duration::from_micros(50));
+    };
+
+    let market = scripted_market();
+    let mut orders: HashMap<u64, (String, f64)> = HashMap::new();
+    let mut fills: Vec<(String, ShadowFill)> = Vec::new();
+    let mut executed = None;
+
+    for snap in &market {
+        // 模拟器先撮合已挂订单
+        for book in &snap.exchanges {
+            for fill in simulator.on_book(book) {
+                fills.push((book.exchange.as_str().to_string(), fill));
+            }
+        }
+
+        // 1. 检测（价差闭合时不应出现机会）
+        let Some(opportunity) = InterExchangeStrategy.detect(&context, snap) else {
+            assert!(engine.detect_and_execute(snap).await.unwrap().is_empty());
+            continue;
+        };
+        if executed.is_some() {
+            continue;
+        }
+        assert_eq!(snap.timestamp_ns, market[1].timestamp_ns, "价差打开前不应检测到机会");
+        persist("opportunities", "opportunity.v1", serde_json::to_value(&opportunity).unwrap());
+
+        // 2./3. 风控审批并执行
+        let results = engine.detect_and_execute(snap).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].accepted, "机会应通过风控并执行: {:?}", results[0].reason);
+        persist("executions", "execution.v1", serde_json::json!({
+            "opportunity_id": opportunity.id,
+            "order_ids": results[0].order_ids,
+        }));
+
+        for leg in &opportunity.legs {
+            let id = simulator
+                .submit(
+                    leg.exchange.as_str(),
+                    leg.symbol.as_str(),
+                    leg.side,
+                    leg.quantity.to_f64(),
+                    Some(leg.price.to_f64()),
+                    snap.timestamp_ns,
+                )
+                .unwrap();
+            orders.insert(id, (leg.exchange.as_str().to_string(), leg.price.to_f64()));
+        }
+        executed = Some(opportunity);
+    }
+
+    let opportunity = executed.expect("价差打开后应检测到机会");
+    assert_eq!(fills.len(), 2, "两条腿都应在模拟器成交");
+    assert!(simulator.open_orders().is_empty());
+
+    // 4. 账本更新
+    let mut gross_profit = 0.0;
+    let mut fees = 0.0;
+    let mut slippage_bps = 0.0;
+    for (exchange, fill) in &fills {
+        let (order_exchange, expected_price) = &orders[&fill.order_id];
+        assert_eq!(order_exchange, exchange);
+        accounts.get_mut(exchange.as_str()).unwrap().apply_fill(fill, "BTC", "USDT", FEE_RATE).unwrap();
+        let notional = fill.quantity * fill.price;
+        gross_profit += match fill.side {
+            common::Side::Buy => -notional,
+            common::Side::Sell => notional,
+        };
+        fees += notional * FEE_RATE;
+        slippage_bps += (fill.price - expected_price).abs() / expected_price * 10_000.0;
+        persist("fills", "fill.v1", serde_json::to_value(fill).unwrap());
+    }
+    assert!(accounts["binance"].balance("BTC") > 0.49);
+    assert!(accounts["binance"].balance("USDT") < 100_000.0);
+    assert!(accounts["okx"].balance("BTC") < 2.0);
+    assert!(accounts["okx"].balance("USDT") > 21_000.0);
+
+    let buy_leg = opportunity.legs.iter().find(|l| l.side == common::Side::Buy).unwrap();
+    let sell_leg = opportunity.legs.iter().find(|l| l.side == common::Side::Sell).unwrap();
+    analyzer.record(&TradeRecord {
+        strategy: StrategyKind::InterExchange,
+        buy_exchange: buy_leg.exchange.as_str().to_string(),
+        sell_exchange: sell_leg.exchange.as_str().to_string(),
+        gross_profit,
+        fees,
+        slippage_bps,
+        capital_used: buy_leg.cost.to_f64(),
+        timestamp_ns: market[2].timestamp_ns,
+    });
+    let performance = analyzer.get_performance_stats();
+    assert_eq!(performance.trades, 1);
+    assert!(performance.net_profit > 0.0);
+
+    let risk_status = engine.get_risk_status().await;
+    assert!(risk_status.daily_pnl > 0.0);
+    assert!(risk_status.is_healthy);
+
+    // 5. 事件落盘：可从文件完整读回，且按主题顺序编号
+    event_log.flush().unwrap();
+    let persisted: Vec<serde_json::Value> = std::io::BufReader::new(std::fs::File::open(&event_path).unwrap())
+        .lines()
+        .map(|l| serde_json::from_str(&l.unwrap()).unwrap())
+        .collect();
+    let topics: Vec<&str> = persisted.iter().map(|e| e["topic"].as_str().unwrap()).collect();
+    assert_eq!(topics, vec!["opportunities", "executions", "fills", "fills"]);
+    assert_eq!(gateway.current_seq("fills"), 2);
+    std::fs::remove_file(&event_path).ok();
+
+    // 6. 指标产出
+    let stats = engine.get_stats().await;
+    assert_eq!(stats.opportunities_executed, 1);
+    assert!(stats.opportunities_detected >= 1);
+    let subjects = subject_metrics.snapshot();
+    assert_eq!(subjects.values().map(|s| s.published).sum::<u64>(), 4);
+}
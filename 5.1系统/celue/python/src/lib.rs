@@ -0,0 +1,205 @@
+//! 回测与分析模块的 Python 绑定
+//!
+//! 供量化在 notebook 中直接调用生产检测代码评估策略：回测引擎、
+//! `MinProfitModel`、机会评分特征提取和绩效分析器。快照、机会和成交
+//! 记录以 dict 传入，字段与 `common` / `adapters` 的 serde 结构一致。
+//!
+//! 构建：`cd python && maturin develop --release`，然后 `import taoli`。
+
+use std::sync::Arc;
+
+use adapters::attribution::{PerformanceAnalyzer as RustPerformanceAnalyzer, ReportFormat, TradeRecord};
+use common::{ArbitrageOpportunity, NormalizedSnapshot};
+use orchestrator::sensitivity::run_backtest;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pythonize::{depythonize, pythonize};
+use strategy::plugins::inter_exchange::InterExchangeStrategy;
+use strategy::plugins::triangular::TriangularStrategy;
+use strategy::scoring::{LogisticRegressionModel, OpportunityScorer as RustOpportunityScorer, FEATURE_NAMES};
+use strategy::{ArbitrageStrategy, FeePrecisionRepoImpl, MarketState, MinProfitModel as RustMinProfitModel, StrategyContext};
+
+fn parse_market_state(state: &str) -> PyResult<MarketState> {
+    match state.to_ascii_lowercase().as_str() {
+        "regular" => Ok(MarketState::Regular),
+        "cautious" => Ok(MarketState::Cautious),
+        "extreme" => Ok(MarketState::Extreme),
+        other => Err(PyValueError::new_err(format!("未知市场状态: {}（regular/cautious/extreme）", other))),
+    }
+}
+
+fn build_strategy(name: &str) -> PyResult<Box<dyn ArbitrageStrategy + Send + Sync>> {
+    match name {
+        "inter_exchange" => Ok(Box::new(InterExchangeStrategy)),
+        "triangular" => Ok(Box::new(TriangularStrategy)),
+        other => Err(PyValueError::new_err(format!("未知策略: {}（inter_exchange/triangular）", other))),
+    }
+}
+
+/// 最小利润阈值模型
+#[pyclass]
+struct MinProfitModel {
+    inner: RustMinProfitModel,
+}
+
+#[pymethods]
+impl MinProfitModel {
+    #[new]
+    #[pyo3(signature = (base_min_profit_bps, cautious_weight = 1.4, extreme_weight = 2.5))]
+    fn new(base_min_profit_bps: u32, cautious_weight: f64, extreme_weight: f64) -> Self {
+        Self { inner: RustMinProfitModel::new(base_min_profit_bps, cautious_weight, extreme_weight) }
+    }
+
+    /// 指定市场状态下的最小利润阈值
+    fn threshold_pct(&self, market_state: &str) -> PyResult<f64> {
+        Ok(self.inner.get_threshold_pct(parse_market_state(market_state)?).to_f64())
+    }
+}
+
+/// 机会评分器：特征提取 + 逻辑回归模型
+#[pyclass]
+struct OpportunityScorer {
+    inner: RustOpportunityScorer,
+}
+
+#[pymethods]
+impl OpportunityScorer {
+    /// `model_path` 为逻辑回归模型 JSON，缺省使用内置系数
+    #[new]
+    #[pyo3(signature = (model_path = None))]
+    fn new(model_path: Option<&str>) -> PyResult<Self> {
+        let model = match model_path {
+            Some(path) => LogisticRegressionModel::from_json_file(path).map_err(|e| PyValueError::new_err(e.to_string()))?,
+            None => LogisticRegressionModel::default(),
+        };
+        Ok(Self { inner: RustOpportunityScorer::new(Box::new(model)) })
+    }
+
+    /// 特征名称，与 `extract` 返回的向量顺序一致
+    #[staticmethod]
+    fn feature_names() -> Vec<&'static str> {
+        FEATURE_NAMES.to_vec()
+    }
+
+    fn record_fee(&self, exchange: &str, fee_bps: f64) {
+        self.inner.record_fee(exchange, fee_bps);
+    }
+
+    fn observe_snapshot(&self, snapshot: &PyAny) -> PyResult<()> {
+        let snapshot: NormalizedSnapshot = depythonize(snapshot)?;
+        self.inner.observe_snapshot(&snapshot);
+        Ok(())
+    }
+
+    /// 提取特征向量（按 `feature_names` 顺序）
+    fn extract(&self, opportunity: &PyAny, snapshot: &PyAny) -> PyResult<Vec<f64>> {
+        let opportunity: ArbitrageOpportunity = depythonize(opportunity)?;
+        let snapshot: NormalizedSnapshot = depythonize(snapshot)?;
+        Ok(self.inner.extract(&opportunity, &snapshot).to_vec())
+    }
+
+    /// 评分，返回 {"confidence": .., "risk": ..}
+    fn score(&self, py: Python<'_>, opportunity: &PyAny, snapshot: &PyAny) -> PyResult<PyObject> {
+        let mut opportunity: ArbitrageOpportunity = depythonize(opportunity)?;
+        let snapshot: NormalizedSnapshot = depythonize(snapshot)?;
+        let score = self.inner.score(&mut opportunity, &snapshot);
+        Ok(pythonize(py, &score)?)
+    }
+}
+
+/// 绩效分析器：按策略和交易所对归因
+#[pyclass]
+struct PerformanceAnalyzer {
+    inner: Arc<RustPerformanceAnalyzer>,
+}
+
+#[pymethods]
+impl PerformanceAnalyzer {
+    #[new]
+    fn new() -> Self {
+        Self { inner: Arc::new(RustPerformanceAnalyzer::new()) }
+    }
+
+    /// 记录成交，dict 字段同 `TradeRecord`
+    fn record(&self, trade: &PyAny) -> PyResult<()> {
+        let trade: TradeRecord = depythonize(trade)?;
+        self.inner.record(&trade);
+        Ok(())
+    }
+
+    /// 账户级汇总
+    fn stats(&self, py: Python<'_>) -> PyResult<PyObject> {
+        Ok(pythonize(py, &self.inner.get_performance_stats())?)
+    }
+
+    /// 按策略、交易所对分组的归因结果
+    fn attribution(&self, py: Python<'_>) -> PyResult<PyObject> {
+        Ok(pythonize(py, &self.inner.attribution())?)
+    }
+
+    /// 渲染报告：json / csv / html
+    #[pyo3(signature = (format = "json"))]
+    fn report(&self, format: &str) -> PyResult<String> {
+        let format = match format {
+            "json" => ReportFormat::Json,
+            "csv" => ReportFormat::Csv,
+            "html" => ReportFormat::Html,
+            other => return Err(PyValueError::new_err(format!("未知报告格式: {}", other))),
+        };
+        self.inner.render_report(format).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+}
+
+/// 在快照序列上回放生产检测代码
+///
+/// 返回 {"snapshots", "opportunities", "profitable", "total_pnl", "hit_rate"}
+#[pyfunction]
+#[pyo3(signature = (strategy, snapshots, min_profit_bps = None, market_state = "regular"))]
+fn backtest(
+    py: Python<'_>,
+    strategy: &str,
+    snapshots: &PyAny,
+    min_profit_bps: Option<u32>,
+    market_state: &str,
+) -> PyResult<PyObject> {
+    let strategy = build_strategy(strategy)?;
+    let snapshots: Vec<NormalizedSnapshot> = depythonize(snapshots)?;
+    let ctx = StrategyContext::new(
+        Arc::new(FeePrecisionRepoImpl::default()),
+        Arc::new(adapters::metrics::AdapterMetrics::new()),
+    );
+    ctx.update_market_state(parse_market_state(market_state)?);
+    if let Some(bps) = min_profit_bps {
+        // 与生产一致：阈值经 MinProfitModel 换算
+        let threshold = RustMinProfitModel::new(bps, 1.0, 1.0).get_threshold_pct(MarketState::Regular);
+        ctx.set_min_profit_for_strategy(strategy.name(), threshold);
+    }
+
+    // 回放期间释放 GIL
+    let result = py.allow_threads(|| run_backtest(strategy.as_ref(), &ctx, &snapshots));
+    let mut value = serde_json::to_value(&result).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    value["hit_rate"] = serde_json::json!(result.hit_rate());
+    Ok(pythonize(py, &value)?)
+}
+
+#[pymodule]
+fn taoli(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    m.add_class::<MinProfitModel>()?;
+    m.add_class::<OpportunityScorer>()?;
+    m.add_class::<PerformanceAnalyzer>()?;
+    m.add_function(wrap_pyfunction!(backtest, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_market_state_and_strategy_lookup() {
+        assert_eq!(parse_market_state("Cautious").unwrap(), MarketState::Cautious);
+        assert!(parse_market_state("panic").is_err());
+        assert_eq!(build_strategy("triangular").unwrap().name(), "triangular");
+        assert!(build_strategy("grid").is_err());
+    }
+}
@@ -0,0 +1,13 @@
+#!/bin/bash
+
+# 端到端黄金路径测试（检测 → 风控 → 执行 → 持久化）
+# 本地运行或由 CI 夜间任务调用，例如 cron: 0 2 * * *
+
+set -e  # 遇到错误立即退出
+
+cd "$(dirname "$0")/.."
+
+echo "🎯 运行黄金路径集成测试..."
+cargo test -p orchestrator --test golden_path -- --nocapture
+
+echo "✅ 黄金路径测试通过"
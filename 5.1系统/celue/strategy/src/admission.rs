@@ -0,0 +1,224 @@
+//! 策略资源预算准入
+//!
+//! 启用策略前，用其声明的 `ResourceRequirements` 对比剩余的 CPU/内存/
+//! API 速率容量，超出则拒绝启用。运行中按每秒窗口统计每个策略的 API
+//! 调用，超出声明速率的执行被限流。预算使用情况通过引擎统计输出。
+
+use std::collections::HashMap;
+
+use metrics::{counter, gauge};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::traits::ResourceRequirements;
+
+/// 可分配给策略的资源总量
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceCapacity {
+    pub cpu_cores: f64,
+    pub memory_mb: u64,
+    /// 所有策略合计的每秒 API 调用数
+    pub api_rate_limit: u32,
+}
+
+impl Default for ResourceCapacity {
+    fn default() -> Self {
+        Self {
+            cpu_cores: std::env::var("CELUE_STRATEGY_CPU_BUDGET")
+                .ok().and_then(|s| s.parse().ok())
+                .unwrap_or_else(|| std::thread::available_parallelism().map(|n| n.get() as f64).unwrap_or(4.0)),
+            memory_mb: std::env::var("CELUE_STRATEGY_MEMORY_BUDGET_MB")
+                .ok().and_then(|s| s.parse().ok())
+                .unwrap_or(4_096),
+            api_rate_limit: std::env::var("CELUE_STRATEGY_API_RATE_BUDGET")
+                .ok().and_then(|s| s.parse().ok())
+                .unwrap_or(100),
+        }
+    }
+}
+
+/// 准入拒绝原因
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum AdmissionError {
+    #[error("策略 {strategy} 需要 {requested} 核 CPU，剩余 {available}")]
+    Cpu { strategy: String, requested: f64, available: f64 },
+    #[error("策略 {strategy} 需要 {requested}MB 内存，剩余 {available}MB")]
+    Memory { strategy: String, requested: u64, available: u64 },
+    #[error("策略 {strategy} 需要 {requested} 次/秒 API 调用，剩余 {available}")]
+    ApiRate { strategy: String, requested: u32, available: u32 },
+}
+
+/// 单个策略的预算使用情况
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BudgetUtilization {
+    pub strategy: String,
+    pub requirements: ResourceRequirements,
+    /// 当前窗口内的 API 调用数
+    pub api_calls_current_window: u32,
+    /// 当前窗口 API 调用 / 声明速率
+    pub api_utilization: f64,
+    /// 累计被限流次数
+    pub throttled_total: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Admitted {
+    requirements: ResourceRequirements,
+    window_start_ms: u64,
+    calls_in_window: u32,
+    throttled_total: u64,
+}
+
+/// 策略准入控制器
+pub struct AdmissionController {
+    capacity: ResourceCapacity,
+    admitted: RwLock<HashMap<String, Admitted>>,
+}
+
+impl AdmissionController {
+    pub fn new(capacity: ResourceCapacity) -> Self {
+        Self { capacity, admitted: RwLock::new(HashMap::new()) }
+    }
+
+    /// 已准入策略的预算合计
+    fn committed(admitted: &HashMap<String, Admitted>) -> (f64, u64, u32) {
+        admitted.values().fold((0.0, 0, 0), |(cpu, mem, api), a| {
+            (cpu + a.requirements.cpu_cores, mem + a.requirements.memory_mb, api + a.requirements.api_rate_limit)
+        })
+    }
+
+    /// 检查剩余容量并准入策略；已准入的策略按新声明重新检查
+    pub fn admit(&self, strategy: &str, requirements: &ResourceRequirements) -> Result<(), AdmissionError> {
+        let mut admitted = self.admitted.write();
+        let previous = admitted.remove(strategy);
+        let (cpu, memory, api) = Self::committed(&admitted);
+        let check = if requirements.cpu_cores > self.capacity.cpu_cores - cpu + 1e-9 {
+            Err(AdmissionError::Cpu {
+                strategy: strategy.to_string(),
+                requested: requirements.cpu_cores,
+                available: (self.capacity.cpu_cores - cpu).max(0.0),
+            })
+        } else if requirements.memory_mb > self.capacity.memory_mb.saturating_sub(memory) {
+            Err(AdmissionError::Memory {
+                strategy: strategy.to_string(),
+                requested: requirements.memory_mb,
+                available: self.capacity.memory_mb.saturating_sub(memory),
+            })
+        } else if requirements.api_rate_limit > self.capacity.api_rate_limit.saturating_sub(api) {
+            Err(AdmissionError::ApiRate {
+                strategy: strategy.to_string(),
+                requested: requirements.api_rate_limit,
+                available: self.capacity.api_rate_limit.saturating_sub(api),
+            })
+        } else {
+            Ok(())
+        };
+
+        match check {
+            Ok(()) => {
+                let throttled_total = previous.map(|p| p.throttled_total).unwrap_or(0);
+                admitted.insert(strategy.to_string(), Admitted {
+                    requirements: requirements.clone(),
+                    window_start_ms: 0,
+                    calls_in_window: 0,
+                    throttled_total,
+                });
+                info!("✅ 策略 {} 通过资源准入: {:?}", strategy, requirements);
+                Ok(())
+            }
+            Err(e) => {
+                if let Some(previous) = previous {
+                    admitted.insert(strategy.to_string(), previous);
+                }
+                warn!("🚫 {}", e);
+                counter!("strategy_admission_rejected_total", "strategy" => strategy.to_string()).increment(1);
+                Err(e)
+            }
+        }
+    }
+
+    /// 释放策略占用的预算
+    pub fn release(&self, strategy: &str) {
+        self.admitted.write().remove(strategy);
+    }
+
+    pub fn is_admitted(&self, strategy: &str) -> bool {
+        self.admitted.read().contains_key(strategy)
+    }
+
+    /// 申请 `calls` 次 API 调用；超过声明速率（或未准入）时返回 false
+    pub fn try_acquire_api(&self, strategy: &str, calls: u32, now_ms: u64) -> bool {
+        let mut admitted = self.admitted.write();
+        let Some(entry) = admitted.get_mut(strategy) else { return false };
+        if now_ms.saturating_sub(entry.window_start_ms) >= 1_000 {
+            entry.window_start_ms = now_ms;
+            entry.calls_in_window = 0;
+        }
+        let allowed = entry.calls_in_window + calls <= entry.requirements.api_rate_limit;
+        if allowed {
+            entry.calls_in_window += calls;
+        } else {
+            entry.throttled_total += 1;
+            counter!("strategy_api_throttled_total", "strategy" => strategy.to_string()).increment(1);
+        }
+        let utilization = entry.calls_in_window as f64 / entry.requirements.api_rate_limit.max(1) as f64;
+        gauge!("strategy_api_budget_utilization", "strategy" => strategy.to_string()).set(utilization);
+        allowed
+    }
+
+    /// 各策略预算使用情况
+    pub fn utilization(&self) -> Vec<BudgetUtilization> {
+        let mut budgets: Vec<BudgetUtilization> = self
+            .admitted
+            .read()
+            .iter()
+            .map(|(strategy, a)| BudgetUtilization {
+                strategy: strategy.clone(),
+                requirements: a.requirements.clone(),
+                api_calls_current_window: a.calls_in_window,
+                api_utilization: a.calls_in_window as f64 / a.requirements.api_rate_limit.max(1) as f64,
+                throttled_total: a.throttled_total,
+            })
+            .collect();
+        budgets.sort_by(|a, b| a.strategy.cmp(&b.strategy));
+        budgets
+    }
+}
+
+impl Default for AdmissionController {
+    fn default() -> Self {
+        Self::new(ResourceCapacity::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_admission_and_api_throttling() {
+        let controller = AdmissionController::new(ResourceCapacity { cpu_cores: 2.0, memory_mb: 1_024, api_rate_limit: 15 });
+        let req = ResourceRequirements { cpu_cores: 1.0, memory_mb: 512, api_rate_limit: 10 };
+        controller.admit("inter_exchange", &req).unwrap();
+        assert!(matches!(controller.admit("triangular", &req), Err(AdmissionError::ApiRate { available: 5, .. })));
+        controller
+            .admit("triangular", &ResourceRequirements { api_rate_limit: 5, ..req.clone() })
+            .unwrap();
+        assert!(matches!(
+            controller.admit("third", &ResourceRequirements { cpu_cores: 0.1, memory_mb: 1, api_rate_limit: 0 }),
+            Err(AdmissionError::Cpu { .. })
+        ));
+
+        assert!(controller.try_acquire_api("triangular", 3, 1_000));
+        assert!(!controller.try_acquire_api("triangular", 3, 1_500));
+        assert!(controller.try_acquire_api("triangular", 3, 2_000));
+        assert!(!controller.try_acquire_api("unknown", 1, 2_000));
+        let budget = &controller.utilization()[1];
+        assert_eq!((budget.strategy.as_str(), budget.throttled_total), ("triangular", 1));
+
+        controller.release("inter_exchange");
+        assert!(controller.admit("third", &ResourceRequirements { cpu_cores: 0.1, memory_mb: 1, api_rate_limit: 0 }).is_ok());
+    }
+}
@@ -43,6 +43,20 @@ pub struct TriangularConfig {
     pub max_paths: usize,
     /// 缓存TTL（秒）
     pub cache_ttl_seconds: u64,
+    /// 环路最少腿数
+    #[serde(default = "default_min_path_length")]
+    pub min_path_length: usize,
+    /// 环路最多腿数
+    #[serde(default = "default_max_path_length")]
+    pub max_path_length: usize,
+}
+
+fn default_min_path_length() -> usize {
+    3
+}
+
+fn default_max_path_length() -> usize {
+    4
 }
 
 /// 风险控制配置
@@ -161,6 +175,12 @@ impl StrategyConfigFile {
                 cache_ttl_seconds: std::env::var("CELUE_CACHE_TTL_SECONDS")
                     .ok().and_then(|s| s.parse().ok())
                     .unwrap_or(60), // 保守默认：1分钟
+                min_path_length: std::env::var("CELUE_MIN_PATH_LENGTH")
+                    .ok().and_then(|s| s.parse().ok())
+                    .unwrap_or(3),
+                max_path_length: std::env::var("CELUE_MAX_PATH_LENGTH")
+                    .ok().and_then(|s| s.parse().ok())
+                    .unwrap_or(4),
             },
             risk: RiskConfig {
                 max_daily_loss_usd: std::env::var("CELUE_MAX_DAILY_LOSS_USD")
@@ -218,6 +238,7 @@ impl StrategyConfigFile {
         StrategyContextConfig {
             inter_exchange_slippage_per_leg_pct: self.inter_exchange.slippage_per_leg_pct,
             inter_exchange_min_liquidity_usd: self.inter_exchange.min_liquidity_usd,
+            ..Default::default()
         }
     }
 
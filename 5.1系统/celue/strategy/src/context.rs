@@ -5,9 +5,15 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use common::types::Exchange;
+use common::{ArbitrageOpportunity, SharedClock};
 use common::precision::FixedPrice;
 use crate::market_state::MarketState;
 use crate::config_loader::ConfigLoader;
+use crate::state_store::StrategyStateStore;
+use crate::fee_tiers::TieredFeeRepo;
+use adapters::analytics::MarketAnalytics;
+use adapters::microstructure::{MicrostructureMonitor, MicrostructureSignals, TimingDecision};
+use adapters::reference_data::{ReferenceDataService, ReferenceKind, ReferenceValue};
 
 // 策略指标类型定义
 pub type StrategyMetrics = Arc<adapters::metrics::AdapterMetrics>;
@@ -17,6 +23,8 @@ pub type StrategyMetrics = Arc<adapters::metrics::AdapterMetrics>;
 pub struct StrategyContextConfig {
     pub inter_exchange_slippage_per_leg_pct: f64,
     pub inter_exchange_min_liquidity_usd: f64,
+    /// 净利润率相差在此范围内的交易所对视为持平，按交易所权重选择
+    pub venue_tie_tolerance_pct: f64,
 }
 
 impl Default for StrategyContextConfig {
@@ -29,6 +37,9 @@ impl Default for StrategyContextConfig {
             inter_exchange_min_liquidity_usd: std::env::var("CELUE_CONTEXT_MIN_LIQUIDITY_USD")
                 .ok().and_then(|s| s.parse().ok())
                 .unwrap_or(50000.0), // 保守默认值：$50K
+            venue_tie_tolerance_pct: std::env::var("CELUE_CONTEXT_VENUE_TIE_TOLERANCE")
+                .ok().and_then(|s| s.parse().ok())
+                .unwrap_or(0.0002), // 2个基点
         }
     }
 }
@@ -39,14 +50,29 @@ impl Default for StrategyContextConfig {
 pub struct StrategyContext {
     pub fee_precision_repo: Arc<dyn FeePrecisionRepo>,
     min_profit_cache: Arc<parking_lot::RwLock<HashMap<String, FixedPrice>>>,
+    slippage_overrides: Arc<parking_lot::RwLock<HashMap<String, f64>>>,
     exchange_weights: Arc<parking_lot::RwLock<HashMap<String, f64>>>,
     current_market_state: Arc<parking_lot::RwLock<MarketState>>,
     strategy_metrics: StrategyMetrics,
     pub inter_exchange_slippage_per_leg_pct: f64,
     // v3.0算法不使用预配置的三角套利参数，全部动态计算
     pub inter_exchange_min_liquidity_usd: f64,
+    /// 交易所对持平容差（净利润率）
+    pub venue_tie_tolerance_pct: f64,
     // 配置加载器（可选）- 用于动态配置加载
     config_loader: Option<Arc<parking_lot::RwLock<ConfigLoader>>>,
+    // 行情分析（可选）- 波动率与价差统计
+    analytics: Option<Arc<MarketAnalytics>>,
+    // 微观结构信号（可选）- 盘口失衡、订单流毒性、报价闪烁
+    microstructure: Option<Arc<MicrostructureMonitor>>,
+    // 第三方参考数据（可选）- 参考价格、持仓量、爆仓量
+    reference_data: Option<Arc<ReferenceDataService>>,
+    // 策略状态持久化（可选）- 重启后恢复滚动统计
+    state_store: Option<Arc<StrategyStateStore>>,
+    // 成交量费率等级（可选）- 按30天成交额计算实际费率
+    fee_tiers: Option<Arc<TieredFeeRepo>>,
+    // 时间来源 - 回测/集成测试注入模拟时钟
+    clock: SharedClock,
 }
 
 impl StrategyContext {
@@ -66,12 +92,127 @@ impl StrategyContext {
         Self {
             fee_precision_repo: fee_repo,
             min_profit_cache: Arc::new(parking_lot::RwLock::new(HashMap::new())),
+            slippage_overrides: Arc::new(parking_lot::RwLock::new(HashMap::new())),
             exchange_weights: Arc::new(parking_lot::RwLock::new(HashMap::new())),
             current_market_state: Arc::new(parking_lot::RwLock::new(MarketState::Regular)),
             strategy_metrics,
             inter_exchange_slippage_per_leg_pct: config.inter_exchange_slippage_per_leg_pct,
             inter_exchange_min_liquidity_usd: config.inter_exchange_min_liquidity_usd,
+            venue_tie_tolerance_pct: config.venue_tie_tolerance_pct,
             config_loader: None, // 默认不启用配置加载器
+            analytics: None,
+            microstructure: None,
+            reference_data: None,
+            state_store: None,
+            fee_tiers: None,
+            clock: common::clock::system_clock(),
+        }
+    }
+
+    /// 注入时钟，策略内所有“当前时间”由它给出
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn clock(&self) -> SharedClock {
+        self.clock.clone()
+    }
+
+    /// 当前纳秒时间戳
+    pub fn now_ns(&self) -> u64 {
+        self.clock.now_ns()
+    }
+
+    /// 接入微观结构信号，供策略判断执行时机
+    pub fn with_microstructure(mut self, monitor: Arc<MicrostructureMonitor>) -> Self {
+        self.microstructure = Some(monitor);
+        self
+    }
+
+    /// 交易所+币对的微观结构信号
+    pub fn get_microstructure(&self, exchange: &str, symbol: &str) -> Option<MicrostructureSignals> {
+        self.microstructure.as_ref().and_then(|m| m.signals(exchange, symbol))
+    }
+
+    /// 机会的执行时机建议；未接入信号时直接执行
+    pub fn execution_timing(&self, opportunity: &ArbitrageOpportunity) -> TimingDecision {
+        self.microstructure
+            .as_ref()
+            .map(|m| m.assess(opportunity))
+            .unwrap_or(TimingDecision::Proceed)
+    }
+
+    /// 接入策略状态存储
+    pub fn with_state_store(mut self, store: Arc<StrategyStateStore>) -> Self {
+        self.state_store = Some(store);
+        self
+    }
+
+    /// 策略状态存储，未接入时策略每次启动重新预热
+    pub fn state_store(&self) -> Option<&Arc<StrategyStateStore>> {
+        self.state_store.as_ref()
+    }
+
+    /// 接入第三方参考数据，作为辅助信号
+    pub fn with_reference_data(mut self, reference_data: Arc<ReferenceDataService>) -> Self {
+        self.reference_data = Some(reference_data);
+        self
+    }
+
+    /// 未过期的第三方参考数据
+    pub fn get_reference_signal(&self, asset: &str, kind: ReferenceKind) -> Option<ReferenceValue> {
+        self.reference_data.as_ref().and_then(|r| r.latest(asset, kind))
+    }
+
+    /// 资产的第三方参考价格（USD）
+    pub fn get_reference_price(&self, asset: &str) -> Option<f64> {
+        self.reference_data.as_ref().and_then(|r| r.reference_price(asset))
+    }
+
+    /// 接入行情分析模块，波动率与价差查询改用实时统计
+    pub fn with_analytics(mut self, analytics: Arc<MarketAnalytics>) -> Self {
+        self.analytics = Some(analytics);
+        self
+    }
+
+    /// 日波动率；尚无统计数据时使用环境变量配置的后备值
+    pub fn get_volatility(&self, symbol: &str) -> f64 {
+        self.analytics
+            .as_ref()
+            .and_then(|a| a.daily_volatility(symbol))
+            .unwrap_or_else(|| {
+                std::env::var("CELUE_FALLBACK_VOLATILITY")
+                    .ok().and_then(|s| s.parse().ok())
+                    .unwrap_or(0.05)
+            })
+    }
+
+    /// 各交易所平均买卖价差（基点）
+    pub fn get_avg_spread_bps(&self, symbol: &str) -> Option<f64> {
+        self.analytics.as_ref().and_then(|a| a.avg_spread_bps(symbol))
+    }
+
+    /// 价差偏离的半衰期（秒）
+    pub fn get_spread_half_life_secs(&self, symbol: &str) -> Option<f64> {
+        self.analytics.as_ref().and_then(|a| a.spread_half_life_secs(symbol))
+    }
+
+    /// 接入成交量费率等级，费率查询改按当前等级；未配置等级表的交易所仍用原仓库
+    pub fn with_fee_tiers(mut self, fee_tiers: Arc<TieredFeeRepo>) -> Self {
+        self.fee_precision_repo = fee_tiers.clone();
+        self.fee_tiers = Some(fee_tiers);
+        self
+    }
+
+    pub fn fee_tiers(&self) -> Option<&Arc<TieredFeeRepo>> {
+        self.fee_tiers.as_ref()
+    }
+
+    /// 记录成交额以更新费率等级；`slices` 为空时按腿的计划数量计
+    pub fn record_traded_volume(&self, opportunity: &ArbitrageOpportunity, slices: &[common::SliceReport]) {
+        if let Some(fee_tiers) = &self.fee_tiers {
+            fee_tiers.record_fills(opportunity, slices);
         }
     }
 
@@ -133,6 +274,25 @@ impl StrategyContext {
         self.min_profit_cache.read().get(strategy).copied()
     }
 
+    /// 策略生效的最小利润率：策略专属值优先，否则使用全局值
+    pub fn min_profit_pct_for(&self, strategy: &str) -> FixedPrice {
+        self.get_min_profit_for_strategy(strategy)
+            .unwrap_or_else(|| self.current_min_profit_pct())
+    }
+
+    pub fn set_slippage_per_leg_for_strategy(&self, strategy: &str, slippage_pct: f64) {
+        self.slippage_overrides.write().insert(strategy.to_string(), slippage_pct);
+    }
+
+    /// 策略生效的单腿滑点缓冲：策略专属值优先，否则使用配置值
+    pub fn slippage_per_leg_pct_for(&self, strategy: &str) -> f64 {
+        self.slippage_overrides
+            .read()
+            .get(strategy)
+            .copied()
+            .unwrap_or(self.inter_exchange_slippage_per_leg_pct)
+    }
+
     pub fn update_market_state(&self, new_state: MarketState) {
         *self.current_market_state.write() = new_state;
     }
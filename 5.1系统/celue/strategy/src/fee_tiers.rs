@@ -0,0 +1,326 @@
+//! 手续费等级跟踪 - 基于30天滚动成交量
+//!
+//! 交易所按我方近30天成交量划分费率等级，实际费率往往低于公开费率。
+//! 按交易所累计成交额（来自执行结果），对照配置中的等级表得到当前
+//! maker/taker 费率，并通过 `FeePrecisionRepo` 暴露给
+//! `StrategyContext::get_taker_fee`，使机会利润按实际等级计算。
+//! 配置 `volume_file` 时成交额落盘，重启后不会退回最低等级。
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::Result;
+use common::precision::FixedPrice;
+use common::types::Exchange;
+use common::{ArbitrageOpportunity, ExecutionResult, SharedClock, SliceReport};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::context::FeePrecisionRepo;
+
+/// 滚动窗口天数
+pub const ROLLING_WINDOW_DAYS: i64 = 30;
+const NS_PER_DAY: i64 = 86_400 * 1_000_000_000;
+
+/// 单个费率等级
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeTier {
+    pub name: String,
+    /// 达到该等级所需的30天成交额（USD）
+    pub min_volume_usd: f64,
+    pub maker_fee: f64,
+    pub taker_fee: f64,
+}
+
+/// 各交易所等级表配置，键为交易所名
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeeTierConfig {
+    pub exchanges: HashMap<String, Vec<FeeTier>>,
+    /// 30天成交额持久化文件（JSON），未配置时只保存在内存
+    #[serde(default)]
+    pub volume_file: Option<PathBuf>,
+}
+
+impl FeeTierConfig {
+    /// 从 TOML 文件加载
+    pub fn load_file(path: impl AsRef<Path>) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(toml::from_str(&content)?)
+    }
+}
+
+/// 按交易所、按天累计成交额
+#[derive(Debug, Default)]
+pub struct VolumeAccumulator {
+    /// exchange -> (day index -> volume)
+    daily: RwLock<HashMap<String, BTreeMap<i64, f64>>>,
+}
+
+impl VolumeAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, exchange: &str, notional_usd: f64, timestamp_ns: i64) {
+        let day = timestamp_ns.div_euclid(NS_PER_DAY);
+        let mut daily = self.daily.write();
+        let days = daily.entry(exchange.to_lowercase()).or_default();
+        *days.entry(day).or_default() += notional_usd.abs();
+        // 丢弃窗口之外的数据
+        days.retain(|d, _| *d > day - ROLLING_WINDOW_DAYS);
+    }
+
+    /// 截至 `now_ns` 的30天成交额（含当天）
+    pub fn rolling_volume(&self, exchange: &str, now_ns: i64) -> f64 {
+        let today = now_ns.div_euclid(NS_PER_DAY);
+        self.daily
+            .read()
+            .get(&exchange.to_lowercase())
+            .map(|days| days.range(today - ROLLING_WINDOW_DAYS + 1..=today).map(|(_, v)| v).sum())
+            .unwrap_or(0.0)
+    }
+
+    /// 写入文件：先写临时文件再改名，避免崩溃留下半截文件
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec(&*self.daily.read())?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// 从文件恢复，返回恢复的交易所数
+    pub fn load(&self, path: &Path) -> Result<usize> {
+        let daily: HashMap<String, BTreeMap<i64, f64>> = serde_json::from_slice(&std::fs::read(path)?)?;
+        let count = daily.len();
+        *self.daily.write() = daily;
+        Ok(count)
+    }
+}
+
+/// 按实际成交量等级调整费率的仓库，未配置等级表的交易所回落到内层仓库
+pub struct TieredFeeRepo {
+    inner: Arc<dyn FeePrecisionRepo>,
+    tiers: HashMap<String, Vec<FeeTier>>,
+    volumes: Arc<VolumeAccumulator>,
+    /// 当前生效的等级名，用于等级变化日志
+    current: RwLock<HashMap<String, String>>,
+    volume_file: Option<PathBuf>,
+    clock: SharedClock,
+}
+
+impl TieredFeeRepo {
+    pub fn new(inner: Arc<dyn FeePrecisionRepo>, config: FeeTierConfig) -> Self {
+        let tiers = config
+            .exchanges
+            .into_iter()
+            .map(|(exchange, mut tiers)| {
+                tiers.sort_by(|a, b| a.min_volume_usd.total_cmp(&b.min_volume_usd));
+                (exchange.to_lowercase(), tiers)
+            })
+            .collect();
+        let volumes = Arc::new(VolumeAccumulator::new());
+        if let Some(path) = config.volume_file.as_deref().filter(|p| p.exists()) {
+            match volumes.load(path) {
+                Ok(count) => info!("💱 已恢复 {} 个交易所的30天成交额: {}", count, path.display()),
+                Err(e) => warn!("⚠️ 恢复30天成交额失败 {}: {}", path.display(), e),
+            }
+        }
+        Self {
+            inner,
+            tiers,
+            volumes,
+            current: RwLock::new(HashMap::new()),
+            volume_file: config.volume_file,
+            clock: common::clock::system_clock(),
+        }
+    }
+
+    /// 滚动成交额窗口按该时钟计算
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn volumes(&self) -> Arc<VolumeAccumulator> {
+        self.volumes.clone()
+    }
+
+    /// 用成功的执行结果累计各腿所在交易所的成交额
+    pub fn record_execution(&self, opportunity: &ArbitrageOpportunity, result: &ExecutionResult) {
+        if result.success {
+            self.record_fills(opportunity, &result.slices);
+        }
+    }
+
+    /// 累计已成交机会的成交额；有拆单明细时用实际成交，否则用腿的计划数量
+    pub fn record_fills(&self, opportunity: &ArbitrageOpportunity, slices: &[SliceReport]) {
+        let now_ns = self.clock.now_ns() as i64;
+        for (index, leg) in opportunity.legs.iter().enumerate() {
+            let slices: Vec<_> = slices.iter().filter(|s| s.leg_index == index).collect();
+            let notional = if slices.is_empty() {
+                leg.price.to_f64() * leg.quantity.to_f64()
+            } else {
+                slices.iter().map(|s| s.price * s.quantity).sum()
+            };
+            self.volumes.record(leg.exchange.as_str(), notional, now_ns);
+        }
+        for leg in &opportunity.legs {
+            self.refresh_tier(leg.exchange.as_str(), now_ns);
+        }
+        if let Some(path) = &self.volume_file {
+            if let Err(e) = self.volumes.save(path) {
+                warn!("⚠️ 保存30天成交额失败 {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    /// 当前等级；未配置等级表时为 None
+    pub fn tier_at(&self, exchange: &str, now_ns: i64) -> Option<FeeTier> {
+        let tiers = self.tiers.get(&exchange.to_lowercase())?;
+        let volume = self.volumes.rolling_volume(exchange, now_ns);
+        tiers.iter().rev().find(|t| volume >= t.min_volume_usd).or(tiers.first()).cloned()
+    }
+
+    fn refresh_tier(&self, exchange: &str, now_ns: i64) {
+        let Some(tier) = self.tier_at(exchange, now_ns) else { return };
+        let previous = self.current.write().insert(exchange.to_lowercase(), tier.name.clone());
+        if previous.as_deref() != Some(tier.name.as_str()) {
+            info!(
+                "💱 {} 手续费等级变更为 {} (maker {:.4}%, taker {:.4}%)",
+                exchange, tier.name, tier.maker_fee * 100.0, tier.taker_fee * 100.0
+            );
+        }
+    }
+
+    fn tier_now(&self, exchange: &str) -> Option<FeeTier> {
+        self.tier_at(exchange, self.clock.now_ns() as i64)
+    }
+}
+
+impl FeePrecisionRepo for TieredFeeRepo {
+    fn get_taker_fee(&self, exchange: &Exchange) -> Option<FixedPrice> {
+        match self.tier_now(exchange.as_str()) {
+            Some(tier) => Some(FixedPrice::from_f64(tier.taker_fee, 6)),
+            None => self.inner.get_taker_fee(exchange),
+        }
+    }
+
+    fn get_maker_fee(&self, exchange: &Exchange) -> Option<FixedPrice> {
+        match self.tier_now(exchange.as_str()) {
+            Some(tier) => Some(FixedPrice::from_f64(tier.maker_fee, 6)),
+            None => self.inner.get_maker_fee(exchange),
+        }
+    }
+
+    fn get_price_precision(&self, exchange: &Exchange, symbol: &str) -> Option<u8> {
+        self.inner.get_price_precision(exchange, symbol)
+    }
+
+    fn get_quantity_precision(&self, exchange: &Exchange, symbol: &str) -> Option<u8> {
+        self.inner.get_quantity_precision(exchange, symbol)
+    }
+
+    fn get_step_size_for_symbol(&self, symbol: &str) -> Option<f64> {
+        self.inner.get_step_size_for_symbol(symbol)
+    }
+
+    fn get_tick_size_for_symbol(&self, symbol: &str) -> Option<f64> {
+        self.inner.get_tick_size_for_symbol(symbol)
+    }
+
+    fn get_fee_rate_bps_for_exchange(&self, exchange: &str) -> Option<f64> {
+        match self.tier_now(exchange) {
+            Some(tier) => Some(tier.taker_fee * 10_000.0),
+            None => self.inner.get_fee_rate_bps_for_exchange(exchange),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::FeePrecisionRepoImpl;
+
+    const TIERS: &str = r#"
+        [[exchanges.binance]]
+        name = "VIP0"
+        min_volume_usd = 0.0
+        maker_fee = 0.001
+        taker_fee = 0.001
+
+        [[exchanges.binance]]
+        name = "VIP1"
+        min_volume_usd = 1000000.0
+        maker_fee = 0.0009
+        taker_fee = 0.0008
+        "#;
+
+    #[test]
+    fn test_tier_follows_rolling_volume() {
+        let config: FeeTierConfig = toml::from_str(TIERS).unwrap();
+        let repo = TieredFeeRepo::new(Arc::new(FeePrecisionRepoImpl::default()), config);
+        let day = NS_PER_DAY;
+
+        repo.volumes().record("binance", 600_000.0, 10 * day);
+        assert_eq!(repo.tier_at("binance", 10 * day).unwrap().name, "VIP0");
+        repo.volumes().record("binance", 600_000.0, 20 * day);
+        assert_eq!(repo.tier_at("binance", 20 * day).unwrap().name, "VIP1");
+        // 第一笔滑出30天窗口后降级
+        assert_eq!(repo.tier_at("binance", 40 * day).unwrap().name, "VIP0");
+
+        // 未配置等级表的交易所使用默认费率
+        assert!(repo.tier_at("okx", 10 * day).is_none());
+        assert_eq!(
+            repo.get_taker_fee(&Exchange::new("okx")),
+            FeePrecisionRepoImpl::default().get_taker_fee(&Exchange::new("okx"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_context_fee_follows_persisted_volume() {
+        use common::clock::SimulatedClock;
+        use common::testing::{inter_exchange_opportunity, leg, opportunity_with_legs};
+        use common::arbitrage::Side;
+        use crate::context::StrategyContext;
+
+        let path = std::env::temp_dir().join(format!("fee_tiers_{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut config: FeeTierConfig = toml::from_str(TIERS).unwrap();
+        config.volume_file = Some(path.clone());
+        let clock: SharedClock = Arc::new(SimulatedClock::from_nanos(10 * NS_PER_DAY as u64));
+
+        let tiers = Arc::new(TieredFeeRepo::new(Arc::new(FeePrecisionRepoImpl::default()), config.clone()).with_clock(clock.clone()));
+        let context = StrategyContext::new(
+            Arc::new(FeePrecisionRepoImpl::default()),
+            Arc::new(adapters::metrics::AdapterMetrics::new()),
+        )
+        .with_fee_tiers(tiers);
+        let binance = Exchange::new("binance");
+        assert_eq!(context.get_taker_fee(&binance).unwrap().to_f64(), 0.001);
+
+        // 两腿都在 binance，各 60 万 USD 名义成交额
+        let opportunity = opportunity_with_legs(
+            "inter_exchange",
+            vec![
+                leg("binance", "BTCUSDT", Side::Buy, 60_000.0, 10.0),
+                leg("binance", "BTCUSDT", Side::Sell, 60_000.0, 10.0),
+            ],
+            0.01,
+            0,
+        );
+        context.record_traded_volume(&opportunity, &[]);
+        assert_eq!(context.get_taker_fee(&binance).unwrap().to_f64(), 0.0008);
+
+        // 失败的执行不计入成交额
+        let failed = ExecutionResult::rejected("x".into(), "rejected".into(), None);
+        context.fee_tiers().unwrap().record_execution(&inter_exchange_opportunity("inter_exchange", "BTCUSDT", 0.01), &failed);
+
+        // 重启后从文件恢复，等级保持不变
+        let restored = TieredFeeRepo::new(Arc::new(FeePrecisionRepoImpl::default()), config).with_clock(clock);
+        assert_eq!(restored.volumes().rolling_volume("binance", 10 * NS_PER_DAY), 1_200_000.0);
+        assert_eq!(restored.tier_now("binance").unwrap().name, "VIP1");
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -14,11 +14,19 @@ pub mod plugins;
 pub mod config_loader;
 pub mod depth_analysis;
 pub mod dynamic_fee_calculator;
+pub mod fee_tiers;
+pub mod threshold_controller;
+pub mod path_discovery;
+pub mod listing_watcher;
+pub mod scoring;
+pub mod staleness_guard;
+pub mod admission;
+pub mod state_store;
 
 pub use context::{StrategyContext, StrategyContextConfig, FeePrecisionRepo, FeePrecisionRepoImpl};
 pub use market_state::{MarketState, AtomicMarketState};
 pub use min_profit::MinProfitModel;
-pub use traits::{ArbitrageStrategy, ExecutionResult};
+pub use traits::{ArbitrageStrategy, ExecutionResult, ResourceRequirements};
 
 /// Strategy configuration
 #[derive(Debug, Clone)]
@@ -0,0 +1,270 @@
+//! 上市/下市监控
+//!
+//! 定期拉取各交易所的产品列表（exchangeInfo / instruments），与上一次
+//! 结果比对，找出与配置币种相关的新上市和下市交易对。每次轮询后整体
+//! 替换 `SymbolRegistry` 中该交易所的规则，并增量同步三角环路图；
+//! 下市交易对的基础币在该交易所仍有持仓时发出严重告警通知值班人员。
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use adapters::alerting::{Alert, AlertManager, AlertSeverity};
+use adapters::funds::{AssetBalance, FundsAdapter};
+use adapters::symbols::SymbolMetadataService;
+use adapters::AdapterResult;
+use async_trait::async_trait;
+use common::symbols::{SymbolRegistry, SymbolSpec};
+use metrics::counter;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::path_discovery::{Listing, PathDiscovery};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListingWatcherConfig {
+    pub poll_interval: Duration,
+    pub exchanges: Vec<String>,
+    /// 关注的币种；交易对的基础币或计价币命中其一即视为相关，为空表示全部
+    pub bases: Vec<String>,
+}
+
+impl Default for ListingWatcherConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(300),
+            exchanges: vec!["binance".to_string(), "okx".to_string(), "bybit".to_string()],
+            bases: Vec::new(),
+        }
+    }
+}
+
+/// 交易所产品列表来源
+#[async_trait]
+pub trait ListingSource: Send + Sync {
+    async fn fetch(&self, exchange: &str) -> AdapterResult<Vec<SymbolSpec>>;
+}
+
+#[async_trait]
+impl ListingSource for SymbolMetadataService {
+    async fn fetch(&self, exchange: &str) -> AdapterResult<Vec<SymbolSpec>> {
+        SymbolMetadataService::fetch(self, exchange).await
+    }
+}
+
+/// 交易对宇宙变化
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ListingEvent {
+    Listed { spec: SymbolSpec },
+    /// `positions` 为该交易所上基础币的非零持仓
+    Delisted { spec: SymbolSpec, positions: Vec<AssetBalance> },
+}
+
+/// 上市/下市监控器
+pub struct ListingWatcher {
+    config: ListingWatcherConfig,
+    source: Arc<dyn ListingSource>,
+    registry: Arc<SymbolRegistry>,
+    paths: Option<Arc<PathDiscovery>>,
+    funds: Option<Arc<FundsAdapter>>,
+    alerts: Option<Arc<AlertManager>>,
+    /// 交易所 -> 规范名 -> 上次看到的相关交易对
+    known: RwLock<HashMap<String, HashMap<String, SymbolSpec>>>,
+}
+
+impl ListingWatcher {
+    pub fn new(config: ListingWatcherConfig, source: Arc<dyn ListingSource>, registry: Arc<SymbolRegistry>) -> Self {
+        let bases = config.bases.iter().map(|b| b.to_uppercase()).collect();
+        Self {
+            config: ListingWatcherConfig { bases, ..config },
+            source,
+            registry,
+            paths: None,
+            funds: None,
+            alerts: None,
+            known: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 上市/下市时同步三角环路图
+    pub fn with_path_discovery(mut self, paths: Arc<PathDiscovery>) -> Self {
+        self.paths = Some(paths);
+        self
+    }
+
+    /// 下市时检查持仓
+    pub fn with_funds(mut self, funds: Arc<FundsAdapter>) -> Self {
+        self.funds = Some(funds);
+        self
+    }
+
+    pub fn with_alerts(mut self, alerts: Arc<AlertManager>) -> Self {
+        self.alerts = Some(alerts);
+        self
+    }
+
+    fn relevant(&self, spec: &SymbolSpec) -> bool {
+        self.config.bases.is_empty()
+            || self.config.bases.iter().any(|b| *b == spec.base || *b == spec.quote)
+    }
+
+    /// 轮询全部交易所；单个交易所失败时保留其上次结果
+    pub async fn poll_once(&self) -> Vec<ListingEvent> {
+        let mut events = Vec::new();
+        for exchange in &self.config.exchanges {
+            match self.source.fetch(exchange).await {
+                Ok(specs) if !specs.is_empty() => events.extend(self.apply(exchange, specs)),
+                Ok(_) => warn!("⚠️ {} 产品列表为空，保留上次结果", exchange),
+                Err(e) => warn!("⚠️ 拉取 {} 产品列表失败: {}", exchange, e),
+            }
+        }
+        events
+    }
+
+    /// 应用一次交易所产品列表，返回相对上次的变化；首次只建立基线
+    pub fn apply(&self, exchange: &str, specs: Vec<SymbolSpec>) -> Vec<ListingEvent> {
+        let exchange = exchange.to_lowercase();
+        let current: HashMap<String, SymbolSpec> = specs
+            .iter()
+            .filter(|s| self.relevant(s))
+            .map(|s| (s.canonical.clone(), SymbolSpec { exchange: exchange.clone(), ..s.clone() }))
+            .collect();
+        self.registry.replace_exchange(&exchange, specs);
+
+        let previous = self.known.write().insert(exchange.clone(), current.clone());
+        if let Some(paths) = &self.paths {
+            let listings: Vec<Listing> = current.values().map(to_listing).collect();
+            paths.sync_listings(&exchange, &listings);
+        }
+        let Some(previous) = previous else {
+            info!("📋 {} 基线: {} 个相关交易对", exchange, current.len());
+            return Vec::new();
+        };
+
+        let before: HashSet<&String> = previous.keys().collect();
+        let after: HashSet<&String> = current.keys().collect();
+        let mut events = Vec::new();
+        for symbol in after.difference(&before) {
+            let spec = current[*symbol].clone();
+            info!("🆕 {} 上市 {}", exchange, spec.canonical);
+            counter!("listing_events_total", "exchange" => exchange.clone(), "kind" => "listed").increment(1);
+            events.push(ListingEvent::Listed { spec });
+        }
+        for symbol in before.difference(&after) {
+            let spec = previous[*symbol].clone();
+            let positions = self.positions(&exchange, &spec.base);
+            counter!("listing_events_total", "exchange" => exchange.clone(), "kind" => "delisted").increment(1);
+            self.notify_delisting(&spec, &positions);
+            events.push(ListingEvent::Delisted { spec, positions });
+        }
+        events
+    }
+
+    fn positions(&self, exchange: &str, asset: &str) -> Vec<AssetBalance> {
+        let Some(funds) = &self.funds else { return Vec::new() };
+        funds
+            .balances()
+            .into_iter()
+            .filter(|b| b.exchange.eq_ignore_ascii_case(exchange) && b.asset.eq_ignore_ascii_case(asset) && b.total > 0.0)
+            .collect()
+    }
+
+    fn notify_delisting(&self, spec: &SymbolSpec, positions: &[AssetBalance]) {
+        let (severity, message) = if positions.is_empty() {
+            warn!("📉 {} 下市 {}", spec.exchange, spec.canonical);
+            (AlertSeverity::Warning, format!("{} delisted {}, no open position", spec.exchange, spec.canonical))
+        } else {
+            let held: f64 = positions.iter().map(|p| p.total).sum();
+            error!("🚨 {} 下市 {}，仍持有 {} {}", spec.exchange, spec.canonical, held, spec.base);
+            (
+                AlertSeverity::Critical,
+                format!("{} delisted {} while holding {} {}; unwind or withdraw", spec.exchange, spec.canonical, held, spec.base),
+            )
+        };
+        let Some(alerts) = self.alerts.clone() else { return };
+        let alert = Alert::new(
+            &format!("delisting:{}:{}", spec.exchange, spec.canonical),
+            severity,
+            &format!("{} delisted on {}", spec.canonical, spec.exchange),
+            &message,
+            "listing_watcher",
+        );
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            handle.spawn(async move {
+                alerts.raise(alert).await;
+            });
+        }
+    }
+
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.config.poll_interval);
+            loop {
+                ticker.tick().await;
+                self.poll_once().await;
+            }
+        })
+    }
+}
+
+fn to_listing(spec: &SymbolSpec) -> Listing {
+    Listing {
+        exchange: spec.exchange.clone(),
+        symbol: spec.canonical.clone(),
+        base: spec.base.clone(),
+        quote: spec.quote.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adapters::funds::FundsConfig;
+
+    struct NoSource;
+
+    #[async_trait]
+    impl ListingSource for NoSource {
+        async fn fetch(&self, _exchange: &str) -> AdapterResult<Vec<SymbolSpec>> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[test]
+    fn test_listing_diff_updates_registry_paths_and_flags_positions() {
+        let registry = Arc::new(SymbolRegistry::new());
+        let paths = Arc::new(PathDiscovery::default());
+        let funds = Arc::new(FundsAdapter::new(FundsConfig::default()));
+        funds.update_balance(AssetBalance {
+            asset: "SOL".to_string(),
+            exchange: "binance".to_string(),
+            free: 3.0,
+            locked: 0.0,
+            total: 3.0,
+            updated_ns: 0,
+        });
+        let config = ListingWatcherConfig { bases: vec!["btc".into(), "usdt".into()], ..Default::default() };
+        let watcher = ListingWatcher::new(config, Arc::new(NoSource), registry.clone())
+            .with_path_discovery(paths.clone())
+            .with_funds(funds);
+
+        let spec = |native: &str, base: &str, quote: &str| SymbolSpec::new("binance", native, base, quote);
+        let baseline = vec![spec("BTCUSDT", "BTC", "USDT"), spec("SOLUSDT", "SOL", "USDT"), spec("SOLBTC", "SOL", "BTC")];
+        assert!(watcher.apply("binance", baseline).is_empty());
+        assert_eq!(paths.cycle_count(), 1);
+
+        let next = vec![spec("BTCUSDT", "BTC", "USDT"), spec("ETHUSDT", "ETH", "USDT"), spec("SOLBTC", "SOL", "BTC")];
+        let events = watcher.apply("binance", next);
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().any(|e| matches!(e, ListingEvent::Listed { spec } if spec.canonical == "ETH/USDT")));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            ListingEvent::Delisted { spec, positions } if spec.canonical == "SOL/USDT" && positions.len() == 1
+        )));
+        assert!(registry.spec("binance", "SOLUSDT").is_none());
+        assert!(registry.spec("binance", "ETH/USDT").is_some());
+        assert_eq!(paths.cycle_count(), 0);
+    }
+}
@@ -0,0 +1,342 @@
+//! 多币种环路发现 - 增量更新的货币图
+//!
+//! 按交易所维护上市交易对构成的货币图，发现长度在
+//! `min_path_length..=max_path_length` 之间的所有环路（3/4 腿），
+//! 在上市/下市时只增量更新受影响的环路，并按历史收益和流动性
+//! 为三角策略提供优先级排序后的路径。
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+
+/// 一条上市交易对（图中的一条无向边）
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Listing {
+    pub exchange: String,
+    pub symbol: String,
+    pub base: String,
+    pub quote: String,
+}
+
+/// 发现的环路
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CyclePath {
+    pub exchange: String,
+    /// 币种序列（首币种不重复出现在末尾）
+    pub currencies: Vec<String>,
+    /// 对应每一步的交易对符号
+    pub symbols: Vec<String>,
+    /// 历史边际收益（bps，EWMA）
+    pub historical_edge_bps: f64,
+    /// 最小腿流动性（USD）
+    pub min_liquidity_usd: f64,
+}
+
+impl CyclePath {
+    /// 优先级：历史收益 × 流动性（对数压缩）
+    pub fn priority(&self) -> f64 {
+        self.historical_edge_bps.max(0.0) * (1.0 + self.min_liquidity_usd.max(0.0)).ln()
+    }
+}
+
+/// 环路唯一键：交易所 + 交易对集合（与遍历方向和起点无关）
+type CycleKey = (String, BTreeSet<String>);
+
+/// 路径发现配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathDiscoveryConfig {
+    pub min_path_length: usize,
+    pub max_path_length: usize,
+    /// 历史收益 EWMA 平滑系数
+    pub edge_ewma_alpha: f64,
+}
+
+impl Default for PathDiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            min_path_length: 3,
+            max_path_length: 4,
+            edge_ewma_alpha: 0.1,
+        }
+    }
+}
+
+#[derive(Default)]
+struct ExchangeGraph {
+    /// 币种 -> (相邻币种 -> 交易对符号)
+    adjacency: HashMap<String, HashMap<String, String>>,
+    /// 已上市交易对（保留 base/quote 方向，邻接表是无向的）
+    listings: HashSet<Listing>,
+}
+
+/// 增量环路发现器
+pub struct PathDiscovery {
+    config: PathDiscoveryConfig,
+    graphs: RwLock<HashMap<String, ExchangeGraph>>,
+    cycles: RwLock<HashMap<CycleKey, CyclePath>>,
+    liquidity: RwLock<HashMap<(String, String), f64>>,
+}
+
+impl PathDiscovery {
+    pub fn new(config: PathDiscoveryConfig) -> Self {
+        let min = config.min_path_length.max(3);
+        let max = config.max_path_length.max(min);
+        Self {
+            config: PathDiscoveryConfig { min_path_length: min, max_path_length: max, ..config },
+            graphs: RwLock::new(HashMap::new()),
+            cycles: RwLock::new(HashMap::new()),
+            liquidity: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 新增上市交易对，仅发现包含该边的新环路；返回新增环路数
+    ///
+    /// 已存在的交易对直接返回 0。
+    pub fn add_listing(&self, listing: &Listing) -> usize {
+        {
+            let mut graphs = self.graphs.write();
+            let graph = graphs.entry(listing.exchange.clone()).or_default();
+            if !graph.listings.insert(listing.clone()) {
+                return 0;
+            }
+            graph.adjacency.entry(listing.base.clone()).or_default()
+                .insert(listing.quote.clone(), listing.symbol.clone());
+            graph.adjacency.entry(listing.quote.clone()).or_default()
+                .insert(listing.base.clone(), listing.symbol.clone());
+        }
+
+        let found = self.cycles_through_edge(listing);
+        let mut cycles = self.cycles.write();
+        let mut added = 0;
+        for (currencies, symbols) in found {
+            let key = (listing.exchange.clone(), symbols.iter().cloned().collect());
+            if cycles.contains_key(&key) {
+                continue;
+            }
+            let min_liquidity_usd = self.min_liquidity(&listing.exchange, &symbols);
+            cycles.insert(key, CyclePath {
+                exchange: listing.exchange.clone(),
+                currencies,
+                symbols,
+                historical_edge_bps: 0.0,
+                min_liquidity_usd,
+            });
+            added += 1;
+        }
+        added
+    }
+
+    /// 下市交易对，删除所有包含该边的环路；返回删除数
+    pub fn remove_listing(&self, listing: &Listing) -> usize {
+        {
+            let mut graphs = self.graphs.write();
+            if let Some(graph) = graphs.get_mut(&listing.exchange) {
+                if let Some(n) = graph.adjacency.get_mut(&listing.base) {
+                    n.remove(&listing.quote);
+                }
+                if let Some(n) = graph.adjacency.get_mut(&listing.quote) {
+                    n.remove(&listing.base);
+                }
+                graph.listings.remove(listing);
+            }
+        }
+        let mut cycles = self.cycles.write();
+        let before = cycles.len();
+        cycles.retain(|(exchange, symbols), _| {
+            !(exchange == &listing.exchange && symbols.contains(&listing.symbol))
+        });
+        before - cycles.len()
+    }
+
+    /// 用交易所当前上市列表整体同步（内部按差异增量更新）
+    pub fn sync_listings(&self, exchange: &str, listings: &[Listing]) {
+        let current: HashSet<Listing> = self.listings(exchange).into_iter().collect();
+        let next: HashSet<Listing> = listings.iter().cloned().collect();
+        for removed in current.difference(&next) {
+            self.remove_listing(removed);
+        }
+        for added in next.difference(&current) {
+            self.add_listing(added);
+        }
+    }
+
+    fn listings(&self, exchange: &str) -> Vec<Listing> {
+        self.graphs
+            .read()
+            .get(exchange)
+            .map(|graph| graph.listings.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// 深度优先搜索：从 quote 出发回到 base 的简单路径，再补上新边构成环路
+    fn cycles_through_edge(&self, listing: &Listing) -> Vec<(Vec<String>, Vec<String>)> {
+        let graphs = self.graphs.read();
+        let Some(graph) = graphs.get(&listing.exchange) else { return Vec::new() };
+
+        let mut results = Vec::new();
+        let mut path = vec![listing.base.clone(), listing.quote.clone()];
+        let mut symbols = vec![listing.symbol.clone()];
+        self.dfs(graph, &listing.base, &mut path, &mut symbols, &mut results);
+        results
+    }
+
+    fn dfs(
+        &self,
+        graph: &ExchangeGraph,
+        target: &str,
+        path: &mut Vec<String>,
+        symbols: &mut Vec<String>,
+        results: &mut Vec<(Vec<String>, Vec<String>)>,
+    ) {
+        let current = path.last().cloned().unwrap_or_default();
+        let Some(neighbours) = graph.adjacency.get(&current) else { return };
+
+        for (next, symbol) in neighbours {
+            if symbols.contains(symbol) {
+                continue;
+            }
+            let legs = symbols.len() + 1;
+            if next == target {
+                if legs >= self.config.min_path_length && legs <= self.config.max_path_length {
+                    let mut s = symbols.clone();
+                    s.push(symbol.clone());
+                    results.push((path.clone(), s));
+                }
+                continue;
+            }
+            if legs >= self.config.max_path_length || path.contains(next) {
+                continue;
+            }
+            path.push(next.clone());
+            symbols.push(symbol.clone());
+            self.dfs(graph, target, path, symbols, results);
+            symbols.pop();
+            path.pop();
+        }
+    }
+
+    fn min_liquidity(&self, exchange: &str, symbols: &[String]) -> f64 {
+        let liquidity = self.liquidity.read();
+        symbols
+            .iter()
+            .map(|s| liquidity.get(&(exchange.to_string(), s.clone())).copied().unwrap_or(0.0))
+            .fold(f64::INFINITY, f64::min)
+            .min(f64::MAX)
+    }
+
+    /// 更新交易对流动性（USD），并刷新相关环路
+    pub fn update_liquidity(&self, exchange: &str, symbol: &str, liquidity_usd: f64) {
+        self.liquidity.write().insert((exchange.to_string(), symbol.to_string()), liquidity_usd);
+        let mut cycles = self.cycles.write();
+        for ((ex, syms), cycle) in cycles.iter_mut() {
+            if ex == exchange && syms.contains(symbol) {
+                cycle.min_liquidity_usd = self.min_liquidity(exchange, &cycle.symbols);
+            }
+        }
+    }
+
+    /// 记录一次观测到的环路收益（bps）
+    pub fn record_edge(&self, exchange: &str, symbols: &[String], edge_bps: f64) {
+        let key = (exchange.to_string(), symbols.iter().cloned().collect());
+        if let Some(cycle) = self.cycles.write().get_mut(&key) {
+            let alpha = self.config.edge_ewma_alpha;
+            cycle.historical_edge_bps = alpha * edge_bps + (1.0 - alpha) * cycle.historical_edge_bps;
+        }
+    }
+
+    /// 指定环路的优先级
+    pub fn priority_of(&self, exchange: &str, symbols: &[String]) -> f64 {
+        let key = (exchange.to_string(), symbols.iter().cloned().collect());
+        self.cycles.read().get(&key).map(|c| c.priority()).unwrap_or(0.0)
+    }
+
+    /// 按优先级排序的前 N 条环路
+    pub fn top_paths(&self, exchange: &str, limit: usize) -> Vec<CyclePath> {
+        let mut paths: Vec<CyclePath> = self
+            .cycles
+            .read()
+            .values()
+            .filter(|c| c.exchange == exchange)
+            .cloned()
+            .collect();
+        paths.sort_by(|a, b| b.priority().partial_cmp(&a.priority()).unwrap_or(std::cmp::Ordering::Equal));
+        paths.truncate(limit);
+        paths
+    }
+
+    /// 当前环路总数
+    pub fn cycle_count(&self) -> usize {
+        self.cycles.read().len()
+    }
+}
+
+impl Default for PathDiscovery {
+    fn default() -> Self {
+        Self::new(PathDiscoveryConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listing(symbol: &str, base: &str, quote: &str) -> Listing {
+        Listing {
+            exchange: "binance".to_string(),
+            symbol: symbol.to_string(),
+            base: base.to_string(),
+            quote: quote.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_incremental_triangle_discovery() {
+        let discovery = PathDiscovery::default();
+        assert_eq!(discovery.add_listing(&listing("BTCUSDT", "BTC", "USDT")), 0);
+        assert_eq!(discovery.add_listing(&listing("ETHUSDT", "ETH", "USDT")), 0);
+        assert_eq!(discovery.add_listing(&listing("ETHBTC", "ETH", "BTC")), 1);
+        assert_eq!(discovery.add_listing(&listing("ETHBTC", "ETH", "BTC")), 0);
+        assert_eq!(discovery.cycle_count(), 1);
+
+        assert_eq!(discovery.remove_listing(&listing("ETHBTC", "ETH", "BTC")), 1);
+        assert_eq!(discovery.cycle_count(), 0);
+    }
+
+    #[test]
+    fn test_four_leg_cycles_respect_bounds() {
+        let discovery = PathDiscovery::new(PathDiscoveryConfig {
+            min_path_length: 4,
+            max_path_length: 4,
+            ..Default::default()
+        });
+        discovery.add_listing(&listing("BTCUSDT", "BTC", "USDT"));
+        discovery.add_listing(&listing("ETHBTC", "ETH", "BTC"));
+        discovery.add_listing(&listing("SOLETH", "SOL", "ETH"));
+        discovery.add_listing(&listing("SOLUSDT", "SOL", "USDT"));
+        assert_eq!(discovery.cycle_count(), 1);
+    }
+
+    #[test]
+    fn test_resync_keeps_cycles_and_their_history() {
+        let discovery = PathDiscovery::default();
+        // ETHBTC 的 base 排序在 quote 之后，不能被当成 BTC/ETH
+        let listings = vec![
+            listing("BTCUSDT", "BTC", "USDT"),
+            listing("ETHUSDT", "ETH", "USDT"),
+            listing("ETHBTC", "ETH", "BTC"),
+        ];
+        discovery.sync_listings("binance", &listings);
+        let symbols: Vec<String> = ["BTCUSDT", "ETHBTC", "ETHUSDT"].iter().map(|s| s.to_string()).collect();
+        discovery.record_edge("binance", &symbols, 50.0);
+        discovery.update_liquidity("binance", "BTCUSDT", 1_000.0);
+        discovery.update_liquidity("binance", "ETHBTC", 1_000.0);
+        discovery.update_liquidity("binance", "ETHUSDT", 1_000.0);
+        let priority = discovery.priority_of("binance", &symbols);
+        assert!(priority > 0.0);
+
+        discovery.sync_listings("binance", &listings);
+        assert_eq!(discovery.cycle_count(), 1);
+        assert_eq!(discovery.priority_of("binance", &symbols), priority);
+    }
+}
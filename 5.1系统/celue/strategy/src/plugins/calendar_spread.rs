@@ -0,0 +1,422 @@
+//! 跨期价差套利策略
+//!
+//! 按 (交易所, 标的) 维护期限结构：永续合约与各交割合约的最新盘口。
+//! 相邻期限之间的理论价差由持有成本决定（年化利率，涉及永续腿时加上
+//! 年化资金费率），实际价差偏离理论价差超过手续费和最小利润要求时，
+//! 做空偏贵的一腿、做多偏便宜的一腿，持有到近月交割收敛。
+//!
+//! 合约命名：`BTCUSDT_240628`（Binance 交割）、`BTC-USDT-240628`（OKX
+//! 交割）、`BTC-USDT-SWAP` / `BTCUSDT`（永续）。交割时间取到期日
+//! 08:00 UTC。距到期不足 `min_days_to_expiry` 的合约不再开新仓，已开
+//! 仓位通过 `roll_schedule` 给出展期指令。
+
+use std::collections::{BTreeMap, HashMap};
+
+use async_trait::async_trait;
+use chrono::{NaiveDate, TimeZone, Utc};
+use common::{
+    arbitrage::{ArbitrageLeg, ArbitrageOpportunity, MarketType, Side},
+    market_data::{NormalizedSnapshot, OrderBook},
+    precision::FixedPrice,
+};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    context::StrategyContext,
+    traits::{ArbitrageStrategy, ExecutionResult, StrategyError, StrategyKind},
+};
+
+const MS_PER_DAY: f64 = 86_400_000.0;
+
+/// 策略参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarSpreadConfig {
+    /// 年化无风险利率（资金成本）
+    pub annual_interest_rate: f64,
+    /// 距到期少于该天数的合约不再开仓，并触发展期
+    pub min_days_to_expiry: f64,
+    /// 只交易到期日在该天数以内的合约
+    pub max_days_to_expiry: f64,
+}
+
+impl Default for CalendarSpreadConfig {
+    fn default() -> Self {
+        Self {
+            annual_interest_rate: std::env::var("CELUE_CALENDAR_INTEREST_RATE")
+                .ok().and_then(|s| s.parse().ok())
+                .unwrap_or(0.05),
+            min_days_to_expiry: std::env::var("CELUE_CALENDAR_MIN_DAYS_TO_EXPIRY")
+                .ok().and_then(|s| s.parse().ok())
+                .unwrap_or(3.0),
+            max_days_to_expiry: std::env::var("CELUE_CALENDAR_MAX_DAYS_TO_EXPIRY")
+                .ok().and_then(|s| s.parse().ok())
+                .unwrap_or(180.0),
+        }
+    }
+}
+
+/// 从合约代码解析出的标的与到期时间
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractSpec {
+    pub underlying: String,
+    /// 到期时间（毫秒），永续为 None
+    pub expiry_ms: Option<u64>,
+}
+
+impl ContractSpec {
+    pub fn parse(symbol: &str) -> Self {
+        let upper = symbol.to_ascii_uppercase();
+        let compact = |s: &str| s.chars().filter(|c| !matches!(c, '-' | '_' | '/')).collect::<String>();
+        if let Some(base) = upper.strip_suffix("-SWAP") {
+            return Self { underlying: compact(base), expiry_ms: None };
+        }
+        if let Some(pos) = upper.rfind(['_', '-']) {
+            let suffix = &upper[pos + 1..];
+            if suffix.len() == 6 && suffix.chars().all(|c| c.is_ascii_digit()) {
+                let expiry_ms = NaiveDate::parse_from_str(suffix, "%y%m%d")
+                    .ok()
+                    .and_then(|d| d.and_hms_opt(8, 0, 0))
+                    .map(|t| Utc.from_utc_datetime(&t).timestamp_millis() as u64);
+                if expiry_ms.is_some() {
+                    return Self { underlying: compact(&upper[..pos]), expiry_ms };
+                }
+            }
+        }
+        Self { underlying: compact(&upper), expiry_ms: None }
+    }
+
+    pub fn days_to_expiry(&self, now_ms: u64) -> Option<f64> {
+        self.expiry_ms.map(|e| e.saturating_sub(now_ms) as f64 / MS_PER_DAY)
+    }
+}
+
+/// 期限结构上的一个点
+#[derive(Debug, Clone)]
+struct TermPoint {
+    spec: ContractSpec,
+    book: OrderBook,
+}
+
+/// 已开仓的价差
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpreadPosition {
+    pub exchange: String,
+    pub underlying: String,
+    pub near_symbol: String,
+    pub far_symbol: String,
+    /// true 表示做多近月、做空远月
+    pub long_near: bool,
+    pub quantity: f64,
+    pub opened_at_ns: u64,
+}
+
+/// 展期指令：把 `from_symbol` 上的腿移到 `to_symbol`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RollInstruction {
+    pub exchange: String,
+    pub underlying: String,
+    pub from_symbol: String,
+    pub to_symbol: Option<String>,
+    pub days_to_expiry: f64,
+}
+
+pub struct CalendarSpreadStrategy {
+    config: CalendarSpreadConfig,
+    /// (交易所, 标的) -> 到期时间（永续为 0）-> 最新盘口
+    term_structure: RwLock<HashMap<(String, String), BTreeMap<u64, TermPoint>>>,
+    /// (交易所, 标的) -> 每 8 小时资金费率
+    funding_rates: RwLock<HashMap<(String, String), f64>>,
+    positions: RwLock<Vec<SpreadPosition>>,
+}
+
+impl CalendarSpreadStrategy {
+    pub fn new(config: CalendarSpreadConfig) -> Self {
+        Self {
+            config,
+            term_structure: RwLock::new(HashMap::new()),
+            funding_rates: RwLock::new(HashMap::new()),
+            positions: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// 更新永续合约资金费率（每 8 小时）
+    pub fn set_funding_rate(&self, exchange: &str, underlying: &str, rate_per_8h: f64) {
+        self.funding_rates
+            .write()
+            .insert((exchange.to_string(), underlying.to_ascii_uppercase()), rate_per_8h);
+    }
+
+    pub fn positions(&self) -> Vec<SpreadPosition> {
+        self.positions.read().clone()
+    }
+
+    fn observe(&self, book: &OrderBook) -> (String, String) {
+        let spec = ContractSpec::parse(book.symbol.as_str());
+        let key = (book.exchange.as_str().to_string(), spec.underlying.clone());
+        self.term_structure
+            .write()
+            .entry(key.clone())
+            .or_default()
+            .insert(spec.expiry_ms.unwrap_or(0), TermPoint { spec, book: book.clone() });
+        key
+    }
+
+    /// 持有成本年化利率：利率，永续腿额外计入年化资金费率
+    fn carry_rate(&self, key: &(String, String), involves_perpetual: bool) -> (f64, f64) {
+        let funding = if involves_perpetual {
+            self.funding_rates.read().get(key).copied().unwrap_or(0.0) * 3.0 * 365.0
+        } else {
+            0.0
+        };
+        (self.config.annual_interest_rate, funding)
+    }
+
+    fn fee_rate(ctx: &StrategyContext, book: &OrderBook) -> Option<f64> {
+        ctx.fee_precision_repo
+            .get_fee_rate_bps_for_exchange(book.exchange.as_str())
+            .map(|bps| bps / 10_000.0)
+            .or_else(|| ctx.get_taker_fee(&book.exchange).map(|f| f.to_f64()))
+    }
+
+    /// 评估近月/远月一对合约
+    fn evaluate(
+        &self,
+        ctx: &StrategyContext,
+        key: &(String, String),
+        near: &TermPoint,
+        far: &TermPoint,
+        now_ms: u64,
+    ) -> Option<ArbitrageOpportunity> {
+        let far_days = far.spec.days_to_expiry(now_ms)?;
+        // 永续对交割持有到远月到期，交割对交割持有到近月到期收敛
+        let (near_days, holding_days) = match near.spec.days_to_expiry(now_ms) {
+            Some(days) => (Some(days), days),
+            None => (None, far_days),
+        };
+        let min_days = near_days.unwrap_or(far_days).min(far_days);
+        if min_days < self.config.min_days_to_expiry || far_days > self.config.max_days_to_expiry {
+            return None;
+        }
+
+        let (near_bid, near_ask) = (near.book.best_bid()?, near.book.best_ask()?);
+        let (far_bid, far_ask) = (far.book.best_bid()?, far.book.best_ask()?);
+        let (interest, funding) = self.carry_rate(key, near.spec.expiry_ms.is_none());
+        let fair_basis = (interest + funding) * holding_days / 365.0;
+
+        // 远月偏贵：买近卖远；远月偏便宜：卖近买远（均按可成交价）
+        let rich_basis = far_bid.price.to_f64() / near_ask.price.to_f64() - 1.0;
+        let cheap_basis = far_ask.price.to_f64() / near_bid.price.to_f64() - 1.0;
+        let (long_near, basis, deviation) = if rich_basis - fair_basis >= fair_basis - cheap_basis {
+            (true, rich_basis, rich_basis - fair_basis)
+        } else {
+            (false, cheap_basis, fair_basis - cheap_basis)
+        };
+
+        // 开仓和平仓各两腿吃单
+        let fees = 2.0 * (Self::fee_rate(ctx, &near.book)? + Self::fee_rate(ctx, &far.book)?);
+        let slippage = 2.0 * ctx.slippage_per_leg_pct_for(self.name());
+        let net_pct = deviation - fees - slippage;
+        if net_pct < ctx.min_profit_pct_for(self.name()).to_f64() {
+            return None;
+        }
+
+        let (near_entry, far_entry) = if long_near { (near_ask, far_bid) } else { (near_bid, far_ask) };
+        let quantity = if near_entry.quantity < far_entry.quantity { near_entry.quantity } else { far_entry.quantity };
+        if quantity.to_f64() <= 0.0 {
+            return None;
+        }
+        let leg = |point: &TermPoint, side: Side, price: FixedPrice| ArbitrageLeg {
+            exchange: point.book.exchange.clone(),
+            symbol: point.book.symbol.clone(),
+            market: if point.spec.expiry_ms.is_some() { MarketType::Delivery } else { MarketType::Perpetual },
+            side,
+            price,
+            quantity,
+            cost: price * quantity,
+        };
+        let (near_side, far_side) = if long_near { (Side::Buy, Side::Sell) } else { (Side::Sell, Side::Buy) };
+        let legs = vec![leg(near, near_side, near_entry.price), leg(far, far_side, far_entry.price)];
+        let notional = near_entry.price.to_f64() * quantity.to_f64();
+        let net_profit = FixedPrice::from_f64(notional * net_pct, near_entry.price.scale());
+
+        let mut opportunity = ArbitrageOpportunity::new_with_legs(
+            self.name(),
+            legs,
+            net_profit,
+            FixedPrice::from_f64(net_pct, 6),
+            now_ms * 1_000_000,
+        )
+        .with_quote_watermark([near.book.timestamp_ns, far.book.timestamp_ns]);
+        let tags = &mut opportunity.tags;
+        tags.insert("underlying".into(), key.1.clone());
+        tags.insert("near_symbol".into(), near.book.symbol.as_str().to_string());
+        tags.insert("far_symbol".into(), far.book.symbol.as_str().to_string());
+        tags.insert("basis_bps".into(), format!("{:.2}", basis * 10_000.0));
+        tags.insert("fair_basis_bps".into(), format!("{:.2}", fair_basis * 10_000.0));
+        tags.insert("deviation_bps".into(), format!("{:.2}", deviation * 10_000.0));
+        tags.insert("interest_rate_annual".into(), format!("{:.4}", interest));
+        tags.insert("funding_rate_annual".into(), format!("{:.4}", funding));
+        tags.insert("near_days_to_expiry".into(), near_days.map_or("perpetual".to_string(), |d| format!("{:.2}", d)));
+        tags.insert("far_days_to_expiry".into(), format!("{:.2}", far_days));
+        tags.insert("direction".into(), if long_near { "long_near_short_far" } else { "short_near_long_far" }.into());
+        Some(opportunity)
+    }
+
+    /// 需要展期的仓位：任一腿距到期不足 `min_days_to_expiry` 时移到下一个到期
+    pub fn roll_schedule(&self, now_ms: u64) -> Vec<RollInstruction> {
+        let term_structure = self.term_structure.read();
+        let mut instructions = Vec::new();
+        for position in self.positions.read().iter() {
+            let key = (position.exchange.clone(), position.underlying.clone());
+            for symbol in [&position.near_symbol, &position.far_symbol] {
+                let spec = ContractSpec::parse(symbol);
+                let Some(days) = spec.days_to_expiry(now_ms) else { continue };
+                if days >= self.config.min_days_to_expiry {
+                    continue;
+                }
+                // 下一个可交易的到期合约，且不是仓位中的另一腿
+                let to_symbol = term_structure.get(&key).and_then(|points| {
+                    points
+                        .values()
+                        .filter(|p| p.spec.days_to_expiry(now_ms).is_some_and(|d| d >= self.config.min_days_to_expiry))
+                        .map(|p| p.book.symbol.as_str().to_string())
+                        .find(|s| s != &position.near_symbol && s != &position.far_symbol)
+                });
+                instructions.push(RollInstruction {
+                    exchange: position.exchange.clone(),
+                    underlying: position.underlying.clone(),
+                    from_symbol: symbol.clone(),
+                    to_symbol,
+                    days_to_expiry: days,
+                });
+            }
+        }
+        instructions
+    }
+}
+
+impl Default for CalendarSpreadStrategy {
+    fn default() -> Self {
+        Self::new(CalendarSpreadConfig::default())
+    }
+}
+
+#[async_trait]
+impl ArbitrageStrategy for CalendarSpreadStrategy {
+    fn name(&self) -> &'static str {
+        "calendar_spread"
+    }
+
+    fn kind(&self) -> StrategyKind {
+        StrategyKind::CalendarSpread
+    }
+
+    fn detect(&self, ctx: &StrategyContext, input: &NormalizedSnapshot) -> Option<ArbitrageOpportunity> {
+        let now_ms = if input.timestamp_ns > 0 {
+            input.timestamp_ns / 1_000_000
+        } else {
+            ctx.clock().now_millis()
+        };
+        let mut keys: Vec<(String, String)> = input.exchanges.iter().map(|b| self.observe(b)).collect();
+        keys.sort();
+        keys.dedup();
+
+        let term_structure = self.term_structure.read();
+        let mut best: Option<ArbitrageOpportunity> = None;
+        for key in &keys {
+            let Some(points) = term_structure.get(key) else { continue };
+            let points: Vec<&TermPoint> = points.values().collect();
+            for (i, near) in points.iter().enumerate() {
+                for far in &points[i + 1..] {
+                    if let Some(candidate) = self.evaluate(ctx, key, near, far, now_ms) {
+                        if best.as_ref().is_none_or(|b| candidate.net_profit_pct > b.net_profit_pct) {
+                            best = Some(candidate);
+                        }
+                    }
+                }
+            }
+        }
+        best
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &StrategyContext,
+        opportunity: &ArbitrageOpportunity,
+    ) -> Result<ExecutionResult, StrategyError> {
+        let [near, far] = opportunity.legs.as_slice() else {
+            return Err(StrategyError::ExecutionFailed("calendar spread requires exactly two legs".to_string()));
+        };
+        // 两腿配对记录，展期依赖仓位信息；下单同其他策略为模拟执行
+        self.positions.write().push(SpreadPosition {
+            exchange: near.exchange.as_str().to_string(),
+            underlying: ContractSpec::parse(near.symbol.as_str()).underlying,
+            near_symbol: near.symbol.as_str().to_string(),
+            far_symbol: far.symbol.as_str().to_string(),
+            long_near: near.side == Side::Buy,
+            quantity: near.quantity.to_f64(),
+            opened_at_ns: opportunity.created_at_ns,
+        });
+        Ok(ExecutionResult {
+            accepted: true,
+            reason: Some("Simulation execution".to_string()),
+            order_ids: vec![format!("sim_{}_near", opportunity.id), format!("sim_{}_far", opportunity.id)],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::FeePrecisionRepoImpl;
+    use common::testing::{book, snapshot};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_rich_future_detection_and_roll() {
+        assert_eq!(
+            ContractSpec::parse("BTC-USDT-SWAP"),
+            ContractSpec { underlying: "BTCUSDT".into(), expiry_ms: None }
+        );
+        let spec = ContractSpec::parse("BTCUSDT_250328");
+        assert_eq!(spec.underlying, "BTCUSDT");
+
+        let ctx = StrategyContext::new(
+            Arc::new(FeePrecisionRepoImpl::default()),
+            Arc::new(adapters::metrics::AdapterMetrics::new()),
+        );
+        ctx.set_min_profit_for_strategy("calendar_spread", FixedPrice::from_f64(0.001, 6));
+        let strategy = CalendarSpreadStrategy::new(CalendarSpreadConfig {
+            annual_interest_rate: 0.05,
+            min_days_to_expiry: 3.0,
+            max_days_to_expiry: 180.0,
+        });
+
+        // 距 2025-03-28 约 90 天，远月溢价 5% 远高于 ~1.2% 的持有成本
+        let now_ns = (spec.expiry_ms.unwrap() - 90 * 86_400_000) * 1_000_000;
+        let books = vec![
+            book("binance", "BTCUSDT", 39_990.0, 40_000.0, now_ns),
+            book("binance", "BTCUSDT_250328", 42_000.0, 42_010.0, now_ns),
+        ];
+        let snapshot = snapshot("BTCUSDT", books, now_ns);
+        let opportunity = strategy.detect(&ctx, &snapshot).expect("rich future should be detected");
+        assert_eq!(opportunity.legs[0].side, Side::Buy);
+        assert_eq!(opportunity.tags["direction"], "long_near_short_far");
+        assert_eq!(opportunity.tags["near_days_to_expiry"], "perpetual");
+        // 偏离 = 5% - 5% × 90/365 ≈ 3.77%，净利润再扣除两次开平仓的手续费与滑点
+        assert_eq!(opportunity.tags["deviation_bps"], "376.71");
+        let net_pct = opportunity.net_profit_pct.to_f64();
+        assert!(net_pct > 0.02 && net_pct < 0.0377, "net_pct = {net_pct}");
+
+        // 资金费率足以解释远月溢价时不再有机会
+        strategy.set_funding_rate("binance", "BTCUSDT", 0.00014);
+        assert!(strategy.detect(&ctx, &snapshot).is_none());
+
+        strategy.execute(&ctx, &opportunity).await.unwrap();
+        let near_expiry_ms = spec.expiry_ms.unwrap() - 86_400_000;
+        let rolls = strategy.roll_schedule(near_expiry_ms);
+        assert_eq!(rolls.len(), 1);
+        assert_eq!(rolls[0].from_symbol, "BTCUSDT_250328");
+    }
+}
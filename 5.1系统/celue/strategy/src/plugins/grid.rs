@@ -0,0 +1,454 @@
+//! 网格交易策略
+//!
+//! 每个 (交易所, 交易对) 在 [lower, upper] 区间内等距布置价格档位，
+//! 价格下方的档位挂买单、上方挂卖单，每一对相邻档位构成一次网格往返。
+//! 档位状态（空闲/挂单中）和库存持久化到 `GridStateStore`，重启后已
+//! 挂单的档位不会被重复下单；客户端订单号由网格代数和档位确定，交易所
+//! 侧也能去重。价格离开区间时以当前价为中心重建网格（代数 +1），旧代的
+//! 挂单通过 `take_pending_cancels` 交给执行层撤销。库存上限取网格配置与
+//! 风控 `max_position_size` 折算数量中较小者。
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use adapters::risk::RiskConfig;
+use anyhow::Result;
+use async_trait::async_trait;
+use common::{
+    arbitrage::{ArbitrageLeg, ArbitrageOpportunity, MarketType, Side},
+    market_data::NormalizedSnapshot,
+    precision::{FixedPrice, FixedQuantity},
+};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::{
+    context::StrategyContext,
+    traits::{ArbitrageStrategy, ExecutionResult, StrategyError, StrategyKind},
+};
+
+/// 单个网格的配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GridConfig {
+    pub exchange: String,
+    pub symbol: String,
+    pub lower: f64,
+    pub upper: f64,
+    /// 档位数（含两端），至少 2
+    pub levels: usize,
+    /// 每档下单数量
+    pub order_quantity: f64,
+    /// 最大持仓数量
+    pub max_inventory: f64,
+    /// 价格离开区间时是否重建网格
+    pub rebalance_on_exit: bool,
+}
+
+impl GridConfig {
+    fn key(&self) -> String {
+        format!("{}:{}", self.exchange, self.symbol)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LevelState {
+    Idle,
+    /// 已提交挂单
+    Open { client_order_id: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GridLevel {
+    pub price: f64,
+    pub state: LevelState,
+}
+
+/// 可持久化的网格状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GridState {
+    /// 每次重建 +1，参与客户端订单号生成
+    pub generation: u64,
+    pub lower: f64,
+    pub upper: f64,
+    pub levels: Vec<GridLevel>,
+    /// 当前持仓数量
+    pub inventory: f64,
+}
+
+impl GridState {
+    fn build(generation: u64, lower: f64, upper: f64, count: usize, inventory: f64) -> Self {
+        let count = count.max(2);
+        let step = (upper - lower) / (count - 1) as f64;
+        let levels = (0..count)
+            .map(|i| GridLevel { price: lower + step * i as f64, state: LevelState::Idle })
+            .collect();
+        Self { generation, lower, upper, levels, inventory }
+    }
+
+    fn open_orders(&self) -> impl Iterator<Item = &str> {
+        self.levels.iter().filter_map(|l| match &l.state {
+            LevelState::Open { client_order_id } => Some(client_order_id.as_str()),
+            LevelState::Idle => None,
+        })
+    }
+
+    /// 挂单中的买单数量
+    fn open_buys(&self, price: f64) -> usize {
+        self.levels
+            .iter()
+            .filter(|l| l.price < price && matches!(l.state, LevelState::Open { .. }))
+            .count()
+    }
+}
+
+fn client_order_id(key: &str, generation: u64, level: usize) -> String {
+    format!("grid-{}-g{}-l{}", key.replace(':', "-"), generation, level)
+}
+
+/// 网格状态存储
+pub trait GridStateStore: Send + Sync {
+    fn load(&self) -> Result<HashMap<String, GridState>>;
+    fn save(&self, states: &HashMap<String, GridState>) -> Result<()>;
+}
+
+/// 内存存储（测试/模拟）
+#[derive(Default)]
+pub struct MemoryGridStateStore {
+    states: Mutex<HashMap<String, GridState>>,
+}
+
+impl GridStateStore for MemoryGridStateStore {
+    fn load(&self) -> Result<HashMap<String, GridState>> {
+        Ok(self.states.lock().clone())
+    }
+
+    fn save(&self, states: &HashMap<String, GridState>) -> Result<()> {
+        *self.states.lock() = states.clone();
+        Ok(())
+    }
+}
+
+/// JSON 文件存储，写临时文件后 rename 保证原子性
+pub struct FileGridStateStore {
+    path: PathBuf,
+}
+
+impl FileGridStateStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl GridStateStore for FileGridStateStore {
+    fn load(&self) -> Result<HashMap<String, GridState>> {
+        match std::fs::read(&self.path) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save(&self, states: &HashMap<String, GridState>) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec(states)?)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+pub struct GridStrategy {
+    grids: HashMap<String, GridConfig>,
+    states: RwLock<HashMap<String, GridState>>,
+    store: Box<dyn GridStateStore>,
+    /// 风控单品种持仓上限（USD）
+    max_position_usd: Option<f64>,
+    /// 网格重建后待撤销的旧挂单（交易所, 客户端订单号）
+    pending_cancels: Mutex<Vec<(String, String)>>,
+}
+
+impl GridStrategy {
+    /// 加载持久化状态；配置区间变化的网格按新配置重建
+    pub fn new(configs: Vec<GridConfig>, store: Box<dyn GridStateStore>) -> Result<Self> {
+        let mut states = store.load()?;
+        let mut pending_cancels = Vec::new();
+        for config in &configs {
+            let key = config.key();
+            let stale = states.get(&key).is_none_or(|s| {
+                (s.lower, s.upper, s.levels.len()) != (config.lower, config.upper, config.levels.max(2))
+            });
+            if stale {
+                let (generation, inventory) = match states.remove(&key) {
+                    Some(previous) => {
+                        pending_cancels.extend(previous.open_orders().map(|id| (config.exchange.clone(), id.to_string())));
+                        (previous.generation + 1, previous.inventory)
+                    }
+                    None => (0, 0.0),
+                };
+                states.insert(key, GridState::build(generation, config.lower, config.upper, config.levels, inventory));
+            } else {
+                info!("♻️ 恢复网格 {}：代数 {}，库存 {}", key, states[&key].generation, states[&key].inventory);
+            }
+        }
+        store.save(&states)?;
+        Ok(Self {
+            grids: configs.into_iter().map(|c| (c.key(), c)).collect(),
+            states: RwLock::new(states),
+            store,
+            max_position_usd: None,
+            pending_cancels: Mutex::new(pending_cancels),
+        })
+    }
+
+    /// 接入风控层的持仓上限
+    pub fn with_risk_config(mut self, risk: &RiskConfig) -> Self {
+        self.max_position_usd = Some(risk.max_position_size);
+        self
+    }
+
+    pub fn state(&self, exchange: &str, symbol: &str) -> Option<GridState> {
+        self.states.read().get(&format!("{}:{}", exchange, symbol)).cloned()
+    }
+
+    /// 取出需要撤销的旧挂单
+    pub fn take_pending_cancels(&self) -> Vec<(String, String)> {
+        std::mem::take(&mut *self.pending_cancels.lock())
+    }
+
+    fn persist(&self, states: &HashMap<String, GridState>) {
+        if let Err(e) = self.store.save(states) {
+            warn!("网格状态持久化失败: {}", e);
+        }
+    }
+
+    fn inventory_limit(&self, config: &GridConfig, price: f64) -> f64 {
+        match self.max_position_usd {
+            Some(usd) if price > 0.0 => config.max_inventory.min(usd / price),
+            _ => config.max_inventory,
+        }
+    }
+
+    /// 成交回报：释放档位并更新库存
+    pub fn on_fill(&self, exchange: &str, symbol: &str, client_order_id: &str, side: Side, quantity: f64) {
+        let key = format!("{}:{}", exchange, symbol);
+        let mut states = self.states.write();
+        let Some(state) = states.get_mut(&key) else { return };
+        match side {
+            Side::Buy => state.inventory += quantity,
+            Side::Sell => state.inventory = (state.inventory - quantity).max(0.0),
+        }
+        if let Some(level) = state
+            .levels
+            .iter_mut()
+            .find(|l| matches!(&l.state, LevelState::Open { client_order_id: id } if id == client_order_id))
+        {
+            level.state = LevelState::Idle;
+        }
+        self.persist(&states);
+    }
+
+    /// 价格离开区间：以当前价为中心、保持宽度重建
+    fn rebalance(&self, config: &GridConfig, state: &mut GridState, price: f64) {
+        let half_width = (state.upper - state.lower) / 2.0;
+        self.pending_cancels
+            .lock()
+            .extend(state.open_orders().map(|id| (config.exchange.clone(), id.to_string())));
+        info!(
+            "🔁 网格 {} 价格 {} 离开区间 [{}, {}]，以当前价重建",
+            config.key(), price, state.lower, state.upper
+        );
+        *state = GridState::build(state.generation + 1, price - half_width, price + half_width, config.levels, state.inventory);
+    }
+}
+
+#[async_trait]
+impl ArbitrageStrategy for GridStrategy {
+    fn name(&self) -> &'static str {
+        "grid_trading"
+    }
+
+    fn kind(&self) -> StrategyKind {
+        StrategyKind::Grid
+    }
+
+    /// 返回当前价所在网格的一对档位：下方档买入、上方档卖出
+    fn detect(&self, ctx: &StrategyContext, input: &NormalizedSnapshot) -> Option<ArbitrageOpportunity> {
+        let mut states = self.states.write();
+        for book in &input.exchanges {
+            let key = format!("{}:{}", book.exchange.as_str(), book.symbol.as_str());
+            let Some(config) = self.grids.get(&key) else { continue };
+            let Some(state) = states.get_mut(&key) else { continue };
+            let (Some(bid), Some(ask)) = (book.best_bid(), book.best_ask()) else { continue };
+            let price = (bid.price.to_f64() + ask.price.to_f64()) / 2.0;
+
+            if price < state.lower || price > state.upper {
+                if !config.rebalance_on_exit {
+                    continue;
+                }
+                self.rebalance(config, state, price);
+                self.persist(&states);
+                return None;
+            }
+
+            // 当前价所在的档位区间
+            let Some(below) = state.levels.iter().rposition(|l| l.price <= price) else { continue };
+            let above = below + 1;
+            if above >= state.levels.len() {
+                continue;
+            }
+            if state.levels[below].state != LevelState::Idle || state.levels[above].state != LevelState::Idle {
+                continue;
+            }
+            // 挂满的买单全部成交后不得超过库存上限
+            let committed = state.inventory + (state.open_buys(price) + 1) as f64 * config.order_quantity;
+            if committed > self.inventory_limit(config, price) + 1e-12 {
+                continue;
+            }
+
+            let (buy_price, sell_price) = (state.levels[below].price, state.levels[above].price);
+            let fee_rate = ctx
+                .fee_precision_repo
+                .get_fee_rate_bps_for_exchange(book.exchange.as_str())
+                .map(|bps| bps / 10_000.0)
+                .unwrap_or(0.001);
+            let net_pct = sell_price / buy_price - 1.0 - 2.0 * fee_rate;
+            if net_pct < ctx.min_profit_pct_for(self.name()).to_f64() {
+                continue;
+            }
+
+            let scale = bid.price.scale();
+            let quantity = FixedQuantity::from_f64(config.order_quantity, 8);
+            let leg = |side, price: f64| {
+                let price = FixedPrice::from_f64(price, scale);
+                ArbitrageLeg { exchange: book.exchange.clone(), symbol: book.symbol.clone(), market: MarketType::Spot, side, price, quantity, cost: price * quantity }
+            };
+            let net_profit = FixedPrice::from_f64(buy_price * config.order_quantity * net_pct, scale);
+            let mut opportunity = ArbitrageOpportunity::new_with_legs(
+                self.name(),
+                vec![leg(Side::Buy, buy_price), leg(Side::Sell, sell_price)],
+                net_profit,
+                FixedPrice::from_f64(net_pct, 6),
+                input.timestamp_ns,
+            )
+            .with_quote_watermark([book.timestamp_ns]);
+            // 网格挂单长期有效，不受默认 TTL 约束
+            opportunity.ttl_ns = 0;
+            opportunity.tags.insert("grid".into(), key.clone());
+            opportunity.tags.insert("grid_generation".into(), state.generation.to_string());
+            opportunity.tags.insert("grid_levels".into(), format!("{},{}", below, above));
+            opportunity.tags.insert("grid_inventory".into(), format!("{}", state.inventory));
+            return Some(opportunity);
+        }
+        None
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &StrategyContext,
+        opportunity: &ArbitrageOpportunity,
+    ) -> Result<ExecutionResult, StrategyError> {
+        let parse = |tag: &str| opportunity.tags.get(tag).cloned();
+        let (Some(key), Some(generation), Some(levels)) = (parse("grid"), parse("grid_generation"), parse("grid_levels")) else {
+            return Err(StrategyError::ExecutionFailed("missing grid tags".to_string()));
+        };
+        let levels: Vec<usize> = levels.split(',').filter_map(|s| s.parse().ok()).collect();
+
+        let mut states = self.states.write();
+        let Some(state) = states.get_mut(&key) else {
+            return Err(StrategyError::ExecutionFailed(format!("unknown grid {}", key)));
+        };
+        if state.generation.to_string() != generation {
+            return Err(StrategyError::OpportunityExpired);
+        }
+        // 档位已被其他执行占用（例如重启前已挂单）时不重复下单
+        if levels.iter().any(|i| state.levels.get(*i).is_none_or(|l| l.state != LevelState::Idle)) {
+            return Ok(ExecutionResult { accepted: false, reason: Some("grid level already open".to_string()), order_ids: vec![] });
+        }
+        let order_ids: Vec<String> = levels.iter().map(|i| client_order_id(&key, state.generation, *i)).collect();
+        for (i, id) in levels.iter().zip(&order_ids) {
+            state.levels[*i].state = LevelState::Open { client_order_id: id.clone() };
+        }
+        self.persist(&states);
+        Ok(ExecutionResult { accepted: true, reason: Some("Simulation execution".to_string()), order_ids })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::FeePrecisionRepoImpl;
+    use common::market_data::OrderBook;
+    use common::types::{Exchange, Symbol};
+    use std::sync::Arc;
+
+    struct SharedStore(Arc<MemoryGridStateStore>);
+
+    impl GridStateStore for SharedStore {
+        fn load(&self) -> Result<HashMap<String, GridState>> {
+            self.0.load()
+        }
+        fn save(&self, states: &HashMap<String, GridState>) -> Result<()> {
+            self.0.save(states)
+        }
+    }
+
+    fn snapshot(mid: f64) -> NormalizedSnapshot {
+        let mut book = OrderBook::new(Exchange::new("binance"), Symbol::new("ETHUSDT"), 1, 1);
+        book.add_bid(FixedPrice::from_f64(mid - 0.5, 2), FixedQuantity::from_f64(10.0, 8));
+        book.add_ask(FixedPrice::from_f64(mid + 0.5, 2), FixedQuantity::from_f64(10.0, 8));
+        NormalizedSnapshot {
+            symbol: Symbol::new("ETHUSDT"),
+            timestamp_ns: 1,
+            exchanges: vec![book],
+            weighted_mid_price: FixedPrice::from_f64(mid, 2),
+            total_bid_volume: FixedQuantity::from_f64(10.0, 8),
+            total_ask_volume: FixedQuantity::from_f64(10.0, 8),
+            quality_score: 1.0,
+            sequence: Some(1),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_grid_restart_rebalance_and_inventory() {
+        let config = GridConfig {
+            exchange: "binance".into(),
+            symbol: "ETHUSDT".into(),
+            lower: 1_000.0,
+            upper: 1_100.0,
+            levels: 6,
+            order_quantity: 1.0,
+            max_inventory: 1.5,
+            rebalance_on_exit: true,
+        };
+        let ctx = StrategyContext::new(
+            Arc::new(FeePrecisionRepoImpl::default()),
+            Arc::new(adapters::metrics::AdapterMetrics::new()),
+        );
+        ctx.set_min_profit_for_strategy("grid_trading", FixedPrice::from_f64(0.001, 6));
+        let store = Arc::new(MemoryGridStateStore::default());
+
+        let grid = GridStrategy::new(vec![config.clone()], Box::new(SharedStore(store.clone()))).unwrap();
+        let opportunity = grid.detect(&ctx, &snapshot(1_050.0)).expect("grid pair around 1050");
+        assert_eq!(opportunity.tags["grid_levels"], "2,3");
+        let result = grid.execute(&ctx, &opportunity).await.unwrap();
+        assert_eq!(result.order_ids, vec!["grid-binance-ETHUSDT-g0-l2", "grid-binance-ETHUSDT-g0-l3"]);
+
+        // 重启后恢复挂单状态，不重复下单
+        let restarted = GridStrategy::new(vec![config], Box::new(SharedStore(store))).unwrap();
+        assert!(restarted.detect(&ctx, &snapshot(1_050.0)).is_none());
+        assert!(!restarted.execute(&ctx, &opportunity).await.unwrap().accepted);
+
+        // 库存接近上限时不再开新的买档
+        restarted.on_fill("binance", "ETHUSDT", "grid-binance-ETHUSDT-g0-l2", Side::Buy, 1.0);
+        assert!(restarted.detect(&ctx, &snapshot(1_030.0)).is_none());
+
+        // 离开区间后重建并撤销旧挂单
+        assert!(restarted.detect(&ctx, &snapshot(1_200.0)).is_none());
+        let state = restarted.state("binance", "ETHUSDT").unwrap();
+        assert_eq!((state.generation, state.lower, state.upper), (1, 1_150.0, 1_250.0));
+        assert_eq!(restarted.take_pending_cancels(), vec![("binance".to_string(), "grid-binance-ETHUSDT-g0-l3".to_string())]);
+    }
+}
@@ -6,7 +6,7 @@ use crate::{
 };
 use async_trait::async_trait;
 use common::{
-    arbitrage::{ArbitrageLeg, ArbitrageOpportunity, Side},
+    arbitrage::{ArbitrageLeg, ArbitrageOpportunity, MarketType, Side},
     market_data::{NormalizedSnapshot, OrderBook},
     precision::{FixedPrice, FixedQuantity},
 };
@@ -33,7 +33,7 @@ impl ArbitrageStrategy for InterExchangeStrategy {
             return None;
         }
 
-        let min_profit_pct = ctx.current_min_profit_pct();
+        let min_profit_pct = ctx.min_profit_pct_for(self.name());
 
         // Only consider order books for the same symbol as the snapshot
         let same_symbol_books: Vec<&OrderBook> = input
@@ -45,18 +45,40 @@ impl ArbitrageStrategy for InterExchangeStrategy {
             return None;
         }
 
-        // Iterate over all unique pairs of exchanges for the same symbol
+        // SoA prefilter: if no venue bids above another venue's ask, no pair can be profitable.
+        // One-sided books are left out of the SoA view but can still form a pair, so only
+        // trust the prefilter when every book made it in
+        if same_symbol_books.len() == input.exchanges.len() {
+            let top = input.top_of_book();
+            if top.len() == input.exchanges.len() && !top.has_cross() {
+                return None;
+            }
+        }
+
+        // Evaluate both directions for every unique pair of exchanges
+        let mut candidates = Vec::new();
         for (book_a, book_b) in same_symbol_books.iter().copied().tuple_combinations() {
             // Opportunity: Buy on A, Sell on B
-            if let Some(opp) = self.find_opportunity(ctx, book_a, book_b, min_profit_pct) {
-                return Some(opp);
-            }
+            candidates.extend(self.find_opportunity(ctx, book_a, book_b, min_profit_pct));
             // Opportunity: Buy on B, Sell on A
-            if let Some(opp) = self.find_opportunity(ctx, book_b, book_a, min_profit_pct) {
-                return Some(opp);
-            }
+            candidates.extend(self.find_opportunity(ctx, book_b, book_a, min_profit_pct));
         }
-        None
+
+        // Most profitable route wins; routes within the tie tolerance of the
+        // best are near-equal and go to the venues with the higher weight
+        let venue_weight = |opp: &ArbitrageOpportunity| -> f64 {
+            opp.legs.iter().map(|l| ctx.get_exchange_weight(l.exchange.as_str())).sum()
+        };
+        let best_pct = candidates.iter().map(|c| c.net_profit_pct).max()?.to_f64();
+        candidates
+            .into_iter()
+            .filter(|c| best_pct - c.net_profit_pct.to_f64() <= ctx.venue_tie_tolerance_pct)
+            .max_by(|a, b| {
+                venue_weight(a)
+                    .partial_cmp(&venue_weight(b))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then_with(|| a.net_profit_pct.cmp(&b.net_profit_pct))
+            })
     }
 
     async fn execute(
@@ -182,7 +204,7 @@ impl InterExchangeStrategy {
         let net_profit_pct = FixedPrice::from_f64(net_profit.to_f64() / buy_cost.to_f64(), 6);
 
         // Apply slippage budget per leg: reduce expected proceeds and increase expected cost
-        let slip = ctx.inter_exchange_slippage_per_leg_pct;
+        let slip = ctx.slippage_per_leg_pct_for(self.name());
         let _ = slip; // used in required below to avoid unused warnings if cfg changes
         // approximate: require additional margin equal to 2*slippage (buy worse, sell worse)
         let required = FixedPrice::from_f64(min_profit_pct.to_f64() + 2.0 * slip, 6);
@@ -199,6 +221,7 @@ impl InterExchangeStrategy {
         let buy_leg = ArbitrageLeg {
             exchange: buy_book.exchange.clone(),
             symbol: buy_book.symbol.clone(),
+            market: MarketType::Spot,
             side: Side::Buy,
             price: buy_price.price,
             quantity: trade_qty,
@@ -208,6 +231,7 @@ impl InterExchangeStrategy {
         let sell_leg = ArbitrageLeg {
             exchange: sell_book.exchange.clone(),
             symbol: sell_book.symbol.clone(),
+            market: MarketType::Spot,
             side: Side::Sell,
             price: sell_price.price,
             quantity: trade_qty,
@@ -221,11 +245,9 @@ impl InterExchangeStrategy {
             sell_leg,
             net_profit,
             net_profit_pct,
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_nanos() as u64,
-        );
+            ctx.now_ns(),
+        )
+        .with_quote_watermark([buy_book.timestamp_ns, sell_book.timestamp_ns]);
 
         Some(opportunity)
     }
@@ -305,4 +327,33 @@ mod tests {
             panic!("Should detect profitable opportunity with 1000 USDT spread");
         }
     }
+
+    #[tokio::test]
+    async fn test_one_sided_books_still_pair() {
+        let strategy = InterExchangeStrategy;
+        let ctx = create_test_context();
+        let symbol = Symbol::new("BTCUSDT");
+
+        // Ask-only and bid-only books never enter the SoA view, so the prefilter must not drop them
+        let mut binance_book = OrderBook::new(Exchange::new("binance"), symbol.clone(), 1000000000, 1);
+        binance_book.add_ask(FixedPrice::from_f64(42800.00, 2), FixedQuantity::from_f64(0.5, 8));
+        let mut okx_book = OrderBook::new(Exchange::new("okx"), symbol.clone(), 1000000100, 1);
+        okx_book.add_bid(FixedPrice::from_f64(43800.00, 2), FixedQuantity::from_f64(0.5, 8));
+
+        let snapshot = NormalizedSnapshot {
+            symbol: symbol.clone(),
+            timestamp_ns: 1000000200,
+            exchanges: vec![binance_book, okx_book],
+            weighted_mid_price: FixedPrice::from_f64(43300.0, 2),
+            total_bid_volume: FixedQuantity::from_f64(0.5, 8),
+            total_ask_volume: FixedQuantity::from_f64(0.5, 8),
+            quality_score: 0.98,
+            sequence: Some(1),
+        };
+        assert!(snapshot.top_of_book().is_empty());
+
+        let opportunity = strategy.detect(&ctx, &snapshot).expect("one-sided books should still pair");
+        assert_eq!(opportunity.legs[0].exchange.as_str(), "binance");
+        assert_eq!(opportunity.legs[1].exchange.as_str(), "okx");
+    }
 }
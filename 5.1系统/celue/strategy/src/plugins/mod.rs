@@ -1,2 +1,4 @@
 pub mod inter_exchange;
 pub mod triangular;
+pub mod calendar_spread;
+pub mod grid;
@@ -12,10 +12,11 @@ use crate::{
     traits::{ArbitrageStrategy, StrategyKind, ExecutionResult, StrategyError},
     depth_analysis::DepthAnalyzer,
     dynamic_fee_calculator::{DynamicFeeCalculator, FeeType},
+    path_discovery::{Listing, PathDiscovery},
 };
 use async_trait::async_trait;
 use common::{
-    arbitrage::{ArbitrageOpportunity, ArbitrageLeg, Side}, 
+    arbitrage::{ArbitrageOpportunity, ArbitrageLeg, MarketType, Side}, 
     market_data::{NormalizedSnapshot, OrderBook}, 
     precision::{FixedPrice, FixedQuantity},
     types::{Exchange, Symbol}
@@ -65,6 +66,8 @@ pub struct TriangularPath {
     pub risk_score: u8,
     /// 预期滑点（百分比）
     pub expected_slippage: f64,
+    /// 三条腿所用订单簿的行情时间戳（纳秒），机会的报价水位取其中最旧者
+    pub quote_timestamps_ns: [u64; 3],
 }
 
 /// 高性能币种关系图
@@ -462,6 +465,20 @@ impl CurrencyRelationshipGraph {
         score.max(0.0).min(1.0)
     }
     
+    /// 指定交易所的上市交易对（base/quote 取自图构建时的解析结果）
+    pub fn listings(&self, exchange: &str) -> Vec<Listing> {
+        self.pair_info
+            .iter()
+            .filter(|((_, _, ex), _)| ex == exchange)
+            .map(|((base, quote, ex), ob)| Listing {
+                exchange: ex.clone(),
+                symbol: ob.symbol.as_str().to_string(),
+                base: base.clone(),
+                quote: quote.clone(),
+            })
+            .collect()
+    }
+
     /// 高性能三角路径发现（O(n^2)优化 + 早停）
     pub fn discover_triangular_paths_optimized_v2(&self, exchange_filter: Option<&str>, max_paths: usize) -> Result<Vec<TriangularPath>> {
         let start_time = Instant::now();
@@ -865,6 +882,7 @@ impl CurrencyRelationshipGraph {
             exchange: exchange.to_string(),
             risk_score,
             expected_slippage,
+            quote_timestamps_ns: [leg1_ob.timestamp_ns, leg2_ob.timestamp_ns, leg3_ob.timestamp_ns],
         })
     }
     
@@ -1029,6 +1047,9 @@ impl CurrencyRelationshipGraph {
 }
 
 /// 生产级动态三角套利策略 v3
+/// 接入环路发现时，按优先级重排前从图中多取的候选倍数
+const PRIORITY_CANDIDATE_FACTOR: usize = 4;
+
 pub struct DynamicTriangularStrategy {
     /// 智能符号解析缓存（实际使用）
     symbol_cache: Arc<Mutex<HashMap<String, (Option<ParsedTradingPair>, Instant)>>>,
@@ -1036,6 +1057,8 @@ pub struct DynamicTriangularStrategy {
     performance_stats: Arc<Mutex<PerformanceStats>>,
     /// 策略配置
     config: Arc<RwLock<StrategyConfig>>,
+    /// 增量环路发现（可选，提供历史收益/流动性优先级）
+    path_discovery: Option<Arc<PathDiscovery>>,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -1080,8 +1103,15 @@ impl DynamicTriangularStrategy {
             symbol_cache: Arc::new(Mutex::new(HashMap::new())),
             performance_stats: Arc::new(Mutex::new(PerformanceStats::default())),
             config: Arc::new(RwLock::new(StrategyConfig::default())),
+            path_discovery: None,
         }
     }
+
+    /// 接入增量环路发现，按历史收益和流动性排序检测路径
+    pub fn with_path_discovery(mut self, discovery: Arc<PathDiscovery>) -> Self {
+        self.path_discovery = Some(discovery);
+        self
+    }
     
     /// 从清洗数据检测三角套利机会（生产级v3）
     pub async fn detect_opportunities_production_v3(&self, ctx: &StrategyContext, input: &NormalizedSnapshot) -> Result<Vec<ArbitrageOpportunity>> {
@@ -1165,19 +1195,47 @@ impl DynamicTriangularStrategy {
         let obs: Vec<OrderBook> = orderbooks.iter().map(|&ob| ob.clone()).collect();
         let graph = CurrencyRelationshipGraph::build_from_cleaned_data_v3(&obs, None)?;
         
-        // 发现三角路径
-        let paths = graph.discover_triangular_paths_optimized_v2(Some(exchange), config.max_paths_per_detection)?;
+        // 发现三角路径；按环路优先级重排时先多取候选，排序后再截断
+        let paths = match &self.path_discovery {
+            Some(discovery) => {
+                // 只补充新出现的交易对；下市由上市监控负责，行情暂缺不删除环路
+                for listing in graph.listings(exchange) {
+                    discovery.add_listing(&listing);
+                }
+                let candidates = config.max_paths_per_detection.saturating_mul(PRIORITY_CANDIDATE_FACTOR);
+                let paths = graph.discover_triangular_paths_optimized_v2(Some(exchange), candidates)?;
+                Self::prioritize_paths(discovery, exchange, paths, config.max_paths_per_detection)
+            }
+            None => graph.discover_triangular_paths_optimized_v2(Some(exchange), config.max_paths_per_detection)?,
+        };
         
         // 转换为套利机会
         let opportunities: Result<Vec<_>> = paths.into_iter()
-            .map(|path| self.convert_to_arbitrage_opportunity_safe_v2(&path))
+            .map(|path| self.convert_to_arbitrage_opportunity_safe_v2(&path, ctx.now_ns()))
             .collect();
         
         Ok(opportunities?.into_iter().flatten().collect())
     }
     
+    /// 反馈观测收益/流动性，按环路优先级排序后保留前 `limit` 条
+    fn prioritize_paths(discovery: &PathDiscovery, exchange: &str, mut paths: Vec<TriangularPath>, limit: usize) -> Vec<TriangularPath> {
+        for path in &paths {
+            discovery.record_edge(exchange, &path.trading_pairs, path.net_profit_rate.to_f64() * 10_000.0);
+            for pair in &path.trading_pairs {
+                discovery.update_liquidity(exchange, pair, path.max_tradable_volume_usd.to_f64());
+            }
+        }
+        paths.sort_by(|a, b| {
+            discovery.priority_of(exchange, &b.trading_pairs)
+                .partial_cmp(&discovery.priority_of(exchange, &a.trading_pairs))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        paths.truncate(limit);
+        paths
+    }
+
     /// 安全转换为套利机会 v2（使用真实价格）
-    fn convert_to_arbitrage_opportunity_safe_v2(&self, path: &TriangularPath) -> Result<Option<ArbitrageOpportunity>> {
+    fn convert_to_arbitrage_opportunity_safe_v2(&self, path: &TriangularPath, now_ns: u64) -> Result<Option<ArbitrageOpportunity>> {
         // 应用基本阈值过滤（简化版）
         if path.net_profit_rate.to_f64() * 100.0 < 0.1 {
             return Ok(None);
@@ -1204,6 +1262,7 @@ impl DynamicTriangularStrategy {
             Ok(ArbitrageLeg {
                 exchange: Exchange::new(&path.exchange),
                 symbol: Symbol::new(&path.trading_pairs[i]),
+                market: MarketType::Spot,
                 side: path.directions[i],
                 price: path.prices[i],
                 quantity: path.quantities[i],
@@ -1212,18 +1271,17 @@ impl DynamicTriangularStrategy {
         }).collect();
         
         let net_profit_usd = path.max_tradable_volume_usd * path.net_profit_rate;
-        let net_profit_pct = path.net_profit_rate * FixedPrice::from_f64(100.0, 6);
+        // 与其他策略一致，net_profit_pct 以小数表示（0.001 = 0.1%）
+        let net_profit_pct = path.net_profit_rate;
         
+        // 报价水位取行情时间而非检测时间，陈旧订单簿构建的机会在认领时被拒绝
         Ok(Some(ArbitrageOpportunity::new_with_legs(
             "dynamic_triangular_v3",
             legs?,
             net_profit_usd,
             net_profit_pct,
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_nanos() as u64,
-        )))
+            now_ns,
+        ).with_quote_watermark(path.quote_timestamps_ns)))
     }
     
     /// 应用风险过滤器 v2（增强版）
@@ -1231,8 +1289,8 @@ impl DynamicTriangularStrategy {
         let mut filtered = Vec::new();
         
         for opp in opportunities {
-            // 最小利润过滤
-            if opp.net_profit_pct.to_f64() < 0.1 {
+            // 最小利润过滤（0.1%）
+            if opp.net_profit_pct.to_f64() < 0.001 {
                 continue;
             }
             
@@ -1355,7 +1413,7 @@ impl ArbitrageStrategy for DynamicTriangularStrategy {
     }
 
     async fn execute(&self, ctx: &StrategyContext, opp: &ArbitrageOpportunity) -> Result<ExecutionResult, StrategyError> {
-        tracing::info!("开始执行三角套利v3: 利润 {:.4}%", opp.net_profit_pct.to_f64());
+        tracing::info!("开始执行三角套利v3: 利润 {:.4}%", opp.net_profit_pct.to_f64() * 100.0);
         
         // TODO: 实现真实的原子性三角套利执行
         // 1. 预检查: 验证价格和流动性仍然有效
@@ -1394,4 +1452,114 @@ impl ArbitrageStrategy for TriangularStrategy {
         let dynamic_strategy = DynamicTriangularStrategy::new();
         dynamic_strategy.execute(ctx, opp).await
     }
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adapters::conflict::{ConflictResolver, Resolution};
+    use common::testing::leg;
+
+    fn path(net_profit_rate: f64) -> TriangularPath {
+        TriangularPath {
+            currencies: ["USDT".to_string(), "BTC".to_string(), "ETH".to_string()],
+            trading_pairs: ["BTCUSDT".to_string(), "ETHBTC".to_string(), "ETHUSDT".to_string()],
+            directions: [Side::Buy, Side::Buy, Side::Sell],
+            prices: [
+                FixedPrice::from_f64(50_000.0, 2),
+                FixedPrice::from_f64(0.06, 6),
+                FixedPrice::from_f64(3_020.0, 2),
+            ],
+            quantities: [
+                FixedQuantity::from_f64(0.1, 6),
+                FixedQuantity::from_f64(1.6, 6),
+                FixedQuantity::from_f64(1.6, 6),
+            ],
+            net_profit_rate: FixedPrice::from_f64(net_profit_rate, 6),
+            max_tradable_volume_usd: FixedPrice::from_f64(5_000.0, 2),
+            weight: FixedPrice::from_f64(0.0, 6),
+            exchange: "binance".to_string(),
+            risk_score: 10,
+            expected_slippage: 0.0,
+            quote_timestamps_ns: [900, 700, 800],
+        }
+    }
+
+    fn inter_exchange(net_profit_pct: f64) -> ArbitrageOpportunity {
+        ArbitrageOpportunity::new_inter_exchange(
+            "inter_exchange",
+            leg("binance", "BTCUSDT", Side::Buy, 50_000.0, 0.1),
+            leg("okx", "BTCUSDT", Side::Sell, 50_250.0, 0.1),
+            FixedPrice::from_f64(5_000.0 * net_profit_pct, 2),
+            FixedPrice::from_f64(net_profit_pct, 6),
+            0,
+        )
+    }
+
+    #[test]
+    fn test_net_profit_pct_is_a_fraction() {
+        let strategy = DynamicTriangularStrategy::new();
+        let opportunity = strategy.convert_to_arbitrage_opportunity_safe_v2(&path(0.0048), 0).unwrap().unwrap();
+        assert!((opportunity.net_profit_pct.to_f64() - 0.0048).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_quote_watermark_is_the_oldest_book() {
+        let strategy = DynamicTriangularStrategy::new();
+        let opportunity = strategy.convert_to_arbitrage_opportunity_safe_v2(&path(0.0048), 1_000).unwrap().unwrap();
+        assert_eq!((opportunity.created_at_ns, opportunity.quote_watermark_ns), (1_000, 700));
+        assert_eq!(opportunity.quote_age_ns(1_000), Some(300));
+    }
+
+    #[test]
+    fn test_paths_are_ranked_by_discovery_priority_before_truncation() {
+        let discovery = PathDiscovery::default();
+        let listing = |symbol: &str, base: &str, quote: &str| Listing {
+            exchange: "binance".to_string(),
+            symbol: symbol.to_string(),
+            base: base.to_string(),
+            quote: quote.to_string(),
+        };
+        discovery.sync_listings("binance", &[
+            listing("BTCUSDT", "BTC", "USDT"),
+            listing("ETHBTC", "ETH", "BTC"),
+            listing("ETHUSDT", "ETH", "USDT"),
+            listing("BNBUSDT", "BNB", "USDT"),
+            listing("BNBBTC", "BNB", "BTC"),
+        ]);
+
+        let eth = path(0.0048);
+        let mut bnb = path(0.0020);
+        bnb.currencies = ["USDT".to_string(), "BTC".to_string(), "BNB".to_string()];
+        bnb.trading_pairs = ["BTCUSDT".to_string(), "BNBBTC".to_string(), "BNBUSDT".to_string()];
+        // BNB 环路历史收益更高，当前利润率虽低仍应排在前面
+        for _ in 0..20 {
+            discovery.record_edge("binance", &bnb.trading_pairs, 200.0);
+        }
+
+        let ranked = DynamicTriangularStrategy::prioritize_paths(&discovery, "binance", vec![eth, bnb], 1);
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].trading_pairs[1], "BNBBTC");
+    }
+
+    #[test]
+    fn test_conflicts_with_inter_exchange_compare_like_units() {
+        let strategy = DynamicTriangularStrategy::new();
+        let resolver = ConflictResolver::default();
+
+        // 0.48% over three legs (×0.9) still beats a 0.40% inter-exchange edge
+        let mut inter = inter_exchange(0.004);
+        let mut better = strategy.convert_to_arbitrage_opportunity_safe_v2(&path(0.0048), 0).unwrap().unwrap();
+        assert_eq!(
+            resolver.resolve(&mut better, std::iter::once(&mut inter)),
+            Resolution::Keep { displaced: vec![inter.id] }
+        );
+
+        // 0.42% × 0.9 does not
+        let mut inter = inter_exchange(0.004);
+        let mut worse = strategy.convert_to_arbitrage_opportunity_safe_v2(&path(0.0042), 0).unwrap().unwrap();
+        assert_eq!(
+            resolver.resolve(&mut worse, std::iter::once(&mut inter)),
+            Resolution::Suppress { by: inter.id }
+        );
+    }
+}
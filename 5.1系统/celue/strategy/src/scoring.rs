@@ -0,0 +1,321 @@
+//! 机会评分 - 特征提取与可插拔模型
+//!
+//! 为每个套利机会提取特征（价差持续性、盘口失衡、近期波动率、交易所延迟、
+//! 手续费趋势），并通过可插拔的 `ScoringModel` 计算经过校准的置信度和
+//! 风险分数。默认提供逻辑回归模型；其他推理后端（如 ONNX）只需实现同一 trait。
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use common::{arbitrage::Side, ArbitrageOpportunity, NormalizedSnapshot};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+
+/// 特征名称（与 `OpportunityFeatures::to_vec` 顺序一致）
+pub const FEATURE_NAMES: [&str; 6] = [
+    "net_profit_bps",
+    "spread_persistence",
+    "book_imbalance",
+    "recent_volatility_bps",
+    "exchange_latency_ms",
+    "fee_trend_bps",
+];
+
+/// 单个机会的特征向量
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpportunityFeatures {
+    /// 净利润（bps）
+    pub net_profit_bps: f64,
+    /// 同一机会连续出现的快照数
+    pub spread_persistence: f64,
+    /// 顶档盘口失衡（-1..1，正值表示有利于成交方向）
+    pub book_imbalance: f64,
+    /// 中间价波动率（bps，EWMA）
+    pub recent_volatility_bps: f64,
+    /// 腿所在交易所的最大处理延迟（毫秒）
+    pub exchange_latency_ms: f64,
+    /// 手续费相对均值的变化（bps，正值表示费率上升）
+    pub fee_trend_bps: f64,
+}
+
+impl OpportunityFeatures {
+    pub fn to_vec(&self) -> Vec<f64> {
+        vec![
+            self.net_profit_bps,
+            self.spread_persistence,
+            self.book_imbalance,
+            self.recent_volatility_bps,
+            self.exchange_latency_ms,
+            self.fee_trend_bps,
+        ]
+    }
+}
+
+/// 模型输出
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct OpportunityScore {
+    /// 机会可成功兑现的概率（0..1）
+    pub confidence: f64,
+    /// 风险分数（0..1，越高越危险）
+    pub risk: f64,
+}
+
+impl OpportunityScore {
+    /// 按置信度和风险折算后的期望收益（bps），用于同轮机会排序
+    pub fn expected_edge_bps(&self, net_profit_bps: f64) -> f64 {
+        net_profit_bps * self.confidence * (1.0 - self.risk.clamp(0.0, 1.0))
+    }
+}
+
+/// 机会净利润（bps）；`net_profit_pct` 以小数表示，0.001 = 10 bps
+pub fn net_profit_bps(opportunity: &ArbitrageOpportunity) -> f64 {
+    opportunity.net_profit_pct.to_f64() * 10_000.0
+}
+
+/// 可插拔评分模型
+pub trait ScoringModel: Send + Sync {
+    fn name(&self) -> &str;
+    fn predict(&self, features: &OpportunityFeatures) -> OpportunityScore;
+}
+
+/// 逻辑回归模型：两个独立的二分类头
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogisticRegressionModel {
+    pub confidence_weights: Vec<f64>,
+    pub confidence_bias: f64,
+    pub risk_weights: Vec<f64>,
+    pub risk_bias: f64,
+}
+
+impl LogisticRegressionModel {
+    /// 从 JSON 权重文件加载
+    pub fn from_json_file(path: impl AsRef<Path>) -> Result<Self> {
+        let content = std::fs::read_to_string(path.as_ref())?;
+        let model: Self = serde_json::from_str(&content)?;
+        if model.confidence_weights.len() != FEATURE_NAMES.len()
+            || model.risk_weights.len() != FEATURE_NAMES.len()
+        {
+            return Err(anyhow!(
+                "模型权重维度不匹配: 期望 {} 个特征",
+                FEATURE_NAMES.len()
+            ));
+        }
+        Ok(model)
+    }
+
+    fn logit(weights: &[f64], bias: f64, x: &[f64]) -> f64 {
+        let z = bias + weights.iter().zip(x).map(|(w, v)| w * v).sum::<f64>();
+        1.0 / (1.0 + (-z).exp())
+    }
+}
+
+impl Default for LogisticRegressionModel {
+    /// 保守的先验权重：利润和持续性提升置信度，波动率、延迟和费率上升提高风险
+    fn default() -> Self {
+        Self {
+            confidence_weights: vec![0.05, 0.3, 0.8, -0.02, -0.05, -0.1],
+            confidence_bias: 0.0,
+            risk_weights: vec![-0.02, -0.2, -0.5, 0.04, 0.08, 0.1],
+            risk_bias: -1.0,
+        }
+    }
+}
+
+impl ScoringModel for LogisticRegressionModel {
+    fn name(&self) -> &str {
+        "logistic_regression"
+    }
+
+    fn predict(&self, features: &OpportunityFeatures) -> OpportunityScore {
+        let x = features.to_vec();
+        OpportunityScore {
+            confidence: Self::logit(&self.confidence_weights, self.confidence_bias, &x),
+            risk: Self::logit(&self.risk_weights, self.risk_bias, &x),
+        }
+    }
+}
+
+#[derive(Default)]
+struct FeatureState {
+    /// 机会键 -> (连续出现次数, 最近出现的快照时间)
+    persistence: HashMap<String, (u32, u64)>,
+    /// 符号 -> (上次中间价, 波动率 EWMA bps)
+    volatility: HashMap<String, (f64, f64)>,
+    /// 交易所 -> (最新费率 bps, 费率 EWMA bps)
+    fees: HashMap<String, (f64, f64)>,
+}
+
+/// 机会评分器：维护特征状态并调用模型
+pub struct OpportunityScorer {
+    model: RwLock<Box<dyn ScoringModel>>,
+    state: RwLock<FeatureState>,
+    /// EWMA 平滑系数
+    alpha: f64,
+    /// 超过该间隔未出现则重置持续性计数（纳秒）
+    persistence_gap_ns: u64,
+}
+
+impl OpportunityScorer {
+    pub fn new(model: Box<dyn ScoringModel>) -> Self {
+        Self {
+            model: RwLock::new(model),
+            state: RwLock::new(FeatureState::default()),
+            alpha: 0.1,
+            persistence_gap_ns: 1_000_000_000,
+        }
+    }
+
+    /// 替换模型
+    pub fn set_model(&self, model: Box<dyn ScoringModel>) {
+        *self.model.write() = model;
+    }
+
+    pub fn model_name(&self) -> String {
+        self.model.read().name().to_string()
+    }
+
+    /// 记录交易所当前手续费（bps），用于手续费趋势特征
+    pub fn record_fee(&self, exchange: &str, fee_bps: f64) {
+        let mut state = self.state.write();
+        let entry = state.fees.entry(exchange.to_string()).or_insert((fee_bps, fee_bps));
+        entry.0 = fee_bps;
+        entry.1 = self.alpha * fee_bps + (1.0 - self.alpha) * entry.1;
+    }
+
+    /// 用快照更新波动率状态
+    pub fn observe_snapshot(&self, snapshot: &NormalizedSnapshot) {
+        let mid = snapshot.weighted_mid_price.to_f64();
+        if mid <= 0.0 {
+            return;
+        }
+        let mut state = self.state.write();
+        let entry = state
+            .volatility
+            .entry(snapshot.symbol.as_str().to_string())
+            .or_insert((mid, 0.0));
+        let ret_bps = ((mid / entry.0).ln() * 10_000.0).abs();
+        entry.1 = self.alpha * ret_bps + (1.0 - self.alpha) * entry.1;
+        entry.0 = mid;
+    }
+
+    /// 提取机会特征
+    pub fn extract(&self, opportunity: &ArbitrageOpportunity, snapshot: &NormalizedSnapshot) -> OpportunityFeatures {
+        let key = opportunity
+            .legs
+            .iter()
+            .map(|l| format!("{}:{}:{:?}", l.exchange.as_str(), l.symbol.as_str(), l.side))
+            .collect::<Vec<_>>()
+            .join("|");
+
+        let mut state = self.state.write();
+
+        let persistence = {
+            let entry = state.persistence.entry(key).or_insert((0, 0));
+            if snapshot.timestamp_ns.saturating_sub(entry.1) > self.persistence_gap_ns {
+                entry.0 = 0;
+            }
+            entry.0 += 1;
+            entry.1 = snapshot.timestamp_ns;
+            entry.0 as f64
+        };
+
+        let mut imbalance_sum = 0.0;
+        let mut imbalance_legs = 0;
+        let mut latency_ns = 0u64;
+        let mut fee_trend = 0.0;
+        for leg in &opportunity.legs {
+            if let Some(book) = snapshot.exchanges.iter().find(|b| b.exchange == leg.exchange && b.symbol == leg.symbol) {
+                let bid = book.bid_quantities.first().map(|q| q.to_f64()).unwrap_or(0.0);
+                let ask = book.ask_quantities.first().map(|q| q.to_f64()).unwrap_or(0.0);
+                if bid + ask > 0.0 {
+                    // 买入时卖盘厚更有利，卖出时买盘厚更有利
+                    let raw = (bid - ask) / (bid + ask);
+                    imbalance_sum += match leg.side {
+                        Side::Buy => -raw,
+                        Side::Sell => raw,
+                    };
+                    imbalance_legs += 1;
+                }
+                latency_ns = latency_ns.max(book.processing_latency_ns);
+            }
+            if let Some((current, mean)) = state.fees.get(leg.exchange.as_str()) {
+                fee_trend += current - mean;
+            }
+        }
+
+        let volatility = opportunity
+            .legs
+            .iter()
+            .filter_map(|l| state.volatility.get(l.symbol.as_str()).map(|v| v.1))
+            .fold(0.0, f64::max);
+
+        OpportunityFeatures {
+            net_profit_bps: net_profit_bps(opportunity),
+            spread_persistence: persistence,
+            book_imbalance: if imbalance_legs > 0 { imbalance_sum / imbalance_legs as f64 } else { 0.0 },
+            recent_volatility_bps: volatility,
+            exchange_latency_ms: latency_ns as f64 / 1_000_000.0,
+            fee_trend_bps: fee_trend,
+        }
+    }
+
+    /// 评分并把结果写入机会标签（confidence_score / risk_score）
+    pub fn score(&self, opportunity: &mut ArbitrageOpportunity, snapshot: &NormalizedSnapshot) -> OpportunityScore {
+        self.observe_snapshot(snapshot);
+        let features = self.extract(opportunity, snapshot);
+        let score = self.model.read().predict(&features);
+        opportunity.tags.insert("confidence_score".to_string(), format!("{:.4}", score.confidence));
+        opportunity.tags.insert("risk_score".to_string(), format!("{:.4}", score.risk));
+        opportunity.tags.insert("scoring_model".to_string(), self.model_name());
+        score
+    }
+}
+
+impl Default for OpportunityScorer {
+    fn default() -> Self {
+        Self::new(Box::new(LogisticRegressionModel::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::testing::{book, inter_exchange_opportunity, snapshot};
+
+    #[test]
+    fn test_features_use_bps_and_persistence() {
+        let scorer = OpportunityScorer::default();
+        let opportunity = inter_exchange_opportunity("inter_exchange", "BTCUSDT", 0.004);
+        let books = vec![book("binance", "BTCUSDT", 99.9, 100.0, 0), book("okx", "BTCUSDT", 100.5, 100.6, 0)];
+
+        let first = scorer.extract(&opportunity, &snapshot("BTCUSDT", books.clone(), 1_000));
+        assert!((first.net_profit_bps - 40.0).abs() < 1e-9);
+        assert_eq!(first.spread_persistence, 1.0);
+        let second = scorer.extract(&opportunity, &snapshot("BTCUSDT", books.clone(), 2_000));
+        assert_eq!(second.spread_persistence, 2.0);
+        // 超过间隔未出现则重新计数
+        let later = scorer.extract(&opportunity, &snapshot("BTCUSDT", books, 5_000_000_000));
+        assert_eq!(later.spread_persistence, 1.0);
+    }
+
+    #[test]
+    fn test_score_ranks_by_expected_edge() {
+        let scorer = OpportunityScorer::default();
+        let books = vec![book("binance", "BTCUSDT", 99.9, 100.0, 0), book("okx", "BTCUSDT", 100.5, 100.6, 0)];
+        let market = snapshot("BTCUSDT", books, 1_000);
+
+        let mut thin = inter_exchange_opportunity("inter_exchange", "BTCUSDT", 0.001);
+        let mut rich = inter_exchange_opportunity("inter_exchange", "ETHUSDT", 0.006);
+        let thin_score = scorer.score(&mut thin, &market);
+        let rich_score = scorer.score(&mut rich, &market);
+        assert!(rich_score.confidence > thin_score.confidence);
+        assert!(rich_score.expected_edge_bps(net_profit_bps(&rich)) > thin_score.expected_edge_bps(net_profit_bps(&thin)));
+        assert_eq!(rich.tags["scoring_model"], "logistic_regression");
+        assert!(rich.tags.contains_key("confidence_score"));
+
+        let certain = OpportunityScore { confidence: 1.0, risk: 0.0 };
+        assert_eq!(certain.expected_edge_bps(25.0), 25.0);
+        assert_eq!(OpportunityScore { confidence: 0.5, risk: 0.5 }.expected_edge_bps(40.0), 10.0);
+    }
+}
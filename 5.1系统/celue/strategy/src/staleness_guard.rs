@@ -0,0 +1,256 @@
+//! 行情陈旧保护 - 按交易所+币对
+//!
+//! 记录每个交易所+币对最近一次行情更新时间。检测前把超过最大时延的
+//! 订单簿从快照中剔除，使陈旧报价不参与机会检测；受影响的机会打上
+//! `stale_excluded` 标签。数据恢复后自动重新纳入。进入/退出陈旧状态时
+//! 更新按币对的指标并发出告警。
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use adapters::alerting::{Alert, AlertManager, AlertSeverity};
+use common::{ArbitrageOpportunity, NormalizedSnapshot, OrderBook};
+use metrics::{counter, gauge};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// 机会标签：检测时被剔除的陈旧交易所
+pub const STALE_EXCLUDED_TAG: &str = "stale_excluded";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StalenessGuardConfig {
+    /// 默认最大行情时延（毫秒）
+    pub max_age_ms: u64,
+    /// 按币对覆盖
+    #[serde(default)]
+    pub per_symbol_max_age_ms: HashMap<String, u64>,
+}
+
+impl Default for StalenessGuardConfig {
+    fn default() -> Self {
+        Self {
+            max_age_ms: std::env::var("CELUE_MAX_MARKET_DATA_AGE_MS")
+                .ok().and_then(|s| s.parse().ok())
+                .unwrap_or(2_000),
+            per_symbol_max_age_ms: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct FeedState {
+    last_update_ns: u64,
+    stale_since_ns: Option<u64>,
+    stale_count: u64,
+}
+
+/// 单路行情状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedStatus {
+    pub exchange: String,
+    pub symbol: String,
+    pub last_update_ns: u64,
+    pub stale: bool,
+    /// 累计进入陈旧状态次数
+    pub stale_count: u64,
+}
+
+/// 状态切换
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FeedTransition {
+    WentStale { exchange: String, symbol: String, age_ms: u64 },
+    Recovered { exchange: String, symbol: String, stale_for_ms: u64 },
+}
+
+/// 剔除陈旧订单簿后的快照
+#[derive(Debug, Clone)]
+pub struct GuardedSnapshot {
+    pub snapshot: NormalizedSnapshot,
+    /// 被剔除的交易所
+    pub excluded: Vec<String>,
+    pub transitions: Vec<FeedTransition>,
+}
+
+impl GuardedSnapshot {
+    /// 在机会上标记检测时被剔除的交易所
+    pub fn mark(&self, opportunity: &mut ArbitrageOpportunity) {
+        if !self.excluded.is_empty() {
+            opportunity.tags.insert(STALE_EXCLUDED_TAG.to_string(), self.excluded.join(","));
+        }
+    }
+}
+
+/// 陈旧行情保护
+pub struct StaleDataGuard {
+    config: StalenessGuardConfig,
+    feeds: RwLock<HashMap<(String, String), FeedState>>,
+    alerts: Option<Arc<AlertManager>>,
+}
+
+impl StaleDataGuard {
+    pub fn new(config: StalenessGuardConfig) -> Self {
+        Self { config, feeds: RwLock::new(HashMap::new()), alerts: None }
+    }
+
+    /// 陈旧/恢复时通过告警管理器通知
+    pub fn with_alerts(mut self, alerts: Arc<AlertManager>) -> Self {
+        self.alerts = Some(alerts);
+        self
+    }
+
+    fn max_age_ns(&self, symbol: &str) -> u64 {
+        self.config.per_symbol_max_age_ms.get(symbol).copied().unwrap_or(self.config.max_age_ms) * 1_000_000
+    }
+
+    /// 记录一次订单簿更新
+    pub fn on_book(&self, book: &OrderBook) {
+        let key = (book.exchange.as_str().to_lowercase(), book.symbol.as_str().to_string());
+        let mut feeds = self.feeds.write();
+        let feed = feeds.entry(key).or_default();
+        feed.last_update_ns = feed.last_update_ns.max(book.timestamp_ns);
+    }
+
+    /// 记录快照中的订单簿并剔除在 `now_ns` 时已陈旧的交易所
+    pub fn filter(&self, snapshot: &NormalizedSnapshot, now_ns: u64) -> GuardedSnapshot {
+        for book in &snapshot.exchanges {
+            self.on_book(book);
+        }
+        let symbol = snapshot.symbol.as_str();
+        let max_age_ns = self.max_age_ns(symbol);
+        let mut transitions = Vec::new();
+        let mut excluded = Vec::new();
+        let mut fresh = Vec::with_capacity(snapshot.exchanges.len());
+        {
+            let mut feeds = self.feeds.write();
+            for book in &snapshot.exchanges {
+                let exchange = book.exchange.as_str().to_lowercase();
+                let Some(feed) = feeds.get_mut(&(exchange.clone(), symbol.to_string())) else { continue };
+                let age_ns = now_ns.saturating_sub(feed.last_update_ns);
+                match (age_ns > max_age_ns, feed.stale_since_ns) {
+                    (true, None) => {
+                        feed.stale_since_ns = Some(now_ns);
+                        feed.stale_count += 1;
+                        transitions.push(FeedTransition::WentStale {
+                            exchange: exchange.clone(),
+                            symbol: symbol.to_string(),
+                            age_ms: age_ns / 1_000_000,
+                        });
+                    }
+                    (false, Some(since)) => {
+                        feed.stale_since_ns = None;
+                        transitions.push(FeedTransition::Recovered {
+                            exchange: exchange.clone(),
+                            symbol: symbol.to_string(),
+                            stale_for_ms: now_ns.saturating_sub(since) / 1_000_000,
+                        });
+                    }
+                    _ => {}
+                }
+                if feed.stale_since_ns.is_some() {
+                    excluded.push(exchange);
+                } else {
+                    fresh.push(book.clone());
+                }
+            }
+            let stale_feeds = feeds.iter().filter(|((_, s), f)| s == symbol && f.stale_since_ns.is_some()).count();
+            gauge!("strategy_stale_feeds", "symbol" => symbol.to_string()).set(stale_feeds as f64);
+        }
+        for transition in &transitions {
+            let (exchange, state) = match transition {
+                FeedTransition::WentStale { exchange, .. } => (exchange, "stale"),
+                FeedTransition::Recovered { exchange, .. } => (exchange, "recovered"),
+            };
+            counter!(
+                "strategy_stale_transitions_total",
+                "symbol" => symbol.to_string(), "exchange" => exchange.clone(), "state" => state
+            )
+            .increment(1);
+        }
+
+        GuardedSnapshot {
+            snapshot: NormalizedSnapshot { exchanges: fresh, ..snapshot.clone() },
+            excluded,
+            transitions,
+        }
+    }
+
+    /// 记录日志并发出告警
+    pub async fn notify(&self, transitions: &[FeedTransition]) {
+        for transition in transitions {
+            let (severity, key, title, message) = match transition {
+                FeedTransition::WentStale { exchange, symbol, age_ms } => {
+                    warn!("⏳ {} {} 行情陈旧 {}ms，暂停参与检测", exchange, symbol, age_ms);
+                    (
+                        AlertSeverity::Warning,
+                        format!("stale_data:{}:{}", exchange, symbol),
+                        "Market data stale",
+                        format!("{} {} has not updated for {}ms", exchange, symbol, age_ms),
+                    )
+                }
+                FeedTransition::Recovered { exchange, symbol, stale_for_ms } => {
+                    info!("✅ {} {} 行情恢复（陈旧 {}ms）", exchange, symbol, stale_for_ms);
+                    (
+                        AlertSeverity::Info,
+                        format!("stale_data_recovered:{}:{}", exchange, symbol),
+                        "Market data recovered",
+                        format!("{} {} resumed after {}ms", exchange, symbol, stale_for_ms),
+                    )
+                }
+            };
+            if let Some(alerts) = &self.alerts {
+                alerts.raise(Alert::new(&key, severity, title, &message, "stale_data_guard")).await;
+            }
+        }
+    }
+
+    pub fn status(&self) -> Vec<FeedStatus> {
+        let mut status: Vec<FeedStatus> = self
+            .feeds
+            .read()
+            .iter()
+            .map(|((exchange, symbol), feed)| FeedStatus {
+                exchange: exchange.clone(),
+                symbol: symbol.clone(),
+                last_update_ns: feed.last_update_ns,
+                stale: feed.stale_since_ns.is_some(),
+                stale_count: feed.stale_count,
+            })
+            .collect();
+        status.sort_by(|a, b| (&a.symbol, &a.exchange).cmp(&(&b.symbol, &b.exchange)));
+        status
+    }
+}
+
+impl Default for StaleDataGuard {
+    fn default() -> Self {
+        Self::new(StalenessGuardConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::testing;
+
+    #[test]
+    fn test_stale_books_excluded_until_fresh() {
+        let book = |exchange: &str, ts_ns: u64| testing::book(exchange, "BTCUSDT", 100.0, 101.0, ts_ns);
+        let snapshot = |books: Vec<OrderBook>, ts_ns: u64| testing::snapshot("BTCUSDT", books, ts_ns);
+        let guard = StaleDataGuard::new(StalenessGuardConfig { max_age_ms: 1_000, per_symbol_max_age_ms: HashMap::new() });
+        let s = 1_000_000_000;
+        let first = guard.filter(&snapshot(vec![book("binance", 10 * s), book("okx", 10 * s)], 10 * s), 10 * s);
+        assert!(first.excluded.is_empty());
+
+        // okx 停止更新
+        let second = guard.filter(&snapshot(vec![book("binance", 12 * s), book("okx", 10 * s)], 12 * s), 12 * s);
+        assert_eq!(second.excluded, vec!["okx".to_string()]);
+        assert_eq!(second.snapshot.exchanges.len(), 1);
+        assert!(matches!(second.transitions[0], FeedTransition::WentStale { .. }));
+
+        let third = guard.filter(&snapshot(vec![book("binance", 13 * s), book("okx", 13 * s)], 13 * s), 13 * s);
+        assert!(third.excluded.is_empty());
+        assert!(matches!(third.transitions[0], FeedTransition::Recovered { stale_for_ms: 1_000, .. }));
+        assert_eq!(guard.status().iter().find(|f| f.exchange == "okx").unwrap().stale_count, 1);
+    }
+}
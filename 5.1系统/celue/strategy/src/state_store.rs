@@ -0,0 +1,343 @@
+//! 策略内部状态持久化
+//!
+//! 持有滚动统计的策略（统计套利的均值/方差、做市库存等）通过
+//! `StrategyContext` 读写本存储，重启后恢复状态而不必重新预热。
+//! 读写在内存中完成，不阻塞检测热路径；后台周期性把有变更的策略整体
+//! 写入后端（本地文件，或 orchestrator 启用 `redis` feature 后的 Redis）。
+//!
+//! 每个条目带策略声明的结构版本号，版本不一致时读取返回
+//! `SchemaMismatch`，由策略决定迁移或丢弃；另带递增的修订号。每个策略的
+//! 序列化总大小受配额限制，超出配额的写入被拒绝。
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use common::SharedClock;
+use metrics::gauge;
+use parking_lot::RwLock;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{info, warn};
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum StateStoreError {
+    #[error("策略 {strategy} 状态超出配额: {used} > {quota} 字节")]
+    QuotaExceeded { strategy: String, used: usize, quota: usize },
+    #[error("策略 {strategy} 的状态 {key} 结构版本为 {stored}，期望 {expected}")]
+    SchemaMismatch { strategy: String, key: String, stored: u32, expected: u32 },
+    #[error("状态序列化失败: {0}")]
+    Serialization(String),
+    #[error("状态存储后端错误: {0}")]
+    Backend(String),
+}
+
+/// 单个状态条目
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateEntry {
+    /// 策略声明的结构版本
+    pub schema_version: u32,
+    /// 每次写入递增
+    pub revision: u64,
+    pub updated_at_ns: u64,
+    pub value: serde_json::Value,
+}
+
+impl StateEntry {
+    fn size(&self, key: &str) -> usize {
+        key.len() + serde_json::to_vec(&self.value).map(|v| v.len()).unwrap_or(0)
+    }
+}
+
+/// 状态存储后端，按策略整体读写
+#[async_trait]
+pub trait StateBackend: Send + Sync {
+    async fn load(&self, strategy: &str) -> Result<HashMap<String, StateEntry>, StateStoreError>;
+    async fn save(&self, strategy: &str, entries: &HashMap<String, StateEntry>) -> Result<(), StateStoreError>;
+}
+
+/// 内存后端（测试与回测）
+#[derive(Default)]
+pub struct MemoryStateBackend {
+    data: RwLock<HashMap<String, HashMap<String, StateEntry>>>,
+}
+
+#[async_trait]
+impl StateBackend for MemoryStateBackend {
+    async fn load(&self, strategy: &str) -> Result<HashMap<String, StateEntry>, StateStoreError> {
+        Ok(self.data.read().get(strategy).cloned().unwrap_or_default())
+    }
+
+    async fn save(&self, strategy: &str, entries: &HashMap<String, StateEntry>) -> Result<(), StateStoreError> {
+        self.data.write().insert(strategy.to_string(), entries.clone());
+        Ok(())
+    }
+}
+
+/// 本地文件后端：每个策略一个 JSON 文件，临时文件加 rename 保证原子性
+pub struct FileStateBackend {
+    dir: PathBuf,
+}
+
+impl FileStateBackend {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, strategy: &str) -> PathBuf {
+        let name: String = strategy
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+            .collect();
+        self.dir.join(format!("{}.json", name))
+    }
+}
+
+#[async_trait]
+impl StateBackend for FileStateBackend {
+    async fn load(&self, strategy: &str) -> Result<HashMap<String, StateEntry>, StateStoreError> {
+        match tokio::fs::read(self.path(strategy)).await {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| StateStoreError::Serialization(e.to_string())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+            Err(e) => Err(StateStoreError::Backend(e.to_string())),
+        }
+    }
+
+    async fn save(&self, strategy: &str, entries: &HashMap<String, StateEntry>) -> Result<(), StateStoreError> {
+        let backend = |e: std::io::Error| StateStoreError::Backend(e.to_string());
+        tokio::fs::create_dir_all(&self.dir).await.map_err(backend)?;
+        let path = self.path(strategy);
+        let tmp = path.with_extension("tmp");
+        let bytes = serde_json::to_vec(entries).map_err(|e| StateStoreError::Serialization(e.to_string()))?;
+        tokio::fs::write(&tmp, bytes).await.map_err(backend)?;
+        tokio::fs::rename(&tmp, &path).await.map_err(backend)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateStoreConfig {
+    /// 未单独配置的策略的配额（字节）
+    pub default_quota_bytes: usize,
+    pub quotas: HashMap<String, usize>,
+    pub flush_interval_ms: u64,
+}
+
+impl Default for StateStoreConfig {
+    fn default() -> Self {
+        Self {
+            default_quota_bytes: std::env::var("CELUE_STRATEGY_STATE_QUOTA_BYTES")
+                .ok().and_then(|s| s.parse().ok())
+                .unwrap_or(1024 * 1024),
+            quotas: HashMap::new(),
+            flush_interval_ms: 5_000,
+        }
+    }
+}
+
+/// 策略状态用量
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateUsage {
+    pub strategy: String,
+    pub entries: usize,
+    pub bytes: usize,
+    pub quota_bytes: usize,
+}
+
+#[derive(Default)]
+struct StrategyState {
+    entries: HashMap<String, StateEntry>,
+    bytes: usize,
+    dirty: bool,
+}
+
+/// 策略状态存储
+pub struct StrategyStateStore {
+    config: StateStoreConfig,
+    backend: Arc<dyn StateBackend>,
+    states: RwLock<HashMap<String, StrategyState>>,
+    clock: SharedClock,
+}
+
+impl StrategyStateStore {
+    pub fn new(config: StateStoreConfig, backend: Arc<dyn StateBackend>) -> Self {
+        Self { config, backend, states: RwLock::new(HashMap::new()), clock: common::clock::system_clock() }
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    fn quota(&self, strategy: &str) -> usize {
+        self.config.quotas.get(strategy).copied().unwrap_or(self.config.default_quota_bytes)
+    }
+
+    /// 读取状态；结构版本不一致时返回 `SchemaMismatch`
+    pub fn get<T: DeserializeOwned>(&self, strategy: &str, key: &str, schema_version: u32) -> Result<Option<T>, StateStoreError> {
+        let states = self.states.read();
+        let Some(entry) = states.get(strategy).and_then(|s| s.entries.get(key)) else { return Ok(None) };
+        if entry.schema_version != schema_version {
+            return Err(StateStoreError::SchemaMismatch {
+                strategy: strategy.to_string(),
+                key: key.to_string(),
+                stored: entry.schema_version,
+                expected: schema_version,
+            });
+        }
+        serde_json::from_value(entry.value.clone())
+            .map(Some)
+            .map_err(|e| StateStoreError::Serialization(e.to_string()))
+    }
+
+    /// 条目元数据（结构版本、修订号、更新时间）
+    pub fn entry(&self, strategy: &str, key: &str) -> Option<StateEntry> {
+        self.states.read().get(strategy).and_then(|s| s.entries.get(key)).cloned()
+    }
+
+    /// 写入状态，返回新的修订号
+    pub fn put<T: Serialize>(&self, strategy: &str, key: &str, schema_version: u32, value: &T) -> Result<u64, StateStoreError> {
+        let value = serde_json::to_value(value).map_err(|e| StateStoreError::Serialization(e.to_string()))?;
+        let quota = self.quota(strategy);
+        let mut states = self.states.write();
+        let state = states.entry(strategy.to_string()).or_default();
+        let previous = state.entries.get(key);
+        let revision = previous.map_or(1, |e| e.revision + 1);
+        let entry = StateEntry { schema_version, revision, updated_at_ns: self.clock.now_ns(), value };
+        let used = state.bytes - previous.map_or(0, |e| e.size(key)) + entry.size(key);
+        if used > quota {
+            return Err(StateStoreError::QuotaExceeded { strategy: strategy.to_string(), used, quota });
+        }
+        state.entries.insert(key.to_string(), entry);
+        state.bytes = used;
+        state.dirty = true;
+        gauge!("strategy_state_bytes", "strategy" => strategy.to_string()).set(used as f64);
+        Ok(revision)
+    }
+
+    pub fn remove(&self, strategy: &str, key: &str) -> bool {
+        let mut states = self.states.write();
+        let Some(state) = states.get_mut(strategy) else { return false };
+        let Some(entry) = state.entries.remove(key) else { return false };
+        state.bytes -= entry.size(key);
+        state.dirty = true;
+        true
+    }
+
+    /// 清空策略状态（如策略参数大改后重新预热）
+    pub fn clear(&self, strategy: &str) {
+        let mut states = self.states.write();
+        let state = states.entry(strategy.to_string()).or_default();
+        state.entries.clear();
+        state.bytes = 0;
+        state.dirty = true;
+    }
+
+    pub fn usage(&self, strategy: &str) -> StateUsage {
+        let states = self.states.read();
+        let state = states.get(strategy);
+        StateUsage {
+            strategy: strategy.to_string(),
+            entries: state.map_or(0, |s| s.entries.len()),
+            bytes: state.map_or(0, |s| s.bytes),
+            quota_bytes: self.quota(strategy),
+        }
+    }
+
+    /// 启动时从后端恢复策略状态，覆盖内存中的同名策略
+    pub async fn restore(&self, strategy: &str) -> Result<usize, StateStoreError> {
+        let entries = self.backend.load(strategy).await?;
+        let bytes = entries.iter().map(|(k, e)| e.size(k)).sum();
+        let count = entries.len();
+        self.states.write().insert(strategy.to_string(), StrategyState { entries, bytes, dirty: false });
+        info!("♻️ 已恢复策略 {} 的 {} 个状态条目 ({} 字节)", strategy, count, bytes);
+        Ok(count)
+    }
+
+    /// 把有变更的策略写入后端，返回写入的策略数
+    pub async fn flush(&self) -> Result<usize, StateStoreError> {
+        let dirty: Vec<(String, HashMap<String, StateEntry>)> = {
+            let mut states = self.states.write();
+            states
+                .iter_mut()
+                .filter(|(_, s)| s.dirty)
+                .map(|(name, s)| {
+                    s.dirty = false;
+                    (name.clone(), s.entries.clone())
+                })
+                .collect()
+        };
+        let mut saved = 0;
+        let mut first_error = None;
+        for (strategy, entries) in dirty {
+            match self.backend.save(&strategy, &entries).await {
+                Ok(()) => saved += 1,
+                Err(e) => {
+                    warn!("策略 {} 状态写入失败: {}", strategy, e);
+                    if let Some(state) = self.states.write().get_mut(&strategy) {
+                        state.dirty = true;
+                    }
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        first_error.map_or(Ok(saved), Err)
+    }
+
+    /// 周期性写入后端
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_millis(self.config.flush_interval_ms.max(1)));
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.flush().await {
+                    warn!("策略状态写入失败: {}", e);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Rolling {
+        mean: f64,
+        samples: u64,
+    }
+
+    #[tokio::test]
+    async fn test_state_survives_restart_with_quota_and_schema() {
+        let backend = Arc::new(MemoryStateBackend::default());
+        let config = StateStoreConfig {
+            quotas: HashMap::from([("market_making".to_string(), 64)]),
+            ..Default::default()
+        };
+        let store = StrategyStateStore::new(config.clone(), backend.clone());
+        let stats = Rolling { mean: 1.5, samples: 10 };
+        assert_eq!(store.put("stat_arb", "btc_eth_spread", 1, &stats).unwrap(), 1);
+        assert_eq!(store.put("stat_arb", "btc_eth_spread", 1, &stats).unwrap(), 2);
+        assert!(matches!(
+            store.put("market_making", "inventory", 1, &vec![0.0_f64; 32]),
+            Err(StateStoreError::QuotaExceeded { .. })
+        ));
+        assert_eq!(store.usage("market_making").bytes, 0);
+        assert_eq!(store.flush().await.unwrap(), 1);
+        assert_eq!(store.flush().await.unwrap(), 0);
+
+        let restarted = StrategyStateStore::new(config, backend);
+        assert_eq!(restarted.restore("stat_arb").await.unwrap(), 1);
+        assert_eq!(restarted.get::<Rolling>("stat_arb", "btc_eth_spread", 1).unwrap(), Some(stats));
+        assert_eq!(restarted.entry("stat_arb", "btc_eth_spread").unwrap().revision, 2);
+        assert!(matches!(
+            restarted.get::<Rolling>("stat_arb", "btc_eth_spread", 2),
+            Err(StateStoreError::SchemaMismatch { stored: 1, expected: 2, .. })
+        ));
+        assert!(restarted.remove("stat_arb", "btc_eth_spread"));
+        assert_eq!(restarted.usage("stat_arb").bytes, 0);
+    }
+}
@@ -0,0 +1,346 @@
+//! 动态利润阈值控制器 - 按波动率状态调整
+//!
+//! 从行情分析模块读取所关注交易对的日波动率，划分市场状态
+//! （平静/正常/波动/极端），按策略缩放 `min_profit_threshold` 与单腿滑点
+//! 缓冲并写入 `StrategyContext`。状态切换带滞回区间：升级立即生效，
+//! 降级需在当前状态停留满最短时长，避免在边界附近反复切换。
+//! 每次调整都记录审计条目。
+
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use adapters::analytics::MarketAnalytics;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use common::precision::FixedPrice;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::context::StrategyContext;
+use crate::market_state::MarketState;
+
+const MAX_AUDIT_ENTRIES: usize = 1000;
+
+/// 波动率状态，由低到高
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VolatilityRegime {
+    Calm,
+    Normal,
+    Volatile,
+    Extreme,
+}
+
+impl VolatilityRegime {
+    fn up(self) -> Option<Self> {
+        match self {
+            Self::Calm => Some(Self::Normal),
+            Self::Normal => Some(Self::Volatile),
+            Self::Volatile => Some(Self::Extreme),
+            Self::Extreme => None,
+        }
+    }
+
+    fn down(self) -> Option<Self> {
+        match self {
+            Self::Calm => None,
+            Self::Normal => Some(Self::Calm),
+            Self::Volatile => Some(Self::Normal),
+            Self::Extreme => Some(Self::Volatile),
+        }
+    }
+
+    /// 对应的粗粒度市场状态
+    pub fn market_state(self) -> MarketState {
+        match self {
+            Self::Calm | Self::Normal => MarketState::Regular,
+            Self::Volatile => MarketState::Cautious,
+            Self::Extreme => MarketState::Extreme,
+        }
+    }
+}
+
+/// 状态分界（日波动率）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegimeBands {
+    /// 低于此值为平静
+    pub calm_below: f64,
+    /// 高于此值为波动
+    pub volatile_above: f64,
+    /// 高于此值为极端
+    pub extreme_above: f64,
+}
+
+impl RegimeBands {
+    /// 进入某状态所需的最低波动率
+    fn lower_bound(&self, regime: VolatilityRegime) -> f64 {
+        match regime {
+            VolatilityRegime::Calm => 0.0,
+            VolatilityRegime::Normal => self.calm_below,
+            VolatilityRegime::Volatile => self.volatile_above,
+            VolatilityRegime::Extreme => self.extreme_above,
+        }
+    }
+}
+
+/// 某状态下相对基准值的倍数
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RegimeScaling {
+    pub profit_multiplier: f64,
+    pub slippage_multiplier: f64,
+}
+
+/// 各状态的缩放倍数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegimeScalingTable {
+    pub calm: RegimeScaling,
+    pub normal: RegimeScaling,
+    pub volatile: RegimeScaling,
+    pub extreme: RegimeScaling,
+}
+
+impl RegimeScalingTable {
+    pub fn get(&self, regime: VolatilityRegime) -> RegimeScaling {
+        match regime {
+            VolatilityRegime::Calm => self.calm,
+            VolatilityRegime::Normal => self.normal,
+            VolatilityRegime::Volatile => self.volatile,
+            VolatilityRegime::Extreme => self.extreme,
+        }
+    }
+}
+
+impl Default for RegimeScalingTable {
+    fn default() -> Self {
+        let scaling = |profit_multiplier, slippage_multiplier| RegimeScaling { profit_multiplier, slippage_multiplier };
+        Self {
+            calm: scaling(0.8, 0.8),
+            normal: scaling(1.0, 1.0),
+            volatile: scaling(1.5, 1.5),
+            extreme: scaling(2.5, 2.0),
+        }
+    }
+}
+
+/// 策略的基准阈值（正常状态下的取值）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyThresholdBase {
+    pub min_profit_bps: f64,
+    pub slippage_per_leg_pct: f64,
+    /// 策略专属缩放表；未设置时使用全局表
+    #[serde(default)]
+    pub scaling: Option<RegimeScalingTable>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThresholdControllerConfig {
+    pub bands: RegimeBands,
+    /// 滞回比例：升级需超过分界 (1 + h) 倍，降级需低于分界 (1 - h) 倍
+    pub hysteresis_pct: f64,
+    /// 降级前在当前状态的最短停留时间（秒）
+    pub min_dwell_secs: i64,
+    /// 参与判断的交易对，取其中最大的波动率；为空则不调整
+    pub symbols: Vec<String>,
+    #[serde(default)]
+    pub scaling: RegimeScalingTable,
+    pub strategies: HashMap<String, StrategyThresholdBase>,
+}
+
+impl ThresholdControllerConfig {
+    /// 从 TOML 文件加载
+    pub fn load_file(path: impl AsRef<Path>) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(toml::from_str(&content)?)
+    }
+}
+
+impl Default for ThresholdControllerConfig {
+    fn default() -> Self {
+        Self {
+            bands: RegimeBands {
+                calm_below: 0.015,
+                volatile_above: 0.04,
+                extreme_above: 0.08,
+            },
+            hysteresis_pct: 0.1,
+            min_dwell_secs: 300,
+            symbols: Vec::new(),
+            scaling: RegimeScalingTable::default(),
+            strategies: HashMap::new(),
+        }
+    }
+}
+
+/// 一次阈值调整的审计记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThresholdAdjustment {
+    pub timestamp: DateTime<Utc>,
+    pub strategy: String,
+    pub from_regime: Option<VolatilityRegime>,
+    pub to_regime: VolatilityRegime,
+    pub volatility: f64,
+    pub min_profit_bps: f64,
+    pub slippage_per_leg_pct: f64,
+}
+
+struct RegimeState {
+    regime: Option<VolatilityRegime>,
+    since: DateTime<Utc>,
+}
+
+/// 动态阈值控制器
+pub struct DynamicThresholdController {
+    config: ThresholdControllerConfig,
+    analytics: Arc<MarketAnalytics>,
+    state: Mutex<RegimeState>,
+    audit: Mutex<VecDeque<ThresholdAdjustment>>,
+}
+
+impl DynamicThresholdController {
+    pub fn new(config: ThresholdControllerConfig, analytics: Arc<MarketAnalytics>) -> Self {
+        Self {
+            config,
+            analytics,
+            state: Mutex::new(RegimeState { regime: None, since: Utc::now() }),
+            audit: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn current_regime(&self) -> Option<VolatilityRegime> {
+        self.state.lock().regime
+    }
+
+    /// 带滞回的状态判定
+    pub fn classify(&self, volatility: f64, current: Option<VolatilityRegime>) -> VolatilityRegime {
+        let bands = &self.config.bands;
+        let h = self.config.hysteresis_pct.max(0.0);
+        let Some(current) = current else {
+            // 首次判定不加滞回
+            let mut regime = VolatilityRegime::Calm;
+            while let Some(up) = regime.up().filter(|up| volatility >= bands.lower_bound(*up)) {
+                regime = up;
+            }
+            return regime;
+        };
+        let mut regime = current;
+        while let Some(up) = regime.up().filter(|up| volatility >= bands.lower_bound(*up) * (1.0 + h)) {
+            regime = up;
+        }
+        if regime == current {
+            while let Some(down) = regime.down().filter(|_| volatility < bands.lower_bound(regime) * (1.0 - h)) {
+                regime = down;
+            }
+        }
+        regime
+    }
+
+    fn observed_volatility(&self) -> Option<f64> {
+        self.config
+            .symbols
+            .iter()
+            .filter_map(|s| self.analytics.daily_volatility(s))
+            .reduce(f64::max)
+    }
+
+    /// 重新判定状态，状态变化时更新各策略阈值并返回调整记录
+    pub fn evaluate(&self, ctx: &StrategyContext, now: DateTime<Utc>) -> Vec<ThresholdAdjustment> {
+        let Some(volatility) = self.observed_volatility() else {
+            return Vec::new();
+        };
+        let (from_regime, regime) = {
+            let mut state = self.state.lock();
+            let previous = state.regime;
+            let mut next = self.classify(volatility, previous);
+            // 降级需满足最短停留时间
+            if let Some(previous) = previous {
+                if next < previous && (now - state.since).num_seconds() < self.config.min_dwell_secs {
+                    next = previous;
+                }
+            }
+            if previous == Some(next) {
+                return Vec::new();
+            }
+            state.regime = Some(next);
+            state.since = now;
+            (previous, next)
+        };
+
+        info!(
+            "🌡️ 波动率状态 {:?} -> {:?} (日波动率 {:.2}%)",
+            from_regime, regime, volatility * 100.0
+        );
+        ctx.update_market_state(regime.market_state());
+
+        let mut adjustments = Vec::new();
+        for (strategy, base) in &self.config.strategies {
+            let scaling = base.scaling.as_ref().unwrap_or(&self.config.scaling).get(regime);
+            let min_profit_bps = base.min_profit_bps * scaling.profit_multiplier;
+            let slippage_per_leg_pct = base.slippage_per_leg_pct * scaling.slippage_multiplier;
+            ctx.set_min_profit_for_strategy(strategy, FixedPrice::from_f64(min_profit_bps / 10_000.0, 6));
+            ctx.set_slippage_per_leg_for_strategy(strategy, slippage_per_leg_pct);
+            info!(
+                "📐 {} 阈值调整: 最小利润 {:.1}bps, 单腿滑点 {:.4}%",
+                strategy, min_profit_bps, slippage_per_leg_pct * 100.0
+            );
+            adjustments.push(ThresholdAdjustment {
+                timestamp: now,
+                strategy: strategy.clone(),
+                from_regime,
+                to_regime: regime,
+                volatility,
+                min_profit_bps,
+                slippage_per_leg_pct,
+            });
+        }
+
+        let mut audit = self.audit.lock();
+        audit.extend(adjustments.iter().cloned());
+        while audit.len() > MAX_AUDIT_ENTRIES {
+            audit.pop_front();
+        }
+        adjustments
+    }
+
+    /// 最近的调整记录，新的在前
+    pub fn audit_log(&self, limit: usize) -> Vec<ThresholdAdjustment> {
+        self.audit.lock().iter().rev().take(limit).cloned().collect()
+    }
+
+    /// 启动周期性评估
+    pub fn spawn(self: Arc<Self>, ctx: Arc<StrategyContext>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.evaluate(&ctx, ctx.clock().now());
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regime_hysteresis() {
+        let controller = DynamicThresholdController::new(
+            ThresholdControllerConfig::default(),
+            Arc::new(MarketAnalytics::default()),
+        );
+        use VolatilityRegime::*;
+        assert_eq!(controller.classify(0.01, None), Calm);
+        assert_eq!(controller.classify(0.05, None), Volatile);
+        // 刚越过分界不升级
+        assert_eq!(controller.classify(0.042, Some(Normal)), Normal);
+        assert_eq!(controller.classify(0.045, Some(Normal)), Volatile);
+        // 刚跌破分界不降级
+        assert_eq!(controller.classify(0.038, Some(Volatile)), Volatile);
+        assert_eq!(controller.classify(0.03, Some(Volatile)), Normal);
+        assert_eq!(controller.classify(0.005, Some(Volatile)), Calm);
+        assert_eq!(controller.classify(0.2, Some(Calm)), Extreme);
+    }
+}
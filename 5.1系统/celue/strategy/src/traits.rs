@@ -3,6 +3,7 @@
 use crate::context::StrategyContext;
 use async_trait::async_trait;
 use common::{arbitrage::ArbitrageOpportunity, market_data::NormalizedSnapshot};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 /// The kind of an arbitrage strategy.
@@ -10,6 +11,8 @@ use thiserror::Error;
 pub enum StrategyKind {
     InterExchange,
     Triangular,
+    CalendarSpread,
+    Grid,
 }
 
 /// Errors that can occur during strategy execution.
@@ -33,6 +36,22 @@ pub struct ExecutionResult {
     pub order_ids: Vec<String>,
 }
 
+/// Resources a strategy declares it needs to run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceRequirements {
+    /// CPU cores (fractional) used by detection and execution.
+    pub cpu_cores: f64,
+    pub memory_mb: u64,
+    /// Exchange API calls per second the strategy may make.
+    pub api_rate_limit: u32,
+}
+
+impl Default for ResourceRequirements {
+    fn default() -> Self {
+        Self { cpu_cores: 0.5, memory_mb: 128, api_rate_limit: 10 }
+    }
+}
+
 /// The core trait that all arbitrage strategies must implement.
 #[async_trait]
 pub trait ArbitrageStrategy: Send + Sync {
@@ -60,4 +79,10 @@ pub trait ArbitrageStrategy: Send + Sync {
         ctx: &StrategyContext,
         opp: &ArbitrageOpportunity,
     ) -> Result<ExecutionResult, StrategyError>;
+
+    /// Resource budget checked by the admission controller before the
+    /// strategy is enabled.
+    fn resource_requirements(&self) -> ResourceRequirements {
+        ResourceRequirements::default()
+    }
 }
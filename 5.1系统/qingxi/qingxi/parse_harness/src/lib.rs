@@ -0,0 +1,36 @@
+//! # 交易所消息解析测试工具
+//!
+//! 黄金样本按交易所存放在 `golden/<exchange>/*.json`，格式见
+//! `market_data_module::adapters::conformance::GoldenSample`。
+//! 新适配器接入时：
+//! 1. 在 `golden/<exchange>/` 下至少放入盘口快照和错误消息样本；
+//! 2. 用 `conformance_suite` 加载并传给 `AdapterRegistry::register_verified`；
+//! 3. `cargo test -p parse_harness` 会对所有内置适配器跑黄金样本、
+//!    变异输入和属性测试。
+
+use market_data_module::adapters::conformance::{ConformanceReport, ConformanceSuite};
+use market_data_module::{AdapterRegistry, ExchangeAdapter, MarketDataError};
+use std::path::PathBuf;
+
+/// 黄金样本根目录
+pub fn golden_root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("golden")
+}
+
+/// 某交易所的一致性套件
+pub fn conformance_suite(exchange: &str) -> Result<ConformanceSuite, MarketDataError> {
+    ConformanceSuite::load_dir(&golden_root().join(exchange))
+}
+
+/// 默认注册表中的全部适配器，按交易所 ID 排序
+pub fn builtin_adapters() -> Vec<Box<dyn ExchangeAdapter>> {
+    let registry = AdapterRegistry::default();
+    let mut exchanges = registry.registered_exchanges();
+    exchanges.sort();
+    exchanges.iter().filter_map(|id| registry.create_adapter(id)).collect()
+}
+
+/// 对一个适配器跑其交易所的套件
+pub fn check_adapter(adapter: &dyn ExchangeAdapter) -> Result<ConformanceReport, MarketDataError> {
+    Ok(conformance_suite(adapter.exchange_id())?.run(adapter))
+}
@@ -0,0 +1,31 @@
+use market_data_module::adapters::binance::BinanceAdapter;
+use market_data_module::AdapterRegistry;
+use parse_harness::{builtin_adapters, check_adapter, conformance_suite};
+
+#[test]
+fn builtin_adapters_pass_golden_samples_and_mutations() {
+    let adapters = builtin_adapters();
+    assert!(!adapters.is_empty());
+    for adapter in adapters {
+        let report = check_adapter(adapter.as_ref())
+            .unwrap_or_else(|e| panic!("{} 缺少黄金样本: {}", adapter.exchange_id(), e));
+        assert!(report.passed(), "{}: {:#?}", report.exchange, report.failures);
+        assert!(report.golden_checked > 0 && report.mutations_checked > 0);
+    }
+}
+
+#[test]
+fn registration_requires_passing_suite() {
+    let mut registry = AdapterRegistry::new();
+    let binance = conformance_suite("binance").unwrap();
+    assert!(registry
+        .register_verified("binance", || Box::new(BinanceAdapter::new()), &binance)
+        .is_ok());
+
+    // 拿 OKX 的样本验证 Binance 解析器，必然不符
+    let okx = conformance_suite("okx").unwrap();
+    assert!(registry
+        .register_verified("okx", || Box::new(BinanceAdapter::new()), &okx)
+        .is_err());
+    assert_eq!(registry.registered_exchanges(), vec!["binance".to_string()]);
+}
@@ -0,0 +1,79 @@
+//! 属性测试：任意输入都不能让解析器 panic
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use market_data_module::adapters::conformance::parse_guarded;
+use market_data_module::types::{SubscriptionDetail, Symbol};
+use parse_harness::builtin_adapters;
+use proptest::prelude::*;
+use serde_json::Value;
+use std::io::Write;
+use tokio_tungstenite::tungstenite::Message;
+
+fn subscriptions() -> Vec<SubscriptionDetail> {
+    vec![SubscriptionDetail::new(Symbol::new("BTC", "USDT"), "orderbook")]
+}
+
+fn gzip(bytes: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(bytes).unwrap();
+    encoder.finish().unwrap()
+}
+
+/// 任意 JSON，叶子偏向交易所消息里常见的键和数值
+fn arb_json() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(Value::from),
+        any::<u64>().prop_map(Value::from),
+        any::<f64>().prop_map(Value::from),
+        "[0-9.eE+-]{0,12}".prop_map(Value::String),
+        ".{0,8}".prop_map(Value::String),
+    ];
+    let key = prop_oneof![
+        Just("stream"), Just("data"), Just("e"), Just("s"), Just("b"), Just("a"), Just("u"), Just("E"),
+        Just("bids"), Just("asks"), Just("tick"), Just("ts"), Just("topic"), Just("op"), Just("seq"),
+        Just("time"), Just("channel"), Just("event"), Just("result"), Just("trades"), Just("ping"),
+    ];
+    leaf.prop_recursive(4, 48, 6, move |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..6).prop_map(Value::Array),
+            prop::collection::vec((key.clone(), inner), 0..6)
+                .prop_map(|entries| Value::Object(entries.into_iter().map(|(k, v)| (k.to_string(), v)).collect())),
+        ]
+    })
+}
+
+fn assert_no_panic(message: &Message) {
+    for adapter in builtin_adapters() {
+        assert!(
+            parse_guarded(adapter.as_ref(), message, &subscriptions()).is_some(),
+            "{} panicked on {:?}",
+            adapter.exchange_id(),
+            message
+        );
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    #[test]
+    fn arbitrary_text_never_panics(text in ".{0,256}") {
+        assert_no_panic(&Message::Text(text));
+    }
+
+    #[test]
+    fn arbitrary_binary_never_panics(bytes in prop::collection::vec(any::<u8>(), 0..256)) {
+        assert_no_panic(&Message::Binary(gzip(&bytes)));
+        assert_no_panic(&Message::Binary(bytes));
+    }
+
+    #[test]
+    fn arbitrary_json_never_panics(value in arb_json()) {
+        let text = value.to_string();
+        assert_no_panic(&Message::Binary(gzip(text.as_bytes())));
+        assert_no_panic(&Message::Text(text));
+    }
+}
@@ -60,19 +60,9 @@ impl BinanceAdapter {
     pub fn rest_api_url(&self) -> Option<&str> {
         self.rest_api_url.as_deref()
     }
-}
-
-#[async_trait]
-impl ExchangeAdapter for BinanceAdapter {
-    fn exchange_id(&self) -> &str {
-        "binance"
-    }
 
-    fn build_subscription_messages(
-        &self,
-        subscriptions: &[SubscriptionDetail],
-    ) -> Result<Vec<Message>, MarketDataError> {
-        let streams: Vec<String> = subscriptions
+    fn stream_names(subscriptions: &[SubscriptionDetail]) -> Result<Vec<String>, MarketDataError> {
+        subscriptions
             .iter()
             .map(|sub| match sub.channel.as_str() {
                 "orderbook" => Ok(format!(
@@ -87,15 +77,40 @@ impl ExchangeAdapter for BinanceAdapter {
                     "Unsupported channel type for Binance: {other}"
                 ))),
             })
-            .collect::<Result<_, _>>()?;
+            .collect()
+    }
+}
+
+#[async_trait]
+impl ExchangeAdapter for BinanceAdapter {
+    fn exchange_id(&self) -> &str {
+        "binance"
+    }
+
+    fn build_subscription_messages(
+        &self,
+        subscriptions: &[SubscriptionDetail],
+    ) -> Result<Vec<Message>, MarketDataError> {
         let sub_msg = json!({
             "method": "SUBSCRIBE",
-            "params": streams,
+            "params": Self::stream_names(subscriptions)?,
             "id": 1
         });
         Ok(vec![Message::Text(sub_msg.to_string())])
     }
 
+    fn build_unsubscription_messages(
+        &self,
+        subscriptions: &[SubscriptionDetail],
+    ) -> Result<Vec<Message>, MarketDataError> {
+        let unsub_msg = json!({
+            "method": "UNSUBSCRIBE",
+            "params": Self::stream_names(subscriptions)?,
+            "id": 2
+        });
+        Ok(vec![Message::Text(unsub_msg.to_string())])
+    }
+
     fn parse_message(
         &self,
         message: &Message,
@@ -0,0 +1,416 @@
+#![allow(dead_code)]
+//! # 适配器一致性套件
+//!
+//! 交易所消息解析各写各的，新增适配器时很容易漏掉边界情况。一致性套件
+//! 由两部分组成：
+//! - 黄金样本：每个交易所的真实推送（快照、增量、成交、错误、心跳），
+//!   解析结果归一化为 `ParsedSummary` 后必须与样本中记录的期望一致；
+//! - 畸形输入：对每个样本做截断、字节翻转、删字段、改类型等变异，
+//!   解析必须返回结果或错误，不允许 panic，空消息不得产出数据。
+//!
+//! `AdapterRegistry::register_verified` 只注册通过套件的适配器。
+
+use super::ExchangeAdapter;
+use crate::errors::MarketDataError;
+use crate::types::{SubscriptionDetail, Symbol, TradeSide};
+use crate::MarketDataMessage;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::Write;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::Path;
+use tokio_tungstenite::tungstenite::Message;
+
+/// 样本类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SampleKind {
+    BookSnapshot,
+    BookDelta,
+    Trade,
+    Heartbeat,
+    /// 订阅确认等控制消息
+    Control,
+    /// 交易所错误或格式非法的消息
+    Error,
+}
+
+/// 样本在线路上的编码
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Encoding {
+    /// `payload` 序列化为 JSON 文本帧
+    #[default]
+    Json,
+    /// `payload` 必须是字符串，原样作为文本帧
+    Raw,
+    /// 同 `Json`（字符串 `payload` 则取原文），gzip 压缩后作为二进制帧（火币）
+    Gzip,
+}
+
+/// 解析结果的归一化摘要；不含本地时间戳等不确定字段
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct ParsedSummary {
+    /// order_book / order_book_snapshot / order_book_update / trade /
+    /// heartbeat / snapshot / none / error
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bids: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asks: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub best_bid: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub best_ask: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence_id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantity: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub side: Option<String>,
+}
+
+impl ParsedSummary {
+    pub fn of(result: &Result<Option<MarketDataMessage>, MarketDataError>) -> Self {
+        let message = match result {
+            Err(_) => return Self { kind: "error".to_string(), ..Default::default() },
+            Ok(None) => return Self { kind: "none".to_string(), ..Default::default() },
+            Ok(Some(message)) => message,
+        };
+        let book = |kind: &str, symbol: &Symbol, bids: &[crate::types::OrderBookEntry], asks: &[crate::types::OrderBookEntry], sequence_id: Option<u64>| Self {
+            kind: kind.to_string(),
+            symbol: Some(symbol.as_pair()),
+            bids: Some(bids.len()),
+            asks: Some(asks.len()),
+            best_bid: bids.iter().map(|e| e.price.0).reduce(f64::max),
+            best_ask: asks.iter().map(|e| e.price.0).reduce(f64::min),
+            sequence_id,
+            ..Default::default()
+        };
+        match message {
+            MarketDataMessage::OrderBook(b) => book("order_book", &b.symbol, &b.bids, &b.asks, b.sequence_id),
+            MarketDataMessage::OrderBookSnapshot(b) => {
+                book("order_book_snapshot", &b.symbol, &b.bids, &b.asks, b.sequence_id)
+            }
+            MarketDataMessage::OrderBookUpdate(u) => {
+                book("order_book_update", &u.symbol, &u.bids, &u.asks, Some(u.final_update_id))
+            }
+            MarketDataMessage::Trade(t) => Self {
+                kind: "trade".to_string(),
+                symbol: Some(t.symbol.as_pair()),
+                price: Some(t.price.0),
+                quantity: Some(t.quantity.0),
+                side: Some(match t.side {
+                    TradeSide::Buy => "buy".to_string(),
+                    TradeSide::Sell => "sell".to_string(),
+                }),
+                ..Default::default()
+            },
+            MarketDataMessage::Heartbeat { .. } => Self { kind: "heartbeat".to_string(), ..Default::default() },
+            MarketDataMessage::Snapshot(_) => Self { kind: "snapshot".to_string(), ..Default::default() },
+        }
+    }
+}
+
+fn default_channel() -> String {
+    "orderbook".to_string()
+}
+
+/// 黄金样本，磁盘上每个样本一个 JSON 文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoldenSample {
+    pub name: String,
+    pub kind: SampleKind,
+    /// 解析时传入的订阅，形如 `BTC/USDT`
+    #[serde(default)]
+    pub subscriptions: Vec<String>,
+    #[serde(default = "default_channel")]
+    pub channel: String,
+    #[serde(default)]
+    pub encoding: Encoding,
+    pub payload: Value,
+    pub expected: ParsedSummary,
+}
+
+impl GoldenSample {
+    pub fn subscription_details(&self) -> Vec<SubscriptionDetail> {
+        self.subscriptions
+            .iter()
+            .filter_map(|pair| Symbol::from_pair(pair))
+            .map(|symbol| SubscriptionDetail::new(symbol, &self.channel))
+            .collect()
+    }
+
+    /// 线路上的原始字节
+    pub fn wire_bytes(&self) -> Vec<u8> {
+        match (&self.encoding, &self.payload) {
+            (Encoding::Raw | Encoding::Gzip, Value::String(raw)) => raw.clone().into_bytes(),
+            _ => self.payload.to_string().into_bytes(),
+        }
+    }
+
+    /// 按编码封装成 WebSocket 帧
+    pub fn frame(&self, bytes: Vec<u8>) -> Message {
+        match self.encoding {
+            Encoding::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                // 写入内存缓冲不会失败
+                let _ = encoder.write_all(&bytes);
+                Message::Binary(encoder.finish().unwrap_or_default())
+            }
+            Encoding::Json | Encoding::Raw => Message::Text(String::from_utf8_lossy(&bytes).into_owned()),
+        }
+    }
+
+    pub fn message(&self) -> Message {
+        self.frame(self.wire_bytes())
+    }
+}
+
+/// 单项失败
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConformanceFailure {
+    pub sample: String,
+    pub reason: String,
+}
+
+/// 套件运行结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConformanceReport {
+    pub exchange: String,
+    pub golden_checked: usize,
+    pub mutations_checked: usize,
+    pub failures: Vec<ConformanceFailure>,
+}
+
+impl ConformanceReport {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// 一致性套件
+#[derive(Debug, Clone)]
+pub struct ConformanceSuite {
+    samples: Vec<GoldenSample>,
+    /// 注册前必须覆盖的样本类别
+    required: Vec<SampleKind>,
+    mutations_per_sample: usize,
+    seed: u64,
+}
+
+impl ConformanceSuite {
+    pub fn new(samples: Vec<GoldenSample>) -> Self {
+        Self {
+            samples,
+            required: vec![SampleKind::BookSnapshot, SampleKind::Error],
+            mutations_per_sample: 64,
+            seed: 0x5eed,
+        }
+    }
+
+    /// 从 `<dir>/*.json` 加载一个交易所的样本，按文件名排序
+    pub fn load_dir(dir: &Path) -> Result<Self, MarketDataError> {
+        let mut paths: Vec<_> = std::fs::read_dir(dir)
+            .map_err(|e| MarketDataError::Configuration(format!("读取黄金样本目录 {} 失败: {}", dir.display(), e)))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+        let mut samples = Vec::with_capacity(paths.len());
+        for path in paths {
+            let text = std::fs::read_to_string(&path)
+                .map_err(|e| MarketDataError::Configuration(format!("读取 {} 失败: {}", path.display(), e)))?;
+            let sample: GoldenSample = serde_json::from_str(&text)
+                .map_err(|e| MarketDataError::Configuration(format!("黄金样本 {} 格式错误: {}", path.display(), e)))?;
+            samples.push(sample);
+        }
+        Ok(Self::new(samples))
+    }
+
+    pub fn with_required(mut self, required: Vec<SampleKind>) -> Self {
+        self.required = required;
+        self
+    }
+
+    pub fn with_mutations(mut self, mutations_per_sample: usize, seed: u64) -> Self {
+        self.mutations_per_sample = mutations_per_sample;
+        self.seed = seed;
+        self
+    }
+
+    pub fn samples(&self) -> &[GoldenSample] {
+        &self.samples
+    }
+
+    pub fn run(&self, adapter: &dyn ExchangeAdapter) -> ConformanceReport {
+        let mut report = ConformanceReport {
+            exchange: adapter.exchange_id().to_string(),
+            golden_checked: 0,
+            mutations_checked: 0,
+            failures: Vec::new(),
+        };
+
+        for kind in &self.required {
+            if !self.samples.iter().any(|s| s.kind == *kind) {
+                report.failures.push(ConformanceFailure {
+                    sample: format!("{:?}", kind),
+                    reason: "缺少该类别的黄金样本".to_string(),
+                });
+            }
+        }
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        for sample in &self.samples {
+            let subscriptions = sample.subscription_details();
+            match parse_guarded(adapter, &sample.message(), &subscriptions) {
+                Some(result) => {
+                    let actual = ParsedSummary::of(&result);
+                    if actual != sample.expected {
+                        report.failures.push(ConformanceFailure {
+                            sample: sample.name.clone(),
+                            reason: format!(
+                                "期望 {} 实际 {}",
+                                serde_json::to_string(&sample.expected).unwrap_or_default(),
+                                serde_json::to_string(&actual).unwrap_or_default()
+                            ),
+                        });
+                    }
+                }
+                None => report.failures.push(ConformanceFailure {
+                    sample: sample.name.clone(),
+                    reason: "黄金样本解析 panic".to_string(),
+                }),
+            }
+            report.golden_checked += 1;
+
+            let wire = sample.wire_bytes();
+            for i in 0..self.mutations_per_sample {
+                let mutated = mutate(&wire, &mut rng);
+                let frame = sample.frame(mutated.clone());
+                match parse_guarded(adapter, &frame, &subscriptions) {
+                    None => report.failures.push(ConformanceFailure {
+                        sample: format!("{}#mutation{}", sample.name, i),
+                        reason: format!("畸形输入导致 panic: {}", String::from_utf8_lossy(&mutated)),
+                    }),
+                    Some(Ok(Some(_))) if mutated.iter().all(u8::is_ascii_whitespace) => {
+                        report.failures.push(ConformanceFailure {
+                            sample: format!("{}#mutation{}", sample.name, i),
+                            reason: "空消息产出了行情数据".to_string(),
+                        })
+                    }
+                    Some(_) => {}
+                }
+                report.mutations_checked += 1;
+            }
+        }
+
+        metrics::counter!("adapter_conformance_runs_total", "exchange" => report.exchange.clone(), "passed" => report.passed().to_string()).increment(1);
+        report
+    }
+}
+
+/// 解析一条消息；panic 时返回 None
+pub fn parse_guarded(
+    adapter: &dyn ExchangeAdapter,
+    message: &Message,
+    subscriptions: &[SubscriptionDetail],
+) -> Option<Result<Option<MarketDataMessage>, MarketDataError>> {
+    catch_unwind(AssertUnwindSafe(|| adapter.parse_message(message, subscriptions))).ok()
+}
+
+/// 一次随机变异：截断、翻转字节、删除片段、插入噪声、数字替换为字符串或清空
+pub fn mutate(input: &[u8], rng: &mut StdRng) -> Vec<u8> {
+    if input.is_empty() {
+        return b"{".to_vec();
+    }
+    let mut out = input.to_vec();
+    match rng.gen_range(0..6) {
+        0 => out.truncate(rng.gen_range(0..input.len())),
+        1 => {
+            for _ in 0..rng.gen_range(1..=4) {
+                let i = rng.gen_range(0..out.len());
+                out[i] ^= 1 << rng.gen_range(0..8);
+            }
+        }
+        2 => {
+            let start = rng.gen_range(0..out.len());
+            let end = rng.gen_range(start..=out.len().min(start + 16));
+            out.drain(start..end);
+        }
+        3 => {
+            let i = rng.gen_range(0..=out.len());
+            let noise: &[&[u8]] = &[b"null", b"[]", b"{}", b"\"\"", b"-1e999", b"NaN", b"\"x\"", b",", b"]"];
+            let token = noise[rng.gen_range(0..noise.len())];
+            out.splice(i..i, token.iter().copied());
+        }
+        4 => {
+            // 把首个数字串替换成非数字字符串
+            if let Some(start) = out.iter().position(u8::is_ascii_digit) {
+                let end = out[start..].iter().position(|b| !b.is_ascii_digit() && *b != b'.').map_or(out.len(), |n| start + n);
+                out.splice(start..end, b"abc".iter().copied());
+            }
+        }
+        _ => out = b" ".to_vec(),
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::binance::BinanceAdapter;
+
+    #[test]
+    fn test_golden_and_mutations_on_binance() {
+        let sample = |name: &str, kind, payload: Value, expected: ParsedSummary| GoldenSample {
+            name: name.to_string(),
+            kind,
+            subscriptions: vec!["BTC/USDT".to_string()],
+            channel: default_channel(),
+            encoding: Encoding::Json,
+            payload,
+            expected,
+        };
+        let suite = ConformanceSuite::new(vec![
+            sample(
+                "depth",
+                SampleKind::BookSnapshot,
+                serde_json::json!({"stream": "btcusdt@depth", "data": {"E": 1, "u": 7, "b": [["100.5", "1"]], "a": [["101", "2"], ["102", "1"]]}}),
+                ParsedSummary {
+                    kind: "order_book".to_string(),
+                    symbol: Some("BTC/USDT".to_string()),
+                    bids: Some(1),
+                    asks: Some(2),
+                    best_bid: Some(100.5),
+                    best_ask: Some(101.0),
+                    sequence_id: Some(7),
+                    ..Default::default()
+                },
+            ),
+            GoldenSample {
+                encoding: Encoding::Raw,
+                ..sample("garbage", SampleKind::Error, Value::String("not json".to_string()), ParsedSummary {
+                    kind: "error".to_string(),
+                    ..Default::default()
+                })
+            },
+        ]);
+
+        let report = suite.run(&BinanceAdapter::new());
+        assert!(report.passed(), "{:?}", report.failures);
+        assert_eq!(report.golden_checked, 2);
+        assert_eq!(report.mutations_checked, 128);
+
+        let incomplete = ConformanceSuite::new(suite.samples()[1..].to_vec());
+        assert!(!incomplete.run(&BinanceAdapter::new()).passed());
+    }
+}
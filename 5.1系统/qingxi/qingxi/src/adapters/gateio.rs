@@ -241,7 +241,7 @@ impl GateioAdapter {
             price: OrderedFloat(price),
             quantity: OrderedFloat(quantity),
             side,
-            timestamp: crate::high_precision_time::Nanos::from_millis((trade_data.time * 1000) as i64), // Gate.io 使用秒
+            timestamp: crate::high_precision_time::Nanos::from_millis(trade_data.time.saturating_mul(1000) as i64), // Gate.io 使用秒
             trade_id: Some(trade_data.id.to_string()),
         })
     }
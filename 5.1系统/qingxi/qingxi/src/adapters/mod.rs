@@ -13,6 +13,7 @@ pub mod huobi;
 pub mod okx;
 pub mod bybit;
 pub mod gateio;
+pub mod conformance;
 
 /// 权威交易所适配器接口 - 所有适配器必须实现此 trait
 #[async_trait]
@@ -26,6 +27,17 @@ pub trait ExchangeAdapter: Send + Sync {
         subscriptions: &[SubscriptionDetail],
     ) -> Result<Vec<Message>, MarketDataError>;
 
+    /// 构建取消订阅消息；不支持时由采集器在本地丢弃该订阅的数据
+    fn build_unsubscription_messages(
+        &self,
+        _subscriptions: &[SubscriptionDetail],
+    ) -> Result<Vec<Message>, MarketDataError> {
+        Err(MarketDataError::UnsupportedOperation(format!(
+            "{} does not support unsubscribe",
+            self.exchange_id()
+        )))
+    }
+
     /// 解析来自交易所的消息 - 返回权威 MarketDataMessage 类型
     fn parse_message(
         &self,
@@ -94,6 +106,29 @@ impl AdapterRegistry {
             .insert(exchange_id.to_string(), Box::new(factory));
     }
 
+    /// 先跑一致性套件，通过后才注册；未通过时返回失败明细
+    pub fn register_verified<F>(
+        &mut self,
+        exchange_id: &str,
+        factory: F,
+        suite: &conformance::ConformanceSuite,
+    ) -> Result<conformance::ConformanceReport, MarketDataError>
+    where
+        F: Fn() -> Box<dyn ExchangeAdapter> + Send + Sync + 'static,
+    {
+        let report = suite.run(factory().as_ref());
+        if !report.passed() {
+            let reasons: Vec<String> = report.failures.iter().map(|f| format!("{}: {}", f.sample, f.reason)).collect();
+            return Err(MarketDataError::Configuration(format!(
+                "适配器 {} 未通过一致性套件: {}",
+                exchange_id,
+                reasons.join("; ")
+            )));
+        }
+        self.register(exchange_id, factory);
+        Ok(report)
+    }
+
     /// 创建适配器实例
     pub fn create_adapter(&self, exchange_id: &str) -> Option<Box<dyn ExchangeAdapter>> {
         self.adapters.get(exchange_id).map(|factory| factory())
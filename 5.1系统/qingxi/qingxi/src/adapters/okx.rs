@@ -70,6 +70,19 @@ impl OkxAdapter {
         let qty = f64::from_str(arr.get(1).and_then(Value::as_str).unwrap_or("0"))?;
         Ok(OrderBookEntry::new(price, qty))
     }
+
+    fn channel_args(subscriptions: &[SubscriptionDetail]) -> Result<Vec<Value>, MarketDataError> {
+        subscriptions
+            .iter()
+            .map(|sub| match sub.channel.as_str() {
+                "orderbook" => Ok(json!({"channel": "books", "instId": sub.symbol.as_pair()})),
+                "trades" => Ok(json!({"channel": "trades", "instId": sub.symbol.as_pair()})),
+                other => Err(MarketDataError::Configuration(format!(
+                    "Unsupported channel type for OKX: {other}"
+                ))),
+            })
+            .collect()
+    }
 }
 
 #[async_trait]
@@ -82,20 +95,18 @@ impl ExchangeAdapter for OkxAdapter {
         &self,
         subscriptions: &[SubscriptionDetail],
     ) -> Result<Vec<Message>, MarketDataError> {
-        let args: Vec<_> = subscriptions
-            .iter()
-            .map(|sub| match sub.channel.as_str() {
-                "orderbook" => Ok(json!({"channel": "books", "instId": sub.symbol.as_pair()})),
-                "trades" => Ok(json!({"channel": "trades", "instId": sub.symbol.as_pair()})),
-                other => Err(MarketDataError::Configuration(format!(
-                    "Unsupported channel type for OKX: {other}"
-                ))),
-            })
-            .collect::<Result<_, _>>()?;
-        let sub_msg = json!({"op": "subscribe", "args": args});
+        let sub_msg = json!({"op": "subscribe", "args": Self::channel_args(subscriptions)?});
         Ok(vec![Message::Text(sub_msg.to_string())])
     }
 
+    fn build_unsubscription_messages(
+        &self,
+        subscriptions: &[SubscriptionDetail],
+    ) -> Result<Vec<Message>, MarketDataError> {
+        let unsub_msg = json!({"op": "unsubscribe", "args": Self::channel_args(subscriptions)?});
+        Ok(vec![Message::Text(unsub_msg.to_string())])
+    }
+
     fn parse_message(
         &self,
         message: &Message,
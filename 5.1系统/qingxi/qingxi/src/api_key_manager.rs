@@ -192,6 +192,7 @@ impl ApiKeyManager {
             rest_endpoint: Some(rest_endpoint.to_string()),
             channel: Some("orderbook".to_string()),
             heartbeat: None,
+            trades: false,
             reconnect_interval_sec: Some(30),
             max_reconnect_attempts: Some(10),
         })
@@ -3,6 +3,8 @@
 //!
 //! 提供L1(内存)、L2(SSD)、L3(网络)多级缓存，优化市场数据访问性能
 
+pub mod preloader;
+
 use std::sync::Arc;
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
@@ -0,0 +1,324 @@
+//! # 启动缓存预热
+//!
+//! 运行期间 `ActivityIndex` 记录每个交易所+交易对的最近活跃时间、按半衰期
+//! 衰减的成交额和最优买卖价，并定期落盘。重启时 `CachePreloader` 按近期
+//! 成交额排序选出最活跃的交易对，从 L2 磁盘缓存恢复其最后的订单簿（缺失
+//! 时用记录的最优价构造一档订单簿），启动数秒内即可开始检测，不必等待
+//! 新行情到达。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use super::MultiLevelCache;
+use crate::errors::MarketDataError;
+use crate::high_precision_time::Nanos;
+use crate::types::{OrderBook, OrderBookEntry, Symbol, TradeUpdate};
+
+const NANOS_PER_SEC: f64 = 1_000_000_000.0;
+
+/// 单个交易所+交易对的活跃度
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActivityRecord {
+    pub exchange: String,
+    /// BASE/QUOTE
+    pub symbol: String,
+    pub last_active_ns: i64,
+    /// 按半衰期衰减到 `volume_updated_ns` 的成交额（计价币）
+    pub decayed_volume: f64,
+    pub volume_updated_ns: i64,
+    pub best_bid: Option<(f64, f64)>,
+    pub best_ask: Option<(f64, f64)>,
+}
+
+impl ActivityRecord {
+    fn new(exchange: &str, symbol: &str, now_ns: i64) -> Self {
+        Self {
+            exchange: exchange.to_string(),
+            symbol: symbol.to_string(),
+            last_active_ns: now_ns,
+            decayed_volume: 0.0,
+            volume_updated_ns: now_ns,
+            best_bid: None,
+            best_ask: None,
+        }
+    }
+
+    /// `now_ns` 时刻的衰减成交额
+    pub fn volume_at(&self, now_ns: i64, half_life: Duration) -> f64 {
+        let elapsed = (now_ns - self.volume_updated_ns).max(0) as f64 / NANOS_PER_SEC;
+        self.decayed_volume * 0.5f64.powf(elapsed / half_life.as_secs_f64().max(1.0))
+    }
+
+    pub fn cache_key(&self) -> String {
+        format!("{}:{}", self.exchange, self.symbol)
+    }
+}
+
+/// 交易对活跃度索引，持久化为 JSON
+pub struct ActivityIndex {
+    records: RwLock<HashMap<(String, String), ActivityRecord>>,
+    half_life: Duration,
+    path: Option<PathBuf>,
+}
+
+impl ActivityIndex {
+    pub fn new(half_life: Duration) -> Self {
+        Self { records: RwLock::new(HashMap::new()), half_life, path: None }
+    }
+
+    /// 从 `path` 加载上次运行保存的索引，之后 `save` 写回同一文件
+    pub fn load(path: impl Into<PathBuf>, half_life: Duration) -> Result<Self, MarketDataError> {
+        let path = path.into();
+        let mut index = Self::new(half_life);
+        if path.exists() {
+            let bytes = std::fs::read(&path)
+                .map_err(|e| MarketDataError::InternalError(format!("读取活跃度索引失败 {}: {}", path.display(), e)))?;
+            let records: Vec<ActivityRecord> = serde_json::from_slice(&bytes)?;
+            info!("📇 已加载 {} 个交易对的活跃度记录: {}", records.len(), path.display());
+            *index.records.get_mut() =
+                records.into_iter().map(|r| ((r.exchange.clone(), r.symbol.clone()), r)).collect();
+        }
+        index.path = Some(path);
+        Ok(index)
+    }
+
+    pub fn record_trade(&self, trade: &TradeUpdate) {
+        let now_ns = trade.timestamp.as_nanos();
+        let notional = trade.price.into_inner() * trade.quantity.into_inner();
+        let mut records = self.records.write();
+        let record = records
+            .entry((trade.source.clone(), trade.symbol.as_pair()))
+            .or_insert_with(|| ActivityRecord::new(&trade.source, &trade.symbol.as_pair(), now_ns));
+        record.decayed_volume = record.volume_at(now_ns, self.half_life) + notional.abs();
+        record.volume_updated_ns = record.volume_updated_ns.max(now_ns);
+        record.last_active_ns = record.last_active_ns.max(now_ns);
+    }
+
+    pub fn record_orderbook(&self, orderbook: &OrderBook) {
+        let now_ns = orderbook.timestamp.as_nanos();
+        let level = |e: &OrderBookEntry| (e.price.into_inner(), e.quantity.into_inner());
+        let mut records = self.records.write();
+        let record = records
+            .entry((orderbook.source.clone(), orderbook.symbol.as_pair()))
+            .or_insert_with(|| ActivityRecord::new(&orderbook.source, &orderbook.symbol.as_pair(), now_ns));
+        record.best_bid = orderbook.bids.first().map(level).or(record.best_bid);
+        record.best_ask = orderbook.asks.first().map(level).or(record.best_ask);
+        record.last_active_ns = record.last_active_ns.max(now_ns);
+    }
+
+    /// 最近 `max_age` 内活跃、按衰减成交额降序排列的记录
+    pub fn ranked(&self, now_ns: i64, max_age: Duration) -> Vec<ActivityRecord> {
+        let cutoff = now_ns - max_age.as_nanos() as i64;
+        let mut ranked: Vec<_> = self
+            .records
+            .read()
+            .values()
+            .filter(|r| r.last_active_ns >= cutoff)
+            .map(|r| (r.volume_at(now_ns, self.half_life), r.clone()))
+            .collect();
+        ranked.sort_by(|(va, a), (vb, b)| vb.total_cmp(va).then(b.last_active_ns.cmp(&a.last_active_ns)));
+        ranked.into_iter().map(|(_, r)| r).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 写回加载时的文件（先写临时文件再改名）
+    pub fn save(&self) -> Result<(), MarketDataError> {
+        let Some(path) = &self.path else { return Ok(()) };
+        let records: Vec<_> = self.records.read().values().cloned().collect();
+        let bytes = serde_json::to_vec(&records)?;
+        let io_error = |e: std::io::Error| MarketDataError::InternalError(format!("保存活跃度索引失败: {}", e));
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, bytes).map_err(io_error)?;
+        std::fs::rename(&tmp, path).map_err(io_error)?;
+        Ok(())
+    }
+
+    /// 定期保存
+    pub fn spawn_saver(self: Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.save() {
+                    warn!("活跃度索引保存失败: {}", e);
+                }
+            }
+        })
+    }
+}
+
+/// 预热配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreloadConfig {
+    /// 最多预热的交易所+交易对数量
+    pub max_symbols: usize,
+    /// 超过该时长未活跃的交易对不预热
+    pub max_age_secs: u64,
+    /// 成交额衰减半衰期
+    pub volume_half_life_secs: u64,
+}
+
+impl Default for PreloadConfig {
+    fn default() -> Self {
+        Self { max_symbols: 200, max_age_secs: 6 * 3600, volume_half_life_secs: 3600 }
+    }
+}
+
+impl PreloadConfig {
+    pub fn half_life(&self) -> Duration {
+        Duration::from_secs(self.volume_half_life_secs)
+    }
+
+    /// 索引文件放在 L2 缓存目录下
+    pub fn index_path(l2_directory: &Path) -> PathBuf {
+        l2_directory.join("activity_index.json")
+    }
+}
+
+/// 预热结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PreloadReport {
+    pub candidates: usize,
+    /// 从 L2 恢复完整订单簿
+    pub from_cache: usize,
+    /// 仅用记录的最优价构造
+    pub from_best_prices: usize,
+    pub skipped: usize,
+}
+
+/// 启动预热器
+pub struct CachePreloader {
+    config: PreloadConfig,
+    index: Arc<ActivityIndex>,
+    cache: Arc<MultiLevelCache>,
+}
+
+impl CachePreloader {
+    pub fn new(config: PreloadConfig, index: Arc<ActivityIndex>, cache: Arc<MultiLevelCache>) -> Self {
+        Self { config, index, cache }
+    }
+
+    /// 按活跃度恢复订单簿；L2 命中的同时提升到 L1
+    pub async fn preload(&self) -> (Vec<OrderBook>, PreloadReport) {
+        let started = std::time::Instant::now();
+        let candidates = self.index.ranked(Nanos::now().as_nanos(), Duration::from_secs(self.config.max_age_secs));
+        let mut report = PreloadReport { candidates: candidates.len().min(self.config.max_symbols), ..Default::default() };
+        let mut books = Vec::with_capacity(report.candidates);
+
+        for record in candidates.into_iter().take(self.config.max_symbols) {
+            let cached = match self.cache.get(&record.cache_key()).await {
+                Ok(snapshot) => snapshot.and_then(|s| s.orderbook).filter(|ob| !ob.bids.is_empty() || !ob.asks.is_empty()),
+                Err(e) => {
+                    debug!("预热读取缓存失败 {}: {}", record.cache_key(), e);
+                    None
+                }
+            };
+            if let Some(orderbook) = cached {
+                report.from_cache += 1;
+                books.push(orderbook);
+            } else if let Some(orderbook) = Self::top_of_book(&record) {
+                report.from_best_prices += 1;
+                books.push(orderbook);
+            } else {
+                report.skipped += 1;
+            }
+        }
+
+        info!(
+            "🔥 缓存预热完成: {} 个候选, {} 个来自缓存, {} 个来自最优价, 耗时 {:?}",
+            report.candidates,
+            report.from_cache,
+            report.from_best_prices,
+            started.elapsed()
+        );
+        (books, report)
+    }
+
+    /// 用最后记录的最优买卖价构造一档订单簿，时间戳保留原值以便下游判断新鲜度
+    fn top_of_book(record: &ActivityRecord) -> Option<OrderBook> {
+        if record.best_bid.is_none() && record.best_ask.is_none() {
+            return None;
+        }
+        let mut orderbook = OrderBook::new(Symbol::from_pair(&record.symbol)?, record.exchange.clone());
+        orderbook.bids = record.best_bid.map(|(p, q)| vec![OrderBookEntry::new(p, q)]).unwrap_or_default();
+        orderbook.asks = record.best_ask.map(|(p, q)| vec![OrderBookEntry::new(p, q)]).unwrap_or_default();
+        orderbook.timestamp = Nanos::from(record.last_active_ns);
+        Some(orderbook)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheLevel;
+    use ordered_float::OrderedFloat;
+
+    fn trade(source: &str, symbol: &str, price: f64, quantity: f64, ts: i64) -> TradeUpdate {
+        TradeUpdate {
+            symbol: Symbol::from_pair(symbol).unwrap(),
+            price: OrderedFloat(price),
+            quantity: OrderedFloat(quantity),
+            side: crate::types::TradeSide::Buy,
+            timestamp: Nanos::from(ts),
+            source: source.to_string(),
+            trade_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_preload_ranks_by_recent_volume() {
+        let dir = std::env::temp_dir().join(format!("qingxi_preload_{}_{}", std::process::id(), Nanos::now().as_nanos()));
+        let now = Nanos::now().as_nanos();
+        let hour = 3_600_000_000_000i64;
+
+        let index = ActivityIndex::load(PreloadConfig::index_path(&dir), Duration::from_secs(3600)).unwrap();
+        // 两小时前的大额成交衰减到 1/4，低于最近的中等成交
+        index.record_trade(&trade("binance", "ETH/USDT", 2000.0, 100.0, now - 2 * hour));
+        index.record_trade(&trade("okx", "BTC/USDT", 60000.0, 2.0, now));
+        index.record_trade(&trade("bybit", "SOL/USDT", 150.0, 1.0, now - 48 * hour));
+
+        let mut book = OrderBook::new(Symbol::from_pair("BTC/USDT").unwrap(), "okx".to_string());
+        book.bids = vec![OrderBookEntry::new(59999.0, 1.0)];
+        book.asks = vec![OrderBookEntry::new(60001.0, 1.0)];
+        book.timestamp = Nanos::from(now);
+        index.record_orderbook(&book);
+        let mut eth = OrderBook::new(Symbol::from_pair("ETH/USDT").unwrap(), "binance".to_string());
+        eth.bids = vec![OrderBookEntry::new(1999.5, 3.0)];
+        eth.timestamp = Nanos::from(now - 2 * hour);
+        index.record_orderbook(&eth);
+        index.save().unwrap();
+
+        let cache = Arc::new(
+            MultiLevelCache::new_detailed(16, Duration::from_secs(60), dir.join("l2"), 16, Duration::from_secs(3600)).unwrap(),
+        );
+        cache.put("okx:BTC/USDT".to_string(), book.clone(), CacheLevel::L2Disk).await.unwrap();
+
+        // 重启：重新加载索引
+        let index = Arc::new(ActivityIndex::load(PreloadConfig::index_path(&dir), Duration::from_secs(3600)).unwrap());
+        assert_eq!(index.len(), 3);
+        let preloader = CachePreloader::new(PreloadConfig::default(), index, cache);
+        let (books, report) = preloader.preload().await;
+
+        assert_eq!(books.iter().map(|b| b.source.as_str()).collect::<Vec<_>>(), vec!["okx", "binance"]);
+        assert_eq!(books[0].asks.len(), 1);
+        assert_eq!((report.from_cache, report.from_best_prices, report.skipped), (1, 1, 0));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
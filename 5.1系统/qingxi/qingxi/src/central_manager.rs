@@ -7,6 +7,9 @@ use super::{
 };
 use crate::batch::{BatchConfig, MarketDataBatchProcessor, SIMDBatchProcessor};
 use crate::cache::{CacheLevel, MultiLevelCache};
+use crate::cache::preloader::{ActivityIndex, CachePreloader, PreloadConfig};
+use crate::collector::subscription_manager::{SubscriptionKey, SubscriptionManager};
+use crate::trade_validation::TradeStats;
 use crate::lockfree::{MarketDataLockFreeBuffer};
 use crate::cleaner::{OptimizedDataCleaner, DataCleaner};
 use crate::event_bus::EventBus;
@@ -45,6 +48,20 @@ pub enum ApiCommand {
         sources: Vec<MarketSourceConfig>,
         responder: oneshot::Sender<Result<(), MarketDataError>>,
     },
+    Subscribe {
+        key: SubscriptionKey,
+        responder: oneshot::Sender<Result<bool, MarketDataError>>,
+    },
+    Unsubscribe {
+        key: SubscriptionKey,
+        responder: oneshot::Sender<Result<bool, MarketDataError>>,
+    },
+    ListSubscriptions {
+        responder: oneshot::Sender<Vec<SubscriptionKey>>,
+    },
+    GetTradeStats {
+        responder: oneshot::Sender<Vec<TradeStats>>,
+    },
 }
 
 // 2. 创建轻量级的"句柄"或"遥控器" ---
@@ -189,6 +206,46 @@ impl CentralManagerHandle {
             .map_err(|_| MarketDataError::InternalError("Config channel closed".to_string()))
     }
 
+    /// 运行时订阅，持久化后重启仍生效；返回是否有变化
+    pub async fn subscribe(&self, key: SubscriptionKey) -> Result<bool, MarketDataError> {
+        let (tx, rx) = oneshot::channel();
+        self.command_sender
+            .send_async(ApiCommand::Subscribe { key, responder: tx })
+            .await
+            .map_err(|_| MarketDataError::InternalError("Command channel closed".to_string()))?;
+        rx.await.map_err(|e| MarketDataError::InternalError(e.to_string()))?
+    }
+
+    /// 取消运行时或配置中的订阅；返回是否有变化
+    pub async fn unsubscribe(&self, key: SubscriptionKey) -> Result<bool, MarketDataError> {
+        let (tx, rx) = oneshot::channel();
+        self.command_sender
+            .send_async(ApiCommand::Unsubscribe { key, responder: tx })
+            .await
+            .map_err(|_| MarketDataError::InternalError("Command channel closed".to_string()))?;
+        rx.await.map_err(|e| MarketDataError::InternalError(e.to_string()))?
+    }
+
+    /// 当前生效的订阅
+    pub async fn list_subscriptions(&self) -> Result<Vec<SubscriptionKey>, MarketDataApiError> {
+        let (tx, rx) = oneshot::channel();
+        self.command_sender
+            .send_async(ApiCommand::ListSubscriptions { responder: tx })
+            .await
+            .map_err(|_| MarketDataApiError::InternalError("Command channel closed".to_string()))?;
+        rx.await.map_err(|e| MarketDataApiError::InternalError(e.to_string()))
+    }
+
+    /// 各交易所+交易对的滚动成交统计
+    pub async fn get_trade_stats(&self) -> Result<Vec<TradeStats>, MarketDataApiError> {
+        let (tx, rx) = oneshot::channel();
+        self.command_sender
+            .send_async(ApiCommand::GetTradeStats { responder: tx })
+            .await
+            .map_err(|_| MarketDataApiError::InternalError("Command channel closed".to_string()))?;
+        rx.await.map_err(|e| MarketDataApiError::InternalError(e.to_string()))
+    }
+
     /// 获取已注册的适配器ID列表
     pub async fn get_registered_adapters_ids(&self) -> Result<Vec<String>, MarketDataApiError> {
         // 通过获取所有订单簿来推断注册的适配器
@@ -220,6 +277,10 @@ pub struct CentralManager {
     simd_processor: Arc<SIMDBatchProcessor>,
     cache_manager: Arc<MultiLevelCache>,
     lockfree_buffer: Arc<MarketDataLockFreeBuffer>,
+    /// 交易对活跃度，用于重启后的缓存预热
+    activity_index: Arc<ActivityIndex>,
+    /// 配置基线 + 运行时订阅增量
+    subscriptions: Arc<SubscriptionManager>,
     
     // 新增：综合性能优化管理器 (暂时注释掉，需要后续集成)
     // performance_manager: Option<Arc<PerformanceOptimizationManager>>,
@@ -309,6 +370,18 @@ impl CentralManager {
             std::time::Duration::from_secs(7200),             // L2 TTL
         ).expect("Failed to create multi-level cache"));
         let lockfree_buffer = Arc::new(MarketDataLockFreeBuffer::new(settings.quality_thresholds.max_orderbook_count));
+        let preload_config = PreloadConfig::default();
+        let index_path = PreloadConfig::index_path(std::path::Path::new(&settings.cache.l2_directory));
+        let activity_index = Arc::new(ActivityIndex::load(&index_path, preload_config.half_life()).unwrap_or_else(|e| {
+            warn!("活跃度索引加载失败，从空索引开始: {}", e);
+            ActivityIndex::new(preload_config.half_life())
+        }));
+        let subscriptions_path = SubscriptionManager::default_path(std::path::Path::new(&settings.cache.l2_directory));
+        let subscriptions = Arc::new(SubscriptionManager::load(&subscriptions_path).unwrap_or_else(|e| {
+            warn!("运行时订阅加载失败，仅使用配置订阅: {}", e);
+            SubscriptionManager::in_memory()
+        }));
+        subscriptions.set_sources(settings.sources.clone());
 
         // 创建优化的数据清洗器通道和组件
         let (_cleaner_input_tx, cleaner_input_rx) = flume::bounded(settings.performance.cleaner_input_buffer_size);
@@ -339,6 +412,8 @@ impl CentralManager {
             simd_processor,
             cache_manager,
             lockfree_buffer,
+            activity_index,
+            subscriptions,
             
             // 数据清洗组件
             data_cleaner,
@@ -420,12 +495,24 @@ impl CentralManager {
         info!("Central Manager started. Waiting for events.");
         let mut initial_data_received = false;
 
+        // 按近期成交额预热上次运行最活跃的交易对，检测无需等待新行情
+        let preloader = CachePreloader::new(PreloadConfig::default(), self.activity_index.clone(), self.cache_manager.clone());
+        let (books, _) = preloader.preload().await;
+        for orderbook in books {
+            self.latest_books.insert((orderbook.source.clone(), orderbook.symbol.clone()), orderbook);
+        }
+        let index_saver = self.activity_index.clone().spawn_saver(std::time::Duration::from_secs(30));
+
         loop {
             tokio::select! {
                 biased;
                 _ = shutdown_rx.recv() => {
                     info!("Manager received shutdown signal. Stopping all systems.");
                     self.collector_system.stop_all().await;
+                    index_saver.abort();
+                    if let Err(e) = self.activity_index.save() {
+                        warn!("活跃度索引保存失败: {}", e);
+                    }
                     break;
                 },
                 Ok(command) = self.command_receiver.recv_async() => {
@@ -433,7 +520,8 @@ impl CentralManager {
                 },
                 Some(new_configs) = self.config_receiver.recv() => {
                     info!("🔄 Received configuration update with {} sources", new_configs.len());
-                    match self.collector_system.reconfigure(new_configs).await {
+                    self.subscriptions.set_sources(new_configs);
+                    match self.collector_system.reconfigure(self.subscriptions.effective_sources()).await {
                         Ok(()) => {
                             info!("✅ Configuration hot reload completed successfully");
                         },
@@ -462,9 +550,29 @@ impl CentralManager {
     async fn handle_api_command(&self, command: ApiCommand) {
         match command {
             ApiCommand::Reconfigure { sources, responder } => {
-                let result = self.collector_system.reconfigure(sources).await;
+                // 运行时订阅增量叠加在新配置之上
+                self.subscriptions.set_sources(sources);
+                let result = self.collector_system.reconfigure(self.subscriptions.effective_sources()).await;
+                responder.send(result).ok();
+            }
+            ApiCommand::Subscribe { key, responder } => {
+                let result = match self.check_channel(&key) {
+                    Ok(()) => self.apply_subscription_change(self.subscriptions.subscribe(&key)).await,
+                    Err(e) => Err(e),
+                };
                 responder.send(result).ok();
             }
+            ApiCommand::Unsubscribe { key, responder } => {
+                let result = self.apply_subscription_change(self.subscriptions.unsubscribe(&key)).await;
+                responder.send(result).ok();
+            }
+            ApiCommand::ListSubscriptions { responder } => {
+                responder.send(self.subscriptions.subscriptions().into_iter().collect()).ok();
+            }
+            ApiCommand::GetTradeStats { responder } => {
+                let now = crate::high_precision_time::Nanos::now();
+                responder.send(self.pipeline.trade_tracker().all_stats(now)).ok();
+            }
             ApiCommand::GetLatestOrderbook {
                 exchange_id,
                 symbol,
@@ -485,7 +593,9 @@ impl CentralManager {
                     orderbook: self.latest_books.iter()
                         .find(|entry| entry.key().1.as_pair() == symbol)
                         .map(|entry| entry.value().clone()),
-                    trades: vec![], // 实际实现中应该从交易缓存获取
+                    trades: Symbol::from_pair(&symbol)
+                        .map(|s| self.pipeline.trade_tracker().last_trades(&s))
+                        .unwrap_or_default(),
                     timestamp: crate::high_precision_time::Nanos::now(),
                     source: "central_manager".to_string(),
                 };
@@ -519,6 +629,33 @@ impl CentralManager {
         }
     }
 
+    /// 交易所需已注册且支持该频道
+    fn check_channel(&self, key: &SubscriptionKey) -> Result<(), MarketDataError> {
+        let exchange = key.exchange.to_lowercase();
+        let channels = self
+            .collector_system
+            .supported_channels(&exchange)
+            .ok_or_else(|| MarketDataError::Configuration(format!("交易所 {} 未注册适配器", key.exchange)))?;
+        if !channels.iter().any(|c| c.eq_ignore_ascii_case(&key.channel)) {
+            return Err(MarketDataError::UnsupportedOperation(format!(
+                "{} 不支持频道 {}，可选: {}",
+                exchange,
+                key.channel,
+                channels.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    /// 订阅集合变化后按生效集合差量更新采集器
+    async fn apply_subscription_change(&self, changed: Result<bool, MarketDataError>) -> Result<bool, MarketDataError> {
+        if changed? {
+            self.collector_system.reconfigure(self.subscriptions.effective_sources()).await?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
     async fn process_adapter_event(&mut self, event: AdapterEvent) {
         match event {
             AdapterEvent::MarketData(market_msg) => {
@@ -583,6 +720,7 @@ impl CentralManager {
 
                         // 更新订单簿缓存
                         let key = (ob.source.clone(), ob.symbol.clone());
+                        self.activity_index.record_orderbook(ob);
                         self.latest_books.insert(key, ob.clone());
 
                         info!("🚀 High-performance data processing: cleaning + lockfree buffer + multi-level cache");
@@ -596,6 +734,8 @@ impl CentralManager {
                             trade.quantity.0
                         );
 
+                        self.activity_index.record_trade(trade);
+
                         // 🚀 使用无锁缓冲区处理交易数据
                         if let Err(_) = self.lockfree_buffer.push_trade(trade.clone()) {
                             debug!("Trade lock-free buffer full");
@@ -697,17 +837,15 @@ impl CentralManager {
 
                 if let Some(local_message) = local_msg {
                     let result = self.pipeline.process(local_message).await;
-                    if let Some(_snapshot) = result.snapshot {
-                        // 简化处理：快照已生成，可以进行异常检测
+                    if result.snapshot.is_some() {
                         info!("📊 Pipeline processed message and generated snapshot");
-
-                        // 如果有异常检测结果，记录异常
-                        if let Some(anomaly) = result.anomaly {
-                            info!(
-                                "🚨 Anomaly detected: {:?} - {}",
-                                anomaly.anomaly_type, anomaly.description
-                            );
-                        }
+                    }
+                    // 成交校验等异常不一定伴随快照，单独记录
+                    if let Some(anomaly) = result.anomaly {
+                        info!(
+                            "🚨 Anomaly detected: {:?} - {}",
+                            anomaly.anomaly_type, anomaly.description
+                        );
                     }
                 }
             }
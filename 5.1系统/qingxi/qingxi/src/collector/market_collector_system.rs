@@ -1,15 +1,21 @@
 #![allow(dead_code)]
 // src/collector/market_collector_system.rs
-use crate::collector::websocket_collector::WebsocketCollector;
+use crate::collector::websocket_collector::{SubscriptionCommand, WebsocketCollector};
 use crate::health::ApiHealthMonitor;
-use crate::types::{MarketSourceConfig, Symbol};
+use crate::types::{MarketSourceConfig, SubscriptionDetail, Symbol};
 use crate::{adapters::ExchangeAdapter, errors::MarketDataError};
 use crate::settings::WebSocketNetworkSettings;
 use dashmap::DashMap;
-use std::{collections::HashMap, sync::Arc};
-use tokio::{sync::broadcast, task::JoinHandle};
+use std::{collections::{BTreeSet, HashMap}, sync::Arc};
+use tokio::{sync::{broadcast, mpsc}, task::JoinHandle};
 use tracing::{info, instrument, warn};
 
+/// 运行中采集器的频道及其订阅控制通道
+struct CollectorControl {
+    channels: BTreeSet<String>,
+    commands: mpsc::UnboundedSender<SubscriptionCommand>,
+}
+
 /// 市场数据采集系统
 pub struct MarketCollectorSystem {
     /// 数据输出通道
@@ -18,6 +24,8 @@ pub struct MarketCollectorSystem {
     adapters: Arc<DashMap<String, Arc<dyn ExchangeAdapter>>>,
     /// 活跃的采集任务
     active_tasks: Arc<DashMap<(String, Symbol), JoinHandle<()>>>,
+    /// 同一交易所+交易对的多个频道共用一条连接，频道变化实时下发
+    controls: Arc<DashMap<(String, Symbol), CollectorControl>>,
     /// 健康监控器
     health_monitor: Arc<ApiHealthMonitor>,
     /// 关闭信号发送器
@@ -38,6 +46,7 @@ impl MarketCollectorSystem {
             data_tx,
             adapters: Arc::new(DashMap::new()),
             active_tasks: Arc::new(DashMap::new()),
+            controls: Arc::new(DashMap::new()),
             health_monitor,
             shutdown_tx,
             network_settings,
@@ -59,20 +68,24 @@ impl MarketCollectorSystem {
     ) -> Result<(), MarketDataError> {
         info!("🔄 Starting intelligent configuration reconfigure");
 
-        // 1. 构建新的活跃订阅集合
-        let new_active_subs: HashMap<(String, Symbol), MarketSourceConfig> = configs
-            .into_iter()
-            .filter(|c| !c.channel.is_empty()) // channel 现在是 String，不是 Option
-            .flat_map(|c| {
-                let exchange = c.exchange_id.clone();
-                let config = c.clone();
-                // 需要将字符串符号转换为 Symbol 结构体
-                c.get_symbols().unwrap_or_default().into_iter().map(move |symbol| {
-                    let key = (exchange.clone(), symbol);
-                    (key, config.clone())
-                })
-            })
-            .collect();
+        // 1. 构建新的活跃订阅集合，同一交易所+交易对的频道合并
+        let mut new_active_subs: HashMap<(String, Symbol), (MarketSourceConfig, BTreeSet<String>)> = HashMap::new();
+        for c in configs.into_iter().filter(|c| !c.channel.is_empty()) {
+            // 需要将字符串符号转换为 Symbol 结构体
+            if let Some(adapter) = self.adapters.get(&c.exchange_id) {
+                if !adapter.supported_channels().contains(&c.channel.as_str()) {
+                    warn!("⚠️ {} does not support channel '{}', skipping", c.exchange_id, c.channel);
+                    continue;
+                }
+            }
+            for symbol in c.get_symbols().unwrap_or_default() {
+                new_active_subs
+                    .entry((c.exchange_id.clone(), symbol))
+                    .or_insert_with(|| (c.clone(), BTreeSet::new()))
+                    .1
+                    .insert(c.channel.clone());
+            }
+        }
 
         // 2. 获取当前活跃的订阅列表
         let current_subs: Vec<(String, Symbol)> = self
@@ -98,6 +111,7 @@ impl MarketCollectorSystem {
                         current_key.1.as_pair()
                     );
                     handle.abort();
+                    self.controls.remove(current_key);
                     stopped_count += 1;
 
                     // 更新健康监控状态
@@ -111,11 +125,16 @@ impl MarketCollectorSystem {
         // 4. 为新出现的订阅启动采集器
         let mut started_count = 0;
         let mut unchanged_count = 0;
+        let mut updated_count = 0;
 
-        for (new_key, config) in new_active_subs {
+        for (new_key, (config, channels)) in new_active_subs {
             if current_subs.contains(&new_key) {
-                // 订阅已存在且配置未改变，不做任何操作
-                unchanged_count += 1;
+                // 连接已存在：只下发频道增减，不重连
+                if self.update_channels(&new_key, &channels) {
+                    updated_count += 1;
+                } else {
+                    unchanged_count += 1;
+                }
                 continue;
             }
 
@@ -128,6 +147,9 @@ impl MarketCollectorSystem {
                 let symbol = new_key.1.clone();
                 let shutdown_rx = self.shutdown_tx.subscribe();
                 let _network_settings = self.network_settings.clone();
+                let subscriptions: Vec<_> =
+                    channels.iter().map(|channel| SubscriptionDetail::new(symbol.clone(), channel)).collect();
+                let (command_tx, command_rx) = mpsc::unbounded_channel();
 
                 // 使用传入的配置而不是硬编码的URL
                 let market_config = MarketSourceConfig {
@@ -151,6 +173,8 @@ impl MarketCollectorSystem {
                     heartbeat_interval_ms: config.heartbeat_interval_ms,
                     reconnect_interval_sec: config.reconnect_interval_sec.or(Some(5)),
                     max_reconnect_attempts: config.max_reconnect_attempts.or(Some(3)),
+                    heartbeat: config.heartbeat.clone(),
+                    trades: false,
                 };
 
                 // 在spawn之前克隆网络设置
@@ -194,7 +218,8 @@ impl MarketCollectorSystem {
                         internal_tx,
                         health_monitor,
                         network_settings_clone,
-                    );
+                    )
+                    .with_subscription_control(subscriptions, command_rx);
 
                     // 并行运行采集器和转换任务
                     tokio::select! {
@@ -208,6 +233,7 @@ impl MarketCollectorSystem {
                     new_key.0,
                     new_key.1.as_pair()
                 );
+                self.controls.insert(new_key.clone(), CollectorControl { channels, commands: command_tx });
                 self.active_tasks.insert(new_key, handle);
                 started_count += 1;
             } else {
@@ -219,13 +245,44 @@ impl MarketCollectorSystem {
         }
 
         info!(
-            "🎯 Configuration update summary: {} stopped, {} started, {} unchanged",
-            stopped_count, started_count, unchanged_count
+            "🎯 Configuration update summary: {} stopped, {} started, {} updated live, {} unchanged",
+            stopped_count, started_count, updated_count, unchanged_count
         );
 
         Ok(())
     }
 
+    /// 向已有连接下发频道增减，返回是否有变化
+    fn update_channels(&self, key: &(String, Symbol), channels: &BTreeSet<String>) -> bool {
+        let Some(mut control) = self.controls.get_mut(key) else { return false };
+        if &control.channels == channels {
+            return false;
+        }
+        let commands = control
+            .channels
+            .difference(channels)
+            .map(|c| SubscriptionCommand::Unsubscribe(SubscriptionDetail::new(key.1.clone(), c)))
+            .chain(
+                channels
+                    .difference(&control.channels)
+                    .map(|c| SubscriptionCommand::Subscribe(SubscriptionDetail::new(key.1.clone(), c))),
+            )
+            .collect::<Vec<_>>();
+        for command in commands {
+            info!("🔀 {}-{}: {:?}", key.0, key.1.as_pair(), command);
+            if control.commands.send(command).is_err() {
+                warn!("Collector for {}-{} is not running", key.0, key.1.as_pair());
+            }
+        }
+        control.channels = channels.clone();
+        true
+    }
+
+    /// 交易所适配器支持的频道，未注册时返回 None
+    pub fn supported_channels(&self, exchange: &str) -> Option<Vec<&'static str>> {
+        self.adapters.get(exchange).map(|adapter| adapter.supported_channels())
+    }
+
     // DashMap 没有 drain 方法，需手动遍历并移除所有条目
     /// 停止所有采集器 - 使用优雅关闭
     pub async fn stop_all(&self) {
@@ -248,6 +305,7 @@ impl MarketCollectorSystem {
             if let Some((_, handle)) = self.active_tasks.remove(&key) {
                 handles.push(handle);
             }
+            self.controls.remove(&key);
         }
         
         // 等待所有任务完成，最多等待30秒
@@ -1,3 +1,5 @@
 #![allow(dead_code)]
 pub mod market_collector_system;
+pub mod subscription_manager;
+pub mod subscription_watchdog;
 pub mod websocket_collector;
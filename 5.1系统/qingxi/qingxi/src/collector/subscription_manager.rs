@@ -0,0 +1,272 @@
+// src/collector/subscription_manager.rs
+//! # 运行时订阅管理
+//!
+//! 配置文件中的 `sources` 是订阅基线；运行时通过 API 增加或取消的订阅
+//! 作为相对基线的增量（`added` / `removed`）持久化到 JSON 文件，重启后
+//! 与基线合并得到生效集合。配置文件后续修改的交易对仍然生效，只有被
+//! 运行时显式改动过的订阅以增量为准。
+//!
+//! 生效集合按 交易所+频道 拆成 `MarketSourceConfig` 交给
+//! `MarketCollectorSystem::reconfigure`，已有连接上的频道变化通过订阅
+//! 控制通道实时下发，不会重连其他交易对。
+
+use crate::errors::MarketDataError;
+use crate::types::{MarketSourceConfig, Symbol};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// 可运行时订阅的频道
+pub const SUBSCRIPTION_CHANNELS: &[&str] = &["orderbook", "trades", "funding"];
+
+/// 交易所 + 交易对 + 频道
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct SubscriptionKey {
+    pub exchange: String,
+    /// BASE/QUOTE
+    pub symbol: String,
+    pub channel: String,
+}
+
+impl SubscriptionKey {
+    pub fn new(exchange: &str, symbol: &Symbol, channel: &str) -> Self {
+        Self { exchange: exchange.to_lowercase(), symbol: symbol.as_pair(), channel: channel.to_lowercase() }
+    }
+
+    /// 规范化并校验频道和交易对格式
+    pub fn normalized(&self) -> Result<Self, MarketDataError> {
+        let channel = self.channel.to_lowercase();
+        if !SUBSCRIPTION_CHANNELS.contains(&channel.as_str()) {
+            return Err(MarketDataError::Configuration(format!(
+                "不支持的频道 {}，可选: {}",
+                self.channel,
+                SUBSCRIPTION_CHANNELS.join(", ")
+            )));
+        }
+        let symbol = Symbol::from_string(&self.symbol).map_err(MarketDataError::Configuration)?;
+        Ok(Self::new(&self.exchange, &symbol, &channel))
+    }
+}
+
+/// 相对配置基线的持久化增量
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SubscriptionOverrides {
+    added: BTreeSet<SubscriptionKey>,
+    removed: BTreeSet<SubscriptionKey>,
+}
+
+/// 订阅管理器
+pub struct SubscriptionManager {
+    path: Option<PathBuf>,
+    sources: RwLock<Vec<MarketSourceConfig>>,
+    overrides: RwLock<SubscriptionOverrides>,
+}
+
+impl SubscriptionManager {
+    /// 不持久化，用于测试
+    pub fn in_memory() -> Self {
+        Self { path: None, sources: RwLock::new(Vec::new()), overrides: RwLock::new(SubscriptionOverrides::default()) }
+    }
+
+    /// 从 `path` 加载上次运行的增量，之后的修改写回同一文件
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, MarketDataError> {
+        let path = path.into();
+        let mut manager = Self::in_memory();
+        if path.exists() {
+            let bytes = std::fs::read(&path)
+                .map_err(|e| MarketDataError::InternalError(format!("读取订阅文件失败 {}: {}", path.display(), e)))?;
+            let overrides: SubscriptionOverrides = serde_json::from_slice(&bytes)?;
+            info!(
+                "📡 已加载运行时订阅: +{} -{} ({})",
+                overrides.added.len(),
+                overrides.removed.len(),
+                path.display()
+            );
+            *manager.overrides.get_mut() = overrides;
+        }
+        manager.path = Some(path);
+        Ok(manager)
+    }
+
+    /// 订阅文件放在 L2 缓存目录下
+    pub fn default_path(l2_directory: &Path) -> PathBuf {
+        l2_directory.join("subscriptions.json")
+    }
+
+    /// 更新配置基线（启动和配置热重载时调用）
+    pub fn set_sources(&self, sources: Vec<MarketSourceConfig>) {
+        *self.sources.write() = sources;
+    }
+
+    fn baseline(&self) -> BTreeSet<SubscriptionKey> {
+        self.sources
+            .read()
+            .iter()
+            .filter(|s| s.enabled && !s.channel.is_empty())
+            .flat_map(|s| {
+                let mut channels = vec![s.channel.as_str()];
+                if s.trades && s.channel != "trades" {
+                    channels.push("trades");
+                }
+                let symbols = s.get_symbols().unwrap_or_default();
+                symbols.into_iter().flat_map(move |symbol| {
+                    channels.clone().into_iter().map(move |channel| SubscriptionKey::new(&s.exchange_id, &symbol, channel))
+                })
+            })
+            .collect()
+    }
+
+    /// 基线与增量合并后的生效订阅
+    pub fn subscriptions(&self) -> BTreeSet<SubscriptionKey> {
+        let overrides = self.overrides.read();
+        let mut active = self.baseline();
+        active.extend(overrides.added.iter().cloned());
+        active.retain(|k| !overrides.removed.contains(k));
+        active
+    }
+
+    /// 增加订阅，已生效时返回 false
+    pub fn subscribe(&self, key: &SubscriptionKey) -> Result<bool, MarketDataError> {
+        let key = key.normalized()?;
+        if self.subscriptions().contains(&key) {
+            return Ok(false);
+        }
+        let in_baseline = self.baseline().contains(&key);
+        {
+            let mut overrides = self.overrides.write();
+            overrides.removed.remove(&key);
+            if !in_baseline {
+                overrides.added.insert(key.clone());
+            }
+        }
+        self.save()?;
+        info!("➕ 订阅 {} {} {}", key.exchange, key.symbol, key.channel);
+        Ok(true)
+    }
+
+    /// 取消订阅，未生效时返回 false
+    pub fn unsubscribe(&self, key: &SubscriptionKey) -> Result<bool, MarketDataError> {
+        let key = key.normalized()?;
+        if !self.subscriptions().contains(&key) {
+            return Ok(false);
+        }
+        let in_baseline = self.baseline().contains(&key);
+        {
+            let mut overrides = self.overrides.write();
+            overrides.added.remove(&key);
+            if in_baseline {
+                overrides.removed.insert(key.clone());
+            }
+        }
+        self.save()?;
+        info!("➖ 取消订阅 {} {} {}", key.exchange, key.symbol, key.channel);
+        Ok(true)
+    }
+
+    /// 按 交易所+频道 生成采集配置；连接参数取同交易所的基线配置
+    pub fn effective_sources(&self) -> Vec<MarketSourceConfig> {
+        let sources = self.sources.read().clone();
+        let mut grouped: BTreeMap<(String, String), Vec<String>> = BTreeMap::new();
+        for key in self.subscriptions() {
+            grouped.entry((key.exchange, key.channel)).or_default().push(key.symbol);
+        }
+        grouped
+            .into_iter()
+            .filter_map(|((exchange, channel), symbols)| {
+                let template = sources
+                    .iter()
+                    .find(|s| s.exchange_id.eq_ignore_ascii_case(&exchange) && s.channel == channel)
+                    .or_else(|| sources.iter().find(|s| s.exchange_id.eq_ignore_ascii_case(&exchange)))?;
+                Some(MarketSourceConfig {
+                    id: format!("{}_{}", exchange, channel),
+                    enabled: true,
+                    symbols,
+                    channel,
+                    trades: false,
+                    ..template.clone()
+                })
+            })
+            .collect()
+    }
+
+    /// 写回加载时的文件（先写临时文件再改名）
+    fn save(&self) -> Result<(), MarketDataError> {
+        let Some(path) = &self.path else { return Ok(()) };
+        let bytes = serde_json::to_vec_pretty(&*self.overrides.read())?;
+        let io_error = |e: std::io::Error| MarketDataError::InternalError(format!("保存订阅文件失败: {}", e));
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, bytes).map_err(io_error)?;
+        std::fs::rename(&tmp, path).map_err(io_error)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(exchange: &str, channel: &str, symbols: &[&str]) -> MarketSourceConfig {
+        MarketSourceConfig {
+            id: format!("{}_{}", exchange, channel),
+            enabled: true,
+            exchange_id: exchange.to_string(),
+            adapter_type: exchange.to_string(),
+            websocket_url: format!("wss://{}.example/ws", exchange),
+            rest_api_url: None,
+            symbols: symbols.iter().map(|s| s.to_string()).collect(),
+            channel: channel.to_string(),
+            rate_limit: None,
+            connection_timeout_ms: None,
+            heartbeat_interval_ms: None,
+            reconnect_interval_sec: None,
+            max_reconnect_attempts: None,
+            heartbeat: None,
+            trades: false,
+            api_key: None,
+            api_secret: None,
+            api_passphrase: None,
+        }
+    }
+
+    fn key(exchange: &str, symbol: &str, channel: &str) -> SubscriptionKey {
+        SubscriptionKey { exchange: exchange.to_string(), symbol: symbol.to_string(), channel: channel.to_string() }
+    }
+
+    #[test]
+    fn test_runtime_subscriptions_persist_as_overrides() {
+        let dir = std::env::temp_dir().join(format!("qingxi_subs_{}_{}", std::process::id(), chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()));
+        let path = SubscriptionManager::default_path(&dir);
+        let sources = vec![source("binance", "orderbook", &["BTC/USDT", "ETH/USDT"])];
+
+        let manager = SubscriptionManager::load(&path).unwrap();
+        manager.set_sources(sources.clone());
+        assert!(manager.subscribe(&key("Binance", "SOL/USDT", "orderbook")).unwrap());
+        assert!(manager.subscribe(&key("binance", "BTC/USDT", "trades")).unwrap());
+        assert!(!manager.subscribe(&key("binance", "BTC/USDT", "orderbook")).unwrap(), "already in baseline");
+        assert!(manager.unsubscribe(&key("binance", "ETH/USDT", "orderbook")).unwrap());
+        assert!(manager.subscribe(&key("binance", "BTC/USDT", "candles")).is_err());
+
+        // 重启后增量仍然生效，基线新增的交易对照常订阅
+        let manager = SubscriptionManager::load(&path).unwrap();
+        manager.set_sources(vec![source("binance", "orderbook", &["BTC/USDT", "ETH/USDT", "XRP/USDT"])]);
+        let active: Vec<_> = manager.subscriptions().into_iter().map(|k| format!("{} {}", k.symbol, k.channel)).collect();
+        assert_eq!(active, vec!["BTC/USDT orderbook", "BTC/USDT trades", "SOL/USDT orderbook", "XRP/USDT orderbook"]);
+
+        let configs = manager.effective_sources();
+        assert_eq!(configs.len(), 2);
+        assert_eq!(configs[0].symbols, vec!["BTC/USDT", "SOL/USDT", "XRP/USDT"]);
+        assert_eq!((configs[1].channel.as_str(), configs[1].websocket_url.as_str()), ("trades", "wss://binance.example/ws"));
+
+        // 取消运行时新增的订阅只删除增量
+        assert!(manager.unsubscribe(&key("binance", "SOL/USDT", "orderbook")).unwrap());
+        assert!(manager.subscribe(&key("binance", "ETH/USDT", "orderbook")).unwrap());
+        assert_eq!(manager.overrides.read().added.len(), 1);
+        assert!(manager.overrides.read().removed.is_empty());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,169 @@
+#![allow(dead_code)]
+// src/collector/subscription_watchdog.rs
+//! # 订阅级心跳看门狗
+//!
+//! 连接级的读超时只能发现整条连接断开；交易所偶尔会在连接保持的情况下
+//! 停止推送某个频道。看门狗按 (交易对, 频道) 记录最后一条消息的时间，
+//! 超过 `interval_sec * max_missed_heartbeats` 没有消息的订阅单独重新订阅，
+//! 不影响同一连接上的其他频道。
+
+use crate::types::{HeartbeatConfig, SubscriptionDetail, Symbol};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+fn default_heartbeat_interval_sec() -> u64 {
+    30
+}
+
+/// 未配置心跳时使用的默认值
+pub fn fallback_heartbeat_config(interval: Duration) -> HeartbeatConfig {
+    HeartbeatConfig {
+        interval_sec: interval.as_secs().max(1),
+        message: None,
+        max_missed_heartbeats: crate::types::default_max_missed_heartbeats(),
+        protocol_ping: true,
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ChannelState {
+    subscription: SubscriptionDetail,
+    last_message: Instant,
+    resubscribes: u32,
+}
+
+/// 单条 WebSocket 连接上各订阅的静默检测
+pub struct SubscriptionWatchdog {
+    exchange: String,
+    stale_after: Duration,
+    channels: HashMap<(Symbol, String), ChannelState>,
+}
+
+impl SubscriptionWatchdog {
+    pub fn new(exchange: &str, heartbeat: &HeartbeatConfig, subscriptions: &[SubscriptionDetail], now: Instant) -> Self {
+        let interval = if heartbeat.interval_sec == 0 {
+            default_heartbeat_interval_sec()
+        } else {
+            heartbeat.interval_sec
+        };
+        let channels = subscriptions
+            .iter()
+            .map(|sub| {
+                (
+                    (sub.symbol.clone(), sub.channel.clone()),
+                    ChannelState { subscription: sub.clone(), last_message: now, resubscribes: 0 },
+                )
+            })
+            .collect();
+        Self {
+            exchange: exchange.to_string(),
+            stale_after: Duration::from_secs(interval * u64::from(heartbeat.max_missed_heartbeats.max(1))),
+            channels,
+        }
+    }
+
+    /// 运行时新增的订阅从 `now` 开始计时
+    pub fn add(&mut self, subscription: &SubscriptionDetail, now: Instant) {
+        self.channels
+            .entry((subscription.symbol.clone(), subscription.channel.clone()))
+            .or_insert_with(|| ChannelState { subscription: subscription.clone(), last_message: now, resubscribes: 0 });
+    }
+
+    pub fn remove(&mut self, subscription: &SubscriptionDetail) {
+        self.channels.remove(&(subscription.symbol.clone(), subscription.channel.clone()));
+    }
+
+    /// 订阅被判定为静默的时长
+    pub fn stale_after(&self) -> Duration {
+        self.stale_after
+    }
+
+    /// 记录某交易对收到的消息；适配器只回传交易对，因此该交易对的所有频道一并刷新
+    pub fn record(&mut self, symbol: &Symbol, now: Instant) {
+        for ((sym, _), state) in self.channels.iter_mut() {
+            if sym == symbol {
+                state.last_message = now;
+            }
+        }
+    }
+
+    /// 更新各频道的静默时长指标，返回需要重新订阅的频道
+    ///
+    /// 返回的频道计时重置，下一次重订阅至少再等待一个 `stale_after`。
+    pub fn check(&mut self, now: Instant) -> Vec<SubscriptionDetail> {
+        let mut stale = Vec::new();
+        for ((symbol, channel), state) in self.channels.iter_mut() {
+            let silent = now.saturating_duration_since(state.last_message);
+            metrics::gauge!(
+                "ws_subscription_staleness_seconds",
+                "exchange" => self.exchange.clone(),
+                "symbol" => symbol.as_pair(),
+                "channel" => channel.clone()
+            )
+            .set(silent.as_secs_f64());
+            if silent > self.stale_after {
+                warn!(
+                    "Subscription {} {} on {} silent for {:?}, resubscribing",
+                    symbol.as_pair(),
+                    channel,
+                    self.exchange,
+                    silent
+                );
+                metrics::counter!(
+                    "ws_subscription_resubscribes_total",
+                    "exchange" => self.exchange.clone(),
+                    "symbol" => symbol.as_pair(),
+                    "channel" => channel.clone()
+                )
+                .increment(1);
+                state.last_message = now;
+                state.resubscribes += 1;
+                stale.push(state.subscription.clone());
+            }
+        }
+        stale
+    }
+
+    /// 某订阅累计重订阅次数
+    pub fn resubscribes(&self, symbol: &Symbol, channel: &str) -> u32 {
+        self.channels
+            .get(&(symbol.clone(), channel.to_string()))
+            .map_or(0, |s| s.resubscribes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_silent_channel_resubscribed_alone() {
+        let heartbeat = HeartbeatConfig {
+            interval_sec: 5,
+            message: None,
+            max_missed_heartbeats: 3,
+            protocol_ping: true,
+        };
+        let btc = Symbol::new("BTC", "USDT");
+        let eth = Symbol::new("ETH", "USDT");
+        let subs = vec![
+            SubscriptionDetail::new(btc.clone(), "orderbook"),
+            SubscriptionDetail::new(eth.clone(), "orderbook"),
+        ];
+        let start = Instant::now();
+        let mut watchdog = SubscriptionWatchdog::new("binance", &heartbeat, &subs, start);
+        assert_eq!(watchdog.stale_after(), Duration::from_secs(15));
+
+        // BTC 持续有消息，ETH 静默
+        watchdog.record(&btc, start + Duration::from_secs(14));
+        assert!(watchdog.check(start + Duration::from_secs(10)).is_empty());
+        let stale = watchdog.check(start + Duration::from_secs(16));
+        assert_eq!(stale, vec![SubscriptionDetail::new(eth.clone(), "orderbook")]);
+        assert_eq!(watchdog.resubscribes(&eth, "orderbook"), 1);
+        assert_eq!(watchdog.resubscribes(&btc, "orderbook"), 0);
+
+        // 重订阅后计时重置
+        assert!(watchdog.check(start + Duration::from_secs(20)).is_empty());
+    }
+}
@@ -6,12 +6,24 @@ use super::super::{
     orderbook::local_orderbook::MarketDataMessage, types::MarketSourceConfig,
     settings::WebSocketNetworkSettings,
 };
+use super::subscription_watchdog::{fallback_heartbeat_config, SubscriptionWatchdog};
 use futures_util::{stream::StreamExt, SinkExt};
-use std::{sync::Arc, time::Duration};
-use tokio::sync::broadcast;
-use tokio_tungstenite::{connect_async, tungstenite::protocol::WebSocketConfig};
+use crate::types::SubscriptionDetail;
+use std::{sync::Arc, time::{Duration, Instant}};
+use tokio::sync::{broadcast, mpsc};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{protocol::WebSocketConfig, Message},
+};
 use tracing::{debug, info, instrument, warn};
 
+/// 运行中连接的订阅变更
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubscriptionCommand {
+    Subscribe(SubscriptionDetail),
+    Unsubscribe(SubscriptionDetail),
+}
+
 pub struct WebsocketCollector {
     config: MarketSourceConfig,
     adapter: Arc<dyn ExchangeAdapter>,
@@ -21,6 +33,9 @@ pub struct WebsocketCollector {
     health_monitor: Arc<ApiHealthMonitor>,
     network_settings: WebSocketNetworkSettings,
     reconnect_attempts: u32,
+    /// 当前订阅，重连时按此重新订阅
+    subscriptions: parking_lot::Mutex<Vec<SubscriptionDetail>>,
+    commands: tokio::sync::Mutex<Option<mpsc::UnboundedReceiver<SubscriptionCommand>>>,
 }
 
 impl WebsocketCollector {
@@ -31,6 +46,17 @@ impl WebsocketCollector {
         health_monitor: Arc<ApiHealthMonitor>,
         network_settings: WebSocketNetworkSettings,
     ) -> Self {
+        let subscriptions = config
+            .symbols
+            .iter()
+            .filter_map(|symbol_str| match crate::types::Symbol::from_string(symbol_str) {
+                Ok(symbol) => Some(SubscriptionDetail { symbol, channel: config.channel.clone() }),
+                Err(e) => {
+                    warn!("Failed to parse symbol '{}': {}", symbol_str, e);
+                    None
+                }
+            })
+            .collect();
         Self {
             config,
             adapter,
@@ -39,9 +65,22 @@ impl WebsocketCollector {
             health_monitor,
             network_settings,
             reconnect_attempts: 0,
+            subscriptions: parking_lot::Mutex::new(subscriptions),
+            commands: tokio::sync::Mutex::new(None),
         }
     }
 
+    /// 用给定订阅替换配置中的订阅，并接收运行时订阅变更
+    pub fn with_subscription_control(
+        mut self,
+        subscriptions: Vec<SubscriptionDetail>,
+        commands: mpsc::UnboundedReceiver<SubscriptionCommand>,
+    ) -> Self {
+        *self.subscriptions.get_mut() = subscriptions;
+        *self.commands.get_mut() = Some(commands);
+        self
+    }
+
     #[instrument(name="collector_run", skip(self, shutdown_rx), fields(exchange = %self.config.exchange_id.to_string()))]
     pub async fn run(mut self, mut shutdown_rx: broadcast::Receiver<()>) {
         loop {
@@ -143,23 +182,13 @@ impl WebsocketCollector {
 
         let (mut write, mut read) = ws_stream.split();
 
+        let mut commands = self.commands.lock().await;
+        let mut subscriptions = self.subscriptions.lock().clone();
+
         // 获取初始快照 (如果需要)
-        for symbol_str in &self.config.symbols {
-            // 将字符串转换为 Symbol
-            let symbol = match crate::types::Symbol::from_string(symbol_str) {
-                Ok(s) => s,
-                Err(e) => {
-                    warn!("Failed to parse symbol '{}': {}", symbol_str, e);
-                    continue;
-                }
-            };
-            
-            let sub = crate::types::SubscriptionDetail {
-                symbol,
-                channel: self.config.channel.clone(),
-            };
+        for sub in &subscriptions {
             if let Some(url) = &self.config.rest_api_url {
-                match self.adapter.get_initial_snapshot(&sub, url).await {
+                match self.adapter.get_initial_snapshot(sub, url).await {
                     Ok(snapshot) => {
                         // 直接使用从适配器返回的MarketDataMessage，无需转换
                         let local_msg = snapshot;
@@ -177,28 +206,6 @@ impl WebsocketCollector {
         }
 
         // 发送订阅消息
-        let subscriptions: Vec<_> = self
-            .config
-            .symbols
-            .iter()
-            .map(|symbol_str| {
-                // 将字符串转换为 Symbol
-                let symbol = match crate::types::Symbol::from_string(symbol_str) {
-                    Ok(s) => s,
-                    Err(e) => {
-                        warn!("Failed to parse symbol '{}': {}", symbol_str, e);
-                        return None;
-                    }
-                };
-                
-                Some(crate::types::SubscriptionDetail {
-                    symbol,
-                    channel: self.config.channel.clone(),
-                })
-            })
-            .filter_map(|x| x) // 过滤掉 None 值
-            .collect();
-
         let sub_msgs = self.adapter.build_subscription_messages(&subscriptions)?;
         for msg in sub_msgs {
             write
@@ -212,7 +219,14 @@ impl WebsocketCollector {
         info!("Subscription messages sent. Starting message loop.");
 
         // 使用配置的心跳间隔
-        let mut heartbeat_interval = tokio::time::interval(self.network_settings.get_heartbeat_interval());
+        let heartbeat = self
+            .config
+            .heartbeat
+            .clone()
+            .unwrap_or_else(|| fallback_heartbeat_config(self.network_settings.get_heartbeat_interval()));
+        let mut heartbeat_interval = tokio::time::interval(Duration::from_secs(heartbeat.interval_sec.max(1)));
+        // 按订阅跟踪最后消息时间，静默的频道单独重新订阅
+        let mut watchdog = SubscriptionWatchdog::new(&self.config.exchange_id, &heartbeat, &subscriptions, Instant::now());
 
         // 核心消息循环 - 添加读取和写入超时
         loop {
@@ -262,6 +276,11 @@ impl WebsocketCollector {
                             }
 
                             if let Some(market_message) = self.adapter.parse_message(&msg, &subscriptions)? {
+                                // 不支持取消订阅的交易所在本地丢弃已取消交易对的数据
+                                if !subscriptions.iter().any(|sub| &sub.symbol == market_message.symbol()) {
+                                    continue;
+                                }
+                                watchdog.record(market_message.symbol(), Instant::now());
                                 // 估算延迟（简化实现，实际应用中可能需要从消息中提取服务器时间戳）
                                 let estimated_latency_us = 1000; // 1ms作为估算值
                                 self.health_monitor.update_message_received(&source_id, estimated_latency_us);
@@ -287,8 +306,66 @@ impl WebsocketCollector {
                         }
                     }
                 },
+                Some(command) = next_command(&mut commands) => {
+                    match command {
+                        SubscriptionCommand::Subscribe(sub) if !subscriptions.contains(&sub) => {
+                            let msgs = match self.adapter.build_subscription_messages(std::slice::from_ref(&sub)) {
+                                Ok(msgs) => msgs,
+                                Err(e) => {
+                                    warn!("Cannot subscribe {} {}: {}", sub.symbol.as_pair(), sub.channel, e);
+                                    continue;
+                                }
+                            };
+                            for msg in msgs {
+                                write.send(msg).await.map_err(|e| MarketDataError::Communication {
+                                    exchange: self.config.exchange_id.clone(),
+                                    details: format!("Failed to subscribe: {}", e),
+                                })?;
+                            }
+                            info!("Subscribed {} {} on live connection", sub.symbol.as_pair(), sub.channel);
+                            watchdog.add(&sub, Instant::now());
+                            subscriptions.push(sub);
+                        }
+                        SubscriptionCommand::Unsubscribe(sub) if subscriptions.contains(&sub) => {
+                            match self.adapter.build_unsubscription_messages(std::slice::from_ref(&sub)) {
+                                Ok(msgs) => {
+                                    for msg in msgs {
+                                        write.send(msg).await.map_err(|e| MarketDataError::Communication {
+                                            exchange: self.config.exchange_id.clone(),
+                                            details: format!("Failed to unsubscribe: {}", e),
+                                        })?;
+                                    }
+                                }
+                                Err(MarketDataError::UnsupportedOperation(_)) => {
+                                    debug!("Unsubscribe unsupported, dropping {} data locally", sub.symbol.as_pair());
+                                }
+                                Err(e) => warn!("Cannot unsubscribe {} {}: {}", sub.symbol.as_pair(), sub.channel, e),
+                            }
+                            info!("Unsubscribed {} {} on live connection", sub.symbol.as_pair(), sub.channel);
+                            watchdog.remove(&sub);
+                            subscriptions.retain(|s| s != &sub);
+                        }
+                        _ => continue,
+                    }
+                    *self.subscriptions.lock() = subscriptions.clone();
+                },
                 _ = heartbeat_interval.tick() => {
-                    if let Some(hb_msg) = self.adapter.get_heartbeat_request() {
+                    let stale = watchdog.check(Instant::now());
+                    if !stale.is_empty() {
+                        for msg in self.adapter.build_subscription_messages(&stale)? {
+                            write.send(msg).await.map_err(|e| MarketDataError::Communication {
+                                exchange: self.config.exchange_id.clone(),
+                                details: format!("Failed to resubscribe: {}", e),
+                            })?;
+                        }
+                    }
+
+                    // 适配器无应用层心跳时使用协议层 Ping
+                    let hb_request = self
+                        .adapter
+                        .get_heartbeat_request()
+                        .or_else(|| heartbeat.protocol_ping.then(|| Message::Ping(Vec::new())));
+                    if let Some(hb_msg) = hb_request {
                         debug!("Sending heartbeat request.");
                         // 添加写入超时到心跳请求
                         let write_result = tokio::time::timeout(
@@ -325,3 +402,16 @@ impl WebsocketCollector {
         Ok(())
     }
 }
+
+/// 没有控制通道或通道关闭后永远挂起
+async fn next_command(
+    commands: &mut Option<mpsc::UnboundedReceiver<SubscriptionCommand>>,
+) -> Option<SubscriptionCommand> {
+    if let Some(rx) = commands.as_mut() {
+        if let Some(command) = rx.recv().await {
+            return Some(command);
+        }
+        *commands = None;
+    }
+    std::future::pending().await
+}
@@ -0,0 +1,462 @@
+//! # 内部总线背压与溢出策略
+//!
+//! 每个订阅者拥有独立的有界队列，队列满时按所属总线配置的策略处理：
+//! - `block`：发布方等待消费者腾出空间（可设超时，超时后丢弃新消息）；
+//! - `drop_oldest`：丢弃队首最旧的消息，适合只关心最新行情的通道；
+//! - `drop_newest`：丢弃新消息；
+//! - `spill_to_disk`：溢出部分按 JSON 行写入磁盘，消费者追上后按原顺序读回。
+//!
+//! 每个订阅者的投递、丢弃、落后（发布时队列已满）和落盘次数都会计入
+//! 指标，慢消费者可直接在面板上定位。策略在 `DataDistributionConfig.buses`
+//! 下按总线配置。
+
+use crate::errors::MarketDataError;
+use parking_lot::{Mutex, RwLock};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+use tokio::time::{Duration, Instant};
+use tracing::{error, warn};
+
+/// 队列满时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowPolicy {
+    Block,
+    #[default]
+    DropOldest,
+    DropNewest,
+    SpillToDisk,
+}
+
+/// 单条总线的策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BusPolicyConfig {
+    #[serde(default)]
+    pub policy: OverflowPolicy,
+    /// 每个订阅者的队列容量；缺省使用总线自身的默认值
+    #[serde(default)]
+    pub capacity: Option<usize>,
+    /// `block` 下发布方最长等待时间，缺省一直等待
+    #[serde(default)]
+    pub block_timeout_ms: Option<u64>,
+    #[serde(default = "default_spill_dir")]
+    pub spill_dir: String,
+}
+
+fn default_spill_dir() -> String {
+    "data/spill".to_string()
+}
+
+impl BusPolicyConfig {
+    pub fn new(policy: OverflowPolicy) -> Self {
+        Self { policy, capacity: None, block_timeout_ms: None, spill_dir: default_spill_dir() }
+    }
+}
+
+impl Default for BusPolicyConfig {
+    fn default() -> Self {
+        Self::new(OverflowPolicy::default())
+    }
+}
+
+/// 各内部总线的策略，挂在 `DataDistributionConfig.buses` 下
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BusPolicies {
+    /// 策略行情：只关心最新数据
+    pub strategy: BusPolicyConfig,
+    pub arbitrage: BusPolicyConfig,
+    /// 风控告警不可丢
+    pub risk: BusPolicyConfig,
+    /// 审计数据不可丢但不能拖慢主流程
+    pub audit: BusPolicyConfig,
+}
+
+impl Default for BusPolicies {
+    fn default() -> Self {
+        Self {
+            strategy: BusPolicyConfig::new(OverflowPolicy::DropOldest),
+            arbitrage: BusPolicyConfig::new(OverflowPolicy::DropOldest),
+            risk: BusPolicyConfig { block_timeout_ms: Some(100), ..BusPolicyConfig::new(OverflowPolicy::Block) },
+            audit: BusPolicyConfig::new(OverflowPolicy::SpillToDisk),
+        }
+    }
+}
+
+/// 订阅者统计
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubscriberStats {
+    pub bus: String,
+    pub subscriber: String,
+    pub policy: OverflowPolicy,
+    pub queue_depth: usize,
+    pub spilled_pending: u64,
+    pub delivered: u64,
+    pub dropped: u64,
+    /// 发布时队列已满的次数
+    pub lagged: u64,
+    pub spilled: u64,
+}
+
+/// 落盘溢出文件，JSON 行格式
+struct SpillFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    read_offset: u64,
+    pending: u64,
+}
+
+impl SpillFile {
+    fn create(dir: &Path, bus: &str, subscriber: &str) -> std::io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}-{}.jsonl", bus, subscriber));
+        let writer = BufWriter::new(File::create(&path)?);
+        Ok(Self { path, writer, read_offset: 0, pending: 0 })
+    }
+
+    fn append<T: Serialize>(&mut self, item: &T) -> std::io::Result<()> {
+        serde_json::to_writer(&mut self.writer, item)?;
+        self.writer.write_all(b"\n")?;
+        self.pending += 1;
+        Ok(())
+    }
+
+    fn read_batch<T: DeserializeOwned>(&mut self, max: usize) -> std::io::Result<Vec<T>> {
+        self.writer.flush()?;
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(self.read_offset))?;
+        let mut reader = BufReader::new(file);
+        let mut items = Vec::new();
+        let mut line = String::new();
+        while items.len() < max && self.pending > 0 {
+            line.clear();
+            let read = reader.read_line(&mut line)?;
+            if read == 0 {
+                break;
+            }
+            self.read_offset += read as u64;
+            self.pending -= 1;
+            items.push(serde_json::from_str(line.trim_end())?);
+        }
+        // 读空后截断，避免文件无限增长
+        if self.pending == 0 {
+            self.writer.get_mut().set_len(0)?;
+            self.writer.seek(SeekFrom::Start(0))?;
+            self.read_offset = 0;
+        }
+        Ok(items)
+    }
+}
+
+struct SubscriberQueue<T> {
+    bus: String,
+    subscriber: String,
+    capacity: usize,
+    items: Mutex<VecDeque<T>>,
+    spill: Mutex<Option<SpillFile>>,
+    readable: Notify,
+    writable: Notify,
+    detached: AtomicBool,
+    closed: AtomicBool,
+    delivered: AtomicU64,
+    dropped: AtomicU64,
+    lagged: AtomicU64,
+    spilled: AtomicU64,
+}
+
+impl<T: Serialize + DeserializeOwned> SubscriberQueue<T> {
+    fn spill_pending(&self) -> u64 {
+        self.spill.lock().as_ref().map(|s| s.pending).unwrap_or(0)
+    }
+
+    fn record_drop(&self, policy: OverflowPolicy) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+        metrics::counter!("bus_messages_dropped_total", "bus" => self.bus.clone(), "subscriber" => self.subscriber.clone(), "policy" => format!("{:?}", policy)).increment(1);
+    }
+
+    async fn push(&self, item: T, config: &BusPolicyConfig) -> Result<(), MarketDataError> {
+        let deadline = config.block_timeout_ms.map(|ms| Instant::now() + Duration::from_millis(ms));
+        let mut item = Some(item);
+        let mut lag_counted = false;
+        loop {
+            {
+                let mut items = self.items.lock();
+                let mut spill = self.spill.lock();
+                // 已有落盘数据时新消息继续落盘，保证顺序
+                let spilling = spill.as_ref().is_some_and(|s| s.pending > 0);
+                if !spilling && items.len() < self.capacity {
+                    items.push_back(item.take().expect("item pushed once"));
+                    metrics::gauge!("bus_subscriber_queue_depth", "bus" => self.bus.clone(), "subscriber" => self.subscriber.clone()).set(items.len() as f64);
+                    drop((items, spill));
+                    self.readable.notify_one();
+                    return Ok(());
+                }
+                if !lag_counted {
+                    lag_counted = true;
+                    self.lagged.fetch_add(1, Ordering::Relaxed);
+                    metrics::counter!("bus_subscriber_lagged_total", "bus" => self.bus.clone(), "subscriber" => self.subscriber.clone()).increment(1);
+                }
+                match config.policy {
+                    OverflowPolicy::DropOldest => {
+                        items.pop_front();
+                        items.push_back(item.take().expect("item pushed once"));
+                        drop((items, spill));
+                        self.record_drop(config.policy);
+                        self.readable.notify_one();
+                        return Ok(());
+                    }
+                    OverflowPolicy::DropNewest => {
+                        drop((items, spill));
+                        self.record_drop(config.policy);
+                        return Ok(());
+                    }
+                    OverflowPolicy::SpillToDisk => {
+                        if spill.is_none() {
+                            *spill = Some(SpillFile::create(Path::new(&config.spill_dir), &self.bus, &self.subscriber).map_err(|e| {
+                                MarketDataError::InternalError(format!("创建溢出文件失败 {}: {}", config.spill_dir, e))
+                            })?);
+                        }
+                        let file = spill.as_mut().expect("spill file created");
+                        file.append(item.as_ref().expect("item pushed once"))
+                            .map_err(|e| MarketDataError::InternalError(format!("写入溢出文件失败: {}", e)))?;
+                        drop((items, spill));
+                        self.spilled.fetch_add(1, Ordering::Relaxed);
+                        metrics::counter!("bus_messages_spilled_total", "bus" => self.bus.clone(), "subscriber" => self.subscriber.clone()).increment(1);
+                        self.readable.notify_one();
+                        return Ok(());
+                    }
+                    OverflowPolicy::Block => {}
+                }
+            }
+
+            let wait = self.writable.notified();
+            match deadline {
+                Some(deadline) => {
+                    if tokio::time::timeout_at(deadline, wait).await.is_err() {
+                        warn!("总线 {} 订阅者 {} 阻塞超时，丢弃新消息", self.bus, self.subscriber);
+                        self.record_drop(config.policy);
+                        return Ok(());
+                    }
+                }
+                None => wait.await,
+            }
+            if self.detached.load(Ordering::Relaxed) {
+                return Ok(());
+            }
+        }
+    }
+
+    fn pop(&self) -> Option<T> {
+        let mut items = self.items.lock();
+        if items.is_empty() {
+            let mut spill = self.spill.lock();
+            if let Some(file) = spill.as_mut().filter(|s| s.pending > 0) {
+                match file.read_batch(self.capacity) {
+                    Ok(batch) => items.extend(batch),
+                    Err(e) => {
+                        let lost = file.pending;
+                        error!("总线 {} 订阅者 {} 读取溢出文件失败，丢弃 {} 条: {}", self.bus, self.subscriber, lost, e);
+                        self.dropped.fetch_add(lost, Ordering::Relaxed);
+                        *spill = None;
+                    }
+                }
+            }
+        }
+        let item = items.pop_front()?;
+        metrics::gauge!("bus_subscriber_queue_depth", "bus" => self.bus.clone(), "subscriber" => self.subscriber.clone()).set(items.len() as f64);
+        drop(items);
+        self.delivered.fetch_add(1, Ordering::Relaxed);
+        self.writable.notify_one();
+        Some(item)
+    }
+
+    fn stats(&self, policy: OverflowPolicy) -> SubscriberStats {
+        SubscriberStats {
+            bus: self.bus.clone(),
+            subscriber: self.subscriber.clone(),
+            policy,
+            queue_depth: self.items.lock().len(),
+            spilled_pending: self.spill_pending(),
+            delivered: self.delivered.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            lagged: self.lagged.load(Ordering::Relaxed),
+            spilled: self.spilled.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 带溢出策略的扇出总线
+pub struct PolicyBus<T> {
+    name: String,
+    config: BusPolicyConfig,
+    capacity: usize,
+    subscribers: RwLock<Vec<Arc<SubscriberQueue<T>>>>,
+}
+
+impl<T: Clone + Serialize + DeserializeOwned + Send + 'static> PolicyBus<T> {
+    /// `default_capacity` 在配置未指定容量时使用
+    pub fn new(name: &str, config: BusPolicyConfig, default_capacity: usize) -> Self {
+        let capacity = config.capacity.unwrap_or(default_capacity).max(1);
+        Self { name: name.to_string(), config, capacity, subscribers: RwLock::new(Vec::new()) }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn policy(&self) -> OverflowPolicy {
+        self.config.policy
+    }
+
+    /// 新订阅者只收到订阅之后发布的消息
+    pub fn subscribe(&self, subscriber: &str) -> BusSubscriber<T> {
+        let queue = Arc::new(SubscriberQueue {
+            bus: self.name.clone(),
+            subscriber: subscriber.to_string(),
+            capacity: self.capacity,
+            items: Mutex::new(VecDeque::with_capacity(self.capacity.min(1024))),
+            spill: Mutex::new(None),
+            readable: Notify::new(),
+            writable: Notify::new(),
+            detached: AtomicBool::new(false),
+            closed: AtomicBool::new(false),
+            delivered: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            lagged: AtomicU64::new(0),
+            spilled: AtomicU64::new(0),
+        });
+        self.subscribers.write().push(queue.clone());
+        BusSubscriber { queue }
+    }
+
+    /// 投递给所有订阅者，返回订阅者数量
+    pub async fn publish(&self, item: T) -> Result<usize, MarketDataError> {
+        let subscribers: Vec<_> = {
+            let mut subscribers = self.subscribers.write();
+            subscribers.retain(|q| !q.detached.load(Ordering::Relaxed));
+            subscribers.clone()
+        };
+        for queue in &subscribers {
+            queue.push(item.clone(), &self.config).await?;
+        }
+        Ok(subscribers.len())
+    }
+
+    pub fn stats(&self) -> Vec<SubscriberStats> {
+        self.subscribers.read().iter().map(|q| q.stats(self.config.policy)).collect()
+    }
+
+    /// 所有订阅者的内存队列深度之和
+    pub fn queue_depth(&self) -> usize {
+        self.subscribers.read().iter().map(|q| q.items.lock().len()).sum()
+    }
+}
+
+impl<T> Drop for PolicyBus<T> {
+    fn drop(&mut self) {
+        for queue in self.subscribers.read().iter() {
+            queue.closed.store(true, Ordering::Relaxed);
+            queue.readable.notify_one();
+        }
+    }
+}
+
+/// 订阅端
+pub struct BusSubscriber<T> {
+    queue: Arc<SubscriberQueue<T>>,
+}
+
+impl<T: Serialize + DeserializeOwned> BusSubscriber<T> {
+    /// 总线关闭且队列读空后返回 None
+    pub async fn recv(&mut self) -> Option<T> {
+        loop {
+            if let Some(item) = self.queue.pop() {
+                return Some(item);
+            }
+            if self.queue.closed.load(Ordering::Relaxed) {
+                return None;
+            }
+            self.queue.readable.notified().await;
+        }
+    }
+
+    pub fn try_recv(&mut self) -> Option<T> {
+        self.queue.pop()
+    }
+}
+
+impl<T> Drop for BusSubscriber<T> {
+    fn drop(&mut self) {
+        self.queue.detached.store(true, Ordering::Relaxed);
+        // 唤醒因该订阅者阻塞的发布方
+        self.queue.writable.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn drain(subscriber: &mut BusSubscriber<u32>) -> Vec<u32> {
+        let mut items = Vec::new();
+        while let Some(item) = subscriber.try_recv() {
+            items.push(item);
+        }
+        items
+    }
+
+    #[tokio::test]
+    async fn test_overflow_policies() {
+        let bus = PolicyBus::new("strategy", BusPolicyConfig::new(OverflowPolicy::DropOldest), 2);
+        let mut slow = bus.subscribe("slow");
+        for i in 0..4 {
+            bus.publish(i).await.unwrap();
+        }
+        assert_eq!(drain(&mut slow).await, vec![2, 3]);
+        let stats = &bus.stats()[0];
+        assert_eq!((stats.dropped, stats.lagged, stats.delivered), (2, 2, 2));
+
+        let bus = PolicyBus::new("risk", BusPolicyConfig::new(OverflowPolicy::DropNewest), 2);
+        let mut slow = bus.subscribe("slow");
+        for i in 0..4 {
+            bus.publish(i).await.unwrap();
+        }
+        assert_eq!(drain(&mut slow).await, vec![0, 1]);
+
+        // 落盘后按原顺序读回
+        let dir = std::env::temp_dir().join(format!("bus_spill_{}_{}", std::process::id(), chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()));
+        let config = BusPolicyConfig { spill_dir: dir.to_string_lossy().into_owned(), ..BusPolicyConfig::new(OverflowPolicy::SpillToDisk) };
+        let bus = PolicyBus::new("audit", config, 2);
+        let mut slow = bus.subscribe("slow");
+        for i in 0..5 {
+            bus.publish(i).await.unwrap();
+        }
+        assert_eq!(slow.try_recv(), Some(0));
+        bus.publish(5).await.unwrap();
+        assert_eq!(drain(&mut slow).await, vec![1, 2, 3, 4, 5]);
+        assert_eq!(bus.stats()[0].spilled, 4);
+        assert_eq!(bus.stats()[0].dropped, 0);
+
+        // 阻塞直到消费者腾出空间
+        let bus = Arc::new(PolicyBus::new("risk", BusPolicyConfig::new(OverflowPolicy::Block), 1));
+        let mut slow = bus.subscribe("slow");
+        bus.publish(0).await.unwrap();
+        let publisher = tokio::spawn({
+            let bus = bus.clone();
+            async move { bus.publish(1).await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!publisher.is_finished());
+        assert_eq!(slow.recv().await, Some(0));
+        publisher.await.unwrap().unwrap();
+        assert_eq!(slow.recv().await, Some(1));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
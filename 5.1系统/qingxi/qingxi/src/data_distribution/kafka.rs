@@ -0,0 +1,519 @@
+//! # Kafka 数据出口
+//!
+//! 可选的 Kafka 生产者，将清洗后的行情、套利机会和成交回报写入可配置的主题，
+//! 供下游分析与存储系统消费。编码兼容 Confluent Schema Registry 线格式
+//! （魔数 0 + 4 字节大端 schema id + 消息体），每个主题可单独选择投递语义。
+//! 配置类型始终可用；实际生产者需启用 `kafka` feature。
+//!
+//! 行情与机会经有界队列交给单个发布任务按序写出，热路径只做一次
+//! `try_send`；队列满时丢弃并计数，不会为每条消息另起任务。
+
+use crate::errors::MarketDataError;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+use super::{CleanedMarketData, CrossExchangePriceSnapshot};
+
+/// 消息编码
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KafkaEncoding {
+    /// JSON；配置了 schema registry 时按 Confluent JSON Schema 线格式加前缀
+    #[default]
+    Json,
+    /// Avro 二进制，必须配置 schema registry
+    Avro,
+}
+
+/// 主题投递语义
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryGuarantee {
+    /// 不等待确认、不重试，适合高频行情
+    AtMostOnce,
+    /// acks=all 并重试，可能重复
+    #[default]
+    AtLeastOnce,
+    /// 幂等生产者，单分区内不重复不丢失
+    ExactlyOnce,
+}
+
+impl DeliveryGuarantee {
+    /// 对应的 librdkafka 生产者参数
+    pub fn producer_settings(&self) -> Vec<(&'static str, &'static str)> {
+        match self {
+            DeliveryGuarantee::AtMostOnce => vec![
+                ("acks", "0"),
+                ("enable.idempotence", "false"),
+                ("message.send.max.retries", "0"),
+            ],
+            DeliveryGuarantee::AtLeastOnce => vec![
+                ("acks", "all"),
+                ("enable.idempotence", "false"),
+                ("message.send.max.retries", "2147483647"),
+            ],
+            DeliveryGuarantee::ExactlyOnce => vec![
+                ("acks", "all"),
+                ("enable.idempotence", "true"),
+                ("max.in.flight.requests.per.connection", "5"),
+            ],
+        }
+    }
+}
+
+/// 单个主题配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KafkaTopicConfig {
+    pub topic: String,
+    #[serde(default)]
+    pub delivery: DeliveryGuarantee,
+}
+
+/// Kafka 出口配置，挂在 `DataDistributionConfig.kafka` 下
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KafkaSinkConfig {
+    /// bootstrap.servers
+    pub brokers: String,
+    #[serde(default = "default_client_id")]
+    pub client_id: String,
+    #[serde(default)]
+    pub schema_registry_url: Option<String>,
+    #[serde(default)]
+    pub encoding: KafkaEncoding,
+    #[serde(default = "default_message_timeout_ms")]
+    pub message_timeout_ms: u64,
+    /// 发布队列容量，满时丢弃行情/机会
+    #[serde(default = "default_queue_capacity")]
+    pub queue_capacity: usize,
+    /// 清洗后的行情；为空则不发布
+    #[serde(default)]
+    pub market_data: Option<KafkaTopicConfig>,
+    /// 套利机会
+    #[serde(default)]
+    pub opportunities: Option<KafkaTopicConfig>,
+    /// 成交回报
+    #[serde(default)]
+    pub executions: Option<KafkaTopicConfig>,
+}
+
+fn default_client_id() -> String {
+    "qingxi-distributor".to_string()
+}
+
+fn default_message_timeout_ms() -> u64 {
+    5000
+}
+
+fn default_queue_capacity() -> usize {
+    10_000
+}
+
+impl KafkaSinkConfig {
+    pub fn validate(&self) -> Result<(), MarketDataError> {
+        if self.brokers.trim().is_empty() {
+            return Err(MarketDataError::Configuration("kafka.brokers 不能为空".to_string()));
+        }
+        if self.encoding == KafkaEncoding::Avro && self.schema_registry_url.is_none() {
+            return Err(MarketDataError::Configuration(
+                "Avro 编码需要配置 kafka.schema_registry_url".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn topic(&self, stream: KafkaStream) -> Option<&KafkaTopicConfig> {
+        match stream {
+            KafkaStream::MarketData => self.market_data.as_ref(),
+            KafkaStream::Opportunities => self.opportunities.as_ref(),
+            KafkaStream::Executions => self.executions.as_ref(),
+        }
+    }
+}
+
+/// 发布的数据流
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KafkaStream {
+    MarketData,
+    Opportunities,
+    Executions,
+}
+
+impl KafkaStream {
+    /// Avro schema，注册到 `<topic>-value` 主题下
+    pub fn avro_schema(&self) -> &'static str {
+        match self {
+            KafkaStream::MarketData => MARKET_DATA_SCHEMA,
+            KafkaStream::Opportunities => OPPORTUNITY_SCHEMA,
+            KafkaStream::Executions => EXECUTION_SCHEMA,
+        }
+    }
+
+    /// JSON Schema，JSON 编码且配置了 registry 时注册
+    pub fn json_schema(&self) -> &'static str {
+        match self {
+            KafkaStream::MarketData => r#"{"title":"CleanedMarketData","type":"object"}"#,
+            KafkaStream::Opportunities => r#"{"title":"OpportunityEvent","type":"object"}"#,
+            KafkaStream::Executions => r#"{"title":"ExecutionEvent","type":"object"}"#,
+        }
+    }
+}
+
+const MARKET_DATA_SCHEMA: &str = r#"{
+  "type": "record", "name": "CleanedMarketData", "namespace": "qingxi",
+  "fields": [
+    {"name": "symbol", "type": "string"},
+    {"name": "exchange", "type": "string"},
+    {"name": "timestamp", "type": "long"},
+    {"name": "sequence", "type": "long"},
+    {"name": "price", "type": "double"},
+    {"name": "quantity", "type": "double"},
+    {"name": "side", "type": {"type": "enum", "name": "TradeSide", "symbols": ["Buy", "Sell"]}},
+    {"name": "quality_score", "type": "double"},
+    {"name": "processing_latency_ns", "type": "long"}
+  ]
+}"#;
+
+const OPPORTUNITY_SCHEMA: &str = r#"{
+  "type": "record", "name": "OpportunityEvent", "namespace": "qingxi",
+  "fields": [
+    {"name": "symbol", "type": "string"},
+    {"name": "timestamp_ns", "type": "long"},
+    {"name": "buy_exchange", "type": "string"},
+    {"name": "sell_exchange", "type": "string"},
+    {"name": "profit_bps", "type": "double"},
+    {"name": "max_volume", "type": "double"},
+    {"name": "confidence", "type": "double"},
+    {"name": "max_spread_bps", "type": "double"}
+  ]
+}"#;
+
+const EXECUTION_SCHEMA: &str = r#"{
+  "type": "record", "name": "ExecutionEvent", "namespace": "qingxi",
+  "fields": [
+    {"name": "execution_id", "type": "string"},
+    {"name": "opportunity_id", "type": ["null", "string"], "default": null},
+    {"name": "exchange", "type": "string"},
+    {"name": "symbol", "type": "string"},
+    {"name": "side", "type": "string"},
+    {"name": "price", "type": "double"},
+    {"name": "quantity", "type": "double"},
+    {"name": "fee", "type": "double"},
+    {"name": "status", "type": "string"},
+    {"name": "timestamp_ns", "type": "long"}
+  ]
+}"#;
+
+/// 套利机会事件（由带机会的价格快照展平）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpportunityEvent {
+    pub symbol: String,
+    pub timestamp_ns: u64,
+    pub buy_exchange: String,
+    pub sell_exchange: String,
+    pub profit_bps: f64,
+    pub max_volume: f64,
+    pub confidence: f64,
+    pub max_spread_bps: f64,
+}
+
+impl OpportunityEvent {
+    pub fn from_snapshot(snapshot: &CrossExchangePriceSnapshot) -> Option<Self> {
+        let opportunity = snapshot.arbitrage_opportunity.as_ref()?;
+        Some(Self {
+            symbol: snapshot.symbol.clone(),
+            timestamp_ns: snapshot.timestamp_ns,
+            buy_exchange: opportunity.buy_exchange.clone(),
+            sell_exchange: opportunity.sell_exchange.clone(),
+            profit_bps: opportunity.profit_bps,
+            max_volume: opportunity.max_volume,
+            confidence: opportunity.confidence,
+            max_spread_bps: snapshot.max_spread_bps,
+        })
+    }
+}
+
+/// 成交回报事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionEvent {
+    pub execution_id: String,
+    pub opportunity_id: Option<String>,
+    pub exchange: String,
+    pub symbol: String,
+    pub side: String,
+    pub price: f64,
+    pub quantity: f64,
+    pub fee: f64,
+    pub status: String,
+    pub timestamp_ns: u64,
+}
+
+/// 等待发布任务写出的事件
+#[derive(Debug, Clone)]
+pub enum KafkaEvent {
+    MarketData(CleanedMarketData),
+    Opportunity(CrossExchangePriceSnapshot),
+}
+
+/// 有界发布队列的发送端
+#[derive(Debug, Clone)]
+pub struct KafkaQueue {
+    sender: mpsc::Sender<KafkaEvent>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl KafkaQueue {
+    pub fn bounded(capacity: usize) -> (Self, mpsc::Receiver<KafkaEvent>) {
+        let (sender, receiver) = mpsc::channel(capacity.max(1));
+        (Self { sender, dropped: Arc::new(AtomicU64::new(0)) }, receiver)
+    }
+
+    /// 不阻塞地入队；队列满或发布任务已退出时丢弃并返回 false
+    pub fn enqueue(&self, event: KafkaEvent) -> bool {
+        match self.sender.try_send(event) {
+            Ok(()) => true,
+            Err(_) => {
+                let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                if dropped.is_power_of_two() {
+                    tracing::warn!("Kafka publish queue full, {} events dropped so far", dropped);
+                }
+                false
+            }
+        }
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// Confluent 线格式：魔数 0 + 大端 schema id + 消息体
+pub fn confluent_frame(schema_id: u32, body: &[u8]) -> Vec<u8> {
+    let mut framed = Vec::with_capacity(5 + body.len());
+    framed.push(0u8);
+    framed.extend_from_slice(&schema_id.to_be_bytes());
+    framed.extend_from_slice(body);
+    framed
+}
+
+#[cfg(feature = "kafka")]
+pub use producer::KafkaSink;
+
+#[cfg(feature = "kafka")]
+mod producer {
+    use super::*;
+    use rdkafka::config::ClientConfig;
+    use rdkafka::producer::{FutureProducer, FutureRecord, Producer};
+    use rdkafka::util::Timeout;
+    use std::collections::HashMap;
+    use std::time::Duration;
+    use tokio::sync::RwLock;
+    use tracing::{debug, info, warn};
+
+    fn kafka_error(e: impl std::fmt::Display) -> MarketDataError {
+        MarketDataError::DistributionError(format!("Kafka: {}", e))
+    }
+
+    /// Kafka 生产者，每种投递语义一个底层 producer
+    pub struct KafkaSink {
+        config: KafkaSinkConfig,
+        producers: HashMap<DeliveryGuarantee, FutureProducer>,
+        http: reqwest::Client,
+        /// 主题 -> 已注册的 schema id
+        schema_ids: RwLock<HashMap<String, u32>>,
+    }
+
+    impl KafkaSink {
+        pub fn new(config: KafkaSinkConfig) -> Result<Self, MarketDataError> {
+            config.validate()?;
+            let mut producers = HashMap::new();
+            for stream in [KafkaStream::MarketData, KafkaStream::Opportunities, KafkaStream::Executions] {
+                let Some(topic) = config.topic(stream) else { continue };
+                if producers.contains_key(&topic.delivery) {
+                    continue;
+                }
+                let mut client = ClientConfig::new();
+                client
+                    .set("bootstrap.servers", &config.brokers)
+                    .set("client.id", format!("{}-{:?}", config.client_id, topic.delivery))
+                    .set("message.timeout.ms", config.message_timeout_ms.to_string());
+                for (key, value) in topic.delivery.producer_settings() {
+                    client.set(key, value);
+                }
+                producers.insert(topic.delivery, client.create::<FutureProducer>().map_err(kafka_error)?);
+            }
+            info!("Kafka sink created: brokers={} producers={}", config.brokers, producers.len());
+            Ok(Self {
+                config,
+                producers,
+                http: reqwest::Client::new(),
+                schema_ids: RwLock::new(HashMap::new()),
+            })
+        }
+
+        pub async fn publish_market_data(&self, data: &CleanedMarketData) -> Result<(), MarketDataError> {
+            self.publish(KafkaStream::MarketData, &data.symbol, data).await
+        }
+
+        /// 只发布带套利机会的快照
+        pub async fn publish_opportunity(&self, snapshot: &CrossExchangePriceSnapshot) -> Result<(), MarketDataError> {
+            match OpportunityEvent::from_snapshot(snapshot) {
+                Some(event) => self.publish(KafkaStream::Opportunities, &event.symbol, &event).await,
+                None => Ok(()),
+            }
+        }
+
+        pub async fn publish_execution(&self, event: &ExecutionEvent) -> Result<(), MarketDataError> {
+            self.publish(KafkaStream::Executions, &event.symbol, event).await
+        }
+
+        pub fn config(&self) -> &KafkaSinkConfig {
+            &self.config
+        }
+
+        /// 发布任务：按入队顺序写出，直到所有发送端关闭
+        pub async fn run(self: Arc<Self>, mut events: mpsc::Receiver<KafkaEvent>) {
+            info!("Kafka publisher started");
+            while let Some(event) = events.recv().await {
+                let result = match &event {
+                    KafkaEvent::MarketData(data) => self.publish_market_data(data).await,
+                    KafkaEvent::Opportunity(snapshot) => self.publish_opportunity(snapshot).await,
+                };
+                if let Err(e) = result {
+                    warn!("Kafka publish failed: {}", e);
+                }
+            }
+            info!("Kafka publisher stopped");
+        }
+
+        /// 按主题配置编码并发送；以 symbol 为 key 保证同一品种分区内有序
+        async fn publish<T: Serialize>(&self, stream: KafkaStream, key: &str, payload: &T) -> Result<(), MarketDataError> {
+            let Some(topic) = self.config.topic(stream) else { return Ok(()) };
+            let Some(producer) = self.producers.get(&topic.delivery) else { return Ok(()) };
+            let body = self.encode(stream, &topic.topic, payload).await?;
+            let record = FutureRecord::to(&topic.topic).key(key).payload(&body);
+
+            if topic.delivery == DeliveryGuarantee::AtMostOnce {
+                // 不等待投递结果
+                if let Err((e, _)) = producer.send_result(record) {
+                    warn!("Kafka enqueue to {} failed: {}", topic.topic, e);
+                }
+                return Ok(());
+            }
+            let timeout = Timeout::After(Duration::from_millis(self.config.message_timeout_ms));
+            let (partition, offset) = producer.send(record, timeout).await.map_err(|(e, _)| kafka_error(e))?;
+            debug!("Kafka delivered to {}[{}]@{}", topic.topic, partition, offset);
+            Ok(())
+        }
+
+        async fn encode<T: Serialize>(&self, stream: KafkaStream, topic: &str, payload: &T) -> Result<Vec<u8>, MarketDataError> {
+            match self.config.encoding {
+                KafkaEncoding::Json => {
+                    let body = serde_json::to_vec(payload).map_err(kafka_error)?;
+                    if self.config.schema_registry_url.is_none() {
+                        return Ok(body);
+                    }
+                    let schema_id = self.schema_id(topic, stream.json_schema(), "JSON").await?;
+                    Ok(confluent_frame(schema_id, &body))
+                }
+                KafkaEncoding::Avro => {
+                    let schema = apache_avro::Schema::parse_str(stream.avro_schema()).map_err(kafka_error)?;
+                    let value = apache_avro::to_value(payload)
+                        .and_then(|v| v.resolve(&schema))
+                        .map_err(kafka_error)?;
+                    let body = apache_avro::to_avro_datum(&schema, value).map_err(kafka_error)?;
+                    let schema_id = self.schema_id(topic, stream.avro_schema(), "AVRO").await?;
+                    Ok(confluent_frame(schema_id, &body))
+                }
+            }
+        }
+
+        /// 注册（或查到已存在的）schema，结果按主题缓存
+        async fn schema_id(&self, topic: &str, schema: &str, schema_type: &str) -> Result<u32, MarketDataError> {
+            if let Some(id) = self.schema_ids.read().await.get(topic) {
+                return Ok(*id);
+            }
+            let registry = self.config.schema_registry_url.as_deref().unwrap_or_default();
+            let url = format!("{}/subjects/{}-value/versions", registry.trim_end_matches('/'), topic);
+            let response: serde_json::Value = self
+                .http
+                .post(&url)
+                .header("Content-Type", "application/vnd.schemaregistry.v1+json")
+                .json(&serde_json::json!({ "schema": schema, "schemaType": schema_type }))
+                .send()
+                .await
+                .and_then(|r| r.error_for_status())
+                .map_err(kafka_error)?
+                .json()
+                .await
+                .map_err(kafka_error)?;
+            let id = response["id"]
+                .as_u64()
+                .ok_or_else(|| kafka_error(format!("schema registry 响应缺少 id: {}", response)))? as u32;
+            info!("Registered {} schema for {}-value: id={}", schema_type, topic, id);
+            self.schema_ids.write().await.insert(topic.to_string(), id);
+            Ok(id)
+        }
+
+        /// 停机前冲刷未发送的消息
+        pub fn flush(&self, timeout: Duration) {
+            for producer in self.producers.values() {
+                if let Err(e) = producer.flush(Timeout::After(timeout)) {
+                    warn!("Kafka flush failed: {}", e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wire_format_and_delivery_settings() {
+        let framed = confluent_frame(42, b"{}");
+        assert_eq!(framed, vec![0, 0, 0, 0, 42, b'{', b'}']);
+
+        assert!(DeliveryGuarantee::AtMostOnce.producer_settings().contains(&("acks", "0")));
+        assert!(DeliveryGuarantee::ExactlyOnce.producer_settings().contains(&("enable.idempotence", "true")));
+
+        let config: KafkaSinkConfig = serde_json::from_str(
+            r#"{"brokers":"localhost:9092","encoding":"avro","market_data":{"topic":"md","delivery":"at_most_once"}}"#,
+        )
+        .unwrap();
+        assert_eq!(config.topic(KafkaStream::MarketData).unwrap().delivery, DeliveryGuarantee::AtMostOnce);
+        assert!(config.topic(KafkaStream::Executions).is_none());
+        // Avro 缺少 schema registry 时拒绝
+        assert!(config.validate().is_err());
+        assert_eq!(config.queue_capacity, 10_000);
+    }
+
+    #[test]
+    fn test_full_queue_drops_instead_of_blocking() {
+        let (queue, mut receiver) = KafkaQueue::bounded(2);
+        let data = CleanedMarketData {
+            symbol: "BTCUSDT".to_string(),
+            exchange: "binance".to_string(),
+            timestamp: 0,
+            sequence: 0,
+            price: 100.0,
+            quantity: 1.0,
+            side: crate::types::TradeSide::Buy,
+            quality_score: 1.0,
+            processing_latency_ns: 0,
+        };
+        assert!(queue.enqueue(KafkaEvent::MarketData(data.clone())));
+        assert!(queue.enqueue(KafkaEvent::MarketData(data.clone())));
+        assert!(!queue.enqueue(KafkaEvent::MarketData(data.clone())));
+        assert_eq!(queue.dropped(), 1);
+
+        assert!(matches!(receiver.try_recv(), Ok(KafkaEvent::MarketData(_))));
+        assert!(queue.enqueue(KafkaEvent::MarketData(data.clone())));
+        // 发布任务退出后同样丢弃
+        drop(receiver);
+        assert!(!queue.enqueue(KafkaEvent::MarketData(data)));
+        assert_eq!(queue.dropped(), 2);
+    }
+}
@@ -4,17 +4,21 @@
 //! 基于现有QingxiSystemState的零影响数据分发系统
 //! 支持实时策略数据传输、套利检测、风控告警和异步审计存储
 
+pub mod backpressure;
+pub mod kafka;
+
 use crate::types::*;
 use crate::errors::*;
 use crate::MarketDataMessage;
 use async_trait::async_trait;
 use serde::{Serialize, Deserialize};
 use std::sync::Arc;
-use tokio::sync::{mpsc, RwLock};
+use tokio::sync::RwLock;
 use tokio::time::{Duration, Instant};
 use tracing::{debug, error, info, warn, instrument};
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, AtomicBool, Ordering};
+use backpressure::{BusPolicies, BusSubscriber, PolicyBus, SubscriberStats};
 
 /// 清洗后的市场数据结构 - 完全兼容现有MarketDataMessage
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -308,19 +312,19 @@ impl LatencyMonitor {
 
 /// Qingxi数据分发器 - 真实实现
 pub struct QingxiDataDistributor {
-    // 实时消息队列（无界队列，优先级最高）
-    strategy_sender: mpsc::UnboundedSender<CleanedMarketData>,
-    strategy_receiver: Arc<RwLock<Option<mpsc::UnboundedReceiver<CleanedMarketData>>>>,
+    // 实时消息总线（有界，溢出策略见 DistributorConfig.buses）
+    strategy_bus: PolicyBus<CleanedMarketData>,
+    strategy_receiver: Arc<RwLock<Option<BusSubscriber<CleanedMarketData>>>>,
     
-    arbitrage_sender: mpsc::UnboundedSender<CrossExchangePriceSnapshot>,
-    arbitrage_receiver: Arc<RwLock<Option<mpsc::UnboundedReceiver<CrossExchangePriceSnapshot>>>>,
+    arbitrage_bus: PolicyBus<CrossExchangePriceSnapshot>,
+    arbitrage_receiver: Arc<RwLock<Option<BusSubscriber<CrossExchangePriceSnapshot>>>>,
     
-    risk_sender: mpsc::UnboundedSender<RiskAlert>,
-    risk_receiver: Arc<RwLock<Option<mpsc::UnboundedReceiver<RiskAlert>>>>,
+    risk_bus: PolicyBus<RiskAlert>,
+    risk_receiver: Arc<RwLock<Option<BusSubscriber<RiskAlert>>>>,
     
-    // 审计存储队列（后台处理，有界队列防止内存爆炸）
-    audit_sender: mpsc::Sender<AuditData>,
-    audit_receiver: Arc<RwLock<Option<mpsc::Receiver<AuditData>>>>,
+    // 审计存储总线（后台处理，默认溢出落盘）
+    audit_bus: PolicyBus<AuditData>,
+    audit_receiver: Arc<RwLock<Option<BusSubscriber<AuditData>>>>,
     
     // 性能监控
     latency_monitor: Arc<LatencyMonitor>,
@@ -329,6 +333,14 @@ pub struct QingxiDataDistributor {
     
     // 配置
     config: DistributorConfig,
+    
+    // 可选的 Kafka 出口：行情/机会经有界队列交给单个发布任务
+    #[cfg(feature = "kafka")]
+    kafka_sink: Option<Arc<kafka::KafkaSink>>,
+    #[cfg(feature = "kafka")]
+    kafka_queue: Option<kafka::KafkaQueue>,
+    #[cfg(feature = "kafka")]
+    kafka_receiver: Arc<RwLock<Option<tokio::sync::mpsc::Receiver<kafka::KafkaEvent>>>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -338,8 +350,14 @@ pub struct DistributorConfig {
     pub enable_quality_scoring: bool,
     pub enable_audit_storage: bool,
     pub latency_window_size: usize,
+    /// 各总线的背压/溢出策略
+    #[serde(default)]
+    pub buses: BusPolicies,
 }
 
+/// 实时总线未配置容量时的默认值
+const DEFAULT_BUS_CAPACITY: usize = 10_000;
+
 impl Default for DistributorConfig {
     fn default() -> Self {
         Self {
@@ -348,30 +366,67 @@ impl Default for DistributorConfig {
             enable_quality_scoring: true,
             enable_audit_storage: true,
             latency_window_size: 1000,
+            buses: BusPolicies::default(),
         }
     }
 }
 
 impl QingxiDataDistributor {
     pub fn new(config: DistributorConfig) -> Self {
-        let (strategy_sender, strategy_receiver) = mpsc::unbounded_channel();
-        let (arbitrage_sender, arbitrage_receiver) = mpsc::unbounded_channel();
-        let (risk_sender, risk_receiver) = mpsc::unbounded_channel();
-        let (audit_sender, audit_receiver) = mpsc::channel(config.audit_queue_capacity);
+        let buses = &config.buses;
+        let strategy_bus = PolicyBus::new("strategy", buses.strategy.clone(), DEFAULT_BUS_CAPACITY);
+        let arbitrage_bus = PolicyBus::new("arbitrage", buses.arbitrage.clone(), DEFAULT_BUS_CAPACITY);
+        let risk_bus = PolicyBus::new("risk", buses.risk.clone(), DEFAULT_BUS_CAPACITY);
+        let audit_bus = PolicyBus::new("audit", buses.audit.clone(), config.audit_queue_capacity);
+        let strategy_receiver = strategy_bus.subscribe("strategy_processor");
+        let arbitrage_receiver = arbitrage_bus.subscribe("arbitrage_processor");
+        let risk_receiver = risk_bus.subscribe("risk_processor");
+        let audit_receiver = audit_bus.subscribe("audit_processor");
         
         Self {
-            strategy_sender,
+            strategy_bus,
             strategy_receiver: Arc::new(RwLock::new(Some(strategy_receiver))),
-            arbitrage_sender,
+            arbitrage_bus,
             arbitrage_receiver: Arc::new(RwLock::new(Some(arbitrage_receiver))),
-            risk_sender,
+            risk_bus,
             risk_receiver: Arc::new(RwLock::new(Some(risk_receiver))),
-            audit_sender,
+            audit_bus,
             audit_receiver: Arc::new(RwLock::new(Some(audit_receiver))),
             latency_monitor: Arc::new(LatencyMonitor::new(config.latency_window_size)),
             start_time: Instant::now(),
             is_running: AtomicBool::new(false),
             config,
+            #[cfg(feature = "kafka")]
+            kafka_sink: None,
+            #[cfg(feature = "kafka")]
+            kafka_queue: None,
+            #[cfg(feature = "kafka")]
+            kafka_receiver: Arc::new(RwLock::new(None)),
+        }
+    }
+    
+    /// 同时将行情、套利机会和成交回报写入 Kafka
+    #[cfg(feature = "kafka")]
+    pub fn with_kafka_sink(mut self, sink: Arc<kafka::KafkaSink>) -> Self {
+        let (queue, receiver) = kafka::KafkaQueue::bounded(sink.config().queue_capacity);
+        self.kafka_sink = Some(sink);
+        self.kafka_queue = Some(queue);
+        self.kafka_receiver = Arc::new(RwLock::new(Some(receiver)));
+        self
+    }
+    
+    /// 因发布队列满而丢弃的 Kafka 事件数
+    #[cfg(feature = "kafka")]
+    pub fn kafka_dropped(&self) -> u64 {
+        self.kafka_queue.as_ref().map_or(0, |q| q.dropped())
+    }
+    
+    /// 发布成交回报（仅 Kafka 出口）
+    #[cfg(feature = "kafka")]
+    pub async fn publish_execution_result(&self, event: kafka::ExecutionEvent) -> Result<(), MarketDataError> {
+        match &self.kafka_sink {
+            Some(sink) => sink.publish_execution(&event).await,
+            None => Ok(()),
         }
     }
     
@@ -397,6 +452,14 @@ impl QingxiDataDistributor {
             self.start_audit_processor().await?;
         }
         
+        // 启动 Kafka 发布任务
+        #[cfg(feature = "kafka")]
+        if let Some(sink) = self.kafka_sink.clone() {
+            if let Some(receiver) = self.kafka_receiver.write().await.take() {
+                tokio::spawn(sink.run(receiver));
+            }
+        }
+        
         info!("QingxiDataDistributor background processors started");
         Ok(())
     }
@@ -678,19 +741,21 @@ impl QingxiDataDistributor {
     /// 获取队列大小统计
     pub async fn get_queue_sizes(&self) -> HashMap<String, usize> {
         let mut sizes = HashMap::new();
-        
-        // 注意：mpsc通道没有直接的len()方法，这里用其他方式估算
-        sizes.insert("strategy".to_string(), 0); // 实际实现中需要维护计数器
-        sizes.insert("arbitrage".to_string(), 0);
-        sizes.insert("risk".to_string(), 0);
-        
-        // audit队列可以通过Sender检查
-        let audit_capacity = self.audit_sender.capacity();
-        let audit_available = self.audit_sender.capacity() - self.audit_sender.capacity();
-        sizes.insert("audit".to_string(), audit_capacity - audit_available);
-        
+        sizes.insert("strategy".to_string(), self.strategy_bus.queue_depth());
+        sizes.insert("arbitrage".to_string(), self.arbitrage_bus.queue_depth());
+        sizes.insert("risk".to_string(), self.risk_bus.queue_depth());
+        sizes.insert("audit".to_string(), self.audit_bus.queue_depth());
         sizes
     }
+    
+    /// 各总线订阅者的投递/丢弃/落后统计
+    pub fn bus_stats(&self) -> Vec<SubscriberStats> {
+        let mut stats = self.strategy_bus.stats();
+        stats.extend(self.arbitrage_bus.stats());
+        stats.extend(self.risk_bus.stats());
+        stats.extend(self.audit_bus.stats());
+        stats
+    }
 }
 
 #[async_trait]
@@ -699,13 +764,17 @@ impl DataDistributor for QingxiDataDistributor {
     async fn send_to_strategy(&self, data: CleanedMarketData) -> Result<(), MarketDataError> {
         let start = Instant::now();
         
-        // 发送到策略队列（绝对不能阻塞）
-        self.strategy_sender
-            .send(data.clone())
-            .map_err(|e| MarketDataError::DistributionError(format!("Strategy send failed: {}", e)))?;
+        // 发送到策略总线（满时按溢出策略处理）
+        self.strategy_bus.publish(data.clone()).await?;
         
         let latency_ns = start.elapsed().as_nanos() as u64;
         
+        // Kafka 发布不占用策略通道的延迟预算
+        #[cfg(feature = "kafka")]
+        if let Some(queue) = &self.kafka_queue {
+            queue.enqueue(kafka::KafkaEvent::MarketData(data.clone()));
+        }
+        
         // 延迟告警
         if latency_ns > self.config.strategy_latency_target_ns {
             warn!("Strategy send latency exceeded target: {}ns > {}ns", 
@@ -717,9 +786,12 @@ impl DataDistributor for QingxiDataDistributor {
     }
     
     async fn send_to_arbitrage(&self, snapshot: CrossExchangePriceSnapshot) -> Result<(), MarketDataError> {
-        self.arbitrage_sender
-            .send(snapshot.clone())
-            .map_err(|e| MarketDataError::DistributionError(format!("Arbitrage send failed: {}", e)))?;
+        self.arbitrage_bus.publish(snapshot.clone()).await?;
+        
+        #[cfg(feature = "kafka")]
+        if let Some(queue) = &self.kafka_queue {
+            queue.enqueue(kafka::KafkaEvent::Opportunity(snapshot.clone()));
+        }
         
         debug!("Arbitrage snapshot sent: {} exchanges={}", 
                snapshot.symbol, snapshot.exchanges.len());
@@ -727,27 +799,17 @@ impl DataDistributor for QingxiDataDistributor {
     }
     
     async fn send_risk_alert(&self, alert: RiskAlert) -> Result<(), MarketDataError> {
-        self.risk_sender
-            .send(alert.clone())
-            .map_err(|e| MarketDataError::DistributionError(format!("Risk alert send failed: {}", e)))?;
+        self.risk_bus.publish(alert.clone()).await?;
         
         debug!("Risk alert sent: {} severity={:?}", alert.alert_id, alert.severity);
         Ok(())
     }
     
     async fn store_for_audit_async(&self, data: AuditData) {
-        // 完全异步，不会阻塞主流程
-        if let Err(e) = self.audit_sender.try_send(data.clone()) {
-            match e {
-                mpsc::error::TrySendError::Full(_) => {
-                    warn!("Audit queue full, dropping data: {}", data.id);
-                }
-                mpsc::error::TrySendError::Closed(_) => {
-                    error!("Audit queue closed, cannot store data: {}", data.id);
-                }
-            }
-        } else {
-            debug!("Audit data queued: {}", data.id);
+        // 溢出按审计总线策略处理（默认落盘），不会丢失也不会阻塞主流程
+        match self.audit_bus.publish(data.clone()).await {
+            Ok(_) => debug!("Audit data queued: {}", data.id),
+            Err(e) => error!("Audit queue failed, cannot store data {}: {}", data.id, e),
         }
     }
     
@@ -300,6 +300,7 @@ impl DynamicConfigManager {
                     rest_endpoint: Some(rest_url),
                     channel: Some("orderbook".to_string()),
                     heartbeat: None,
+                    trades: false,
                     reconnect_interval_sec: Some(params.global_params.reconnect_interval_sec),
                     max_reconnect_attempts: Some(params.global_params.max_reconnect_attempts),
                 };
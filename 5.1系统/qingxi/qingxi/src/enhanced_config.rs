@@ -4,6 +4,8 @@
 //! 基于TOML的配置加载和管理，支持热重载和配置验证
 
 use crate::data_distribution::DistributorConfig;
+use crate::data_distribution::backpressure::BusPolicies;
+use crate::data_distribution::kafka::KafkaSinkConfig;
 use crate::api_health_monitor_enhanced::HealthMonitorConfig;
 use crate::system_enhanced::{EnhancedConfig, StorageMode, LatencyConfig};
 use serde::{Serialize, Deserialize};
@@ -91,6 +93,12 @@ pub struct DataDistributionConfig {
     pub buffer_size: usize,
     pub batch_size: usize,
     pub flush_interval_ms: u64,
+    /// 可选的 Kafka 出口
+    #[serde(default)]
+    pub kafka: Option<KafkaSinkConfig>,
+    /// 各内部总线的背压/溢出策略
+    #[serde(default)]
+    pub buses: BusPolicies,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -180,6 +188,8 @@ impl Default for QingxiEnhancedConfiguration {
                 buffer_size: 50000,
                 batch_size: 100,
                 flush_interval_ms: 10,
+                kafka: None,
+                buses: BusPolicies::default(),
             },
             performance_optimization: PerformanceOptimizationConfig {
                 enable_adaptive_connections: true,
@@ -373,6 +383,7 @@ impl ConfigurationManager {
                 enable_quality_scoring: self.config.data_quality.enable_cross_exchange_validation,
                 enable_audit_storage: self.config.storage_strategy.audit_storage_enabled,
                 latency_window_size: self.config.latency_monitoring.latency_window_size,
+                buses: self.config.data_distribution.buses.clone(),
             },
             latency_config: LatencyConfig {
                 target_strategy_latency_ns: (self.config.latency_monitoring.target_strategy_latency_ms * 1_000_000.0) as u64,
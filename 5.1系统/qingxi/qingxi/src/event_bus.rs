@@ -1,12 +1,14 @@
 #![allow(dead_code)]
 //! # 事件总线系统
 //!
-//! 提供系统组件间的事件通信
+//! 提供系统组件间的事件通信。广播通道对落后的接收者会静默丢弃旧事件，
+//! 通过 `subscribe_named` 订阅可把落后和丢弃次数按订阅者计入指标。
 
 use crate::events::SystemEvent;
 use std::sync::Arc;
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
 use tokio::sync::{broadcast, RwLock};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 /// 事件总线，用于系统组件间的事件通信
 pub struct EventBus {
@@ -42,6 +44,11 @@ impl EventBus {
         self.sender.subscribe()
     }
     
+    /// 具名订阅，落后时记录丢弃的事件数
+    pub fn subscribe_named(&self, name: &str) -> EventSubscriber {
+        EventSubscriber { name: name.to_string(), receiver: self.sender.subscribe() }
+    }
+    
     /// 注册订阅者（用于统计）
     pub async fn register_subscriber(&self, name: String) {
         let mut subscribers = self.subscribers.write().await;
@@ -55,6 +62,41 @@ impl EventBus {
     }
 }
 
+/// 具名事件订阅者
+pub struct EventSubscriber {
+    name: String,
+    receiver: broadcast::Receiver<SystemEvent>,
+}
+
+impl EventSubscriber {
+    /// 接收下一个事件；落后被跳过的事件计入指标后继续接收，总线关闭返回 None
+    pub async fn recv(&mut self) -> Option<SystemEvent> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(event) => return Some(event),
+                Err(RecvError::Lagged(skipped)) => self.record_lag(skipped),
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+    
+    pub fn try_recv(&mut self) -> Option<SystemEvent> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(event) => return Some(event),
+                Err(TryRecvError::Lagged(skipped)) => self.record_lag(skipped),
+                Err(_) => return None,
+            }
+        }
+    }
+    
+    fn record_lag(&self, skipped: u64) {
+        warn!("⚠️ 事件订阅者 {} 落后，丢失 {} 个事件", self.name, skipped);
+        metrics::counter!("bus_subscriber_lagged_total", "bus" => "events", "subscriber" => self.name.clone()).increment(1);
+        metrics::counter!("bus_messages_dropped_total", "bus" => "events", "subscriber" => self.name.clone(), "policy" => "DropOldest").increment(skipped);
+    }
+}
+
 impl Clone for EventBus {
     fn clone(&self) -> Self {
         Self {
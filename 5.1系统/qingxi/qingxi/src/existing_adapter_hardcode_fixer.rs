@@ -337,6 +337,8 @@ impl DynamicAdapterConfig {
             heartbeat_interval_ms: Some(30000),
             reconnect_interval_sec: Some(5),
             max_reconnect_attempts: Some(5),
+            heartbeat: None,
+            trades: false,
         }
     }
 }
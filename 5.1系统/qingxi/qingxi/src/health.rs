@@ -27,6 +27,9 @@ pub struct HealthStatus {
     pub last_error: Option<String>,
     /// 最后错误时间
     pub last_error_at: Option<Nanos>,
+    /// 首次登记时间（用于计算消息速率）
+    #[serde(default)]
+    pub first_seen_at: Nanos,
 }
 
 impl HealthStatus {
@@ -39,6 +42,25 @@ impl HealthStatus {
             is_connected: false,
             last_error: None,
             last_error_at: None,
+            first_seen_at: Nanos::now(),
+        }
+    }
+
+    /// 数据源所属适配器（source_id 的交易所前缀，如 "binance-BTC/USDT" -> "binance"）
+    pub fn adapter_id(&self) -> &str {
+        self.source_id
+            .split_once('-')
+            .map(|(adapter, _)| adapter)
+            .unwrap_or(&self.source_id)
+    }
+
+    /// 自首次登记以来的平均消息速率（条/秒）
+    pub fn message_rate(&self, now: Nanos) -> f64 {
+        let elapsed_secs = (now - self.first_seen_at).as_nanos() as f64 / 1e9;
+        if elapsed_secs <= 0.0 {
+            0.0
+        } else {
+            self.message_count as f64 / elapsed_secs
         }
     }
 
@@ -184,6 +206,61 @@ impl ApiHealthMonitor {
         }
     }
 
+    /// 按适配器聚合数据源健康状态
+    pub fn get_adapter_health(&self) -> Vec<AdapterHealth> {
+        let now = Nanos::now();
+        let mut adapters: std::collections::BTreeMap<String, AdapterHealth> =
+            std::collections::BTreeMap::new();
+
+        for status in self.get_all_health_statuses() {
+            let adapter_id = status.adapter_id().to_string();
+            let entry = adapters
+                .entry(adapter_id.clone())
+                .or_insert_with(|| AdapterHealth::new(adapter_id));
+
+            let healthy = status.is_healthy(self.health_timeout_ms);
+            entry.total_sources += 1;
+            if healthy {
+                entry.healthy_sources += 1;
+            }
+            if status.is_connected {
+                entry.connected_sources += 1;
+            }
+            entry.total_messages += status.message_count;
+            entry.message_rate_per_sec += status.message_rate(now);
+            entry.average_latency_us += status.latency_us;
+            // 从未收到消息的数据源没有心跳，last_message_at 只是登记时间
+            if status.message_count > 0
+                && entry.last_heartbeat_at.is_none_or(|last| status.last_message_at > last)
+            {
+                entry.last_heartbeat_at = Some(status.last_message_at);
+            }
+            if let (Some(error), Some(error_at)) = (&status.last_error, status.last_error_at) {
+                if entry.last_error_at.is_none_or(|last| error_at > last) {
+                    entry.last_error = Some(error.clone());
+                    entry.last_error_at = Some(error_at);
+                }
+            }
+        }
+
+        adapters
+            .into_values()
+            .map(|mut adapter| {
+                if adapter.total_sources > 0 {
+                    adapter.average_latency_us /= adapter.total_sources as u64;
+                }
+                adapter.connection_state = if adapter.total_sources == 0 || adapter.connected_sources == 0 {
+                    AdapterConnectionState::Disconnected
+                } else if adapter.healthy_sources == adapter.total_sources {
+                    AdapterConnectionState::Connected
+                } else {
+                    AdapterConnectionState::Degraded
+                };
+                adapter
+            })
+            .collect()
+    }
+
     /// 生成健康报告摘要
     pub fn get_health_summary(&self) -> HealthSummary {
         let all_statuses = self.get_all_health_statuses();
@@ -216,6 +293,49 @@ impl ApiHealthMonitor {
     }
 }
 
+/// 适配器连接状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdapterConnectionState {
+    Connected,
+    Degraded,
+    Disconnected,
+}
+
+/// 单个适配器的聚合健康状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdapterHealth {
+    pub adapter_id: String,
+    pub connection_state: AdapterConnectionState,
+    pub total_sources: usize,
+    pub healthy_sources: usize,
+    pub connected_sources: usize,
+    pub total_messages: u64,
+    pub message_rate_per_sec: f64,
+    pub average_latency_us: u64,
+    pub last_heartbeat_at: Option<Nanos>,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<Nanos>,
+}
+
+impl AdapterHealth {
+    fn new(adapter_id: String) -> Self {
+        Self {
+            adapter_id,
+            connection_state: AdapterConnectionState::Disconnected,
+            total_sources: 0,
+            healthy_sources: 0,
+            connected_sources: 0,
+            total_messages: 0,
+            message_rate_per_sec: 0.0,
+            average_latency_us: 0,
+            last_heartbeat_at: None,
+            last_error: None,
+            last_error_at: None,
+        }
+    }
+}
+
 /// 健康状态摘要
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HealthSummary {
@@ -226,3 +346,63 @@ pub struct HealthSummary {
     pub total_messages: u64,
     pub timestamp: Nanos,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adapter<'a>(adapters: &'a [AdapterHealth], id: &str) -> &'a AdapterHealth {
+        adapters.iter().find(|a| a.adapter_id == id).unwrap()
+    }
+
+    #[test]
+    fn test_stale_adapter_is_degraded() {
+        let monitor = ApiHealthMonitor::new(1_000);
+        monitor.update_message_received("binance-BTC/USDT", 100);
+        monitor.update_message_received("binance-ETH/USDT", 100);
+        // 仍连接但超过超时时间没有消息
+        monitor
+            .health_statuses
+            .get_mut("binance-ETH/USDT")
+            .unwrap()
+            .last_message_at = Nanos::now() - Nanos::from_millis(5_000);
+
+        let adapters = monitor.get_adapter_health();
+        let binance = adapter(&adapters, "binance");
+        assert_eq!(binance.connected_sources, 2);
+        assert_eq!(binance.healthy_sources, 1);
+        assert_eq!(binance.connection_state, AdapterConnectionState::Degraded);
+    }
+
+    #[test]
+    fn test_never_seen_adapter_has_no_heartbeat() {
+        let monitor = ApiHealthMonitor::new(1_000);
+        monitor.update_connection_status("huobi-BTC/USDT", false);
+
+        let adapters = monitor.get_adapter_health();
+        let huobi = adapter(&adapters, "huobi");
+        assert_eq!(huobi.total_messages, 0);
+        assert_eq!(huobi.message_rate_per_sec, 0.0);
+        assert!(huobi.last_heartbeat_at.is_none());
+        assert_eq!(huobi.connection_state, AdapterConnectionState::Disconnected);
+    }
+
+    #[test]
+    fn test_message_rate_sums_sources_since_first_seen() {
+        let monitor = ApiHealthMonitor::new(60_000);
+        for source in ["okx-BTC/USDT", "okx-ETH/USDT"] {
+            for _ in 0..50 {
+                monitor.update_message_received(source, 10);
+            }
+            monitor.health_statuses.get_mut(source).unwrap().first_seen_at =
+                Nanos::now() - Nanos::from_millis(10_000);
+        }
+
+        let adapters = monitor.get_adapter_health();
+        let okx = adapter(&adapters, "okx");
+        assert_eq!(okx.total_messages, 100);
+        // 每个数据源 50 条 / 10 秒
+        assert!((okx.message_rate_per_sec - 10.0).abs() < 0.1, "rate = {}", okx.message_rate_per_sec);
+        assert_eq!(okx.connection_state, AdapterConnectionState::Connected);
+    }
+}
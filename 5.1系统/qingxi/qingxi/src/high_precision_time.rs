@@ -18,12 +18,13 @@ impl Nanos {
 
     /// 从毫秒创建纳秒时间戳
     pub fn from_millis(millis: i64) -> Self {
-        Self(millis * 1_000_000)
+        // 畸形消息里的时间戳可能溢出，饱和而不是 panic
+        Self(millis.saturating_mul(1_000_000))
     }
 
     /// 从微秒创建纳秒时间戳
     pub fn from_micros(micros: i64) -> Self {
-        Self(micros * 1_000)
+        Self(micros.saturating_mul(1_000))
     }
 
     /// 从u64毫秒时间戳创建
@@ -43,6 +43,7 @@ impl HttpApiServer {
         match (method, path) {
             (&Method::GET, "/api/v1/health") => self.handle_health_check().await,
             (&Method::GET, "/api/v1/health/summary") => self.handle_health_summary().await,
+            (&Method::GET, "/api/health/market-data") => self.handle_market_data_health().await,
             (&Method::GET, path) if path.starts_with("/api/v1/orderbook/") => {
                 self.handle_orderbook_request(path).await
             },
@@ -54,6 +55,10 @@ impl HttpApiServer {
             (&Method::POST, "/api/v1/v3/reset-stats") => self.handle_v3_reset_stats().await,
             (&Method::POST, "/api/v1/v3/enable-optimization") => self.handle_v3_enable_optimization(req).await,
             (&Method::POST, "/api/v1/reconfigure") => self.handle_reconfigure_request(req).await,
+            (&Method::GET, "/api/v1/subscriptions") => self.handle_subscriptions_list().await,
+            (&Method::GET, "/api/v1/trades/stats") => self.handle_trade_stats().await,
+            (&Method::POST, "/api/v1/subscriptions") => self.handle_subscription_change(req, true).await,
+            (&Method::DELETE, "/api/v1/subscriptions") => self.handle_subscription_change(req, false).await,
             (&Method::POST, "/api/v1/system/start") => self.handle_stats().await,
             (&Method::POST, "/api/v1/system/stop") => self.handle_stats().await,
             (&Method::POST, "/api/v1/system/restart") => self.handle_stats().await,
@@ -122,6 +127,17 @@ impl HttpApiServer {
             .expect("Operation failed"))
     }
 
+    /// 按适配器聚合的行情健康状态（统一网关入口）
+    async fn handle_market_data_health(&self) -> Result<Response<Body>, Infallible> {
+        let response = market_data_health_body(&self.health_monitor);
+
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header("content-type", "application/json")
+            .body(Body::from(response.to_string()))
+            .expect("Operation failed"))
+    }
+
     /// 获取订单簿数据
     async fn handle_orderbook_request(&self, path: &str) -> Result<Response<Body>, Infallible> {
         // 解析路径: /api/v1/orderbook/{exchange}/{symbol}
@@ -361,6 +377,7 @@ impl HttpApiServer {
             "endpoints": {
                 "health": "/api/v1/health",
                 "health_summary": "/api/v1/health/summary",
+                "market_data_health": "/api/health/market-data",
                 "orderbook": "/api/v1/orderbook/{exchange}/{symbol}",
                 "exchanges": "/api/v1/exchanges",
                 "symbols": "/api/v1/symbols",
@@ -369,7 +386,9 @@ impl HttpApiServer {
                 "v3_optimization_status": "/api/v1/v3/optimization-status",
                 "v3_reset_stats": "/api/v1/v3/reset-stats (POST)",
                 "v3_enable_optimization": "/api/v1/v3/enable-optimization (POST)",
-                "reconfigure": "/api/v1/reconfigure (POST)"
+                "reconfigure": "/api/v1/reconfigure (POST)",
+                "subscriptions": "/api/v1/subscriptions (GET/POST/DELETE)",
+                "trade_stats": "/api/v1/trades/stats"
             },
             "v3_features": {
                 "o1_sorting": "65536 bucket O(1) sorting engine",
@@ -482,6 +501,102 @@ impl HttpApiServer {
         }
     }
 
+    /// 滚动成交统计
+    async fn handle_trade_stats(&self) -> Result<Response<Body>, Infallible> {
+        match self.manager.get_trade_stats().await {
+            Ok(stats) => Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("content-type", "application/json")
+                .body(Body::from(json!({
+                    "trades": stats,
+                    "total": stats.len(),
+                    "timestamp": chrono::Utc::now().timestamp_millis()
+                }).to_string()))
+                .expect("Failed to build response")),
+            Err(e) => Ok(Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .header("content-type", "application/json")
+                .body(Body::from(json!({
+                    "status": "error",
+                    "message": "Could not retrieve trade stats",
+                    "error": e.to_string()
+                }).to_string()))
+                .expect("Failed to build response")),
+        }
+    }
+
+    /// 当前生效的订阅
+    async fn handle_subscriptions_list(&self) -> Result<Response<Body>, Infallible> {
+        match self.manager.list_subscriptions().await {
+            Ok(subscriptions) => Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("content-type", "application/json")
+                .body(Body::from(json!({
+                    "subscriptions": subscriptions,
+                    "total": subscriptions.len(),
+                    "timestamp": chrono::Utc::now().timestamp_millis()
+                }).to_string()))
+                .expect("Failed to build response")),
+            Err(e) => Ok(Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .header("content-type", "application/json")
+                .body(Body::from(json!({
+                    "status": "error",
+                    "message": "Could not retrieve subscriptions",
+                    "error": e.to_string()
+                }).to_string()))
+                .expect("Failed to build response")),
+        }
+    }
+
+    /// 增加或取消订阅，请求体 `{"exchange", "symbol", "channel"}`
+    async fn handle_subscription_change(&self, req: Request<Body>, subscribe: bool) -> Result<Response<Body>, Infallible> {
+        let body_bytes = match hyper::body::to_bytes(req.into_body()).await {
+            Ok(bytes) => bytes,
+            Err(_) => return Ok(self.bad_request("Failed to read request body")),
+        };
+        let key: crate::collector::subscription_manager::SubscriptionKey = match serde_json::from_slice(&body_bytes) {
+            Ok(key) => key,
+            Err(e) => return Ok(self.bad_request(&format!("Invalid subscription: {}", e))),
+        };
+
+        let result = if subscribe {
+            self.manager.subscribe(key.clone()).await
+        } else {
+            self.manager.unsubscribe(key.clone()).await
+        };
+        match result {
+            Ok(changed) => {
+                info!("📡 Subscription {} {} {} {} (changed: {})", if subscribe { "add" } else { "remove" }, key.exchange, key.symbol, key.channel, changed);
+                Ok(Response::builder()
+                    .status(StatusCode::OK)
+                    .header("content-type", "application/json")
+                    .body(Body::from(json!({
+                        "status": "success",
+                        "changed": changed,
+                        "subscription": key,
+                        "timestamp": chrono::Utc::now().timestamp_millis()
+                    }).to_string()))
+                    .expect("Failed to build response"))
+            }
+            Err(e @ (crate::errors::MarketDataError::Configuration(_) | crate::errors::MarketDataError::UnsupportedOperation(_))) => {
+                Ok(self.bad_request(&e.to_string()))
+            }
+            Err(e) => {
+                error!("Failed to update subscription: {}", e);
+                Ok(Response::builder()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .header("content-type", "application/json")
+                    .body(Body::from(json!({
+                        "status": "error",
+                        "message": "Failed to update subscription",
+                        "error": e.to_string()
+                    }).to_string()))
+                    .expect("Failed to build response"))
+            }
+        }
+    }
+
     /// 404 Not Found
     fn not_found(&self) -> Response<Body> {
         let error = json!({
@@ -641,6 +756,37 @@ impl HttpApiServer {
     }
 }
 
+/// `/api/health/market-data` 响应体：总体摘要加按适配器聚合的状态
+fn market_data_health_body(health_monitor: &ApiHealthMonitor) -> serde_json::Value {
+    let health_summary = health_monitor.get_health_summary();
+    let adapters = health_monitor.get_adapter_health();
+
+    json!({
+        "status": if health_summary.unhealthy_sources == 0 { "healthy" } else { "degraded" },
+        "summary": {
+            "total_sources": health_summary.total_sources,
+            "healthy_sources": health_summary.healthy_sources,
+            "unhealthy_sources": health_summary.unhealthy_sources,
+            "average_latency_us": health_summary.average_latency_us,
+            "total_messages": health_summary.total_messages,
+        },
+        "adapters": adapters.iter().map(|adapter| json!({
+            "adapter_id": adapter.adapter_id,
+            "connection_state": adapter.connection_state,
+            "total_sources": adapter.total_sources,
+            "healthy_sources": adapter.healthy_sources,
+            "connected_sources": adapter.connected_sources,
+            "total_messages": adapter.total_messages,
+            "message_rate_per_sec": adapter.message_rate_per_sec,
+            "average_latency_us": adapter.average_latency_us,
+            "last_heartbeat_at": adapter.last_heartbeat_at.map(|t| t.as_millis()),
+            "last_error": adapter.last_error,
+            "last_error_at": adapter.last_error_at.map(|t| t.as_millis()),
+        })).collect::<Vec<_>>(),
+        "timestamp": health_summary.timestamp.as_millis()
+    })
+}
+
 /// 启动HTTP API服务器
 pub async fn serve_http_api(
     addr: SocketAddr,
@@ -672,3 +818,31 @@ pub async fn serve_http_api(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_market_data_health_body_groups_sources_by_adapter() {
+        let monitor = ApiHealthMonitor::new(60_000);
+        monitor.update_message_received("binance-BTC/USDT", 100);
+        monitor.update_message_received("binance-ETH/USDT", 300);
+        monitor.update_message_received("okx-BTC/USDT", 50);
+        monitor.update_error_status("okx-BTC/USDT", "socket closed".to_string());
+
+        let body = market_data_health_body(&monitor);
+        assert_eq!(body["status"], "degraded");
+        assert_eq!(body["summary"]["total_sources"], 3);
+        assert_eq!(body["summary"]["unhealthy_sources"], 1);
+
+        let adapters = body["adapters"].as_array().unwrap();
+        assert_eq!(adapters.len(), 2);
+        assert_eq!(adapters[0]["adapter_id"], "binance");
+        assert_eq!(adapters[0]["connection_state"], "connected");
+        assert_eq!(adapters[0]["average_latency_us"], 200);
+        assert_eq!(adapters[1]["adapter_id"], "okx");
+        assert_eq!(adapters[1]["connection_state"], "disconnected");
+        assert_eq!(adapters[1]["last_error"], "socket closed");
+    }
+}
@@ -37,6 +37,7 @@ pub mod orderbook;
 pub mod pipeline;
 pub mod reasoner_client;
 pub mod settings;
+pub mod trade_validation;
 pub mod simd_utils;
 pub mod types;
 
@@ -97,7 +98,7 @@ pub use adapters::{AdapterRegistry, ExchangeAdapter};
 pub use http_api::{HttpApiServer, serve_http_api};
 
 // 健康监控重导出
-pub use health::{ApiHealthMonitor, HealthStatus, HealthSummary};
+pub use health::{AdapterConnectionState, AdapterHealth, ApiHealthMonitor, HealthStatus, HealthSummary};
 
 // 第三方类型重导出
 pub use ordered_float::OrderedFloat;
@@ -3,6 +3,7 @@ use crate::{
     anomaly::AnomalyDetector,
     orderbook::local_orderbook::{LocalOrderBook, MarketDataMessage},
     settings::Settings,
+    trade_validation::TradeTracker,
     types::*,
 };
 use dashmap::DashMap;
@@ -45,6 +46,8 @@ pub struct DataPipeline {
     #[allow(dead_code)]
     anomaly_detector: AnomalyDetector,
     snapshot_pool: Option<Arc<crate::object_pool::ObjectPool<MarketDataSnapshot>>>,
+    /// 成交统计，并用成交校验盘口
+    trade_tracker: Arc<TradeTracker>,
 }
 
 impl DataPipeline {
@@ -53,9 +56,14 @@ impl DataPipeline {
             local_books: Arc::new(DashMap::new()),
             anomaly_detector: AnomalyDetector::new(settings),
             snapshot_pool: None,
+            trade_tracker: Arc::new(TradeTracker::default()),
         }
     }
 
+    pub fn trade_tracker(&self) -> Arc<TradeTracker> {
+        self.trade_tracker.clone()
+    }
+
     pub fn with_snapshot_pool(
         mut self,
         pool: Arc<crate::object_pool::ObjectPool<MarketDataSnapshot>>,
@@ -72,6 +80,7 @@ impl DataPipeline {
 
                 // 检测订单簿异常
                 let anomaly = self.anomaly_detector.detect_orderbook_anomalies(&orderbook);
+                self.trade_tracker.record_orderbook(&orderbook);
 
                 let local_book = Arc::new(Mutex::new(LocalOrderBook::new(orderbook)));
                 self.local_books.insert(key, local_book);
@@ -87,6 +96,7 @@ impl DataPipeline {
 
                 // 检测订单簿异常
                 let anomaly = self.anomaly_detector.detect_orderbook_anomalies(&snapshot);
+                self.trade_tracker.record_orderbook(&snapshot);
 
                 let local_book = Arc::new(Mutex::new(LocalOrderBook::new(snapshot)));
                 self.local_books.insert(key, local_book);
@@ -107,6 +117,7 @@ impl DataPipeline {
                         let anomaly = self
                             .anomaly_detector
                             .detect_orderbook_anomalies(&book_snapshot);
+                        self.trade_tracker.record_orderbook(&book_snapshot);
 
                         // 成功应用更新，使用对象池创建快照
                         let market_snapshot = if let Some(pool) = &self.snapshot_pool {
@@ -142,9 +153,12 @@ impl DataPipeline {
                     ProcessResult::new()
                 }
             }
-            MarketDataMessage::Trade(_trade) => {
-                // 可以处理交易数据
-                ProcessResult::new()
+            MarketDataMessage::Trade(trade) => {
+                // 更新成交统计，成交价落在盘口之外时报告异常
+                match self.trade_tracker.record_trade(&trade) {
+                    Some(anomaly) => ProcessResult::new().with_anomaly(anomaly),
+                    None => ProcessResult::new(),
+                }
             }
             MarketDataMessage::Snapshot(snapshot) => {
                 // 处理快照消息
@@ -153,6 +167,7 @@ impl DataPipeline {
 
                     // 检测订单簿异常
                     let anomaly = self.anomaly_detector.detect_orderbook_anomalies(&orderbook);
+                    self.trade_tracker.record_orderbook(&orderbook);
 
                     let local_book = Arc::new(Mutex::new(LocalOrderBook::new(orderbook)));
                     self.local_books.insert(key, local_book);
@@ -0,0 +1,265 @@
+#![allow(dead_code)]
+//! # 成交流统计与基于成交的订单簿校验
+//!
+//! 按 交易所+交易对 维护最近成交和滚动窗口内的成交笔数、成交量、VWAP。
+//! 每笔成交都与同一交易所最近的盘口比对：成交价明显落在最优买价之下或
+//! 最优卖价之上，说明本地订单簿已经过期或错乱（未收到的更新、交叉盘口），
+//! 生成异常结果交给异常管道处理。
+
+use crate::high_precision_time::Nanos;
+use crate::types::{
+    AnomalyDetectionResult, AnomalySeverity, AnomalyType, OrderBook, Symbol, TradeSide, TradeUpdate,
+};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::Duration;
+use tracing::warn;
+
+/// 成交校验配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeValidationConfig {
+    /// 成交率统计窗口
+    pub window_secs: u64,
+    /// 成交价超出盘口的容忍度（基点）
+    pub outside_book_tolerance_bps: f64,
+    /// 盘口早于成交超过该时长时不做校验（毫秒）
+    pub max_book_age_ms: u64,
+}
+
+impl Default for TradeValidationConfig {
+    fn default() -> Self {
+        Self { window_secs: 60, outside_book_tolerance_bps: 10.0, max_book_age_ms: 2_000 }
+    }
+}
+
+/// 单个交易所+交易对的成交统计
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradeStats {
+    pub exchange: String,
+    pub symbol: String,
+    pub last_price: f64,
+    pub last_quantity: f64,
+    pub last_side: TradeSide,
+    pub last_trade_ns: i64,
+    pub trades_in_window: usize,
+    pub trades_per_sec: f64,
+    pub volume_in_window: f64,
+    pub vwap: f64,
+    /// 累计落在盘口之外的成交笔数
+    pub outside_book: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TopOfBook {
+    best_bid: Option<f64>,
+    best_ask: Option<f64>,
+    timestamp_ns: i64,
+}
+
+#[derive(Debug, Default)]
+struct TradeWindow {
+    /// (时间, 价格, 数量)
+    trades: VecDeque<(i64, f64, f64)>,
+    last: Option<TradeUpdate>,
+    outside_book: u64,
+}
+
+/// 成交跟踪器
+pub struct TradeTracker {
+    config: TradeValidationConfig,
+    books: DashMap<(String, Symbol), TopOfBook>,
+    windows: DashMap<(String, Symbol), TradeWindow>,
+}
+
+impl TradeTracker {
+    pub fn new(config: TradeValidationConfig) -> Self {
+        Self { config, books: DashMap::new(), windows: DashMap::new() }
+    }
+
+    /// 记录最新盘口供成交校验
+    pub fn record_orderbook(&self, orderbook: &OrderBook) {
+        self.books.insert(
+            (orderbook.source.clone(), orderbook.symbol.clone()),
+            TopOfBook {
+                best_bid: orderbook.bids.first().map(|e| e.price.into_inner()),
+                best_ask: orderbook.asks.first().map(|e| e.price.into_inner()),
+                timestamp_ns: orderbook.timestamp.as_nanos(),
+            },
+        );
+    }
+
+    /// 记录成交；成交价落在盘口之外时返回异常
+    pub fn record_trade(&self, trade: &TradeUpdate) -> Option<AnomalyDetectionResult> {
+        let key = (trade.source.clone(), trade.symbol.clone());
+        let now_ns = trade.timestamp.as_nanos();
+        let price = trade.price.into_inner();
+        let anomaly = self.check_against_book(&key, trade);
+
+        let mut window = self.windows.entry(key).or_default();
+        window.trades.push_back((now_ns, price, trade.quantity.into_inner()));
+        let cutoff = now_ns - Duration::from_secs(self.config.window_secs).as_nanos() as i64;
+        while window.trades.front().is_some_and(|(ts, _, _)| *ts < cutoff) {
+            window.trades.pop_front();
+        }
+        if window.last.as_ref().map_or(true, |last| last.timestamp <= trade.timestamp) {
+            window.last = Some(trade.clone());
+        }
+        if anomaly.is_some() {
+            window.outside_book += 1;
+        }
+        let rate = window.trades.len() as f64 / self.config.window_secs.max(1) as f64;
+        metrics::gauge!("trade_rate_per_sec", "exchange" => trade.source.clone(), "symbol" => trade.symbol.as_pair())
+            .set(rate);
+        anomaly
+    }
+
+    fn check_against_book(&self, key: &(String, Symbol), trade: &TradeUpdate) -> Option<AnomalyDetectionResult> {
+        let book = *self.books.get(key)?;
+        let trade_ns = trade.timestamp.as_nanos();
+        let max_age_ns = Duration::from_millis(self.config.max_book_age_ms).as_nanos() as i64;
+        if trade_ns - book.timestamp_ns > max_age_ns {
+            return None;
+        }
+        let price = trade.price.into_inner();
+        let tolerance = self.config.outside_book_tolerance_bps / 10_000.0;
+        let (side, reference, deviation_bps) = match (book.best_bid, book.best_ask) {
+            (Some(bid), _) if bid > 0.0 && price < bid * (1.0 - tolerance) => ("below best bid", bid, (bid - price) / bid * 10_000.0),
+            (_, Some(ask)) if ask > 0.0 && price > ask * (1.0 + tolerance) => ("above best ask", ask, (price - ask) / ask * 10_000.0),
+            _ => return None,
+        };
+
+        metrics::counter!("trade_outside_book_total", "exchange" => trade.source.clone(), "symbol" => trade.symbol.as_pair())
+            .increment(1);
+        warn!(
+            "🚨 {} {} 成交价 {:.4} {} {:.4} ({:.1} bps)，订单簿可能已过期",
+            trade.source,
+            trade.symbol.as_pair(),
+            price,
+            side,
+            reference,
+            deviation_bps
+        );
+        Some(AnomalyDetectionResult {
+            anomaly_type: AnomalyType::Other("TradeOutsideBook".to_string()),
+            severity: if deviation_bps > self.config.outside_book_tolerance_bps * 5.0 {
+                AnomalySeverity::Critical
+            } else {
+                AnomalySeverity::Warning
+            },
+            description: format!("Trade printed {} by {:.1} bps", side, deviation_bps),
+            details: format!(
+                "Trade: {:.4}, Best bid: {:?}, Best ask: {:?}, Book age: {} ms",
+                price,
+                book.best_bid,
+                book.best_ask,
+                (trade_ns - book.timestamp_ns).max(0) / 1_000_000
+            ),
+            timestamp: trade.timestamp,
+            symbol: trade.symbol.clone(),
+            source: trade.source.clone(),
+            recovery_suggestion: Some("Resync the order book from a fresh snapshot".to_string()),
+        })
+    }
+
+    /// 统计某交易所+交易对，`now` 用于剔除窗口外的成交
+    pub fn stats(&self, exchange: &str, symbol: &Symbol, now: Nanos) -> Option<TradeStats> {
+        let window = self.windows.get(&(exchange.to_string(), symbol.clone()))?;
+        Some(Self::summarize(exchange, symbol, &window, now.as_nanos(), self.config.window_secs))
+    }
+
+    pub fn all_stats(&self, now: Nanos) -> Vec<TradeStats> {
+        self.windows
+            .iter()
+            .map(|entry| Self::summarize(&entry.key().0, &entry.key().1, entry.value(), now.as_nanos(), self.config.window_secs))
+            .collect()
+    }
+
+    /// 某交易对在各交易所的最近成交
+    pub fn last_trades(&self, symbol: &Symbol) -> Vec<TradeUpdate> {
+        self.windows
+            .iter()
+            .filter(|entry| &entry.key().1 == symbol)
+            .filter_map(|entry| entry.value().last.clone())
+            .collect()
+    }
+
+    fn summarize(exchange: &str, symbol: &Symbol, window: &TradeWindow, now_ns: i64, window_secs: u64) -> TradeStats {
+        let cutoff = now_ns - Duration::from_secs(window_secs).as_nanos() as i64;
+        let recent: Vec<_> = window.trades.iter().filter(|(ts, _, _)| *ts >= cutoff).collect();
+        let volume: f64 = recent.iter().map(|(_, _, q)| q).sum();
+        let notional: f64 = recent.iter().map(|(_, p, q)| p * q).sum();
+        let last = window.last.as_ref();
+        TradeStats {
+            exchange: exchange.to_string(),
+            symbol: symbol.as_pair(),
+            last_price: last.map_or(0.0, |t| t.price.into_inner()),
+            last_quantity: last.map_or(0.0, |t| t.quantity.into_inner()),
+            last_side: last.map_or(TradeSide::Buy, |t| t.side.clone()),
+            last_trade_ns: last.map_or(0, |t| t.timestamp.as_nanos()),
+            trades_in_window: recent.len(),
+            trades_per_sec: recent.len() as f64 / window_secs.max(1) as f64,
+            volume_in_window: volume,
+            vwap: if volume > 0.0 { notional / volume } else { 0.0 },
+            outside_book: window.outside_book,
+        }
+    }
+}
+
+impl Default for TradeTracker {
+    fn default() -> Self {
+        Self::new(TradeValidationConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::OrderBookEntry;
+    use ordered_float::OrderedFloat;
+
+    fn trade(price: f64, quantity: f64, ts: i64) -> TradeUpdate {
+        TradeUpdate {
+            symbol: Symbol::new("BTC", "USDT"),
+            price: OrderedFloat(price),
+            quantity: OrderedFloat(quantity),
+            side: TradeSide::Sell,
+            timestamp: Nanos::from(ts),
+            source: "binance".to_string(),
+            trade_id: None,
+        }
+    }
+
+    #[test]
+    fn test_trade_stats_and_outside_book_detection() {
+        let tracker = TradeTracker::default();
+        let second = 1_000_000_000i64;
+        let start = 1_000 * second;
+
+        // 没有盘口时只做统计
+        assert!(tracker.record_trade(&trade(100.0, 1.0, start)).is_none());
+
+        let mut book = OrderBook::new(Symbol::new("BTC", "USDT"), "binance".to_string());
+        book.bids = vec![OrderBookEntry::new(99.9, 1.0)];
+        book.asks = vec![OrderBookEntry::new(100.1, 1.0)];
+        book.timestamp = Nanos::from(start);
+        tracker.record_orderbook(&book);
+
+        // 容忍度内的成交不报警
+        assert!(tracker.record_trade(&trade(100.15, 1.0, start + second)).is_none());
+        let anomaly = tracker.record_trade(&trade(99.5, 2.0, start + second)).unwrap();
+        assert_eq!(anomaly.anomaly_type, AnomalyType::Other("TradeOutsideBook".to_string()));
+        assert_eq!(anomaly.severity, AnomalySeverity::Warning);
+        assert_eq!(tracker.record_trade(&trade(90.0, 1.0, start + second)).unwrap().severity, AnomalySeverity::Critical);
+        // 盘口过旧时不校验
+        assert!(tracker.record_trade(&trade(90.0, 1.0, start + 10 * second)).is_none());
+
+        let stats = tracker.stats("binance", &Symbol::new("BTC", "USDT"), Nanos::from(start + 10 * second)).unwrap();
+        assert_eq!((stats.trades_in_window, stats.outside_book, stats.last_price), (5, 2, 90.0));
+        assert!((stats.vwap - (100.0 + 100.15 + 99.5 * 2.0 + 90.0 + 90.0) / 6.0).abs() < 1e-9);
+        // 窗口外的成交不计入成交率
+        let later = tracker.stats("binance", &Symbol::new("BTC", "USDT"), Nanos::from(start + 65 * second)).unwrap();
+        assert_eq!(later.trades_in_window, 1);
+        assert_eq!(tracker.last_trades(&Symbol::new("BTC", "USDT")).len(), 1);
+    }
+}
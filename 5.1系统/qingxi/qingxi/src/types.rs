@@ -198,6 +198,16 @@ impl SubscriptionDetail {
 pub struct HeartbeatConfig {
     pub interval_sec: u64,
     pub message: Option<String>,
+    /// 订阅连续静默多少个心跳间隔后单独重新订阅
+    #[serde(default = "default_max_missed_heartbeats")]
+    pub max_missed_heartbeats: u32,
+    /// 适配器没有应用层心跳时发送 WebSocket 协议层 Ping
+    #[serde(default = "default_true")]
+    pub protocol_ping: bool,
+}
+
+pub fn default_max_missed_heartbeats() -> u32 {
+    3
 }
 
 fn default_true() -> bool {
@@ -231,6 +241,12 @@ pub struct MarketSourceConfig {
     pub reconnect_interval_sec: Option<u64>,
     #[serde(default)]
     pub max_reconnect_attempts: Option<u32>,
+    /// 订阅级心跳看门狗配置，缺省按网络设置的心跳间隔
+    #[serde(default)]
+    pub heartbeat: Option<HeartbeatConfig>,
+    /// 同时订阅这些交易对的成交流，用于成交统计和盘口校验
+    #[serde(default)]
+    pub trades: bool,
 
     // --- 为未来扩展保留 API 密钥字段 (保持默认) ---
     #[serde(default)]
@@ -269,6 +269,7 @@ async fn test_event_bus() {
             is_connected: true,
             last_error: None,
             last_error_at: None,
+            first_seen_at: high_precision_time::Nanos::now(),
         },
         message: "测试事件".to_string(),
     };